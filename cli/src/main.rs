@@ -0,0 +1,1840 @@
+use clap::{Parser, Subcommand};
+use esp32dev_core::arduino;
+use esp32dev_core::benchmark::{self, BenchmarkOptions};
+use esp32dev_core::ble_uart;
+use esp32dev_core::boot_timing::BootTimer;
+use esp32dev_core::circuitpython::{self, SyncWatch};
+use esp32dev_core::console::Console;
+use esp32dev_core::coredump;
+use esp32dev_core::defmt::{self, DefmtStream};
+use esp32dev_core::device_db::{self, DeviceDb};
+use esp32dev_core::devices::{self, DEFAULT_VID_ALLOWLIST};
+use esp32dev_core::elf2image;
+use esp32dev_core::esp_interaction::{self, FlasherSessions};
+use esp32dev_core::espota::{self, OtaOptions, OtaTarget};
+use esp32dev_core::esptool;
+use esp32dev_core::flash_encryption;
+use esp32dev_core::flash_watch::FlashWatch;
+use esp32dev_core::full_image;
+use esp32dev_core::gdb;
+use esp32dev_core::idf;
+use esp32dev_core::image_info;
+use esp32dev_core::improv;
+use esp32dev_core::inventory::{self, Inventory};
+use esp32dev_core::junit;
+use esp32dev_core::lifecycle::DeviceTracker;
+use esp32dev_core::micropython;
+use esp32dev_core::models::{
+    AppError, FlashPlan, FlashSegment, InventoryBatch, NetLogProtocol, RecordQuery, StepStatus,
+    TaskInfo, TestSequence, TestStep,
+};
+use esp32dev_core::monitor::{self, Monitor};
+use esp32dev_core::mpy_firmware;
+use esp32dev_core::openocd::{self, OpenOcd};
+use esp32dev_core::platformio;
+use esp32dev_core::scripting::{self, ScriptOptions};
+use esp32dev_core::sequence;
+use esp32dev_core::signature::{self, SigningKey};
+use esp32dev_core::soak::{self, SoakOptions};
+use esp32dev_core::svd;
+use esp32dev_core::tasks::TaskManager;
+use esp32dev_core::webrepl::WebRepl;
+use esp32dev_core::wifi_prov::{self, ProvOptions};
+use std::io::{BufRead, Read, Write};
+use std::process::ExitCode;
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Flash, erase, inspect and monitor ESP boards without the GUI.
+#[derive(Parser)]
+#[command(name = "esp32dev-cli", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Write a firmware image to flash
+    Flash {
+        /// Path to the .bin image
+        file: String,
+        /// Serial port, or rfc2217://host:port for a board on a remote
+        /// bench; auto-detected when omitted
+        #[arg(long)]
+        port: Option<String>,
+        /// Flash offset, hex (0x10000) or decimal
+        #[arg(long, default_value = "0x0")]
+        address: String,
+        /// Baud rate used after the initial sync
+        #[arg(long, default_value_t = 460800)]
+        baud: u32,
+        /// Write the app to the OTA slot after the running one and boot it
+        /// from there, instead of at --address
+        #[arg(long, conflicts_with = "address")]
+        ota_slot: bool,
+    },
+    /// Upload a firmware image over Wi-Fi to a board running ArduinoOTA
+    Ota {
+        /// Path to the .bin image
+        file: String,
+        /// IP address or hostname of the board
+        #[arg(long)]
+        host: String,
+        #[arg(long, default_value_t = espota::DEFAULT_PORT)]
+        port: u16,
+        /// Password set with `ArduinoOTA.setPassword`
+        #[arg(long)]
+        password: Option<String>,
+        /// Replace the filesystem image (SPIFFS/LittleFS) instead of the app
+        #[arg(long)]
+        filesystem: bool,
+    },
+    /// Send Wi-Fi credentials to Improv firmware (ESPHome, WLED) over serial
+    Improv {
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long)]
+        ssid: String,
+        #[arg(long, default_value = "")]
+        password: String,
+    },
+    /// List named Bluetooth LE devices nearby
+    BleScan {
+        /// Seconds to listen for advertisements
+        #[arg(long, default_value_t = 5)]
+        seconds: u64,
+        /// Only list devices offering the Nordic UART service
+        #[arg(long)]
+        uart: bool,
+    },
+    /// Print the output of a logger speaking the Nordic UART service over
+    /// Bluetooth LE; lines typed are sent to it
+    BleMonitor {
+        /// Address or name from `ble-scan --uart`
+        device: String,
+    },
+    /// Provision Wi-Fi over Bluetooth LE (ESP-IDF wifi_provisioning)
+    BleProvision {
+        /// Address or name from `ble-scan`, e.g. PROV_1A2B3C
+        #[arg(long)]
+        device: String,
+        #[arg(long)]
+        ssid: String,
+        #[arg(long, default_value = "")]
+        password: String,
+        /// Proof of possession configured in the firmware
+        #[arg(long, default_value = "")]
+        pop: String,
+        /// Security scheme 0, 1 or 2; asked from the device when omitted
+        #[arg(long)]
+        security: Option<u8>,
+        /// Security 2 username
+        #[arg(long, default_value = wifi_prov::DEFAULT_USERNAME)]
+        username: String,
+    },
+    /// Erase the whole flash chip
+    Erase {
+        #[arg(long)]
+        port: Option<String>,
+    },
+    /// Time writing and reading back a scratch region of flash at several
+    /// baud rates, to find the fastest one the bridge and cable handle
+    Benchmark {
+        #[arg(long)]
+        port: Option<String>,
+        /// Baud rates to try, comma separated
+        #[arg(long, value_delimiter = ',', default_values_t = benchmark::DEFAULT_BAUD_RATES)]
+        baud: Vec<u32>,
+        /// Start of the scratch region; the end of the flash by default
+        #[arg(long)]
+        offset: Option<String>,
+        /// Bytes to write and read at each rate, a multiple of 4096
+        #[arg(long, default_value_t = benchmark::DEFAULT_LEN)]
+        size: u32,
+        /// Print the runs as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Decode a bootloader or app image: header, segments, checksum and
+    /// appended SHA-256, like esptool.py image_info
+    ImageInfo {
+        /// Path to the .bin image
+        file: String,
+        /// Print the decoded image as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Convert an ELF into a bootable .bin image for a chip, like esptool.py
+    /// elf2image
+    Elf2image {
+        /// Path to the ELF
+        elf: String,
+        /// Target chip, e.g. esp32s3
+        #[arg(long)]
+        chip: String,
+        /// Where to write the image; next to the ELF with a .bin extension
+        /// by default
+        #[arg(long, short)]
+        output: Option<String>,
+        /// qio, qout, dio or dout
+        #[arg(long, default_value = elf2image::DEFAULT_FLASH_MODE)]
+        flash_mode: String,
+        /// 1MB to 128MB
+        #[arg(long, default_value = elf2image::DEFAULT_FLASH_SIZE)]
+        flash_size: String,
+        /// e.g. 80m, 40m or 20m, depending on the chip
+        #[arg(long, default_value = elf2image::DEFAULT_FLASH_FREQ)]
+        flash_freq: String,
+    },
+    /// Assemble a full flash image from an ELF, a bootloader and a
+    /// partition table CSV, to program at offset 0x0
+    FullImage {
+        /// Path to the app ELF
+        elf: String,
+        /// Target chip, e.g. esp32s3
+        #[arg(long)]
+        chip: String,
+        /// Second stage bootloader, e.g. build/bootloader/bootloader.bin
+        #[arg(long)]
+        bootloader: String,
+        /// Partition table CSV; ESP-IDF's single app table by default
+        #[arg(long)]
+        partitions: Option<String>,
+        /// Where to write the image
+        #[arg(long, short)]
+        output: String,
+        /// qio, qout, dio or dout
+        #[arg(long, default_value = elf2image::DEFAULT_FLASH_MODE)]
+        flash_mode: String,
+        /// 1MB to 128MB
+        #[arg(long, default_value = elf2image::DEFAULT_FLASH_SIZE)]
+        flash_size: String,
+        /// e.g. 80m, 40m or 20m, depending on the chip
+        #[arg(long, default_value = elf2image::DEFAULT_FLASH_FREQ)]
+        flash_freq: String,
+    },
+    /// Append the MAC address and serial number of each board plugged in
+    /// to a CSV, until Ctrl+C
+    Inventory {
+        /// CSV to write; an earlier inventory is carried on
+        #[arg(long, short)]
+        output: String,
+        /// Batch or lot number written on every row
+        #[arg(long, default_value = "")]
+        batch: String,
+        #[arg(long, default_value = "")]
+        operator: String,
+        #[arg(long, default_value = "")]
+        note: String,
+    },
+    /// Print records from the app's device database (devices.db in its
+    /// config directory) of boards flashed and provisioned, newest first
+    Records {
+        /// The devices.db file
+        db: String,
+        /// Part of a MAC address or USB serial number
+        #[arg(long)]
+        device: Option<String>,
+        /// Part of an operator name
+        #[arg(long)]
+        operator: Option<String>,
+        /// Only this production run; "" for records outside of a run
+        #[arg(long)]
+        run: Option<String>,
+        /// e.g. flash, kiosk, improv or ble_provision
+        #[arg(long)]
+        action: Option<String>,
+        /// Only records that passed
+        #[arg(long, conflicts_with = "failed")]
+        passed: bool,
+        /// Only records that failed
+        #[arg(long)]
+        failed: bool,
+        /// Only the last N days
+        #[arg(long)]
+        days: Option<u64>,
+        #[arg(long)]
+        limit: Option<u32>,
+        /// Print CSV instead of JSON
+        #[arg(long)]
+        csv: bool,
+    },
+    /// List the production runs in the app's device database, or print the
+    /// summary report of one: units, failures with reasons and durations
+    Runs {
+        /// The devices.db file
+        db: String,
+        /// The run to report on; "" for records outside of a run
+        run: Option<String>,
+        /// Print the run's records as CSV instead of the report
+        #[arg(long, requires = "run")]
+        csv: bool,
+    },
+    /// Print chip details as JSON
+    Info {
+        #[arg(long)]
+        port: Option<String>,
+    },
+    /// Print a report of the board (chip, eFuses, partition table, flash
+    /// ID) as JSON, or as printable HTML
+    Report {
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long)]
+        html: bool,
+    },
+    /// Stream serial output to stdout and send stdin lines to the device
+    Monitor {
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long, default_value_t = 115200)]
+        baud: u32,
+        /// Also record the raw traffic with its timing to this file
+        #[arg(long)]
+        capture: Option<String>,
+        /// Decode defmt logs with the table in this app ELF
+        #[arg(long)]
+        defmt: Option<String>,
+    },
+    /// Print logs a board forwards over the network (syslog-style UDP by default)
+    Listen {
+        #[arg(long, default_value_t = monitor::NET_LOG_PORT)]
+        port: u16,
+        /// Accept TCP connections instead of UDP datagrams
+        #[arg(long)]
+        tcp: bool,
+    },
+    /// Manage files on a MicroPython board through its raw REPL
+    Fs {
+        #[arg(long)]
+        port: Option<String>,
+        #[command(subcommand)]
+        op: FsOp,
+    },
+    /// Download an official MicroPython build for the board and flash it
+    Micropython {
+        #[arg(long)]
+        port: Option<String>,
+        /// Chip model (ESP32, ESP32-S3, ...), probed from the board when omitted
+        #[arg(long)]
+        chip: Option<String>,
+        /// Release to flash, e.g. v1.23.0; the newest release by default
+        #[arg(long)]
+        version: Option<String>,
+        /// Expected SHA-256 of the image
+        #[arg(long)]
+        sha256: Option<String>,
+        /// Only list the published builds
+        #[arg(long)]
+        list: bool,
+        #[arg(long, default_value_t = 460800)]
+        baud: u32,
+    },
+    /// Flash an ESP-IDF build like `idf.py flash`, without the IDF environment
+    Idf {
+        /// Project or build directory
+        #[arg(default_value = ".")]
+        project: String,
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long, default_value_t = 460800)]
+        baud: u32,
+    },
+    /// Flash the build of a PlatformIO project like `pio run -t upload`
+    Pio {
+        /// Project directory, the one with platformio.ini
+        #[arg(default_value = ".")]
+        project: String,
+        /// Environment to flash; the first of default_envs when omitted
+        #[arg(long)]
+        env: Option<String>,
+        #[arg(long)]
+        port: Option<String>,
+        /// Defaults to the environment's upload_speed, else 460800
+        #[arg(long)]
+        baud: Option<u32>,
+    },
+    /// Flash a build again each time it is rebuilt and monitor the board in
+    /// between, like `idf.py flash monitor` with the build run elsewhere
+    Watch {
+        /// A .bin image, or an ESP-IDF project or build directory
+        #[arg(default_value = ".")]
+        path: String,
+        #[arg(long)]
+        port: Option<String>,
+        /// Flash offset of a .bin image, hex (0x10000) or decimal
+        #[arg(long, default_value = "0x10000")]
+        address: String,
+        #[arg(long, default_value_t = 460800)]
+        baud: u32,
+        #[arg(long, default_value_t = 115200)]
+        monitor_baud: u32,
+    },
+    /// Build an Arduino sketch with arduino-cli and flash it
+    Arduino {
+        /// Sketch folder
+        #[arg(default_value = ".")]
+        sketch: String,
+        /// Board to build for; the dev module of the connected chip when omitted
+        #[arg(long)]
+        fqbn: Option<String>,
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long, default_value_t = 460800)]
+        baud: u32,
+    },
+    /// Run OpenOCD on the board's USB-JTAG or an ESP-Prog for GDB to attach
+    /// to, until interrupted
+    Openocd {
+        /// Chip to debug (esp32s3, ESP32-C3, ...); identified over the serial
+        /// port when omitted
+        #[arg(long)]
+        chip: Option<String>,
+        #[arg(long)]
+        port: Option<String>,
+        /// Print the generated config instead of running OpenOCD
+        #[arg(long)]
+        print_config: bool,
+    },
+    /// Start GDB on an app against a running `openocd`, reset the chip and
+    /// stop at a breakpoint
+    Gdb {
+        /// The app ELF, or an ESP-IDF project or build directory
+        #[arg(default_value = ".")]
+        path: String,
+        /// Chip the app is for; identified over the serial port when omitted
+        #[arg(long)]
+        chip: Option<String>,
+        #[arg(long)]
+        port: Option<String>,
+        /// Where to stop first
+        #[arg(long = "break", default_value = "app_main")]
+        breakpoint: String,
+    },
+    /// List a chip's peripherals from its SVD file, or the registers and
+    /// bitfields of one of them
+    Svd {
+        /// Chip (esp32s3, ESP32-C3, ...)
+        chip: String,
+        peripheral: Option<String>,
+    },
+    /// Print a crash report from an ESP-IDF core dump: the crashed task,
+    /// each task's backtrace and registers, like espcoredump.py info_corefile
+    Coredump {
+        /// The app ELF, or an ESP-IDF project or build directory
+        #[arg(default_value = ".")]
+        elf: String,
+        /// A saved dump (monitor output, base64, partition or ELF); the
+        /// board's coredump partition is read when omitted
+        #[arg(long)]
+        file: Option<String>,
+        #[arg(long)]
+        port: Option<String>,
+    },
+    /// Copy code.py and lib/ of a CircuitPython project to the CIRCUITPY drive
+    Circuitpy {
+        /// Project folder
+        #[arg(default_value = ".")]
+        project: String,
+        /// Mount point of the drive, found automatically when omitted
+        #[arg(long)]
+        drive: Option<String>,
+        /// Keep copying whenever a project file changes
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Open a MicroPython WebREPL session, or copy a file over it
+    Webrepl {
+        /// IP address or hostname of the board, optionally with :port
+        host: String,
+        #[arg(long)]
+        password: String,
+        /// Upload LOCAL to REMOTE on the board instead of opening the REPL
+        #[arg(long, num_args = 2, value_names = ["LOCAL", "REMOTE"], conflicts_with = "get")]
+        put: Option<Vec<String>>,
+        /// Download REMOTE from the board to LOCAL
+        #[arg(long, num_args = 2, value_names = ["REMOTE", "LOCAL"])]
+        get: Option<Vec<String>>,
+    },
+    /// Run a rhai automation script (send/expect/reset/flash) against a board
+    Script {
+        /// Path to the .rhai script
+        file: String,
+        #[arg(long)]
+        port: Option<String>,
+        /// Monitor baud rate the script talks at
+        #[arg(long, default_value_t = 115200)]
+        baud: u32,
+        /// Default offset for `flash(path)`
+        #[arg(long, default_value = "0x0")]
+        flash_address: String,
+        #[arg(long, default_value_t = 460800)]
+        flash_baud: u32,
+    },
+    /// Run a test sequence (JSON list of flash/reset/send/expect steps) and
+    /// report each step; fails when any step does
+    Test {
+        /// Path to the sequence .json
+        file: String,
+        #[arg(long)]
+        port: Option<String>,
+        /// Monitor baud rate, unless the sequence sets its own
+        #[arg(long, default_value_t = 115200)]
+        baud: u32,
+        /// Offset for flash steps that give none
+        #[arg(long, default_value = "0x10000")]
+        flash_address: String,
+        #[arg(long, default_value_t = 460800)]
+        flash_baud: u32,
+        /// Print the report as JSON instead of the device output
+        #[arg(long)]
+        json: bool,
+        /// Also write the report as JUnit XML, for CI
+        #[arg(long)]
+        junit: Option<String>,
+    },
+    /// Assert that a board prints each PATTERN (regex) in order after a
+    /// reset; exits non-zero when one doesn't show up in time
+    Expect {
+        #[arg(required = true)]
+        patterns: Vec<String>,
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long, default_value_t = 115200)]
+        baud: u32,
+        /// Milliseconds each pattern may take
+        #[arg(long, default_value_t = 10000)]
+        timeout_ms: u64,
+        /// Watch the running firmware instead of resetting first
+        #[arg(long)]
+        no_reset: bool,
+        /// Print the report as JSON instead of the device output
+        #[arg(long)]
+        json: bool,
+        /// Also write the report as JUnit XML, for CI
+        #[arg(long)]
+        junit: Option<String>,
+    },
+    /// Reset a board over and over and flag boots that miss a marker,
+    /// panic or brown out
+    Soak {
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long, default_value_t = 115200)]
+        baud: u32,
+        #[arg(long, default_value_t = 20)]
+        cycles: u32,
+        /// Regex every good boot prints
+        #[arg(long)]
+        marker: Option<String>,
+        /// Milliseconds to wait for the marker, or to watch each boot
+        #[arg(long, default_value_t = 5000)]
+        timeout_ms: u64,
+        /// Milliseconds from one reset to the next
+        #[arg(long)]
+        interval_ms: Option<u64>,
+        /// Print the report as JSON instead of the device output
+        #[arg(long)]
+        json: bool,
+        /// Also write the report as JUnit XML, for CI
+        #[arg(long)]
+        junit: Option<String>,
+    },
+    /// Reset a board and time its boot milestones: second-stage
+    /// bootloader, app_main and an optional marker
+    BootTime {
+        #[arg(long)]
+        port: Option<String>,
+        #[arg(long, default_value_t = 115200)]
+        baud: u32,
+        #[arg(long, default_value_t = 5)]
+        cycles: u32,
+        /// Regex that ends the boot instead of app_main
+        #[arg(long)]
+        marker: Option<String>,
+        /// Milliseconds a boot may take
+        #[arg(long, default_value_t = 10000)]
+        timeout_ms: u64,
+    },
+    /// Add a Secure Boot v2 signature to an app or bootloader image, like
+    /// `espsecure.py sign_data --version 2`
+    Sign {
+        /// Path to the .bin image; an existing signature is replaced
+        file: String,
+        /// PEM private key, RSA-3072 or ECDSA; read from stdin when omitted
+        #[arg(long)]
+        key: Option<String>,
+        /// Where to write the signed image, FILE-signed.bin by default
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    /// Encrypt an image for a chip with flash encryption in release mode,
+    /// like `espsecure.py encrypt_flash_data`; flash the result as is
+    Encrypt {
+        /// Path to the .bin image
+        file: String,
+        /// Target chip, e.g. esp32s3; the ESP32 and ESP32-C2 schemes aren't
+        /// supported
+        #[arg(long)]
+        chip: String,
+        /// Raw XTS-AES key file, 32 or 64 bytes
+        #[arg(long)]
+        key: String,
+        /// Flash offset the image will be written to
+        #[arg(long)]
+        address: String,
+        /// Where to write the encrypted image, FILE-encrypted.bin by default
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FsOp {
+    /// List a directory
+    Ls {
+        #[arg(default_value = "/")]
+        path: String,
+    },
+    /// Copy a local file to the board
+    Put { local: String, remote: String },
+    /// Copy a file from the board
+    Get { remote: String, local: String },
+    /// Delete a file or an empty directory
+    Rm { path: String },
+    /// Create a directory
+    Mkdir { path: String },
+    /// Run a local .py file on the board without copying it, printing its output
+    Run { local: String },
+}
+
+fn resolve_port(port: Option<String>) -> Result<String, AppError> {
+    if let Some(port) = port {
+        return Ok(port);
+    }
+    let status = devices::detect(&DEFAULT_VID_ALLOWLIST);
+    status
+        .port_name
+        .ok_or_else(|| AppError::PortNotFound(format!("No board detected ({})", status.message)))
+}
+
+fn print_progress(info: &TaskInfo) {
+    if info.state != "running" {
+        return;
+    }
+    let eta = info
+        .eta_secs
+        .map_or(String::new(), |secs| format!(", ~{}s left", secs));
+    match (info.current * 100).checked_div(info.total) {
+        Some(percent) => eprint!("\r{} {}: {}%{}   ", info.kind, info.phase, percent, eta),
+        None => eprint!("\r{} {}{}   ", info.kind, info.phase, eta),
+    }
+}
+
+/// Flashes the images of a build after checking the board is the chip it
+/// was built for.
+fn flash_plan(
+    sessions: &FlasherSessions,
+    tasks: &TaskManager,
+    plan: &FlashPlan,
+    port: Option<String>,
+    baud: u32,
+) -> Result<(), AppError> {
+    let port = resolve_port(port)?;
+    let chip = plan.chip.as_deref();
+    eprintln!(
+        "Equivalent: {}",
+        esptool::write_flash(chip, &port, baud, &plan.segments)
+    );
+    let task = tasks.start("flash", Some(&port));
+    let result = chip
+        .map_or(Ok(()), |chip| {
+            esp_interaction::check_chip(sessions, &port, chip)
+        })
+        .and_then(|_| {
+            esp_interaction::flash_segments(sessions, &port, &plan.segments, baud, &task)
+        });
+    task.finish(&result);
+    eprintln!();
+    println!("{}", result?);
+    Ok(())
+}
+
+/// The device database at `path`, which has to exist already.
+fn open_device_db(path: &str) -> Result<DeviceDb, AppError> {
+    // Opening would create an empty one
+    let path = std::path::Path::new(path);
+    if !path.is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "No device database at {}",
+            path.display()
+        )));
+    }
+    DeviceDb::open(path)
+}
+
+fn run(command: Command) -> Result<(), AppError> {
+    let tasks = TaskManager::new(print_progress);
+    // One operation per run, nothing to keep a session for
+    let sessions = FlasherSessions::default();
+
+    match command {
+        Command::Flash {
+            file,
+            port,
+            address,
+            baud,
+            ota_slot,
+        } => {
+            let port = resolve_port(port)?;
+            if ota_slot {
+                let task = tasks.start("flash", Some(&port));
+                let result = esp_interaction::flash_ota_slot(&sessions, &port, &file, baud, &task);
+                task.finish(&result);
+                eprintln!();
+                println!("{}", result?);
+                return Ok(());
+            }
+            let address = esp_interaction::parse_flash_address(&address)?;
+            let segment = FlashSegment {
+                path: file.clone(),
+                address,
+            };
+            eprintln!(
+                "Equivalent: {}",
+                esptool::write_flash(None, &port, baud, &[segment])
+            );
+            let task = tasks.start("flash", Some(&port));
+            let result =
+                esp_interaction::flash_firmware(&sessions, &port, &file, address, baud, &task);
+            task.finish(&result);
+            eprintln!();
+            println!("{}", result?);
+        }
+        Command::Ota {
+            file,
+            host,
+            port,
+            password,
+            filesystem,
+        } => {
+            let options = OtaOptions {
+                host: &host,
+                port,
+                password: password.as_deref(),
+                target: if filesystem {
+                    OtaTarget::Filesystem
+                } else {
+                    OtaTarget::Firmware
+                },
+            };
+            let task = tasks.start("ota", Some(&host));
+            let result = espota::upload(&options, file.as_ref(), &task);
+            task.finish(&result);
+            eprintln!();
+            result?;
+            println!("Uploaded {} to {}", file, host);
+        }
+        Command::Improv {
+            port,
+            ssid,
+            password,
+        } => {
+            let port = resolve_port(port)?;
+            let task = tasks.start("improv", Some(&port));
+            let result = improv::provision(&port, &ssid, &password, &task);
+            task.finish(&result);
+            eprintln!();
+            let outcome = result?;
+            if let Some(device) = outcome.device {
+                println!(
+                    "{} {} on {} ({})",
+                    device.firmware, device.version, device.chip, device.device_name
+                );
+            }
+            match outcome.url {
+                Some(url) => println!("Joined {}, reachable at {}", ssid, url),
+                None => println!("Joined {}", ssid),
+            }
+        }
+        Command::BleScan { seconds, uart } => {
+            let devices = if uart {
+                ble_uart::scan(Duration::from_secs(seconds))?
+            } else {
+                wifi_prov::scan(Duration::from_secs(seconds))?
+            };
+            for device in devices {
+                let rssi = device.rssi.map(|r| r.to_string()).unwrap_or_default();
+                println!("{:<20} {:>5}  {}", device.id, rssi, device.name);
+            }
+        }
+        Command::BleProvision {
+            device,
+            ssid,
+            password,
+            pop,
+            security,
+            username,
+        } => {
+            let options = ProvOptions {
+                device: &device,
+                security,
+                pop: &pop,
+                username: &username,
+                ssid: &ssid,
+                passphrase: &password,
+            };
+            let task = tasks.start("ble_provision", Some(&device));
+            let result = wifi_prov::provision(&options, &task);
+            task.finish(&result);
+            eprintln!();
+            match result?.ip {
+                Some(ip) => println!("{} joined {} as {}", device, ssid, ip),
+                None => println!("{} joined {}", device, ssid),
+            }
+        }
+        Command::Erase { port } => {
+            let port = resolve_port(port)?;
+            eprintln!("Equivalent: {}", esptool::erase_flash(None, &port));
+            let task = tasks.start("erase", Some(&port));
+            let result = esp_interaction::erase_flash(&sessions, &port, &task);
+            task.finish(&result);
+            eprintln!();
+            println!("{}", result?);
+        }
+        Command::Benchmark {
+            port,
+            baud,
+            offset,
+            size,
+            json,
+        } => {
+            let options = BenchmarkOptions {
+                port_name: resolve_port(port)?,
+                baud_rates: baud,
+                offset: offset
+                    .as_deref()
+                    .map(esp_interaction::parse_flash_address)
+                    .transpose()?,
+                len: size,
+            };
+            let task = tasks.start("benchmark", Some(&options.port_name));
+            let result = esp_interaction::flash_benchmark(&sessions, &options, &task);
+            task.finish(&result);
+            eprintln!();
+            let runs = result?;
+
+            if json {
+                let text = serde_json::to_string_pretty(&runs)
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
+                println!("{}", text);
+            } else {
+                println!("{:>8}  {:>12}  {:>12}  result", "baud", "write", "read");
+                for run in &runs {
+                    let rate = |rate: Option<u64>| {
+                        rate.map(esp_interaction::format_rate)
+                            .unwrap_or_else(|| "-".to_string())
+                    };
+                    println!(
+                        "{:>8}  {:>12}  {:>12}  {}",
+                        run.baud_rate,
+                        rate(run.write_rate),
+                        rate(run.read_rate),
+                        run.error.as_deref().unwrap_or("ok")
+                    );
+                }
+            }
+            let summary = benchmark::summary(&runs);
+            if benchmark::fastest(&runs).is_none() {
+                return Err(AppError::Flasher(summary));
+            }
+            eprintln!("-- {} --", summary);
+        }
+        Command::Elf2image {
+            elf,
+            chip,
+            output,
+            flash_mode,
+            flash_size,
+            flash_freq,
+        } => {
+            let options = elf2image::ImageOptions {
+                chip,
+                flash_mode,
+                flash_size,
+                flash_freq,
+            };
+            let output = elf2image::convert_file(
+                std::path::Path::new(&elf),
+                output.as_deref().map(std::path::Path::new),
+                &options,
+            )?;
+            let info = image_info::load(&output)?;
+            println!(
+                "Wrote {} ({} bytes, {} segments)",
+                output.display(),
+                info.image_len,
+                info.segments.len()
+            );
+        }
+        Command::FullImage {
+            elf,
+            chip,
+            bootloader,
+            partitions,
+            output,
+            flash_mode,
+            flash_size,
+            flash_freq,
+        } => {
+            let options = elf2image::ImageOptions {
+                chip,
+                flash_mode,
+                flash_size,
+                flash_freq,
+            };
+            let len = full_image::assemble_file(
+                std::path::Path::new(&elf),
+                std::path::Path::new(&bootloader),
+                partitions.as_deref().map(std::path::Path::new),
+                &options,
+                std::path::Path::new(&output),
+            )?;
+            println!("Wrote {} ({} bytes); flash it at 0x0", output, len);
+        }
+        Command::Inventory {
+            output,
+            batch,
+            operator,
+            note,
+        } => {
+            let batch = InventoryBatch {
+                batch,
+                operator,
+                note,
+            };
+            let mut inventory = Inventory::open(std::path::Path::new(&output), batch)?;
+            eprintln!(
+                "-- recording boards to {} ({} so far), Ctrl+C to exit --",
+                output,
+                inventory.count()
+            );
+            let (tx, rx) = mpsc::channel();
+            let tracker = DeviceTracker::new(move |device| {
+                let _ = tx.send(device.clone());
+            });
+            loop {
+                let found = devices::scan(&DEFAULT_VID_ALLOWLIST);
+                let ports: Vec<String> = found.iter().filter_map(|d| d.port_name.clone()).collect();
+                sessions.retain(&ports);
+                tracker.sync(found);
+                for port in tracker.unidentified() {
+                    let result = esp_interaction::connect_and_get_info(&sessions, &port);
+                    if let Err(e) = &result {
+                        eprintln!("{}: {}", port, e);
+                    }
+                    tracker.identified(&port, &result);
+                }
+                for device in rx.try_iter() {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default();
+                    let Some(row) = inventory.record(&device, now)? else {
+                        continue;
+                    };
+                    let status = if row.duplicate {
+                        "already recorded"
+                    } else {
+                        "recorded"
+                    };
+                    println!(
+                        "{}  {}  {}  {}",
+                        row.mac_address,
+                        row.chip_model.as_deref().unwrap_or("-"),
+                        row.serial_number.as_deref().unwrap_or("-"),
+                        status
+                    );
+                }
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+        Command::Records {
+            db,
+            device,
+            operator,
+            run,
+            action,
+            passed,
+            failed,
+            days,
+            limit,
+            csv,
+        } => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            let query = RecordQuery {
+                device,
+                operator,
+                run,
+                action,
+                passed: (passed || failed).then_some(passed),
+                since: days.map(|days| now.saturating_sub(days * 86_400)),
+                until: None,
+                limit,
+            };
+            let records = open_device_db(&db)?.query(&query)?;
+            if csv {
+                print!("{}", device_db::to_csv(&records));
+            } else {
+                let text = serde_json::to_string_pretty(&records)
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
+                println!("{}", text);
+            }
+        }
+        Command::Runs { db, run, csv } => {
+            let db = open_device_db(&db)?;
+            match run {
+                None => {
+                    for run in db.runs()? {
+                        let name = if run.name.is_empty() {
+                            "(outside of a run)"
+                        } else {
+                            &run.name
+                        };
+                        println!(
+                            "{}  {} to {}  {} records",
+                            name,
+                            inventory::timestamp(run.first_at),
+                            inventory::timestamp(run.last_at),
+                            run.records
+                        );
+                    }
+                }
+                Some(run) if csv => {
+                    let query = RecordQuery {
+                        run: Some(run),
+                        ..RecordQuery::default()
+                    };
+                    print!("{}", device_db::to_csv(&db.query(&query)?));
+                }
+                Some(run) => print!("{}", db.run_report(&run)?.to_text()),
+            }
+        }
+        Command::ImageInfo { file, json } => {
+            let info = image_info::load(std::path::Path::new(&file))?;
+            if json {
+                let text = serde_json::to_string_pretty(&info)
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
+                println!("{}", text);
+            } else {
+                println!(
+                    "Chip: {} (id {})",
+                    info.chip.as_deref().unwrap_or("unknown"),
+                    info.chip_id
+                );
+                println!("Entry point: 0x{:08x}", info.entry_point);
+                println!(
+                    "Flash: {} {} {}",
+                    info.flash_mode.as_deref().unwrap_or("?"),
+                    info.flash_size.as_deref().unwrap_or("?"),
+                    info.flash_freq.as_deref().unwrap_or("?")
+                );
+                println!(
+                    "Chip revision: {} to {}",
+                    info.min_chip_revision, info.max_chip_revision
+                );
+                println!("{} segments:", info.segments.len());
+                for (i, segment) in info.segments.iter().enumerate() {
+                    println!(
+                        "  {}: 0x{:08x} len 0x{:05x} at file offset 0x{:05x}",
+                        i, segment.load_address, segment.len, segment.file_offset
+                    );
+                }
+                let valid = |valid: bool| if valid { "valid" } else { "invalid" };
+                println!(
+                    "Checksum: 0x{:02x} ({})",
+                    info.checksum,
+                    valid(info.checksum_valid)
+                );
+                match info.hash_valid {
+                    Some(hash_valid) => println!("SHA-256: {}", valid(hash_valid)),
+                    None => println!("SHA-256: not appended"),
+                }
+                if let Some(app) = &info.app {
+                    println!(
+                        "App: {} {}, ESP-IDF {}, built {}",
+                        app.project_name, app.version, app.idf_version, app.compiled
+                    );
+                }
+                if let Some(signature) = &info.signature {
+                    println!("Signature: {}", signature);
+                }
+            }
+            if !info.problems.is_empty() {
+                return Err(AppError::InvalidInput(info.problems.join("; ")));
+            }
+        }
+        Command::Info { port } => {
+            let port = resolve_port(port)?;
+            let details = esp_interaction::connect_and_get_info(&sessions, &port)?;
+            let json = serde_json::to_string_pretty(&details)
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            println!("{}", json);
+        }
+        Command::Report { port, html } => {
+            let port = resolve_port(port)?;
+            let status = devices::detect(&DEFAULT_VID_ALLOWLIST);
+            let status = (status.port_name.as_deref() == Some(port.as_str())).then_some(status);
+            let task = tasks.start("read", Some(&port));
+            let report = esp_interaction::device_report(&sessions, &port, status, &task);
+            task.finish(&report);
+            let report = report?;
+            if html {
+                print!("{}", report.to_html());
+            } else {
+                println!("{}", report.to_json());
+            }
+        }
+        Command::Monitor {
+            port,
+            baud,
+            capture,
+            defmt,
+        } => {
+            let port = resolve_port(port)?;
+            let stream = match &defmt {
+                Some(path) => {
+                    let table = defmt::Table::load(std::path::Path::new(path))?;
+                    Some(Mutex::new(DefmtStream::new(std::sync::Arc::new(table))))
+                }
+                None => None,
+            };
+            let monitor = Monitor::default();
+            monitor.connect(&port, baud, move |bytes| {
+                let mut stdout = std::io::stdout().lock();
+                match &stream {
+                    Some(stream) => {
+                        for chunk in stream.lock().unwrap().feed(bytes) {
+                            let _ = match chunk {
+                                defmt::Chunk::Text(text) => stdout.write_all(&text),
+                                defmt::Chunk::Line(line) => writeln!(stdout, "{}", line),
+                            };
+                        }
+                    }
+                    None => {
+                        let _ = stdout.write_all(bytes);
+                    }
+                }
+                let _ = stdout.flush();
+            })?;
+            if let Some(path) = &capture {
+                monitor.start_capture(std::path::Path::new(path))?;
+            }
+            eprintln!("-- monitoring {} @ {}, Ctrl+C to exit --", port, baud);
+
+            for line in std::io::stdin().lock().lines() {
+                let line = line?;
+                monitor.send(format!("{}\r\n", line).as_bytes())?;
+            }
+            monitor.disconnect()?;
+        }
+        Command::Listen { port, tcp } => {
+            let protocol = if tcp {
+                NetLogProtocol::Tcp
+            } else {
+                NetLogProtocol::Udp
+            };
+            let monitor = Monitor::default();
+            let port = monitor.listen(protocol, port, |bytes| {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(bytes);
+                let _ = stdout.flush();
+            })?;
+            eprintln!(
+                "-- listening on {} port {}, Ctrl+C to exit --",
+                protocol.as_str(),
+                port
+            );
+            loop {
+                std::thread::park();
+            }
+        }
+        Command::Micropython {
+            port,
+            chip,
+            version,
+            sha256,
+            list,
+            baud,
+        } => {
+            let chip = match chip {
+                Some(chip) => chip,
+                None => {
+                    let port = resolve_port(port.clone())?;
+                    esp_interaction::connect_and_get_info(&sessions, &port)?
+                        .chip_model
+                        .ok_or_else(|| AppError::WrongChip("Chip model unknown".to_string()))?
+                }
+            };
+            if list {
+                for release in mpy_firmware::releases(&chip)? {
+                    println!("{}\t{}\t{}", release.version, release.date, release.url);
+                }
+                return Ok(());
+            }
+            let cache = std::env::temp_dir().join("esp32dev-micropython");
+            let port = resolve_port(port)?;
+            let task = tasks.start("flash", Some(&port));
+            let fetched =
+                mpy_firmware::fetch(&cache, &chip, version.as_deref(), sha256.as_deref(), &task);
+            let result = fetched.and_then(|image| {
+                eprintln!("\n{} (sha256 {})", image.path.display(), image.sha256);
+                let path = image.path.display().to_string();
+                esp_interaction::flash_firmware(&sessions, &port, &path, image.offset, baud, &task)
+            });
+            task.finish(&result);
+            eprintln!();
+            println!("{}", result?);
+        }
+        Command::Idf {
+            project,
+            port,
+            baud,
+        } => {
+            let plan = idf::load(project.as_ref())?;
+            flash_plan(&sessions, &tasks, &plan, port, baud)?;
+        }
+        Command::Pio {
+            project,
+            env,
+            port,
+            baud,
+        } => {
+            let plan = platformio::load(project.as_ref(), env.as_deref())?;
+            let baud = baud.or(plan.baud_rate).unwrap_or(460800);
+            flash_plan(&sessions, &tasks, &plan, port, baud)?;
+        }
+        Command::Watch {
+            path,
+            port,
+            address,
+            baud,
+            monitor_baud,
+        } => {
+            let watched_path = path.clone();
+            let (segments, chip) = if std::path::Path::new(&path).is_dir() {
+                let plan = idf::load(path.as_ref())?;
+                (plan.segments, plan.chip)
+            } else {
+                let address = esp_interaction::parse_flash_address(&address)?;
+                (vec![FlashSegment { path, address }], None)
+            };
+            let port = resolve_port(port)?;
+            let monitor = Monitor::default();
+            monitor.connect(&port, monitor_baud, |bytes| {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(bytes);
+                let _ = stdout.flush();
+            })?;
+
+            let paths = segments.iter().map(|s| s.path.clone().into()).collect();
+            let (watched, board) = (monitor.clone(), port.clone());
+            let (tasks, sessions) = (tasks.clone(), sessions.clone());
+            let watch = FlashWatch::start(paths, move || {
+                eprintln!("-- rebuilt, flashing {} --", board);
+                let task = tasks.start("flash", Some(&board));
+                // The monitor lets go of the port meanwhile and comes back
+                // on the new firmware
+                let flash = || {
+                    if let Some(chip) = &chip {
+                        esp_interaction::check_chip(&sessions, &board, chip)?;
+                    }
+                    esp_interaction::flash_segments(&sessions, &board, &segments, baud, &task)
+                };
+                let result = watched.with_port(&board, "flash", |_| (), flash);
+                task.finish(&result);
+                eprintln!();
+                match result {
+                    Ok(summary) => eprintln!("-- {} --", summary),
+                    Err(e) => eprintln!("-- flash failed: {} --", e),
+                }
+            });
+            eprintln!(
+                "-- flashing {} on every rebuild, monitoring {} @ {}, Ctrl+C to exit --",
+                watched_path, port, monitor_baud
+            );
+
+            for line in std::io::stdin().lock().lines() {
+                let line = line?;
+                monitor.send(format!("{}\r\n", line).as_bytes())?;
+            }
+            watch.stop();
+            monitor.disconnect()?;
+        }
+        Command::Arduino {
+            sketch,
+            fqbn,
+            port,
+            baud,
+        } => {
+            let cli = arduino::find_cli().ok_or_else(|| {
+                AppError::Config("arduino-cli was not found on the PATH".to_string())
+            })?;
+            let port = resolve_port(port)?;
+            let fqbn = match fqbn {
+                Some(fqbn) => fqbn,
+                None => {
+                    let chip = esp_interaction::connect_and_get_info(&sessions, &port)?
+                        .chip_model
+                        .ok_or_else(|| AppError::WrongChip("Chip model unknown".to_string()))?;
+                    arduino::fqbn(&chip).ok_or_else(|| {
+                        AppError::InvalidInput(format!(
+                            "No Arduino board for {}; pass --fqbn",
+                            chip
+                        ))
+                    })?
+                }
+            };
+            let (_, name) = arduino::sketch(sketch.as_ref())?;
+            let out_dir = std::env::temp_dir().join("esp32dev-arduino").join(name);
+            let task = tasks.start("arduino_build", Some(&port));
+            let result = arduino::compile(&cli, sketch.as_ref(), &fqbn, &out_dir, &task, |text| {
+                eprint!("{}", text)
+            });
+            task.finish(&result);
+            flash_plan(&sessions, &tasks, &result?, Some(port), baud)?;
+        }
+        Command::Openocd {
+            chip,
+            port,
+            print_config,
+        } => {
+            let chip = match chip {
+                Some(chip) => chip,
+                None => {
+                    let port = resolve_port(port)?;
+                    esp_interaction::connect_and_get_info(&sessions, &port)?
+                        .chip_model
+                        .ok_or_else(|| AppError::WrongChip("Chip model unknown".to_string()))?
+                }
+            };
+            let found = openocd::probes();
+            let probe = openocd::pick_probe(&found, &chip).cloned().ok_or_else(|| {
+                AppError::PortNotFound(format!("No JTAG adapter for {} is connected", chip))
+            })?;
+            if print_config {
+                print!("{}", openocd::config(&chip, &probe, openocd::GDB_PORT)?);
+                return Ok(());
+            }
+            let program = openocd::find().ok_or_else(|| {
+                AppError::Config("openocd was not found on the PATH or in ~/.espressif".to_string())
+            })?;
+            let mut running = OpenOcd::start(&program, &chip, probe, |line| eprintln!("{}", line))?;
+            eprintln!(
+                "GDB: target extended-remote :{} (Ctrl+C to stop)",
+                openocd::GDB_PORT
+            );
+            while running.status().running {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            if let Some(code) = running.status().exit_code.filter(|code| *code != 0) {
+                return Err(AppError::Config(format!("openocd exited with {}", code)));
+            }
+        }
+        Command::Gdb {
+            path,
+            chip,
+            port,
+            breakpoint,
+        } => {
+            let elf = if std::path::Path::new(&path).is_dir() {
+                idf::app_elf(path.as_ref())?
+            } else {
+                std::path::PathBuf::from(&path)
+            };
+            let chip = match chip {
+                Some(chip) => chip,
+                None => {
+                    let port = resolve_port(port)?;
+                    esp_interaction::connect_and_get_info(&sessions, &port)?
+                        .chip_model
+                        .ok_or_else(|| AppError::WrongChip("Chip model unknown".to_string()))?
+                }
+            };
+            let program = gdb::find(&chip)?;
+            // What ESP-IDF's gdbinit does: halt from reset and run to the
+            // breakpoint
+            let status = std::process::Command::new(&program)
+                .arg("-q")
+                .arg("-ex")
+                .arg(format!("target extended-remote :{}", openocd::GDB_PORT))
+                .args(["-ex", "monitor reset halt"])
+                .args(["-ex", "maintenance flush register-cache"])
+                .arg("-ex")
+                .arg(format!("thbreak {}", breakpoint))
+                .args(["-ex", "continue"])
+                .arg(&elf)
+                .status()
+                .map_err(|e| AppError::Io(format!("Cannot run {}: {}", program.display(), e)))?;
+            if !status.success() {
+                return Err(AppError::Config(format!("GDB exited with {}", status)));
+            }
+        }
+        Command::Svd { chip, peripheral } => {
+            let cache = std::env::temp_dir().join("esp32dev-svd");
+            let peripherals = svd::load(None, &cache, &chip)?;
+            let Some(name) = peripheral else {
+                for p in &peripherals {
+                    println!(
+                        "{:<20} {:#010x}  {}",
+                        p.name,
+                        p.base_address,
+                        p.description.as_deref().unwrap_or("")
+                    );
+                }
+                return Ok(());
+            };
+            let p = peripherals
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(&name))
+                .ok_or_else(|| AppError::InvalidInput(format!("No peripheral {}", name)))?;
+            for register in &p.registers {
+                let reset = register
+                    .reset_value
+                    .map(|value| format!("{:#010x}", value))
+                    .unwrap_or_default();
+                println!(
+                    "{:#010x}  {:<28} {:<10}  {}",
+                    register.address,
+                    register.name,
+                    reset,
+                    register.access.as_deref().unwrap_or("")
+                );
+                for field in register.fields.iter().rev() {
+                    let bits = if field.width > 1 {
+                        format!("[{}:{}]", field.lsb + field.width - 1, field.lsb)
+                    } else {
+                        format!("[{}]", field.lsb)
+                    };
+                    println!("            {:<8} {}", bits, field.name);
+                }
+            }
+        }
+        Command::Coredump { elf, file, port } => {
+            let elf = if std::path::Path::new(&elf).is_dir() {
+                idf::app_elf(elf.as_ref())?
+            } else {
+                std::path::PathBuf::from(&elf)
+            };
+            let dump = match file {
+                Some(file) => std::fs::read(file)?,
+                None => {
+                    let port = resolve_port(port)?;
+                    let task = tasks.start("read", Some(&port));
+                    let dump = esp_interaction::read_coredump(&sessions, &port, &task);
+                    task.finish(&dump);
+                    dump?
+                }
+            };
+            print!("{}", coredump::report(&dump, &elf)?.to_text());
+        }
+        Command::Circuitpy {
+            project,
+            drive,
+            watch,
+        } => {
+            let drive = match drive {
+                Some(drive) => drive,
+                None => {
+                    let serial = devices::detect(&DEFAULT_VID_ALLOWLIST).serial_number;
+                    let found = circuitpython::pick(circuitpython::drives(), serial.as_deref())
+                        .ok_or(AppError::NotConnected)?;
+                    eprintln!("{} (CircuitPython {})", found.path, found.version);
+                    found.path
+                }
+            };
+            let report = |copied: Vec<String>| {
+                for file in copied {
+                    println!("{}", file);
+                }
+            };
+            if !watch {
+                report(circuitpython::sync(project.as_ref(), drive.as_ref())?);
+                return Ok(());
+            }
+            eprintln!("-- copying changes to {}, Ctrl+C to exit --", drive);
+            let _watch =
+                SyncWatch::start(project.into(), drive.into(), move |result| match result {
+                    Ok(copied) => report(copied),
+                    Err(e) => eprintln!("{}", e),
+                });
+            loop {
+                std::thread::park();
+            }
+        }
+        Command::Fs { port, op } => {
+            let port = resolve_port(port)?;
+            micropython::session(&port, |repl| match op {
+                FsOp::Ls { path } => {
+                    for entry in repl.list(&path)? {
+                        if entry.is_dir {
+                            println!("{:>10}  {}/", "", entry.name);
+                        } else {
+                            println!("{:>10}  {}", entry.size, entry.name);
+                        }
+                    }
+                    Ok(())
+                }
+                FsOp::Put { local, remote } => {
+                    let data = std::fs::read(&local)?;
+                    repl.write_file(&remote, &data, |written| {
+                        eprint!("\r{}/{} bytes", written, data.len());
+                    })?;
+                    eprintln!();
+                    println!("Uploaded {} to {}", local, remote);
+                    Ok(())
+                }
+                FsOp::Get { remote, local } => {
+                    std::fs::write(&local, repl.read_file(&remote)?)?;
+                    println!("Downloaded {} to {}", remote, local);
+                    Ok(())
+                }
+                FsOp::Rm { path } => repl.remove(&path),
+                FsOp::Mkdir { path } => repl.mkdir(&path),
+                FsOp::Run { local } => {
+                    let code = std::fs::read_to_string(&local)?;
+                    repl.run(
+                        &code,
+                        || false,
+                        |bytes| {
+                            let mut stdout = std::io::stdout().lock();
+                            let _ = stdout.write_all(bytes);
+                            let _ = stdout.flush();
+                        },
+                    )
+                }
+            })?;
+        }
+        Command::BleMonitor { device } => {
+            let monitor = Monitor::default();
+            monitor.ble_uart(&device, |bytes| {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(bytes);
+                let _ = stdout.flush();
+            })?;
+            eprintln!("-- BLE UART on {}, Ctrl+C to exit --", device);
+
+            for line in std::io::stdin().lock().lines() {
+                let line = line?;
+                monitor.send(format!("{}\r\n", line).as_bytes())?;
+            }
+            monitor.disconnect()?;
+        }
+        Command::Webrepl {
+            host,
+            password,
+            put,
+            get,
+        } => {
+            if let Some([local, remote]) = put.as_deref() {
+                let data = std::fs::read(local)?;
+                let mut repl = WebRepl::connect(&host, &password)?;
+                let result = repl.put(remote, &data);
+                repl.close();
+                result?;
+                println!("Uploaded {} to {}", local, remote);
+            } else if let Some([remote, local]) = get.as_deref() {
+                let mut repl = WebRepl::connect(&host, &password)?;
+                let result = repl.get(remote);
+                repl.close();
+                std::fs::write(local, result?)?;
+                println!("Downloaded {} to {}", remote, local);
+            } else {
+                let monitor = Monitor::default();
+                monitor.webrepl(&host, &password, |bytes| {
+                    let mut stdout = std::io::stdout().lock();
+                    let _ = stdout.write_all(bytes);
+                    let _ = stdout.flush();
+                })?;
+                eprintln!("-- WebREPL on {}, Ctrl+C to exit --", host);
+
+                for line in std::io::stdin().lock().lines() {
+                    let line = line?;
+                    monitor.send(format!("{}\r", line).as_bytes())?;
+                }
+                monitor.disconnect()?;
+            }
+        }
+        Command::Script {
+            file,
+            port,
+            baud,
+            flash_address,
+            flash_baud,
+        } => {
+            let source = std::fs::read_to_string(&file)?;
+            let options = ScriptOptions {
+                port_name: resolve_port(port)?,
+                baud_rate: baud,
+                flash_address: esp_interaction::parse_flash_address(&flash_address)?,
+                flash_baud,
+            };
+            // Progress lines would interleave with the device output
+            let quiet = TaskManager::default();
+            let task = quiet.start("script", Some(&options.port_name));
+            let result = scripting::run(&source, &options, &task, |text| {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(text.as_bytes());
+                let _ = stdout.flush();
+            });
+            task.finish(&result);
+            eprintln!("-- {} --", result?);
+        }
+        Command::Test {
+            file,
+            port,
+            baud,
+            flash_address,
+            flash_baud,
+            json,
+            junit,
+        } => {
+            let test = sequence::load(std::path::Path::new(&file))?;
+            let options = ScriptOptions {
+                port_name: resolve_port(port)?,
+                baud_rate: baud,
+                flash_address: esp_interaction::parse_flash_address(&flash_address)?,
+                flash_baud,
+            };
+            run_test(&test, &options, json, junit.as_deref())?;
+        }
+        Command::Expect {
+            patterns,
+            port,
+            baud,
+            timeout_ms,
+            no_reset,
+            json,
+            junit,
+        } => {
+            let reset = (!no_reset).then_some(TestStep::Reset);
+            let expects = patterns.into_iter().map(|pattern| TestStep::Expect {
+                pattern,
+                timeout_ms: Some(timeout_ms),
+            });
+            let test = TestSequence {
+                name: "expect".to_string(),
+                baud_rate: None,
+                steps: reset.into_iter().chain(expects).collect(),
+            };
+            // No flash steps, so the flash defaults don't matter
+            let options = ScriptOptions {
+                port_name: resolve_port(port)?,
+                baud_rate: baud,
+                flash_address: 0,
+                flash_baud: baud,
+            };
+            run_test(&test, &options, json, junit.as_deref())?;
+        }
+        Command::Soak {
+            port,
+            baud,
+            cycles,
+            marker,
+            timeout_ms,
+            interval_ms,
+            json,
+            junit,
+        } => {
+            let options = SoakOptions {
+                port_name: resolve_port(port)?,
+                baud_rate: baud,
+                cycles,
+                marker,
+                boot_timeout: Duration::from_millis(timeout_ms),
+                interval: interval_ms.map(Duration::from_millis),
+            };
+            let quiet = TaskManager::default();
+            let task = quiet.start("soak", Some(&options.port_name));
+            let result = soak::run(&options, &task, move |text| {
+                if !json {
+                    let mut stdout = std::io::stdout().lock();
+                    let _ = stdout.write_all(text.as_bytes());
+                    let _ = stdout.flush();
+                }
+            });
+            task.finish(&result);
+            let report = result?;
+
+            if json {
+                let text = serde_json::to_string_pretty(&report)
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
+                println!("{}", text);
+            }
+            if let Some(path) = junit {
+                std::fs::write(path, junit::soak_report(&report))?;
+            }
+            let summary = soak::summary(&report);
+            if report.failed() > 0 {
+                return Err(AppError::Script(summary));
+            }
+            eprintln!("-- {} --", summary);
+        }
+        Command::BootTime {
+            port,
+            baud,
+            cycles,
+            marker,
+            timeout_ms,
+        } => {
+            let port_name = resolve_port(port)?;
+            let timer = Mutex::new(BootTimer::new(marker.as_deref())?);
+            let (boots_tx, boots) = mpsc::channel();
+            let quiet = TaskManager::default();
+            let task = quiet.start("boot_time", Some(&port_name));
+            let console = Console::open(&port_name, baud, task.clone(), move |bytes| {
+                if let Some(timing) = timer.lock().unwrap().push(bytes, Instant::now()) {
+                    let _ = boots_tx.send(timing);
+                }
+            })?;
+
+            let ms = |ms: Option<u64>| ms.map_or("-".to_string(), |ms| format!("{} ms", ms));
+            println!(
+                "{:>4}  {:>12}  {:>12}  {:>12}",
+                "boot", "bootloader", "app_main", "marker"
+            );
+            for cycle in 1..=cycles {
+                // Drop a boot that finished before this reset
+                while boots.try_recv().is_ok() {}
+                console.reset()?;
+                let timing = boots
+                    .recv_timeout(Duration::from_millis(timeout_ms))
+                    .map_err(|_| {
+                        AppError::Timeout(format!(
+                            "Boot {} didn't finish within {} ms",
+                            cycle, timeout_ms
+                        ))
+                    })?;
+                println!(
+                    "{:>4}  {:>12}  {:>12}  {:>12}",
+                    cycle,
+                    ms(timing.bootloader_ms),
+                    ms(timing.app_main_ms),
+                    ms(timing.marker_ms)
+                );
+            }
+        }
+        Command::Sign { file, key, output } => {
+            let pem = match key {
+                Some(path) => std::fs::read_to_string(path)?,
+                None => {
+                    eprintln!("Paste the PEM private key, then end input (Ctrl-D):");
+                    let mut pem = String::new();
+                    std::io::stdin().read_to_string(&mut pem)?;
+                    pem
+                }
+            };
+            let key = SigningKey::from_pem(&pem)?;
+            let signed = signature::sign_file(
+                file.as_ref(),
+                &key,
+                output.as_deref().map(std::path::Path::new),
+            )?;
+            println!(
+                "Signed {} with {} key {} -> {}",
+                file, signed.scheme, signed.key_id, signed.path
+            );
+        }
+        Command::Encrypt {
+            file,
+            chip,
+            key,
+            address,
+            output,
+        } => {
+            let address = esp_interaction::parse_flash_address(&address)?;
+            let written = flash_encryption::encrypt_file(
+                &chip,
+                file.as_ref(),
+                address,
+                key.as_ref(),
+                output.as_deref().map(std::path::Path::new),
+            )?;
+            println!("Encrypted {} for 0x{:X} -> {}", file, address, written);
+        }
+    }
+    Ok(())
+}
+
+/// Runs a test sequence for `test` and `expect`, printing a line per step
+/// and writing JUnit XML to `junit` when given. Fails when a step did.
+fn run_test(
+    test: &TestSequence,
+    options: &ScriptOptions,
+    json: bool,
+    junit: Option<&str>,
+) -> Result<(), AppError> {
+    let quiet = TaskManager::default();
+    let task = quiet.start("test", Some(&options.port_name));
+    let result = sequence::run(test, options, &task, move |text| {
+        if !json {
+            let mut stdout = std::io::stdout().lock();
+            let _ = stdout.write_all(text.as_bytes());
+            let _ = stdout.flush();
+        }
+    });
+    task.finish(&result);
+    let report = result?;
+
+    if json {
+        let text =
+            serde_json::to_string_pretty(&report).map_err(|e| AppError::Internal(e.to_string()))?;
+        println!("{}", text);
+    } else {
+        eprintln!();
+        for step in &report.steps {
+            let status = match step.status {
+                StepStatus::Passed => "PASS",
+                StepStatus::Failed => "FAIL",
+                StepStatus::Skipped => "SKIP",
+            };
+            eprintln!(
+                "{}  {:<40} {:>6} ms  {}",
+                status,
+                step.description,
+                step.duration_ms,
+                step.detail.as_deref().unwrap_or("").trim()
+            );
+        }
+    }
+    if let Some(path) = junit {
+        std::fs::write(path, junit::test_report(&report))?;
+    }
+    eprintln!("-- {} --", report.outcome()?);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error[{}]: {}", e.code(), e.message());
+            if let Some(hint) = e.hint() {
+                eprintln!("hint: {}", hint);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
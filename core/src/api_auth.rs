@@ -0,0 +1,55 @@
+//! Who may use the local control API. Binding to 127.0.0.1 keeps other
+//! machines out but not web pages: any page can send requests to
+//! localhost, and a DNS rebinding page does so under its own host name. So
+//! the `Host` has to name the loopback address, a foreign `Origin` is
+//! refused, and requests that change anything carry the install's token.
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+const TOKEN_LEN: usize = 32;
+
+/// A fresh random token, as hex.
+pub fn new_token() -> String {
+    let mut bytes = [0u8; TOKEN_LEN];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether `host` (the `Host` header) names the API on `port` by its
+/// loopback address.
+pub fn host_allowed(host: Option<&str>, port: u16) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    ["127.0.0.1", "localhost"]
+        .iter()
+        .any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, port)))
+}
+
+/// Whether a request with `origin` comes from the app or a local page. CLI
+/// tools send no origin at all.
+pub fn origin_allowed(origin: Option<&str>) -> bool {
+    let Some(origin) = origin else {
+        return true;
+    };
+    let host = origin.split("://").nth(1).unwrap_or_default();
+    let host = host.rsplit_once(':').map_or(host, |(h, _)| h);
+    matches!(host, "localhost" | "127.0.0.1" | "tauri.localhost")
+}
+
+/// Whether `authorization` is `Bearer <token>`, compared in constant time.
+pub fn bearer_matches(authorization: Option<&str>, token: &str) -> bool {
+    let Some((scheme, given)) = authorization.and_then(|a| a.trim().split_once(' ')) else {
+        return false;
+    };
+    let given = given.trim().as_bytes();
+    scheme.eq_ignore_ascii_case("bearer")
+        && !token.is_empty()
+        && given.len() == token.len()
+        && given
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,179 @@
+//! Arduino sketches, built with an installed `arduino-cli` and flashed by
+//! us. `arduino-cli compile --output-dir` leaves `<sketch>.ino.bin` next to
+//! the bootloader and partition table it was linked against, which are laid
+//! out like any other Arduino-style build.
+
+use crate::chips;
+use crate::images::BuildImages;
+use crate::models::{AppError, FlashPlan};
+use crate::tasks::TaskHandle;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+const CLI: &str = "arduino-cli";
+// How often a running compile checks for cancellation
+const POLL: Duration = Duration::from_millis(100);
+
+/// FQBN of the generic dev module board of the esp32 core for `model`.
+pub fn fqbn(model: &str) -> Option<String> {
+    let key = chips::model_key(model);
+    let known = [
+        "esp32", "esp32s2", "esp32s3", "esp32c2", "esp32c3", "esp32c5", "esp32c6", "esp32h2",
+        "esp32p4",
+    ];
+    known
+        .contains(&key.as_str())
+        .then(|| format!("esp32:esp32:{}", key))
+}
+
+/// `arduino-cli` on the `PATH`, if installed.
+pub fn find_cli() -> Option<PathBuf> {
+    let name = format!("{}{}", CLI, std::env::consts::EXE_SUFFIX);
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(&name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The sketch directory and its name for a sketch folder or its `.ino`.
+pub fn sketch(path: &Path) -> Result<(PathBuf, String), AppError> {
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(path).to_path_buf()
+    } else {
+        path.to_path_buf()
+    };
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !dir.join(format!("{}.ino", name)).is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "{} is not a sketch; it needs a {}.ino",
+            dir.display(),
+            name
+        )));
+    }
+    Ok((dir, name))
+}
+
+/// The Arduino15 data directory `arduino-cli` installs cores into.
+fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("ARDUINO_DIRECTORIES_DATA") {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        return Some(PathBuf::from(std::env::var_os("LOCALAPPDATA")?).join("Arduino15"));
+    }
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library/Arduino15"))
+    } else {
+        Some(home.join(".arduino15"))
+    }
+}
+
+/// `boot_app0.bin` of the newest installed esp32 core, if any.
+fn boot_app0() -> Option<PathBuf> {
+    let cores = data_dir()?.join("packages/esp32/hardware/esp32");
+    let version = |path: &Path| -> Vec<u64> {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        name.split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    std::fs::read_dir(cores)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .max_by_key(|path| version(path))
+        .map(|core| core.join("tools/partitions/boot_app0.bin"))
+        .filter(|path| path.is_file())
+}
+
+/// The images `arduino-cli compile` wrote for sketch `name` to `out_dir`.
+pub fn load(out_dir: &Path, name: &str) -> Result<FlashPlan, AppError> {
+    let images = BuildImages {
+        bootloader: out_dir.join(format!("{}.ino.bootloader.bin", name)),
+        partitions: out_dir.join(format!("{}.ino.partitions.bin", name)),
+        app: out_dir.join(format!("{}.ino.bin", name)),
+        boot_app0: boot_app0(),
+    };
+    images.plan(out_dir, |path| {
+        AppError::InvalidInput(format!(
+            "{} is missing; was the sketch built for an esp32 board?",
+            path.display()
+        ))
+    })
+}
+
+fn forward(stream: impl Read + Send + 'static, lines: mpsc::Sender<String>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            if lines.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Compiles the sketch at `sketch_path` for `fqbn` with `cli` into `out_dir`
+/// and returns its images. Compiler output goes to `output` line by line;
+/// cancelling the task stops the compiler.
+pub fn compile(
+    cli: &Path,
+    sketch_path: &Path,
+    fqbn: &str,
+    out_dir: &Path,
+    task: &TaskHandle,
+    mut output: impl FnMut(&str),
+) -> Result<FlashPlan, AppError> {
+    let (dir, name) = sketch(sketch_path)?;
+    std::fs::create_dir_all(out_dir).map_err(|e| AppError::Io(e.to_string()))?;
+    task.phase("compiling");
+    let mut child = Command::new(cli)
+        .arg("compile")
+        .arg("--fqbn")
+        .arg(fqbn)
+        .arg("--output-dir")
+        .arg(out_dir)
+        .arg(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Io(format!("Cannot run {}: {}", cli.display(), e)))?;
+
+    let (sender, lines) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward(stderr, sender);
+    }
+    loop {
+        match lines.recv_timeout(POLL) {
+            Ok(line) => output(&format!("{}\n", line)),
+            Err(RecvTimeoutError::Timeout) => {}
+            // Both streams closed: the compiler is done
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if task.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AppError::Cancelled);
+        }
+    }
+
+    let status = child.wait().map_err(|e| AppError::Io(e.to_string()))?;
+    if !status.success() {
+        return Err(AppError::InvalidInput(format!(
+            "{} did not compile ({}); see the build log",
+            name, status
+        )));
+    }
+    load(out_dir, &name)
+}
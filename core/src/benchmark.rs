@@ -0,0 +1,135 @@
+//! Flash throughput at several baud rates. A test pattern is written to a
+//! scratch region outside every partition and read back at each rate, so
+//! users can pick the fastest rate their bridge and cable handle reliably.
+//! The flasher side is `esp_interaction::flash_benchmark`.
+
+use crate::esp_interaction::format_rate;
+use crate::models::{AppError, BenchmarkRun};
+use crate::otadata::{self, Partition};
+
+pub const DEFAULT_BAUD_RATES: [u32; 5] = [115200, 460800, 921600, 1500000, 2000000];
+/// Enough to average out the per-command overhead, short enough to take
+/// well under a minute at 115200.
+pub const DEFAULT_LEN: u32 = 64 * 1024;
+
+const SECTOR: u32 = 0x1000;
+// The bootloader and partition table; never scratch
+const RESERVED_END: u32 = otadata::PARTITION_TABLE_OFFSET + SECTOR;
+
+pub struct BenchmarkOptions {
+    pub port_name: String,
+    pub baud_rates: Vec<u32>,
+    /// Start of the scratch region; the end of the flash when `None`
+    pub offset: Option<u32>,
+    pub len: u32,
+}
+
+/// The bytes written at one rate. The stub inflates compressed writes, so
+/// a plain fill would cross the wire in a few packets; this is
+/// pseudo-random instead. A different `seed` per rate keeps a read of the
+/// previous run's data from passing.
+pub fn pattern(len: usize, seed: u32) -> Vec<u8> {
+    // xorshift32, which must not start from zero
+    let mut state = seed.wrapping_mul(0x9e37_79b9) | 1;
+    let mut data = Vec::with_capacity(len + 4);
+    while data.len() < len {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        data.extend_from_slice(&state.to_le_bytes());
+    }
+    data.truncate(len);
+    data
+}
+
+/// Where the pattern of `len` bytes goes: at `requested`, or at the end of
+/// a flash of `flash_size` bytes. Refuses regions that aren't whole
+/// sectors, overlap the bootloader, the partition table or a partition, or
+/// run past the end of the flash.
+pub fn scratch_region(
+    partitions: &[Partition],
+    flash_size: Option<u32>,
+    len: u32,
+    requested: Option<u32>,
+) -> Result<u32, AppError> {
+    if len == 0 || !len.is_multiple_of(SECTOR) {
+        return Err(AppError::InvalidInput(format!(
+            "The benchmark size must be a multiple of 4 KB, not {} bytes",
+            len
+        )));
+    }
+    let start = match (requested, flash_size) {
+        (Some(offset), _) => offset,
+        (None, Some(size)) => size.checked_sub(len).ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "{} bytes don't fit in a flash of {} bytes",
+                len, size
+            ))
+        })?,
+        (None, None) => {
+            return Err(AppError::InvalidInput(
+                "Flash size unknown, give a scratch offset for the benchmark".into(),
+            ))
+        }
+    };
+    if !start.is_multiple_of(SECTOR) {
+        return Err(AppError::InvalidInput(format!(
+            "Scratch offset 0x{:X} is not at a sector boundary",
+            start
+        )));
+    }
+    if start < RESERVED_END {
+        return Err(AppError::InvalidInput(format!(
+            "Scratch offset 0x{:X} is in the bootloader or partition table",
+            start
+        )));
+    }
+    let end = start as u64 + len as u64;
+    if let Some(size) = flash_size.filter(|&size| end > size as u64) {
+        return Err(AppError::InvalidInput(format!(
+            "Scratch region 0x{:X}..0x{:X} runs past the end of the flash at 0x{:X}",
+            start, end, size
+        )));
+    }
+    let overlapped = partitions
+        .iter()
+        .find(|p| (p.offset as u64) < end && (start as u64) < p.offset as u64 + p.size as u64);
+    if let Some(partition) = overlapped {
+        return Err(AppError::InvalidInput(format!(
+            "Scratch region 0x{:X}..0x{:X} overlaps partition {} at 0x{:X}",
+            start, end, partition.label, partition.offset
+        )));
+    }
+    Ok(start)
+}
+
+/// The verified run with the highest write rate, the one that decides how
+/// long flashing takes.
+pub fn fastest(runs: &[BenchmarkRun]) -> Option<&BenchmarkRun> {
+    runs.iter()
+        .filter(|run| run.verified)
+        .max_by_key(|run| run.write_rate.unwrap_or_default())
+}
+
+/// One line per benchmark, e.g. `Fastest reliable rate: 921600 baud
+/// (85.3 KB/s write, 120.4 KB/s read); failed at 2000000`.
+pub fn summary(runs: &[BenchmarkRun]) -> String {
+    let failed: Vec<String> = runs
+        .iter()
+        .filter(|run| !run.verified)
+        .map(|run| run.baud_rate.to_string())
+        .collect();
+    let mut line = match fastest(runs) {
+        Some(run) => format!(
+            "Fastest reliable rate: {} baud ({} write, {} read)",
+            run.baud_rate,
+            format_rate(run.write_rate.unwrap_or_default()),
+            format_rate(run.read_rate.unwrap_or_default())
+        ),
+        None => "No rate passed".to_string(),
+    };
+    if !failed.is_empty() {
+        line.push_str(&format!("; failed at {}", failed.join(", ")));
+    }
+    line
+}
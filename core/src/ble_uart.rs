@@ -0,0 +1,176 @@
+//! Nordic UART Service over Bluetooth LE, as battery-powered loggers use in
+//! place of a serial port: the device notifies its output on the TX
+//! characteristic and takes input written to the RX characteristic.
+
+use crate::models::{AppError, ProvDevice};
+use crate::wifi_prov::ble::{adapter, ble_error, runtime, scan_peripherals, timed};
+use btleplug::api::{CharPropFlags, Characteristic, Peripheral as _, WriteType};
+use btleplug::platform::Peripheral;
+use futures::StreamExt;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+pub const SERVICE: Uuid = Uuid::from_u128(0x6e400001_b5a3_f393_e0a9_e50e24dcca9e);
+// Named from the device's side: it receives on RX and transmits on TX
+pub const RX: Uuid = Uuid::from_u128(0x6e400002_b5a3_f393_e0a9_e50e24dcca9e);
+pub const TX: Uuid = Uuid::from_u128(0x6e400003_b5a3_f393_e0a9_e50e24dcca9e);
+
+// What fits one write at the default ATT MTU of 23; btleplug doesn't tell
+// the negotiated one
+const CHUNK_SIZE: usize = 20;
+const CONNECT_SCAN: Duration = Duration::from_secs(3);
+// Not every platform ends the notification stream when the link drops
+const LINK_CHECK: Duration = Duration::from_secs(1);
+
+fn advertises_uart(peripheral: &Peripheral, runtime: &Runtime) -> bool {
+    runtime
+        .block_on(peripheral.properties())
+        .ok()
+        .flatten()
+        .is_some_and(|properties| properties.services.contains(&SERVICE))
+}
+
+/// Lists devices advertising the UART service, strongest signal first.
+pub fn scan(duration: Duration) -> Result<Vec<ProvDevice>, AppError> {
+    let runtime = runtime()?;
+    let found = runtime.block_on(async {
+        let adapter = adapter().await?;
+        scan_peripherals(&adapter, duration).await
+    })?;
+    Ok(found
+        .into_iter()
+        .filter(|(peripheral, _)| advertises_uart(peripheral, &runtime))
+        .map(|(_, device)| device)
+        .collect())
+}
+
+/// A connection to one device's UART service.
+pub struct BleUart {
+    runtime: Runtime,
+    peripheral: Peripheral,
+    rx: Characteristic,
+    write_type: WriteType,
+    name: String,
+    // Fed by a task forwarding TX notifications; closed when they end
+    received: Receiver<Vec<u8>>,
+    checked: Instant,
+}
+
+impl BleUart {
+    /// Connects to the device `id` (address or name, see
+    /// [`ProvDevice::id`]) and subscribes to its output.
+    pub fn connect(id: &str) -> Result<Self, AppError> {
+        let runtime = runtime()?;
+        let (peripheral, name) = runtime.block_on(async {
+            let adapter = adapter().await?;
+            scan_peripherals(&adapter, CONNECT_SCAN)
+                .await?
+                .into_iter()
+                .find(|(_, device)| device.id == id || device.name == id)
+                .map(|(peripheral, device)| (peripheral, device.name))
+                .ok_or_else(|| AppError::PortNotFound(format!("{} is not advertising", id)))
+        })?;
+
+        let characteristics = runtime.block_on(async {
+            timed("connect", peripheral.connect()).await?;
+            timed("service discovery", peripheral.discover_services()).await?;
+            Ok::<_, AppError>(peripheral.characteristics())
+        })?;
+        let find = |uuid: Uuid| characteristics.iter().find(|c| c.uuid == uuid).cloned();
+        let (Some(rx), Some(tx)) = (find(RX), find(TX)) else {
+            runtime.block_on(peripheral.disconnect()).ok();
+            return Err(AppError::WrongChip(format!(
+                "{} does not offer the Nordic UART service",
+                name
+            )));
+        };
+        // Loggers favour the faster write without response where offered
+        let write_type = if rx
+            .properties
+            .contains(CharPropFlags::WRITE_WITHOUT_RESPONSE)
+        {
+            WriteType::WithoutResponse
+        } else {
+            WriteType::WithResponse
+        };
+
+        let (sender, received) = mpsc::channel();
+        runtime.block_on(async {
+            let mut notifications = peripheral.notifications().await.map_err(ble_error)?;
+            timed("subscribe", peripheral.subscribe(&tx)).await?;
+            tokio::spawn(async move {
+                while let Some(notification) = notifications.next().await {
+                    if notification.uuid == TX && sender.send(notification.value).is_err() {
+                        break;
+                    }
+                }
+            });
+            Ok::<_, AppError>(())
+        })?;
+
+        info!("BLE UART connected to {}", name);
+        Ok(BleUart {
+            runtime,
+            peripheral,
+            rx,
+            write_type,
+            name,
+            received,
+            checked: Instant::now(),
+        })
+    }
+
+    /// Name the device advertises.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Output notified since the last call, empty if none arrived. Fails
+    /// once the device has disconnected.
+    pub fn read(&mut self) -> Result<Vec<u8>, AppError> {
+        let mut data = Vec::new();
+        let mut ended = loop {
+            match self.received.try_recv() {
+                Ok(chunk) => data.extend(chunk),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        // Hand over what arrived before the link went away first
+        if !data.is_empty() {
+            return Ok(data);
+        }
+        if !ended && self.checked.elapsed() >= LINK_CHECK {
+            self.checked = Instant::now();
+            let connected = self.runtime.block_on(self.peripheral.is_connected());
+            ended = !connected.unwrap_or(true);
+        }
+        if ended {
+            return Err(AppError::Connection(format!("{} disconnected", self.name)));
+        }
+        Ok(data)
+    }
+
+    /// Writes `data` to the device, split into writes that fit one packet.
+    pub fn send(&mut self, data: &[u8]) -> Result<(), AppError> {
+        let peripheral = &self.peripheral;
+        let (rx, write_type) = (&self.rx, self.write_type);
+        self.runtime.block_on(async {
+            for chunk in data.chunks(CHUNK_SIZE) {
+                timed("write", peripheral.write(rx, chunk, write_type)).await?;
+            }
+            Ok::<_, AppError>(())
+        })?;
+        debug!("BLE UART send: {} bytes", data.len());
+        Ok(())
+    }
+
+    pub fn close(self) {
+        if let Err(e) = self.runtime.block_on(self.peripheral.disconnect()) {
+            warn!("BLE disconnect failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Pinout definitions of the dev boards we have drawings for, kept as JSON
+//! under `core/boards` so they can be edited without touching code.
+
+use crate::models::{BoardDefinition, ReservedPin};
+use tracing::warn;
+
+const DEFINITIONS: &[&str] = &[
+    include_str!("../boards/esp32-s3.json"),
+    include_str!("../boards/esp32-c6.json"),
+    include_str!("../boards/esp32-h2.json"),
+    include_str!("../boards/esp32-c2.json"),
+    include_str!("../boards/esp32-p4.json"),
+];
+
+/// All bundled definitions. A file that fails to parse is a build mistake
+/// and is skipped with a warning rather than taking the pinout down.
+pub fn all() -> Vec<BoardDefinition> {
+    DEFINITIONS
+        .iter()
+        .filter_map(|json| match serde_json::from_str(json) {
+            Ok(definition) => Some(definition),
+            Err(e) => {
+                warn!("Bad board definition: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Tags of pins to keep free of buttons and loads, in the order listed
+const RESERVED_TAGS: [&str; 4] = ["strapping", "usb", "flash", "psram"];
+
+/// Strapping, USB and flash/PSRAM pins of a board, header pins and those
+/// wired inside the module alike, grouped by kind and then by GPIO.
+pub fn reserved_pins(definition: &BoardDefinition) -> Vec<ReservedPin> {
+    let header = definition
+        .pins
+        .iter()
+        .filter_map(|pin| Some((pin.gpio?, &pin.tags, &pin.note)));
+    let internal = definition
+        .internal
+        .iter()
+        .map(|pin| (pin.gpio, &pin.tags, &pin.note));
+    let mut reserved: Vec<ReservedPin> = header
+        .chain(internal)
+        .flat_map(|(gpio, tags, note)| {
+            tags.iter()
+                .filter(|tag| RESERVED_TAGS.contains(&tag.as_str()))
+                .map(move |tag| ReservedPin {
+                    gpio,
+                    kind: tag.clone(),
+                    note: note.clone(),
+                })
+        })
+        .collect();
+    let rank = |kind: &str| RESERVED_TAGS.iter().position(|t| *t == kind);
+    reserved.sort_by_key(|pin| (rank(&pin.kind), pin.gpio));
+    reserved
+}
+
+/// Definition for a chip model, in either the `esp32s3` or `ESP32-S3`
+/// spelling.
+pub fn for_chip(model: &str) -> Option<BoardDefinition> {
+    let key = model.to_ascii_lowercase().replace('-', "");
+    all().into_iter().find(|definition| definition.chip == key)
+}
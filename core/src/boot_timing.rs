@@ -0,0 +1,129 @@
+//! Boot-time measurement from serial output: when the ROM banner, the
+//! second-stage bootloader, `app_main` and an optional user marker show up,
+//! for developers working on startup latency.
+
+use crate::models::{AppError, BootTiming};
+use regex::Regex;
+use std::sync::LazyLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// Longest partial line kept while waiting for its newline
+const MAX_LINE: usize = 4096;
+
+static ROM_BANNER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(ESP-ROM:|rst:0x)").unwrap());
+static BOOTLOADER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"2nd stage bootloader|^[IWE] \(\d+\) boot: ").unwrap());
+static APP_MAIN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"main_task: Calling app_main\(\)|cpu_start: Starting scheduler").unwrap()
+});
+
+struct Boot {
+    started: Instant,
+    timing: BootTiming,
+    reported: bool,
+}
+
+impl Boot {
+    fn has_milestone(&self) -> bool {
+        let t = &self.timing;
+        t.bootloader_ms.is_some() || t.app_main_ms.is_some() || t.marker_ms.is_some()
+    }
+}
+
+/// Follows a serial stream and reports each boot once it is through:
+/// at the `marker` line if one is given, at `app_main` otherwise.
+pub struct BootTimer {
+    marker: Option<Regex>,
+    line: Vec<u8>,
+    boot: Option<Boot>,
+}
+
+impl BootTimer {
+    /// `marker` is a regex; blank means none.
+    pub fn new(marker: Option<&str>) -> Result<Self, AppError> {
+        let marker = marker
+            .filter(|pattern| !pattern.trim().is_empty())
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    AppError::InvalidInput(format!("Invalid pattern {:?}: {}", pattern, e))
+                })
+            })
+            .transpose()?;
+        Ok(BootTimer {
+            marker,
+            line: Vec::new(),
+            boot: None,
+        })
+    }
+
+    /// Feeds output that arrived at `now`; returns a boot that just
+    /// finished.
+    pub fn push(&mut self, bytes: &[u8], now: Instant) -> Option<BootTiming> {
+        let mut finished = None;
+        for &byte in bytes {
+            if byte != b'\n' {
+                if self.line.len() < MAX_LINE {
+                    self.line.push(byte);
+                }
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&line);
+            if let Some(timing) = self.line_done(line.trim_end_matches('\r'), now) {
+                finished = Some(timing);
+            }
+        }
+        finished
+    }
+
+    fn line_done(&mut self, line: &str, now: Instant) -> Option<BootTiming> {
+        if ROM_BANNER.is_match(line) {
+            // Newer ROMs print several banner lines; only start over once
+            // the last boot got anywhere
+            if self
+                .boot
+                .as_ref()
+                .is_none_or(|b| b.has_milestone() || b.reported)
+            {
+                let at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                self.boot = Some(Boot {
+                    started: now,
+                    timing: BootTiming {
+                        at,
+                        bootloader_ms: None,
+                        app_main_ms: None,
+                        marker_ms: None,
+                    },
+                    reported: false,
+                });
+            }
+            return None;
+        }
+
+        let boot = self.boot.as_mut().filter(|b| !b.reported)?;
+        let ms = Some(now.duration_since(boot.started).as_millis() as u64);
+        let timing = &mut boot.timing;
+        if timing.bootloader_ms.is_none() && BOOTLOADER.is_match(line) {
+            timing.bootloader_ms = ms;
+        }
+        if timing.app_main_ms.is_none() && APP_MAIN.is_match(line) {
+            timing.app_main_ms = ms;
+        }
+        let done = match &self.marker {
+            Some(marker) if marker.is_match(line) => {
+                timing.marker_ms = ms;
+                true
+            }
+            Some(_) => false,
+            None => timing.app_main_ms.is_some(),
+        };
+        if done {
+            boot.reported = true;
+            return Some(boot.timing.clone());
+        }
+        None
+    }
+}
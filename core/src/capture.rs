@@ -0,0 +1,186 @@
+//! Recordings of serial traffic, for reproducing bugs reported from the
+//! field. A capture is a text file with a `#` header and a line per chunk
+//! read from or written to the device: milliseconds since the recording
+//! started, `rx` or `tx`, and the bytes in hex, e.g. `1532 rx 48690a`.
+
+use crate::models::AppError;
+use crate::tasks::TaskHandle;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+const HEADER: &str = "# esp32dev capture v1";
+// Longest sleep between cancel checks during a replay
+const REPLAY_TICK: Duration = Duration::from_millis(50);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// From the device
+    Rx,
+    /// To the device
+    Tx,
+}
+
+/// Where a replay goes: the monitor view shows the received bytes as if
+/// the device sent them, the port gets the sent bytes again.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayTarget {
+    Monitor,
+    Port,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaptureEvent {
+    pub at_ms: u64,
+    pub direction: Direction,
+    pub data: Vec<u8>,
+}
+
+impl CaptureEvent {
+    pub fn to_line(&self) -> String {
+        let direction = match self.direction {
+            Direction::Rx => "rx",
+            Direction::Tx => "tx",
+        };
+        let hex: String = self.data.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{} {} {}", self.at_ms, direction, hex)
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Reads the events of a capture, in file order.
+pub fn parse(text: &str) -> Result<Vec<CaptureEvent>, AppError> {
+    let mut events = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let event = (|| {
+            let at_ms = fields.next()?.parse().ok()?;
+            let direction = match fields.next()? {
+                "rx" => Direction::Rx,
+                "tx" => Direction::Tx,
+                _ => return None,
+            };
+            let data = parse_hex(fields.next().unwrap_or_default())?;
+            Some(CaptureEvent {
+                at_ms,
+                direction,
+                data,
+            })
+        })();
+        match event {
+            Some(event) => events.push(event),
+            None => {
+                return Err(AppError::InvalidInput(format!(
+                    "Capture line {} is not `<ms> rx|tx <hex>`: {}",
+                    index + 1,
+                    line
+                )))
+            }
+        }
+    }
+    Ok(events)
+}
+
+pub fn load(path: &Path) -> Result<Vec<CaptureEvent>, AppError> {
+    parse(&std::fs::read_to_string(path)?)
+}
+
+/// Appends the traffic of a monitor session to a capture file.
+pub struct Recorder {
+    file: BufWriter<File>,
+    path: PathBuf,
+    started: Instant,
+    events: u64,
+}
+
+impl Recorder {
+    /// Starts a capture at `path`, noting `source` (e.g. the port and baud
+    /// rate) in the header.
+    pub fn create(path: &Path, source: &str) -> Result<Self, AppError> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}\n# {}", HEADER, source)?;
+        Ok(Recorder {
+            file,
+            path: path.to_path_buf(),
+            started: Instant::now(),
+            events: 0,
+        })
+    }
+
+    pub fn write(&mut self, direction: Direction, data: &[u8]) {
+        let event = CaptureEvent {
+            at_ms: self.started.elapsed().as_millis() as u64,
+            direction,
+            data: data.to_vec(),
+        };
+        // Flushed per chunk so a killed app still leaves the capture behind;
+        // a full disk shouldn't take the monitor down with it
+        let written = writeln!(self.file, "{}", event.to_line()).and_then(|_| self.file.flush());
+        if let Err(e) = written {
+            warn!("Capture write to {} failed: {}", self.path.display(), e);
+        }
+        self.events += 1;
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Flushes the file; returns its path and the number of events.
+    pub fn finish(mut self) -> Result<(PathBuf, u64), AppError> {
+        self.file.flush()?;
+        Ok((self.path, self.events))
+    }
+}
+
+/// Calls `play` for each event at its recorded time, the gaps divided by
+/// `speed`. Cancelling `task` stops between events. Returns how many were
+/// played.
+pub fn replay(
+    events: &[CaptureEvent],
+    speed: f64,
+    task: &TaskHandle,
+    mut play: impl FnMut(&CaptureEvent) -> Result<(), AppError>,
+) -> Result<usize, AppError> {
+    if speed <= 0.0 {
+        return Err(AppError::InvalidInput(format!(
+            "Replay speed must be above 0, got {}",
+            speed
+        )));
+    }
+    let start = Instant::now();
+    let first = events.first().map_or(0, |e| e.at_ms);
+    for (index, event) in events.iter().enumerate() {
+        let offset_ms = event.at_ms.saturating_sub(first) as f64;
+        let due = Duration::from_secs_f64(offset_ms / 1000.0 / speed);
+        loop {
+            task.checkpoint()?;
+            let remaining = due.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            std::thread::sleep(remaining.min(REPLAY_TICK));
+        }
+        play(event)?;
+        task.progress(index as u64 + 1, events.len() as u64);
+    }
+    Ok(events.len())
+}
@@ -0,0 +1,20 @@
+//! Checksums the ROM and bootloader use for their own structures.
+
+/// CRC-32 as `esp_rom_crc32_le` computes it: the reflected zlib polynomial,
+/// inverted on the way in and out. An `init` of 0 gives zlib's CRC-32, which
+/// signature blocks are checked with; the bootloader checks otadata entries
+/// with an `init` of `u32::MAX`.
+pub fn crc32_le(init: u32, data: &[u8]) -> u32 {
+    let mut crc = !init;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,74 @@
+//! Static per-target data from the datasheets, for what can't be probed
+//! or when probing only got as far as the chip model.
+
+use crate::models::ChipCapabilities;
+
+const MB: u32 = 1024 * 1024;
+const GB: u32 = 1024 * MB;
+
+/// espflash's spelling of a model name, e.g. `esp32s3` for `ESP32-S3`.
+pub fn model_key(model: &str) -> String {
+    model.trim().to_ascii_lowercase().replace('-', "")
+}
+
+/// Looks up a chip by model name, accepting both espflash's `esp32s3` and
+/// the marketing `ESP32-S3` spelling.
+pub fn capabilities(model: &str) -> Option<ChipCapabilities> {
+    let key = model_key(model);
+    let (wifi, ble, ieee802154, usb_otg, cores, max_flash_bytes) = match key.as_str() {
+        "esp32" => (true, true, false, false, 2, 16 * MB),
+        "esp32s2" => (true, false, false, true, 1, GB),
+        "esp32s3" => (true, true, false, true, 2, GB),
+        "esp32c2" => (true, true, false, false, 1, 16 * MB),
+        "esp32c3" => (true, true, false, false, 1, 16 * MB),
+        "esp32c5" => (true, true, true, false, 1, 16 * MB),
+        "esp32c6" => (true, true, true, false, 1, 16 * MB),
+        "esp32c61" => (true, true, false, false, 1, 16 * MB),
+        "esp32h2" => (false, true, true, false, 1, 16 * MB),
+        "esp32h4" => (false, true, true, false, 2, 16 * MB),
+        "esp32p4" => (false, false, false, true, 2, 64 * MB),
+        _ => return None,
+    };
+    Some(ChipCapabilities {
+        wifi,
+        ble,
+        ieee802154,
+        usb_otg,
+        cores,
+        max_flash_bytes,
+    })
+}
+
+/// Model for the chip id an application or bootloader image header
+/// (`esp_image_header_t.chip_id`) was built for.
+pub fn from_image_chip_id(id: u16) -> Option<&'static str> {
+    match id {
+        0x0000 => Some("esp32"),
+        0x0002 => Some("esp32s2"),
+        0x0005 => Some("esp32c3"),
+        0x0009 => Some("esp32s3"),
+        0x000c => Some("esp32c2"),
+        0x000d => Some("esp32c6"),
+        0x0010 => Some("esp32h2"),
+        0x0012 => Some("esp32p4"),
+        0x0014 => Some("esp32c61"),
+        0x0017 => Some("esp32c5"),
+        _ => None,
+    }
+}
+
+/// The image header chip id for `model`, the inverse of
+/// [`from_image_chip_id`].
+pub fn image_chip_id(model: &str) -> Option<u16> {
+    let key = model_key(model);
+    (0..=0x17).find(|&id| from_image_chip_id(id) == Some(key.as_str()))
+}
+
+/// Flash offset of the second stage bootloader on `model`.
+pub fn bootloader_offset(model: &str) -> u32 {
+    match model_key(model).as_str() {
+        "esp32" | "esp32s2" => 0x1000,
+        "esp32p4" | "esp32c5" => 0x2000,
+        _ => 0x0,
+    }
+}
@@ -0,0 +1,252 @@
+//! CircuitPython boards show up as a USB drive named `CIRCUITPY` and run
+//! whatever `code.py` is on it, so a project is "flashed" by copying files.
+//! The drive is recognised by its `boot_out.txt`, whose `UUID:` line is the
+//! USB serial number the board enumerates with.
+
+use crate::models::{AppError, CircuitPyDrive};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+/// Files next to `code.py` that belong to a project.
+const PROJECT_FILES: [&str; 4] = ["code.py", "main.py", "boot.py", "settings.toml"];
+/// Directories copied whole.
+const PROJECT_DIRS: [&str; 1] = ["lib"];
+const POLL: Duration = Duration::from_millis(500);
+
+/// Version, board name, board id and UUID from a `boot_out.txt`.
+fn parse_boot_out(text: &str) -> Option<(String, String, Option<String>, Option<String>)> {
+    let mut lines = text.lines();
+    let banner = lines.next()?.trim();
+    let rest = banner.split("CircuitPython ").nth(1)?;
+    let (version, board) = match rest.split_once("; ") {
+        Some((release, board)) => (release, board),
+        None => (rest, ""),
+    };
+    let version = version.split(" on ").next().unwrap_or(version);
+    let mut board_id = None;
+    let mut uuid = None;
+    for line in lines {
+        if let Some(id) = line.trim().strip_prefix("Board ID:") {
+            board_id = Some(id.trim().to_string());
+        } else if let Some(id) = line.trim().strip_prefix("UUID:") {
+            uuid = Some(id.trim().to_string());
+        }
+    }
+    Some((version.to_string(), board.to_string(), board_id, uuid))
+}
+
+/// The CircuitPython drive mounted at `root`, if that is one.
+pub fn drive_at(root: &Path) -> Option<CircuitPyDrive> {
+    let text = std::fs::read_to_string(root.join("boot_out.txt")).ok()?;
+    let (version, board, board_id, uuid) = parse_boot_out(&text)?;
+    Some(CircuitPyDrive {
+        path: root.display().to_string(),
+        version,
+        board,
+        board_id,
+        uuid,
+    })
+}
+
+/// Where removable drives get mounted on this system.
+fn mount_points() -> Vec<PathBuf> {
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default()
+    };
+    if cfg!(windows) {
+        return (b'D'..=b'Z')
+            .map(|letter| PathBuf::from(format!("{}:\\", letter as char)))
+            .collect();
+    }
+    if cfg!(target_os = "macos") {
+        return subdirs(Path::new("/Volumes"));
+    }
+    // udisks mounts under /media/$USER or /run/media/$USER, some setups
+    // straight under /media
+    let mut points = Vec::new();
+    for base in ["/media", "/run/media"] {
+        for dir in subdirs(Path::new(base)) {
+            points.extend(subdirs(&dir));
+            points.push(dir);
+        }
+    }
+    points
+}
+
+/// Mounted CircuitPython drives.
+pub fn drives() -> Vec<CircuitPyDrive> {
+    mount_points().iter().filter_map(|p| drive_at(p)).collect()
+}
+
+/// The drive of the board with USB `serial_number` among `drives`. Drives
+/// of older releases have no UUID; one of those is taken when it is the
+/// only candidate.
+pub fn pick(drives: Vec<CircuitPyDrive>, serial_number: Option<&str>) -> Option<CircuitPyDrive> {
+    let serial = serial_number.filter(|s| !s.is_empty());
+    let (matching, rest): (Vec<_>, Vec<_>) = drives.into_iter().partition(|d| {
+        d.uuid
+            .as_deref()
+            .zip(serial)
+            .is_some_and(|(uuid, serial)| uuid.eq_ignore_ascii_case(serial))
+    });
+    if let Some(drive) = matching.into_iter().next() {
+        return Some(drive);
+    }
+    let mut candidates: Vec<CircuitPyDrive> = rest
+        .into_iter()
+        .filter(|d| serial.is_none() || d.uuid.is_none())
+        .collect();
+    match candidates.len() {
+        1 => candidates.pop(),
+        _ => None,
+    }
+}
+
+/// Project files under `project`, relative to it.
+fn project_files(project: &Path) -> Vec<PathBuf> {
+    fn walk(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') || name == "__pycache__" {
+                continue;
+            }
+            if path.is_dir() {
+                walk(root, &path, out);
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                out.push(relative.to_path_buf());
+            }
+        }
+    }
+    let mut files: Vec<PathBuf> = PROJECT_FILES
+        .iter()
+        .map(PathBuf::from)
+        .filter(|name| project.join(name).is_file())
+        .collect();
+    for dir in PROJECT_DIRS {
+        walk(project, &project.join(dir), &mut files);
+    }
+    files.sort();
+    files
+}
+
+/// Copies the project files in `project` that differ from the drive at
+/// `drive` and returns them, with `/` separators. Unchanged files are not
+/// rewritten: every write to `code.py` restarts the board.
+pub fn sync(project: &Path, drive: &Path) -> Result<Vec<String>, AppError> {
+    let files = project_files(project);
+    if files.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "{} has no code.py, main.py or lib folder",
+            project.display()
+        )));
+    }
+    let mut copied = Vec::new();
+    for relative in files {
+        let data = std::fs::read(project.join(&relative))?;
+        let target = drive.join(&relative);
+        if std::fs::read(&target).is_ok_and(|existing| existing == data) {
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Flushed right away; boards get unplugged without ejecting
+        let mut file = std::fs::File::create(&target)?;
+        std::io::Write::write_all(&mut file, &data)?;
+        file.sync_all()?;
+        copied.push(
+            relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+        );
+    }
+    if !copied.is_empty() {
+        info!("Copied {} to {}", copied.join(", "), drive.display());
+    }
+    Ok(copied)
+}
+
+/// Modification times of the project files, to notice edits.
+fn snapshot(project: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    project_files(project)
+        .into_iter()
+        .map(|relative| {
+            let modified = std::fs::metadata(project.join(&relative))
+                .and_then(|m| m.modified())
+                .ok();
+            (relative, modified)
+        })
+        .collect()
+}
+
+/// Copies a project to its drive whenever a file in it changes, until
+/// stopped or dropped.
+pub struct SyncWatch {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SyncWatch {
+    /// Syncs once right away, then on every change. `on_sync` gets the
+    /// result of each sync that copied something or failed.
+    pub fn start(
+        project: PathBuf,
+        drive: PathBuf,
+        on_sync: impl Fn(Result<Vec<String>, AppError>) + Send + 'static,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let thread = std::thread::spawn(move || {
+            let mut seen = None;
+            while !flag.load(Ordering::Relaxed) {
+                let current = snapshot(&project);
+                if seen.as_ref() != Some(&current) {
+                    match sync(&project, &drive) {
+                        Ok(copied) if copied.is_empty() => {}
+                        result => {
+                            if let Err(e) = &result {
+                                warn!("CircuitPython sync failed: {}", e);
+                            }
+                            on_sync(result);
+                        }
+                    }
+                    seen = Some(current);
+                }
+                std::thread::sleep(POLL);
+            }
+        });
+        SyncWatch {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn stop(mut self) {
+        self.halt();
+    }
+
+    fn halt(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl Drop for SyncWatch {
+    fn drop(&mut self) {
+        self.halt();
+    }
+}
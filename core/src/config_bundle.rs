@@ -0,0 +1,78 @@
+//! The app's configuration in one file, to set up other benches the same
+//! way: the settings (flash defaults, API, shortcuts, monitor triggers,
+//! pin usage) and the monitor settings remembered per board. Production
+//! mode is left out: it's entered and left on the bench itself, with its
+//! password.
+
+use crate::models::{AppError, DevicePrefs, Settings};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Bumped when a field changes meaning; older bundles still import.
+pub const CONFIG_FORMAT: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBundle {
+    pub format: u32,
+    pub settings: Settings,
+    // Keyed by USB serial number
+    #[serde(default)]
+    pub device_prefs: HashMap<String, DevicePrefs>,
+}
+
+impl ConfigBundle {
+    pub fn new(settings: Settings, device_prefs: HashMap<String, DevicePrefs>) -> Self {
+        ConfigBundle {
+            format: CONFIG_FORMAT,
+            settings,
+            device_prefs,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(settings) = value.get_mut("settings") {
+            *settings = shareable_settings(&self.settings);
+        }
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+
+    /// The settings replacing `current` on import. The production mode of
+    /// `current` is kept, and importing is refused while it's on.
+    pub fn settings_over(&self, current: &Settings) -> Result<Settings, AppError> {
+        if current.kiosk.enabled {
+            return Err(AppError::InvalidInput(
+                "Leave production mode before importing a configuration".to_string(),
+            ));
+        }
+        let mut settings = self.settings.clone();
+        settings.kiosk = current.kiosk.clone();
+        Ok(settings)
+    }
+}
+
+/// `settings` as JSON for leaving the bench, in a bundle or a bug report:
+/// without production mode, whose password hash and golden image stay on
+/// the machine.
+pub fn shareable_settings(settings: &Settings) -> serde_json::Value {
+    let mut value = serde_json::to_value(settings).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("kiosk");
+    }
+    value
+}
+
+/// Reads a bundle written by [`ConfigBundle::to_json`], refusing ones from
+/// a newer version of the app.
+pub fn parse(text: &str) -> Result<ConfigBundle, AppError> {
+    let bundle: ConfigBundle = serde_json::from_str(text)
+        .map_err(|e| AppError::Config(format!("Not a configuration file: {}", e)))?;
+    if bundle.format > CONFIG_FORMAT {
+        return Err(AppError::Config(format!(
+            "The configuration is format {}, this version reads up to {}",
+            bundle.format, CONFIG_FORMAT
+        )));
+    }
+    Ok(bundle)
+}
@@ -0,0 +1,175 @@
+use crate::esp_interaction::{self, FlasherSessions};
+use crate::models::AppError;
+use crate::monitor::Monitor;
+use crate::tasks::TaskHandle;
+use regex::Regex;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+// Received text kept for `expect`; older output is dropped once exceeded
+const MAX_BUFFER: usize = 64 * 1024;
+// How often a blocked `expect` wakes up to look for a cancel request
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+type Buffer = Arc<(Mutex<String>, Condvar)>;
+
+/// Scripted conversation with a device: send text, wait for output matching
+/// a pattern, and reset or reflash the board in between.
+///
+/// Wraps a [`Monitor`] whose output is both echoed to the caller and
+/// accumulated for `expect`, which consumes everything up to the match.
+pub struct Console {
+    port_name: String,
+    monitor: Monitor,
+    buffer: Buffer,
+    task: TaskHandle,
+}
+
+impl Console {
+    pub fn open(
+        port_name: &str,
+        baud_rate: u32,
+        task: TaskHandle,
+        echo: impl Fn(&[u8]) + Send + 'static,
+    ) -> Result<Self, AppError> {
+        let buffer: Buffer = Arc::new((Mutex::new(String::new()), Condvar::new()));
+        let monitor = Monitor::default();
+
+        let sink = buffer.clone();
+        monitor.connect(port_name, baud_rate, move |bytes| {
+            echo(bytes);
+            let (text, ready) = &*sink;
+            let mut text = text.lock().unwrap();
+            text.push_str(&String::from_utf8_lossy(bytes));
+            if text.len() > MAX_BUFFER {
+                let mut cut = text.len() - MAX_BUFFER;
+                while !text.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                text.drain(..cut);
+            }
+            ready.notify_all();
+        })?;
+
+        Ok(Console {
+            port_name: port_name.to_string(),
+            monitor,
+            buffer,
+            task,
+        })
+    }
+
+    pub fn send(&self, text: &str) -> Result<(), AppError> {
+        self.task.checkpoint()?;
+        self.monitor.send(text.as_bytes())
+    }
+
+    /// Waits until the received output matches `pattern` and returns the
+    /// matched text. Output up to the end of the match is consumed, so the
+    /// same line is never matched twice.
+    pub fn expect(&self, pattern: &str, timeout: Duration) -> Result<String, AppError> {
+        let re = Regex::new(pattern)
+            .map_err(|e| AppError::InvalidInput(format!("Invalid pattern {:?}: {}", pattern, e)))?;
+        let deadline = Instant::now() + timeout;
+
+        let (text, ready) = &*self.buffer;
+        let mut text = text.lock().unwrap();
+        loop {
+            if let Some(m) = re.find(&text) {
+                let matched = m.as_str().to_string();
+                let end = m.end();
+                text.drain(..end);
+                debug!("expect {:?} matched {:?}", pattern, matched);
+                return Ok(matched);
+            }
+
+            self.task.checkpoint()?;
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(AppError::Timeout(format!(
+                    "No output matching {:?} within {} ms",
+                    pattern,
+                    timeout.as_millis()
+                )));
+            }
+            let wait = (deadline - now).min(POLL_INTERVAL);
+            text = ready.wait_timeout(text, wait).unwrap().0;
+        }
+    }
+
+    /// Drops everything received so far.
+    pub fn clear(&self) {
+        self.buffer.0.lock().unwrap().clear();
+    }
+
+    /// Sleeps for `duration`, waking early if the task is cancelled.
+    pub fn wait(&self, duration: Duration) -> Result<(), AppError> {
+        let deadline = Instant::now() + duration;
+        loop {
+            self.task.checkpoint()?;
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            std::thread::sleep((deadline - now).min(POLL_INTERVAL));
+        }
+    }
+
+    /// Pulses EN through RTS; output from the fresh boot is kept.
+    pub fn reset(&self) -> Result<(), AppError> {
+        self.task.checkpoint()?;
+        self.clear();
+        self.monitor.with_port(
+            &self.port_name,
+            "reset",
+            |_| {},
+            || esp_interaction::hard_reset(&self.port_name),
+        )
+    }
+
+    pub fn flash(&self, path: &str, address: u32, baud_rate: u32) -> Result<String, AppError> {
+        self.task.checkpoint()?;
+        self.monitor.with_port(
+            &self.port_name,
+            "flash",
+            |_| {},
+            || {
+                esp_interaction::flash_firmware(
+                    &FlasherSessions::default(),
+                    &self.port_name,
+                    path,
+                    address,
+                    baud_rate,
+                    &self.task,
+                )
+            },
+        )
+    }
+
+    pub fn erase(&self) -> Result<String, AppError> {
+        self.task.checkpoint()?;
+        self.monitor.with_port(
+            &self.port_name,
+            "erase",
+            |_| {},
+            || {
+                esp_interaction::erase_flash(
+                    &FlasherSessions::default(),
+                    &self.port_name,
+                    &self.task,
+                )
+            },
+        )
+    }
+
+    pub fn task(&self) -> &TaskHandle {
+        &self.task
+    }
+}
+
+impl Drop for Console {
+    fn drop(&mut self) {
+        let _ = self.monitor.disconnect();
+    }
+}
@@ -0,0 +1,732 @@
+//! Crash reports from ESP-IDF core dumps, our take on `espcoredump.py
+//! info_corefile`: the crashed task and why, every task's registers and
+//! backtrace, and the memory the dump holds. The dump is an ELF core file
+//! (`CONFIG_ESP_COREDUMP_DATA_FORMAT_ELF`, the default) printed in base64
+//! over UART or left in the `coredump` partition; the app ELF names the
+//! code addresses.
+
+use crate::models::AppError;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::Path;
+
+const START_MARKER: &str = "CORE DUMP START";
+const END_MARKER: &str = "CORE DUMP END";
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+// The dump's header before the ELF, at most
+const MAX_HEADER_LEN: usize = 64;
+const CHECKSUM_LEN: usize = 4;
+const ERASED: u32 = 0xffff_ffff;
+const EM_XTENSA: u16 = 94;
+const EM_RISCV: u16 = 243;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PROGRAM_HEADER_LEN: usize = 32;
+const SECTION_HEADER_LEN: usize = 40;
+const SYMBOL_LEN: usize = 16;
+const SHT_SYMTAB: u32 = 2;
+const STT_FUNC: u8 = 2;
+const NT_PRSTATUS: u32 = 1;
+// elf_prstatus up to the registers; `pr_pid` is the task's TCB
+const PRSTATUS_PID: usize = 24;
+const PRSTATUS_REGS: usize = 72;
+// FreeRTOS TCB as ESP-IDF builds it
+const TCB_NAME: u32 = 52;
+const TASK_NAME_LEN: usize = 16;
+// Xtensa gregset: pc, ps, lbeg, lend, lcount, sar, windowstart,
+// windowbase, 56 reserved words, then the address registers
+const XTENSA_AR: usize = 64;
+const MAX_FRAMES: usize = 64;
+// Return addresses guessed off a RISC-V stack
+const MAX_GUESSED: usize = 16;
+
+const XTENSA_REGISTERS: [&str; 8] = [
+    "pc",
+    "ps",
+    "lbeg",
+    "lend",
+    "lcount",
+    "sar",
+    "windowstart",
+    "windowbase",
+];
+const RISCV_REGISTERS: [&str; 32] = [
+    "pc", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4", "t5",
+    "t6",
+];
+
+/// A register and its value, with what the value means when it is a code.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreRegister {
+    pub name: String,
+    pub value: u32,
+    pub meaning: Option<String>,
+}
+
+/// A backtrace entry: the code address and the function it is in.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreFrame {
+    pub address: u32,
+    // `name+0x1c`
+    pub function: Option<String>,
+    // Picked off the stack rather than unwound, so maybe stale
+    pub guessed: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreTask {
+    // Address of the task's TCB, its handle
+    pub handle: u32,
+    pub name: String,
+    pub crashed: bool,
+    pub registers: Vec<CoreRegister>,
+    pub frames: Vec<CoreFrame>,
+    // From the stack pointer to the end of the stack
+    pub stack_used: Option<u32>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryRegion {
+    pub address: u32,
+    pub size: u32,
+}
+
+/// What a core dump says about a crash, the crashed task first.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreDumpReport {
+    // `xtensa` or `riscv`
+    pub arch: String,
+    pub panic_reason: Option<String>,
+    // Whether the app ELF is the build that crashed, if the dump says
+    pub elf_matches: Option<bool>,
+    // Exception registers saved apart from the tasks' (Xtensa)
+    pub exception: Vec<CoreRegister>,
+    pub tasks: Vec<CoreTask>,
+    pub regions: Vec<MemoryRegion>,
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// The text up to the first NUL.
+fn c_string(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+fn not_a_dump(what: &str) -> AppError {
+    AppError::InvalidInput(format!("Not a core dump: {}", what))
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for byte in text
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace() && *b != b'=')
+    {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = (bits << 6) | u32::from(value);
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            data.push((bits >> count) as u8);
+        }
+    }
+    Some(data)
+}
+
+/// The core ELF in `data`: output the board printed over UART, log lines
+/// around the dump included, the contents of the `coredump` partition, or
+/// the ELF itself.
+pub fn extract(data: &[u8]) -> Result<Vec<u8>, AppError> {
+    if data.starts_with(ELF_MAGIC) {
+        return Ok(data.to_vec());
+    }
+    let text = String::from_utf8_lossy(data);
+    if let Some(start) = text.find(START_MARKER) {
+        let after = &text[start..];
+        // The rest of the marker line
+        let body = after.find('\n').map_or("", |i| &after[i + 1..]);
+        let Some(end) = body.find(END_MARKER) else {
+            return Err(not_a_dump("the output stops before the end of the dump"));
+        };
+        // Up to the line with the end marker
+        let body = body[..end].rfind('\n').map_or("", |i| &body[..i]);
+        let dump = base64_decode(body).ok_or_else(|| not_a_dump("bad base64 in the output"))?;
+        return from_partition(&dump);
+    }
+    if data
+        .iter()
+        .all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+    {
+        if let Some(dump) = base64_decode(&text) {
+            return from_partition(&dump);
+        }
+    }
+    from_partition(data)
+}
+
+/// The ELF behind the dump's header: its length, the format version and,
+/// by version, task counts and the chip revision.
+fn from_partition(data: &[u8]) -> Result<Vec<u8>, AppError> {
+    let len = match u32_at(data, 0) {
+        None => return Err(not_a_dump("too short")),
+        Some(ERASED) => {
+            return Err(AppError::InvalidInput(
+                "The coredump partition is empty: nothing crashed since it was erased".to_string(),
+            ))
+        }
+        Some(len) => (len as usize).min(data.len()),
+    };
+    let header = &data[..MAX_HEADER_LEN.min(len)];
+    let Some(start) = header.windows(ELF_MAGIC.len()).position(|w| w == ELF_MAGIC) else {
+        return Err(AppError::InvalidInput(
+            "Only ELF core dumps can be read; build with CONFIG_ESP_COREDUMP_DATA_FORMAT_ELF"
+                .to_string(),
+        ));
+    };
+    // The length counts the checksum trailing the ELF
+    Ok(data[start..len.saturating_sub(CHECKSUM_LEN).max(start)].to_vec())
+}
+
+struct Note<'a> {
+    name: String,
+    kind: u32,
+    desc: &'a [u8],
+}
+
+fn notes(mut data: &[u8]) -> Vec<Note<'_>> {
+    let align = |len: usize| (len + 3) & !3;
+    let mut found = Vec::new();
+    while let (Some(name_len), Some(desc_len), Some(kind)) =
+        (u32_at(data, 0), u32_at(data, 4), u32_at(data, 8))
+    {
+        let (name_len, desc_len) = (name_len as usize, desc_len as usize);
+        let desc_at = 12 + align(name_len);
+        let (Some(name), Some(desc)) = (
+            data.get(12..12 + name_len),
+            data.get(desc_at..desc_at + desc_len),
+        ) else {
+            break;
+        };
+        found.push(Note {
+            name: c_string(name),
+            kind,
+            desc,
+        });
+        data = data.get(desc_at + align(desc_len)..).unwrap_or_default();
+    }
+    found
+}
+
+/// The memory and notes of a core ELF.
+struct Core<'a> {
+    machine: u16,
+    segments: Vec<(u32, &'a [u8])>,
+    notes: Vec<Note<'a>>,
+}
+
+impl<'a> Core<'a> {
+    fn parse(elf: &'a [u8]) -> Result<Self, AppError> {
+        if !elf.starts_with(ELF_MAGIC) || elf.get(4..6) != Some(&[1, 1]) {
+            return Err(not_a_dump("not a 32-bit little-endian ELF"));
+        }
+        let bad = || not_a_dump("bad program header table");
+        let machine = u16_at(elf, 18).ok_or_else(bad)?;
+        let table = u32_at(elf, 28).ok_or_else(bad)? as usize;
+        let entry_len = u16_at(elf, 42).ok_or_else(bad)? as usize;
+        let count = u16_at(elf, 44).ok_or_else(bad)? as usize;
+        if entry_len < PROGRAM_HEADER_LEN {
+            return Err(bad());
+        }
+        let mut core = Core {
+            machine,
+            segments: Vec::new(),
+            notes: Vec::new(),
+        };
+        for i in 0..count {
+            let header = elf
+                .get(table + i * entry_len..table + (i + 1) * entry_len)
+                .ok_or_else(bad)?;
+            let (Some(kind), Some(offset), Some(address), Some(size)) = (
+                u32_at(header, 0),
+                u32_at(header, 4),
+                u32_at(header, 8),
+                u32_at(header, 16),
+            ) else {
+                return Err(bad());
+            };
+            let contents = elf
+                .get(offset as usize..offset as usize + size as usize)
+                .ok_or_else(|| not_a_dump("cut short"))?;
+            match kind {
+                PT_LOAD => core.segments.push((address, contents)),
+                PT_NOTE => core.notes.extend(notes(contents)),
+                _ => {}
+            }
+        }
+        Ok(core)
+    }
+
+    fn memory(&self, address: u32, len: usize) -> Option<&'a [u8]> {
+        self.segments.iter().find_map(|&(start, data)| {
+            let at = address.checked_sub(start)? as usize;
+            data.get(at..at + len)
+        })
+    }
+
+    fn word(&self, address: u32) -> Option<u32> {
+        u32_at(self.memory(address, 4)?, 0)
+    }
+
+    /// End of the segment holding `address`.
+    fn segment_end(&self, address: u32) -> Option<u32> {
+        self.segments.iter().find_map(|&(start, data)| {
+            let end = start.saturating_add(data.len() as u32);
+            (start..end).contains(&address).then_some(end)
+        })
+    }
+
+    fn note(&self, name: &str) -> Option<&Note<'a>> {
+        self.notes.iter().find(|note| note.name == name)
+    }
+}
+
+/// Function symbols of the app ELF, by address.
+struct Symbols(Vec<(u32, u32, String)>);
+
+impl Symbols {
+    fn parse(elf: &[u8]) -> Result<Self, AppError> {
+        if !elf.starts_with(ELF_MAGIC) || elf.get(4..6) != Some(&[1, 1]) {
+            return Err(AppError::InvalidInput(
+                "The app ELF is not a 32-bit little-endian ELF".to_string(),
+            ));
+        }
+        let table = u32_at(elf, 32).unwrap_or_default() as usize;
+        let entry_len = u16_at(elf, 46).unwrap_or_default() as usize;
+        let count = u16_at(elf, 48).unwrap_or_default() as usize;
+        let header = |i: usize| elf.get(table + i * entry_len..table + (i + 1) * entry_len);
+        let contents = |header: &[u8]| {
+            let offset = u32_at(header, 16)? as usize;
+            let size = u32_at(header, 20)? as usize;
+            elf.get(offset..offset + size)
+        };
+        let mut functions = Vec::new();
+        if entry_len >= SECTION_HEADER_LEN {
+            for section in (0..count).filter_map(header) {
+                if u32_at(section, 4) != Some(SHT_SYMTAB) {
+                    continue;
+                }
+                let (Some(symbols), Some(strings)) = (
+                    contents(section),
+                    u32_at(section, 24)
+                        .and_then(|link| header(link as usize))
+                        .and_then(contents),
+                ) else {
+                    continue;
+                };
+                for symbol in symbols.chunks_exact(SYMBOL_LEN) {
+                    let (Some(name), Some(value), Some(size)) =
+                        (u32_at(symbol, 0), u32_at(symbol, 4), u32_at(symbol, 8))
+                    else {
+                        continue;
+                    };
+                    if symbol[12] & 0xf != STT_FUNC || value == 0 {
+                        continue;
+                    }
+                    let name = strings.get(name as usize..).map(c_string);
+                    functions.push((value, size, name.unwrap_or_default()));
+                }
+            }
+        }
+        if functions.is_empty() {
+            return Err(AppError::InvalidInput(
+                "The app ELF has no symbols; was it stripped?".to_string(),
+            ));
+        }
+        functions.sort();
+        Ok(Symbols(functions))
+    }
+
+    /// `name+0x1c` for the function holding `address`.
+    fn name(&self, address: u32) -> Option<String> {
+        let i = self.0.partition_point(|(start, _, _)| *start <= address);
+        let (start, size, name) = self.0.get(i.checked_sub(1)?)?;
+        let offset = address - start;
+        if offset >= (*size).max(1) {
+            return None;
+        }
+        Some(if offset == 0 {
+            name.clone()
+        } else {
+            format!("{}+{:#x}", name, offset)
+        })
+    }
+}
+
+/// An Xtensa return address: the window increment in the top bits, then
+/// back into the call instruction, as ESP-IDF's panic handler prints it.
+fn xtensa_return(pc: u32) -> u32 {
+    let pc = if pc & 0x8000_0000 != 0 {
+        (pc & 0x3fff_ffff) | 0x4000_0000
+    } else {
+        pc
+    };
+    pc.wrapping_sub(3)
+}
+
+fn xtensa_cause(code: u32) -> Option<&'static str> {
+    Some(match code {
+        0 => "IllegalInstruction",
+        1 => "Syscall",
+        2 => "InstructionFetchError",
+        3 => "LoadStoreError",
+        4 => "Level1Interrupt",
+        5 => "Alloca",
+        6 => "IntegerDivideByZero",
+        8 => "Privileged",
+        9 => "LoadStoreAlignment",
+        12 => "InstrPIFDataError",
+        13 => "LoadStorePIFDataError",
+        14 => "InstrPIFAddrError",
+        15 => "LoadStorePIFAddrError",
+        16 => "InstTLBMiss",
+        17 => "InstTLBMultiHit",
+        18 => "InstFetchPrivilege",
+        20 => "InstFetchProhibited",
+        24 => "LoadStoreTLBMiss",
+        25 => "LoadStoreTLBMultihit",
+        26 => "LoadStorePrivilege",
+        28 => "LoadProhibited",
+        29 => "StoreProhibited",
+        _ => return None,
+    })
+}
+
+/// Name of an Xtensa special register saved with the exception.
+fn xtensa_special(index: u32) -> String {
+    match index {
+        177..=183 => format!("epc{}", index - 176),
+        194..=199 => format!("eps{}", index - 192),
+        232 => "exccause".to_string(),
+        238 => "excvaddr".to_string(),
+        _ => format!("sr{}", index),
+    }
+}
+
+fn frame(symbols: &Symbols, address: u32, lookup: u32, guessed: bool) -> CoreFrame {
+    CoreFrame {
+        address,
+        function: symbols.name(lookup),
+        guessed,
+    }
+}
+
+/// Unwinds an Xtensa task with the windowed ABI: each function's caller
+/// saved its return address and stack pointer just below its own stack
+/// pointer.
+fn xtensa_frames(core: &Core, symbols: &Symbols, registers: &[u32]) -> Vec<CoreFrame> {
+    let [pc, a0, a1] = [0, XTENSA_AR, XTENSA_AR + 1].map(|i| registers.get(i).copied());
+    let (Some(pc), Some(mut next), Some(mut sp)) = (pc, a0, a1) else {
+        return Vec::new();
+    };
+    let mut frames = vec![frame(symbols, pc, pc, false)];
+    while next != 0 && frames.len() < MAX_FRAMES {
+        let pc = xtensa_return(next);
+        frames.push(frame(symbols, pc, pc, false));
+        let (Some(caller_next), Some(caller_sp)) = (
+            core.word(sp.wrapping_sub(16)),
+            core.word(sp.wrapping_sub(12)),
+        ) else {
+            break;
+        };
+        next = caller_next;
+        sp = caller_sp;
+    }
+    frames
+}
+
+/// A RISC-V task's PC and return address; unwinding further takes the
+/// ELF's debug info, so return addresses found on the stack follow,
+/// marked as guesses.
+fn riscv_frames(core: &Core, symbols: &Symbols, registers: &[u32]) -> Vec<CoreFrame> {
+    let (Some(&pc), Some(&ra), Some(&sp)) = (registers.first(), registers.get(1), registers.get(2))
+    else {
+        return Vec::new();
+    };
+    let mut frames = vec![frame(symbols, pc, pc, false)];
+    if ra != 0 {
+        frames.push(frame(symbols, ra, ra.wrapping_sub(1), false));
+    }
+    let end = core.segment_end(sp).unwrap_or(sp);
+    let mut guessed = 0;
+    for address in (sp..end.saturating_sub(3)).step_by(4) {
+        if guessed == MAX_GUESSED {
+            break;
+        }
+        let Some(word) = core.word(address) else {
+            break;
+        };
+        if word % 2 != 0 || word == ra || symbols.name(word.wrapping_sub(1)).is_none() {
+            continue;
+        }
+        frames.push(frame(symbols, word, word.wrapping_sub(1), true));
+        guessed += 1;
+    }
+    frames
+}
+
+/// Reads the crash out of `core`, the ELF [`extract`] gives, naming code
+/// with the symbols of `elf`, the app that crashed.
+pub fn analyze(core: &[u8], elf: &[u8]) -> Result<CoreDumpReport, AppError> {
+    let core = Core::parse(core)?;
+    let symbols = Symbols::parse(elf)?;
+    let arch = match core.machine {
+        EM_XTENSA => "xtensa",
+        EM_RISCV => "riscv",
+        other => return Err(not_a_dump(&format!("unknown machine {}", other))),
+    };
+
+    let panic_reason = core
+        .note("ESP_PANIC_DETAILS")
+        .map(|note| c_string(note.desc).trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    // ESP-IDF keeps the ELF's SHA-256 in hex, maybe cut short
+    let elf_matches = core.note("ESP_CORE_DUMP_INFO").and_then(|note| {
+        let stored = c_string(note.desc.get(4..)?).to_ascii_lowercase();
+        let stored: String = stored.chars().take_while(char::is_ascii_hexdigit).collect();
+        if stored.is_empty() {
+            return None;
+        }
+        let digest: String = Sha256::digest(elf)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Some(digest.starts_with(&stored))
+    });
+
+    let extra = core.note("EXTRA_INFO").map(|note| note.desc);
+    let crashed = extra.and_then(|desc| u32_at(desc, 0));
+    let mut exception = Vec::new();
+    if let (Some(desc), EM_XTENSA) = (extra, core.machine) {
+        for pair in desc.get(4..).unwrap_or_default().chunks_exact(8) {
+            let (Some(index), Some(value)) = (u32_at(pair, 0), u32_at(pair, 4)) else {
+                continue;
+            };
+            if index == 0 {
+                continue;
+            }
+            let name = xtensa_special(index);
+            let meaning = (name == "exccause")
+                .then(|| xtensa_cause(value).map(str::to_string))
+                .flatten();
+            exception.push(CoreRegister {
+                name,
+                value,
+                meaning,
+            });
+        }
+    }
+
+    let mut tasks = Vec::new();
+    for note in &core.notes {
+        if note.name != "CORE" || note.kind != NT_PRSTATUS {
+            continue;
+        }
+        let Some(handle) = u32_at(note.desc, PRSTATUS_PID) else {
+            continue;
+        };
+        let values: Vec<u32> = note
+            .desc
+            .get(PRSTATUS_REGS..)
+            .unwrap_or_default()
+            .chunks_exact(4)
+            .filter_map(|word| u32_at(word, 0))
+            .collect();
+        let named: Vec<(String, usize)> = match core.machine {
+            EM_XTENSA => XTENSA_REGISTERS
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.to_string(), i))
+                .chain((0..16).map(|i| (format!("a{}", i), XTENSA_AR + i)))
+                .collect(),
+            _ => RISCV_REGISTERS
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.to_string(), i))
+                .collect(),
+        };
+        let registers = named
+            .into_iter()
+            .filter_map(|(name, i)| {
+                Some(CoreRegister {
+                    name,
+                    value: *values.get(i)?,
+                    meaning: None,
+                })
+            })
+            .collect();
+        let (frames, sp) = match core.machine {
+            EM_XTENSA => (
+                xtensa_frames(&core, &symbols, &values),
+                values.get(XTENSA_AR + 1),
+            ),
+            _ => (riscv_frames(&core, &symbols, &values), values.get(2)),
+        };
+        tasks.push(CoreTask {
+            handle,
+            name: core
+                .memory(handle.wrapping_add(TCB_NAME), TASK_NAME_LEN)
+                .map(c_string)
+                .unwrap_or_default(),
+            crashed: Some(handle) == crashed,
+            registers,
+            frames,
+            stack_used: sp.and_then(|&sp| Some(core.segment_end(sp)? - sp)),
+        });
+    }
+    if tasks.is_empty() {
+        return Err(not_a_dump("no tasks in it"));
+    }
+    // Without the extra info the first task dumped is the one that crashed
+    if !tasks.iter().any(|task| task.crashed) {
+        tasks[0].crashed = true;
+    }
+    tasks.sort_by_key(|task| !task.crashed);
+
+    Ok(CoreDumpReport {
+        arch: arch.to_string(),
+        panic_reason,
+        elf_matches,
+        exception,
+        tasks,
+        regions: core
+            .segments
+            .iter()
+            .map(|&(address, data)| MemoryRegion {
+                address,
+                size: data.len() as u32,
+            })
+            .collect(),
+    })
+}
+
+/// [`analyze`] on a dump in any form [`extract`] reads and the app ELF at
+/// `elf`.
+pub fn report(dump: &[u8], elf: &Path) -> Result<CoreDumpReport, AppError> {
+    let core = extract(dump)?;
+    let elf = std::fs::read(elf)?;
+    analyze(&core, &elf)
+}
+
+fn write_frames(out: &mut String, frames: &[CoreFrame]) {
+    for (i, frame) in frames.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "#{:<2} {:#010x} in {}{}",
+            i,
+            frame.address,
+            frame.function.as_deref().unwrap_or("??"),
+            if frame.guessed {
+                " (from the stack)"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+fn write_registers(out: &mut String, registers: &[CoreRegister]) {
+    for register in registers {
+        let _ = write!(out, "{:<14} {:#010x}", register.name, register.value);
+        if let Some(meaning) = &register.meaning {
+            let _ = write!(out, " ({})", meaning);
+        }
+        out.push('\n');
+    }
+}
+
+impl CoreDumpReport {
+    /// The report as text, laid out like `espcoredump.py info_corefile`.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let heading = |out: &mut String, title: &str| {
+            let _ = writeln!(out, "{:=^64}", format!(" {} ", title));
+        };
+        heading(&mut out, &format!("CORE DUMP ({})", self.arch));
+        if let Some(task) = self.tasks.iter().find(|task| task.crashed) {
+            let _ = writeln!(
+                out,
+                "Crashed task: '{}' (TCB {:#010x})",
+                task.name, task.handle
+            );
+        }
+        if let Some(reason) = &self.panic_reason {
+            let _ = writeln!(out, "Panic reason: {}", reason);
+        }
+        if self.elf_matches == Some(false) {
+            out.push_str("WARNING: the app ELF is not the build that crashed\n");
+        }
+
+        if let Some(task) = self.tasks.iter().find(|task| task.crashed) {
+            heading(&mut out, "CRASHED TASK REGISTERS");
+            write_registers(&mut out, &self.exception);
+            write_registers(&mut out, &task.registers);
+            heading(&mut out, "CRASHED TASK STACK");
+            write_frames(&mut out, &task.frames);
+        }
+
+        heading(&mut out, "TASKS");
+        for task in &self.tasks {
+            let top = task.frames.first();
+            let _ = write!(
+                out,
+                "{} {:<16} {:#010x}  {:#010x} in {}",
+                if task.crashed { '*' } else { ' ' },
+                task.name,
+                task.handle,
+                top.map_or(0, |frame| frame.address),
+                top.and_then(|frame| frame.function.as_deref())
+                    .unwrap_or("??")
+            );
+            if let Some(used) = task.stack_used {
+                let _ = write!(out, ", {} bytes of stack used", used);
+            }
+            out.push('\n');
+        }
+        for task in self.tasks.iter().filter(|task| !task.crashed) {
+            heading(
+                &mut out,
+                &format!("TASK '{}' (TCB {:#010x})", task.name, task.handle),
+            );
+            write_frames(&mut out, &task.frames);
+        }
+
+        heading(&mut out, "MEMORY REGIONS");
+        for region in &self.regions {
+            let _ = writeln!(out, "{:#010x}  {:#8x} bytes", region.address, region.size);
+        }
+        heading(&mut out, "END OF CORE DUMP");
+        out
+    }
+}
@@ -0,0 +1,592 @@
+//! defmt log decoding for esp-hal and embassy apps. defmt keeps format
+//! strings out of the firmware: they live in the `.defmt` section of the
+//! app ELF and the board sends binary frames referring to them, which
+//! `esp-println` puts on the serial port behind an `FF 00` marker. The
+//! frames are rzCOBS encoded, defmt's wire format 4.
+
+use crate::models::AppError;
+use defmt_parser::{DisplayHint, Fragment, Parameter, ParserMode, TimePrecision, Type};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const SECTION_HEADER_LEN: usize = 40;
+const SYMBOL_LEN: usize = 16;
+const SHT_SYMTAB: u32 = 2;
+const ENCODING_SYMBOL: &str = "_defmt_encoding_ = ";
+// Start of a frame in esp-println's output; never valid UTF-8
+const MARKER: u8 = 0xFF;
+// Longer runs without a frame end are noise, not a frame
+const MAX_FRAME: usize = 16 * 1024;
+// Nesting of `Format` values, against loops in a corrupt frame
+const MAX_DEPTH: usize = 32;
+
+/// Name of a symbol in `.defmt`, which is JSON.
+#[derive(Deserialize)]
+struct Symbol {
+    tag: String,
+    data: String,
+}
+
+/// A string of the table: a log statement (`defmt_info`, ...), a `Format`
+/// impl (`defmt_derived`, `defmt_write`, ...) or an interned string.
+struct Entry {
+    tag: String,
+    data: String,
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn malformed(what: &str) -> AppError {
+    AppError::InvalidInput(format!("Malformed ELF: {}", what))
+}
+
+/// The NUL-terminated string at `offset` of a string table.
+fn name_at(strings: &[u8], offset: u32) -> Option<&str> {
+    let rest = strings.get(offset as usize..)?;
+    let end = rest.iter().position(|&b| b == 0)?;
+    std::str::from_utf8(&rest[..end]).ok()
+}
+
+/// Undoes rzCOBS on a frame without its `00` terminator. The encoder pads
+/// the last group with zeros, which decoding ignores.
+pub fn rzcobs_decode(frame: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(frame.len() * 2);
+    // Groups are read back to front
+    let mut bytes = frame.iter().rev().copied();
+    while let Some(code) = bytes.next() {
+        match code {
+            0 => return None,
+            0x01..=0x7F => {
+                for bit in (0..7).rev() {
+                    if code & (1 << bit) == 0 {
+                        data.push(bytes.next()?);
+                    } else {
+                        data.push(0);
+                    }
+                }
+            }
+            0x80..=0xFE => {
+                data.push(0);
+                for _ in 0..(code & 0x7F) + 7 {
+                    data.push(bytes.next()?);
+                }
+            }
+            0xFF => {
+                for _ in 0..134 {
+                    data.push(bytes.next()?);
+                }
+            }
+        }
+    }
+    data.reverse();
+    Some(data)
+}
+
+/// The arguments of a frame, read in order.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(taken)
+    }
+
+    /// A little-endian unsigned integer of `len` bytes.
+    fn uint(&mut self, len: usize) -> Option<u128> {
+        let bytes = self.take(len)?;
+        Some(
+            bytes
+                .iter()
+                .rev()
+                .fold(0u128, |value, &b| (value << 8) | u128::from(b)),
+        )
+    }
+
+    fn index(&mut self) -> Option<u16> {
+        self.uint(2).map(|index| index as u16)
+    }
+
+    /// `usize` is sent as 32 bits whatever the target.
+    fn len(&mut self) -> Option<usize> {
+        self.uint(4).map(|len| len as usize)
+    }
+}
+
+/// An argument of a format string, decoded.
+enum Arg {
+    Unsigned(u128),
+    Signed(i128),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Bytes(Vec<u8>),
+    Str(String),
+    // Rendered from its own format string
+    Formatted(String),
+}
+
+fn integer(out: &mut String, value: u128, negative: bool, hint: Option<&DisplayHint>) {
+    let sign = if negative { "-" } else { "" };
+    let _ = match hint {
+        // As in Rust, the width counts the `0x`
+        Some(DisplayHint::Hexadecimal {
+            alternate,
+            uppercase,
+            zero_pad,
+        }) => match (alternate, uppercase) {
+            (false, false) => write!(out, "{}{:0pad$x}", sign, value, pad = *zero_pad),
+            (false, true) => write!(out, "{}{:0pad$X}", sign, value, pad = *zero_pad),
+            (true, false) => write!(out, "{}{:#0pad$x}", sign, value, pad = *zero_pad),
+            (true, true) => write!(out, "{}{:#0pad$X}", sign, value, pad = *zero_pad),
+        },
+        Some(DisplayHint::Binary {
+            alternate,
+            zero_pad,
+        }) => {
+            if *alternate {
+                write!(out, "{}{:#0pad$b}", sign, value, pad = *zero_pad)
+            } else {
+                write!(out, "{}{:0pad$b}", sign, value, pad = *zero_pad)
+            }
+        }
+        Some(DisplayHint::Octal {
+            alternate,
+            zero_pad,
+        }) => {
+            if *alternate {
+                write!(out, "{}{:#0pad$o}", sign, value, pad = *zero_pad)
+            } else {
+                write!(out, "{}{:0pad$o}", sign, value, pad = *zero_pad)
+            }
+        }
+        // Ticks since boot, shown as seconds
+        Some(DisplayHint::Seconds(precision) | DisplayHint::Time(precision)) => match precision {
+            TimePrecision::Micros => {
+                write!(
+                    out,
+                    "{}{}.{:06}",
+                    sign,
+                    value / 1_000_000,
+                    value % 1_000_000
+                )
+            }
+            TimePrecision::Millis => {
+                write!(out, "{}{}.{:03}", sign, value / 1_000, value % 1_000)
+            }
+            TimePrecision::Seconds => write!(out, "{}{}", sign, value),
+        },
+        Some(DisplayHint::NoHint { zero_pad }) => {
+            write!(out, "{}{:0pad$}", sign, value, pad = *zero_pad)
+        }
+        _ => write!(out, "{}{}", sign, value),
+    };
+}
+
+fn show(out: &mut String, arg: &Arg, parameter: &Parameter) {
+    let hint = parameter.hint.as_ref();
+    match (arg, &parameter.ty) {
+        (Arg::Unsigned(value), Type::BitField(range)) => {
+            let width = u32::from(range.end.saturating_sub(range.start));
+            let mask = 1u128.checked_shl(width).map_or(u128::MAX, |bit| bit - 1);
+            integer(out, (value >> range.start) & mask, false, hint);
+        }
+        (Arg::Unsigned(value), _) => integer(out, *value, false, hint),
+        (Arg::Signed(value), _) => integer(out, value.unsigned_abs(), *value < 0, hint),
+        (Arg::Float(value), _) => {
+            let _ = write!(out, "{}", value);
+        }
+        (Arg::Bool(value), _) => {
+            let _ = write!(out, "{}", value);
+        }
+        (Arg::Char(value), _) => match hint {
+            Some(DisplayHint::Debug) => {
+                let _ = write!(out, "{:?}", value);
+            }
+            _ => out.push(*value),
+        },
+        (Arg::Bytes(bytes), _) => match hint {
+            Some(DisplayHint::Ascii) => {
+                out.push_str("b\"");
+                for &b in bytes {
+                    out.extend(std::ascii::escape_default(b).map(char::from));
+                }
+                out.push('"');
+            }
+            _ => {
+                out.push('[');
+                for (i, &b) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    integer(out, u128::from(b), false, hint);
+                }
+                out.push(']');
+            }
+        },
+        (Arg::Str(text), _) => match hint {
+            Some(DisplayHint::Debug) => {
+                let _ = write!(out, "{:?}", text);
+            }
+            _ => out.push_str(text),
+        },
+        (Arg::Formatted(text), _) => out.push_str(text),
+    }
+}
+
+/// The defmt strings of an app by the index its frames refer to them with.
+pub struct Table {
+    entries: HashMap<u16, Entry>,
+    // Format of the timestamp in front of each frame, if the app has one
+    timestamp: Option<String>,
+}
+
+impl Table {
+    /// Reads the table out of the symbols of a 32-bit little-endian ELF.
+    pub fn parse(elf: &[u8]) -> Result<Table, AppError> {
+        if elf.len() < 52 || !elf.starts_with(ELF_MAGIC) {
+            return Err(AppError::InvalidInput("Not an ELF file".to_string()));
+        }
+        if elf[4] != 1 || elf[5] != 1 {
+            return Err(malformed("not a 32-bit little-endian ELF"));
+        }
+        let bad_table = || malformed("bad section header table");
+        let table = u32_at(elf, 32).ok_or_else(bad_table)? as usize;
+        let entry_len = u16_at(elf, 46).ok_or_else(bad_table)? as usize;
+        let count = u16_at(elf, 48).ok_or_else(bad_table)? as usize;
+        let names_index = u16_at(elf, 50).ok_or_else(bad_table)? as usize;
+        if entry_len < SECTION_HEADER_LEN {
+            return Err(bad_table());
+        }
+        let header = |i: usize| elf.get(table + i * entry_len..table + (i + 1) * entry_len);
+        let contents = |header: &[u8]| {
+            let offset = u32_at(header, 16)? as usize;
+            let size = u32_at(header, 20)? as usize;
+            elf.get(offset..offset + size)
+        };
+        let names = header(names_index)
+            .and_then(contents)
+            .ok_or_else(bad_table)?;
+
+        let mut defmt = None;
+        let mut symtab = None;
+        for i in 0..count {
+            let section = header(i).ok_or_else(bad_table)?;
+            let name = u32_at(section, 0).and_then(|offset| name_at(names, offset));
+            if name == Some(".defmt") {
+                defmt = Some(i);
+            }
+            if u32_at(section, 4) == Some(SHT_SYMTAB) {
+                symtab = Some(section);
+            }
+        }
+        let defmt = defmt.ok_or_else(|| {
+            AppError::InvalidInput(
+                "The ELF has no defmt table; is the app using defmt?".to_string(),
+            )
+        })?;
+        let symtab = symtab.ok_or_else(|| malformed("no symbol table; was it stripped?"))?;
+        let symbols = contents(symtab).ok_or_else(bad_table)?;
+        let strings = u32_at(symtab, 24)
+            .and_then(|link| header(link as usize))
+            .and_then(contents)
+            .ok_or_else(bad_table)?;
+
+        let mut entries = HashMap::new();
+        let mut timestamp = None;
+        for symbol in symbols.chunks_exact(SYMBOL_LEN) {
+            let Some(name) = u32_at(symbol, 0).and_then(|offset| name_at(strings, offset)) else {
+                continue;
+            };
+            if let Some(encoding) = name.strip_prefix(ENCODING_SYMBOL) {
+                if encoding != "rzcobs" {
+                    return Err(AppError::InvalidInput(format!(
+                        "The app uses the {} defmt encoding; only rzcobs can be read off a serial port",
+                        encoding
+                    )));
+                }
+                continue;
+            }
+            if u16_at(symbol, 14) != Some(defmt as u16) {
+                continue;
+            }
+            // Version markers and the like aren't JSON
+            let Ok(Symbol { tag, data }) = serde_json::from_str(name) else {
+                continue;
+            };
+            let Some(index) = u32_at(symbol, 4) else {
+                continue;
+            };
+            if tag == "defmt_timestamp" {
+                timestamp = Some(data);
+            } else {
+                entries.insert(index as u16, Entry { tag, data });
+            }
+        }
+        Ok(Table { entries, timestamp })
+    }
+
+    pub fn load(path: &Path) -> Result<Table, AppError> {
+        let elf = std::fs::read(path).map_err(|e| AppError::Io(e.to_string()))?;
+        Table::parse(&elf)
+    }
+
+    /// The log line a decoded frame stands for: timestamp, level and
+    /// message. `None` when the frame doesn't fit the table, e.g. the ELF
+    /// isn't the one running.
+    pub fn decode(&self, frame: &[u8]) -> Option<String> {
+        let mut reader = Reader { data: frame };
+        let entry = self.entries.get(&reader.index()?)?;
+        let level = match entry.tag.as_str() {
+            "defmt_trace" => Some("TRACE"),
+            "defmt_debug" => Some("DEBUG"),
+            "defmt_info" => Some("INFO"),
+            "defmt_warn" => Some("WARN"),
+            "defmt_error" => Some("ERROR"),
+            "defmt_println" => None,
+            _ => return None,
+        };
+        let mut line = String::new();
+        if let Some(timestamp) = &self.timestamp {
+            line.push_str(&self.format(timestamp, &mut reader, 0)?);
+            line.push(' ');
+        }
+        if let Some(level) = level {
+            let _ = write!(line, "[{:<5}] ", level);
+        }
+        line.push_str(&self.format(&entry.data, &mut reader, 0)?);
+        Some(line)
+    }
+
+    /// Renders `format` with its arguments taken from `reader`.
+    fn format(&self, format: &str, reader: &mut Reader, depth: usize) -> Option<String> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let fragments = defmt_parser::parse(format, ParserMode::ForwardsCompatible).ok()?;
+        let parameters: Vec<&Parameter> = fragments
+            .iter()
+            .filter_map(|fragment| match fragment {
+                Fragment::Parameter(parameter) => Some(parameter),
+                Fragment::Literal(_) => None,
+            })
+            .collect();
+        let count = parameters.iter().map(|p| p.index + 1).max().unwrap_or(0);
+        let mut args = Vec::with_capacity(count);
+        for index in 0..count {
+            let uses: Vec<&Parameter> = parameters
+                .iter()
+                .copied()
+                .filter(|p| p.index == index)
+                .collect();
+            let arg = match &uses.first()?.ty {
+                // Bitfields of one argument share a value, sent cut down to
+                // the bytes they cover
+                Type::BitField(_) => {
+                    let (low, high) = defmt_parser::get_max_bitfield_range(uses.iter().copied())?;
+                    let first = usize::from(low / 8);
+                    let last = usize::from(high.saturating_sub(1) / 8);
+                    let len = match last - first + 1 {
+                        1 => 1,
+                        2 => 2,
+                        3..=4 => 4,
+                        5..=8 => 8,
+                        _ => 16,
+                    };
+                    Arg::Unsigned(reader.uint(len)?.checked_shl(first as u32 * 8)?)
+                }
+                ty => self.arg(ty, reader, depth)?,
+            };
+            args.push(arg);
+        }
+        let mut out = String::new();
+        for fragment in &fragments {
+            match fragment {
+                Fragment::Literal(text) => out.push_str(text),
+                Fragment::Parameter(parameter) => show(&mut out, &args[parameter.index], parameter),
+            }
+        }
+        Some(out)
+    }
+
+    fn arg(&self, ty: &Type, reader: &mut Reader, depth: usize) -> Option<Arg> {
+        Some(match ty {
+            Type::U8 => Arg::Unsigned(reader.uint(1)?),
+            Type::U16 => Arg::Unsigned(reader.uint(2)?),
+            Type::U32 | Type::Usize => Arg::Unsigned(reader.uint(4)?),
+            Type::U64 => Arg::Unsigned(reader.uint(8)?),
+            Type::U128 => Arg::Unsigned(reader.uint(16)?),
+            Type::I8 => Arg::Signed(i128::from(reader.uint(1)? as u8 as i8)),
+            Type::I16 => Arg::Signed(i128::from(reader.uint(2)? as u16 as i16)),
+            Type::I32 | Type::Isize => Arg::Signed(i128::from(reader.uint(4)? as u32 as i32)),
+            Type::I64 => Arg::Signed(i128::from(reader.uint(8)? as u64 as i64)),
+            Type::I128 => Arg::Signed(reader.uint(16)? as i128),
+            Type::F32 => Arg::Float(f64::from(f32::from_bits(reader.uint(4)? as u32))),
+            Type::F64 => Arg::Float(f64::from_bits(reader.uint(8)? as u64)),
+            Type::Bool => Arg::Bool(reader.uint(1)? != 0),
+            Type::Char => Arg::Char(char::from_u32(reader.uint(4)? as u32)?),
+            Type::Str => {
+                let len = reader.len()?;
+                Arg::Str(String::from_utf8_lossy(reader.take(len)?).into_owned())
+            }
+            Type::IStr => Arg::Str(self.entries.get(&reader.index()?)?.data.clone()),
+            Type::U8Slice => {
+                let len = reader.len()?;
+                Arg::Bytes(reader.take(len)?.to_vec())
+            }
+            Type::U8Array(len) => Arg::Bytes(reader.take(*len)?.to_vec()),
+            Type::Format => {
+                let index = reader.index()?;
+                Arg::Formatted(self.value(index, reader, depth + 1)?)
+            }
+            Type::FormatSlice => {
+                let len = reader.len()?;
+                let index = reader.index()?;
+                Arg::Formatted(self.values(index, len, reader, depth + 1)?)
+            }
+            Type::FormatArray(len) => {
+                let index = reader.index()?;
+                Arg::Formatted(self.values(index, *len, reader, depth + 1)?)
+            }
+            // Formatted on the chip, ended by a byte UTF-8 never has
+            Type::Debug | Type::Display => {
+                let end = reader.data.iter().position(|&b| b == MARKER)?;
+                let text = String::from_utf8_lossy(reader.take(end)?).into_owned();
+                reader.take(1)?;
+                Arg::Str(text)
+            }
+            // What `write!` calls in a `Format` impl left, up to a 0 index
+            Type::FormatSequence => {
+                let mut text = String::new();
+                loop {
+                    let index = reader.index()?;
+                    if index == 0 {
+                        break;
+                    }
+                    text.push_str(&self.value(index, reader, depth + 1)?);
+                }
+                Arg::Formatted(text)
+            }
+            Type::BitField(_) => return None,
+        })
+    }
+
+    /// A `Format` value: the string at `index` with its data. Derived enums
+    /// list their variants split by `|`, the variant sent first.
+    fn value(&self, index: u16, reader: &mut Reader, depth: usize) -> Option<String> {
+        let entry = self.entries.get(&index)?;
+        if entry.tag != "defmt_derived" || !entry.data.contains('|') {
+            return self.format(&entry.data, reader, depth);
+        }
+        let variants: Vec<&str> = entry.data.split('|').collect();
+        let discriminant = match variants.len() {
+            0..=255 => reader.uint(1)?,
+            256..=65535 => reader.uint(2)?,
+            _ => reader.uint(4)?,
+        };
+        let variant = variants.get(discriminant as usize)?;
+        self.format(variant, reader, depth)
+    }
+
+    fn values(&self, index: u16, len: usize, reader: &mut Reader, depth: usize) -> Option<String> {
+        let mut text = String::from("[");
+        for i in 0..len {
+            if i > 0 {
+                text.push_str(", ");
+            }
+            text.push_str(&self.value(index, reader, depth)?);
+        }
+        text.push(']');
+        Some(text)
+    }
+}
+
+/// A piece of the received stream: plain output, or a decoded log line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Chunk {
+    Text(Vec<u8>),
+    Line(String),
+}
+
+/// Picks defmt frames out of a byte stream that also carries plain text,
+/// like the bootloader's, and decodes them. Frames may be split across
+/// reads.
+pub struct DefmtStream {
+    table: Arc<Table>,
+    // Bytes of the frame being received
+    frame: Option<Vec<u8>>,
+    // The last byte was the first of a marker
+    marker: bool,
+}
+
+impl DefmtStream {
+    pub fn new(table: Arc<Table>) -> Self {
+        DefmtStream {
+            table,
+            frame: None,
+            marker: false,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Chunk> {
+        let mut chunks = Vec::new();
+        let mut text = Vec::new();
+        for &byte in bytes {
+            if let Some(frame) = &mut self.frame {
+                if byte != 0 {
+                    frame.push(byte);
+                    if frame.len() > MAX_FRAME {
+                        self.frame = None;
+                    }
+                } else if !frame.is_empty() {
+                    let line = rzcobs_decode(frame)
+                        .and_then(|data| self.table.decode(&data))
+                        .unwrap_or_else(|| {
+                            "defmt: undecodable frame; is the ELF the one running?".to_string()
+                        });
+                    chunks.push(Chunk::Line(line));
+                    self.frame = None;
+                }
+                // Else the separator the encoder sends before its first frame
+                continue;
+            }
+            if self.marker {
+                self.marker = byte == MARKER;
+                if byte == 0 {
+                    if !text.is_empty() {
+                        chunks.push(Chunk::Text(std::mem::take(&mut text)));
+                    }
+                    self.frame = Some(Vec::new());
+                } else {
+                    text.push(MARKER);
+                    if byte != MARKER {
+                        text.push(byte);
+                    }
+                }
+            } else if byte == MARKER {
+                self.marker = true;
+            } else {
+                text.push(byte);
+            }
+        }
+        if !text.is_empty() {
+            chunks.push(Chunk::Text(text));
+        }
+        chunks
+    }
+}
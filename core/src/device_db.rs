@@ -0,0 +1,421 @@
+//! The device database: every flashing and provisioning of a board, with
+//! its MAC address, serial number, firmware version and checksum, who did
+//! it and whether it worked. Kept in SQLite so a line's production history
+//! survives restarts and can be audited or exported later.
+
+use crate::image_info;
+use crate::images::{self, DEFAULT_APP_OFFSET, PARTITION_TABLE_OFFSET};
+use crate::inventory::{quote, timestamp};
+use crate::models::{
+    AppError, DeviceRecord, DurationStats, FailureReason, ProductionRun, RecordQuery, RunReport,
+};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, Row};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+// One per version of the tables, each bringing a file up from the version
+// before it; a new file goes through them all
+const MIGRATIONS: [&str; 3] = [
+    "CREATE TABLE records (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        at INTEGER NOT NULL,
+        action TEXT NOT NULL,
+        mac_address TEXT,
+        serial_number TEXT,
+        chip_model TEXT,
+        firmware_version TEXT,
+        checksum TEXT,
+        operator TEXT NOT NULL,
+        passed INTEGER NOT NULL,
+        detail TEXT NOT NULL
+    );
+    CREATE INDEX records_at ON records (at);
+    CREATE INDEX records_mac ON records (mac_address);",
+    "ALTER TABLE records ADD COLUMN run TEXT NOT NULL DEFAULT '';
+    ALTER TABLE records ADD COLUMN duration_ms INTEGER;
+    CREATE INDEX records_run ON records (run);",
+    "ALTER TABLE records ADD COLUMN golden_hash TEXT;",
+];
+const COLUMNS: &str = "id, at, action, mac_address, serial_number, chip_model, \
+                       firmware_version, checksum, operator, run, passed, detail, duration_ms, \
+                       golden_hash";
+// How long a write waits for the app or the CLI holding the file
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Columns of `to_csv`, in order.
+pub const CSV_COLUMNS: [&str; 13] = [
+    "recorded_at",
+    "action",
+    "mac",
+    "usb_serial",
+    "chip",
+    "firmware_version",
+    "checksum",
+    "operator",
+    "run",
+    "result",
+    "detail",
+    "duration_ms",
+    "golden_sha256",
+];
+
+/// An open device database.
+pub struct DeviceDb {
+    conn: Connection,
+}
+
+impl DeviceDb {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    pub fn open(path: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let version = usize::try_from(version).unwrap_or(usize::MAX);
+        if version > MIGRATIONS.len() {
+            return Err(AppError::Io(format!(
+                "Device database {} was written by a newer esp32dev",
+                path.display()
+            )));
+        }
+        for (done, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(&format!(
+                "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+                migration,
+                done + 1
+            ))?;
+        }
+        Ok(DeviceDb { conn })
+    }
+
+    /// Adds `record`, returning the id it was given.
+    pub fn record(&self, record: &DeviceRecord) -> Result<i64, AppError> {
+        self.conn.execute(
+            &format!(
+                "INSERT INTO records ({}) \
+                 VALUES (NULL, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                COLUMNS
+            ),
+            params![
+                sql_time(record.at),
+                record.action,
+                record.mac_address,
+                record.serial_number,
+                record.chip_model,
+                record.firmware_version,
+                record.checksum,
+                record.operator,
+                record.run,
+                record.passed,
+                record.detail,
+                record.duration_ms.map(sql_time),
+                record.golden_hash,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Records matching `query`, most recent first.
+    pub fn query(&self, query: &RecordQuery) -> Result<Vec<DeviceRecord>, AppError> {
+        let mut sql = format!("SELECT {} FROM records WHERE 1 = 1", COLUMNS);
+        let mut values = Vec::new();
+        let mut bind = |value: Value| {
+            values.push(value);
+            values.len()
+        };
+        if let Some(device) = filter_text(&query.device) {
+            let n = bind(Value::Text(device.to_lowercase()));
+            sql.push_str(&format!(
+                " AND (instr(lower(mac_address), ?{n}) > 0 OR instr(lower(serial_number), ?{n}) > 0)"
+            ));
+        }
+        if let Some(operator) = filter_text(&query.operator) {
+            let n = bind(Value::Text(operator.to_lowercase()));
+            sql.push_str(&format!(" AND instr(lower(operator), ?{}) > 0", n));
+        }
+        if let Some(run) = &query.run {
+            let n = bind(Value::Text(run.trim().to_string()));
+            sql.push_str(&format!(" AND run = ?{}", n));
+        }
+        if let Some(action) = filter_text(&query.action) {
+            let n = bind(Value::Text(action.to_string()));
+            sql.push_str(&format!(" AND action = ?{}", n));
+        }
+        if let Some(passed) = query.passed {
+            let n = bind(Value::Integer(passed.into()));
+            sql.push_str(&format!(" AND passed = ?{}", n));
+        }
+        if let Some(since) = query.since {
+            let n = bind(Value::Integer(sql_time(since)));
+            sql.push_str(&format!(" AND at >= ?{}", n));
+        }
+        if let Some(until) = query.until {
+            let n = bind(Value::Integer(sql_time(until)));
+            sql.push_str(&format!(" AND at <= ?{}", n));
+        }
+        sql.push_str(" ORDER BY at DESC, id DESC");
+        if let Some(limit) = query.limit {
+            let n = bind(Value::Integer(limit.into()));
+            sql.push_str(&format!(" LIMIT ?{}", n));
+        }
+
+        let mut statement = self.conn.prepare(&sql)?;
+        let records = statement
+            .query_map(params_from_iter(values), read_record)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    /// The production runs recorded, the most recently active first.
+    pub fn runs(&self) -> Result<Vec<ProductionRun>, AppError> {
+        let mut statement = self.conn.prepare(
+            "SELECT run, min(at), max(at), count(*) FROM records \
+             GROUP BY run ORDER BY max(at) DESC, run",
+        )?;
+        let runs = statement
+            .query_map([], |row| {
+                Ok(ProductionRun {
+                    name: row.get(0)?,
+                    first_at: row.get::<_, i64>(1)?.max(0) as u64,
+                    last_at: row.get::<_, i64>(2)?.max(0) as u64,
+                    records: row.get::<_, i64>(3)?.try_into().unwrap_or(u32::MAX),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(runs)
+    }
+
+    /// Summary of the production run `run`.
+    pub fn run_report(&self, run: &str) -> Result<RunReport, AppError> {
+        let records = self.query(&RecordQuery {
+            run: Some(run.to_string()),
+            ..RecordQuery::default()
+        })?;
+        Ok(summarize(run, &records))
+    }
+}
+
+fn filter_text(text: &Option<String>) -> Option<&str> {
+    text.as_deref().map(str::trim).filter(|t| !t.is_empty())
+}
+
+// SQLite integers are signed
+fn sql_time(secs: u64) -> i64 {
+    i64::try_from(secs).unwrap_or(i64::MAX)
+}
+
+fn read_record(row: &Row) -> rusqlite::Result<DeviceRecord> {
+    Ok(DeviceRecord {
+        id: row.get(0)?,
+        at: row.get::<_, i64>(1)?.max(0) as u64,
+        action: row.get(2)?,
+        mac_address: row.get(3)?,
+        serial_number: row.get(4)?,
+        chip_model: row.get(5)?,
+        firmware_version: row.get(6)?,
+        checksum: row.get(7)?,
+        operator: row.get(8)?,
+        run: row.get(9)?,
+        passed: row.get(10)?,
+        detail: row.get(11)?,
+        duration_ms: row.get::<_, Option<i64>>(12)?.map(|ms| ms.max(0) as u64),
+        golden_hash: row.get(13)?,
+    })
+}
+
+/// Version of the app among `images`, each the contents written at an
+/// address: the app slot the partition table names when a table is among
+/// them, else the first image carrying an app descriptor.
+pub fn firmware_version(images: &[(u32, Vec<u8>)]) -> Option<String> {
+    // Flash contents at `address`, if one of the images covers it
+    let at = |address: u32| {
+        images.iter().find_map(|(start, data)| {
+            let offset = address.checked_sub(*start)? as usize;
+            data.get(offset..).filter(|rest| !rest.is_empty())
+        })
+    };
+    let app_offset = at(PARTITION_TABLE_OFFSET)
+        .and_then(images::app_offset)
+        .unwrap_or(DEFAULT_APP_OFFSET);
+    at(app_offset)
+        .into_iter()
+        .chain(images.iter().map(|(_, data)| data.as_slice()))
+        .find_map(|data| image_info::parse(data).ok()?.app)
+        .map(|app| app.version)
+}
+
+/// SHA-256 in hex over the address and contents of each image in turn, so
+/// the same files flashed to the same places always give the same checksum.
+pub fn checksum(images: &[(u32, Vec<u8>)]) -> String {
+    let mut hasher = Sha256::new();
+    for (address, data) in images {
+        hasher.update(address.to_le_bytes());
+        hasher.update(data);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `records` as CSV with a header line, for handing over to whoever audits
+/// the line.
+pub fn to_csv(records: &[DeviceRecord]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    for record in records {
+        let fields = [
+            timestamp(record.at),
+            record.action.clone(),
+            record.mac_address.clone().unwrap_or_default(),
+            record.serial_number.clone().unwrap_or_default(),
+            record.chip_model.clone().unwrap_or_default(),
+            record.firmware_version.clone().unwrap_or_default(),
+            record.checksum.clone().unwrap_or_default(),
+            record.operator.clone(),
+            record.run.clone(),
+            if record.passed { "pass" } else { "fail" }.to_string(),
+            record.detail.clone(),
+            record
+                .duration_ms
+                .map(|ms| ms.to_string())
+                .unwrap_or_default(),
+            record.golden_hash.clone().unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Summary of `records`, those of the production run `run`: the boards
+/// tried and how each ended up, why attempts failed, and how long the
+/// ones that passed took.
+pub fn summarize(run: &str, records: &[DeviceRecord]) -> RunReport {
+    let mut chronological: Vec<&DeviceRecord> = records.iter().collect();
+    chronological.sort_by_key(|r| (r.at, r.id));
+
+    // Whether each board's latest attempt passed
+    let mut units: HashMap<String, bool> = HashMap::new();
+    let mut reasons: HashMap<&str, u32> = HashMap::new();
+    let mut durations = Vec::new();
+    for record in &chronological {
+        // A board nothing is known of counts on its own
+        let unit = record
+            .mac_address
+            .clone()
+            .or_else(|| record.serial_number.clone())
+            .map(|id| id.to_lowercase())
+            .unwrap_or_else(|| format!("#{}", record.id));
+        units.insert(unit, record.passed);
+        if record.passed {
+            durations.extend(record.duration_ms);
+        } else {
+            *reasons.entry(record.detail.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut failures: Vec<FailureReason> = reasons
+        .into_iter()
+        .map(|(reason, count)| FailureReason {
+            reason: reason.to_string(),
+            count,
+        })
+        .collect();
+    failures.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.reason.cmp(&b.reason)));
+
+    durations.sort_unstable();
+    let durations = (!durations.is_empty()).then(|| DurationStats {
+        min_ms: durations[0],
+        median_ms: durations[durations.len() / 2],
+        mean_ms: durations.iter().sum::<u64>() / durations.len() as u64,
+        max_ms: durations[durations.len() - 1],
+    });
+
+    let distinct = |field: fn(&DeviceRecord) -> Option<&str>| -> Vec<String> {
+        let set: BTreeSet<&str> = records.iter().filter_map(field).collect();
+        set.into_iter().map(str::to_string).collect()
+    };
+    let passed = units.values().filter(|&&passed| passed).count() as u32;
+    RunReport {
+        run: run.to_string(),
+        first_at: chronological.first().map_or(0, |r| r.at),
+        last_at: chronological.last().map_or(0, |r| r.at),
+        attempts: records.len() as u32,
+        units: units.len() as u32,
+        passed,
+        failed: units.len() as u32 - passed,
+        failures,
+        durations,
+        operators: distinct(|r| Some(r.operator.as_str()).filter(|o| !o.is_empty())),
+        firmware_versions: distinct(|r| r.firmware_version.as_deref()),
+    }
+}
+
+// `ms` as seconds with one decimal
+fn seconds(ms: u64) -> String {
+    format!("{}.{} s", ms / 1000, ms % 1000 / 100)
+}
+
+impl RunReport {
+    /// The report as text, for handing over with the run's CSV.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let name = if self.run.is_empty() {
+            "(outside of a run)"
+        } else {
+            &self.run
+        };
+        let _ = writeln!(out, "Production run: {}", name);
+        if self.attempts > 0 {
+            let _ = writeln!(
+                out,
+                "From {} to {} UTC",
+                timestamp(self.first_at),
+                timestamp(self.last_at)
+            );
+        }
+        if !self.operators.is_empty() {
+            let _ = writeln!(out, "Operators: {}", self.operators.join(", "));
+        }
+        if !self.firmware_versions.is_empty() {
+            let _ = writeln!(out, "Firmware: {}", self.firmware_versions.join(", "));
+        }
+        out.push('\n');
+        let _ = writeln!(out, "Units:    {}", self.units);
+        let _ = writeln!(out, "Passed:   {}", self.passed);
+        let _ = writeln!(out, "Failed:   {}", self.failed);
+        let _ = writeln!(out, "Attempts: {}", self.attempts);
+        if self.units > 0 {
+            let _ = writeln!(
+                out,
+                "Yield:    {:.1}%",
+                f64::from(self.passed) * 100.0 / f64::from(self.units)
+            );
+        }
+        if let Some(d) = &self.durations {
+            out.push('\n');
+            let _ = writeln!(
+                out,
+                "Duration of a passing attempt: min {}, median {}, mean {}, max {}",
+                seconds(d.min_ms),
+                seconds(d.median_ms),
+                seconds(d.mean_ms),
+                seconds(d.max_ms)
+            );
+        }
+        if !self.failures.is_empty() {
+            out.push('\n');
+            out.push_str("Failures:\n");
+            for failure in &self.failures {
+                let _ = writeln!(out, "{:>5}  {}", failure.count, failure.reason);
+            }
+        }
+        out
+    }
+}
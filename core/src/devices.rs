@@ -0,0 +1,180 @@
+use crate::models::{DeviceStatus, SerialPortEntry};
+use serialport::SerialPortType;
+
+/// Silicon Labs CP210x, WCH CH34x, Espressif native USB and FTDI vendor ids,
+/// used when no allowlist is configured.
+pub const DEFAULT_VID_ALLOWLIST: [u16; 4] = [0x10C4, 0x1A86, 0x303A, 0x0403];
+
+/// The USB-Serial/JTAG built into the C3, S3, C6 and later chips. The S2
+/// and S3 USB-OTG consoles have other product ids.
+pub const USB_JTAG_VID_PID: &str = "303A:1001";
+
+fn connection_type(vid: u16) -> Option<String> {
+    Some(if vid == 0x303A {
+        "native_usb".to_string()
+    } else {
+        "uart_bridge".to_string()
+    })
+}
+
+/// Lists every ESP board whose USB vendor id is in `vid_allowlist`.
+///
+/// Boards with a serial port come first. Only when none has a port is the
+/// USB bus checked for boards whose driver is missing.
+pub fn scan(vid_allowlist: &[u16]) -> Vec<DeviceStatus> {
+    let mut found = scan_hardware(vid_allowlist);
+    found.extend(simulated_device());
+    found
+}
+
+#[cfg(feature = "simulator")]
+fn simulated_device() -> Option<DeviceStatus> {
+    Some(crate::simulator::device_status())
+}
+
+#[cfg(not(feature = "simulator"))]
+fn simulated_device() -> Option<DeviceStatus> {
+    None
+}
+
+/// USB serial ports whose vendor id passes `keep`.
+fn usb_ports(keep: impl Fn(u16) -> bool) -> Vec<DeviceStatus> {
+    let mut found = Vec::new();
+    if let Ok(ports) = serialport::available_ports() {
+        for p in ports {
+            if let SerialPortType::UsbPort(info) = p.port_type {
+                if keep(info.vid) {
+                    found.push(DeviceStatus {
+                        code: "ok".to_string(),
+                        message: format!("Connected ({})", p.port_name),
+                        port_name: Some(p.port_name),
+                        product_name: info.product,
+                        serial_number: info.serial_number,
+                        vid_pid: Some(format!("{:04X}:{:04X}", info.vid, info.pid)),
+                        connection_type: connection_type(info.vid),
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
+fn scan_hardware(vid_allowlist: &[u16]) -> Vec<DeviceStatus> {
+    // 1. Try to find ESP32 in COM ports
+    let mut found = usb_ports(|vid| vid_allowlist.contains(&vid));
+    if !found.is_empty() {
+        return found;
+    }
+
+    // 2. If no COM port found, check USB bus for missing drivers
+    if let Ok(devices) = nusb::list_devices() {
+        for dev in devices {
+            let vid = dev.vendor_id();
+            let pid = dev.product_id();
+            if vid_allowlist.contains(&vid) {
+                found.push(DeviceStatus {
+                    code: "missing_driver".to_string(),
+                    message: "Driver Missing".to_string(),
+                    port_name: None,
+                    product_name: dev.product_string().map(|s| s.to_string()),
+                    serial_number: dev.serial_number().map(|s| s.to_string()),
+                    vid_pid: Some(format!("{:04X}:{:04X}", vid, pid)),
+                    connection_type: connection_type(vid),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// Lists every serial port, ESP boards (by `vid_allowlist`) first, so a
+/// board behind an unknown USB bridge can still be picked by hand.
+pub fn list_ports(vid_allowlist: &[u16]) -> Vec<SerialPortEntry> {
+    let mut ports: Vec<SerialPortEntry> = serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| match p.port_type {
+            SerialPortType::UsbPort(info) => SerialPortEntry {
+                port_name: p.port_name,
+                description: info.product.or(info.manufacturer),
+                vid_pid: Some(format!("{:04X}:{:04X}", info.vid, info.pid)),
+                is_esp: vid_allowlist.contains(&info.vid),
+            },
+            _ => SerialPortEntry {
+                port_name: p.port_name,
+                description: None,
+                vid_pid: None,
+                is_esp: false,
+            },
+        })
+        .collect();
+    ports.extend(simulated_device().map(|d| SerialPortEntry {
+        port_name: d.port_name.unwrap_or_default(),
+        description: d.product_name,
+        vid_pid: d.vid_pid,
+        is_esp: true,
+    }));
+    ports.sort_by(|a, b| b.is_esp.cmp(&a.is_esp).then(a.port_name.cmp(&b.port_name)));
+    ports
+}
+
+/// Finds the first ESP board whose USB vendor id is in `vid_allowlist`.
+pub fn detect(vid_allowlist: &[u16]) -> DeviceStatus {
+    scan(vid_allowlist)
+        .into_iter()
+        .next()
+        // No device found
+        .unwrap_or_else(|| DeviceStatus {
+            code: "none".to_string(),
+            message: "Disconnected".to_string(),
+            port_name: None,
+            product_name: None,
+            serial_number: None,
+            vid_pid: None,
+            connection_type: None,
+        })
+}
+
+/// Whether `port_name` is a chip's own USB-Serial/JTAG rather than a UART
+/// bridge. It ignores the baud rate, and DTR and RTS drive the chip's reset
+/// and boot mode from inside.
+pub fn is_usb_jtag(port_name: &str) -> bool {
+    usb_ports(|vid| vid == 0x303A).iter().any(|d| {
+        d.port_name.as_deref() == Some(port_name) && d.vid_pid.as_deref() == Some(USB_JTAG_VID_PID)
+    })
+}
+
+/// USB serial number of the board on `port_name`, if it reports one.
+pub fn serial_number(port_name: &str) -> Option<String> {
+    usb_ports(|_| true)
+        .into_iter()
+        .find(|d| d.port_name.as_deref() == Some(port_name))
+        .and_then(|d| d.serial_number)
+}
+
+/// Where the board with USB serial number `serial`, last seen on
+/// `port_name`, is now. Native USB boards re-enumerate when reset or
+/// switched into download mode, often under another port name.
+pub fn follow(port_name: &str, serial: &str) -> Option<String> {
+    relocate(port_name, serial, &usb_ports(|_| true))
+}
+
+/// [`follow`] over the ports in `found`: `port_name` while it still
+/// carries `serial`, otherwise the one port that does. Bridges sharing a
+/// generic serial number are never told apart, so they aren't followed.
+pub fn relocate(port_name: &str, serial: &str, found: &[DeviceStatus]) -> Option<String> {
+    let mut candidates = found
+        .iter()
+        .filter(|d| d.serial_number.as_deref() == Some(serial))
+        .filter_map(|d| d.port_name.as_deref());
+    let first = candidates.next()?;
+    let rest: Vec<&str> = candidates.collect();
+    if first == port_name || rest.contains(&port_name) {
+        Some(port_name.to_string())
+    } else if rest.is_empty() {
+        Some(first.to_string())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,385 @@
+//! ELF to bootable image conversion, laid out the way `esptool.py
+//! elf2image` does it, for builds that only leave an ELF behind. Sections
+//! the chip maps from flash are placed so their file offset and load
+//! address agree modulo the 64 KB MMU page, padded with RAM segments where
+//! they fit; the rest follow as RAM segments.
+
+use crate::chips;
+use crate::image_info::{self, flash_freqs};
+use crate::models::AppError;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_FLASH_MODE: &str = "dio";
+pub const DEFAULT_FLASH_SIZE: &str = "4MB";
+pub const DEFAULT_FLASH_FREQ: &str = "40m";
+
+pub(crate) const ELF_MAGIC: &[u8] = b"\x7fELF";
+const ELFCLASS32: u8 = 1;
+const ELFDATA2LSB: u8 = 1;
+const EM_XTENSA: u16 = 94;
+const EM_RISCV: u16 = 243;
+const SHF_ALLOC: u32 = 0x2;
+// PROGBITS and the init/fini arrays; NOBITS sections such as .bss are
+// zeroed at startup and take no room in the image
+const LOADED_SECTION_TYPES: [u32; 4] = [1, 14, 15, 16];
+const SECTION_HEADER_LEN: usize = 40;
+
+const IMAGE_MAGIC: u8 = 0xe9;
+const WP_PIN_DISABLED: u8 = 0xee;
+const HEADER_LEN: usize = 24;
+const SEGMENT_HEADER_LEN: usize = 8;
+const MMU_PAGE: usize = 0x10000;
+// The ESP-IDF bootloader skips mapping the last page of a flash segment
+// that ends less than this far into it
+const MIN_PAGE_TAIL: usize = 0x24;
+const MAX_SEGMENTS: usize = 16;
+const CHECKSUM_SEED: u8 = 0xef;
+const APP_DESC_MAGIC: u32 = 0xabcd_5432;
+// app_elf_sha256 within esp_app_desc_t
+const APP_DESC_ELF_SHA256_AT: usize = 144;
+
+/// Target and flash parameters written into the image header.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageOptions {
+    /// espflash spelling or the marketing name, e.g. `esp32s3`
+    pub chip: String,
+    pub flash_mode: String,
+    pub flash_size: String,
+    pub flash_freq: String,
+}
+
+struct Segment {
+    address: u32,
+    data: Vec<u8>,
+    /// Name of the first section merged into it
+    name: String,
+}
+
+fn u16_at(data: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([data[at], data[at + 1]])
+}
+
+fn u32_at(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+fn malformed(what: &str) -> AppError {
+    AppError::InvalidInput(format!("Malformed ELF: {}", what))
+}
+
+/// Address ranges `chip` maps from flash, instruction bus then data bus.
+fn flash_ranges(chip: &str) -> [(u32, u32); 2] {
+    match chip {
+        "esp32" => [(0x400d_0000, 0x4040_0000), (0x3f40_0000, 0x3f80_0000)],
+        "esp32s2" => [(0x4008_0000, 0x40b8_0000), (0x3f00_0000, 0x3f3f_0000)],
+        "esp32s3" => [(0x4200_0000, 0x4400_0000), (0x3c00_0000, 0x3e00_0000)],
+        "esp32c2" => [(0x4200_0000, 0x4240_0000), (0x3c00_0000, 0x3c40_0000)],
+        "esp32c3" => [(0x4200_0000, 0x4280_0000), (0x3c00_0000, 0x3c80_0000)],
+        "esp32c6" | "esp32h2" => [(0x4200_0000, 0x4280_0000), (0x4280_0000, 0x4300_0000)],
+        "esp32p4" => [(0x4000_0000, 0x4c00_0000), (0x4000_0000, 0x4c00_0000)],
+        _ => [(0x4200_0000, 0x4400_0000), (0x4200_0000, 0x4400_0000)],
+    }
+}
+
+fn is_flash_address(chip: &str, address: u32) -> bool {
+    flash_ranges(chip)
+        .iter()
+        .any(|&(start, end)| (start..end).contains(&address))
+}
+
+/// The header's flash mode byte and size/frequency byte for `options`.
+fn flash_params(chip: &str, options: &ImageOptions) -> Result<(u8, u8), AppError> {
+    let mode = (0..4)
+        .find(|&code| image_info::flash_mode(code) == Some(options.flash_mode.as_str()))
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "Unknown flash mode {}; use qio, qout, dio or dout",
+                options.flash_mode
+            ))
+        })?;
+    let size = (0..8)
+        .find(|&code| image_info::flash_size(code << 4) == Some(options.flash_size.as_str()))
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "Unknown flash size {}; use 1MB to 128MB",
+                options.flash_size
+            ))
+        })?;
+    let freqs = flash_freqs(chip);
+    let freq = freqs
+        .iter()
+        .find(|(freq, _)| *freq == options.flash_freq)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| {
+            let supported: Vec<&str> = freqs.iter().map(|(freq, _)| *freq).collect();
+            AppError::InvalidInput(format!(
+                "{} doesn't run its flash at {}; use {}",
+                chip,
+                options.flash_freq,
+                supported.join(", ")
+            ))
+        })?;
+    Ok((mode, (size << 4) | freq))
+}
+
+/// The entry point and loaded sections of a 32-bit little-endian ELF, after
+/// checking it was built for `chip`'s architecture.
+fn read_elf(elf: &[u8], chip: &str) -> Result<(u32, Vec<Segment>), AppError> {
+    if elf.len() < 52 || !elf.starts_with(ELF_MAGIC) {
+        return Err(AppError::InvalidInput("Not an ELF file".to_string()));
+    }
+    if elf[4] != ELFCLASS32 || elf[5] != ELFDATA2LSB {
+        return Err(malformed("not a 32-bit little-endian ELF"));
+    }
+    let xtensa = matches!(chip, "esp32" | "esp32s2" | "esp32s3");
+    match (u16_at(elf, 18), xtensa) {
+        (EM_XTENSA, true) | (EM_RISCV, false) => {}
+        (EM_XTENSA, false) | (EM_RISCV, true) => {
+            return Err(AppError::InvalidInput(format!(
+                "The ELF was built for {}, not for {}",
+                if xtensa { "RISC-V" } else { "Xtensa" },
+                chip
+            )))
+        }
+        (machine, _) => {
+            return Err(AppError::InvalidInput(format!(
+                "The ELF is for machine {}, not an ESP chip",
+                machine
+            )))
+        }
+    }
+
+    let entry = u32_at(elf, 24);
+    let table = u32_at(elf, 32) as usize;
+    let entry_len = u16_at(elf, 46) as usize;
+    let count = u16_at(elf, 48) as usize;
+    let names_index = u16_at(elf, 50) as usize;
+    if entry_len < SECTION_HEADER_LEN || names_index >= count {
+        return Err(malformed("bad section header table"));
+    }
+    let header = |i: usize| {
+        elf.get(table + i * entry_len..table + i * entry_len + SECTION_HEADER_LEN)
+            .ok_or_else(|| malformed("section header table past the end of the file"))
+    };
+    let names = header(names_index)?;
+    let names_at = u32_at(names, 16) as usize;
+
+    let mut segments = Vec::new();
+    for i in 0..count {
+        let header = header(i)?;
+        let kind = u32_at(header, 4);
+        let flags = u32_at(header, 8);
+        let address = u32_at(header, 12);
+        let offset = u32_at(header, 16) as usize;
+        let len = u32_at(header, 20) as usize;
+        if !LOADED_SECTION_TYPES.contains(&kind) || flags & SHF_ALLOC == 0 {
+            continue;
+        }
+        if address == 0 || len == 0 {
+            continue;
+        }
+        let data = elf
+            .get(offset..offset + len)
+            .ok_or_else(|| malformed("section data past the end of the file"))?;
+        let name_at = names_at + u32_at(header, 0) as usize;
+        let name = elf
+            .get(name_at..)
+            .map(|rest| {
+                let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+                String::from_utf8_lossy(&rest[..end]).into_owned()
+            })
+            .unwrap_or_default();
+        segments.push(Segment {
+            address,
+            data: data.to_vec(),
+            name,
+        });
+    }
+    if segments.is_empty() {
+        return Err(AppError::InvalidInput(
+            "The ELF has no sections to load".to_string(),
+        ));
+    }
+    Ok((entry, segments))
+}
+
+/// Sorts `segments` by address and joins the ones that continue each
+/// other in the same kind of memory, then pads each to a word.
+fn merge(chip: &str, mut segments: Vec<Segment>) -> Vec<Segment> {
+    segments.sort_by_key(|segment| segment.address);
+    let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if let Some(last) = merged.last_mut() {
+            let end = last.address as usize + last.data.len();
+            if end == segment.address as usize
+                && is_flash_address(chip, last.address) == is_flash_address(chip, segment.address)
+            {
+                last.data.extend_from_slice(&segment.data);
+                continue;
+            }
+        }
+        merged.push(segment);
+    }
+    for segment in &mut merged {
+        segment
+            .data
+            .resize(segment.data.len().next_multiple_of(4), 0);
+    }
+    merged
+}
+
+/// Bytes of data a segment written at `pos` needs, so that the next
+/// segment's data lands at `address` modulo the MMU page.
+fn padding_needed(pos: usize, address: u32) -> usize {
+    let target = address as usize % MMU_PAGE;
+    let data_at = (pos + SEGMENT_HEADER_LEN) % MMU_PAGE;
+    if data_at == target {
+        return 0;
+    }
+    // The padding segment brings a header of its own
+    (target + 2 * MMU_PAGE - data_at - SEGMENT_HEADER_LEN) % MMU_PAGE
+}
+
+fn write_segment(image: &mut Vec<u8>, address: u32, data: &[u8]) {
+    image.extend_from_slice(&address.to_le_bytes());
+    image.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    image.extend_from_slice(data);
+}
+
+/// Converts `elf` into an app or bootloader image for `options.chip`, with
+/// the checksum and SHA-256 appended. An ESP-IDF app description gets the
+/// ELF's SHA-256 filled in, as `idf.py build` does.
+pub fn convert(elf: &[u8], options: &ImageOptions) -> Result<Vec<u8>, AppError> {
+    let chip = chips::model_key(&options.chip);
+    let chip_id = chips::image_chip_id(&chip)
+        .ok_or_else(|| AppError::InvalidInput(format!("Unknown chip {}", options.chip)))?;
+    let (mode, size_freq) = flash_params(&chip, options)?;
+    let (entry, sections) = read_elf(elf, &chip)?;
+
+    let (mut flash, mut ram): (Vec<Segment>, Vec<Segment>) = merge(&chip, sections)
+        .into_iter()
+        .partition(|segment| is_flash_address(&chip, segment.address));
+    // The bootloader looks for the app description at the start of the
+    // first flash segment, which on chips with a unified bus isn't the lowest
+    if let Some(i) = flash.iter().position(|s| s.name == ".flash.appdesc") {
+        let desc = flash.remove(i);
+        flash.insert(0, desc);
+    }
+    for pair in flash.windows(2) {
+        if pair[0].address as usize / MMU_PAGE == pair[1].address as usize / MMU_PAGE {
+            return Err(AppError::InvalidInput(format!(
+                "Sections at 0x{:08x} and 0x{:08x} share a 64 KB flash page; \
+                 merge them in the linker script",
+                pair[0].address, pair[1].address
+            )));
+        }
+    }
+
+    let mut image = vec![IMAGE_MAGIC, 0, mode, size_freq];
+    image.extend_from_slice(&entry.to_le_bytes());
+    image.extend_from_slice(&[WP_PIN_DISABLED, 0, 0, 0]);
+    image.extend_from_slice(&chip_id.to_le_bytes());
+    // Deprecated revision byte, then the minimum and maximum revision
+    image.push(0);
+    image.extend_from_slice(&0u16.to_le_bytes());
+    image.extend_from_slice(&u16::MAX.to_le_bytes());
+    image.extend_from_slice(&[0; 4]);
+    image.push(1);
+    debug_assert_eq!(image.len(), HEADER_LEN);
+
+    let mut count = 0;
+    let mut checksum = CHECKSUM_SEED;
+    let mut add = |image: &mut Vec<u8>, address: u32, data: &[u8]| {
+        write_segment(image, address, data);
+        checksum = data.iter().fold(checksum, |sum, b| sum ^ b);
+        count += 1;
+    };
+    for mut segment in flash {
+        let padding = padding_needed(image.len(), segment.address);
+        if padding > 0 {
+            // Fill the gap with the start of a RAM segment if one is left,
+            // otherwise with zeros loaded nowhere
+            match ram.first_mut() {
+                Some(first) if padding > SEGMENT_HEADER_LEN => {
+                    let take = padding.min(first.data.len());
+                    let head: Vec<u8> = first.data.drain(..take).collect();
+                    add(&mut image, first.address, &head);
+                    first.address += take as u32;
+                    if first.data.is_empty() {
+                        ram.remove(0);
+                    }
+                }
+                _ => add(&mut image, 0, &vec![0; padding]),
+            }
+            // A short RAM segment may not have filled the whole gap
+            let padding = padding_needed(image.len(), segment.address);
+            if padding > 0 {
+                add(&mut image, 0, &vec![0; padding]);
+            }
+        }
+        let tail = (image.len() + SEGMENT_HEADER_LEN + segment.data.len()) % MMU_PAGE;
+        if tail < MIN_PAGE_TAIL {
+            segment
+                .data
+                .resize(segment.data.len() + MIN_PAGE_TAIL - tail, 0);
+        }
+        add(&mut image, segment.address, &segment.data);
+    }
+    for segment in &ram {
+        add(&mut image, segment.address, &segment.data);
+    }
+    if count > MAX_SEGMENTS {
+        return Err(AppError::InvalidInput(format!(
+            "The image needs {} segments; the bootloader loads at most {}",
+            count, MAX_SEGMENTS
+        )));
+    }
+    image[1] = count as u8;
+
+    // The ELF's hash goes into the app description before the checksum
+    // and SHA-256 are taken over it
+    let first_data = HEADER_LEN + SEGMENT_HEADER_LEN;
+    if image.len() >= first_data + APP_DESC_ELF_SHA256_AT + 32
+        && u32_at(&image, first_data) == APP_DESC_MAGIC
+    {
+        let at = first_data + APP_DESC_ELF_SHA256_AT;
+        let digest = Sha256::digest(elf);
+        let old: Vec<u8> = image[at..at + 32].to_vec();
+        image[at..at + 32].copy_from_slice(&digest);
+        checksum = old
+            .iter()
+            .chain(digest.iter())
+            .fold(checksum, |sum, b| sum ^ b);
+    }
+
+    while image.len() % 16 != 15 {
+        image.push(0);
+    }
+    image.push(checksum);
+    let digest = Sha256::digest(&image);
+    image.extend_from_slice(&digest);
+    Ok(image)
+}
+
+/// Converts the ELF at `elf_path` and writes the image to `output`, next to
+/// the ELF with a `.bin` extension when `None`. The result is decoded again
+/// before it is written, so a broken image never reaches the disk.
+pub fn convert_file(
+    elf_path: &Path,
+    output: Option<&Path>,
+    options: &ImageOptions,
+) -> Result<PathBuf, AppError> {
+    let image = convert(&std::fs::read(elf_path)?, options)?;
+    let info = image_info::parse(&image)?;
+    if !info.problems.is_empty() {
+        return Err(AppError::Internal(info.problems.join("; ")));
+    }
+    let output = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| elf_path.with_extension("bin"));
+    std::fs::write(&output, image)?;
+    Ok(output)
+}
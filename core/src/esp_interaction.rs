@@ -0,0 +1,1489 @@
+use crate::benchmark::{self, BenchmarkOptions};
+use crate::chips;
+use crate::devices;
+use crate::esptool;
+use crate::flash_estimate;
+use crate::image_info;
+use crate::models::{
+    AppError, BenchmarkRun, ChipDetails, CrystalWarning, DeviceStatus, FlashSegment, MemoryInfo,
+};
+use crate::otadata;
+use crate::report::{DeviceReport, FlashId, SecuritySummary};
+use crate::signature;
+use crate::tasks::TaskHandle;
+use crate::transport;
+use espflash::command::{Command, CommandType};
+use espflash::connection::{Connection, ResetAfterOperation, ResetBeforeOperation};
+use espflash::flasher::Flasher;
+use espflash::target::{efuse, Chip, ProgressCallbacks, XtalFrequency};
+use md5::{Digest, Md5};
+use serialport::UsbPortInfo;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+// Images are written in slices so a cancel request takes effect between them
+const WRITE_SLICE: usize = 256 * 1024;
+// Flash erase unit, the smallest part of an image written on its own
+const SECTOR: usize = 4096;
+// Blocks hashed on the device first when looking for changed sectors
+const DIFF_BLOCK: usize = 64 * 1024;
+// First app partition in the default partition tables
+const APP_OFFSET: u32 = 0x10000;
+const APP_DESC_MAGIC: u32 = 0xABCD_5432;
+// Header byte of bootloader and app images, the ones Secure Boot verifies
+const IMAGE_MAGIC: u8 = 0xE9;
+// Partition type `data`, subtype `coredump`
+const COREDUMP_KIND: u8 = 0x01;
+const COREDUMP_SUBTYPE: u8 = 0x03;
+// SECURE_BOOT_EN in the ROM's security info flags
+const SECURE_BOOT_EN: u32 = 1 << 0;
+// How long a native USB board may take to come back after a reset
+const REENUMERATE_TIMEOUT: Duration = Duration::from_secs(3);
+// Writing time after which the time left is taken from the measured rate
+const ETA_FROM_RATE_AFTER: Duration = Duration::from_secs(1);
+
+fn connect_flasher(port_name: &str, target_baud: Option<u32>) -> Result<Flasher, AppError> {
+    if transport::is_network(port_name) {
+        return connect_network_flasher(port_name);
+    }
+    let serial = devices::serial_number(port_name);
+    match connect_serial_flasher(port_name, target_baud) {
+        Err(e) => {
+            // Native USB boards may re-enumerate on their way into download
+            // mode and come back under another name
+            let Some(moved) = serial.and_then(|serial| moved_to(port_name, &serial)) else {
+                return Err(e);
+            };
+            info!("{} came back as {}, connecting there", port_name, moved);
+            connect_serial_flasher(&moved, target_baud)
+        }
+        connected => connected,
+    }
+}
+
+/// Waits for the board with USB serial number `serial` to show up on a
+/// port other than `port_name`. Gives up at once while it is still there.
+fn moved_to(port_name: &str, serial: &str) -> Option<String> {
+    let deadline = Instant::now() + REENUMERATE_TIMEOUT;
+    loop {
+        match devices::follow(port_name, serial) {
+            Some(port) if port == port_name => return None,
+            Some(port) => return Some(port),
+            None if Instant::now() >= deadline => return None,
+            None => std::thread::sleep(Duration::from_millis(100)),
+        }
+    }
+}
+
+fn connect_serial_flasher(port_name: &str, target_baud: Option<u32>) -> Result<Flasher, AppError> {
+    // 1. Open Native Serial Port
+    let serial_port = serialport::new(port_name, 115200).open_native()?;
+
+    // 2. Find Port Info (Vital for Native USB support)
+    // We must provide the correct VID/PID so espflash knows which reset strategy to use.
+    let ports = serialport::available_ports().unwrap_or_default();
+    let port_info = ports
+        .iter()
+        .find(|p| p.port_name == port_name)
+        .map(|p| match &p.port_type {
+            serialport::SerialPortType::UsbPort(info) => info.clone(),
+            _ => unknown_port_info(),
+        })
+        .unwrap_or_else(unknown_port_info);
+
+    // 3. Create Connection
+    let connection = Connection::new(
+        serial_port,
+        port_info,
+        ResetAfterOperation::default(),
+        ResetBeforeOperation::default(),
+        115200,
+    );
+
+    // 4. Connect Flasher
+    let flasher = Flasher::connect(
+        connection,
+        true,  // load stub (Optimistically try true to fix connection error)
+        false, // verify, write_images checks each slice itself
+        false, // skip unchanged regions, done by write_images too
+        None,  // chip
+        target_baud,
+    )?;
+
+    Ok(flasher)
+}
+
+fn unknown_port_info() -> UsbPortInfo {
+    UsbPortInfo {
+        vid: 0,
+        pid: 0,
+        serial_number: None,
+        manufacturer: None,
+        product: None,
+    }
+}
+
+/// espflash only takes a native port, so a network link is bridged to one.
+/// The bridged end has no control lines: the board is reset into download
+/// mode over the link first, and back into its app by [`hard_reset`].
+#[cfg(unix)]
+fn connect_network_flasher(port_name: &str) -> Result<Flasher, AppError> {
+    let mut link = transport::open(port_name, 115200)?;
+    if transport::has_control_lines(port_name) {
+        transport::enter_download_mode(link.as_mut())?;
+    } else {
+        info!(
+            "{} can't reset the board; it must already be in download mode",
+            port_name
+        );
+    }
+    let connection = Connection::new(
+        transport::bridge(link)?,
+        unknown_port_info(),
+        ResetAfterOperation::NoReset,
+        ResetBeforeOperation::NoReset,
+        115200,
+    );
+    let flasher = Flasher::connect(
+        connection, // over the bridged end
+        true,       // load stub
+        false,      // verify, write_images checks each slice itself
+        false,      // skip unchanged regions, done by write_images too
+        None,       // chip
+        None,       // stays at 115200, see `with_flasher`
+    )?;
+    Ok(flasher)
+}
+
+#[cfg(not(unix))]
+fn connect_network_flasher(port_name: &str) -> Result<Flasher, AppError> {
+    Err(AppError::Connection(format!(
+        "Flashing over {} needs a macOS or Linux host",
+        port_name
+    )))
+}
+
+struct Session {
+    flasher: Flasher,
+    baud: u32,
+    last_used: Instant,
+}
+
+/// Connected flashers kept per port, so back-to-back operations skip the
+/// sync and stub upload.
+///
+/// A cached session keeps the port open: anything else that opens it (the
+/// monitor, a script console) must [`release`](Self::release) it first.
+/// Sessions idle for longer than the timeout are closed in the background.
+/// The default instance caches nothing.
+#[derive(Clone, Default)]
+pub struct FlasherSessions {
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+    idle_timeout: Duration,
+}
+
+impl FlasherSessions {
+    pub fn new(idle_timeout: Duration) -> Self {
+        let sessions = FlasherSessions {
+            sessions: Arc::default(),
+            idle_timeout,
+        };
+        let weak = Arc::downgrade(&sessions.sessions);
+        std::thread::spawn(move || expire_sessions(weak, idle_timeout));
+        sessions
+    }
+
+    /// Runs `op` on the cached session for `port_name`, connecting first if
+    /// there is none. A failed operation drops the session, since the chip
+    /// state is unknown afterwards.
+    pub fn with_flasher<T>(
+        &self,
+        port_name: &str,
+        target_baud: Option<u32>,
+        op: impl FnOnce(&mut Flasher) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        // Rate changes don't reach the far end of a network bridge
+        let target_baud = target_baud.filter(|_| !transport::is_network(port_name));
+        let baud = target_baud.unwrap_or(115200);
+        let cached = self.sessions.lock().unwrap().remove(port_name);
+        let mut session = match cached {
+            Some(mut session) => {
+                debug!("Reusing flasher session on {}", port_name);
+                if session.baud != baud {
+                    session.flasher.change_baud(baud)?;
+                    session.baud = baud;
+                }
+                session
+            }
+            None => Session {
+                flasher: connect_flasher(port_name, target_baud)?,
+                baud,
+                last_used: Instant::now(),
+            },
+        };
+
+        let result = op(&mut session.flasher)?;
+        if !self.idle_timeout.is_zero() {
+            session.last_used = Instant::now();
+            self.sessions
+                .lock()
+                .unwrap()
+                .insert(port_name.to_string(), session);
+        }
+        Ok(result)
+    }
+
+    /// Closes the session on `port_name`, if any.
+    pub fn release(&self, port_name: &str) {
+        #[cfg(feature = "simulator")]
+        if crate::simulator::is_simulated(port_name) {
+            crate::simulator::release_flasher();
+        }
+        if self.sessions.lock().unwrap().remove(port_name).is_some() {
+            debug!("Flasher session on {} released", port_name);
+        }
+    }
+
+    /// Closes sessions on ports that are no longer present. Network links
+    /// never show up in a scan and are left to time out.
+    pub fn retain(&self, ports: &[String]) {
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|port, _| ports.contains(port) || transport::is_network(port));
+    }
+}
+
+fn expire_sessions(sessions: Weak<Mutex<HashMap<String, Session>>>, idle_timeout: Duration) {
+    while let Some(sessions) = sessions.upgrade() {
+        sessions.lock().unwrap().retain(|port, session| {
+            let keep = session.last_used.elapsed() < idle_timeout;
+            if !keep {
+                debug!("Flasher session on {} timed out", port);
+            }
+            keep
+        });
+        drop(sessions);
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+pub fn connect_and_get_info(
+    sessions: &FlasherSessions,
+    port_name: &str,
+) -> Result<ChipDetails, AppError> {
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        crate::simulator::hold_flasher();
+        return Ok(crate::simulator::chip_details());
+    }
+    let details = sessions.with_flasher(port_name, None, read_chip_details)?;
+    debug!(
+        "esptool.py equivalent: {}",
+        esptool::flash_id(details.chip_model.as_deref(), port_name)
+    );
+    Ok(details)
+}
+
+/// The model and MAC of the board on `port_name` right now, for records
+/// that must not mix up boards swapped on one port. Only connecting can
+/// fail; a MAC the ROM won't give is left empty.
+pub fn chip_identity(sessions: &FlasherSessions, port_name: &str) -> Result<ChipDetails, AppError> {
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        let chip = crate::simulator::chip_details();
+        return Ok(ChipDetails {
+            chip_model: chip.chip_model,
+            mac_address: chip.mac_address,
+            ..ChipDetails::default()
+        });
+    }
+    sessions.with_flasher(port_name, None, |flasher| {
+        let mac_address = flasher.device_info().ok().and_then(|info| info.mac_address);
+        Ok(ChipDetails {
+            chip_model: Some(flasher.chip().to_string()),
+            mac_address,
+            ..ChipDetails::default()
+        })
+    })
+}
+
+/// Reads everything the report covers from the board on `port_name`. Only
+/// the chip details are required; the parts the ROM or the flash won't give
+/// are left empty. `status` is what the OS reported for the port.
+pub fn device_report(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    status: Option<DeviceStatus>,
+    task: &TaskHandle,
+) -> Result<DeviceReport, AppError> {
+    let mut report = DeviceReport {
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        port_name: port_name.to_string(),
+        status,
+        chip: ChipDetails::default(),
+        security: None,
+        partitions: Vec::new(),
+        flash_id: None,
+    };
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        report.chip = crate::simulator::chip_details();
+        return Ok(report);
+    }
+    task.phase("reading");
+    sessions.with_flasher(port_name, None, |flasher| {
+        report.chip = read_chip_details(flasher)?;
+        // Not reported by the ESP32 ROM
+        report.security = flasher
+            .security_info()
+            .ok()
+            .map(|info| SecuritySummary::new(info.flags, info.flash_crypt_cnt, &info.key_purposes));
+        if report.chip.secure_download_mode {
+            return Ok(());
+        }
+        report.flash_id = flash_id(flasher).map(FlashId::from_raw);
+        task.checkpoint()?;
+        match read_region(
+            flasher,
+            otadata::PARTITION_TABLE_OFFSET,
+            otadata::PARTITION_TABLE_LEN,
+        ) {
+            Ok(table) => report.partitions = otadata::partitions(&table),
+            Err(e) => warn!("Failed to read the partition table: {}", e.message()),
+        }
+        Ok(())
+    })?;
+    Ok(report)
+}
+
+// Raw RDID response of the SPI flash, the command espflash sizes flash by
+fn flash_id(flasher: &mut Flasher) -> Option<u32> {
+    let response = flasher
+        .connection()
+        .with_timeout(CommandType::FlashDetect.timeout(), |connection| {
+            connection.command(Command::FlashDetect)
+        });
+    match response.and_then(|value| value.try_into()) {
+        Ok(id) => Some(id),
+        Err(e) => {
+            warn!("Failed to read the flash ID: {}", e);
+            None
+        }
+    }
+}
+
+/// Fails with [`AppError::WrongChip`] unless the board on `port_name` is an
+/// `expected` (`esp32s3` or `ESP32-S3`), e.g. before writing a build made
+/// for one target.
+pub fn check_chip(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    expected: &str,
+) -> Result<(), AppError> {
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        let found = crate::simulator::chip_details().chip_model;
+        return expect_chip(port_name, expected, &found.unwrap_or_default());
+    }
+    let found = sessions.with_flasher(port_name, None, |flasher| Ok(flasher.chip().to_string()))?;
+    expect_chip(port_name, expected, &found)
+}
+
+fn expect_chip(port_name: &str, expected: &str, found: &str) -> Result<(), AppError> {
+    if chips::model_key(found) != chips::model_key(expected) {
+        return Err(AppError::WrongChip(format!(
+            "Built for {}, but the board on {} is {}",
+            expected, port_name, found
+        )));
+    }
+    Ok(())
+}
+
+/// Human readable flash size, e.g. "4 MB" or "512 KB".
+pub fn format_flash_size(bytes: u32) -> String {
+    const MB: u32 = 1024 * 1024;
+    if bytes >= MB {
+        format!("{} MB", bytes / MB)
+    } else {
+        format!("{} KB", bytes / 1024)
+    }
+}
+
+fn read_chip_details(flasher: &mut Flasher) -> Result<ChipDetails, AppError> {
+    // Use the chip trait to get the model dynamically
+    let chip_model = Some(flasher.chip().to_string());
+    let capabilities = chips::capabilities(&flasher.chip().to_string());
+
+    if flasher.secure_download_mode() {
+        info!("Secure Download Mode enabled, skipping eFuse and flash reads");
+        return Ok(ChipDetails {
+            chip_model,
+            rom_version: rom_version(flasher),
+            capabilities,
+            secure_download_mode: true,
+            ..ChipDetails::default()
+        });
+    }
+
+    // Read the JEDEC ID; None when the chip needs another detection method
+    let flash_size_bytes = match flasher.flash_detect() {
+        Ok(size) => size.map(|s| s.size()),
+        Err(e) => {
+            warn!("Failed to detect flash size: {}", e);
+            None
+        }
+    };
+    let flash_size = flash_size_bytes.map(format_flash_size);
+
+    // Retrieve Device Info (MAC, Features, etc.)
+    let (mac_address, features) = match flasher.device_info() {
+        Ok(info) => {
+            let mac = info.mac_address;
+
+            // Probe for features
+            let feats_probe = info.features;
+            let feats_str = feats_probe
+                .iter()
+                .map(|f| format!("{:?}", f))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            (mac, Some(feats_str))
+        }
+        Err(e) => {
+            warn!("Failed to get device info: {}", e);
+            (None, None)
+        }
+    };
+
+    // Get Chip Revision
+    let (wafer_major, wafer_minor) = match flasher.chip().revision(flasher.connection()) {
+        Ok((major, minor)) => (Some(major), Some(minor)),
+        Err(_) => (None, None),
+    };
+    let chip_revision = wafer_major
+        .zip(wafer_minor)
+        .map(|(major, minor)| format!("v{}.{}", major, minor));
+
+    let chip = flasher.chip();
+    let pkg = match package_version(chip, flasher.connection()) {
+        Ok(pkg) => pkg,
+        Err(e) => {
+            warn!("Failed to read package version: {}", e);
+            None
+        }
+    };
+    let package = pkg.map(|pkg| package_name(chip, pkg));
+
+    let (flash, psram) = match memory_layout(chip, flasher.connection(), pkg) {
+        Ok(layout) => layout,
+        Err(e) => {
+            warn!("Failed to read flash/PSRAM eFuses: {}", e);
+            (None, None)
+        }
+    };
+
+    let rom_version = rom_version(flasher);
+
+    let sdk_version = read_sdk_version(flasher);
+
+    // Get Crystal Frequency
+    let xtal = flasher.chip().xtal_frequency(flasher.connection()).ok();
+    let crystal_frequency = xtal.map(|freq| freq.to_string());
+    let crystal_warning = xtal.and_then(|freq| crystal_warning(flasher.chip(), freq));
+
+    Ok(ChipDetails {
+        chip_model,
+        mac_address,
+        flash_size,
+        flash_size_bytes,
+        features,
+        crystal_frequency,
+        chip_revision,
+        wafer_major,
+        wafer_minor,
+        package,
+        rom_version,
+        sdk_version,
+        crystal_warning,
+        flash,
+        psram,
+        capabilities,
+        secure_download_mode: false,
+    })
+}
+
+fn rom_version(flasher: &mut Flasher) -> Option<String> {
+    // Not supported by the ESP32 and ESP32-S2 ROMs
+    flasher
+        .security_info()
+        .ok()
+        .and_then(|info| info.eco_version)
+        .map(|eco| format!("ECO{}", eco))
+}
+
+fn xtal_mhz(freq: XtalFrequency) -> u32 {
+    match freq {
+        XtalFrequency::_26Mhz => 26,
+        XtalFrequency::_32Mhz => 32,
+        XtalFrequency::_48Mhz => 48,
+        _ => 40,
+    }
+}
+
+fn crystal_warning(chip: Chip, detected: XtalFrequency) -> Option<CrystalWarning> {
+    let expected = chip.default_xtal_frequency();
+    if detected == expected {
+        return None;
+    }
+    let apparent_baud = 115200 * xtal_mhz(detected) / xtal_mhz(expected);
+    warn!(
+        "{} crystal on {}, which defaults to {}",
+        detected, chip, expected
+    );
+    Some(CrystalWarning {
+        detected: detected.to_string(),
+        expected: expected.to_string(),
+        apparent_baud,
+        message: format!(
+            "{} crystal detected but {} firmware defaults to {}: serial output will be garbled \
+             unless the app is built for {} (or monitor at {} baud)",
+            detected, chip, expected, detected, apparent_baud
+        ),
+    })
+}
+
+fn package_version(
+    chip: Chip,
+    connection: &mut Connection,
+) -> Result<Option<u32>, espflash::Error> {
+    let field = match chip {
+        Chip::Esp32 => {
+            let word3 = chip.read_efuse_raw(connection, 0, 3)?;
+            return Ok(Some(((word3 >> 9) & 0x7) + (((word3 >> 2) & 0x1) << 3)));
+        }
+        Chip::Esp32c2 => efuse::esp32c2::PKG_VERSION,
+        Chip::Esp32c3 => efuse::esp32c3::PKG_VERSION,
+        Chip::Esp32c5 => efuse::esp32c5::PKG_VERSION,
+        Chip::Esp32c6 => efuse::esp32c6::PKG_VERSION,
+        Chip::Esp32c61 => efuse::esp32c61::PKG_VERSION,
+        Chip::Esp32h2 => efuse::esp32h2::PKG_VERSION,
+        Chip::Esp32h4 => efuse::esp32h4::PKG_VERSION,
+        Chip::Esp32p4 => efuse::esp32p4::PKG_VERSION,
+        Chip::Esp32s2 => efuse::esp32s2::PKG_VERSION,
+        Chip::Esp32s3 => efuse::esp32s3::PKG_VERSION,
+        Chip::Esp32s31 => efuse::esp32s31::PKG_VERSION,
+        _ => return Ok(None),
+    };
+    chip.read_efuse_le::<u32>(connection, field).map(Some)
+}
+
+/// Part name for a package version, as esptool reports it.
+fn package_name(chip: Chip, pkg: u32) -> String {
+    let name = match (chip, pkg) {
+        (Chip::Esp32, 0) => "ESP32-D0WDQ6",
+        (Chip::Esp32, 1) => "ESP32-D0WD",
+        (Chip::Esp32, 2) => "ESP32-D2WD",
+        (Chip::Esp32, 4) => "ESP32-U4WDH",
+        (Chip::Esp32, 5) => "ESP32-PICO-D4",
+        (Chip::Esp32, 6) => "ESP32-PICO-V3-02",
+        (Chip::Esp32, 7) => "ESP32-D0WDR2-V3",
+        (Chip::Esp32c3, 0) => "ESP32-C3 (QFN32)",
+        (Chip::Esp32c3, 1) => "ESP8685 (QFN28)",
+        (Chip::Esp32c3, 2) => "ESP32-C3 AZ (QFN32)",
+        (Chip::Esp32c3, 3) => "ESP8686 (QFN24)",
+        (Chip::Esp32c6, 0) => "ESP32-C6 (QFN40)",
+        (Chip::Esp32c6, 1) => "ESP32-C6FH4 (QFN32)",
+        (Chip::Esp32s2, 0) => "ESP32-S2",
+        (Chip::Esp32s2, 1) => "ESP32-S2FH2",
+        (Chip::Esp32s2, 2) => "ESP32-S2FH4",
+        (Chip::Esp32s3, 0) => "ESP32-S3 (QFN56)",
+        (Chip::Esp32s3, 1) => "ESP32-S3-PICO-1 (LGA56)",
+        _ => return format!("{} (package {})", chip, pkg),
+    };
+    name.to_string()
+}
+
+/// Embedded vs external flash and embedded PSRAM, decoded from the eFuses
+/// the way esptool does. Chips without these fields report `None`.
+fn memory_layout(
+    chip: Chip,
+    connection: &mut Connection,
+    pkg: Option<u32>,
+) -> Result<(Option<MemoryInfo>, Option<MemoryInfo>), espflash::Error> {
+    let memory = |size: Option<&str>, vendor: Option<&str>, mode: &str| MemoryInfo {
+        embedded: size.is_some(),
+        size: size.map(str::to_string),
+        vendor: vendor.map(str::to_string),
+        mode: Some(mode.to_string()),
+    };
+    // 1 means the flash is wired for octal (OPI) mode
+    let flash_mode = |connection: &mut Connection, field| {
+        chip.read_efuse_le::<u32>(connection, field)
+            .map(|octal| if octal == 1 { "octal" } else { "quad" })
+    };
+
+    let layout = match chip {
+        Chip::Esp32 => {
+            let flash = match pkg {
+                Some(2) => Some("2 MB"),
+                Some(4) | Some(5) => Some("4 MB"),
+                Some(6) => Some("8 MB"),
+                _ => None,
+            };
+            // PICO-V3-02 and D0WDR2-V3 carry 2 MB of PSRAM
+            let psram =
+                matches!(pkg, Some(6) | Some(7)).then(|| memory(Some("2 MB"), None, "quad"));
+            (Some(memory(flash, None, "quad")), psram)
+        }
+        Chip::Esp32s2 => {
+            let size = |version| match version {
+                1 => Some("2 MB"),
+                2 => Some("4 MB"),
+                _ => None,
+            };
+            let mode = flash_mode(connection, efuse::esp32s2::FLASH_TYPE)?;
+            let flash = size(chip.read_efuse_le::<u32>(connection, efuse::esp32s2::FLASH_VERSION)?);
+            let psram = size(chip.read_efuse_le::<u32>(connection, efuse::esp32s2::PSRAM_VERSION)?);
+            (
+                Some(memory(flash, None, mode)),
+                psram.map(|size| memory(Some(size), None, "quad")),
+            )
+        }
+        Chip::Esp32s3 => {
+            let mode = flash_mode(connection, efuse::esp32s3::FLASH_TYPE)?;
+            let flash = match chip.read_efuse_le::<u32>(connection, efuse::esp32s3::FLASH_CAP)? {
+                1 => Some("8 MB"),
+                2 => Some("4 MB"),
+                _ => None,
+            };
+            let vendor =
+                match chip.read_efuse_le::<u32>(connection, efuse::esp32s3::FLASH_VENDOR)? {
+                    5 => Some("BY"),
+                    vendor => flash_vendor(vendor),
+                };
+            let psram_cap = chip.read_efuse_le::<u32>(connection, efuse::esp32s3::PSRAM_CAP)?
+                | chip.read_efuse_le::<u32>(connection, efuse::esp32s3::PSRAM_CAP_3)? << 2;
+            let psram_vendor =
+                match chip.read_efuse_le::<u32>(connection, efuse::esp32s3::PSRAM_VENDOR)? {
+                    1 => Some("AP_3v3"),
+                    2 => Some("AP_1v8"),
+                    _ => None,
+                };
+            // The 8 and 16 MB dies (R8, R16V) are octal, the smaller ones quad
+            let psram = match psram_cap {
+                1 => Some(memory(Some("8 MB"), psram_vendor, "octal")),
+                2 => Some(memory(Some("2 MB"), psram_vendor, "quad")),
+                3 => Some(memory(Some("16 MB"), psram_vendor, "octal")),
+                4 => Some(memory(Some("4 MB"), psram_vendor, "quad")),
+                _ => None,
+            };
+            (Some(memory(flash, flash.and(vendor), mode)), psram)
+        }
+        Chip::Esp32c3 => {
+            let mode = flash_mode(connection, efuse::esp32c3::FLASH_TYPE)?;
+            let flash =
+                flash_capacity(chip.read_efuse_le::<u32>(connection, efuse::esp32c3::FLASH_CAP)?);
+            let vendor =
+                match chip.read_efuse_le::<u32>(connection, efuse::esp32c3::FLASH_VENDOR)? {
+                    5 => Some("ZBIT"),
+                    vendor => flash_vendor(vendor),
+                };
+            (Some(memory(flash, flash.and(vendor), mode)), None)
+        }
+        Chip::Esp32c5 | Chip::Esp32c6 | Chip::Esp32h2 => {
+            let (cap, vendor) = match chip {
+                Chip::Esp32c5 => (efuse::esp32c5::FLASH_CAP, efuse::esp32c5::FLASH_VENDOR),
+                Chip::Esp32c6 => (efuse::esp32c6::FLASH_CAP, efuse::esp32c6::FLASH_VENDOR),
+                _ => (efuse::esp32h2::FLASH_CAP, efuse::esp32h2::FLASH_VENDOR),
+            };
+            let flash = flash_capacity(chip.read_efuse_le::<u32>(connection, cap)?);
+            let vendor = flash_vendor(chip.read_efuse_le::<u32>(connection, vendor)?);
+            let psram = if chip == Chip::Esp32c5
+                && chip.read_efuse_le::<u32>(connection, efuse::esp32c5::PSRAM_CAP)? != 0
+            {
+                Some(MemoryInfo {
+                    embedded: true,
+                    size: None,
+                    vendor: None,
+                    mode: Some("quad".to_string()),
+                })
+            } else {
+                None
+            };
+            (Some(memory(flash, flash.and(vendor), "quad")), psram)
+        }
+        _ => (None, None),
+    };
+    Ok(layout)
+}
+
+/// FLASH_CAP eFuse shared by the C-series and H2.
+fn flash_capacity(cap: u32) -> Option<&'static str> {
+    match cap {
+        1 => Some("4 MB"),
+        2 => Some("2 MB"),
+        3 => Some("1 MB"),
+        4 => Some("8 MB"),
+        _ => None,
+    }
+}
+
+fn flash_vendor(vendor: u32) -> Option<&'static str> {
+    match vendor {
+        1 => Some("XMC"),
+        2 => Some("GD"),
+        3 => Some("FM"),
+        4 => Some("TT"),
+        _ => None,
+    }
+}
+
+/// ESP-IDF version the flashed app was built with, from the app descriptor
+/// that follows the image and first segment headers.
+fn read_sdk_version(flasher: &mut Flasher) -> Option<String> {
+    let image = read_region(flasher, APP_OFFSET, 0x100).ok()?;
+    // esp_image_header_t (24 bytes), then esp_image_segment_header_t (8)
+    let desc = image.get(32..)?;
+    let magic = u32::from_le_bytes(desc.get(..4)?.try_into().ok()?);
+    if image[0] != 0xE9 || magic != APP_DESC_MAGIC {
+        return None;
+    }
+    // esp_app_desc_t.idf_ver
+    let idf_ver = desc.get(112..144)?;
+    let end = idf_ver
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(idf_ver.len());
+    Some(String::from_utf8_lossy(&idf_ver[..end]).into_owned())
+}
+
+/// Reads `len` bytes of flash at `offset`. espflash only reads into a file,
+/// so this goes through a temporary one.
+fn read_region(flasher: &mut Flasher, offset: u32, len: u32) -> Result<Vec<u8>, AppError> {
+    let path = std::env::temp_dir().join(format!(
+        "esp32dev-flash-{}-{:x}.bin",
+        std::process::id(),
+        offset
+    ));
+    let result = flasher
+        .read_flash(offset, len, 0x1000, 64, path.clone())
+        .map_err(AppError::from)
+        .and_then(|_| Ok(std::fs::read(&path)?));
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+/// A flash offset as hex with a `0x` prefix, e.g. `0x10000`, or decimal.
+pub fn parse_flash_address(address: &str) -> Result<u32, AppError> {
+    let trimmed = address.trim();
+    let (digits, radix) = match trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        Some(hex) => (hex, 16),
+        None => (trimmed, 10),
+    };
+    // `from_str_radix` would also take a leading sign
+    let parsed = Some(digits)
+        .filter(|d| !d.is_empty() && d.chars().all(|c| c.is_digit(radix)))
+        .and_then(|d| u32::from_str_radix(d, radix).ok());
+    parsed.ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "Invalid flash address {:?}: expected hex such as 0x10000",
+            address
+        ))
+    })
+}
+
+/// `bytes_per_sec` the way esptool reports it, e.g. `312.5 KB/s`.
+pub fn format_rate(bytes_per_sec: u64) -> String {
+    format!("{:.1} KB/s", bytes_per_sec as f64 / 1024.0)
+}
+
+fn rate(bytes: u64, since: Instant) -> u64 {
+    let secs = since.elapsed().as_secs_f64();
+    if secs > 0.0 {
+        (bytes as f64 / secs) as u64
+    } else {
+        0
+    }
+}
+
+/// Maps espflash's per-slice chunk counter onto byte progress of the whole image.
+struct SliceProgress<'a> {
+    task: &'a TaskHandle,
+    done: u64,
+    len: u64,
+    total: u64,
+    chunks: usize,
+    // When writing began, for the effective rate including verification
+    started: Instant,
+}
+
+impl ProgressCallbacks for SliceProgress<'_> {
+    fn init(&mut self, _addr: u32, total: usize) {
+        self.chunks = total.max(1);
+        self.task.phase("writing");
+    }
+
+    fn update(&mut self, current: usize) {
+        let written = self.done + self.len * current as u64 / self.chunks as u64;
+        let bytes_per_sec = rate(written, self.started);
+        self.task.throughput(bytes_per_sec);
+        // The first second's rate says little; the estimate stands till then
+        if self.started.elapsed() >= ETA_FROM_RATE_AFTER {
+            let remaining = self.total.saturating_sub(written);
+            self.task
+                .eta(flash_estimate::remaining_secs(remaining, bytes_per_sec));
+        }
+        self.task.progress(written, self.total);
+    }
+
+    // espflash doesn't verify here; write_images checks each slice itself
+    fn verifying(&mut self) {}
+
+    fn finish(&mut self, _skipped: bool) {}
+}
+
+pub fn flash_firmware(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    firmware_path: &str,
+    address: u32,
+    baud_rate: u32,
+    task: &TaskHandle,
+) -> Result<String, AppError> {
+    let segment = FlashSegment {
+        path: firmware_path.to_string(),
+        address,
+    };
+    flash_segments(sessions, port_name, &[segment], baud_rate, task)
+}
+
+/// Writes an app to the OTA slot after the running one and selects it in
+/// otadata, as an OTA update on the device would. The running app stays
+/// in its slot as the fallback, where flashing at 0x10000 overwrites it.
+pub fn flash_ota_slot(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    firmware_path: &str,
+    baud_rate: u32,
+    task: &TaskHandle,
+) -> Result<String, AppError> {
+    task.phase("reading");
+    let app = std::fs::read(firmware_path)?;
+    if app.first() != Some(&IMAGE_MAGIC) {
+        return Err(AppError::InvalidInput(format!(
+            "{} is not an app image",
+            firmware_path
+        )));
+    }
+    task.checkpoint()?;
+
+    task.phase("connecting");
+    let target_baud = (baud_rate != 115200).then_some(baud_rate);
+    let update = sessions.with_flasher(port_name, target_baud, |flasher| {
+        task.phase("partition");
+        let table = read_region(
+            flasher,
+            otadata::PARTITION_TABLE_OFFSET,
+            otadata::PARTITION_TABLE_LEN,
+        )?;
+        let layout = otadata::OtaLayout::from_table(&table)?;
+        let current = read_region(flasher, layout.otadata.offset, 0x2000)?;
+        info!(
+            "Running OTA slot: {:?} of {}",
+            layout.active_slot(&current),
+            layout.slots.len()
+        );
+        layout.update(&current, app.len())
+    })?;
+    info!(
+        "Writing {} to {} at 0x{:X}",
+        firmware_path, update.label, update.app_offset
+    );
+
+    // The app first, so a failed write leaves the old one selected
+    let images = vec![
+        (
+            FlashSegment {
+                path: firmware_path.to_string(),
+                address: update.app_offset,
+            },
+            app,
+        ),
+        (
+            FlashSegment {
+                path: "otadata".to_string(),
+                address: update.otadata_offset,
+            },
+            update.otadata,
+        ),
+    ];
+    let result = write_images(sessions, port_name, images, baud_rate, task)?;
+    Ok(format!("{}; booting {} next", result, update.label))
+}
+
+/// Contents of the `coredump` data partition, where ESP-IDF saves a core
+/// dump on a crash when built to dump to flash.
+pub fn read_coredump(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    task: &TaskHandle,
+) -> Result<Vec<u8>, AppError> {
+    task.phase("reading");
+    sessions.with_flasher(port_name, None, |flasher| {
+        if flasher.secure_download_mode() {
+            return Err(AppError::SecureDownload(
+                "Flash can't be read back in Secure Download Mode".to_string(),
+            ));
+        }
+        let table = read_region(
+            flasher,
+            otadata::PARTITION_TABLE_OFFSET,
+            otadata::PARTITION_TABLE_LEN,
+        )?;
+        let partition = otadata::partitions(&table)
+            .into_iter()
+            .find(|p| p.kind == COREDUMP_KIND && p.subtype == COREDUMP_SUBTYPE)
+            .ok_or_else(|| {
+                AppError::InvalidInput("The partition table has no coredump partition".into())
+            })?;
+        task.checkpoint()?;
+        info!(
+            "Reading core dump partition {} at 0x{:X}",
+            partition.label, partition.offset
+        );
+        read_region(flasher, partition.offset, partition.size)
+    })
+}
+
+/// Parts of an image of `len` bytes that need writing: whole sectors (the
+/// last cut off at `len`), neighbours merged. `differs` tells whether the
+/// flash under a range holds something else; it is asked about 64 KB
+/// blocks first and then only about the sectors of blocks that differ.
+pub fn changed_ranges(
+    len: usize,
+    mut differs: impl FnMut(Range<usize>) -> Result<bool, AppError>,
+) -> Result<Vec<Range<usize>>, AppError> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for block in (0..len).step_by(DIFF_BLOCK) {
+        let block_end = (block + DIFF_BLOCK).min(len);
+        if !differs(block..block_end)? {
+            continue;
+        }
+        for start in (block..block_end).step_by(SECTOR) {
+            let end = (start + SECTOR).min(block_end);
+            if !differs(start..end)? {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => ranges.push(start..end),
+            }
+        }
+    }
+    Ok(ranges)
+}
+
+/// Writes several images in one session, e.g. bootloader, partition table
+/// and app, then resets once. Only sectors whose content differs from the
+/// flash are written, so reflashing a slightly changed build is quick.
+/// Progress covers what is written of all of them together.
+pub fn flash_segments(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    segments: &[FlashSegment],
+    baud_rate: u32,
+    task: &TaskHandle,
+) -> Result<String, AppError> {
+    let images = read_segments(segments, task)?;
+    write_images(sessions, port_name, images, baud_rate, task)
+}
+
+/// Contents of the files of `segments`, for [`write_images`].
+pub fn read_segments(
+    segments: &[FlashSegment],
+    task: &TaskHandle,
+) -> Result<Vec<(FlashSegment, Vec<u8>)>, AppError> {
+    if segments.is_empty() {
+        return Err(AppError::InvalidInput("No firmware files to flash".into()));
+    }
+
+    task.phase("reading");
+    let mut images = Vec::with_capacity(segments.len());
+    for segment in segments {
+        let data = std::fs::read(&segment.path)?;
+        if data.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "Firmware file is empty: {}",
+                segment.path
+            )));
+        }
+        images.push((segment.clone(), data));
+    }
+    Ok(images)
+}
+
+/// Writes images already in memory, see [`flash_segments`]. A segment's
+/// `path` only names it in messages. Bootloader and app images that are
+/// cut short or fail their checksum are refused before connecting.
+///
+/// Each slice is checked against the stub's MD5 after it is written and
+/// before the next one starts, not in parallel: the stub answers one
+/// command at a time. What this saves over a full verify pass is reading
+/// the flash twice; verifying still adds the time of one MD5 per slice.
+pub fn write_images(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    images: Vec<(FlashSegment, Vec<u8>)>,
+    baud_rate: u32,
+    task: &TaskHandle,
+) -> Result<String, AppError> {
+    check_overlaps(&images)?;
+    for (segment, data) in &images {
+        check_aligned(segment)?;
+        image_info::check(data)
+            .map_err(|e| AppError::InvalidInput(format!("{}: {}", segment.path, e.message())))?;
+    }
+    task.checkpoint()?;
+
+    let estimate =
+        flash_estimate::estimate(images.iter().map(|(_, data)| data.as_slice()), baud_rate);
+    info!(
+        "Estimated {} s for {} bytes ({} compressed) at {} baud",
+        estimate.secs, estimate.bytes, estimate.compressed, baud_rate
+    );
+    task.eta(Some(estimate.secs));
+    let total: u64 = images.iter().map(|(_, data)| data.len() as u64).sum();
+    let summary = match images.as_slice() {
+        [(segment, _)] => format!("Flashed {} bytes at 0x{:X}", total, segment.address),
+        _ => format!("Flashed {} files ({} bytes)", images.len(), total),
+    };
+
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        crate::simulator::flash(total, task)?;
+        return Ok(summary);
+    }
+
+    task.phase("connecting");
+    // Only switch baud when a faster rate was configured
+    let target_baud = (baud_rate != 115200).then_some(baud_rate);
+    let written = sessions.with_flasher(port_name, target_baud, |flasher| {
+        let chip = flasher.chip().to_string();
+        let segments: Vec<FlashSegment> = images.iter().map(|(s, _)| s.clone()).collect();
+        info!(
+            "esptool.py equivalent: {}",
+            esptool::write_flash(Some(&chip), port_name, baud_rate, &segments)
+        );
+        if secure_boot_enabled(flasher) {
+            check_signatures(&images)?;
+        }
+        let revision = flasher.chip().revision(flasher.connection()).ok();
+        check_revisions(&images, revision)?;
+        // Flash without a JEDEC size code is left to espflash's own checks
+        let flash_size = flasher.flash_detect().ok().flatten();
+        if let Some(flash_size) = flash_size.map(|size| size.size()) {
+            for (segment, data) in &images {
+                check_fits(segment, data.len(), flash_size)?;
+            }
+        }
+        // Each slice is verified against the stub's MD5 of the flash right
+        // after it is written, before the next write. The same MD5 finds
+        // the sectors that need writing at all.
+        let verify = !flasher.secure_download_mode();
+        if !verify {
+            warn!("Secure Download Mode: written flash can't be read back to verify");
+        }
+        task.phase("comparing");
+        let mut slices: Vec<(&FlashSegment, u32, &[u8])> = Vec::new();
+        for (segment, data) in &images {
+            task.checkpoint()?;
+            let ranges = if verify {
+                changed_ranges(data.len(), |range| {
+                    let expected = u128::from_be_bytes(Md5::digest(&data[range.clone()]).into());
+                    let address = segment.address + range.start as u32;
+                    Ok(flasher.checksum_md5(address, range.len() as u32)? != expected)
+                })?
+            } else {
+                std::iter::once(0..data.len()).collect()
+            };
+            let changed: usize = ranges.iter().map(|range| range.len()).sum();
+            info!(
+                "Flashing {} ({} of {} bytes changed) -> {} @ 0x{:X}",
+                segment.path,
+                changed,
+                data.len(),
+                port_name,
+                segment.address
+            );
+            for range in ranges {
+                for start in range.clone().step_by(WRITE_SLICE) {
+                    let end = (start + WRITE_SLICE).min(range.end);
+                    slices.push((segment, segment.address + start as u32, &data[start..end]));
+                }
+            }
+        }
+        let written: u64 = slices.iter().map(|(_, _, slice)| slice.len() as u64).sum();
+        // Only the changed sectors are left to send, compressing about as well
+        let compressed = estimate.compressed * written / estimate.bytes.max(1);
+        task.eta(Some(flash_estimate::estimate_secs(
+            written, compressed, baud_rate, false,
+        )));
+
+        let started = Instant::now();
+        let mut done = 0;
+        for &(segment, address, slice) in &slices {
+            task.checkpoint()?;
+            let mut progress = SliceProgress {
+                task,
+                done,
+                len: slice.len() as u64,
+                total: written,
+                chunks: 1,
+                started,
+            };
+            flasher.write_bin_to_flash(address, slice, &mut progress)?;
+            if verify {
+                task.phase("verifying");
+                let expected = u128::from_be_bytes(Md5::digest(slice).into());
+                if flasher.checksum_md5(address, slice.len() as u32)? != expected {
+                    return Err(AppError::Flasher(format!(
+                        "Flash at 0x{:X} doesn't match {} after writing",
+                        address, segment.path
+                    )));
+                }
+            }
+            done += slice.len() as u64;
+        }
+        let bytes_per_sec = (written > 0).then(|| rate(written, started));
+        match bytes_per_sec {
+            Some(bytes_per_sec) => {
+                info!("Wrote {} bytes at {}", written, format_rate(bytes_per_sec));
+                task.throughput(bytes_per_sec);
+            }
+            None => info!("Flash already holds the images, nothing written"),
+        }
+        task.progress(written, written);
+
+        // Reboot into the new firmware
+        task.phase("resetting");
+        let chip = flasher.chip();
+        // No stub was loaded if the ROM is in Secure Download Mode
+        let is_stub = !flasher.secure_download_mode();
+        flasher.connection().reset_after(is_stub, chip)?;
+        Ok((written, bytes_per_sec))
+    })?;
+    // The stub is gone after the reset
+    sessions.release(port_name);
+    if transport::is_network(port_name) && transport::has_control_lines(port_name) {
+        hard_reset(port_name)?;
+    }
+    info!("Flash complete");
+
+    Ok(match written {
+        (0, _) => "Flash already holds these images, nothing written".to_string(),
+        (written, Some(bytes_per_sec)) if written < total => format!(
+            "{}, {} changed, at {}",
+            summary,
+            written,
+            format_rate(bytes_per_sec)
+        ),
+        (_, bytes_per_sec) => format!(
+            "{} at {}",
+            summary,
+            format_rate(bytes_per_sec.unwrap_or_default())
+        ),
+    })
+}
+
+fn secure_boot_enabled(flasher: &mut Flasher) -> bool {
+    // Not reported by the ESP32 and ESP32-S2 ROMs, which then flash as before
+    flasher
+        .security_info()
+        .map(|info| info.flags & SECURE_BOOT_EN != 0)
+        .unwrap_or(false)
+}
+
+/// Bootloaders and apps built for a later revision than the chip's won't
+/// start, so such writes are refused before they start; doubtful ones are
+/// logged.
+fn check_revisions(
+    images: &[(FlashSegment, Vec<u8>)],
+    revision: Option<(u32, u32)>,
+) -> Result<(), AppError> {
+    for (segment, data) in images {
+        let warning = image_info::check_revision(data, revision)
+            .map_err(|e| AppError::WrongChip(format!("{}: {}", segment.path, e.message())))?;
+        if let Some(warning) = warning {
+            warn!("{} {}", segment.path, warning);
+        }
+    }
+    Ok(())
+}
+
+/// With Secure Boot on, the ROM won't run a bootloader or app without a
+/// valid signature block, so such writes are refused before they start.
+fn check_signatures(images: &[(FlashSegment, Vec<u8>)]) -> Result<(), AppError> {
+    for (segment, data) in images {
+        if data[0] != IMAGE_MAGIC {
+            continue;
+        }
+        match signature::inspect(data) {
+            Ok(Some(info)) => warn!(
+                "Secure Boot: {} is {} signed by key {}; it must match the key digest in eFuse",
+                segment.path,
+                info.scheme,
+                info.keys()
+            ),
+            Ok(None) => {
+                return Err(AppError::SecureBoot(format!(
+                    "{} is not signed but Secure Boot is enabled on this chip",
+                    segment.path
+                )))
+            }
+            Err(e) => {
+                return Err(AppError::SecureBoot(format!(
+                    "{}: {}",
+                    segment.path,
+                    e.message()
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rejects image sets where one file would overwrite the start of the next.
+fn check_overlaps(images: &[(FlashSegment, Vec<u8>)]) -> Result<(), AppError> {
+    let mut ranges: Vec<_> = images
+        .iter()
+        .map(|(segment, data)| (segment.address as u64, data.len() as u64, &segment.path))
+        .collect();
+    ranges.sort_by_key(|&(address, _, _)| address);
+    for pair in ranges.windows(2) {
+        let (address, len, path) = pair[0];
+        let (next_address, _, next_path) = pair[1];
+        if address + len > next_address {
+            return Err(AppError::InvalidInput(format!(
+                "{} at 0x{:X} overlaps {} at 0x{:X}",
+                path, address, next_path, next_address
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Refuses an image that doesn't start on a flash sector, which writing
+/// would have to erase partly.
+pub fn check_aligned(segment: &FlashSegment) -> Result<(), AppError> {
+    if !(segment.address as usize).is_multiple_of(SECTOR) {
+        return Err(AppError::InvalidInput(format!(
+            "{} at 0x{:X} doesn't start on a 0x{:X} byte flash sector",
+            segment.path, segment.address, SECTOR
+        )));
+    }
+    Ok(())
+}
+
+/// Refuses an image of `len` bytes at `segment`'s address that runs past
+/// the end of a flash of `flash_size` bytes.
+pub fn check_fits(segment: &FlashSegment, len: usize, flash_size: u32) -> Result<(), AppError> {
+    let end = u64::from(segment.address) + len as u64;
+    if end > u64::from(flash_size) {
+        return Err(AppError::FlashOverflow {
+            path: segment.path.clone(),
+            address: segment.address,
+            end,
+            flash_size,
+        });
+    }
+    Ok(())
+}
+
+pub fn erase_flash(
+    sessions: &FlasherSessions,
+    port_name: &str,
+    task: &TaskHandle,
+) -> Result<String, AppError> {
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        crate::simulator::erase(task)?;
+        return Ok("Flash Memory Erased Successfully".to_string());
+    }
+
+    task.phase("connecting");
+    sessions.with_flasher(port_name, None, |flasher| {
+        task.checkpoint()?;
+
+        task.phase("erasing");
+        info!("Erasing flash...");
+        let chip = flasher.chip().to_string();
+        info!(
+            "esptool.py equivalent: {}",
+            esptool::erase_flash(Some(&chip), port_name)
+        );
+        if flasher.secure_download_mode() {
+            // Full-chip erase is a stub command
+            return Err(AppError::SecureDownload(
+                "Erasing the whole flash is not possible in Secure Download Mode".to_string(),
+            ));
+        }
+        flasher.erase_flash()?;
+        Ok(())
+    })?;
+    info!("Flash erased successfully");
+
+    Ok("Flash Memory Erased Successfully".to_string())
+}
+
+/// Writes a test pattern to a scratch region and reads it back at each of
+/// `options.baud_rates`, timing both, see [`benchmark`]. A rate that fails
+/// is reported with its error and the next one connects afresh; only a
+/// cancel stops the run. The region is left holding the last pattern.
+pub fn flash_benchmark(
+    sessions: &FlasherSessions,
+    options: &BenchmarkOptions,
+    task: &TaskHandle,
+) -> Result<Vec<BenchmarkRun>, AppError> {
+    let port_name = options.port_name.as_str();
+    if options.baud_rates.is_empty() {
+        return Err(AppError::InvalidInput("No baud rates to benchmark".into()));
+    }
+
+    task.phase("connecting");
+    let offset = sessions.with_flasher(port_name, None, |flasher| {
+        if flasher.secure_download_mode() {
+            return Err(AppError::SecureDownload(
+                "Flash can't be read back in Secure Download Mode".to_string(),
+            ));
+        }
+        task.phase("partition");
+        let flash_size = flasher
+            .flash_detect()
+            .ok()
+            .flatten()
+            .map(|size| size.size());
+        let table = read_region(
+            flasher,
+            otadata::PARTITION_TABLE_OFFSET,
+            otadata::PARTITION_TABLE_LEN,
+        )?;
+        benchmark::scratch_region(
+            &otadata::partitions(&table),
+            flash_size,
+            options.len,
+            options.offset,
+        )
+    })?;
+    info!(
+        "Benchmarking {} bytes at 0x{:X} on {}",
+        options.len, offset, port_name
+    );
+
+    // Each rate writes and reads the region once
+    let len = options.len as u64;
+    let total = 2 * len * options.baud_rates.len() as u64;
+    let mut runs = Vec::with_capacity(options.baud_rates.len());
+    for (i, &baud_rate) in options.baud_rates.iter().enumerate() {
+        task.checkpoint()?;
+        task.phase("connecting");
+        let data = benchmark::pattern(options.len as usize, baud_rate);
+        let done = 2 * len * i as u64;
+        let mut run = BenchmarkRun {
+            baud_rate,
+            write_rate: None,
+            read_rate: None,
+            verified: false,
+            error: None,
+        };
+        let target_baud = (baud_rate != 115200).then_some(baud_rate);
+        let result = sessions.with_flasher(port_name, target_baud, |flasher| {
+            let started = Instant::now();
+            let mut progress = SliceProgress {
+                task,
+                done,
+                len,
+                total,
+                chunks: 1,
+                started,
+            };
+            flasher.write_bin_to_flash(offset, &data, &mut progress)?;
+            run.write_rate = Some(rate(len, started));
+            task.checkpoint()?;
+
+            task.phase("reading_back");
+            let started = Instant::now();
+            let read = read_region(flasher, offset, options.len)?;
+            run.read_rate = Some(rate(len, started));
+            task.progress(done + 2 * len, total);
+            if read != data {
+                return Err(AppError::Flasher(format!(
+                    "Flash read back at {} baud doesn't match what was written",
+                    baud_rate
+                )));
+            }
+            run.verified = true;
+            Ok(())
+        });
+        match result {
+            Err(AppError::Cancelled) => return Err(AppError::Cancelled),
+            Err(e) => {
+                warn!("Benchmark at {} baud failed: {}", baud_rate, e);
+                run.error = Some(e.to_string());
+            }
+            Ok(()) => info!(
+                "{} baud: write {}, read {}",
+                baud_rate,
+                format_rate(run.write_rate.unwrap_or_default()),
+                format_rate(run.read_rate.unwrap_or_default())
+            ),
+        }
+        runs.push(run);
+    }
+
+    // The stub is still running, at the last rate
+    task.phase("resetting");
+    sessions.release(port_name);
+    hard_reset(port_name)?;
+    info!("{}", benchmark::summary(&runs));
+    Ok(runs)
+}
+
+/// Resets the board on `port_name` for the user. A cached flasher session
+/// is closed first: it holds the port open, and the chip it talked to is
+/// about to reboot.
+pub fn reset(sessions: &FlasherSessions, port_name: &str) -> Result<(), AppError> {
+    sessions.release(port_name);
+    hard_reset(port_name)
+}
+
+/// Restarts the application the same way the auto-reset circuit does:
+/// EN is pulsed low through RTS while IO0 (DTR) stays high. A chip's own
+/// USB-Serial/JTAG does the same from inside, with its own timing.
+pub fn hard_reset(port_name: &str) -> Result<(), AppError> {
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        crate::simulator::open_exclusive()?;
+        crate::simulator::reset();
+        return Ok(());
+    }
+
+    let mut link = transport::open(port_name, 115200)?;
+    if devices::is_usb_jtag(port_name) {
+        transport::reset_usb_jtag_to_app(link.as_mut())?;
+    } else {
+        transport::reset_to_app(link.as_mut())?;
+    }
+    info!("Hard reset {}", port_name);
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! Network uploads to boards running ArduinoOTA, speaking the same protocol
+//! as `espota.py`: a UDP invitation (with optional MD5 challenge), then the
+//! board connects back over TCP and pulls the image.
+
+use crate::models::AppError;
+use crate::tasks::TaskHandle;
+use md5::{Digest, Md5};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Port ArduinoOTA listens on unless the sketch changes it.
+pub const DEFAULT_PORT: u16 = 3232;
+
+// Invitations go over UDP and may be lost; espota.py also retries
+const INVITE_ATTEMPTS: u32 = 5;
+const INVITE_TIMEOUT: Duration = Duration::from_secs(2);
+// The board has to tear down Wi-Fi work before it connects back
+const ACCEPT_TIMEOUT: Duration = Duration::from_secs(10);
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(10);
+// Checking the MD5 and committing the partition takes a while
+const RESULT_TIMEOUT: Duration = Duration::from_secs(60);
+const CHUNK_SIZE: usize = 1024;
+
+/// What the image replaces on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OtaTarget {
+    Firmware,
+    Filesystem,
+}
+
+impl OtaTarget {
+    fn command(self) -> u32 {
+        match self {
+            OtaTarget::Firmware => 0,
+            OtaTarget::Filesystem => 100,
+        }
+    }
+}
+
+pub struct OtaOptions<'a> {
+    pub host: &'a str,
+    pub port: u16,
+    /// Required when the sketch calls `ArduinoOTA.setPassword`.
+    pub password: Option<&'a str>,
+    pub target: OtaTarget,
+}
+
+fn md5_hex(data: &[u8]) -> String {
+    Md5::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn io_error(e: std::io::Error) -> AppError {
+    if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) {
+        AppError::Timeout(e.to_string())
+    } else {
+        AppError::Connection(e.to_string())
+    }
+}
+
+/// Answer to an `AUTH <nonce>` challenge, as ArduinoOTA computes it.
+pub fn auth_response(password: &str, nonce: &str, cnonce: &str) -> String {
+    md5_hex(format!("{}:{}:{}", md5_hex(password.as_bytes()), nonce, cnonce).as_bytes())
+}
+
+/// Sends `message` until the board answers; `None` after the last attempt.
+fn exchange(socket: &UdpSocket, message: &str, attempts: u32) -> Result<Option<String>, AppError> {
+    let mut buf = [0u8; 128];
+    for _ in 0..attempts {
+        socket
+            .send(message.as_bytes())
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        match socket.recv(&mut buf) {
+            Ok(n) => return Ok(Some(String::from_utf8_lossy(&buf[..n]).trim().to_string())),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(AppError::Connection(e.to_string())),
+        }
+    }
+    Ok(None)
+}
+
+/// Waits for the board to connect back, giving up on cancel or timeout.
+fn accept(listener: &TcpListener, task: &TaskHandle) -> Result<TcpStream, AppError> {
+    listener.set_nonblocking(true).map_err(io_error)?;
+    let deadline = Instant::now() + ACCEPT_TIMEOUT;
+    loop {
+        task.checkpoint()?;
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false).map_err(io_error)?;
+                return Ok(stream);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(AppError::Timeout(
+                        "The board accepted the upload but never connected back; check that no firewall blocks incoming connections".to_string(),
+                    ));
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+}
+
+/// Uploads the image at `path` to the board at `options.host`.
+pub fn upload(options: &OtaOptions, path: &Path, task: &TaskHandle) -> Result<(), AppError> {
+    let image = std::fs::read(path).map_err(|e| AppError::Io(e.to_string()))?;
+    let size = image.len();
+    let file_md5 = md5_hex(&image);
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    task.phase("connecting");
+    // Listen first so the port is ready by the time the board accepts
+    let listener = TcpListener::bind(("0.0.0.0", 0)).map_err(io_error)?;
+    let local_port = listener.local_addr().map_err(io_error)?.port();
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).map_err(io_error)?;
+    socket
+        .connect((options.host, options.port))
+        .map_err(|e| AppError::Connection(format!("{}: {}", options.host, e)))?;
+    socket
+        .set_read_timeout(Some(INVITE_TIMEOUT))
+        .map_err(io_error)?;
+
+    let invitation = format!(
+        "{} {} {} {}\n",
+        options.target.command(),
+        local_port,
+        size,
+        file_md5
+    );
+    let reply = exchange(&socket, &invitation, INVITE_ATTEMPTS)?.ok_or_else(|| {
+        AppError::Timeout(format!(
+            "No answer from {}:{}; is the board running ArduinoOTA on this network?",
+            options.host, options.port
+        ))
+    })?;
+    task.checkpoint()?;
+
+    if let Some(nonce) = reply.strip_prefix("AUTH") {
+        let password = options.password.ok_or_else(|| {
+            AppError::InvalidInput("The board requires an OTA password".to_string())
+        })?;
+        let cnonce =
+            md5_hex(format!("{}{}{}{}", file_name, size, file_md5, options.host).as_bytes());
+        let response = format!(
+            "200 {} {}\n",
+            cnonce,
+            auth_response(password, nonce.trim(), &cnonce)
+        );
+        match exchange(&socket, &response, 1)? {
+            Some(reply) if reply == "OK" => {}
+            Some(_) => {
+                return Err(AppError::InvalidInput(
+                    "The board rejected the OTA password".to_string(),
+                ))
+            }
+            None => {
+                return Err(AppError::Timeout(
+                    "No answer to the OTA password".to_string(),
+                ))
+            }
+        }
+    } else if reply != "OK" {
+        return Err(AppError::Connection(format!(
+            "Unexpected answer to the invitation: {:?}",
+            reply
+        )));
+    }
+
+    let mut stream = accept(&listener, task)?;
+    stream
+        .set_read_timeout(Some(CHUNK_TIMEOUT))
+        .map_err(io_error)?;
+    stream
+        .set_write_timeout(Some(CHUNK_TIMEOUT))
+        .map_err(io_error)?;
+
+    // The board acknowledges every chunk with its byte count, and with
+    // `OK` once the image is complete and verified
+    task.phase("writing");
+    let mut buf = [0u8; 32];
+    let mut done = false;
+    let mut sent = 0;
+    for chunk in image.chunks(CHUNK_SIZE) {
+        task.checkpoint()?;
+        stream.write_all(chunk).map_err(io_error)?;
+        let n = stream.read(&mut buf).map_err(io_error)?;
+        done = String::from_utf8_lossy(&buf[..n]).contains("OK");
+        sent += chunk.len();
+        task.progress(sent as u64, size as u64);
+    }
+
+    task.phase("verifying");
+    stream
+        .set_read_timeout(Some(RESULT_TIMEOUT))
+        .map_err(io_error)?;
+    while !done {
+        let n = stream.read(&mut buf).map_err(io_error)?;
+        let text = String::from_utf8_lossy(&buf[..n]);
+        if text.contains("OK") {
+            done = true;
+        } else if text.contains("ERROR") {
+            return Err(AppError::Flasher(format!(
+                "The board refused the image: {}",
+                text.trim()
+            )));
+        } else if n == 0 {
+            return Err(AppError::Flasher(
+                "The board closed the connection before confirming the image".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
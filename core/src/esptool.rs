@@ -0,0 +1,71 @@
+//! The `esptool.py` invocation equivalent to each flasher operation, for
+//! reproducing it from a script or pasting it into a bug report. Both tools
+//! sync at 115200 baud and only then switch to `--baud`, so the rates line up.
+
+use crate::chips;
+use crate::models::FlashSegment;
+
+/// Baud the ROM loader syncs at, and the rate erase and chip reads stay at.
+pub const DEFAULT_BAUD: u32 = 115200;
+
+/// `--chip` value for a model name such as `esp32s3` or `ESP32-S3`; `auto`
+/// when the chip has not been detected yet.
+fn chip_arg(chip_model: Option<&str>) -> String {
+    chip_model
+        .map(chips::model_key)
+        .filter(|model| !model.is_empty())
+        .unwrap_or_else(|| "auto".to_string())
+}
+
+/// Quotes `arg` for a POSIX shell, leaving plain words alone.
+fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+fn base(chip_model: Option<&str>, port_name: &str, baud_rate: u32) -> String {
+    format!(
+        "esptool.py --chip {} --port {} --baud {}",
+        chip_arg(chip_model),
+        quote(port_name),
+        baud_rate
+    )
+}
+
+/// `write_flash` for `segments`, followed by the hard reset the app does.
+pub fn write_flash(
+    chip_model: Option<&str>,
+    port_name: &str,
+    baud_rate: u32,
+    segments: &[FlashSegment],
+) -> String {
+    let mut command = format!(
+        "{} --before default_reset --after hard_reset write_flash",
+        base(chip_model, port_name, baud_rate)
+    );
+    for segment in segments {
+        command.push_str(&format!(
+            " 0x{:X} {}",
+            segment.address,
+            quote(&segment.path)
+        ));
+    }
+    command
+}
+
+pub fn erase_flash(chip_model: Option<&str>, port_name: &str) -> String {
+    format!("{} erase_flash", base(chip_model, port_name, DEFAULT_BAUD))
+}
+
+/// `flash_id` prints the chip, features, MAC and flash size, the closest
+/// match to reading chip info.
+pub fn flash_id(chip_model: Option<&str>, port_name: &str) -> String {
+    format!("{} flash_id", base(chip_model, port_name, DEFAULT_BAUD))
+}
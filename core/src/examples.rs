@@ -0,0 +1,39 @@
+//! Catalog of known-good test images bundled under `firmware/examples`, to
+//! check a board works before suspecting one's own firmware.
+
+use crate::chips;
+use crate::models::AppError;
+
+/// Example ids, simplest first.
+pub const EXAMPLES: [&str; 3] = ["hello-world", "blink", "wifi-scan"];
+
+/// Whether `example` can run on `model`; the Wi-Fi scan needs a radio.
+pub fn supports(example: &str, model: &str) -> bool {
+    match example {
+        "wifi-scan" => chips::capabilities(model).is_some_and(|caps| caps.wifi),
+        _ => EXAMPLES.contains(&example) && chips::capabilities(model).is_some(),
+    }
+}
+
+/// Examples that can run on `model`, in catalog order.
+pub fn for_chip(model: &str) -> Vec<&'static str> {
+    EXAMPLES
+        .into_iter()
+        .filter(|example| supports(example, model))
+        .collect()
+}
+
+/// Resource path of the merged image, e.g. `firmware/examples/blink-esp32s3.bin`.
+pub fn image_path(example: &str, model: &str) -> Result<String, AppError> {
+    if !supports(example, model) {
+        return Err(AppError::InvalidInput(format!(
+            "No {} example for {}",
+            example, model
+        )));
+    }
+    Ok(format!(
+        "firmware/examples/{}-{}.bin",
+        example,
+        model.to_ascii_lowercase().replace('-', "")
+    ))
+}
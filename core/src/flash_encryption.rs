@@ -0,0 +1,170 @@
+//! Pre-encrypting images for chips with flash encryption, like
+//! `espsecure.py encrypt_flash_data`. In release mode the ROM no longer
+//! encrypts what it is sent, so factory programming writes ciphertext made
+//! with the device's key for the exact offset it lands at.
+//!
+//! This is the XTS-AES scheme of the ESP32-S2 and later chips: every
+//! 128-byte unit of flash is one XTS data unit, tweaked by its address,
+//! with the bytes reversed on the way in and out as the hardware does.
+//! The original ESP32 (AES-256 with a per-address key tweak) and the
+//! ESP32-C2 (an XTS-AES-128 key derived from a 128-bit eFuse key with
+//! SHA-256) encrypt differently and are refused rather than given
+//! ciphertext they can't boot.
+
+use crate::chips;
+use crate::models::AppError;
+use aes::cipher::consts::U16;
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockDecrypt, BlockEncrypt, BlockSizeUser, KeyInit};
+use aes::{Aes128, Aes256};
+use std::path::Path;
+
+const UNIT: usize = 0x80;
+const BLOCK: usize = 16;
+
+/// Encrypts `data`, whole 16-byte blocks, as `chip` would store it from
+/// `address` on.
+pub fn encrypt(chip: &str, data: &[u8], address: u32, key: &[u8]) -> Result<Vec<u8>, AppError> {
+    transform(chip, data, address, key, false)
+}
+
+/// Decrypts raw flash contents read back from `address` of `chip`.
+pub fn decrypt(chip: &str, data: &[u8], address: u32, key: &[u8]) -> Result<Vec<u8>, AppError> {
+    transform(chip, data, address, key, true)
+}
+
+/// Refuses chips that don't use the XTS-AES scheme, naming the chip.
+fn check_chip(chip: &str) -> Result<(), AppError> {
+    let unsupported = |scheme: &str| {
+        Err(AppError::InvalidInput(format!(
+            "{} flash encryption is {}, which isn't supported",
+            chip, scheme
+        )))
+    };
+    match chips::model_key(chip).as_str() {
+        "esp32" => unsupported("AES-256 with a per-address key tweak"),
+        "esp32c2" => unsupported("XTS-AES-128 with a SHA-256-derived key"),
+        _ if chips::capabilities(chip).is_none() => Err(AppError::InvalidInput(format!(
+            "Unknown chip for flash encryption: {}",
+            chip
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn transform(
+    chip: &str,
+    data: &[u8],
+    address: u32,
+    key: &[u8],
+    decrypt: bool,
+) -> Result<Vec<u8>, AppError> {
+    check_chip(chip)?;
+    if !(address as usize).is_multiple_of(BLOCK) {
+        return Err(AppError::InvalidInput(format!(
+            "Encrypted flash offset 0x{:X} must be a multiple of 16",
+            address
+        )));
+    }
+    // A cut-off last block could never be decrypted again
+    if !data.len().is_multiple_of(BLOCK) {
+        return Err(AppError::InvalidInput(format!(
+            "Encrypted data is {} bytes, not a multiple of 16",
+            data.len()
+        )));
+    }
+    let (key1, key2) = key.split_at(key.len() / 2);
+    match key.len() {
+        32 => Ok(units::<Aes128>(data, address, key1, key2, decrypt)),
+        64 => Ok(units::<Aes256>(data, address, key1, key2, decrypt)),
+        n => Err(AppError::InvalidInput(format!(
+            "Flash encryption keys are 32 (XTS-AES-128) or 64 (XTS-AES-256) bytes, not {}",
+            n
+        ))),
+    }
+}
+
+fn units<C>(data: &[u8], address: u32, key1: &[u8], key2: &[u8], decrypt: bool) -> Vec<u8>
+where
+    C: BlockEncrypt + BlockDecrypt + KeyInit + BlockSizeUser<BlockSize = U16>,
+{
+    let data_cipher = C::new_from_slice(key1).unwrap();
+    let tweak_cipher = C::new_from_slice(key2).unwrap();
+
+    // Zero-pad out to whole units; flash past the ends is left as it is
+    let pad_left = address as usize % UNIT;
+    let mut buf = vec![0u8; pad_left];
+    buf.extend_from_slice(data);
+    buf.resize(buf.len().div_ceil(UNIT) * UNIT, 0);
+
+    let base = address & !(UNIT as u32 - 1);
+    for (i, unit) in buf.chunks_exact_mut(UNIT).enumerate() {
+        let mut tweak = [0u8; BLOCK];
+        tweak[..4].copy_from_slice(&(base + (i * UNIT) as u32).to_le_bytes());
+        let mut tweak = GenericArray::from(tweak);
+        tweak_cipher.encrypt_block(&mut tweak);
+
+        unit.reverse();
+        for block in unit.chunks_exact_mut(BLOCK) {
+            let mut x = GenericArray::clone_from_slice(block);
+            x.iter_mut().zip(tweak.iter()).for_each(|(b, t)| *b ^= t);
+            if decrypt {
+                data_cipher.decrypt_block(&mut x);
+            } else {
+                data_cipher.encrypt_block(&mut x);
+            }
+            block
+                .iter_mut()
+                .zip(x.iter().zip(tweak.iter()))
+                .for_each(|(b, (x, t))| *b = x ^ t);
+            double(&mut tweak);
+        }
+        unit.reverse();
+    }
+    buf[pad_left..pad_left + data.len()].to_vec()
+}
+
+/// Multiplies the tweak by x in GF(2^128), little-endian as XTS orders it.
+fn double(tweak: &mut [u8]) {
+    let mut carry = 0;
+    for byte in tweak.iter_mut() {
+        let next = *byte >> 7;
+        *byte = (*byte << 1) | carry;
+        carry = next;
+    }
+    if carry != 0 {
+        tweak[0] ^= 0x87;
+    }
+}
+
+/// Encrypts the image at `path` for `address` of `chip` with the raw key
+/// in `key_path` (as `espsecure.py generate_flash_encryption_key` writes
+/// it) and saves it as `-encrypted.bin` next to the image, or to `output`.
+/// Returns the path written.
+pub fn encrypt_file(
+    chip: &str,
+    path: &Path,
+    address: u32,
+    key_path: &Path,
+    output: Option<&Path>,
+) -> Result<String, AppError> {
+    check_chip(chip)?;
+    let key = std::fs::read(key_path)?;
+    let mut data = std::fs::read(path)?;
+    if data.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "Firmware file is empty: {}",
+            path.display()
+        )));
+    }
+    // Encryption works on 16-byte blocks; pad like erased flash
+    data.resize(data.len().div_ceil(BLOCK) * BLOCK, 0xff);
+    let encrypted = encrypt(chip, &data, address, &key)?;
+
+    let output = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{}-encrypted.bin", stem))
+    });
+    std::fs::write(&output, encrypted)?;
+    Ok(output.display().to_string())
+}
@@ -0,0 +1,57 @@
+//! How long writing images through the flasher stub takes: an estimate
+//! before connecting, and the time left once writing is under way.
+
+use crate::models::FlashEstimate;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+// Syncing, loading the stub and switching baud
+const CONNECT_SECS: f64 = 2.0;
+// Erasing and programming a typical SPI flash, when the UART isn't the
+// bottleneck
+const PROGRAM_BYTES_PER_SEC: f64 = 200.0 * 1024.0;
+// A start and a stop bit around each byte
+const BITS_PER_BYTE: f64 = 10.0;
+
+/// Bytes `data` takes on the wire, as the stub is sent it zlib compressed.
+/// espflash compresses harder, so this errs long.
+pub fn compressed_len(data: &[u8]) -> u64 {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+    // Writing into a Vec doesn't fail
+    let _ = encoder.write_all(data);
+    encoder
+        .finish()
+        .map_or(data.len() as u64, |out| out.len() as u64)
+}
+
+/// Seconds writing `bytes` that compress to `compressed` takes at
+/// `baud_rate`: the slower of sending them and the chip programming them,
+/// plus connecting when `connect` is set.
+pub fn estimate_secs(bytes: u64, compressed: u64, baud_rate: u32, connect: bool) -> u64 {
+    let wire = compressed as f64 * BITS_PER_BYTE / f64::from(baud_rate.max(1));
+    let program = bytes as f64 / PROGRAM_BYTES_PER_SEC;
+    let overhead = if connect { CONNECT_SECS } else { 0.0 };
+    (wire.max(program) + overhead).ceil() as u64
+}
+
+/// Estimate for writing all of `images` in one session at `baud_rate`,
+/// assuming every sector differs from the flash.
+pub fn estimate<'a>(images: impl IntoIterator<Item = &'a [u8]>, baud_rate: u32) -> FlashEstimate {
+    let (bytes, compressed) = images
+        .into_iter()
+        .fold((0, 0), |(bytes, compressed), data| {
+            (bytes + data.len() as u64, compressed + compressed_len(data))
+        });
+    FlashEstimate {
+        bytes,
+        compressed,
+        baud_rate,
+        secs: estimate_secs(bytes, compressed, baud_rate, true),
+    }
+}
+
+/// Seconds left for `remaining` bytes at the rate measured so far.
+pub fn remaining_secs(remaining: u64, bytes_per_sec: u64) -> Option<u64> {
+    (bytes_per_sec > 0).then(|| remaining.div_ceil(bytes_per_sec))
+}
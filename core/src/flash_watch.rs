@@ -0,0 +1,117 @@
+//! Watch-and-flash: notices when the images of a build are rebuilt, for an
+//! `idf.py flash monitor` loop with the build running in another terminal.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL: Duration = Duration::from_millis(250);
+/// How long rebuilt images must hold still before they are flashed; a
+/// build writes them over a while, and the bootloader and partition table
+/// after the app.
+pub const SETTLE: Duration = Duration::from_millis(750);
+
+/// Modification time and size of each watched file, `None` while missing.
+type Stamp = Vec<Option<(SystemTime, u64)>>;
+
+fn stamp(paths: &[PathBuf]) -> Stamp {
+    paths
+        .iter()
+        .map(|path| {
+            let meta = std::fs::metadata(path).ok()?;
+            Some((meta.modified().ok()?, meta.len()))
+        })
+        .collect()
+}
+
+/// Tells when watched files have been rebuilt: they differ from what was
+/// last flashed and haven't changed for [`SETTLE`].
+pub struct Rebuilds {
+    paths: Vec<PathBuf>,
+    flashed: Stamp,
+    // Changed files and when they were last seen changing
+    pending: Option<(Stamp, Instant)>,
+}
+
+impl Rebuilds {
+    /// Starts from the files as they are now, which count as flashed.
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let flashed = stamp(&paths);
+        Rebuilds {
+            paths,
+            flashed,
+            pending: None,
+        }
+    }
+
+    /// Looks at the files at `now`; true once a rebuild has settled. Files
+    /// missing midway through a clean build hold it back.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let current = stamp(&self.paths);
+        if current == self.flashed || current.iter().any(Option::is_none) {
+            self.pending = None;
+            return false;
+        }
+        match &self.pending {
+            Some((seen, since)) if *seen == current => {
+                if now.duration_since(*since) < SETTLE {
+                    return false;
+                }
+                self.flashed = current;
+                self.pending = None;
+                true
+            }
+            _ => {
+                self.pending = Some((current, now));
+                false
+            }
+        }
+    }
+}
+
+/// Calls back whenever a build is rewritten, until stopped or dropped.
+pub struct FlashWatch {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FlashWatch {
+    /// Watches `paths`, the images of one build; `on_rebuild` runs on the
+    /// watch thread, so a flash it does finishes before the next look.
+    pub fn start(paths: Vec<PathBuf>, on_rebuild: impl Fn() + Send + 'static) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let thread = std::thread::spawn(move || {
+            let mut rebuilds = Rebuilds::new(paths);
+            while !flag.load(Ordering::Relaxed) {
+                if rebuilds.poll(Instant::now()) {
+                    on_rebuild();
+                }
+                std::thread::sleep(POLL);
+            }
+        });
+        FlashWatch {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn stop(mut self) {
+        self.halt();
+    }
+
+    fn halt(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl Drop for FlashWatch {
+    fn drop(&mut self) {
+        self.halt();
+    }
+}
@@ -0,0 +1,310 @@
+//! One-file flash images built from an ELF, a partition table CSV and a
+//! bootloader, the way `idf.py build` followed by `esptool.py merge_bin`
+//! would lay them out. Handy for production lines that program a single
+//! file at offset 0x0.
+
+use crate::chips;
+use crate::elf2image::{self, ImageOptions};
+use crate::image_info;
+use crate::images;
+use crate::models::AppError;
+use crate::otadata::{self, PARTITION_TABLE_LEN, PARTITION_TABLE_OFFSET};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// ESP-IDF releases with bundled bootloaders, newest first.
+pub const IDF_VERSIONS: [&str; 2] = ["v5.3", "v5.1"];
+
+/// IDF's `partitions_singleapp.csv`, for builds that don't bring a table.
+pub const DEFAULT_PARTITIONS_CSV: &str = "\
+# Name,   Type, SubType, Offset,  Size, Flags
+nvs,      data, nvs,     0x9000,  0x6000,
+phy_init, data, phy,     0xf000,  0x1000,
+factory,  app,  factory, 0x10000, 1M,
+";
+
+const PARTITION_MAGIC: [u8; 2] = [0xaa, 0x50];
+const MD5_MAGIC: [u8; 2] = [0xeb, 0xeb];
+const LABEL_LEN: usize = 16;
+const APP_ALIGN: u32 = 0x10000;
+const DATA_ALIGN: u32 = 0x1000;
+const TYPE_APP: u8 = 0x00;
+const TYPE_DATA: u8 = 0x01;
+const HASH_APPENDED_AT: usize = 23;
+const HASH_LEN: usize = 32;
+
+/// Resource path of the bundled bootloader for `chip` from `idf_version`,
+/// e.g. `firmware/bootloaders/bootloader-esp32s3-v5.3.bin`.
+pub fn bootloader_resource(chip: &str, idf_version: &str) -> Result<String, AppError> {
+    if !IDF_VERSIONS.contains(&idf_version) {
+        return Err(AppError::InvalidInput(format!(
+            "No bootloaders bundled for ESP-IDF {}; use {}",
+            idf_version,
+            IDF_VERSIONS.join(" or ")
+        )));
+    }
+    let chip = chips::model_key(chip);
+    if chips::image_chip_id(&chip).is_none() {
+        return Err(AppError::InvalidInput(format!("Unknown chip {}", chip)));
+    }
+    Ok(format!(
+        "firmware/bootloaders/bootloader-{}-{}.bin",
+        chip, idf_version
+    ))
+}
+
+fn subtype(kind: u8, name: &str) -> Option<u8> {
+    let named = match (kind, name) {
+        (TYPE_APP, "factory") => 0x00,
+        (TYPE_APP, "test") => 0x20,
+        (TYPE_DATA, "ota") => 0x00,
+        (TYPE_DATA, "phy") => 0x01,
+        (TYPE_DATA, "nvs") => 0x02,
+        (TYPE_DATA, "coredump") => 0x03,
+        (TYPE_DATA, "nvs_keys") => 0x04,
+        (TYPE_DATA, "efuse") => 0x05,
+        (TYPE_DATA, "undefined") => 0x06,
+        (TYPE_DATA, "esphttpd") => 0x80,
+        (TYPE_DATA, "fat") => 0x81,
+        (TYPE_DATA, "spiffs") => 0x82,
+        (TYPE_DATA, "littlefs") => 0x83,
+        _ => {
+            if let Some(slot) = name.strip_prefix("ota_").filter(|_| kind == TYPE_APP) {
+                let slot: u8 = slot.parse().ok()?;
+                return (slot < 16).then_some(0x10 + slot);
+            }
+            return number(name).and_then(|n| u8::try_from(n).ok());
+        }
+    };
+    Some(named)
+}
+
+/// A decimal or `0x` number, with an optional `K` or `M` multiplier.
+fn number(text: &str) -> Option<u32> {
+    let text = text.trim();
+    let (digits, multiplier) = match text.char_indices().last()? {
+        (at, 'k' | 'K') => (&text[..at], 1024),
+        (at, 'm' | 'M') => (&text[..at], 1024 * 1024),
+        _ => (text, 1),
+    };
+    let value = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    value.checked_mul(multiplier)
+}
+
+/// The binary partition table for a `gen_esp32part.py` style CSV: MD5
+/// entry included, padded with 0xFF to its full length. Partitions without
+/// an offset follow the previous one, apps on a 64 KB boundary.
+pub fn partition_table(csv: &str) -> Result<Vec<u8>, AppError> {
+    let mut table = Vec::new();
+    // The first sector after the table
+    let first = PARTITION_TABLE_OFFSET + PARTITION_TABLE_LEN.next_multiple_of(DATA_ALIGN);
+    let mut next = first;
+    let mut placed: Vec<(String, u32, u32)> = Vec::new();
+    for (i, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |what: String| {
+            AppError::InvalidInput(format!("Partition table line {}: {}", i + 1, what))
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 5 {
+            return Err(invalid(
+                "expected name, type, subtype, offset and size".to_string(),
+            ));
+        }
+        let name = fields[0];
+        if name.is_empty() || name.len() > LABEL_LEN {
+            return Err(invalid(format!("name {:?} must be 1 to 16 bytes", name)));
+        }
+        let kind = match fields[1] {
+            "app" => TYPE_APP,
+            "data" => TYPE_DATA,
+            other => number(other)
+                .and_then(|n| u8::try_from(n).ok())
+                .ok_or_else(|| invalid(format!("unknown type {}", other)))?,
+        };
+        let sub = subtype(kind, fields[2])
+            .ok_or_else(|| invalid(format!("unknown subtype {}", fields[2])))?;
+        let align = if kind == TYPE_APP {
+            APP_ALIGN
+        } else {
+            DATA_ALIGN
+        };
+        let offset = match fields[3] {
+            "" => next.next_multiple_of(align),
+            text => number(text).ok_or_else(|| invalid(format!("bad offset {}", text)))?,
+        };
+        if !offset.is_multiple_of(align) {
+            return Err(invalid(format!(
+                "offset 0x{:x} is not aligned to 0x{:x}",
+                offset, align
+            )));
+        }
+        let size = number(fields[4])
+            .filter(|&size| size > 0)
+            .ok_or_else(|| invalid(format!("bad size {}", fields[4])))?;
+        let end = offset
+            .checked_add(size)
+            .ok_or_else(|| invalid("ends past 4 GB".to_string()))?;
+        if offset < first {
+            return Err(invalid(format!(
+                "{} overlaps the bootloader or partition table",
+                name
+            )));
+        }
+        if let Some((other, _, _)) = placed
+            .iter()
+            .find(|(_, start, stop)| offset < *stop && *start < end)
+        {
+            return Err(invalid(format!("{} overlaps {}", name, other)));
+        }
+        let mut flags = 0u32;
+        for flag in fields.get(5).unwrap_or(&"").split(':').map(str::trim) {
+            flags |= match flag {
+                "" => 0,
+                "encrypted" => 1,
+                "readonly" => 2,
+                other => return Err(invalid(format!("unknown flag {}", other))),
+            };
+        }
+
+        table.extend_from_slice(&PARTITION_MAGIC);
+        table.extend_from_slice(&[kind, sub]);
+        table.extend_from_slice(&offset.to_le_bytes());
+        table.extend_from_slice(&size.to_le_bytes());
+        let mut label = [0u8; LABEL_LEN];
+        label[..name.len()].copy_from_slice(name.as_bytes());
+        table.extend_from_slice(&label);
+        table.extend_from_slice(&flags.to_le_bytes());
+        placed.push((name.to_string(), offset, end));
+        next = end;
+    }
+    if placed.is_empty() {
+        return Err(AppError::InvalidInput(
+            "The partition table CSV lists no partitions".to_string(),
+        ));
+    }
+
+    let digest = Md5::digest(&table);
+    table.extend_from_slice(&MD5_MAGIC);
+    table.extend_from_slice(&[0xff; 14]);
+    table.extend_from_slice(&digest);
+    if table.len() > PARTITION_TABLE_LEN as usize {
+        return Err(AppError::InvalidInput(format!(
+            "{} partitions don't fit in the partition table",
+            placed.len()
+        )));
+    }
+    table.resize(PARTITION_TABLE_LEN as usize, 0xff);
+    Ok(table)
+}
+
+/// Copies the app's flash parameters into the bootloader header, which the
+/// ROM reads to set up flash, and refreshes the appended SHA-256 to match.
+fn patch_bootloader(bootloader: &[u8], app: &[u8], chip: &str) -> Result<Vec<u8>, AppError> {
+    let info = image_info::parse(bootloader)
+        .map_err(|e| AppError::InvalidInput(format!("Bootloader: {}", e.message())))?;
+    image_info::check(bootloader)?;
+    if info.chip.as_deref() != Some(chip) {
+        return Err(AppError::InvalidInput(format!(
+            "The bootloader was built for {}, not for {}",
+            info.chip.as_deref().unwrap_or("an unknown chip"),
+            chip
+        )));
+    }
+    let mut patched = bootloader[..info.image_len].to_vec();
+    patched[2..4].copy_from_slice(&app[2..4]);
+    if patched[HASH_APPENDED_AT] == 1 {
+        let end = patched.len() - HASH_LEN;
+        let digest = Sha256::digest(&patched[..end]);
+        patched[end..].copy_from_slice(&digest);
+    }
+    Ok(patched)
+}
+
+/// Puts the bootloader, the table from `partitions_csv` and the app built
+/// from `elf` where the chip and the table want them, gaps filled with
+/// 0xFF as erased flash reads. The image ends with the app.
+pub fn assemble(
+    elf: &[u8],
+    bootloader: &[u8],
+    partitions_csv: &str,
+    options: &ImageOptions,
+) -> Result<Vec<u8>, AppError> {
+    let chip = chips::model_key(&options.chip);
+    let app = elf2image::convert(elf, options)?;
+    let bootloader = patch_bootloader(bootloader, &app, &chip)?;
+    let table = partition_table(partitions_csv)?;
+
+    let bootloader_at = chips::bootloader_offset(&chip) as usize;
+    let table_at = PARTITION_TABLE_OFFSET as usize;
+    if bootloader_at + bootloader.len() > table_at {
+        return Err(AppError::InvalidInput(format!(
+            "The bootloader ({} bytes) runs into the partition table at 0x{:x}",
+            bootloader.len(),
+            table_at
+        )));
+    }
+    let app_at = images::app_offset(&table).ok_or_else(|| {
+        AppError::InvalidInput("The partition table has no app partition".to_string())
+    })?;
+    let partitions = otadata::partitions(&table);
+    let slot = partitions
+        .iter()
+        .find(|p| p.offset == app_at)
+        .map(|p| p.size)
+        .unwrap_or_default();
+    if app.len() > slot as usize {
+        return Err(AppError::InvalidInput(format!(
+            "The app ({} bytes) doesn't fit its 0x{:x} byte partition",
+            app.len(),
+            slot
+        )));
+    }
+    let flash_bytes = number(options.flash_size.trim_end_matches('B')).unwrap_or(u32::MAX);
+    if let Some(p) = partitions
+        .iter()
+        .find(|p| p.offset as u64 + p.size as u64 > flash_bytes as u64)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Partition {} ends past the {} flash",
+            p.label, options.flash_size
+        )));
+    }
+
+    let app_at = app_at as usize;
+    let mut image = vec![0xff; app_at + app.len()];
+    image[bootloader_at..bootloader_at + bootloader.len()].copy_from_slice(&bootloader);
+    image[table_at..table_at + table.len()].copy_from_slice(&table);
+    image[app_at..].copy_from_slice(&app);
+    Ok(image)
+}
+
+/// [`assemble`] on files, writing the image to `output`. The bundled table
+/// [`DEFAULT_PARTITIONS_CSV`] is used when `partitions_csv` is `None`.
+pub fn assemble_file(
+    elf: &Path,
+    bootloader: &Path,
+    partitions_csv: Option<&Path>,
+    options: &ImageOptions,
+    output: &Path,
+) -> Result<usize, AppError> {
+    let csv = match partitions_csv {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => DEFAULT_PARTITIONS_CSV.to_string(),
+    };
+    let image = assemble(
+        &std::fs::read(elf)?,
+        &std::fs::read(bootloader)?,
+        &csv,
+        options,
+    )?;
+    std::fs::write(output, &image)?;
+    Ok(image.len())
+}
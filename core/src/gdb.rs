@@ -0,0 +1,580 @@
+//! GDB on top of [`crate::openocd`]: runs the toolchain's GDB on the app
+//! ELF and drives it over GDB/MI, enough for breakpoints, halting and
+//! backtraces without an IDE.
+
+use crate::chips;
+use crate::idf;
+use crate::models::{AppError, GdbStatus, GdbTarget, StackFrame};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+// Attaching halts the chip through OpenOCD, which can take a while
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const EXIT_GRACE: Duration = Duration::from_secs(1);
+// Console lines kept for the panel
+const LOG_LIMIT: usize = 500;
+
+/// A value in a GDB/MI record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MiValue {
+    Const(String),
+    Tuple(Vec<(String, MiValue)>),
+    /// Items of `[frame={...},frame={...}]` lose their names
+    List(Vec<MiValue>),
+}
+
+impl MiValue {
+    /// Field `name` of a tuple.
+    pub fn get(&self, name: &str) -> Option<&MiValue> {
+        match self {
+            MiValue::Tuple(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MiValue::Const(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Field `name` of a tuple, if it is a string.
+    pub fn str(&self, name: &str) -> Option<&str> {
+        self.get(name)?.as_str()
+    }
+}
+
+/// One line of GDB/MI output.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MiRecord {
+    /// `^done`, `^error` and the like, answering the command sent with
+    /// `token`
+    Result {
+        token: Option<u32>,
+        class: String,
+        results: MiValue,
+    },
+    /// `*running`, `*stopped`: the target changed state
+    Exec { class: String, results: MiValue },
+    /// `=` and `+` notifications
+    Notify { class: String, results: MiValue },
+    /// `~`, `@` and `&` output meant for a person
+    Stream(String),
+    /// `(gdb)`
+    Prompt,
+    /// Anything else GDB prints
+    Other(String),
+}
+
+struct Cursor<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn new(text: &str) -> Cursor<'_> {
+        Cursor {
+            text: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// A C string, with GDB's octal escapes for bytes outside ASCII.
+    fn cstring(&mut self) -> String {
+        let mut bytes = Vec::new();
+        self.eat(b'"');
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let Some(escaped) = self.peek() else { break };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b't' => bytes.push(b'\t'),
+                        b'r' => bytes.push(b'\r'),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                let Some(digit @ b'0'..=b'7') = self.peek() else {
+                                    break;
+                                };
+                                value = value * 8 + u32::from(digit - b'0');
+                                self.pos += 1;
+                            }
+                            bytes.push(value as u8);
+                        }
+                        other => bytes.push(other),
+                    }
+                }
+                _ => bytes.push(c),
+            }
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    fn name(&mut self) -> String {
+        let start = self.pos;
+        while !matches!(self.peek(), None | Some(b'=' | b',' | b'}' | b']')) {
+            self.pos += 1;
+        }
+        String::from_utf8_lossy(&self.text[start..self.pos]).into_owned()
+    }
+
+    fn value(&mut self) -> MiValue {
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                MiValue::Tuple(self.results(b'}'))
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                while !matches!(self.peek(), None | Some(b']')) {
+                    if !matches!(self.peek(), Some(b'"' | b'{' | b'[')) {
+                        self.name();
+                        self.eat(b'=');
+                    }
+                    items.push(self.value());
+                    if !self.eat(b',') {
+                        break;
+                    }
+                }
+                self.eat(b']');
+                MiValue::List(items)
+            }
+            _ => MiValue::Const(self.cstring()),
+        }
+    }
+
+    /// `name=value` pairs up to `end`, which is consumed.
+    fn results(&mut self, end: u8) -> Vec<(String, MiValue)> {
+        let mut fields = Vec::new();
+        while self.peek().is_some_and(|c| c != end) {
+            let name = self.name();
+            if !self.eat(b'=') {
+                break;
+            }
+            fields.push((name, self.value()));
+            if !self.eat(b',') {
+                break;
+            }
+        }
+        self.eat(end);
+        fields
+    }
+}
+
+/// Parses one line GDB printed in MI mode.
+pub fn parse_record(line: &str) -> MiRecord {
+    let line = line.trim_end();
+    if line == "(gdb)" {
+        return MiRecord::Prompt;
+    }
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    let token = line[..digits].parse().ok();
+    let rest = &line[digits..];
+    match rest.bytes().next() {
+        Some(b'~' | b'@' | b'&') => MiRecord::Stream(Cursor::new(&rest[1..]).cstring()),
+        Some(kind @ (b'^' | b'*' | b'=' | b'+')) => {
+            let (class, tail) = rest[1..].split_once(',').unwrap_or((&rest[1..], ""));
+            let class = class.to_string();
+            let results = MiValue::Tuple(Cursor::new(tail).results(b'\n'));
+            match kind {
+                b'^' => MiRecord::Result {
+                    token,
+                    class,
+                    results,
+                },
+                b'*' => MiRecord::Exec { class, results },
+                _ => MiRecord::Notify { class, results },
+            }
+        }
+        _ => MiRecord::Other(line.to_string()),
+    }
+}
+
+/// A `frame={...}` tuple.
+pub fn frame(value: &MiValue) -> StackFrame {
+    StackFrame {
+        level: value.str("level").and_then(|l| l.parse().ok()).unwrap_or(0),
+        address: value.str("addr").unwrap_or_default().to_string(),
+        // GDB says ?? for code it has no symbols for
+        function: value.str("func").filter(|f| *f != "??").map(str::to_string),
+        file: value.str("file").map(str::to_string),
+        line: value.str("line").and_then(|l| l.parse().ok()),
+    }
+}
+
+/// The frames of a `-stack-list-frames` answer.
+pub fn frames(results: &MiValue) -> Vec<StackFrame> {
+    match results.get("stack") {
+        Some(MiValue::List(items)) => items.iter().map(frame).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The bytes of a `-data-read-memory-bytes` answer: its first block, which
+/// is all of it unless part of the range couldn't be read.
+pub fn memory(results: &MiValue) -> Option<Vec<u8>> {
+    let Some(MiValue::List(blocks)) = results.get("memory") else {
+        return None;
+    };
+    let hex = blocks.first()?.str("contents")?;
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// GDB for `model` and the ESP-IDF tool it ships in: one per Xtensa chip,
+/// one for every RISC-V chip.
+pub fn program(model: &str) -> Option<(&'static str, String)> {
+    let key = chips::model_key(model);
+    match key.as_str() {
+        "esp32" | "esp32s2" | "esp32s3" => {
+            Some(("xtensa-esp-elf-gdb", format!("xtensa-{}-elf-gdb", key)))
+        }
+        "esp32c2" | "esp32c3" | "esp32c5" | "esp32c6" | "esp32c61" | "esp32h2" | "esp32p4" => {
+            Some(("riscv32-esp-elf-gdb", "riscv32-esp-elf-gdb".to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Finds the GDB for `model` on `PATH` or as installed by ESP-IDF.
+pub fn find(model: &str) -> Result<PathBuf, AppError> {
+    let (tool, name) =
+        program(model).ok_or_else(|| AppError::InvalidInput(format!("No GDB for {}", model)))?;
+    idf::find_tool(tool, &name).ok_or_else(|| {
+        AppError::Config(format!(
+            "{} was not found on the PATH or in ~/.espressif",
+            name
+        ))
+    })
+}
+
+/// Quotes `text` as an MI C string.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Something that happened in a session.
+#[derive(Debug, Clone)]
+pub enum GdbEvent {
+    Output(String),
+    Target(GdbTarget),
+}
+
+type Events = Arc<dyn Fn(GdbEvent) + Send + Sync>;
+// Token, class and results of a `^` record
+type Answer = (Option<u32>, String, MiValue);
+
+struct Shared {
+    log: VecDeque<String>,
+    target: GdbTarget,
+}
+
+fn output(shared: &Mutex<Shared>, events: &Events, line: &str) {
+    {
+        let mut shared = shared.lock().unwrap();
+        if shared.log.len() == LOG_LIMIT {
+            shared.log.pop_front();
+        }
+        shared.log.push_back(line.to_string());
+    }
+    events(GdbEvent::Output(line.to_string()));
+}
+
+fn set_target(shared: &Mutex<Shared>, events: &Events, target: GdbTarget) {
+    shared.lock().unwrap().target = target.clone();
+    events(GdbEvent::Target(target));
+}
+
+fn read_mi(
+    stream: impl Read + Send + 'static,
+    shared: Arc<Mutex<Shared>>,
+    answers: Sender<Answer>,
+    events: Events,
+) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            match parse_record(&line) {
+                MiRecord::Result {
+                    token,
+                    class,
+                    results,
+                } => {
+                    if let Some(message) = results.str("msg").filter(|_| class == "error") {
+                        output(&shared, &events, message);
+                    }
+                    // Nobody waits for answers that came too late
+                    answers.send((token, class, results)).ok();
+                }
+                MiRecord::Exec { class, results } => {
+                    let state = match class.as_str() {
+                        "running" => "running",
+                        "stopped"
+                            if results
+                                .str("reason")
+                                .is_some_and(|r| r.starts_with("exited")) =>
+                        {
+                            "exited"
+                        }
+                        "stopped" => "stopped",
+                        _ => continue,
+                    };
+                    let target = GdbTarget {
+                        state: state.to_string(),
+                        reason: results.str("reason").map(str::to_string),
+                        frame: results.get("frame").map(frame),
+                    };
+                    set_target(&shared, &events, target);
+                }
+                MiRecord::Stream(text) => {
+                    for line in text.lines() {
+                        output(&shared, &events, line);
+                    }
+                }
+                MiRecord::Other(text) => output(&shared, &events, &text),
+                MiRecord::Notify { .. } | MiRecord::Prompt => {}
+            }
+        }
+    });
+}
+
+fn read_stderr(stream: impl Read + Send + 'static, shared: Arc<Mutex<Shared>>, events: Events) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            output(&shared, &events, &line);
+        }
+    });
+}
+
+/// A GDB attached to OpenOCD, debugging one ELF.
+pub struct Gdb {
+    child: Child,
+    stdin: ChildStdin,
+    answers: Receiver<Answer>,
+    shared: Arc<Mutex<Shared>>,
+    events: Events,
+    elf: PathBuf,
+    token: u32,
+}
+
+impl Gdb {
+    /// Starts `program` on `elf` and attaches it to OpenOCD's GDB server
+    /// on `gdb_port`, which halts the chip. `on_event` runs on reader
+    /// threads.
+    pub fn start(
+        program: &Path,
+        elf: &Path,
+        gdb_port: u16,
+        on_event: impl Fn(GdbEvent) + Send + Sync + 'static,
+    ) -> Result<Self, AppError> {
+        if !elf.is_file() {
+            return Err(AppError::InvalidInput(format!(
+                "{} is not a file",
+                elf.display()
+            )));
+        }
+        let mut child = Command::new(program)
+            .arg("--interpreter=mi2")
+            .arg("--nx")
+            .arg("--quiet")
+            .arg(elf)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| AppError::Io(format!("Cannot run {}: {}", program.display(), e)))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| AppError::Internal("GDB has no stdin".to_string()))?;
+
+        let shared = Arc::new(Mutex::new(Shared {
+            log: VecDeque::new(),
+            target: GdbTarget::default(),
+        }));
+        let events: Events = Arc::new(on_event);
+        let (sender, answers) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            read_mi(stdout, shared.clone(), sender, events.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            read_stderr(stderr, shared.clone(), events.clone());
+        }
+        let mut gdb = Gdb {
+            child,
+            stdin,
+            answers,
+            shared,
+            events,
+            elf: elf.to_path_buf(),
+            token: 0,
+        };
+
+        let attach = format!("-target-select extended-remote :{}", gdb_port);
+        if let Err(e) = gdb.command(&attach, CONNECT_TIMEOUT) {
+            return Err(AppError::Connection(format!(
+                "GDB cannot attach to OpenOCD on port {}: {}",
+                gdb_port,
+                e.message()
+            )));
+        }
+        {
+            // Attaching halts the chip, whether or not GDB said so
+            let mut shared = gdb.shared.lock().unwrap();
+            if shared.target.state.is_empty() {
+                shared.target.state = "stopped".to_string();
+            }
+        }
+        info!("GDB attached to :{} with {}", gdb_port, elf.display());
+        Ok(gdb)
+    }
+
+    /// Sends an MI command and waits for its answer.
+    fn command(&mut self, command: &str, timeout: Duration) -> Result<MiValue, AppError> {
+        self.token += 1;
+        let token = self.token;
+        debug!("GDB <- {}{}", token, command);
+        writeln!(self.stdin, "{}{}", token, command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| AppError::Connection(format!("GDB is gone: {}", e)))?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.answers.recv_timeout(left) {
+                Ok((Some(answered), class, results)) if answered == token => {
+                    if class == "error" {
+                        let message = results.str("msg").unwrap_or("GDB refused");
+                        return Err(AppError::InvalidInput(message.to_string()));
+                    }
+                    return Ok(results);
+                }
+                // Late answers to commands that timed out
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(AppError::Timeout(format!("GDB did not answer {}", command)))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(AppError::Connection("GDB exited".to_string()))
+                }
+            }
+        }
+    }
+
+    /// Runs a command typed at the console; what it prints goes to the log.
+    pub fn console(&mut self, command: &str) -> Result<(), AppError> {
+        let command = format!("-interpreter-exec console {}", quote(command));
+        self.command(&command, COMMAND_TIMEOUT).map(|_| ())
+    }
+
+    /// Sets a breakpoint at `location` (`app_main`, `main.c:42`), kept
+    /// pending until the code is there.
+    pub fn break_at(&mut self, location: &str) -> Result<(), AppError> {
+        let command = format!("-break-insert -f {}", quote(location));
+        self.command(&command, COMMAND_TIMEOUT).map(|_| ())
+    }
+
+    pub fn resume(&mut self) -> Result<(), AppError> {
+        self.command("-exec-continue", COMMAND_TIMEOUT).map(|_| ())
+    }
+
+    /// Halts the running chip; it reports where once it has stopped.
+    pub fn halt(&mut self) -> Result<(), AppError> {
+        self.command("-exec-interrupt", COMMAND_TIMEOUT).map(|_| ())
+    }
+
+    /// Resets the chip and holds it at the first instruction, so that
+    /// resuming runs into breakpoints from the start.
+    pub fn reset(&mut self) -> Result<(), AppError> {
+        self.console("monitor reset halt")?;
+        // GDB still holds the registers from before the reset
+        self.console("maintenance flush register-cache")?;
+        let target = GdbTarget {
+            state: "stopped".to_string(),
+            reason: Some("reset".to_string()),
+            frame: None,
+        };
+        set_target(&self.shared, &self.events, target);
+        Ok(())
+    }
+
+    /// `len` bytes of the halted chip's memory from `address`.
+    pub fn read_memory(&mut self, address: u32, len: u32) -> Result<Vec<u8>, AppError> {
+        let command = format!("-data-read-memory-bytes {:#x} {}", address, len);
+        let results = self.command(&command, COMMAND_TIMEOUT)?;
+        memory(&results)
+            .filter(|bytes| bytes.len() == len as usize)
+            .ok_or_else(|| AppError::InvalidInput(format!("Cannot read {:#x}", address)))
+    }
+
+    /// The call stack of the stopped chip, innermost frame first.
+    pub fn backtrace(&mut self) -> Result<Vec<StackFrame>, AppError> {
+        let results = self.command("-stack-list-frames", COMMAND_TIMEOUT)?;
+        Ok(frames(&results))
+    }
+
+    pub fn status(&mut self) -> GdbStatus {
+        let running = matches!(self.child.try_wait(), Ok(None));
+        let shared = self.shared.lock().unwrap();
+        GdbStatus {
+            running,
+            elf: Some(self.elf.display().to_string()),
+            target: shared.target.clone(),
+            log: shared.log.iter().cloned().collect(),
+        }
+    }
+
+    /// Quits GDB, killing it if it doesn't go.
+    pub fn stop(&mut self) {
+        if !matches!(self.child.try_wait(), Ok(None)) {
+            return;
+        }
+        writeln!(self.stdin, "-gdb-exit").ok();
+        self.stdin.flush().ok();
+        let deadline = Instant::now() + EXIT_GRACE;
+        while Instant::now() < deadline {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                info!("GDB exited");
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        if let Err(e) = self.child.kill() {
+            warn!("Cannot stop GDB: {}", e);
+        }
+        self.child.wait().ok();
+    }
+}
+
+impl Drop for Gdb {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
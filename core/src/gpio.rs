@@ -0,0 +1,65 @@
+//! Client for the GPIO helper firmware (`firmware/gpio-helper`), spoken over
+//! a running monitor session: one request line, one `OK`/`ERR` reply.
+
+use crate::models::{AppError, PinReading};
+use crate::monitor::Monitor;
+use std::time::Duration;
+
+/// Protocol name and version the helper reports to `PING`.
+pub const PROTOCOL: &str = "gpio-helper 1";
+/// Pin modes understood by `MODE`.
+pub const MODES: [&str; 5] = ["off", "in", "pullup", "out", "adc"];
+
+const REPLY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Checks the helper is running on the monitored board and returns the
+/// protocol it reports.
+pub fn ping(monitor: &Monitor) -> Result<String, AppError> {
+    let reply = monitor.request("PING", REPLY_TIMEOUT)?;
+    if reply != PROTOCOL {
+        return Err(AppError::Connection(format!(
+            "Expected {:?} from the GPIO helper, got {:?}",
+            PROTOCOL, reply
+        )));
+    }
+    Ok(reply)
+}
+
+pub fn set_mode(monitor: &Monitor, gpio: u32, mode: &str) -> Result<(), AppError> {
+    if !MODES.contains(&mode) {
+        return Err(AppError::InvalidInput(format!(
+            "Unknown pin mode {:?}",
+            mode
+        )));
+    }
+    monitor.request(&format!("MODE {} {}", gpio, mode), REPLY_TIMEOUT)?;
+    Ok(())
+}
+
+pub fn write(monitor: &Monitor, gpio: u32, high: bool) -> Result<(), AppError> {
+    monitor.request(&format!("SET {} {}", gpio, high as u8), REPLY_TIMEOUT)?;
+    Ok(())
+}
+
+/// Reads every pin configured as an input or ADC pin.
+pub fn poll(monitor: &Monitor) -> Result<Vec<PinReading>, AppError> {
+    let reply = monitor.request("POLL", REPLY_TIMEOUT)?;
+    reply
+        .split_whitespace()
+        .map(|pair| {
+            pair.split_once(':')
+                .and_then(|(gpio, value)| {
+                    Some(PinReading {
+                        gpio: gpio.parse().ok()?,
+                        value: value.parse().ok()?,
+                    })
+                })
+                .ok_or_else(|| {
+                    AppError::Connection(format!(
+                        "Malformed reading {:?} from the GPIO helper",
+                        pair
+                    ))
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,170 @@
+//! ESP-IDF build directories. `idf.py build` records in `flasher_args.json`
+//! which image goes where and for which chip, which is all `idf.py flash`
+//! needs; reading it lets a build be flashed without the IDF environment.
+//!
+//! The flash mode, size and frequency recorded there are the ones the
+//! bootloader was built with and already carries in its header, so the
+//! images are written as they are.
+
+use crate::esp_interaction;
+use crate::models::{AppError, FlashPlan, FlashSegment};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const FLASHER_ARGS: &str = "flasher_args.json";
+const PROJECT_DESCRIPTION: &str = "project_description.json";
+
+#[derive(Deserialize, Default)]
+struct FlashSettings {
+    flash_mode: Option<String>,
+    flash_size: Option<String>,
+    flash_freq: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct ExtraArgs {
+    chip: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProjectDescription {
+    app_elf: String,
+}
+
+#[derive(Deserialize)]
+struct FlasherArgs {
+    #[serde(default)]
+    flash_settings: FlashSettings,
+    flash_files: BTreeMap<String, String>,
+    #[serde(default)]
+    extra_esptool_args: ExtraArgs,
+    /// Per-image entries like `"app": { "offset", "file", "encrypted" }`
+    #[serde(flatten)]
+    items: BTreeMap<String, serde_json::Value>,
+}
+
+/// The build directory for `path`, which may be the project or its build
+/// directory.
+pub fn build_dir(path: &Path) -> PathBuf {
+    if path.join(FLASHER_ARGS).is_file() {
+        path.to_path_buf()
+    } else {
+        path.join("build")
+    }
+}
+
+/// Reads `flasher_args.json` of the build in `build_dir`. Files are
+/// resolved against `build_dir` and listed by offset.
+pub fn parse_flasher_args(text: &str, build_dir: &Path) -> Result<FlashPlan, AppError> {
+    let args: FlasherArgs = serde_json::from_str(text)
+        .map_err(|e| AppError::InvalidInput(format!("Unreadable {}: {}", FLASHER_ARGS, e)))?;
+    let encrypted = args
+        .items
+        .values()
+        .any(|item| item.get("encrypted").and_then(|e| e.as_str()) == Some("true"));
+    if encrypted {
+        return Err(AppError::InvalidInput(
+            "Builds with flash encryption are not supported".to_string(),
+        ));
+    }
+    let mut segments = args
+        .flash_files
+        .into_iter()
+        .map(|(offset, file)| {
+            Ok(FlashSegment {
+                address: esp_interaction::parse_flash_address(&offset)?,
+                path: build_dir.join(file).display().to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    if segments.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "{} lists no files to flash",
+            FLASHER_ARGS
+        )));
+    }
+    segments.sort_by_key(|segment| segment.address);
+    Ok(FlashPlan {
+        build_dir: build_dir.display().to_string(),
+        chip: args.extra_esptool_args.chip,
+        flash_mode: args.flash_settings.flash_mode,
+        flash_size: args.flash_settings.flash_size,
+        flash_freq: args.flash_settings.flash_freq,
+        baud_rate: None,
+        segments,
+    })
+}
+
+/// The flash plan of the project or build directory at `path`, checking
+/// that every image was actually built.
+pub fn load(path: &Path) -> Result<FlashPlan, AppError> {
+    let dir = build_dir(path);
+    let text = std::fs::read_to_string(dir.join(FLASHER_ARGS)).map_err(|_| {
+        AppError::InvalidInput(format!(
+            "{} has no {}; run `idf.py build` first",
+            dir.display(),
+            FLASHER_ARGS
+        ))
+    })?;
+    let plan = parse_flasher_args(&text, &dir)?;
+    if let Some(missing) = plan.segments.iter().find(|s| !Path::new(&s.path).is_file()) {
+        return Err(AppError::InvalidInput(format!(
+            "{} is missing; rebuild the project",
+            missing.path
+        )));
+    }
+    Ok(plan)
+}
+
+/// The app ELF of the project or build directory at `path`, as named in
+/// `project_description.json`.
+pub fn app_elf(path: &Path) -> Result<PathBuf, AppError> {
+    let dir = build_dir(path);
+    let text = std::fs::read_to_string(dir.join(PROJECT_DESCRIPTION)).map_err(|_| {
+        AppError::InvalidInput(format!(
+            "{} has no {}; run `idf.py build` first",
+            dir.display(),
+            PROJECT_DESCRIPTION
+        ))
+    })?;
+    let description: ProjectDescription = serde_json::from_str(&text).map_err(|e| {
+        AppError::InvalidInput(format!("Unreadable {}: {}", PROJECT_DESCRIPTION, e))
+    })?;
+    let elf = dir.join(description.app_elf);
+    if !elf.is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "{} is missing; rebuild the project",
+            elf.display()
+        )));
+    }
+    Ok(elf)
+}
+
+/// Finds `program` on `PATH`, then in the newest version of `tool` the
+/// ESP-IDF installer put under `$IDF_TOOLS_PATH` (`~/.espressif` by
+/// default), laid out as `tools/<tool>/<version>/<tool>/bin/`.
+pub fn find_tool(tool: &str, program: &str) -> Option<PathBuf> {
+    let name = format!("{}{}", program, std::env::consts::EXE_SUFFIX);
+    let on_path = std::env::var_os("PATH").and_then(|path| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(&name))
+            .find(|candidate| candidate.is_file())
+    });
+    on_path.or_else(|| {
+        let tools = match std::env::var_os("IDF_TOOLS_PATH") {
+            Some(dir) => PathBuf::from(dir),
+            None => {
+                let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+                PathBuf::from(home).join(".espressif")
+            }
+        };
+        // Version directories are named like `v0.12.0-esp32-20240318`
+        std::fs::read_dir(tools.join("tools").join(tool))
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join(tool).join("bin").join(&name))
+            .filter(|candidate| candidate.is_file())
+            .max()
+    })
+}
@@ -0,0 +1,281 @@
+//! Bootloader and app images decoded the way `esptool.py image_info` does:
+//! the extended header, the segment list, the checksum byte after the
+//! segments and the SHA-256 the build may append. A file cut short or
+//! damaged on the way shows up here instead of as a board that won't boot.
+
+use crate::chips;
+use crate::models::{AppDescription, AppError, ImageInfo, ImageSegment};
+use crate::signature;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const IMAGE_MAGIC: u8 = 0xe9;
+// esp_image_header_t, extended header included
+const HEADER_LEN: usize = 24;
+const SEGMENT_HEADER_LEN: usize = 8;
+const CHIP_ID_AT: usize = 12;
+// Major revision only, as images from before ESP-IDF 5.0 have it
+const LEGACY_MIN_REV_AT: usize = 14;
+const MIN_REV_AT: usize = 15;
+const MAX_REV_AT: usize = 17;
+const HASH_APPENDED_AT: usize = 23;
+// The bootloader refuses images with more
+const MAX_SEGMENTS: usize = 16;
+const CHECKSUM_SEED: u8 = 0xef;
+const HASH_LEN: usize = 32;
+const APP_DESC_MAGIC: u32 = 0xabcd_5432;
+// esp_app_desc_t, up to the end of app_elf_sha256
+const APP_DESC_LEN: usize = 176;
+
+fn u16_at(data: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([data[at], data[at + 1]])
+}
+
+fn u32_at(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+/// SPI mode of the header's mode byte.
+pub(crate) fn flash_mode(code: u8) -> Option<&'static str> {
+    ["qio", "qout", "dio", "dout"].get(code as usize).copied()
+}
+
+/// Flash size of the high nibble of the header's size/frequency byte.
+pub(crate) fn flash_size(code: u8) -> Option<&'static str> {
+    ["1MB", "2MB", "4MB", "8MB", "16MB", "32MB", "64MB", "128MB"]
+        .get((code >> 4) as usize)
+        .copied()
+}
+
+/// Flash frequencies `chip` supports and their codes in the low nibble of
+/// the size/frequency byte, which depend on the chip's flash clock.
+pub(crate) fn flash_freqs(chip: &str) -> &'static [(&'static str, u8)] {
+    match chips::model_key(chip).as_str() {
+        "esp32c2" => &[("60m", 0xf), ("30m", 0x0), ("20m", 0x1), ("15m", 0x2)],
+        "esp32c6" => &[("80m", 0x0), ("40m", 0x0), ("20m", 0x2)],
+        "esp32h2" => &[("48m", 0xf), ("24m", 0x0), ("16m", 0x1), ("12m", 0x2)],
+        "esp32c5" | "esp32c61" | "esp32p4" => &[("80m", 0xf), ("40m", 0x0), ("20m", 0x2)],
+        _ => &[("80m", 0xf), ("40m", 0x0), ("26m", 0x1), ("20m", 0x2)],
+    }
+}
+
+fn flash_freq(chip: &str, code: u8) -> Option<&'static str> {
+    flash_freqs(chip)
+        .iter()
+        .find(|(_, c)| *c == code & 0x0f)
+        .map(|(freq, _)| *freq)
+}
+
+/// A chip revision as the header stores it, `major * 100 + minor`; the
+/// maximum stands for no upper bound.
+fn revision(full: u16) -> String {
+    if full == u16::MAX {
+        return "any".to_string();
+    }
+    format!("v{}.{}", full / 100, full % 100)
+}
+
+/// The C string in `bytes`, up to the first NUL.
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn app_description(segment: &[u8]) -> Option<AppDescription> {
+    if segment.len() < APP_DESC_LEN || u32_at(segment, 0) != APP_DESC_MAGIC {
+        return None;
+    }
+    Some(AppDescription {
+        project_name: c_string(&segment[48..80]),
+        version: c_string(&segment[16..48]),
+        idf_version: c_string(&segment[112..144]),
+        compiled: format!(
+            "{} {}",
+            c_string(&segment[96..112]),
+            c_string(&segment[80..96])
+        ),
+        secure_version: u32_at(segment, 4),
+        elf_sha256: segment[144..APP_DESC_LEN]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    })
+}
+
+fn truncated(what: &str, len: usize) -> AppError {
+    AppError::InvalidInput(format!(
+        "Image is truncated: the file ends at {} bytes, before {}",
+        len, what
+    ))
+}
+
+/// Whether `data` starts like a bootloader or app image for a known chip.
+/// The chip id keeps other files that happen to start with the magic byte,
+/// such as a FAT image's jump instruction, from counting.
+pub fn is_image(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN
+        && data[0] == IMAGE_MAGIC
+        && chips::from_image_chip_id(u16_at(data, CHIP_ID_AT)).is_some()
+}
+
+/// Decodes the image at the start of `data`; anything after it, as in a
+/// merged binary, is ignored. Fails when `data` is no image or ends before
+/// the image does. A wrong checksum, hash or signature is not an error but
+/// listed in `problems`.
+pub fn parse(data: &[u8]) -> Result<ImageInfo, AppError> {
+    if data.first() != Some(&IMAGE_MAGIC) {
+        return Err(AppError::InvalidInput(
+            "Not an ESP image: it doesn't start with 0xE9".to_string(),
+        ));
+    }
+    if data.len() < HEADER_LEN {
+        return Err(truncated("the end of the header", data.len()));
+    }
+    let count = data[1] as usize;
+    if count == 0 || count > MAX_SEGMENTS {
+        return Err(AppError::InvalidInput(format!(
+            "Image header claims {} segments",
+            count
+        )));
+    }
+
+    let mut segments = Vec::with_capacity(count);
+    let mut checksum = CHECKSUM_SEED;
+    let mut offset = HEADER_LEN;
+    for i in 0..count {
+        let header = data
+            .get(offset..offset + SEGMENT_HEADER_LEN)
+            .ok_or_else(|| truncated(&format!("the header of segment {}", i), data.len()))?;
+        let len = u32_at(header, 4);
+        let start = offset + SEGMENT_HEADER_LEN;
+        let end = start + len as usize;
+        let contents = data
+            .get(start..end)
+            .ok_or_else(|| truncated(&format!("the end of segment {}", i), data.len()))?;
+        checksum = contents.iter().fold(checksum, |sum, b| sum ^ b);
+        segments.push(ImageSegment {
+            load_address: u32_at(header, 0),
+            len,
+            file_offset: start,
+        });
+        offset = end;
+    }
+
+    // The checksum is the last byte of the 16-byte block the segments end in
+    let checksum_at = offset / 16 * 16 + 15;
+    let stored = *data
+        .get(checksum_at)
+        .ok_or_else(|| truncated("the checksum", data.len()))?;
+    let mut image_len = checksum_at + 1;
+    let mut problems = Vec::new();
+    if stored != checksum {
+        problems.push(format!(
+            "Checksum 0x{:02x} doesn't match the contents (0x{:02x})",
+            stored, checksum
+        ));
+    }
+    let hash_valid = if data[HASH_APPENDED_AT] == 1 {
+        let hash = data
+            .get(image_len..image_len + HASH_LEN)
+            .ok_or_else(|| truncated("the appended SHA-256", data.len()))?;
+        let valid = Sha256::digest(&data[..image_len]).as_slice() == hash;
+        if !valid {
+            problems.push("The appended SHA-256 doesn't match the contents".to_string());
+        }
+        image_len += HASH_LEN;
+        Some(valid)
+    } else {
+        None
+    };
+    let signature = match signature::inspect(data) {
+        Ok(info) => info.map(|info| format!("{}, key {}", info.scheme, info.keys())),
+        Err(e) => {
+            problems.push(e.message());
+            None
+        }
+    };
+
+    let chip_id = u16_at(data, CHIP_ID_AT);
+    let chip = chips::from_image_chip_id(chip_id);
+    let first = &segments[0];
+    let app = app_description(&data[first.file_offset..first.file_offset + first.len as usize]);
+    Ok(ImageInfo {
+        chip: chip.map(str::to_string),
+        chip_id,
+        entry_point: u32_at(data, 4),
+        flash_mode: flash_mode(data[2]).map(str::to_string),
+        flash_size: flash_size(data[3]).map(str::to_string),
+        flash_freq: flash_freq(chip.unwrap_or("esp32"), data[3]).map(str::to_string),
+        min_chip_revision: revision(u16_at(data, MIN_REV_AT)),
+        max_chip_revision: revision(u16_at(data, MAX_REV_AT)),
+        segments,
+        image_len,
+        checksum: stored,
+        checksum_valid: stored == checksum,
+        hash_valid,
+        app,
+        signature,
+        problems,
+    })
+}
+
+pub fn load(path: &Path) -> Result<ImageInfo, AppError> {
+    parse(&std::fs::read(path)?)
+}
+
+/// Refuses an image the ROM or bootloader would not boot: cut short, or
+/// with a checksum or appended hash that doesn't match. Files that are no
+/// image, see [`is_image`], pass.
+pub fn check(data: &[u8]) -> Result<(), AppError> {
+    if !is_image(data) {
+        return Ok(());
+    }
+    let info = parse(data)?;
+    if !info.checksum_valid || info.hash_valid == Some(false) {
+        return Err(AppError::InvalidInput(format!(
+            "Image is corrupt: {}",
+            info.problems.join("; ")
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the chip revisions an image supports against the chip's,
+/// `(major, minor)`, or `None` when it couldn't be read. An image that needs
+/// a later revision fails with [`AppError::WrongChip`], as the ROM or
+/// bootloader would refuse to start it. A chip newer than the image's
+/// maximum, which an eFuse can tell the bootloader to let pass, or of an
+/// unknown revision, gives a warning. Files that are no image pass.
+pub fn check_revision(data: &[u8], chip: Option<(u32, u32)>) -> Result<Option<String>, AppError> {
+    if !is_image(data) {
+        return Ok(None);
+    }
+    let min = u16_at(data, MIN_REV_AT).max(u16::from(data[LEGACY_MIN_REV_AT]) * 100);
+    let max = u16_at(data, MAX_REV_AT);
+    let Some((major, minor)) = chip else {
+        return Ok((min > 0).then(|| {
+            format!(
+                "needs chip revision {} or later, but the chip's revision couldn't be read",
+                revision(min)
+            )
+        }));
+    };
+    let full = major * 100 + minor;
+    if full < u32::from(min) {
+        return Err(AppError::WrongChip(format!(
+            "Needs chip revision {} or later, but the chip is v{}.{} and would not boot it",
+            revision(min),
+            major,
+            minor
+        )));
+    }
+    if max != u16::MAX && full > u32::from(max) {
+        return Ok(Some(format!(
+            "built for chip revisions up to {}, but the chip is v{}.{} and may not boot it",
+            revision(max),
+            major,
+            minor
+        )));
+    }
+    Ok(None)
+}
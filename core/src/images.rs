@@ -0,0 +1,216 @@
+//! Bootloader, partition table and app images as an Arduino-style build
+//! leaves them. Where each goes is read off the images themselves: the
+//! bootloader header names the chip and the partition table the app slot.
+
+use crate::chips;
+use crate::elf2image::{self, ImageOptions, ELF_MAGIC};
+use crate::image_info::{flash_freqs, flash_mode, flash_size};
+use crate::models::{AppError, ChipDetails, FlashPlan, FlashSegment, ImageKind, PlacedImage};
+use std::path::{Path, PathBuf};
+
+pub(crate) const PARTITION_TABLE_OFFSET: u32 = 0x8000;
+pub(crate) const DEFAULT_APP_OFFSET: u32 = 0x10000;
+/// Initial otadata the Arduino core flashes so the first app partition boots
+const BOOT_APP0_OFFSET: u32 = 0xe000;
+
+const IMAGE_MAGIC: u8 = 0xe9;
+const CHIP_ID_AT: usize = 12;
+const PARTITION_MAGIC: [u8; 2] = [0xaa, 0x50];
+const PARTITION_ENTRY: usize = 32;
+
+/// Chip model and flash mode/size from an image header.
+pub(crate) fn image_info(
+    data: &[u8],
+) -> Option<(&'static str, Option<&'static str>, Option<&'static str>)> {
+    if data.len() <= CHIP_ID_AT + 2 || data[0] != IMAGE_MAGIC {
+        return None;
+    }
+    let chip =
+        chips::from_image_chip_id(u16::from_le_bytes([data[CHIP_ID_AT], data[CHIP_ID_AT + 1]]))?;
+    Some((chip, flash_mode(data[2]), flash_size(data[3])))
+}
+
+/// Offset of the app an upload writes: the factory partition, or the first
+/// OTA slot when there is none.
+pub fn app_offset(partitions: &[u8]) -> Option<u32> {
+    let apps: Vec<(u8, u32)> = partitions
+        .chunks_exact(PARTITION_ENTRY)
+        .take_while(|entry| entry[..2] == PARTITION_MAGIC)
+        .filter(|entry| entry[2] == 0)
+        .map(|entry| {
+            let offset = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+            (entry[3], offset)
+        })
+        .collect();
+    apps.iter()
+        .find(|&&(subtype, _)| subtype == 0x00)
+        .or_else(|| apps.iter().find(|&&(subtype, _)| subtype == 0x10))
+        .or(apps.first())
+        .map(|&(_, offset)| offset)
+}
+
+/// What a lone image is and where it goes, read off its contents: a merged
+/// image at the start of flash, a bootloader at the chip's bootloader
+/// offset, a partition table at 0x8000 and anything else with an image
+/// header at the usual app offset. Refuses files that are none of these, and
+/// images the ROM or bootloader would not boot.
+pub fn place(data: &[u8]) -> Result<(ImageKind, u32, Option<&'static str>), AppError> {
+    // A merged image has the bootloader at the chip's offset and the
+    // partition table at its own
+    let table = PARTITION_TABLE_OFFSET as usize;
+    if data.get(table..table + 2) == Some(&PARTITION_MAGIC[..]) {
+        let bootloader = [0x0, 0x1000, 0x2000].into_iter().find_map(|at| {
+            let (chip, ..) = image_info(data.get(at..)?)?;
+            (chips::bootloader_offset(chip) == at as u32).then_some((chip, at))
+        });
+        if let Some((chip, at)) = bootloader {
+            crate::image_info::check(&data[at..])?;
+            return Ok((ImageKind::Merged, 0x0, Some(chip)));
+        }
+    }
+    if data.starts_with(&PARTITION_MAGIC) {
+        return Ok((ImageKind::PartitionTable, PARTITION_TABLE_OFFSET, None));
+    }
+    let Some((chip, ..)) = image_info(data) else {
+        return Err(AppError::InvalidInput(
+            "Not a firmware image: no ESP image header, partition table or ELF".to_string(),
+        ));
+    };
+    crate::image_info::check(data)?;
+    // Bootloaders carry no app description and have to fit below the
+    // partition table
+    let bootloader_at = chips::bootloader_offset(chip);
+    let is_app = crate::image_info::parse(data)?.app.is_some();
+    if !is_app && data.len() <= (PARTITION_TABLE_OFFSET - bootloader_at) as usize {
+        Ok((ImageKind::Bootloader, bootloader_at, Some(chip)))
+    } else {
+        Ok((ImageKind::App, DEFAULT_APP_OFFSET, Some(chip)))
+    }
+}
+
+/// Flash size and mode the board described by `details` needs in an image
+/// header: the detected size, and `dout` for octal flash, which the header
+/// has no mode for. Defaults stand in for what wasn't detected.
+fn detected_flash(details: &ChipDetails) -> (String, String) {
+    const MB: u32 = 1024 * 1024;
+    let size = details
+        .flash_size_bytes
+        .map(|bytes| format!("{}MB", bytes / MB))
+        .filter(|size| {
+            (0..8)
+                .filter_map(flash_size)
+                .any(|known| known == size.as_str())
+        })
+        .unwrap_or_else(|| elf2image::DEFAULT_FLASH_SIZE.to_string());
+    let octal = details
+        .flash
+        .as_ref()
+        .is_some_and(|flash| flash.mode.as_deref() == Some("octal"));
+    let mode = if octal {
+        "dout"
+    } else {
+        elf2image::DEFAULT_FLASH_MODE
+    };
+    (size, mode.to_string())
+}
+
+/// Checks the file at `path` for flashing on its own and places it, see
+/// [`place`]. An ELF is first converted into an app image for `board`, the
+/// connected one, with its detected flash size and mode, and written next
+/// to it.
+pub fn place_file(path: &Path, board: Option<&ChipDetails>) -> Result<PlacedImage, AppError> {
+    let data = std::fs::read(path)?;
+    if !data.starts_with(ELF_MAGIC) {
+        let (kind, address, chip) = place(&data)?;
+        return Ok(PlacedImage {
+            path: path.display().to_string(),
+            kind,
+            address,
+            chip: chip.map(str::to_string),
+            converted: false,
+        });
+    }
+    // Xtensa and RISC-V ELFs don't say which chip they are for
+    let Some((board, chip)) = board.and_then(|b| Some((b, b.chip_model.as_deref()?))) else {
+        return Err(AppError::InvalidInput(
+            "Connect the board first: an ELF is converted for the connected chip".to_string(),
+        ));
+    };
+    let (flash_size, flash_mode) = detected_flash(board);
+    let options = ImageOptions {
+        chip: chip.to_string(),
+        flash_mode,
+        flash_size,
+        flash_freq: flash_freqs(chip)[1].0.to_string(),
+    };
+    let output = elf2image::convert_file(path, None, &options)?;
+    Ok(PlacedImage {
+        path: output.display().to_string(),
+        kind: ImageKind::App,
+        address: DEFAULT_APP_OFFSET,
+        chip: Some(chips::model_key(chip)),
+        converted: true,
+    })
+}
+
+/// The files of one build.
+pub(crate) struct BuildImages {
+    pub bootloader: PathBuf,
+    pub partitions: PathBuf,
+    pub app: PathBuf,
+    pub boot_app0: Option<PathBuf>,
+}
+
+impl BuildImages {
+    /// Reads the images and places each; `missing` is the error for an
+    /// image that was not built.
+    pub(crate) fn plan(
+        self,
+        build_dir: &Path,
+        missing: impl Fn(&Path) -> AppError,
+    ) -> Result<FlashPlan, AppError> {
+        let read = |path: &Path| std::fs::read(path).map_err(|_| missing(path));
+        let app = read(&self.app)?;
+        let bootloader = read(&self.bootloader)?;
+        let partitions = read(&self.partitions)?;
+
+        let Some((chip, flash_mode, flash_size)) =
+            image_info(&bootloader).or_else(|| image_info(&app))
+        else {
+            return Err(AppError::InvalidInput(format!(
+                "{} is not an ESP bootloader image",
+                self.bootloader.display()
+            )));
+        };
+        let mut segments = vec![
+            FlashSegment {
+                path: self.bootloader.display().to_string(),
+                address: chips::bootloader_offset(chip),
+            },
+            FlashSegment {
+                path: self.partitions.display().to_string(),
+                address: PARTITION_TABLE_OFFSET,
+            },
+        ];
+        if let Some(boot_app0) = self.boot_app0 {
+            segments.push(FlashSegment {
+                path: boot_app0.display().to_string(),
+                address: BOOT_APP0_OFFSET,
+            });
+        }
+        segments.push(FlashSegment {
+            path: self.app.display().to_string(),
+            address: app_offset(&partitions).unwrap_or(DEFAULT_APP_OFFSET),
+        });
+
+        Ok(FlashPlan {
+            build_dir: build_dir.display().to_string(),
+            chip: Some(chip.to_string()),
+            flash_mode: flash_mode.map(str::to_string),
+            flash_size: flash_size.map(str::to_string),
+            flash_freq: None,
+            baud_rate: None,
+            segments,
+        })
+    }
+}
@@ -0,0 +1,280 @@
+//! Client side of the Improv Wi-Fi serial protocol, which ESPHome, WLED and
+//! other firmware speak to receive Wi-Fi credentials over the USB cable.
+//!
+//! Every frame is `IMPROV`, a version byte, a type byte, a length byte, the
+//! payload and a checksum (the sum of all preceding bytes). Frames arrive
+//! mixed with the firmware's normal log output, which is skipped.
+
+use crate::models::{AppError, ImprovDevice, ImprovOutcome};
+use crate::tasks::TaskHandle;
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Improv firmware listens at this rate regardless of the log baud.
+pub const BAUD_RATE: u32 = 115200;
+
+const MAGIC: &[u8] = b"IMPROV";
+const VERSION: u8 = 1;
+
+pub const TYPE_CURRENT_STATE: u8 = 0x01;
+pub const TYPE_ERROR_STATE: u8 = 0x02;
+pub const TYPE_RPC: u8 = 0x03;
+pub const TYPE_RPC_RESULT: u8 = 0x04;
+
+pub const CMD_WIFI_SETTINGS: u8 = 0x01;
+pub const CMD_CURRENT_STATE: u8 = 0x02;
+pub const CMD_DEVICE_INFO: u8 = 0x03;
+
+pub const STATE_AUTHORIZATION_REQUIRED: u8 = 0x01;
+pub const STATE_AUTHORIZED: u8 = 0x02;
+pub const STATE_PROVISIONING: u8 = 0x03;
+pub const STATE_PROVISIONED: u8 = 0x04;
+
+const ERROR_UNABLE_TO_CONNECT: u8 = 0x03;
+const ERROR_NOT_AUTHORIZED: u8 = 0x04;
+
+// Quick questions; firmware without Improv never answers
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+// Opening the port can reset the board, which then has to boot first
+const STATE_ATTEMPTS: u32 = 3;
+// Joining the network, DHCP included
+const PROVISION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Encodes one frame.
+pub fn frame(kind: u8, data: &[u8]) -> Vec<u8> {
+    let mut frame = MAGIC.to_vec();
+    frame.extend_from_slice(&[VERSION, kind, data.len() as u8]);
+    frame.extend_from_slice(data);
+    let checksum = frame.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+    frame.push(checksum);
+    frame
+}
+
+/// Payload of an RPC command frame.
+pub fn rpc(command: u8, data: &[u8]) -> Vec<u8> {
+    let mut payload = vec![command, data.len() as u8];
+    payload.extend_from_slice(data);
+    frame(TYPE_RPC, &payload)
+}
+
+/// Length-prefixed strings, the encoding of RPC arguments and results.
+pub fn encode_strings(strings: &[&str]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for s in strings {
+        data.push(s.len() as u8);
+        data.extend_from_slice(s.as_bytes());
+    }
+    data
+}
+
+fn decode_strings(mut data: &[u8]) -> Vec<String> {
+    let mut strings = Vec::new();
+    while let Some((&len, rest)) = data.split_first() {
+        let len = (len as usize).min(rest.len());
+        strings.push(String::from_utf8_lossy(&rest[..len]).into_owned());
+        data = &rest[len..];
+    }
+    strings
+}
+
+struct Frame {
+    kind: u8,
+    data: Vec<u8>,
+}
+
+/// Takes the first complete, valid frame out of `buffer`, dropping the
+/// log output in front of it.
+fn take_frame(buffer: &mut Vec<u8>) -> Option<Frame> {
+    loop {
+        let Some(start) = buffer.windows(MAGIC.len()).position(|w| w == MAGIC) else {
+            // Keep a tail that may be the start of the next magic
+            let keep = buffer.len().min(MAGIC.len() - 1);
+            buffer.drain(..buffer.len() - keep);
+            return None;
+        };
+        buffer.drain(..start);
+        let header = MAGIC.len() + 3;
+        if buffer.len() < header {
+            return None;
+        }
+        let len = buffer[header - 1] as usize;
+        if buffer.len() < header + len + 1 {
+            return None;
+        }
+        let checksum = buffer[..header + len]
+            .iter()
+            .fold(0u8, |sum, b| sum.wrapping_add(*b));
+        if buffer[MAGIC.len()] != VERSION || buffer[header + len] != checksum {
+            // Log text that happened to contain the magic
+            buffer.drain(..1);
+            continue;
+        }
+        let frame = Frame {
+            kind: buffer[MAGIC.len() + 1],
+            data: buffer[header..header + len].to_vec(),
+        };
+        buffer.drain(..header + len + 1);
+        return Some(frame);
+    }
+}
+
+fn device_error(code: u8, ssid: &str) -> AppError {
+    match code {
+        ERROR_UNABLE_TO_CONNECT => AppError::Connection(format!(
+            "The device could not join {:?}; check the name and password",
+            ssid
+        )),
+        ERROR_NOT_AUTHORIZED => AppError::InvalidInput(
+            "The device wants authorization first; press its button and try again".to_string(),
+        ),
+        code => AppError::Connection(format!("The device reported Improv error {:#04x}", code)),
+    }
+}
+
+/// Improv session over an open port, or anything else that carries bytes.
+pub struct Improv<S: Read + Write> {
+    stream: S,
+    buffer: Vec<u8>,
+}
+
+impl<S: Read + Write> Improv<S> {
+    pub fn new(stream: S) -> Self {
+        Improv {
+            stream,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn send(&mut self, command: u8, data: &[u8]) -> Result<(), AppError> {
+        self.stream.write_all(&rpc(command, data))?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    /// Waits for the next frame, giving up on cancel or at `deadline`.
+    fn receive(&mut self, deadline: Instant, task: &TaskHandle) -> Result<Frame, AppError> {
+        let mut buf = [0u8; 256];
+        loop {
+            if let Some(frame) = take_frame(&mut self.buffer) {
+                return Ok(frame);
+            }
+            task.checkpoint()?;
+            if Instant::now() >= deadline {
+                return Err(AppError::Timeout(
+                    "No Improv answer; is the firmware built with Improv serial support?"
+                        .to_string(),
+                ));
+            }
+            match self.stream.read(&mut buf) {
+                Ok(0) => std::thread::sleep(Duration::from_millis(10)),
+                Ok(n) => self.buffer.extend_from_slice(&buf[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Waits for the result of `command`, failing on an error frame.
+    fn result(
+        &mut self,
+        command: u8,
+        timeout: Duration,
+        ssid: &str,
+        task: &TaskHandle,
+    ) -> Result<Vec<String>, AppError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let frame = self.receive(deadline, task)?;
+            match frame.kind {
+                TYPE_RPC_RESULT if frame.data.first() == Some(&command) => {
+                    return Ok(decode_strings(frame.data.get(2..).unwrap_or_default()));
+                }
+                TYPE_ERROR_STATE if frame.data.first().is_some_and(|code| *code != 0) => {
+                    return Err(device_error(frame.data[0], ssid));
+                }
+                TYPE_CURRENT_STATE => debug!("Improv state {:?}", frame.data.first()),
+                _ => {}
+            }
+        }
+    }
+
+    /// One of the `STATE_*` values.
+    pub fn current_state(&mut self, task: &TaskHandle) -> Result<u8, AppError> {
+        self.send(CMD_CURRENT_STATE, &[])?;
+        let deadline = Instant::now() + REQUEST_TIMEOUT;
+        loop {
+            let frame = self.receive(deadline, task)?;
+            match (frame.kind, frame.data.first()) {
+                (TYPE_CURRENT_STATE, Some(state)) => return Ok(*state),
+                (TYPE_ERROR_STATE, Some(code)) if *code != 0 => {
+                    return Err(device_error(*code, ""))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn device_info(&mut self, task: &TaskHandle) -> Result<ImprovDevice, AppError> {
+        self.send(CMD_DEVICE_INFO, &[])?;
+        let mut fields = self
+            .result(CMD_DEVICE_INFO, REQUEST_TIMEOUT, "", task)?
+            .into_iter();
+        Ok(ImprovDevice {
+            firmware: fields.next().unwrap_or_default(),
+            version: fields.next().unwrap_or_default(),
+            chip: fields.next().unwrap_or_default(),
+            device_name: fields.next().unwrap_or_default(),
+        })
+    }
+
+    /// Sends the credentials and waits until the device has joined the
+    /// network. Returns the URLs it offers, often its web UI.
+    pub fn send_wifi_settings(
+        &mut self,
+        ssid: &str,
+        password: &str,
+        task: &TaskHandle,
+    ) -> Result<Vec<String>, AppError> {
+        if ssid.is_empty() || ssid.len() > 32 || password.len() > 64 {
+            return Err(AppError::InvalidInput(
+                "Wi-Fi names are 1-32 bytes and passwords at most 64".to_string(),
+            ));
+        }
+        self.send(CMD_WIFI_SETTINGS, &encode_strings(&[ssid, password]))?;
+        self.result(CMD_WIFI_SETTINGS, PROVISION_TIMEOUT, ssid, task)
+    }
+}
+
+/// Provisions the board on `port_name` with the given network.
+pub fn provision(
+    port_name: &str,
+    ssid: &str,
+    password: &str,
+    task: &TaskHandle,
+) -> Result<ImprovOutcome, AppError> {
+    task.phase("connecting");
+    let port = crate::monitor::open_port(port_name, BAUD_RATE)?;
+    let mut improv = Improv::new(port);
+
+    let mut state = improv.current_state(task);
+    for _ in 1..STATE_ATTEMPTS {
+        if !matches!(state, Err(AppError::Timeout(_))) {
+            break;
+        }
+        state = improv.current_state(task);
+    }
+    if state? == STATE_AUTHORIZATION_REQUIRED {
+        return Err(device_error(ERROR_NOT_AUTHORIZED, ssid));
+    }
+    // Older firmware does not answer this; it is only shown to the user
+    let device = improv.device_info(task).ok();
+
+    task.phase("provisioning");
+    let urls = improv.send_wifi_settings(ssid, password, task)?;
+    info!("Improv provisioned {} on {}", ssid, port_name);
+    Ok(ImprovOutcome {
+        device,
+        url: urls.into_iter().next(),
+    })
+}
@@ -0,0 +1,189 @@
+//! Inventory mode: boards plugged in one after another are read as they are
+//! identified and appended to a CSV, one row per MAC address, together with
+//! the batch details the operator entered. The file feeds label printing
+//! and fleet registration, so it can be reopened later to carry on a batch
+//! without writing the same board twice.
+
+use crate::label;
+use crate::models::{AppError, DeviceSnapshot, DeviceState, InventoryBatch, InventoryRow};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Columns of an inventory CSV, in order.
+pub const COLUMNS: [&str; 10] = [
+    "recorded_at",
+    "mac",
+    "chip",
+    "revision",
+    "flash_size",
+    "usb_serial",
+    "port",
+    "batch",
+    "operator",
+    "note",
+];
+
+// Where the MAC address sits in a row
+const MAC_COLUMN: usize = 1;
+
+/// An inventory CSV being filled in.
+pub struct Inventory {
+    path: PathBuf,
+    batch: InventoryBatch,
+    // Lowercase MAC addresses already in the file
+    seen: HashSet<String>,
+}
+
+impl Inventory {
+    /// Opens `path` for `batch`, writing the header to a new or empty file.
+    /// An existing inventory is carried on; its boards count as recorded.
+    pub fn open(path: &Path, batch: InventoryBatch) -> Result<Self, AppError> {
+        let mut seen = HashSet::new();
+        let existing = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut lines = existing.lines().filter(|line| !line.trim().is_empty());
+        match lines.next() {
+            Some(header) => {
+                if split(header) != COLUMNS {
+                    return Err(AppError::InvalidInput(format!(
+                        "{} is not an inventory CSV; choose a new file",
+                        path.display()
+                    )));
+                }
+                for line in lines {
+                    if let Some(mac) = split(line).get(MAC_COLUMN) {
+                        seen.insert(mac.to_ascii_lowercase());
+                    }
+                }
+            }
+            None => std::fs::write(path, format!("{}\n", COLUMNS.join(",")))?,
+        }
+        Ok(Inventory {
+            path: path.to_path_buf(),
+            batch,
+            seen,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn batch(&self) -> &InventoryBatch {
+        &self.batch
+    }
+
+    /// Boards in the file.
+    pub fn count(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Appends a row for a board that was just identified, `at` Unix time
+    /// in seconds. Other lifecycle events, and boards whose MAC address
+    /// couldn't be read, give `None`. A board already in the file comes back
+    /// marked `duplicate` and isn't written again.
+    pub fn record(
+        &mut self,
+        device: &DeviceSnapshot,
+        at: u64,
+    ) -> Result<Option<InventoryRow>, AppError> {
+        // Later probes of the same board come through as Identified again
+        if device.state != DeviceState::Identified
+            || device.previous == Some(DeviceState::Identified)
+        {
+            return Ok(None);
+        }
+        let Some(chip) = &device.chip else {
+            return Ok(None);
+        };
+        let Some(mac) = chip.mac_address.clone() else {
+            return Ok(None);
+        };
+        let mut row = InventoryRow {
+            recorded_at: timestamp(at),
+            mac_address: mac,
+            chip_model: chip.chip_model.clone(),
+            chip_revision: chip.chip_revision.clone(),
+            flash_size: chip.flash_size.clone(),
+            serial_number: device.status.serial_number.clone(),
+            port_name: device.status.port_name.clone(),
+            duplicate: false,
+        };
+        if !self.seen.insert(row.mac_address.to_ascii_lowercase()) {
+            row.duplicate = true;
+            return Ok(Some(row));
+        }
+
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+        let fields = [
+            row.recorded_at.clone(),
+            row.mac_address.clone(),
+            optional(&row.chip_model),
+            optional(&row.chip_revision),
+            optional(&row.flash_size),
+            optional(&row.serial_number),
+            optional(&row.port_name),
+            self.batch.batch.clone(),
+            self.batch.operator.clone(),
+            self.batch.note.clone(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+        let written = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", line.join(",")));
+        if let Err(e) = written {
+            // Not in the file, so a retry may write it
+            self.seen.remove(&row.mac_address.to_ascii_lowercase());
+            return Err(e.into());
+        }
+        Ok(Some(row))
+    }
+}
+
+/// Unix time `secs` as `YYYY-MM-DD HH:MM:SS`, UTC.
+pub fn timestamp(secs: u64) -> String {
+    let day = secs % 86_400;
+    format!(
+        "{} {:02}:{:02}:{:02}",
+        label::format_date(secs),
+        day / 3600,
+        day / 60 % 60,
+        day % 60
+    )
+}
+
+// One CSV field; line breaks would split the row, so they become spaces
+pub(crate) fn quote(field: &str) -> String {
+    let field = field.replace(['\r', '\n'], " ");
+    if field.contains([',', '"']) || field.trim() != field {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+// The fields of one CSV line
+fn split(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    fields
+}
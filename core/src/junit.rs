@@ -0,0 +1,103 @@
+//! JUnit XML for test sequence and soak reports, the format CI servers
+//! read to show hardware-in-the-loop results next to unit tests.
+
+use crate::models::{SoakReport, StepStatus, TestReport};
+use std::fmt::Write;
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Not allowed in XML 1.0 at all, e.g. ANSI escapes in boot logs
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// One `<testsuite>` with a `<testcase>` per step.
+pub fn test_report(report: &TestReport) -> String {
+    let count = |status| report.steps.iter().filter(|s| s.status == status).count();
+    let suite = escape(&report.name);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" hostname=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">",
+        suite,
+        escape(&report.port_name),
+        report.steps.len(),
+        count(StepStatus::Failed),
+        count(StepStatus::Skipped),
+        seconds(report.duration_ms)
+    );
+    for (i, step) in report.steps.iter().enumerate() {
+        let _ = write!(
+            xml,
+            "    <testcase classname=\"{}\" name=\"{}. {}\" time=\"{}\"",
+            suite,
+            i + 1,
+            escape(&step.description),
+            seconds(step.duration_ms)
+        );
+        match step.status {
+            StepStatus::Passed => xml.push_str("/>\n"),
+            StepStatus::Skipped => xml.push_str(">\n      <skipped/>\n    </testcase>\n"),
+            StepStatus::Failed => {
+                let detail = escape(step.detail.as_deref().unwrap_or_default());
+                let _ = write!(
+                    xml,
+                    ">\n      <failure message=\"{}\"/>\n    </testcase>\n",
+                    detail
+                );
+            }
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// One `<testsuite>` with a `<testcase>` per boot; failed boots carry
+/// their log.
+pub fn soak_report(report: &SoakReport) -> String {
+    let total_ms: u64 = report.boots.iter().map(|b| b.duration_ms).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"soak\" hostname=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{}\">",
+        escape(&report.port_name),
+        report.boots.len(),
+        report.failed(),
+        seconds(total_ms)
+    );
+    for boot in &report.boots {
+        let _ = write!(
+            xml,
+            "    <testcase classname=\"soak\" name=\"boot {}\" time=\"{}\"",
+            boot.cycle,
+            seconds(boot.duration_ms)
+        );
+        if boot.faults.is_empty() {
+            xml.push_str("/>\n");
+            continue;
+        }
+        let faults: Vec<&str> = boot.faults.iter().map(|f| f.as_str()).collect();
+        let _ = write!(
+            xml,
+            ">\n      <failure message=\"{}\"/>\n      <system-out>{}</system-out>\n    </testcase>\n",
+            faults.join(", "),
+            escape(&boot.log)
+        );
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
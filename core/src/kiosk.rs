@@ -0,0 +1,127 @@
+//! Production (kiosk) mode for line operators: one golden image flashed
+//! at the press of a button, a pass/fail count, and nothing else until
+//! someone with the password leaves it. The password keeps the image and
+//! address from being changed by accident; it won't stop anyone
+//! determined, who can edit the settings file.
+//!
+//! The golden image is registered with its SHA-256, and every flash reads
+//! the file once and checks it against that before writing those bytes,
+//! so a .bin swapped on the bench PC is refused rather than shipped.
+
+use crate::esp_interaction::parse_flash_address;
+use crate::models::{AppError, KioskSettings};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const SALT_LEN: usize = 16;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn digest(salt: &str, password: &str) -> String {
+    hex(&Sha256::digest(format!("{}${}", salt, password)))
+}
+
+/// `salt$sha256` of `password` with a fresh salt; empty for no password.
+pub fn hash_password(password: &str) -> String {
+    if password.is_empty() {
+        return String::new();
+    }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let salt = hex(&salt);
+    format!("{}${}", salt, digest(&salt, password))
+}
+
+/// SHA-256 of `data` in hex, as `sha256sum` prints it.
+pub fn image_hash(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+// `hash` as 64 lowercase hex digits; a `sha256sum` line works too
+fn parse_hash(hash: &str) -> Result<String, AppError> {
+    let hash = hash.split_whitespace().next().unwrap_or_default();
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::InvalidInput(
+            "The golden SHA-256 should be 64 hex digits".to_string(),
+        ));
+    }
+    Ok(hash.to_ascii_lowercase())
+}
+
+/// Kiosk mode on, flashing `image_path` at `address` as long as it matches
+/// `golden_hash`, counts at zero.
+pub fn enter(
+    image_path: &str,
+    address: &str,
+    golden_hash: &str,
+    password: &str,
+) -> Result<KioskSettings, AppError> {
+    if !Path::new(image_path).is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "Golden image {} not found",
+            image_path
+        )));
+    }
+    parse_flash_address(address)?;
+    let golden_hash = parse_hash(golden_hash)?;
+    let actual = image_hash(&std::fs::read(image_path)?);
+    if actual != golden_hash {
+        return Err(AppError::InvalidInput(format!(
+            "{} has SHA-256 {}, not the golden {}",
+            image_path, actual, golden_hash
+        )));
+    }
+    Ok(KioskSettings {
+        enabled: true,
+        image_path: image_path.to_string(),
+        address: address.trim().to_string(),
+        golden_hash,
+        password_hash: hash_password(password),
+        passed: 0,
+        failed: 0,
+    })
+}
+
+/// Whether `password` lets the operator out of kiosk mode.
+pub fn unlocks(kiosk: &KioskSettings, password: &str) -> bool {
+    match kiosk.password_hash.split_once('$') {
+        Some((salt, hash)) => digest(salt, password) == hash,
+        // A hash that doesn't parse keeps the kiosk locked
+        None => kiosk.password_hash.is_empty(),
+    }
+}
+
+/// Refuses writing flash other than through the kiosk's own flash while
+/// production mode is on, so only the golden image reaches the boards.
+pub fn check_unlocked(kiosk: &KioskSettings) -> Result<(), AppError> {
+    if kiosk.enabled {
+        return Err(AppError::InvalidInput(
+            "Production mode only flashes its golden image; leave it first".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks `data`, the golden image as read for writing, against the hash
+/// registered on entering. Check the bytes about to be flashed rather than
+/// the file, which could be swapped in between.
+pub fn check_golden(kiosk: &KioskSettings, data: &[u8]) -> Result<(), AppError> {
+    // Kiosk settings from before hashes were registered
+    if kiosk.golden_hash.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No golden SHA-256 registered; leave and enter production mode again".to_string(),
+        ));
+    }
+    let actual = image_hash(data);
+    if actual != kiosk.golden_hash {
+        return Err(AppError::InvalidInput(format!(
+            "{} no longer matches the golden image (SHA-256 {}, registered {}); not flashing",
+            kiosk.image_path, actual, kiosk.golden_hash
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//! QR code labels for production benches. The code holds the MAC address,
+//! USB serial number and flash date of a unit, one `KEY:value` line each,
+//! and is laid out for the label stock of common label printers. SVG
+//! labels print the same fields as text next to the code; PNG ones hold
+//! the code alone, at the printer's resolution.
+
+use crate::models::AppError;
+use qrcode::{Color, QrCode};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+// Light modules around the code that scanners need to find it
+const QUIET_ZONE: usize = 4;
+// Unprinted border of the label, in mm
+const MARGIN_MM: f32 = 1.5;
+// Narrowest space beside the code worth printing text in, in mm
+const MIN_TEXT_MM: f32 = 15.0;
+const MM_PER_INCH: f32 = 25.4;
+
+/// A label stock, landscape.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelSize {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub width_mm: f32,
+    pub height_mm: f32,
+}
+
+pub const LABEL_SIZES: [LabelSize; 5] = [
+    LabelSize {
+        key: "dk11201",
+        name: "Brother DK-11201 (29 × 90 mm)",
+        width_mm: 90.0,
+        height_mm: 29.0,
+    },
+    LabelSize {
+        key: "dk11204",
+        name: "Brother DK-11204 (17 × 54 mm)",
+        width_mm: 54.0,
+        height_mm: 17.0,
+    },
+    LabelSize {
+        key: "dymo11354",
+        name: "Dymo 11354 (32 × 57 mm)",
+        width_mm: 57.0,
+        height_mm: 32.0,
+    },
+    LabelSize {
+        key: "zebra2x1",
+        name: "Zebra 2 × 1 in (51 × 25 mm)",
+        width_mm: 50.8,
+        height_mm: 25.4,
+    },
+    LabelSize {
+        key: "square25",
+        name: "Square 25 × 25 mm",
+        width_mm: 25.0,
+        height_mm: 25.0,
+    },
+];
+
+pub fn label_size(key: &str) -> Option<LabelSize> {
+    LABEL_SIZES.iter().find(|size| size.key == key).copied()
+}
+
+/// What goes on the label of one unit.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceLabel {
+    pub mac: Option<String>,
+    pub serial_number: Option<String>,
+    // Unix time in seconds of the last flash
+    pub flashed_at: Option<u64>,
+}
+
+impl DeviceLabel {
+    /// The known fields as `KEY:value` lines.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(mac) = &self.mac {
+            lines.push(format!("MAC:{}", mac));
+        }
+        if let Some(serial) = &self.serial_number {
+            lines.push(format!("SN:{}", serial));
+        }
+        if let Some(at) = self.flashed_at {
+            lines.push(format!("FLASHED:{}", format_date(at)));
+        }
+        lines
+    }
+
+    /// Text the QR code encodes.
+    pub fn payload(&self) -> String {
+        self.lines().join("\n")
+    }
+}
+
+/// `YYYY-MM-DD` (UTC) of a Unix time in seconds.
+pub fn format_date(secs: u64) -> String {
+    // Days to civil date, after Howard Hinnant's `civil_from_days`
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// The code with its quiet zone, as rows of dark flags
+fn modules(label: &DeviceLabel) -> Result<(usize, Vec<bool>), AppError> {
+    let payload = label.payload();
+    if payload.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Nothing to put on the label: no MAC, serial number or flash date".to_string(),
+        ));
+    }
+    let code = QrCode::new(payload.as_bytes())
+        .map_err(|e| AppError::InvalidInput(format!("Label text doesn't fit a QR code: {}", e)))?;
+    let width = code.width();
+    let side = width + 2 * QUIET_ZONE;
+    let colors = code.to_colors();
+    let mut dark = vec![false; side * side];
+    for y in 0..width {
+        for x in 0..width {
+            dark[(y + QUIET_ZONE) * side + x + QUIET_ZONE] = colors[y * width + x] == Color::Dark;
+        }
+    }
+    Ok((side, dark))
+}
+
+// Side of the code in mm: the label's height, or width if that's shorter
+fn code_mm(size: &LabelSize) -> f32 {
+    size.height_mm.min(size.width_mm) - 2.0 * MARGIN_MM
+}
+
+/// The label as an SVG document in mm, the fields printed beside the code
+/// when the label is wide enough.
+pub fn render_svg(label: &DeviceLabel, size: &LabelSize) -> Result<String, AppError> {
+    let (side, dark) = modules(label)?;
+    let code = code_mm(size);
+    let module = code / side as f32;
+    let top = (size.height_mm - code) / 2.0;
+
+    let mut path = String::new();
+    for (i, _) in dark.iter().enumerate().filter(|(_, &d)| d) {
+        let _ = write!(path, "M{},{}h1v1h-1z", i % side, i / side);
+    }
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" \
+         viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"#fff\"/>\n\
+         <path transform=\"translate({x} {y}) scale({m})\" d=\"{d}\" fill=\"#000\"/>\n",
+        w = size.width_mm,
+        h = size.height_mm,
+        x = MARGIN_MM,
+        y = top,
+        m = module,
+        d = path
+    );
+
+    let text_x = MARGIN_MM + code + MARGIN_MM;
+    let lines = label.lines();
+    if size.width_mm - text_x - MARGIN_MM >= MIN_TEXT_MM {
+        let font = ((size.height_mm - 2.0 * MARGIN_MM) / (lines.len() as f32 * 1.4)).min(3.5);
+        for (i, line) in lines.iter().enumerate() {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\">{}</text>",
+                text_x,
+                top + font * (1.0 + 1.4 * i as f32),
+                font,
+                crate::junit::escape(line)
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// The label as a grayscale PNG at `dpi`, the code alone at its left edge.
+/// The image is the full label so printers don't rescale it.
+pub fn render_png(label: &DeviceLabel, size: &LabelSize, dpi: u32) -> Result<Vec<u8>, AppError> {
+    let (side, dark) = modules(label)?;
+    let px = |mm: f32| (mm * dpi as f32 / MM_PER_INCH).round() as usize;
+    let (width, height) = (px(size.width_mm), px(size.height_mm));
+    // Whole pixels per module keep the edges sharp
+    let module = px(code_mm(size)) / side;
+    if module == 0 {
+        return Err(AppError::InvalidInput(format!(
+            "A {}-module code doesn't fit {} at {} dpi",
+            side, size.name, dpi
+        )));
+    }
+    let left = px(MARGIN_MM);
+    let top = (height - side * module) / 2;
+
+    let mut pixels = vec![0xffu8; width * height];
+    for (i, _) in dark.iter().enumerate().filter(|(_, &d)| d) {
+        let (x0, y0) = (left + (i % side) * module, top + (i / side) * module);
+        for y in y0..y0 + module {
+            pixels[y * width + x0..y * width + x0 + module].fill(0);
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: (dpi as f32 / MM_PER_INCH * 1000.0).round() as u32,
+        yppu: (dpi as f32 / MM_PER_INCH * 1000.0).round() as u32,
+        unit: png::Unit::Meter,
+    }));
+    let png_error = |e: png::EncodingError| AppError::Internal(e.to_string());
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    writer.write_image_data(&pixels).map_err(png_error)?;
+    writer.finish().map_err(png_error)?;
+    Ok(out)
+}
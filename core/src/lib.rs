@@ -0,0 +1,64 @@
+//! Hardware side of esp32dev: device detection, flasher operations, the
+//! serial monitor and the task registry. Used by the Tauri app and the
+//! headless CLI so both drive the exact same code paths.
+
+pub mod api_auth;
+pub mod arduino;
+pub mod benchmark;
+pub mod ble_uart;
+pub mod boards;
+pub mod boot_timing;
+pub mod capture;
+pub mod checksum;
+pub mod chips;
+pub mod circuitpython;
+pub mod config_bundle;
+pub mod console;
+pub mod coredump;
+pub mod defmt;
+pub mod device_db;
+pub mod devices;
+pub mod elf2image;
+pub mod esp_interaction;
+pub mod espota;
+pub mod esptool;
+pub mod examples;
+pub mod flash_encryption;
+pub mod flash_estimate;
+pub mod flash_watch;
+pub mod full_image;
+pub mod gdb;
+pub mod gpio;
+pub mod idf;
+pub mod image_info;
+pub mod images;
+pub mod improv;
+pub mod inventory;
+pub mod junit;
+pub mod kiosk;
+pub mod label;
+pub mod lifecycle;
+pub mod micropython;
+pub mod models;
+pub mod monitor;
+pub mod mpy_firmware;
+pub mod openocd;
+pub mod otadata;
+pub mod pin_usage;
+pub mod platformio;
+pub mod report;
+pub mod reset_reason;
+pub mod scripting;
+pub mod sequence;
+pub mod signature;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+pub mod soak;
+pub mod svd;
+pub mod tasks;
+pub mod terminal;
+pub mod time_sync;
+pub mod transport;
+pub mod triggers;
+pub mod webrepl;
+pub mod wifi_prov;
@@ -0,0 +1,275 @@
+use crate::models::{AppError, ChipDetails, DeviceSnapshot, DeviceState, DeviceStatus};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+type Notify = Arc<dyn Fn(&DeviceSnapshot) + Send + Sync>;
+
+struct Entry {
+    snapshot: DeviceSnapshot,
+    // State to return to once a monitor session or flash ends
+    resume: DeviceState,
+    // Chip details came from the cache and want a fresh probe
+    stale: bool,
+}
+
+fn device_key(status: &DeviceStatus) -> String {
+    match &status.port_name {
+        Some(port) => port.clone(),
+        None => format!("usb:{}", status.vid_pid.as_deref().unwrap_or("unknown")),
+    }
+}
+
+/// What a board is remembered by across re-enumeration: its USB serial
+/// number. Bridges that report none can't be told apart from the next
+/// board plugged into the same port, so they are never remembered.
+fn identity(status: &DeviceStatus) -> Option<String> {
+    status
+        .serial_number
+        .as_ref()
+        .map(|serial| format!("sn:{}", serial))
+}
+
+/// Per-device state machine:
+/// `Detected → Identified → Monitoring / Flashing`, with `Error` after a
+/// failed probe or operation and `Disconnected` when the board goes away.
+///
+/// Every transition is reported through `notify`, so the UI follows the
+/// backend instead of diffing status polls.
+///
+/// Chip details are remembered per USB serial number, so a board that comes
+/// back is `Identified` straight away and only re-probed in the background.
+#[derive(Clone)]
+pub struct DeviceTracker {
+    devices: Arc<Mutex<HashMap<String, Entry>>>,
+    chips: Arc<Mutex<HashMap<String, ChipDetails>>>,
+    notify: Notify,
+}
+
+impl Default for DeviceTracker {
+    fn default() -> Self {
+        DeviceTracker::new(|_| {})
+    }
+}
+
+impl DeviceTracker {
+    pub fn new(notify: impl Fn(&DeviceSnapshot) + Send + Sync + 'static) -> Self {
+        DeviceTracker {
+            devices: Arc::new(Mutex::new(HashMap::new())),
+            chips: Arc::new(Mutex::new(HashMap::new())),
+            notify: Arc::new(notify),
+        }
+    }
+
+    pub fn list(&self) -> Vec<DeviceSnapshot> {
+        let mut list: Vec<DeviceSnapshot> = self
+            .devices
+            .lock()
+            .unwrap()
+            .values()
+            .map(|e| e.snapshot.clone())
+            .collect();
+        list.sort_by(|a, b| a.key.cmp(&b.key));
+        list
+    }
+
+    /// Applies one scan of the bus: new boards become `Detected`, missing
+    /// ones are reported `Disconnected` and dropped.
+    pub fn sync(&self, found: Vec<DeviceStatus>) {
+        let mut events = Vec::new();
+        {
+            let mut devices = self.devices.lock().unwrap();
+            let chips = self.chips.lock().unwrap();
+            let keys: Vec<String> = found.iter().map(device_key).collect();
+
+            // Boards re-enumerate while being flashed or reset; keep those
+            let gone: Vec<String> = devices
+                .iter()
+                .filter(|(key, e)| !keys.contains(key) && e.snapshot.state != DeviceState::Flashing)
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in gone {
+                if let Some(entry) = devices.remove(&key) {
+                    let mut snapshot = entry.snapshot;
+                    snapshot.previous = Some(snapshot.state);
+                    snapshot.state = DeviceState::Disconnected;
+                    events.push(snapshot);
+                }
+            }
+
+            for (key, status) in keys.into_iter().zip(found) {
+                match devices.get_mut(&key) {
+                    Some(entry) => {
+                        // Same board, details (e.g. product string) may change
+                        if entry.snapshot.status != status {
+                            entry.snapshot.status = status;
+                            entry.snapshot.previous = Some(entry.snapshot.state);
+                            events.push(entry.snapshot.clone());
+                        }
+                    }
+                    None => {
+                        let chip = identity(&status).and_then(|id| chips.get(&id).cloned());
+                        let state = if chip.is_some() {
+                            DeviceState::Identified
+                        } else {
+                            DeviceState::Detected
+                        };
+                        let snapshot = DeviceSnapshot {
+                            key: key.clone(),
+                            state,
+                            previous: None,
+                            status,
+                            chip,
+                            error: None,
+                        };
+                        events.push(snapshot.clone());
+                        devices.insert(
+                            key,
+                            Entry {
+                                stale: snapshot.chip.is_some(),
+                                snapshot,
+                                resume: state,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        for event in &events {
+            (self.notify)(event);
+        }
+    }
+
+    /// Ports of boards still waiting to be identified, or showing cached
+    /// details that are due for a fresh probe.
+    pub fn unidentified(&self) -> Vec<String> {
+        self.devices
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| e.snapshot.state == DeviceState::Detected || e.stale)
+            .filter_map(|e| e.snapshot.status.port_name.clone())
+            .collect()
+    }
+
+    /// The chip details last probed for the board on `port`, if any.
+    pub fn cached_chip(&self, port: &str) -> Option<ChipDetails> {
+        self.devices
+            .lock()
+            .unwrap()
+            .get(port)
+            .and_then(|e| e.snapshot.chip.clone())
+    }
+
+    /// Asks for the board on `port` to be probed again in the background.
+    pub fn refresh(&self, port: &str) {
+        if let Some(entry) = self.devices.lock().unwrap().get_mut(port) {
+            entry.stale = true;
+        }
+    }
+
+    fn transition(&self, key: &str, f: impl FnOnce(&mut Entry) -> Option<DeviceState>) {
+        let event = {
+            let mut devices = self.devices.lock().unwrap();
+            let Some(entry) = devices.get_mut(key) else {
+                return;
+            };
+            let before = entry.snapshot.state;
+            match f(entry) {
+                Some(next) => {
+                    entry.snapshot.previous = Some(before);
+                    entry.snapshot.state = next;
+                    entry.snapshot.clone()
+                }
+                None => return,
+            }
+        };
+        (self.notify)(&event);
+    }
+
+    /// Records the outcome of probing the chip on `port`.
+    pub fn identified(&self, port: &str, result: &Result<ChipDetails, AppError>) {
+        self.transition(port, |entry| {
+            entry.stale = false;
+            let idle = matches!(
+                entry.snapshot.state,
+                DeviceState::Detected | DeviceState::Identified | DeviceState::Error
+            );
+            match result {
+                Ok(chip) => {
+                    if let Some(id) = identity(&entry.snapshot.status) {
+                        self.chips.lock().unwrap().insert(id, chip.clone());
+                    }
+                    entry.snapshot.chip = Some(chip.clone());
+                    entry.snapshot.error = None;
+                    if idle {
+                        entry.resume = DeviceState::Identified;
+                        Some(DeviceState::Identified)
+                    } else {
+                        // Busy: keep the state, just refresh the details
+                        Some(entry.snapshot.state)
+                    }
+                }
+                Err(e) => {
+                    entry.snapshot.error = Some(e.clone());
+                    idle.then_some(DeviceState::Error)
+                }
+            }
+        });
+    }
+
+    /// The serial monitor attached to or detached from `port`.
+    pub fn monitoring(&self, port: &str, active: bool) {
+        self.transition(port, |entry| match (active, entry.snapshot.state) {
+            (true, DeviceState::Monitoring) => None,
+            (true, current) => {
+                if current != DeviceState::Flashing {
+                    entry.resume = current;
+                }
+                Some(DeviceState::Monitoring)
+            }
+            (false, DeviceState::Monitoring) => Some(entry.resume),
+            (false, DeviceState::Flashing) => {
+                // Don't fall back into Monitoring once the operation ends
+                if entry.resume == DeviceState::Monitoring {
+                    entry.resume = if entry.snapshot.chip.is_some() {
+                        DeviceState::Identified
+                    } else {
+                        DeviceState::Detected
+                    };
+                }
+                None
+            }
+            (false, _) => None,
+        });
+    }
+
+    /// An operation that owns the port (flash, erase, script) started.
+    pub fn begin_operation(&self, port: &str) {
+        self.transition(port, |entry| {
+            if entry.snapshot.state != DeviceState::Flashing {
+                entry.resume = entry.snapshot.state;
+            }
+            Some(DeviceState::Flashing)
+        });
+    }
+
+    /// The operation on `port` ended; failures move the device to `Error`.
+    pub fn end_operation<T>(&self, port: &str, result: &Result<T, AppError>) {
+        self.transition(port, |entry| match result {
+            Ok(_) | Err(AppError::Cancelled) => {
+                entry.snapshot.error = None;
+                // A failed earlier probe shouldn't outlive a successful flash
+                Some(match entry.resume {
+                    DeviceState::Error if entry.snapshot.chip.is_some() => DeviceState::Identified,
+                    DeviceState::Error => DeviceState::Detected,
+                    other => other,
+                })
+            }
+            Err(e) => {
+                entry.snapshot.error = Some(e.clone());
+                Some(DeviceState::Error)
+            }
+        });
+    }
+}
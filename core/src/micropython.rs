@@ -0,0 +1,374 @@
+//! File access on MicroPython boards through the raw REPL, the way
+//! `mpremote` and Thonny do it: Ctrl-A switches the REPL to raw mode, where
+//! each snippet sent and ended with Ctrl-D is answered with `OK`, its
+//! output, `\x04`, its exception text, `\x04` and the `>` prompt.
+
+use crate::models::{AppError, MpyEntry, MpyInfo};
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// The REPL UART runs at this rate; USB CDC ports ignore it.
+pub const BAUD_RATE: u32 = 115200;
+
+const RAW_BANNER: &[u8] = b"raw REPL; CTRL-B to exit\r\n";
+const CTRL_A: u8 = 0x01;
+const CTRL_B: u8 = 0x02;
+const CTRL_C: u8 = 0x03;
+const CTRL_D: u8 = 0x04;
+
+// Firmware without a REPL never answers
+const ENTER_TIMEOUT: Duration = Duration::from_secs(2);
+// Without output for this long the board is considered hung; reset by
+// every byte received, so long listings and downloads are fine
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+// The REPL reads from a small UART buffer; mpremote paces writes the same
+const WRITE_CHUNK: usize = 256;
+const WRITE_PAUSE: Duration = Duration::from_millis(10);
+// Bytes per `write` call when uploading
+const FILE_CHUNK: usize = 256;
+
+/// `s` as a Python string literal.
+fn py_str(s: &str) -> String {
+    let mut literal = String::from("'");
+    for c in s.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\'' => literal.push_str("\\'"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// `data` as a Python bytes literal.
+fn py_bytes(data: &[u8]) -> String {
+    let mut literal = String::from("b'");
+    for &b in data {
+        match b {
+            b'\\' => literal.push_str("\\\\"),
+            b'\'' => literal.push_str("\\'"),
+            0x20..=0x7e => literal.push(b as char),
+            b => literal.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+fn unhex(text: &str) -> Result<Vec<u8>, AppError> {
+    let digits: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    let value = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    digits
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => Some((value(*hi)? << 4) | value(*lo)?),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| AppError::Connection("Garbled file data from the board".to_string()))
+}
+
+/// The exception line of a traceback, e.g. `OSError: [Errno 2] ENOENT`.
+fn exception(stderr: &str) -> String {
+    stderr
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .unwrap_or("unknown error")
+        .to_string()
+}
+
+/// Turns the exception text of a snippet into an error.
+fn check(stderr: &[u8]) -> Result<(), AppError> {
+    if stderr.is_empty() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(stderr);
+    Err(AppError::InvalidInput(format!(
+        "MicroPython: {}",
+        exception(&stderr)
+    )))
+}
+
+/// Raw REPL session over an open port, or anything else that carries bytes.
+pub struct RawRepl<S: Read + Write> {
+    stream: S,
+    buffer: Vec<u8>,
+}
+
+impl<S: Read + Write> RawRepl<S> {
+    pub fn new(stream: S) -> Self {
+        RawRepl {
+            stream,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), AppError> {
+        self.stream.write_all(data)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    /// Reads until `marker` and returns what came before it.
+    fn read_until(&mut self, marker: &[u8], timeout: Duration) -> Result<Vec<u8>, AppError> {
+        let mut deadline = Instant::now() + timeout;
+        let mut buf = [0u8; 512];
+        loop {
+            if let Some(at) = self.buffer.windows(marker.len()).position(|w| w == marker) {
+                let before = self.buffer[..at].to_vec();
+                self.buffer.drain(..at + marker.len());
+                return Ok(before);
+            }
+            if Instant::now() >= deadline {
+                return Err(AppError::Timeout(
+                    "The MicroPython REPL stopped answering".to_string(),
+                ));
+            }
+            match self.stream.read(&mut buf) {
+                Ok(0) => std::thread::sleep(Duration::from_millis(5)),
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&buf[..n]);
+                    deadline = Instant::now() + timeout;
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Stops whatever program runs and switches to the raw REPL. Fails with
+    /// a timeout when the port has no MicroPython REPL.
+    pub fn enter(&mut self) -> Result<(), AppError> {
+        self.write(&[b'\r', CTRL_C, CTRL_C])?;
+        std::thread::sleep(Duration::from_millis(100));
+        // Drop whatever the interrupted program printed
+        let mut buf = [0u8; 512];
+        while matches!(self.stream.read(&mut buf), Ok(n) if n > 0) {}
+        self.buffer.clear();
+
+        self.write(&[b'\r', CTRL_A])?;
+        self.read_until(RAW_BANNER, ENTER_TIMEOUT)
+            .map_err(|_| AppError::Timeout("No MicroPython REPL answered".to_string()))?;
+        self.read_until(b">", ENTER_TIMEOUT)?;
+        debug!("Raw REPL entered");
+        Ok(())
+    }
+
+    /// Back to the friendly REPL.
+    pub fn exit(&mut self) -> Result<(), AppError> {
+        self.write(&[b'\r', CTRL_B])
+    }
+
+    /// Sends `code` and waits for the REPL to take it.
+    fn submit(&mut self, code: &str) -> Result<(), AppError> {
+        for chunk in code.as_bytes().chunks(WRITE_CHUNK) {
+            self.write(chunk)?;
+            std::thread::sleep(WRITE_PAUSE);
+        }
+        self.write(&[CTRL_D])?;
+        let ack = self.read_until(b"OK", IDLE_TIMEOUT)?;
+        if !ack.is_empty() {
+            debug!(
+                "Raw REPL noise before OK: {:?}",
+                String::from_utf8_lossy(&ack)
+            );
+        }
+        Ok(())
+    }
+
+    /// Hands output to `on_output` as it arrives, up to the `\x04` ending
+    /// the section. Without `timeout` it waits for as long as the program
+    /// runs; once `interrupted` returns true the program gets a Ctrl-C.
+    fn stream_section(
+        &mut self,
+        timeout: Option<Duration>,
+        interrupted: &dyn Fn() -> bool,
+        on_output: &mut dyn FnMut(&[u8]),
+    ) -> Result<(), AppError> {
+        let mut deadline = timeout.map(|t| Instant::now() + t);
+        let mut interrupt_sent = false;
+        let mut buf = [0u8; 512];
+        loop {
+            if let Some(at) = self.buffer.iter().position(|&b| b == CTRL_D) {
+                if at > 0 {
+                    on_output(&self.buffer[..at]);
+                }
+                self.buffer.drain(..=at);
+                return Ok(());
+            }
+            if !self.buffer.is_empty() {
+                on_output(&self.buffer);
+                self.buffer.clear();
+            }
+            if !interrupt_sent && interrupted() {
+                self.write(&[CTRL_C])?;
+                interrupt_sent = true;
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Err(AppError::Timeout(
+                    "The MicroPython REPL stopped answering".to_string(),
+                ));
+            }
+            match self.stream.read(&mut buf) {
+                Ok(0) => std::thread::sleep(Duration::from_millis(5)),
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&buf[..n]);
+                    deadline = timeout.map(|t| Instant::now() + t);
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Runs `code` and returns what it printed. An exception becomes
+    /// `InvalidInput` with the exception line as message.
+    pub fn exec(&mut self, code: &str) -> Result<String, AppError> {
+        self.submit(code)?;
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        self.stream_section(Some(IDLE_TIMEOUT), &|| false, &mut |b| {
+            stdout.extend_from_slice(b)
+        })?;
+        self.stream_section(Some(IDLE_TIMEOUT), &|| false, &mut |b| {
+            stderr.extend_from_slice(b)
+        })?;
+        self.read_until(b">", IDLE_TIMEOUT)?;
+        check(&stderr)?;
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
+    }
+
+    /// Runs a whole program, passing its output and then any traceback to
+    /// `on_output` as they arrive. There is no time limit; when
+    /// `interrupted` turns true the program is stopped with Ctrl-C and the
+    /// run ends as `Cancelled`.
+    pub fn run(
+        &mut self,
+        code: &str,
+        interrupted: impl Fn() -> bool,
+        mut on_output: impl FnMut(&[u8]),
+    ) -> Result<(), AppError> {
+        self.submit(code)?;
+        self.stream_section(None, &interrupted, &mut on_output)?;
+        let mut stderr = Vec::new();
+        self.stream_section(Some(IDLE_TIMEOUT), &|| false, &mut |b| {
+            stderr.extend_from_slice(b);
+            on_output(b);
+        })?;
+        self.read_until(b">", IDLE_TIMEOUT)?;
+        if interrupted() {
+            return Err(AppError::Cancelled);
+        }
+        check(&stderr)
+    }
+
+    pub fn info(&mut self) -> Result<MpyInfo, AppError> {
+        let out = self.exec("import os\nu=os.uname()\nprint(u.release)\nprint(u.machine)")?;
+        let mut lines = out.lines().map(|line| line.trim().to_string());
+        Ok(MpyInfo {
+            version: lines.next().unwrap_or_default(),
+            machine: lines.next().unwrap_or_default(),
+        })
+    }
+
+    /// Entries of the directory `path`, directories first.
+    pub fn list(&mut self, path: &str) -> Result<Vec<MpyEntry>, AppError> {
+        let code = format!(
+            "import os\np={}\nfor n in os.listdir(p):\n s=os.stat(p.rstrip('/')+'/'+n)\n print('%d\\t%d\\t%s'%(s[0]&0x4000 and 1 or 0,s[6],n))",
+            py_str(path)
+        );
+        let out = self.exec(&code)?;
+        let mut entries: Vec<MpyEntry> = out
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let is_dir = fields.next()? == "1";
+                let size = fields.next()?.parse().ok()?;
+                let name = fields.next()?.trim_end_matches('\r').to_string();
+                Some(MpyEntry { name, is_dir, size })
+            })
+            .collect();
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        Ok(entries)
+    }
+
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>, AppError> {
+        let code = format!(
+            "try:\n from binascii import hexlify\nexcept ImportError:\n from ubinascii import hexlify\nf=open({},'rb')\nwhile 1:\n b=f.read(512)\n if not b:break\n print(hexlify(b).decode())\nf.close()",
+            py_str(path)
+        );
+        let data = unhex(&self.exec(&code)?)?;
+        info!("MicroPython read {} ({} bytes)", path, data.len());
+        Ok(data)
+    }
+
+    /// Writes `data` to `path`, replacing the file. `on_progress` gets the
+    /// bytes written so far.
+    pub fn write_file(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        on_progress: impl Fn(usize),
+    ) -> Result<(), AppError> {
+        self.exec(&format!("f=open({},'wb')\nw=f.write", py_str(path)))?;
+        let mut written = 0;
+        for chunk in data.chunks(FILE_CHUNK) {
+            if let Err(e) = self.exec(&format!("w({})", py_bytes(chunk))) {
+                self.exec("f.close()").ok();
+                return Err(e);
+            }
+            written += chunk.len();
+            on_progress(written);
+        }
+        self.exec("f.close()")?;
+        info!("MicroPython wrote {} ({} bytes)", path, data.len());
+        Ok(())
+    }
+
+    /// Deletes a file, or a directory if it is empty.
+    pub fn remove(&mut self, path: &str) -> Result<(), AppError> {
+        self.exec(&format!(
+            "import os\np={}\ntry:\n os.remove(p)\nexcept OSError:\n os.rmdir(p)",
+            py_str(path)
+        ))?;
+        Ok(())
+    }
+
+    pub fn mkdir(&mut self, path: &str) -> Result<(), AppError> {
+        self.exec(&format!("import os\nos.mkdir({})", py_str(path)))?;
+        Ok(())
+    }
+}
+
+/// `name` inside the board directory `dir`.
+pub fn join(dir: &str, name: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), name)
+}
+
+/// Opens `port_name`, enters the raw REPL, runs `op` and leaves the REPL
+/// as it was found (minus the program that was running).
+pub fn session<T>(
+    port_name: &str,
+    op: impl FnOnce(&mut RawRepl<Box<dyn serialport::SerialPort>>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let port = crate::monitor::open_port(port_name, BAUD_RATE)?;
+    let mut repl = RawRepl::new(port);
+    repl.enter()?;
+    let result = op(&mut repl);
+    repl.exit().ok();
+    result
+}
+
+/// What MicroPython runs on `port_name`, or `None` if no REPL answered.
+pub fn detect(port_name: &str) -> Result<Option<MpyInfo>, AppError> {
+    match session(port_name, |repl| repl.info()) {
+        Ok(info) => Ok(Some(info)),
+        Err(AppError::Timeout(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
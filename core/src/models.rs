@@ -0,0 +1,1472 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Clone, PartialEq)]
+pub struct DeviceStatus {
+    pub code: String, // "ok", "missing_driver", "none"
+    pub message: String,
+    pub port_name: Option<String>,
+    pub product_name: Option<String>,
+    pub serial_number: Option<String>,
+    pub vid_pid: Option<String>,
+    pub connection_type: Option<String>,
+}
+
+/// A serial port as the OS lists it, ESP board or not.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialPortEntry {
+    pub port_name: String,
+    pub description: Option<String>,
+    pub vid_pid: Option<String>,
+    // USB vendor id is in the allowlist
+    pub is_esp: bool,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct ChipDetails {
+    pub chip_model: Option<String>,
+    pub mac_address: Option<String>,
+    pub flash_size: Option<String>,
+    // Same size in bytes, from the SPI flash ID
+    pub flash_size_bytes: Option<u32>,
+    pub features: Option<String>,
+    pub crystal_frequency: Option<String>,
+    pub chip_revision: Option<String>,
+    pub wafer_major: Option<u32>,
+    pub wafer_minor: Option<u32>,
+    // Part name from the package eFuse, e.g. "ESP32-D0WD"
+    pub package: Option<String>,
+    // ROM patch level from GET_SECURITY_INFO, e.g. "ECO3"
+    pub rom_version: Option<String>,
+    // ESP-IDF version of the flashed app, if it carries an app descriptor
+    pub sdk_version: Option<String>,
+    pub crystal_warning: Option<CrystalWarning>,
+    // In-package or external, from the eFuses; None when the chip doesn't say
+    pub flash: Option<MemoryInfo>,
+    // Only embedded PSRAM is recorded in eFuse, external PSRAM stays None
+    pub psram: Option<MemoryInfo>,
+    // From the static table, so it is there even if the probes above fail
+    pub capabilities: Option<ChipCapabilities>,
+    // The ROM refuses reads and the stub; only the fields above that come
+    // from GET_SECURITY_INFO are filled in
+    pub secure_download_mode: bool,
+}
+
+/// The board's crystal differs from the one firmware for the chip assumes
+/// by default, e.g. a 26 MHz ESP32. Such builds run every clock off by the
+/// ratio, which shows up as garbled serial output.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CrystalWarning {
+    pub detected: String,
+    pub expected: String,
+    // Rate a 115200 baud console actually runs at with the wrong assumption
+    pub apparent_baud: u32,
+    pub message: String,
+}
+
+/// Where a flash or PSRAM die sits and how it is wired. Octal parts need
+/// firmware built for octal (OPI) mode, and embedded ones take over the
+/// SPI pins that external parts would leave to the board.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryInfo {
+    pub embedded: bool,
+    // Capacity of the embedded die, e.g. "8 MB"
+    pub size: Option<String>,
+    pub vendor: Option<String>,
+    // "quad" or "octal"
+    pub mode: Option<String>,
+}
+
+/// What a target can do according to its datasheet.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChipCapabilities {
+    pub wifi: bool,
+    pub ble: bool,
+    pub ieee802154: bool,
+    pub usb_otg: bool,
+    pub cores: u8,
+    // Largest flash the memory map can address
+    pub max_flash_bytes: u32,
+}
+
+/// A dev board's header and what each pin can do, from the definitions
+/// bundled with the app (see `boards`).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardDefinition {
+    pub chip: String,  // espflash spelling, e.g. "esp32s3"
+    pub board: String, // e.g. "ESP32-S3-DevKitC-1"
+    pub svg: String,   // drawing under public/boards
+    pub pins: Vec<PinInfo>,
+    // GPIOs wired inside the module and not on the header, e.g. to flash
+    #[serde(default)]
+    pub internal: Vec<InternalPin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PinInfo {
+    // Id of the pin's group in the board drawing
+    pub svg_id: String,
+    pub number: u32,
+    pub name: String,
+    #[serde(default)]
+    pub gpio: Option<u32>,
+    // Signal names from the datasheet, e.g. "ADC1_CH3", "TOUCH4"
+    #[serde(default)]
+    pub functions: Vec<String>,
+    // Categories the UI highlights: "adc", "touch", "strapping", "usb",
+    // "psram", "jtag", "uart", "led", "power", "ground"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// A GPIO the module uses itself, such as the SPI flash pins.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalPin {
+    pub gpio: u32,
+    // "flash" or "psram"
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// A GPIO that is risky to wire a button or load to, and why: `kind` is
+/// "strapping", "usb", "flash" or "psram".
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservedPin {
+    pub gpio: u32,
+    pub kind: String,
+    pub note: Option<String>,
+}
+
+/// One image of a multi-file flash and the offset it goes to.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FlashSegment {
+    pub path: String,
+    pub address: u32,
+}
+
+/// Images a build system laid out for flashing, and the target it built
+/// them for.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FlashPlan {
+    pub build_dir: String,
+    /// espflash spelling, e.g. `esp32s3`
+    pub chip: Option<String>,
+    pub flash_mode: Option<String>,
+    pub flash_size: Option<String>,
+    pub flash_freq: Option<String>,
+    /// Upload speed the project asks for
+    pub baud_rate: Option<u32>,
+    pub segments: Vec<FlashSegment>,
+}
+
+/// What a firmware file holds, which decides where it is flashed.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageKind {
+    /// Bootloader, partition table and app merged into one file
+    Merged,
+    Bootloader,
+    PartitionTable,
+    App,
+}
+
+/// A firmware file checked for flashing on its own and the offset it goes
+/// to. For an ELF, `path` is the image it was converted into.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlacedImage {
+    pub path: String,
+    pub kind: ImageKind,
+    pub address: u32,
+    /// espflash spelling, e.g. `esp32s3`; `None` for a partition table
+    pub chip: Option<String>,
+    pub converted: bool,
+}
+
+/// What the Arduino integration found: the installed `arduino-cli` and the
+/// board to build for.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArduinoStatus {
+    pub cli_path: Option<String>,
+    pub fqbn: Option<String>,
+}
+
+/// Last successful monitor settings, remembered per device serial number.
+/// The monitor options are `None` in files saved before they were kept,
+/// which leaves the current ones alone.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DevicePrefs {
+    pub port_name: String,
+    pub baud_rate: u32,
+    #[serde(default)]
+    pub boot_marker: Option<String>,
+    #[serde(default)]
+    pub local_echo: Option<bool>,
+    #[serde(default)]
+    pub encoding: Option<TextEncoding>,
+}
+
+/// Error returned by every command. Serialized as
+/// `{ kind, code, message, hint }` so the frontend can branch on `kind`
+/// and localize the text instead of parsing message strings;
+/// `FlashOverflow` adds the `overflow` in bytes.
+#[derive(Debug, Clone)]
+pub enum AppError {
+    PortNotFound(String),
+    PortBusy(String),
+    NotConnected,
+    WrongChip(String),
+    StubTimeout(String),
+    Connection(String),
+    Flasher(String),
+    SecureDownload(String),
+    SecureBoot(String),
+    /// An image at `address` running `end - flash_size` bytes past the end
+    /// of the flash
+    FlashOverflow {
+        path: String,
+        address: u32,
+        end: u64,
+        flash_size: u32,
+    },
+    Io(String),
+    Config(String),
+    InvalidInput(String),
+    Script(String),
+    Cancelled,
+    TaskNotFound(u64),
+    Timeout(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::PortNotFound(_) => "PortNotFound",
+            AppError::PortBusy(_) => "PortBusy",
+            AppError::NotConnected => "NotConnected",
+            AppError::WrongChip(_) => "WrongChip",
+            AppError::StubTimeout(_) => "StubTimeout",
+            AppError::Connection(_) => "Connection",
+            AppError::Flasher(_) => "Flasher",
+            AppError::SecureDownload(_) => "SecureDownload",
+            AppError::SecureBoot(_) => "SecureBoot",
+            AppError::FlashOverflow { .. } => "FlashOverflow",
+            AppError::Io(_) => "Io",
+            AppError::Config(_) => "Config",
+            AppError::InvalidInput(_) => "InvalidInput",
+            AppError::Script(_) => "Script",
+            AppError::Cancelled => "Cancelled",
+            AppError::TaskNotFound(_) => "TaskNotFound",
+            AppError::Timeout(_) => "Timeout",
+            AppError::Internal(_) => "Internal",
+        }
+    }
+
+    pub fn code(&self) -> u16 {
+        match self {
+            AppError::PortNotFound(_) => 101,
+            AppError::PortBusy(_) => 102,
+            AppError::NotConnected => 103,
+            AppError::WrongChip(_) => 201,
+            AppError::StubTimeout(_) => 202,
+            AppError::Connection(_) => 203,
+            AppError::Flasher(_) => 204,
+            AppError::SecureDownload(_) => 205,
+            AppError::SecureBoot(_) => 206,
+            AppError::FlashOverflow { .. } => 207,
+            AppError::Io(_) => 301,
+            AppError::Config(_) => 302,
+            AppError::InvalidInput(_) => 303,
+            AppError::Script(_) => 304,
+            AppError::Cancelled => 401,
+            AppError::TaskNotFound(_) => 402,
+            AppError::Timeout(_) => 403,
+            AppError::Internal(_) => 900,
+        }
+    }
+
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AppError::PortNotFound(_) => {
+                Some("Check the USB cable and that the board shows up as a serial port.")
+            }
+            AppError::PortBusy(_) => {
+                Some("Close other programs (or the serial monitor) using this port and retry.")
+            }
+            AppError::NotConnected => Some("Connect to the device first."),
+            AppError::WrongChip(_) => {
+                Some("Make sure the firmware was built for the connected chip.")
+            }
+            AppError::StubTimeout(_) => Some(
+                "Hold BOOT while pressing RESET to enter download mode, or try a lower baud rate.",
+            ),
+            AppError::Connection(_) => {
+                Some("Make sure the board is in download mode and the cable supports data.")
+            }
+            AppError::SecureDownload(_) => Some(
+                "Secure Download Mode only allows writing flash from 0x8000 up; erasing, reading back and eFuse access are blocked.",
+            ),
+            AppError::SecureBoot(_) => Some(
+                "Secure Boot is enabled: sign the image with the chip's key (espsecure.py sign_data) before flashing.",
+            ),
+            AppError::FlashOverflow { .. } => Some(
+                "Flash the image at a lower address, or check the board's flash size and the partition table.",
+            ),
+            AppError::Io(_) => Some("Check that the file exists and is readable."),
+            AppError::InvalidInput(_) => Some("Check the value you entered."),
+            AppError::Script(_) => Some("Fix the script at the reported line and run it again."),
+            AppError::Timeout(_) => {
+                Some("The device did not answer in time; check the firmware output and baud rate.")
+            }
+            AppError::Flasher(_)
+            | AppError::Config(_)
+            | AppError::Cancelled
+            | AppError::TaskNotFound(_)
+            | AppError::Internal(_) => None,
+        }
+    }
+
+    /// Bytes a `FlashOverflow` image runs past the end of the flash.
+    pub fn overflow(&self) -> Option<u64> {
+        match self {
+            AppError::FlashOverflow {
+                end, flash_size, ..
+            } => Some(end - u64::from(*flash_size)),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            AppError::NotConnected => "Not connected".to_string(),
+            AppError::Cancelled => "Cancelled".to_string(),
+            AppError::TaskNotFound(id) => format!("No task with id {}", id),
+            AppError::FlashOverflow {
+                path,
+                address,
+                end,
+                flash_size,
+            } => format!(
+                "{} at 0x{:X} ends at 0x{:X}, {} bytes past the end of the {} flash",
+                path,
+                address,
+                end,
+                self.overflow().unwrap_or_default(),
+                crate::esp_interaction::format_flash_size(*flash_size)
+            ),
+            AppError::PortNotFound(m)
+            | AppError::PortBusy(m)
+            | AppError::WrongChip(m)
+            | AppError::StubTimeout(m)
+            | AppError::Connection(m)
+            | AppError::Flasher(m)
+            | AppError::SecureDownload(m)
+            | AppError::SecureBoot(m)
+            | AppError::Io(m)
+            | AppError::Config(m)
+            | AppError::InvalidInput(m)
+            | AppError::Script(m)
+            | AppError::Timeout(m)
+            | AppError::Internal(m) => m.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let overflow = self.overflow();
+        let mut s = serializer.serialize_struct("AppError", 4 + overflow.is_some() as usize)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("code", &self.code())?;
+        s.serialize_field("message", &self.message())?;
+        s.serialize_field("hint", &self.hint())?;
+        if let Some(overflow) = overflow {
+            s.serialize_field("overflow", &overflow)?;
+        }
+        s.end()
+    }
+}
+
+/// Joins an error and all of its sources, since espflash keeps the useful
+/// detail ("Timeout while running SyncFrame command") in the source chain.
+fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut text = e.to_string();
+    let mut source = e.source();
+    while let Some(s) = source {
+        text.push_str(": ");
+        text.push_str(&s.to_string());
+        source = s.source();
+    }
+    text
+}
+
+impl From<serialport::Error> for AppError {
+    fn from(e: serialport::Error) -> Self {
+        let message = e.to_string();
+        match e.kind() {
+            serialport::ErrorKind::NoDevice => AppError::PortNotFound(message),
+            serialport::ErrorKind::Io(std::io::ErrorKind::NotFound) => {
+                AppError::PortNotFound(message)
+            }
+            serialport::ErrorKind::Io(std::io::ErrorKind::PermissionDenied) => {
+                AppError::PortBusy(message)
+            }
+            _ if message.to_lowercase().contains("busy") => AppError::PortBusy(message),
+            _ => AppError::Connection(message),
+        }
+    }
+}
+
+impl From<espflash::Error> for AppError {
+    fn from(e: espflash::Error) -> Self {
+        let message = error_chain(&e);
+        match e {
+            espflash::Error::ChipMismatch(..) | espflash::Error::ChipDetectError(_) => {
+                AppError::WrongChip(message)
+            }
+            espflash::Error::Connection(_) => {
+                let lower = message.to_lowercase();
+                if lower.contains("timeout") || lower.contains("stub handshake") {
+                    AppError::StubTimeout(message)
+                } else if lower.contains("serial port not found") {
+                    AppError::PortNotFound(message)
+                } else {
+                    AppError::Connection(message)
+                }
+            }
+            espflash::Error::FileOpenError(..) => AppError::Io(message),
+            espflash::Error::SecureDownloadBootloaderProtection => {
+                AppError::SecureDownload(message)
+            }
+            _ => AppError::Flasher(message),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Io(format!("Device database: {}", e))
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct MonitorStateEvent {
+    pub state: String, // "suspended", "resumed", "stopped", "moved"
+    pub port_name: String,
+    // Operation that borrowed the port, e.g. "flash"; for "moved", the port
+    // the board left
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorStatus {
+    // "disconnected", "connected", "reconnecting", "suspended", "listening", "webrepl",
+    // "ble"
+    pub state: String,
+    // "udp:5140" or "tcp:5140" while listening, "ws://host:8266" with WebREPL,
+    // "ble://<name>" with a BLE UART
+    pub port_name: Option<String>,
+    pub baud_rate: Option<u32>,
+    // Sent lines are echoed into the output
+    pub local_echo: bool,
+    pub encoding: TextEncoding,
+    // ELF whose defmt table decodes the output
+    pub defmt_elf: Option<String>,
+}
+
+/// How the monitor decodes received bytes into text. Plenty of firmware
+/// for the Chinese market prints GBK.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Gbk,
+    Latin1,
+}
+
+/// What a device running Improv firmware says about itself.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImprovDevice {
+    pub firmware: String,
+    pub version: String,
+    pub chip: String,
+    pub device_name: String,
+}
+
+/// Result of Improv provisioning, as returned by `improv_provision`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImprovOutcome {
+    pub device: Option<ImprovDevice>,
+    /// Where the device can be reached now, usually its web UI.
+    pub url: Option<String>,
+}
+
+/// A BLE device found while looking for boards to provision.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvDevice {
+    /// Address, or the name where the platform hides addresses.
+    pub id: String,
+    pub name: String,
+    pub rssi: Option<i16>,
+}
+
+/// Result of BLE provisioning, as returned by `ble_provision`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvOutcome {
+    /// Address the board got on the network.
+    pub ip: Option<String>,
+}
+
+/// A board answering on the MicroPython REPL.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MpyInfo {
+    /// `os.uname().release`, e.g. `1.23.0`
+    pub version: String,
+    /// `os.uname().machine`, e.g. `Generic ESP32 module with ESP32`
+    pub machine: String,
+}
+
+/// One entry of a directory on a MicroPython board.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MpyEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// `esptool.py` lines equivalent to the flashing form's operations.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EsptoolCommands {
+    /// `None` while the form has rows that can't be flashed
+    pub write_flash: Option<String>,
+    pub erase_flash: String,
+    pub flash_id: String,
+}
+
+/// An official MicroPython build on micropython.org.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MpyRelease {
+    /// e.g. `v1.23.0`, or `v1.24.0-preview.39.g411d66586` for nightlies
+    pub version: String,
+    /// Build date as `YYYYMMDD`
+    pub date: String,
+    pub file_name: String,
+    pub url: String,
+    pub preview: bool,
+}
+
+/// A mounted CIRCUITPY drive, as described by its `boot_out.txt`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitPyDrive {
+    pub path: String,
+    /// e.g. `9.0.0`
+    pub version: String,
+    /// e.g. `Adafruit Feather ESP32-S3 No PSRAM with ESP32S3`
+    pub board: String,
+    pub board_id: Option<String>,
+    /// Matches the USB serial number of the board
+    pub uuid: Option<String>,
+}
+
+/// Transport a board forwards its log output over, for the monitor's
+/// network mode.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum NetLogProtocol {
+    Udp,
+    Tcp,
+}
+
+impl NetLogProtocol {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NetLogProtocol::Udp => "udp",
+            NetLogProtocol::Tcp => "tcp",
+        }
+    }
+}
+
+/// A window of monitor output lines, as returned by `monitor_lines`. Line
+/// numbers keep counting across trims and clears, so `first..end` is what
+/// the backend still holds and `start` is the number of `lines[0]`.
+/// `repeats[i]` counts the lines folded into `lines[i]` for repeating it.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorLines {
+    pub first: u64,
+    pub end: u64,
+    pub start: u64,
+    pub lines: Vec<String>,
+    pub repeats: Vec<u32>,
+}
+
+/// The screen of the monitor's terminal mode, as returned by
+/// `monitor_screen`: `lines` are its rows without trailing blanks,
+/// `scrollback` what scrolled off the top, oldest first.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalScreen {
+    pub rows: usize,
+    pub cols: usize,
+    pub scrollback: Vec<String>,
+    pub lines: Vec<String>,
+    pub cursor_row: usize,
+    pub cursor_col: usize,
+    pub cursor_visible: bool,
+}
+
+/// An input or ADC pin as read by the GPIO helper firmware.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PinReading {
+    pub gpio: u32,
+    pub value: u32, // 0/1 for digital inputs, millivolts for ADC pins
+}
+
+/// A test image from the example catalog, as offered for one chip.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ExampleFirmware {
+    pub id: String,
+    pub bundled: bool, // false when this build ships without the image
+}
+
+/// Lifecycle of a board as seen by the backend.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceState {
+    Detected,     // on the bus, not probed yet
+    Identified,   // chip details known
+    Monitoring,   // serial monitor attached
+    Flashing,     // flash/erase/script owns the port
+    Error,        // last probe or operation failed
+    Disconnected, // gone; only sent as the final event
+}
+
+/// A board and its lifecycle state, also the `device-state` event payload.
+#[derive(Serialize, Clone)]
+pub struct DeviceSnapshot {
+    pub key: String, // port name, or "usb:VID:PID" when there is no port
+    pub state: DeviceState,
+    pub previous: Option<DeviceState>,
+    pub status: DeviceStatus,
+    pub chip: Option<ChipDetails>,
+    pub error: Option<AppError>,
+}
+
+/// A user-facing notice about a finished operation or a device change,
+/// the `notify` event payload. The UI picks a localized title by `kind`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub level: String, // "info", "success", "warning", "error"
+    pub kind: String,  // e.g. "flash_completed", "erase_failed", "device_lost"
+    pub port_name: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Snapshot of a long-running operation, also the `task-progress` event payload.
+#[derive(Serialize, Clone)]
+pub struct TaskInfo {
+    pub id: u64,
+    pub kind: String,  // "flash", "erase", ...
+    pub state: String, // "running", "completed", "failed", "cancelled"
+    pub phase: String, // operation specific, e.g. "connecting", "writing"
+    pub current: u64,
+    pub total: u64,
+    pub port_name: Option<String>,
+    pub message: Option<String>,
+    // Effective rate of byte-counted work such as flash writes
+    pub bytes_per_sec: Option<u64>,
+    // Seconds left, estimated up front and then from the rate
+    pub eta_secs: Option<u64>,
+}
+
+/// How long flashing some images should take, see `flash_estimate`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlashEstimate {
+    pub bytes: u64,
+    /// On the wire, compressed for the stub
+    pub compressed: u64,
+    pub baud_rate: u32,
+    pub secs: u64,
+}
+
+/// One successful flash of a device, kept to spot slow cables and bridges.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlashRecord {
+    /// Unix time in seconds
+    pub at: u64,
+    pub bytes: u64,
+    pub bytes_per_sec: u64,
+    pub baud_rate: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct FlashSettings {
+    pub address: String,
+    pub baud_rate: u32, // baud used for flashing after the initial sync
+}
+
+impl Default for FlashSettings {
+    fn default() -> Self {
+        FlashSettings {
+            address: "0x0".to_string(),
+            baud_rate: 115200,
+        }
+    }
+}
+
+/// Local control API (HTTP + WebSocket on 127.0.0.1), off by default.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        ApiSettings {
+            enabled: false,
+            port: 8765,
+        }
+    }
+}
+
+/// Key bindings, as accelerator strings (`"Ctrl+L"`, `"F5"`). An empty
+/// string leaves the action unbound.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    pub clear_log: String,
+    pub focus_input: String,
+    pub flash: String,
+    pub reset: String,
+}
+
+impl ShortcutSettings {
+    /// `(action, accelerator)` pairs; the action is what the UI receives.
+    pub fn bindings(&self) -> [(&'static str, &str); 4] {
+        [
+            ("clear_log", &self.clear_log),
+            ("focus_input", &self.focus_input),
+            ("flash", &self.flash),
+            ("reset", &self.reset),
+        ]
+    }
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        ShortcutSettings {
+            clear_log: "Ctrl+L".to_string(),
+            focus_input: "Ctrl+K".to_string(),
+            flash: "F5".to_string(),
+            reset: "F6".to_string(),
+        }
+    }
+}
+
+/// Which events raise an OS notification. The in-app toasts are shown
+/// either way.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct DesktopNotifications {
+    pub device_connected: bool,
+    pub device_disconnected: bool,
+    pub flash_completed: bool,
+    pub flash_failed: bool,
+    pub monitor_triggers: bool,
+}
+
+impl Default for DesktopNotifications {
+    fn default() -> Self {
+        // Boards come and go all day at the bench; only their loss and the
+        // end of a flash are worth interrupting for
+        DesktopNotifications {
+            device_connected: false,
+            device_disconnected: true,
+            flash_completed: true,
+            flash_failed: true,
+            monitor_triggers: true,
+        }
+    }
+}
+
+/// Production (kiosk) mode: the app shows only a button flashing
+/// `image_path` at `address`, and the pass/fail counts of this run.
+/// Changed through the kiosk commands only, see `kiosk`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct KioskSettings {
+    pub enabled: bool,
+    pub image_path: String,
+    pub address: String,
+    // SHA-256 in hex the image has to match before each flash; registered
+    // on entering
+    pub golden_hash: String,
+    // `salt$sha256` in hex; empty when leaving needs no password
+    pub password_hash: String,
+    pub passed: u32,
+    pub failed: u32,
+}
+
+/// Outcome of one press of the kiosk's flash button, with the counts
+/// including it.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KioskRun {
+    // Flasher summary when the unit passed
+    pub summary: Option<String>,
+    pub error: Option<AppError>,
+    pub passed: u32,
+    pub failed: u32,
+}
+
+/// Persisted application settings. Unknown or missing fields fall back to
+/// the defaults so older settings files keep loading.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: String,    // "dark", "light"
+    pub language: String, // locale code, e.g. "zh", "en"
+    pub default_baud: u32,
+    pub vid_allowlist: Vec<u16>, // USB vendor ids treated as ESP boards
+    pub flash: FlashSettings,
+    pub api: ApiSettings,
+    pub shortcuts: ShortcutSettings,
+    pub triggers: Vec<MonitorTrigger>,
+    pub desktop_notifications: DesktopNotifications,
+    pub pin_usage: Vec<PinUsage>, // shown on the pinout
+    // Console command setting the board's clock, see `time_sync`
+    pub time_command: String,
+    pub kiosk: KioskSettings,
+    // Who is at the bench and the production run they are on, written
+    // with every device record
+    pub operator: String,
+    pub production_run: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: "dark".to_string(),
+            language: "zh".to_string(),
+            default_baud: 115200,
+            vid_allowlist: crate::devices::DEFAULT_VID_ALLOWLIST.to_vec(),
+            flash: FlashSettings::default(),
+            api: ApiSettings::default(),
+            shortcuts: ShortcutSettings::default(),
+            triggers: Vec::new(),
+            desktop_notifications: DesktopNotifications::default(),
+            pin_usage: Vec::new(),
+            time_command: crate::time_sync::DEFAULT_COMMAND.to_string(),
+            kiosk: KioskSettings::default(),
+            operator: String::new(),
+            production_run: String::new(),
+        }
+    }
+}
+
+/// What a firmware uses a pin for, which picks its colour on the pinout.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PinRole {
+    I2c,
+    Spi,
+    Uart,
+    Pwm,
+    /// Anything else, e.g. an LED or a button
+    Gpio,
+}
+
+/// A GPIO a firmware uses, e.g. GPIO21 as `I2C_MASTER_SDA`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PinUsage {
+    pub gpio: u32,
+    pub role: PinRole,
+    #[serde(default)]
+    pub signal: String,
+}
+
+/// What a monitor trigger does when its pattern shows up, written e.g.
+/// `{"kind": "send", "text": "y"}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// Sent to the device followed by CRLF
+    Send { text: String },
+    /// Adds a marker line to the monitor log
+    Mark { label: String },
+    /// Desktop notification; the matched line when `message` is empty
+    Notify { message: String },
+    /// Ends the monitor session
+    Stop,
+}
+
+/// A regex checked against monitor output as it arrives. Each trigger
+/// fires at most once per line, and also on a line still waiting for its
+/// newline, so prompts can be answered.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MonitorTrigger {
+    pub pattern: String,
+    pub action: TriggerAction,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// One step of a test sequence, written in the sequence file as e.g.
+/// `{"step": "expect", "pattern": "READY", "timeout_ms": 10000}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum TestStep {
+    /// `address` defaults to the configured flash offset
+    Flash {
+        path: String,
+        address: Option<String>,
+    },
+    Erase,
+    Reset,
+    /// Sent followed by CRLF
+    Send {
+        text: String,
+    },
+    /// Regex the output must match; `timeout_ms` defaults to 5 s
+    Expect {
+        pattern: String,
+        timeout_ms: Option<u64>,
+    },
+    Wait {
+        ms: u64,
+    },
+}
+
+/// A fixed list of steps run against a board, loaded from a JSON file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TestSequence {
+    #[serde(default)]
+    pub name: String,
+    /// Monitor baud rate the steps talk at, instead of the caller's
+    pub baud_rate: Option<u32>,
+    pub steps: Vec<TestStep>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    Skipped, // an earlier step failed
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StepResult {
+    pub description: String,
+    pub status: StepStatus,
+    pub duration_ms: u64,
+    /// Matched text, flasher summary or the error of a failed step
+    pub detail: Option<String>,
+}
+
+/// Outcome of running a test sequence, step by step.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TestReport {
+    pub name: String,
+    pub port_name: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    pub steps: Vec<StepResult>,
+}
+
+/// Something wrong with one boot of a soak test.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BootFault {
+    MissingMarker, // expected output never showed up
+    Panic,         // Guru Meditation, abort() and the like
+    Brownout,
+}
+
+impl BootFault {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BootFault::MissingMarker => "missing_marker",
+            BootFault::Panic => "panic",
+            BootFault::Brownout => "brownout",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BootRecord {
+    pub cycle: u32,
+    pub faults: Vec<BootFault>,
+    /// Until the marker was seen, or the whole observation window
+    pub duration_ms: u64,
+    /// Output of this boot, cut off at 16 KB
+    pub log: String,
+}
+
+/// Flash throughput measured at one baud rate, see
+/// `esp_interaction::flash_benchmark`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkRun {
+    pub baud_rate: u32,
+    /// Bytes per second, erase included; `None` when the write failed
+    pub write_rate: Option<u64>,
+    pub read_rate: Option<u64>,
+    /// The pattern read back matched the one written
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+/// Header, segments and integrity of a bootloader or app image, as
+/// `esptool.py image_info` shows them; see `image_info::parse`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageInfo {
+    /// `None` for a chip id this version doesn't know
+    pub chip: Option<String>,
+    pub chip_id: u16,
+    pub entry_point: u32,
+    pub flash_mode: Option<String>,
+    pub flash_size: Option<String>,
+    pub flash_freq: Option<String>,
+    /// e.g. `v0.0`
+    pub min_chip_revision: String,
+    pub max_chip_revision: String,
+    pub segments: Vec<ImageSegment>,
+    /// Bytes up to the checksum, or the appended SHA-256 when there is one
+    pub image_len: usize,
+    pub checksum: u8,
+    pub checksum_valid: bool,
+    /// `None` when no SHA-256 is appended
+    pub hash_valid: Option<bool>,
+    pub app: Option<AppDescription>,
+    /// Secure Boot signature, e.g. `RSA-PSS, key 1a2b3c4d5e6f7081`
+    pub signature: Option<String>,
+    /// What makes the image unbootable; empty for a good one
+    pub problems: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageSegment {
+    pub load_address: u32,
+    pub len: u32,
+    /// Where the segment's data starts in the file
+    pub file_offset: usize,
+}
+
+/// `esp_app_desc_t` at the start of an app's first segment.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDescription {
+    pub project_name: String,
+    pub version: String,
+    pub idf_version: String,
+    /// Build date and time, e.g. `Mar  4 2025 10:12:40`
+    pub compiled: String,
+    pub secure_version: u32,
+    /// First bytes of the ELF file's SHA-256, in hex
+    pub elf_sha256: String,
+}
+
+/// Outcome of resetting a board over and over, see `soak::run`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SoakReport {
+    pub port_name: String,
+    pub boots: Vec<BootRecord>,
+}
+
+impl SoakReport {
+    /// Boots with at least one fault.
+    pub fn failed(&self) -> usize {
+        self.boots.iter().filter(|b| !b.faults.is_empty()).count()
+    }
+
+    /// Boots showing `fault`.
+    pub fn count(&self, fault: BootFault) -> usize {
+        self.boots
+            .iter()
+            .filter(|b| b.faults.contains(&fault))
+            .count()
+    }
+
+    /// Share of failed boots, 0.0 when none ran.
+    pub fn failure_rate(&self) -> f64 {
+        if self.boots.is_empty() {
+            0.0
+        } else {
+            self.failed() as f64 / self.boots.len() as f64
+        }
+    }
+}
+
+/// Milestones of one boot, in ms after the ROM banner; `None` when the
+/// firmware never printed that one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BootTiming {
+    /// Unix time in seconds
+    pub at: u64,
+    pub bootloader_ms: Option<u64>,
+    pub app_main_ms: Option<u64>,
+    pub marker_ms: Option<u64>,
+}
+
+/// Why a board reset, as far as its boot output tells; see `reset_reason`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetCause {
+    PowerOn,
+    Power, // brownout or a glitch on the supply
+    Watchdog,
+    Panic, // Guru Meditation, abort() and the like
+    DeepSleepWake,
+    Software, // esp_restart()
+    External, // the reset pin, or the host over USB
+    Unknown,
+}
+
+impl ResetCause {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResetCause::PowerOn => "power_on",
+            ResetCause::Power => "power",
+            ResetCause::Watchdog => "watchdog",
+            ResetCause::Panic => "panic",
+            ResetCause::DeepSleepWake => "deep_sleep_wake",
+            ResetCause::Software => "software",
+            ResetCause::External => "external",
+            ResetCause::Unknown => "unknown",
+        }
+    }
+}
+
+/// A reset seen in a board's output, with what gave its cause away.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetReason {
+    /// Unix time in seconds
+    pub at: u64,
+    pub cause: ResetCause,
+    /// As the ROM printed it, e.g. `0xf (BROWNOUT_RST)`
+    pub rom_reason: String,
+    /// The brownout, watchdog or panic line printed before the reset
+    pub banner: Option<String>,
+}
+
+/// A firmware image written out with a Secure Boot v2 signature.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedImage {
+    pub path: String,
+    /// `RSA-PSS` or `ECDSA`
+    pub scheme: String,
+    /// Hex prefix of the public key digest
+    pub key_id: String,
+}
+
+/// Operator-entered details written with every row of an inventory CSV.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryBatch {
+    pub batch: String,
+    pub operator: String,
+    pub note: String,
+}
+
+/// A board read in inventory mode, also the `inventory-row` event payload.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryRow {
+    /// UTC, `YYYY-MM-DD HH:MM:SS`
+    pub recorded_at: String,
+    pub mac_address: String,
+    pub chip_model: Option<String>,
+    pub chip_revision: Option<String>,
+    pub flash_size: Option<String>,
+    pub serial_number: Option<String>,
+    pub port_name: Option<String>,
+    /// Already in the file; not written again
+    pub duplicate: bool,
+}
+
+/// Where inventory mode is writing to, if it is on.
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryStatus {
+    pub path: Option<String>,
+    pub batch: Option<InventoryBatch>,
+    /// Boards in the file, including those from earlier sessions
+    pub count: usize,
+}
+
+/// One flashing or provisioning of a board, as kept in the device database;
+/// see `device_db`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceRecord {
+    /// Assigned when recorded
+    pub id: i64,
+    /// Unix time in seconds
+    pub at: u64,
+    /// The task kind, e.g. `flash`, `kiosk`, `improv` or `ble_provision`
+    pub action: String,
+    pub mac_address: Option<String>,
+    pub serial_number: Option<String>,
+    pub chip_model: Option<String>,
+    /// From the app descriptor of the image written
+    pub firmware_version: Option<String>,
+    /// SHA-256 over the images written, in hex
+    pub checksum: Option<String>,
+    pub operator: String,
+    /// The production run it was part of; empty outside of one
+    pub run: String,
+    pub passed: bool,
+    /// Flasher summary, the error, or where a provisioned board went
+    pub detail: String,
+    /// How long the flashing or provisioning took
+    pub duration_ms: Option<u64>,
+    /// SHA-256 in hex of the golden image a kiosk unit was checked
+    /// against before flashing
+    pub golden_hash: Option<String>,
+}
+
+/// Filters for `DeviceDb::query`; fields left out match everything.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordQuery {
+    /// Part of a MAC address or USB serial number
+    pub device: Option<String>,
+    /// Part of an operator name
+    pub operator: Option<String>,
+    /// The whole run name, empty for records outside of a run
+    pub run: Option<String>,
+    pub action: Option<String>,
+    pub passed: Option<bool>,
+    /// Unix times in seconds, both inclusive
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    /// Most recent records returned
+    pub limit: Option<u32>,
+}
+
+/// A production run in the device database, as listed for picking one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductionRun {
+    /// Empty for the records made outside of a run
+    pub name: String,
+    /// Unix times in seconds of its first and last record
+    pub first_at: u64,
+    pub last_at: u64,
+    pub records: u32,
+}
+
+/// Failed attempts in a run that gave the same reason.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FailureReason {
+    pub reason: String,
+    pub count: u32,
+}
+
+/// How long attempts took, in ms.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DurationStats {
+    pub min_ms: u64,
+    pub median_ms: u64,
+    pub mean_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Summary of a production run, see `device_db::summarize`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub run: String,
+    /// Unix times in seconds of the first and last record; 0 for an
+    /// empty run
+    pub first_at: u64,
+    pub last_at: u64,
+    pub attempts: u32,
+    /// Boards tried, told apart by MAC address, else USB serial number
+    pub units: u32,
+    /// Boards whose last attempt passed, and those whose last one failed
+    pub passed: u32,
+    pub failed: u32,
+    /// Failed attempts by reason, most frequent first
+    pub failures: Vec<FailureReason>,
+    /// Of the attempts that passed; `None` when none was timed
+    pub durations: Option<DurationStats>,
+    pub operators: Vec<String>,
+    pub firmware_versions: Vec<String>,
+}
+
+/// A JTAG adapter OpenOCD can debug a board through.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JtagProbe {
+    /// `usb_jtag` (the chip's own USB-Serial/JTAG) or `esp_prog`
+    pub kind: String,
+    pub vid_pid: String,
+    pub serial_number: Option<String>,
+    pub product_name: Option<String>,
+}
+
+/// The supervised OpenOCD process, kept after it exits so its log stays
+/// readable.
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOcdStatus {
+    pub running: bool,
+    /// Listening for GDB connections
+    pub ready: bool,
+    pub chip_model: Option<String>,
+    pub probe: Option<JtagProbe>,
+    pub gdb_port: u16,
+    pub exit_code: Option<i32>,
+    /// The newest output lines, oldest first
+    pub log: Vec<String>,
+}
+
+/// One frame of a backtrace, innermost first.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StackFrame {
+    pub level: u32,
+    pub address: String,
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// What the debugged chip is doing, also the `gdb-state` event payload.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GdbTarget {
+    /// "running", "stopped" or "exited"; empty before GDB has attached
+    pub state: String,
+    /// Why it stopped, as GDB words it (`breakpoint-hit`, `signal-received`)
+    pub reason: Option<String>,
+    pub frame: Option<StackFrame>,
+}
+
+/// The GDB session of the debug panel.
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GdbStatus {
+    /// The GDB process is alive
+    pub running: bool,
+    pub elf: Option<String>,
+    pub target: GdbTarget,
+    /// Console output, oldest first
+    pub log: Vec<String>,
+}
+
+/// A peripheral described by a chip's SVD file.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SvdPeripheral {
+    pub name: String,
+    pub description: Option<String>,
+    pub base_address: u32,
+    pub registers: Vec<SvdRegister>,
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SvdRegister {
+    pub name: String,
+    pub description: Option<String>,
+    /// Absolute, the peripheral's base included
+    pub address: u32,
+    /// In bits
+    pub size: u32,
+    /// `read-only`, `write-only`, `read-write`, ...
+    pub access: Option<String>,
+    pub reset_value: Option<u32>,
+    /// Set when reading changes the register, like popping a FIFO
+    pub read_action: Option<String>,
+    pub fields: Vec<SvdField>,
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SvdField {
+    pub name: String,
+    pub description: Option<String>,
+    pub lsb: u32,
+    pub width: u32,
+    pub access: Option<String>,
+}
+
+/// A register read from the chip, its bitfields decoded.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterValue {
+    pub name: String,
+    pub address: u32,
+    pub value: u32,
+    pub fields: Vec<FieldValue>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldValue {
+    pub name: String,
+    pub value: u32,
+}
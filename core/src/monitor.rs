@@ -0,0 +1,1183 @@
+use crate::ble_uart::BleUart;
+use crate::capture::{Direction, Recorder};
+use crate::defmt::{Chunk, DefmtStream, Table};
+use crate::devices;
+use crate::models::{
+    AppError, MonitorLines, MonitorStateEvent, MonitorStatus, NetLogProtocol, TerminalScreen,
+    TextEncoding,
+};
+use crate::terminal::Screen;
+use crate::transport;
+use crate::webrepl::WebRepl;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, trace, warn};
+
+// Tail of received bytes kept for diagnostics, enough for a core dump
+// printed in base64
+const HISTORY_BYTES: usize = 256 * 1024;
+// Lines kept for the monitor view
+const LOG_LINES: usize = 50_000;
+// Upper bound on one `lines` query
+const MAX_WINDOW: usize = 1000;
+// Raw copies kept of a repeated line
+const MAX_REPEATS: usize = 1000;
+// Finished lines kept uncollapsed for `request`
+const TAIL_LINES: usize = 1024;
+// Upper bound on a reader loop iteration (reconnect backoff plus reopen)
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Port the monitor listens on for forwarded logs unless told otherwise.
+/// The syslog port itself (514) needs root on most systems.
+pub const NET_LOG_PORT: u16 = 5140;
+
+pub(crate) fn open_port(
+    port_name: &str,
+    baud_rate: u32,
+) -> Result<Box<dyn serialport::SerialPort>, AppError> {
+    #[cfg(feature = "simulator")]
+    if crate::simulator::is_simulated(port_name) {
+        return Ok(crate::simulator::open(baud_rate));
+    }
+
+    let mut port = serialport::new(port_name, baud_rate)
+        .timeout(Duration::from_millis(10))
+        .open()?;
+
+    // ESP32 requires DTR=false, RTS=false to run normally. Released in the
+    // wrong order the lines reset the board on every open, and a
+    // USB-Serial/JTAG board re-enumerates, is reopened and resets again.
+    transport::release_lines(&mut port).ok();
+
+    Ok(port)
+}
+
+/// Decodes a byte stream as UTF-8 without mangling characters split across
+/// reads; invalid bytes become U+FFFD.
+#[derive(Default)]
+pub(crate) struct Utf8Stream {
+    // Start of a character whose remaining bytes haven't arrived yet
+    pending: Vec<u8>,
+}
+
+impl Utf8Stream {
+    pub(crate) fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut text = String::new();
+        let mut rest = self.pending.as_slice();
+        while !rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    rest = after;
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &rest[len..];
+                        }
+                        // Cut off at the end: wait for the rest
+                        None => break,
+                    }
+                }
+            }
+        }
+        let used = self.pending.len() - rest.len();
+        self.pending.drain(..used);
+        text
+    }
+}
+
+/// Decodes a byte stream in the encoding picked for the session, keeping
+/// characters split across reads whole.
+pub(crate) enum TextStream {
+    Utf8(Utf8Stream),
+    Gbk(encoding_rs::Decoder),
+    // Every byte is a character
+    Latin1,
+}
+
+impl Default for TextStream {
+    fn default() -> Self {
+        TextStream::Utf8(Utf8Stream::default())
+    }
+}
+
+impl TextStream {
+    pub(crate) fn new(encoding: TextEncoding) -> Self {
+        match encoding {
+            TextEncoding::Utf8 => TextStream::default(),
+            TextEncoding::Gbk => {
+                TextStream::Gbk(encoding_rs::GBK.new_decoder_without_bom_handling())
+            }
+            TextEncoding::Latin1 => TextStream::Latin1,
+        }
+    }
+
+    pub(crate) fn decode(&mut self, bytes: &[u8]) -> String {
+        match self {
+            TextStream::Utf8(stream) => stream.decode(bytes),
+            TextStream::Gbk(decoder) => {
+                let capacity = decoder
+                    .max_utf8_buffer_length(bytes.len())
+                    .unwrap_or(bytes.len() * 3);
+                let mut text = String::with_capacity(capacity);
+                let _ = decoder.decode_to_string(bytes, &mut text, false);
+                text
+            }
+            TextStream::Latin1 => bytes.iter().map(|&b| char::from(b)).collect(),
+        }
+    }
+}
+
+/// The parts of an ESP-IDF log line around its `(<time>)`, e.g. `E` and
+/// `task_wdt: ...` for `E (5010) task_wdt: ...`.
+fn split_timestamp(line: &str) -> Option<(&str, &str)> {
+    let open = line.find(" (")?;
+    // Only the level letter and a colour code come before it
+    if open > 12 {
+        return None;
+    }
+    let rest = &line[open + 2..];
+    let close = rest.find(") ")?;
+    let time = &rest[..close];
+    if time.is_empty()
+        || !time
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b == b':' || b == b'.')
+    {
+        return None;
+    }
+    Some((&line[..open], &rest[close + 1..]))
+}
+
+/// Whether two lines say the same thing, ignoring ESP-IDF log timestamps.
+fn same_message(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    match (split_timestamp(a), split_timestamp(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// A line of the monitor view, standing for a run of lines that repeat it.
+#[derive(Default)]
+struct LogLine {
+    text: String,
+    // Lines of the run after the first
+    repeats: u32,
+    // Their raw text, the most recent `MAX_REPEATS`
+    copies: VecDeque<String>,
+}
+
+/// Received text split into lines for the monitor view. Lines are numbered
+/// from the start of the app so the UI can page through them while old ones
+/// fall off the front. A line that repeats the one before it, e.g. watchdog
+/// spam, is folded into it rather than numbered.
+#[derive(Default)]
+struct LineLog {
+    lines: VecDeque<LogLine>,
+    decoder: TextStream,
+    // Picks defmt frames out of the output when an ELF was given
+    defmt: Option<DefmtStream>,
+    // Number of `lines[0]`
+    first: u64,
+    // The last line has not seen its newline yet
+    open: bool,
+    // Recent finished lines as received, before folding
+    tail: VecDeque<String>,
+    // Finished lines received in all
+    received: u64,
+}
+
+impl LineLog {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        let Some(defmt) = self.defmt.as_mut() else {
+            let text = self.decoder.decode(bytes);
+            self.push(&text);
+            return;
+        };
+        for chunk in defmt.feed(bytes) {
+            match chunk {
+                Chunk::Text(bytes) => {
+                    let text = self.decoder.decode(&bytes);
+                    self.push(&text);
+                }
+                Chunk::Line(line) => self.note(&line),
+            }
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        let mut parts = text.split('\n').peekable();
+        while let Some(part) = parts.next() {
+            let last = parts.peek().is_none();
+            match self.lines.back_mut() {
+                Some(line) if self.open => line.text.push_str(part),
+                _ if last && part.is_empty() => {}
+                _ => self.lines.push_back(LogLine {
+                    text: part.to_string(),
+                    ..LogLine::default()
+                }),
+            }
+            if !last {
+                self.finish();
+                self.open = false;
+            } else if !part.is_empty() {
+                self.open = true;
+            }
+        }
+        let excess = self.lines.len().saturating_sub(LOG_LINES);
+        self.lines.drain(..excess);
+        self.first += excess as u64;
+    }
+
+    /// Ends the last line at its newline, folding it into the line before if
+    /// it repeats it.
+    fn finish(&mut self) {
+        let Some(mut line) = self.lines.pop_back() else {
+            return;
+        };
+        // Drop the CR of a CRLF
+        if line.text.ends_with('\r') {
+            line.text.pop();
+        }
+        self.tail.push_back(line.text.clone());
+        if self.tail.len() > TAIL_LINES {
+            self.tail.pop_front();
+        }
+        self.received += 1;
+        match self.lines.back_mut() {
+            Some(previous) if same_message(&previous.text, &line.text) => {
+                previous.repeats += 1;
+                previous.copies.push_back(line.text);
+                if previous.copies.len() > MAX_REPEATS {
+                    previous.copies.pop_front();
+                }
+            }
+            _ => self.lines.push_back(line),
+        }
+    }
+
+    /// Adds `text` as a line of its own, closing any partial line first.
+    fn note(&mut self, text: &str) {
+        if self.open {
+            self.finish();
+            self.open = false;
+        }
+        self.push(text);
+        self.push("\n");
+    }
+
+    fn end(&self) -> u64 {
+        self.first + self.lines.len() as u64
+    }
+
+    // Count of finished lines as received; unlike line numbers it goes up
+    // for repeats too
+    fn complete_end(&self) -> u64 {
+        self.received
+    }
+
+    /// Finished lines received since `complete_end` was `from`, unfolded,
+    /// and the count to continue from.
+    fn complete_since(&self, from: u64) -> (Vec<String>, u64) {
+        let held = self.received - self.tail.len() as u64;
+        let from = from.clamp(held, self.received);
+        let lines = self
+            .tail
+            .iter()
+            .skip((from - held) as usize)
+            .cloned()
+            .collect();
+        (lines, self.received)
+    }
+
+    /// The raw lines line `number` stands for, the line itself first.
+    fn run(&self, number: u64) -> Vec<String> {
+        let Some(line) = number
+            .checked_sub(self.first)
+            .and_then(|i| self.lines.get(i as usize))
+        else {
+            return Vec::new();
+        };
+        std::iter::once(&line.text)
+            .chain(&line.copies)
+            .cloned()
+            .collect()
+    }
+
+    fn clear(&mut self) {
+        self.first = self.end();
+        self.lines.clear();
+        self.open = false;
+    }
+
+    /// Up to `count` lines from `start`, or the last `count` when `start` is
+    /// `None`. Out of range starts are clamped to what is still held.
+    fn window(&self, start: Option<u64>, count: usize) -> MonitorLines {
+        let count = count.min(MAX_WINDOW);
+        let end = self.end();
+        let start = start
+            .unwrap_or_else(|| end.saturating_sub(count as u64))
+            .clamp(self.first, end);
+        let skip = (start - self.first) as usize;
+        MonitorLines {
+            first: self.first,
+            end,
+            start,
+            lines: self
+                .lines
+                .iter()
+                .skip(skip)
+                .take(count)
+                .map(|line| line.text.clone())
+                .collect(),
+            repeats: self
+                .lines
+                .iter()
+                .skip(skip)
+                .take(count)
+                .map(|line| line.repeats)
+                .collect(),
+        }
+    }
+}
+
+/// Adds received bytes to the diagnostics history, the line log, the
+/// capture and the terminal screen, if those are running. Returns what the
+/// terminal answers to the device.
+fn record(
+    history: &Mutex<VecDeque<u8>>,
+    log: &Mutex<LineLog>,
+    capture: &Mutex<Option<Recorder>>,
+    terminal: &Mutex<Option<Screen>>,
+    bytes: &[u8],
+) -> Vec<u8> {
+    if let Some(recorder) = capture.lock().unwrap().as_mut() {
+        recorder.write(Direction::Rx, bytes);
+    }
+    {
+        let mut history = history.lock().unwrap();
+        history.extend(bytes);
+        let excess = history.len().saturating_sub(HISTORY_BYTES);
+        history.drain(..excess);
+    }
+    log.lock().unwrap().push_bytes(bytes);
+    terminal
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|screen| screen.feed(bytes))
+        .unwrap_or_default()
+}
+
+/// Where a network session receives from.
+enum NetSocket {
+    Udp(UdpSocket),
+    Tcp(TcpListener),
+}
+
+/// Drops the `<PRI>` header syslog clients put in front of each message.
+fn strip_priority(message: &str) -> &str {
+    message
+        .strip_prefix('<')
+        .and_then(|rest| rest.split_once('>'))
+        .filter(|(pri, _)| !pri.is_empty() && pri.len() <= 3)
+        .filter(|(pri, _)| pri.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(message, |(_, text)| text)
+}
+
+fn would_block(e: &std::io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+/// Serial monitor session: a reader thread that forwards incoming bytes and
+/// transparently reconnects when the port drops (e.g. on reset). It can also
+/// listen on the network instead, for boards that forward their logs over
+/// Wi-Fi, talk to a MicroPython WebREPL, or follow a logger's Bluetooth LE
+/// UART.
+#[derive(Clone, Default)]
+pub struct Monitor {
+    port: Arc<Mutex<Option<Box<dyn serialport::SerialPort>>>>,
+    should_run: Arc<Mutex<bool>>,
+    // Set while a flash/erase borrows the port; the reader must not reopen it.
+    // Only read or written while holding the `port` lock.
+    suspended: Arc<Mutex<bool>>,
+    // Port name and baud of the running session, used to resume after a suspend
+    config: Arc<Mutex<Option<(String, u32)>>>,
+    // USB serial number of the monitored board, to find it again when it
+    // re-enumerates under another port name
+    usb_serial: Arc<Mutex<Option<String>>>,
+    // `udp:<port>` or `tcp:<port>` while listening on the network,
+    // `ws://<host>:<port>` with a WebREPL session, `ble://<name>` with a
+    // BLE UART
+    network: Arc<Mutex<Option<String>>>,
+    // MicroPython WebREPL session in place of a serial port
+    webrepl: Arc<Mutex<Option<WebRepl>>>,
+    // Nordic UART Service link in place of a serial port
+    ble: Arc<Mutex<Option<BleUart>>>,
+    // Recent output, kept across sessions
+    history: Arc<Mutex<VecDeque<u8>>>,
+    // Recent output as lines, for the monitor view
+    log: Arc<Mutex<LineLog>>,
+    // Raw traffic recording, kept across sessions until stopped
+    capture: Arc<Mutex<Option<Recorder>>>,
+    // Screen of the terminal mode, for consoles that redraw their prompt
+    terminal: Arc<Mutex<Option<Screen>>>,
+    // Set for firmware that echoes input itself; lines sent are then not
+    // added to the output a second time
+    echo_off: Arc<Mutex<bool>>,
+    // What received bytes are decoded as, for the log and the terminal
+    encoding: Arc<Mutex<TextEncoding>>,
+    // ELF of the defmt table the log is decoded with
+    defmt_elf: Arc<Mutex<Option<PathBuf>>>,
+    // Reader thread of the running session, joined on disconnect
+    reader: Arc<Mutex<Option<JoinHandle<()>>>>,
+    // Held by `request` so replies can't be taken by another caller
+    requests: Arc<Mutex<()>>,
+}
+
+impl Monitor {
+    pub fn connect(
+        &self,
+        port_name: &str,
+        baud_rate: u32,
+        on_data: impl Fn(&[u8]) + Send + 'static,
+    ) -> Result<(), AppError> {
+        // Never leave a previous reader running next to the new one
+        self.disconnect()?;
+        let serial_port = open_port(port_name, baud_rate)?;
+
+        // Set run flag
+        {
+            let mut run = self.should_run.lock().unwrap();
+            *run = true;
+        }
+
+        // Store port (wrap in Arc/Mutex logic)
+        {
+            let mut port_guard = self.port.lock().unwrap();
+            *port_guard = Some(serial_port);
+            *self.suspended.lock().unwrap() = false;
+        }
+        *self.config.lock().unwrap() = Some((port_name.to_string(), baud_rate));
+        *self.usb_serial.lock().unwrap() = devices::serial_number(port_name);
+
+        // Clone Arcs for thread (cheap clone)
+        let port_clone = self.port.clone();
+        let run_clone = self.should_run.clone();
+        let suspended_clone = self.suspended.clone();
+        let config = self.config.clone();
+        let usb_serial = self.usb_serial.clone();
+        let history = self.history.clone();
+        let log = self.log.clone();
+        let capture = self.capture.clone();
+        let terminal = self.terminal.clone();
+        let baud_rate_thread = baud_rate;
+
+        // Spawn read thread
+        let reader = std::thread::spawn(move || {
+            let mut serial_buf: Vec<u8> = vec![0; 1000];
+            loop {
+                // Check run flag
+                if !*run_clone.lock().unwrap() {
+                    break;
+                }
+
+                let mut fatal_error = false;
+                let mut got_data = false;
+                let mut read_len = 0;
+                let mut suspended = false;
+
+                // Scope for lock
+                {
+                    let mut guard = port_clone.lock().unwrap();
+                    if *suspended_clone.lock().unwrap() {
+                        // Port is lent to the flasher, leave it alone
+                        suspended = true;
+                    } else if let Some(port) = guard.as_mut() {
+                        match port.read(serial_buf.as_mut_slice()) {
+                            Ok(t) => {
+                                if t > 0 {
+                                    got_data = true;
+                                    read_len = t;
+                                }
+                            }
+                            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => (),
+                            Err(e) => {
+                                warn!("Monitor Error: {:?} - triggering reconnect", e);
+                                fatal_error = true;
+                            }
+                        }
+                    } else {
+                        // Port is None, need reconnect
+                        fatal_error = true;
+                    }
+
+                    if fatal_error {
+                        *guard = None;
+                    }
+                }
+
+                if got_data {
+                    trace!("Serial Read {} bytes", read_len);
+                    let replies =
+                        record(&history, &log, &capture, &terminal, &serial_buf[..read_len]);
+                    if !replies.is_empty() {
+                        if let Some(port) = port_clone.lock().unwrap().as_mut() {
+                            port.write_all(&replies).ok();
+                        }
+                    }
+                    on_data(&serial_buf[..read_len]);
+                }
+
+                if suspended {
+                    std::thread::sleep(Duration::from_millis(50));
+                } else if fatal_error {
+                    // Wait before retrying
+                    std::thread::sleep(Duration::from_millis(500));
+
+                    // The board may have come back under another name
+                    let Some(last_port) = config.lock().unwrap().as_ref().map(|(p, _)| p.clone())
+                    else {
+                        continue;
+                    };
+                    let serial = usb_serial.lock().unwrap().clone();
+                    let target = serial
+                        .and_then(|serial| devices::follow(&last_port, &serial))
+                        .unwrap_or(last_port);
+
+                    // Reopen under the lock so a suspend can't slip in between
+                    let mut guard = port_clone.lock().unwrap();
+                    if guard.is_none()
+                        && !*suspended_clone.lock().unwrap()
+                        && *run_clone.lock().unwrap()
+                    {
+                        debug!("Attempting reconnect to {}...", target);
+                        if let Ok(new_port) = open_port(&target, baud_rate_thread) {
+                            *guard = Some(new_port);
+                            if let Some((port, _)) = config.lock().unwrap().as_mut() {
+                                if *port != target {
+                                    info!("Board moved from {} to {}", port, target);
+                                    *port = target;
+                                }
+                            }
+                            info!("Reconnected successfully!");
+                        }
+                        // Otherwise reconnect failed, just retry next loop
+                    }
+                } else {
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+            }
+            info!("Monitor thread stopped");
+        });
+        *self.reader.lock().unwrap() = Some(reader);
+
+        info!("Monitor connect: {} @ {}", port_name, baud_rate);
+        Ok(())
+    }
+
+    /// Receives log output forwarded over the network instead of reading a
+    /// serial port: syslog-style datagrams over UDP, or plain text from any
+    /// number of TCP clients. Output goes to the same log as serial output.
+    /// There is no device to write to, so `send` fails while listening.
+    /// Returns the port actually bound, which matters when `port` is 0.
+    pub fn listen(
+        &self,
+        protocol: NetLogProtocol,
+        port: u16,
+        on_data: impl Fn(&[u8]) + Send + 'static,
+    ) -> Result<u16, AppError> {
+        self.disconnect()?;
+        let unavailable = |e: std::io::Error| {
+            AppError::Config(format!(
+                "{} port {} unavailable: {}",
+                protocol.as_str(),
+                port,
+                e
+            ))
+        };
+        let socket = match protocol {
+            NetLogProtocol::Udp => {
+                let socket = UdpSocket::bind(("0.0.0.0", port)).map_err(unavailable)?;
+                socket
+                    .set_read_timeout(Some(Duration::from_millis(10)))
+                    .map_err(unavailable)?;
+                NetSocket::Udp(socket)
+            }
+            NetLogProtocol::Tcp => {
+                let listener = TcpListener::bind(("0.0.0.0", port)).map_err(unavailable)?;
+                listener.set_nonblocking(true).map_err(unavailable)?;
+                NetSocket::Tcp(listener)
+            }
+        };
+        let bound = match &socket {
+            NetSocket::Udp(socket) => socket.local_addr(),
+            NetSocket::Tcp(listener) => listener.local_addr(),
+        }
+        .map_err(unavailable)?
+        .port();
+
+        *self.should_run.lock().unwrap() = true;
+        *self.network.lock().unwrap() = Some(format!("{}:{}", protocol.as_str(), bound));
+
+        let run = self.should_run.clone();
+        let history = self.history.clone();
+        let log = self.log.clone();
+        let capture = self.capture.clone();
+        let terminal = self.terminal.clone();
+        let reader = std::thread::spawn(move || {
+            let mut buf = vec![0u8; 2048];
+            let mut clients: Vec<TcpStream> = Vec::new();
+            while *run.lock().unwrap() {
+                let mut got_data = false;
+                match &socket {
+                    NetSocket::Udp(socket) => match socket.recv_from(&mut buf) {
+                        // One datagram is one message, without the newline
+                        Ok((n, _)) => {
+                            let text = String::from_utf8_lossy(&buf[..n]);
+                            let mut message = strip_priority(&text).trim_end().to_string();
+                            message.push('\n');
+                            record(&history, &log, &capture, &terminal, message.as_bytes());
+                            on_data(message.as_bytes());
+                            got_data = true;
+                        }
+                        Err(ref e) if would_block(e) => (),
+                        Err(e) => warn!("Network log receive failed: {}", e),
+                    },
+                    NetSocket::Tcp(listener) => {
+                        while let Ok((stream, peer)) = listener.accept() {
+                            if stream.set_nonblocking(true).is_ok() {
+                                info!("Network log client connected: {}", peer);
+                                clients.push(stream);
+                            }
+                        }
+                        clients.retain_mut(|stream| match stream.read(&mut buf) {
+                            Ok(0) => false,
+                            Ok(n) => {
+                                record(&history, &log, &capture, &terminal, &buf[..n]);
+                                on_data(&buf[..n]);
+                                got_data = true;
+                                true
+                            }
+                            Err(ref e) if would_block(e) => true,
+                            Err(e) => {
+                                debug!("Network log client dropped: {}", e);
+                                false
+                            }
+                        });
+                    }
+                }
+                if !got_data {
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+            }
+            info!("Network log listener stopped");
+        });
+        *self.reader.lock().unwrap() = Some(reader);
+
+        info!("Monitor listening on {} port {}", protocol.as_str(), bound);
+        Ok(bound)
+    }
+
+    /// Opens the REPL of a MicroPython board over Wi-Fi. Output goes to the
+    /// same log as serial output and `send` types into the REPL; file
+    /// transfers go through [`Monitor::with_webrepl`].
+    pub fn webrepl(
+        &self,
+        host: &str,
+        password: &str,
+        on_data: impl Fn(&[u8]) + Send + 'static,
+    ) -> Result<(), AppError> {
+        self.disconnect()?;
+        let repl = WebRepl::connect(host, password)?;
+        let address = repl.address().to_string();
+
+        *self.should_run.lock().unwrap() = true;
+        *self.webrepl.lock().unwrap() = Some(repl);
+        *self.network.lock().unwrap() = Some(format!("ws://{}", address));
+
+        let run = self.should_run.clone();
+        let session = self.webrepl.clone();
+        let network = self.network.clone();
+        let history = self.history.clone();
+        let log = self.log.clone();
+        let capture = self.capture.clone();
+        let terminal = self.terminal.clone();
+        let reader = std::thread::spawn(move || {
+            while *run.lock().unwrap() {
+                let received = match session.lock().unwrap().as_mut() {
+                    Some(repl) => repl.read_text(),
+                    None => break,
+                };
+                match received {
+                    Ok(bytes) if bytes.is_empty() => std::thread::sleep(Duration::from_millis(5)),
+                    Ok(bytes) => {
+                        let replies = record(&history, &log, &capture, &terminal, &bytes);
+                        if !replies.is_empty() {
+                            if let Some(repl) = session.lock().unwrap().as_mut() {
+                                repl.send_text(&replies).ok();
+                            }
+                        }
+                        on_data(&bytes);
+                    }
+                    Err(e) => {
+                        // There is nothing to reopen, the user connects again
+                        warn!("WebREPL session ended: {}", e);
+                        log.lock().unwrap().note(&e.to_string());
+                        *session.lock().unwrap() = None;
+                        *network.lock().unwrap() = None;
+                        break;
+                    }
+                }
+            }
+            info!("WebREPL reader stopped");
+        });
+        *self.reader.lock().unwrap() = Some(reader);
+        Ok(())
+    }
+
+    /// Runs `op` on the WebREPL session, e.g. a file transfer. The reader
+    /// waits meanwhile, so REPL output can't get mixed into the transfer.
+    pub fn with_webrepl<T>(
+        &self,
+        op: impl FnOnce(&mut WebRepl) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let mut guard = self.webrepl.lock().unwrap();
+        op(guard.as_mut().ok_or(AppError::NotConnected)?)
+    }
+
+    /// Follows the output of a device speaking the Nordic UART Service over
+    /// Bluetooth LE (`device` as found by [`crate::ble_uart::scan`]). Output
+    /// goes to the same log as serial output and `send` writes to the device.
+    pub fn ble_uart(
+        &self,
+        device: &str,
+        on_data: impl Fn(&[u8]) + Send + 'static,
+    ) -> Result<(), AppError> {
+        self.disconnect()?;
+        let link = BleUart::connect(device)?;
+        let name = link.name().to_string();
+
+        *self.should_run.lock().unwrap() = true;
+        *self.ble.lock().unwrap() = Some(link);
+        *self.network.lock().unwrap() = Some(format!("ble://{}", name));
+
+        let run = self.should_run.clone();
+        let session = self.ble.clone();
+        let network = self.network.clone();
+        let history = self.history.clone();
+        let log = self.log.clone();
+        let capture = self.capture.clone();
+        let terminal = self.terminal.clone();
+        let reader = std::thread::spawn(move || {
+            while *run.lock().unwrap() {
+                let received = match session.lock().unwrap().as_mut() {
+                    Some(link) => link.read(),
+                    None => break,
+                };
+                match received {
+                    Ok(bytes) if bytes.is_empty() => std::thread::sleep(Duration::from_millis(5)),
+                    Ok(bytes) => {
+                        let replies = record(&history, &log, &capture, &terminal, &bytes);
+                        if !replies.is_empty() {
+                            if let Some(link) = session.lock().unwrap().as_mut() {
+                                link.send(&replies).ok();
+                            }
+                        }
+                        on_data(&bytes);
+                    }
+                    Err(e) => {
+                        // Loggers sleep or walk out of range; the user
+                        // connects again
+                        warn!("BLE UART session ended: {}", e);
+                        log.lock().unwrap().note(&e.to_string());
+                        if let Some(link) = session.lock().unwrap().take() {
+                            link.close();
+                        }
+                        *network.lock().unwrap() = None;
+                        break;
+                    }
+                }
+            }
+            info!("BLE UART reader stopped");
+        });
+        *self.reader.lock().unwrap() = Some(reader);
+        Ok(())
+    }
+
+    /// Stops the session and returns once the reader thread has exited, so
+    /// the OS handle is released by the time a flash opens the port.
+    pub fn disconnect(&self) -> Result<(), AppError> {
+        *self.should_run.lock().unwrap() = false;
+        *self.port.lock().unwrap() = None;
+        *self.config.lock().unwrap() = None;
+        *self.usb_serial.lock().unwrap() = None;
+        *self.network.lock().unwrap() = None;
+        if let Some(repl) = self.webrepl.lock().unwrap().take() {
+            repl.close();
+        }
+        if let Some(link) = self.ble.lock().unwrap().take() {
+            link.close();
+        }
+
+        let Some(reader) = self.reader.lock().unwrap().take() else {
+            return Ok(());
+        };
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while !reader.is_finished() {
+            if Instant::now() > deadline {
+                warn!("Monitor thread did not stop within {:?}", SHUTDOWN_TIMEOUT);
+                return Err(AppError::Timeout(
+                    "serial monitor did not release the port".to_string(),
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        if reader.join().is_err() {
+            warn!("Monitor thread panicked");
+        }
+        info!("Monitor disconnect");
+        Ok(())
+    }
+
+    /// Current session state, as reported by `monitor_status`.
+    pub fn status(&self) -> MonitorStatus {
+        if let Some(address) = self.network.lock().unwrap().clone() {
+            let state = if address.starts_with("ws://") {
+                "webrepl"
+            } else if address.starts_with("ble://") {
+                "ble"
+            } else {
+                "listening"
+            };
+            return MonitorStatus {
+                state: state.to_string(),
+                port_name: Some(address),
+                baud_rate: None,
+                local_echo: self.local_echo(),
+                encoding: self.encoding(),
+                defmt_elf: self.defmt_elf(),
+            };
+        }
+        let config = self.config.lock().unwrap().clone();
+        let state = match &config {
+            None => "disconnected",
+            Some(_) => {
+                let guard = self.port.lock().unwrap();
+                if *self.suspended.lock().unwrap() {
+                    "suspended"
+                } else if guard.is_none() {
+                    "reconnecting"
+                } else {
+                    "connected"
+                }
+            }
+        };
+        MonitorStatus {
+            state: state.to_string(),
+            port_name: config.as_ref().map(|(port, _)| port.clone()),
+            baud_rate: config.map(|(_, baud)| baud),
+            local_echo: self.local_echo(),
+            encoding: self.encoding(),
+            defmt_elf: self.defmt_elf(),
+        }
+    }
+
+    /// Serial port of the running session, if any.
+    pub fn active_port(&self) -> Option<String> {
+        self.config
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(port, _)| port.clone())
+    }
+
+    /// USB serial number of the board the serial session follows across
+    /// re-enumeration, if it reports one.
+    pub fn usb_serial(&self) -> Option<String> {
+        self.usb_serial.lock().unwrap().clone()
+    }
+
+    /// Last bytes received by any session of this monitor.
+    pub fn recent_output(&self) -> Vec<u8> {
+        self.history.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Lines of received output, see [`MonitorLines`].
+    pub fn lines(&self, start: Option<u64>, count: usize) -> MonitorLines {
+        self.log.lock().unwrap().window(start, count)
+    }
+
+    /// The lines as received that line `number` of [`Monitor::lines`] stands
+    /// for: it and its repeats, or nothing once it has been trimmed.
+    pub fn repeats(&self, number: u64) -> Vec<String> {
+        self.log.lock().unwrap().run(number)
+    }
+
+    /// Appends a line of our own to the output, e.g. the echo of a command.
+    pub fn note(&self, text: &str) {
+        self.log.lock().unwrap().note(text);
+    }
+
+    /// Adds output the device produced outside this session, e.g. while a
+    /// MicroPython program ran with the port borrowed.
+    pub fn output(&self, bytes: &[u8]) {
+        record(
+            &self.history,
+            &self.log,
+            &self.capture,
+            &self.terminal,
+            bytes,
+        );
+    }
+
+    /// Turns the terminal mode on with a blank screen of `rows` by `cols`,
+    /// or off with `None`. While it is on, the monitor answers the status
+    /// queries interactive consoles probe the terminal with.
+    pub fn set_terminal(&self, size: Option<(usize, usize)>) {
+        let encoding = self.encoding();
+        *self.terminal.lock().unwrap() = size.map(|(rows, cols)| {
+            let mut screen = Screen::new(rows, cols);
+            screen.set_encoding(encoding);
+            screen
+        });
+    }
+
+    /// The terminal screen, `None` unless the terminal mode is on.
+    pub fn screen(&self) -> Option<TerminalScreen> {
+        self.terminal.lock().unwrap().as_ref().map(Screen::snapshot)
+    }
+
+    /// Empties the monitor view. The diagnostics history is kept.
+    pub fn clear_lines(&self) {
+        self.log.lock().unwrap().clear();
+    }
+
+    pub fn send(&self, data: &[u8]) -> Result<(), AppError> {
+        if let Some(repl) = self.webrepl.lock().unwrap().as_mut() {
+            repl.send_text(data)?;
+        } else if let Some(link) = self.ble.lock().unwrap().as_mut() {
+            link.send(data)?;
+        } else {
+            let mut guard = self.port.lock().unwrap();
+            let port = guard.as_mut().ok_or(AppError::NotConnected)?;
+            port.write_all(data)?;
+            debug!("Monitor send: {} bytes", data.len());
+        }
+        if let Some(recorder) = self.capture.lock().unwrap().as_mut() {
+            recorder.write(Direction::Tx, data);
+        }
+        Ok(())
+    }
+
+    /// Sends `line` with a CRLF and, with local echo on, adds it to the
+    /// output as `> line` so it shows in the view and exports alike.
+    pub fn send_line(&self, line: &str) -> Result<(), AppError> {
+        self.send(format!("{}\r\n", line).as_bytes())?;
+        if self.local_echo() {
+            self.note(&format!("> {}", line));
+        }
+        Ok(())
+    }
+
+    /// Turns echoing sent lines into the output on or off. It is on until
+    /// turned off, for firmware that doesn't echo its input.
+    pub fn set_local_echo(&self, on: bool) {
+        *self.echo_off.lock().unwrap() = !on;
+    }
+
+    pub fn local_echo(&self) -> bool {
+        !*self.echo_off.lock().unwrap()
+    }
+
+    /// Decodes output received from now on as `encoding`. Lines already in
+    /// the log stay as they were decoded.
+    pub fn set_encoding(&self, encoding: TextEncoding) {
+        *self.encoding.lock().unwrap() = encoding;
+        self.log.lock().unwrap().decoder = TextStream::new(encoding);
+        if let Some(screen) = self.terminal.lock().unwrap().as_mut() {
+            screen.set_encoding(encoding);
+        }
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        *self.encoding.lock().unwrap()
+    }
+
+    /// Decodes defmt frames in the output received from now on with the
+    /// table in `elf`, or stops with `None`.
+    pub fn set_defmt(&self, elf: Option<&Path>) -> Result<(), AppError> {
+        let stream = match elf {
+            Some(path) => Some(DefmtStream::new(Arc::new(Table::load(path)?))),
+            None => None,
+        };
+        self.log.lock().unwrap().defmt = stream;
+        *self.defmt_elf.lock().unwrap() = elf.map(Path::to_path_buf);
+        Ok(())
+    }
+
+    pub fn defmt_elf(&self) -> Option<String> {
+        self.defmt_elf
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|path| path.display().to_string())
+    }
+
+    /// Starts recording received and sent bytes to `path`, replacing a
+    /// capture already running.
+    pub fn start_capture(&self, path: &Path) -> Result<(), AppError> {
+        let source = match self.status() {
+            MonitorStatus {
+                port_name: Some(port),
+                baud_rate: Some(baud),
+                ..
+            } => format!("{} @ {}", port, baud),
+            MonitorStatus {
+                port_name: Some(address),
+                ..
+            } => address,
+            _ => "no session".to_string(),
+        };
+        let recorder = Recorder::create(path, &source)?;
+        if let Some(previous) = self.capture.lock().unwrap().replace(recorder) {
+            previous.finish()?;
+        }
+        info!("Monitor capture started: {}", path.display());
+        Ok(())
+    }
+
+    /// Stops the capture; returns its file and how many chunks it holds.
+    pub fn stop_capture(&self) -> Result<Option<(PathBuf, u64)>, AppError> {
+        let Some(recorder) = self.capture.lock().unwrap().take() else {
+            return Ok(None);
+        };
+        let (path, events) = recorder.finish()?;
+        info!(
+            "Monitor capture stopped: {} ({} events)",
+            path.display(),
+            events
+        );
+        Ok(Some((path, events)))
+    }
+
+    /// File of the running capture, if any.
+    pub fn capture_path(&self) -> Option<PathBuf> {
+        self.capture
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|recorder| recorder.path().to_path_buf())
+    }
+
+    /// Sends `line` to a device speaking a line protocol that answers
+    /// `OK [value]` or `ERR <reason>` and returns the value. Other output
+    /// received in between, e.g. log lines, is skipped.
+    pub fn request(&self, line: &str, timeout: Duration) -> Result<String, AppError> {
+        let _turn = self.requests.lock().unwrap();
+        let mut next = self.log.lock().unwrap().complete_end();
+        self.send(format!("{}\r\n", line).as_bytes())?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let (lines, end) = self.log.lock().unwrap().complete_since(next);
+            for reply in &lines {
+                let reply = reply.trim();
+                if reply == "OK" {
+                    return Ok(String::new());
+                }
+                if let Some(value) = reply.strip_prefix("OK ") {
+                    return Ok(value.trim().to_string());
+                }
+                if let Some(reason) = reply.strip_prefix("ERR") {
+                    return Err(AppError::InvalidInput(format!(
+                        "{}: {}",
+                        line,
+                        reason.trim()
+                    )));
+                }
+            }
+            next = end;
+
+            if Instant::now() >= deadline {
+                return Err(AppError::Timeout(format!(
+                    "No reply to {:?} within {} ms",
+                    line,
+                    timeout.as_millis()
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Runs `op` with exclusive access to `port_name`.
+    ///
+    /// If the monitor holds the same port it is suspended (the OS handle is
+    /// closed) for the duration of `op` and reopened afterwards, so flashing
+    /// never races the reader thread for the device. `on_state` is told about
+    /// the suspend and the resume.
+    pub fn with_port<T>(
+        &self,
+        port_name: &str,
+        reason: &str,
+        on_state: impl Fn(MonitorStateEvent),
+        op: impl FnOnce() -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let session = self.config.lock().unwrap().clone();
+        let baud_rate = match session {
+            Some((ref monitored, baud)) if monitored == port_name => baud,
+            // Monitor idle or on another port: nothing to arbitrate
+            _ => return op(),
+        };
+
+        let event = |state: &str| MonitorStateEvent {
+            state: state.to_string(),
+            port_name: port_name.to_string(),
+            reason: reason.to_string(),
+        };
+
+        {
+            let mut guard = self.port.lock().unwrap();
+            *self.suspended.lock().unwrap() = true;
+            // Dropping the port closes the OS handle
+            *guard = None;
+        }
+        info!("Monitor suspended for {} on {}", reason, port_name);
+        on_state(event("suspended"));
+
+        let result = op();
+
+        {
+            let mut guard = self.port.lock().unwrap();
+            // The session may have been disconnected while we held the port
+            if *self.should_run.lock().unwrap() {
+                match open_port(port_name, baud_rate) {
+                    Ok(port) => *guard = Some(port),
+                    Err(e) => {
+                        // Typical after a reset on native USB; the reader keeps retrying
+                        warn!("Resume reopen failed ({}), leaving it to the reader", e);
+                    }
+                }
+            }
+            *self.suspended.lock().unwrap() = false;
+        }
+        info!("Monitor resumed on {}", port_name);
+        on_state(event("resumed"));
+
+        result
+    }
+}
@@ -0,0 +1,246 @@
+//! Official MicroPython builds from micropython.org for the generic ESP32
+//! boards. Releases are read off the board's download page; images are
+//! checked (SHA-256, image header of the right chip) before they are kept
+//! in a cache directory for the flasher.
+
+use crate::chips;
+use crate::images;
+use crate::models::{AppError, MpyRelease};
+use crate::tasks::TaskHandle;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::info;
+
+const SITE: &str = "https://micropython.org";
+const TIMEOUT: Duration = Duration::from_secs(60);
+// Images are ~2 MB; anything far larger is not firmware
+const MAX_IMAGE: u64 = 16 * 1024 * 1024;
+// Read at a time, between progress reports and cancel checks
+const CHUNK: usize = 64 * 1024;
+
+/// A downloaded image, ready for the flasher.
+#[derive(Debug, Clone)]
+pub struct MpyImage {
+    pub path: PathBuf,
+    pub sha256: String,
+    /// Where the image goes: it starts with the bootloader
+    pub offset: u32,
+}
+
+fn unsupported(model: &str) -> AppError {
+    AppError::InvalidInput(format!("No official MicroPython build for {}", model))
+}
+
+/// Name of the generic board on micropython.org, e.g. `ESP32_GENERIC_S3`.
+pub fn board(model: &str) -> Option<&'static str> {
+    match chips::model_key(model).as_str() {
+        "esp32" => Some("ESP32_GENERIC"),
+        "esp32s2" => Some("ESP32_GENERIC_S2"),
+        "esp32c3" => Some("ESP32_GENERIC_C3"),
+        "esp32s3" => Some("ESP32_GENERIC_S3"),
+        "esp32c6" => Some("ESP32_GENERIC_C6"),
+        _ => None,
+    }
+}
+
+/// Flash offset of MicroPython images for `model`: they start with the
+/// bootloader.
+pub fn offset(model: &str) -> Option<u32> {
+    board(model).map(|_| chips::bootloader_offset(model))
+}
+
+/// Builds linked from a board's download page, newest first with
+/// releases ahead of previews.
+pub fn parse_releases(board: &str, page: &str) -> Vec<MpyRelease> {
+    let pattern = format!(
+        r#"/resources/firmware/({}-(\d{{8}})-(v[0-9][0-9A-Za-z.\-]*)\.bin)""#,
+        regex::escape(board)
+    );
+    let Ok(links) = Regex::new(&pattern) else {
+        return Vec::new();
+    };
+    let mut releases: Vec<MpyRelease> = Vec::new();
+    for caps in links.captures_iter(page) {
+        let file_name = caps[1].to_string();
+        if releases.iter().any(|r| r.file_name == file_name) {
+            continue;
+        }
+        releases.push(MpyRelease {
+            version: caps[3].to_string(),
+            date: caps[2].to_string(),
+            url: format!("{}/resources/firmware/{}", SITE, file_name),
+            preview: caps[3].contains("preview"),
+            file_name,
+        });
+    }
+    releases.sort_by(|a, b| a.preview.cmp(&b.preview).then_with(|| b.date.cmp(&a.date)));
+    releases
+}
+
+fn get(url: &str) -> Result<ureq::Response, AppError> {
+    ureq::get(url)
+        .timeout(TIMEOUT)
+        .call()
+        .map_err(|e| AppError::Connection(format!("{}: {}", url, e)))
+}
+
+/// Builds published for the chip `model`.
+pub fn releases(model: &str) -> Result<Vec<MpyRelease>, AppError> {
+    let board = board(model).ok_or_else(|| unsupported(model))?;
+    let page = get(&format!("{}/download/{}/", SITE, board))?
+        .into_string()
+        .map_err(|e| AppError::Connection(e.to_string()))?;
+    Ok(parse_releases(board, &page))
+}
+
+/// Checks `data` is an image built for `model`.
+pub fn verify_image(data: &[u8], model: &str) -> Result<(), AppError> {
+    board(model).ok_or_else(|| unsupported(model))?;
+    let Some((chip, _, _)) = images::image_info(data) else {
+        return Err(AppError::InvalidInput(
+            "The download is not an ESP firmware image".to_string(),
+        ));
+    };
+    if chip != chips::model_key(model) {
+        return Err(AppError::WrongChip(format!(
+            "The image is built for {}, not {}",
+            chip, model
+        )));
+    }
+    Ok(())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Verifies `data` and writes it into `cache` as `release`, next to a
+/// `.sha256` file. Fails when `expected_sha256` is given and differs.
+pub fn store(
+    cache: &Path,
+    release: &MpyRelease,
+    data: &[u8],
+    expected_sha256: Option<&str>,
+    model: &str,
+) -> Result<MpyImage, AppError> {
+    let offset = offset(model).ok_or_else(|| unsupported(model))?;
+    let sha256 = sha256_hex(data);
+    if let Some(expected) = expected_sha256 {
+        if !expected.trim().eq_ignore_ascii_case(&sha256) {
+            return Err(AppError::InvalidInput(format!(
+                "Checksum mismatch for {}: got {}",
+                release.file_name, sha256
+            )));
+        }
+    }
+    verify_image(data, model)?;
+    std::fs::create_dir_all(cache)?;
+    let path = cache.join(&release.file_name);
+    std::fs::write(&path, data)?;
+    std::fs::write(path.with_extension("sha256"), &sha256)?;
+    Ok(MpyImage {
+        path,
+        sha256,
+        offset,
+    })
+}
+
+/// A cached image that still matches its recorded checksum.
+fn cached(
+    cache: &Path,
+    release: &MpyRelease,
+    expected_sha256: Option<&str>,
+    model: &str,
+) -> Option<MpyImage> {
+    let path = cache.join(&release.file_name);
+    let recorded = std::fs::read_to_string(path.with_extension("sha256")).ok()?;
+    let data = std::fs::read(&path).ok()?;
+    let sha256 = sha256_hex(&data);
+    let expected = expected_sha256.unwrap_or(&recorded).trim();
+    if sha256 != recorded.trim() || !expected.eq_ignore_ascii_case(&sha256) {
+        return None;
+    }
+    verify_image(&data, model).ok()?;
+    Some(MpyImage {
+        path,
+        sha256,
+        offset: offset(model)?,
+    })
+}
+
+/// Reads the body of `response`, reporting progress on `task` when the
+/// server gives the length, and stopping when it is cancelled.
+fn read_body(response: ureq::Response, task: &TaskHandle) -> Result<Vec<u8>, AppError> {
+    let total = response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok())
+        .map_or(0, |len| len.min(MAX_IMAGE));
+    let mut reader = response.into_reader().take(MAX_IMAGE);
+    let mut data = Vec::new();
+    let mut chunk = vec![0; CHUNK];
+    loop {
+        task.checkpoint()?;
+        let len = reader
+            .read(&mut chunk)
+            .map_err(|e| AppError::Connection(e.to_string()))?;
+        if len == 0 {
+            return Ok(data);
+        }
+        data.extend_from_slice(&chunk[..len]);
+        if total > 0 {
+            task.progress(data.len() as u64, total);
+        }
+    }
+}
+
+/// The image of `release` for `model`, downloaded into `cache` unless an
+/// intact copy is there already.
+pub fn download(
+    cache: &Path,
+    release: &MpyRelease,
+    expected_sha256: Option<&str>,
+    model: &str,
+    task: &TaskHandle,
+) -> Result<MpyImage, AppError> {
+    if let Some(image) = cached(cache, release, expected_sha256, model) {
+        info!("Using cached {}", release.file_name);
+        return Ok(image);
+    }
+    info!("Downloading {}", release.url);
+    task.phase("downloading");
+    let data = read_body(get(&release.url)?, task)?;
+    store(cache, release, &data, expected_sha256, model)
+}
+
+/// `version` for `model` (`None` for the newest release), downloaded.
+pub fn fetch(
+    cache: &Path,
+    model: &str,
+    version: Option<&str>,
+    expected_sha256: Option<&str>,
+    task: &TaskHandle,
+) -> Result<MpyImage, AppError> {
+    task.phase("downloading");
+    let releases = releases(model)?;
+    task.checkpoint()?;
+    let release = match version {
+        Some(version) => releases
+            .iter()
+            .find(|r| r.version == version || r.version.trim_start_matches('v') == version),
+        None => releases.iter().find(|r| !r.preview),
+    }
+    .ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "MicroPython {} is not published for {}",
+            version.unwrap_or("release"),
+            model
+        ))
+    })?;
+    download(cache, release, expected_sha256, model, task)
+}
@@ -0,0 +1,238 @@
+//! JTAG debugging through OpenOCD: finds the adapter, writes the config for
+//! the chip behind it and keeps the `openocd` process running for GDB to
+//! attach to. Espressif's fork (`openocd-esp32`) is needed for the targets.
+
+use crate::chips;
+use crate::idf;
+use crate::models::{AppError, JtagProbe, OpenOcdStatus};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+const PROGRAM: &str = "openocd";
+pub const GDB_PORT: u16 = 3333;
+const TELNET_PORT: u16 = 4444;
+// Output lines kept for the panel
+const LOG_LIMIT: usize = 500;
+
+/// Chips with a USB-Serial/JTAG controller; the others need an ESP-Prog.
+const USB_JTAG_CHIPS: [&str; 7] = [
+    "esp32s3", "esp32c3", "esp32c5", "esp32c6", "esp32c61", "esp32h2", "esp32p4",
+];
+/// Chips `openocd-esp32` has a `target/<chip>.cfg` for.
+const TARGETS: [&str; 10] = [
+    "esp32", "esp32s2", "esp32s3", "esp32c2", "esp32c3", "esp32c5", "esp32c6", "esp32c61",
+    "esp32h2", "esp32p4",
+];
+
+fn probe_kind(vid: u16, pid: u16) -> Option<&'static str> {
+    match (vid, pid) {
+        (0x303A, 0x1001) => Some("usb_jtag"),
+        // FT2232H, channel A wired to JTAG
+        (0x0403, 0x6010) => Some("esp_prog"),
+        _ => None,
+    }
+}
+
+/// JTAG adapters on the USB bus, whether or not a driver claimed them.
+pub fn probes() -> Vec<JtagProbe> {
+    let Ok(devices) = nusb::list_devices() else {
+        return Vec::new();
+    };
+    devices
+        .filter_map(|dev| {
+            let (vid, pid) = (dev.vendor_id(), dev.product_id());
+            Some(JtagProbe {
+                kind: probe_kind(vid, pid)?.to_string(),
+                vid_pid: format!("{:04X}:{:04X}", vid, pid),
+                serial_number: dev.serial_number().map(str::to_string),
+                product_name: dev.product_string().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Whether `model` can be debugged over its own USB port.
+pub fn has_usb_jtag(model: &str) -> bool {
+    USB_JTAG_CHIPS.contains(&chips::model_key(model).as_str())
+}
+
+/// The adapter to debug a `model` board through: the chip's own USB-JTAG
+/// when it has one, else an ESP-Prog. A native USB port of another chip
+/// (or one in a different mode) is no use, so `None` means nothing fits.
+pub fn pick_probe<'a>(found: &'a [JtagProbe], model: &str) -> Option<&'a JtagProbe> {
+    let usb_jtag = found.iter().find(|p| p.kind == "usb_jtag");
+    let esp_prog = found.iter().find(|p| p.kind == "esp_prog");
+    if has_usb_jtag(model) {
+        usb_jtag.or(esp_prog)
+    } else {
+        esp_prog
+    }
+}
+
+/// OpenOCD config for a `model` board behind `probe`, serving GDB on
+/// `gdb_port`.
+pub fn config(model: &str, probe: &JtagProbe, gdb_port: u16) -> Result<String, AppError> {
+    let target = chips::model_key(model);
+    if !TARGETS.contains(&target.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "OpenOCD has no target for {}",
+            model
+        )));
+    }
+    let (interface, speed) = match probe.kind.as_str() {
+        "usb_jtag" if !has_usb_jtag(&target) => {
+            return Err(AppError::WrongChip(format!(
+                "{} has no built-in USB-JTAG; connect an ESP-Prog",
+                model
+            )));
+        }
+        "usb_jtag" => ("interface/esp_usb_jtag.cfg", 40000),
+        "esp_prog" => ("interface/ftdi/esp32_devkitj_v1.cfg", 20000),
+        other => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown JTAG adapter {}",
+                other
+            )))
+        }
+    };
+
+    let mut cfg = format!("# {} through {} ({})\n", model, probe.kind, probe.vid_pid);
+    writeln!(cfg, "source [find {}]", interface).ok();
+    // Pins the adapter down when more than one is plugged in
+    if let Some(serial) = &probe.serial_number {
+        writeln!(cfg, "adapter serial {}", serial).ok();
+    }
+    writeln!(cfg, "adapter speed {}", speed).ok();
+    writeln!(cfg, "source [find target/{}.cfg]", target).ok();
+    writeln!(cfg, "gdb_port {}", gdb_port).ok();
+    writeln!(cfg, "telnet_port {}", TELNET_PORT).ok();
+    Ok(cfg)
+}
+
+/// Whether an OpenOCD output line says GDB can attach now.
+pub fn is_ready(line: &str) -> bool {
+    line.contains("Listening on port") && line.contains("for gdb connections")
+}
+
+/// Finds `openocd` on `PATH` or as installed by ESP-IDF.
+pub fn find() -> Option<PathBuf> {
+    idf::find_tool("openocd-esp32", PROGRAM)
+}
+
+struct Shared {
+    log: VecDeque<String>,
+    ready: bool,
+}
+
+/// A running (or exited) OpenOCD, its output kept and handed to a callback.
+pub struct OpenOcd {
+    child: Child,
+    shared: Arc<Mutex<Shared>>,
+    chip_model: String,
+    probe: JtagProbe,
+    gdb_port: u16,
+}
+
+impl OpenOcd {
+    /// Writes the config for a `model` board behind `probe` and starts
+    /// `program` with it. `on_line` gets every output line, from a reader
+    /// thread.
+    pub fn start(
+        program: &Path,
+        model: &str,
+        probe: JtagProbe,
+        on_line: impl Fn(&str) + Send + Sync + 'static,
+    ) -> Result<Self, AppError> {
+        let cfg = config(model, &probe, GDB_PORT)?;
+        let cfg_path = std::env::temp_dir().join("esp32dev-openocd.cfg");
+        std::fs::write(&cfg_path, cfg).map_err(|e| AppError::Io(e.to_string()))?;
+
+        let mut child = Command::new(program)
+            .arg("-f")
+            .arg(&cfg_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| AppError::Io(format!("Cannot run {}: {}", program.display(), e)))?;
+        info!("OpenOCD started for {} through {}", model, probe.kind);
+
+        let shared = Arc::new(Mutex::new(Shared {
+            log: VecDeque::new(),
+            ready: false,
+        }));
+        let on_line = Arc::new(on_line);
+        if let Some(stdout) = child.stdout.take() {
+            forward(stdout, shared.clone(), on_line.clone());
+        }
+        // OpenOCD logs to stderr
+        if let Some(stderr) = child.stderr.take() {
+            forward(stderr, shared.clone(), on_line);
+        }
+        Ok(OpenOcd {
+            child,
+            shared,
+            chip_model: model.to_string(),
+            probe,
+            gdb_port: GDB_PORT,
+        })
+    }
+
+    pub fn status(&mut self) -> OpenOcdStatus {
+        let exit = self.child.try_wait().ok().flatten();
+        let shared = self.shared.lock().unwrap();
+        OpenOcdStatus {
+            running: exit.is_none(),
+            ready: exit.is_none() && shared.ready,
+            chip_model: Some(self.chip_model.clone()),
+            probe: Some(self.probe.clone()),
+            gdb_port: self.gdb_port,
+            exit_code: exit.and_then(|status| status.code()),
+            log: shared.log.iter().cloned().collect(),
+        }
+    }
+
+    /// Stops OpenOCD, dropping any GDB session attached to it.
+    pub fn stop(&mut self) {
+        if self.child.try_wait().ok().flatten().is_some() {
+            return;
+        }
+        if let Err(e) = self.child.kill() {
+            warn!("Cannot stop OpenOCD: {}", e);
+        }
+        self.child.wait().ok();
+        info!("OpenOCD stopped");
+    }
+}
+
+impl Drop for OpenOcd {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn forward(
+    stream: impl Read + Send + 'static,
+    shared: Arc<Mutex<Shared>>,
+    on_line: Arc<impl Fn(&str) + Send + Sync + 'static>,
+) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            {
+                let mut shared = shared.lock().unwrap();
+                shared.ready |= is_ready(&line);
+                if shared.log.len() == LOG_LIMIT {
+                    shared.log.pop_front();
+                }
+                shared.log.push_back(line.clone());
+            }
+            on_line(&line);
+        }
+    });
+}
@@ -0,0 +1,200 @@
+//! OTA slot selection the way the bootloader makes it. The partition table
+//! lists the app slots, and each of the two sectors of the `otadata`
+//! partition may hold a select entry; the valid entry with the highest
+//! sequence number boots OTA slot `(seq - 1) % slots`. With no valid entry
+//! the factory app boots, or `ota_0` when there is none.
+
+use crate::checksum::crc32_le;
+use crate::models::AppError;
+use serde::Serialize;
+
+pub const PARTITION_TABLE_OFFSET: u32 = 0x8000;
+pub const PARTITION_TABLE_LEN: u32 = 0xc00;
+const PARTITION_MAGIC: [u8; 2] = [0xaa, 0x50];
+const PARTITION_ENTRY: usize = 32;
+const TYPE_APP: u8 = 0x00;
+const TYPE_DATA: u8 = 0x01;
+const SUBTYPE_OTADATA: u8 = 0x00;
+const SUBTYPE_OTA_0: u8 = 0x10;
+const SUBTYPE_OTA_LAST: u8 = 0x1f;
+
+const SECTOR: usize = 0x1000;
+// esp_ota_select_entry_t: ota_seq, seq_label[20], ota_state, crc
+const SELECT_ENTRY: usize = 32;
+const STATE_AT: usize = 24;
+const CRC_AT: usize = 28;
+// ota_state values the bootloader skips an entry for
+const STATE_INVALID: u32 = 3;
+const STATE_ABORTED: u32 = 4;
+// Set when rollback is off; with it on the bootloader also takes it as valid
+const STATE_UNDEFINED: u32 = u32::MAX;
+
+/// An entry of the partition table.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Partition {
+    pub label: String,
+    pub kind: u8,
+    pub subtype: u8,
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// Entries of a partition table as read from flash, up to the MD5 entry or
+/// the erased end.
+pub fn partitions(table: &[u8]) -> Vec<Partition> {
+    table
+        .chunks_exact(PARTITION_ENTRY)
+        .take_while(|entry| entry[..2] == PARTITION_MAGIC)
+        .map(|entry| {
+            let label = &entry[12..28];
+            let end = label.iter().position(|&b| b == 0).unwrap_or(label.len());
+            Partition {
+                label: String::from_utf8_lossy(&label[..end]).into_owned(),
+                kind: entry[2],
+                subtype: entry[3],
+                offset: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                size: u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]),
+            }
+        })
+        .collect()
+}
+
+/// CRC the bootloader checks an entry's sequence number with.
+pub fn select_entry_crc(seq: u32) -> u32 {
+    crc32_le(u32::MAX, &seq.to_le_bytes())
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+/// Where the next app goes and what to write to boot it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtaUpdate {
+    pub slot: usize,
+    pub label: String,
+    pub app_offset: u32,
+    /// Flash offset of the otadata sector to rewrite
+    pub otadata_offset: u32,
+    /// The whole sector, erased apart from the select entry
+    pub otadata: Vec<u8>,
+}
+
+/// The otadata partition and OTA app slots of a partition table.
+#[derive(Debug, Clone)]
+pub struct OtaLayout {
+    pub otadata: Partition,
+    /// `ota_0`, `ota_1`, ... in order
+    pub slots: Vec<Partition>,
+}
+
+impl OtaLayout {
+    pub fn from_table(table: &[u8]) -> Result<Self, AppError> {
+        let partitions = partitions(table);
+        if partitions.is_empty() {
+            return Err(AppError::InvalidInput(
+                "No partition table found at 0x8000".to_string(),
+            ));
+        }
+        let otadata = partitions
+            .iter()
+            .find(|p| p.kind == TYPE_DATA && p.subtype == SUBTYPE_OTADATA)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::InvalidInput(
+                    "The partition table has no otadata partition, so the app has a single \
+                     slot: flash it at its offset instead"
+                        .to_string(),
+                )
+            })?;
+        let mut slots: Vec<Partition> = partitions
+            .into_iter()
+            .filter(|p| {
+                p.kind == TYPE_APP && (SUBTYPE_OTA_0..=SUBTYPE_OTA_LAST).contains(&p.subtype)
+            })
+            .collect();
+        slots.sort_by_key(|p| p.subtype);
+        if slots.is_empty() {
+            return Err(AppError::InvalidInput(
+                "The partition table has no OTA app slots".to_string(),
+            ));
+        }
+        Ok(OtaLayout { otadata, slots })
+    }
+
+    // Index of the select entry the bootloader goes by
+    fn active_entry(&self, otadata: &[u8]) -> Option<usize> {
+        (0..2)
+            .filter_map(|i| {
+                let entry = otadata.get(i * SECTOR..i * SECTOR + SELECT_ENTRY)?;
+                let seq = read_u32(entry, 0);
+                let state = read_u32(entry, STATE_AT);
+                let valid = seq != u32::MAX
+                    && read_u32(entry, CRC_AT) == select_entry_crc(seq)
+                    && state != STATE_INVALID
+                    && state != STATE_ABORTED;
+                valid.then_some((i, seq))
+            })
+            .max_by_key(|&(_, seq)| seq)
+            .map(|(i, _)| i)
+    }
+
+    /// OTA slot booting from `otadata` (both sectors); `None` when no select
+    /// entry is valid and the factory app or `ota_0` boots.
+    pub fn active_slot(&self, otadata: &[u8]) -> Option<usize> {
+        let entry = self.active_entry(otadata)?;
+        let seq = read_u32(&otadata[entry * SECTOR..], 0);
+        Some((seq.wrapping_sub(1) as usize) % self.slots.len())
+    }
+
+    /// Picks the slot after the running one for an app of `app_len` bytes
+    /// and the select entry that boots it, as `esp_ota_set_boot_partition`
+    /// would write it.
+    pub fn update(&self, otadata: &[u8], app_len: usize) -> Result<OtaUpdate, AppError> {
+        let count = self.slots.len();
+        let active = self.active_entry(otadata);
+        let slot = match self.active_slot(otadata) {
+            Some(running) => (running + 1) % count,
+            None => 0,
+        };
+        let target = &self.slots[slot];
+        if app_len > target.size as usize {
+            return Err(AppError::InvalidInput(format!(
+                "The app ({} bytes) doesn't fit {} ({} bytes)",
+                app_len, target.label, target.size
+            )));
+        }
+
+        // Lowest sequence number at or above the active one that selects
+        // `slot`, written over the other entry
+        let (seq, entry) = match active {
+            Some(i) => {
+                let current = read_u32(&otadata[i * SECTOR..], 0);
+                let base = ((slot + 1) % count) as u32;
+                let mut seq = base;
+                while current > seq {
+                    seq += count as u32;
+                }
+                (seq, 1 - i)
+            }
+            None => (slot as u32 + 1, 0),
+        };
+        let mut sector = vec![0xff; SECTOR];
+        // Keep the entry's label, as the device does
+        if let Some(old) = otadata.get(entry * SECTOR..entry * SECTOR + SELECT_ENTRY) {
+            sector[4..STATE_AT].copy_from_slice(&old[4..STATE_AT]);
+        }
+        sector[..4].copy_from_slice(&seq.to_le_bytes());
+        sector[STATE_AT..CRC_AT].copy_from_slice(&STATE_UNDEFINED.to_le_bytes());
+        sector[CRC_AT..SELECT_ENTRY].copy_from_slice(&select_entry_crc(seq).to_le_bytes());
+
+        Ok(OtaUpdate {
+            slot,
+            label: target.label.clone(),
+            app_offset: target.offset,
+            otadata_offset: self.otadata.offset + (entry * SECTOR) as u32,
+            otadata: sector,
+        })
+    }
+}
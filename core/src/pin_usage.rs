@@ -0,0 +1,68 @@
+//! Pins a firmware uses, for checking the wiring against the pinout. They
+//! come from an ESP-IDF `sdkconfig`, where Kconfig options such as
+//! `CONFIG_I2C_MASTER_SDA=21` name them, or from a JSON list of
+//! `{"gpio": 21, "role": "i2c", "signal": "SDA"}`.
+
+use crate::models::{AppError, PinRole, PinUsage};
+use std::path::Path;
+
+// Higher than any GPIO on a current chip
+const MAX_GPIO: u32 = 64;
+
+// Words marking a Kconfig option as a pin wherever they appear
+const PIN_WORDS: [&str; 3] = ["GPIO", "PIN", "IO"];
+// Words naming a pin when they end the option, e.g. `I2C_MASTER_SDA`; `TX`
+// on its own also shows up in e.g. `ESP_WIFI_TX_BUFFER_TYPE`
+const SIGNAL_WORDS: [&str; 15] = [
+    "SDA", "SCL", "MOSI", "MISO", "SCLK", "CLK", "CS", "TX", "RX", "TXD", "RXD", "RTS", "CTS",
+    "LED", "BUTTON",
+];
+
+/// Reads a pin list from `text`, JSON or `sdkconfig` lines.
+pub fn parse(text: &str) -> Result<Vec<PinUsage>, AppError> {
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(text)
+            .map_err(|e| AppError::InvalidInput(format!("Invalid pin list: {}", e)));
+    }
+    Ok(from_sdkconfig(text))
+}
+
+/// Reads the pin list from a file, see [`parse`].
+pub fn load(path: &Path) -> Result<Vec<PinUsage>, AppError> {
+    parse(&std::fs::read_to_string(path)?)
+}
+
+/// Pins set in an `sdkconfig`. Options set to -1 (unused) are left out.
+pub fn from_sdkconfig(text: &str) -> Vec<PinUsage> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("CONFIG_")?.split_once('='))
+        .filter_map(|(name, value)| {
+            let words: Vec<&str> = name.split('_').collect();
+            let last = words.last().copied().unwrap_or_default();
+            if !SIGNAL_WORDS.contains(&last) && !words.iter().any(|w| PIN_WORDS.contains(w)) {
+                return None;
+            }
+            let gpio = value.trim().parse::<u32>().ok().filter(|&g| g < MAX_GPIO)?;
+            Some(PinUsage {
+                gpio,
+                role: role(&words),
+                signal: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn role(words: &[&str]) -> PinRole {
+    let has = |names: &[&str]| words.iter().any(|w| names.contains(w));
+    if has(&["I2C", "SDA", "SCL"]) {
+        PinRole::I2c
+    } else if has(&["SPI", "MOSI", "MISO", "SCLK", "CS"]) {
+        PinRole::Spi
+    } else if has(&["UART", "TX", "RX", "TXD", "RXD", "RTS", "CTS"]) {
+        PinRole::Uart
+    } else if has(&["PWM", "LEDC", "MCPWM"]) {
+        PinRole::Pwm
+    } else {
+        PinRole::Gpio
+    }
+}
@@ -0,0 +1,149 @@
+//! PlatformIO projects. `pio run` leaves bootloader, partition table and
+//! app under `.pio/build/<env>/`; where each goes is read off the images
+//! themselves, the way `pio run -t upload` would lay them out.
+
+use crate::images::BuildImages;
+use crate::models::{AppError, FlashPlan};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const INI: &str = "platformio.ini";
+
+/// Sections of a `platformio.ini`, with multi-line values joined by `\n`.
+pub type Ini = HashMap<String, HashMap<String, String>>;
+
+pub fn parse_ini(text: &str) -> Ini {
+    let mut ini = Ini::new();
+    let mut section = String::new();
+    let mut last_key = None::<String>;
+    for raw in text.lines() {
+        let line = raw.split(" ;").next().unwrap_or(raw).trim_end();
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with(';') || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            section = name.trim().to_string();
+            ini.entry(section.clone()).or_default();
+            last_key = None;
+            continue;
+        }
+        let values = ini.entry(section.clone()).or_default();
+        // Indented lines continue the previous value
+        if line.starts_with(char::is_whitespace) {
+            if let Some(value) = last_key.as_ref().and_then(|key| values.get_mut(key)) {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(trimmed);
+                continue;
+            }
+        }
+        if let Some((key, value)) = trimmed.split_once('=') {
+            let key = key.trim().to_string();
+            values.insert(key.clone(), value.trim().to_string());
+            last_key = Some(key);
+        }
+    }
+    ini
+}
+
+/// `[env:<name>]` sections in file order, which the map does not keep.
+fn env_names(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| {
+            let name = line.trim().strip_prefix("[env:")?.strip_suffix(']')?;
+            Some(name.trim().to_string())
+        })
+        .collect()
+}
+
+/// `key` of environment `env`, following `extends` and falling back to the
+/// shared `[env]` section.
+pub fn env_value(ini: &Ini, env: &str, key: &str) -> Option<String> {
+    let mut section = format!("env:{}", env);
+    // extends chains are short; the bound only stops cycles
+    for _ in 0..8 {
+        let values = ini.get(&section)?;
+        if let Some(value) = values.get(key) {
+            return Some(value.clone());
+        }
+        match values.get("extends") {
+            Some(parent) => section = parent.trim().to_string(),
+            None => break,
+        }
+    }
+    ini.get("env").and_then(|values| values.get(key)).cloned()
+}
+
+/// The environment `pio run` builds first: the first of `default_envs`,
+/// or else the first `[env:...]` section.
+pub fn default_env(text: &str, ini: &Ini) -> Option<String> {
+    let configured = ini
+        .get("platformio")
+        .and_then(|values| values.get("default_envs"))
+        .and_then(|envs| {
+            envs.split([',', '\n'])
+                .map(str::trim)
+                .find(|env| !env.is_empty())
+                .map(str::to_string)
+        });
+    configured.or_else(|| env_names(text).into_iter().next())
+}
+
+/// `boot_app0.bin` of an installed Arduino core, if any.
+fn boot_app0() -> Option<PathBuf> {
+    let core_dir = std::env::var_os("PLATFORMIO_CORE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+            Some(PathBuf::from(home).join(".platformio"))
+        })?;
+    let path =
+        core_dir.join("packages/framework-arduinoespressif32/tools/partitions/boot_app0.bin");
+    path.is_file().then_some(path)
+}
+
+/// The images of `env` (the default environment when `None`) of the
+/// PlatformIO project at `project`, with its `upload_speed`.
+pub fn load(project: &Path, env: Option<&str>) -> Result<FlashPlan, AppError> {
+    let text = std::fs::read_to_string(project.join(INI))
+        .map_err(|_| AppError::InvalidInput(format!("{} has no {}", project.display(), INI)))?;
+    let ini = parse_ini(&text);
+    let env = match env {
+        Some(env) => env.to_string(),
+        None => default_env(&text, &ini).ok_or_else(|| {
+            AppError::InvalidInput(format!("{} defines no [env:...] section", INI))
+        })?,
+    };
+    if !ini.contains_key(&format!("env:{}", env)) {
+        return Err(AppError::InvalidInput(format!(
+            "{} has no environment {}",
+            INI, env
+        )));
+    }
+
+    let build_root = ini
+        .get("platformio")
+        .and_then(|values| values.get("build_dir"))
+        .map(|dir| project.join(dir))
+        .unwrap_or_else(|| project.join(".pio/build"));
+    let build_dir = build_root.join(&env);
+    let arduino = env_value(&ini, &env, "framework")
+        .is_some_and(|framework| framework.split([',', '\n']).any(|f| f.trim() == "arduino"));
+    let images = BuildImages {
+        bootloader: build_dir.join("bootloader.bin"),
+        partitions: build_dir.join("partitions.bin"),
+        app: build_dir.join("firmware.bin"),
+        boot_app0: boot_app0().filter(|_| arduino),
+    };
+    let mut plan = images.plan(&build_dir, |path| {
+        AppError::InvalidInput(format!(
+            "{} is missing; run `pio run -e {}` first",
+            path.display(),
+            env
+        ))
+    })?;
+    plan.baud_rate = env_value(&ini, &env, "upload_speed").and_then(|speed| speed.parse().ok());
+    Ok(plan)
+}
@@ -0,0 +1,263 @@
+//! Everything known about one board in a single document, for RMA tickets
+//! and lab inventory: the USB port, the chip details, the security eFuses,
+//! the partition table and the flash chip ID. Saved as JSON or as HTML
+//! meant for printing.
+
+use crate::junit::escape;
+use crate::models::{ChipDetails, DeviceStatus, MemoryInfo};
+use crate::otadata::Partition;
+use serde::Serialize;
+use std::fmt::Write;
+
+// Flag bits of the ROM's GET_SECURITY_INFO response
+const SECURE_BOOT_EN: u32 = 1 << 0;
+const SECURE_DOWNLOAD_ENABLE: u32 = 1 << 2;
+const SOFT_DIS_JTAG: u32 = 1 << 6;
+const HARD_DIS_JTAG: u32 = 1 << 7;
+const DIS_USB: u32 = 1 << 8;
+
+/// The security eFuses as GET_SECURITY_INFO reports them. Not available
+/// from the ESP32 ROM.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SecuritySummary {
+    pub flags: u32,
+    pub secure_boot: bool,
+    pub secure_download: bool,
+    pub jtag_disabled: bool,
+    pub usb_disabled: bool,
+    pub flash_crypt_cnt: u8,
+    // An odd number of bits set in the counter
+    pub flash_encryption: bool,
+    // Purpose of each eFuse key block, e.g. 4 for XTS_AES_128_KEY
+    pub key_purposes: Vec<u8>,
+}
+
+impl SecuritySummary {
+    pub fn new(flags: u32, flash_crypt_cnt: u8, key_purposes: &[u8]) -> Self {
+        SecuritySummary {
+            flags,
+            secure_boot: flags & SECURE_BOOT_EN != 0,
+            secure_download: flags & SECURE_DOWNLOAD_ENABLE != 0,
+            jtag_disabled: flags & (SOFT_DIS_JTAG | HARD_DIS_JTAG) != 0,
+            usb_disabled: flags & DIS_USB != 0,
+            flash_crypt_cnt,
+            flash_encryption: flash_crypt_cnt.count_ones() % 2 == 1,
+            key_purposes: key_purposes.to_vec(),
+        }
+    }
+}
+
+/// JEDEC ID of the SPI flash chip, split the way esptool.py prints it.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlashId {
+    pub manufacturer: u8,
+    // Memory type and capacity bytes
+    pub device: u16,
+}
+
+impl FlashId {
+    /// From the 24 bits of the RDID response, as the loader returns them.
+    pub fn from_raw(id: u32) -> Self {
+        FlashId {
+            manufacturer: (id & 0xff) as u8,
+            device: (((id >> 8) & 0xff) << 8 | ((id >> 16) & 0xff)) as u16,
+        }
+    }
+}
+
+impl std::fmt::Display for FlashId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02X} {:04X}", self.manufacturer, self.device)
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceReport {
+    // Seconds since the Unix epoch
+    pub generated_at: u64,
+    pub port_name: String,
+    // What the OS reports for the port, when the board was tracked
+    pub status: Option<DeviceStatus>,
+    pub chip: ChipDetails,
+    pub security: Option<SecuritySummary>,
+    // Empty when the table couldn't be read or the flash is blank
+    pub partitions: Vec<Partition>,
+    pub flash_id: Option<FlashId>,
+}
+
+impl DeviceReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// A standalone page with the same content, laid out for printing.
+    pub fn to_html(&self) -> String {
+        let chip = &self.chip;
+        let title = format!(
+            "{} on {}",
+            chip.chip_model.as_deref().unwrap_or("Device"),
+            self.port_name
+        );
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             <style>\nbody {{ font-family: sans-serif; margin: 2em; }}\n\
+             table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n\
+             th, td {{ border: 1px solid #999; padding: 4px 8px; text-align: left; }}\n\
+             th {{ background: #eee; }}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n\
+             <p>Generated {} (Unix time)</p>\n",
+            escape(&title),
+            escape(&title),
+            self.generated_at
+        );
+
+        let mut rows = Vec::new();
+        if let Some(status) = &self.status {
+            rows.push(("Product", status.product_name.clone()));
+            rows.push(("Serial number", status.serial_number.clone()));
+            rows.push(("VID:PID", status.vid_pid.clone()));
+            rows.push(("Connection", status.connection_type.clone()));
+        }
+        field_table(&mut html, "Port", &self.port_name, rows);
+
+        let rows = vec![
+            ("Model", chip.chip_model.clone()),
+            ("Revision", chip.chip_revision.clone()),
+            ("Package", chip.package.clone()),
+            ("MAC address", chip.mac_address.clone()),
+            ("Flash size", chip.flash_size.clone()),
+            ("Flash", chip.flash.as_ref().map(describe_memory)),
+            ("PSRAM", chip.psram.as_ref().map(describe_memory)),
+            ("Flash ID", self.flash_id.map(|id| id.to_string())),
+            ("Crystal", chip.crystal_frequency.clone()),
+            ("ROM", chip.rom_version.clone()),
+            ("App ESP-IDF", chip.sdk_version.clone()),
+            ("Features", chip.features.clone()),
+            (
+                "Secure Download Mode",
+                chip.secure_download_mode.then(|| "enabled".to_string()),
+            ),
+        ];
+        field_table(&mut html, "Chip", "", rows);
+
+        if let Some(security) = &self.security {
+            let on = |enabled: bool| Some(if enabled { "yes" } else { "no" }.to_string());
+            let purposes = security
+                .key_purposes
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let rows = vec![
+                ("Flags", Some(format!("0x{:08X}", security.flags))),
+                ("Secure Boot", on(security.secure_boot)),
+                (
+                    "Flash encryption",
+                    Some(format!(
+                        "{} (FLASH_CRYPT_CNT {:#x})",
+                        if security.flash_encryption {
+                            "yes"
+                        } else {
+                            "no"
+                        },
+                        security.flash_crypt_cnt
+                    )),
+                ),
+                ("Secure Download Mode", on(security.secure_download)),
+                ("JTAG disabled", on(security.jtag_disabled)),
+                ("USB disabled", on(security.usb_disabled)),
+                ("Key purposes", Some(purposes)),
+            ];
+            field_table(&mut html, "eFuse", "", rows);
+        }
+
+        html.push_str("<h2>Partition table</h2>\n");
+        if self.partitions.is_empty() {
+            html.push_str("<p>Not read</p>\n");
+        } else {
+            html.push_str(
+                "<table>\n<tr><th>Label</th><th>Type</th><th>Offset</th><th>Size</th></tr>\n",
+            );
+            for p in &self.partitions {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>0x{:X}</td><td>0x{:X}</td></tr>",
+                    escape(&p.label),
+                    partition_type(p),
+                    p.offset,
+                    p.size
+                );
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+// A two-column table of the fields that are known
+fn field_table(html: &mut String, heading: &str, caption: &str, rows: Vec<(&str, Option<String>)>) {
+    let _ = writeln!(html, "<h2>{}</h2>", escape(heading));
+    if !caption.is_empty() {
+        let _ = writeln!(html, "<p>{}</p>", escape(caption));
+    }
+    html.push_str("<table>\n");
+    for (name, value) in rows {
+        if let Some(value) = value {
+            let _ = writeln!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape(name),
+                escape(&value)
+            );
+        }
+    }
+    html.push_str("</table>\n");
+}
+
+fn describe_memory(memory: &MemoryInfo) -> String {
+    let place = if memory.embedded {
+        "embedded"
+    } else {
+        "external"
+    };
+    [
+        Some(place),
+        memory.size.as_deref(),
+        memory.vendor.as_deref(),
+        memory.mode.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+// Type and subtype the way partition CSV files name them
+fn partition_type(p: &Partition) -> String {
+    let subtype = match (p.kind, p.subtype) {
+        (0x00, 0x00) => "factory".to_string(),
+        (0x00, 0x10..=0x1f) => format!("ota_{}", p.subtype - 0x10),
+        (0x00, 0x20) => "test".to_string(),
+        (0x01, 0x00) => "ota".to_string(),
+        (0x01, 0x01) => "phy".to_string(),
+        (0x01, 0x02) => "nvs".to_string(),
+        (0x01, 0x03) => "coredump".to_string(),
+        (0x01, 0x04) => "nvs_keys".to_string(),
+        (0x01, 0x05) => "efuse".to_string(),
+        (0x01, 0x81) => "fat".to_string(),
+        (0x01, 0x82) => "spiffs".to_string(),
+        (0x01, 0x83) => "littlefs".to_string(),
+        (_, subtype) => format!("0x{:02x}", subtype),
+    };
+    let kind = match p.kind {
+        0x00 => "app".to_string(),
+        0x01 => "data".to_string(),
+        kind => format!("0x{:02x}", kind),
+    };
+    format!("{}, {}", kind, subtype)
+}
@@ -0,0 +1,118 @@
+//! Why a board reset, from its serial output: the reason the ROM prints at
+//! the start of every boot (`rst:0xf (BROWNOUT_RST)`), and the brownout,
+//! watchdog or panic banner the firmware printed on its way down, sorted
+//! into a few causes the UI can explain.
+
+use crate::models::{ResetCause, ResetReason};
+use crate::soak::{BROWNOUT, PANIC};
+use regex::Regex;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Longest partial line kept while waiting for its newline
+const MAX_LINE: usize = 4096;
+
+static ROM_REASON: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"rst:(0x[0-9a-fA-F]+) \(([A-Za-z0-9_]+)\)").unwrap());
+static WATCHDOG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)task watchdog got triggered|interrupt wdt timeout|rtc watchdog").unwrap()
+});
+
+/// Cause of a reset from the name the ROM gives it. The names differ
+/// between chips (`SW_CPU_RESET`, `RTC_SW_CPU_RST`), so they are matched
+/// by their parts rather than their numbers.
+pub fn rom_cause(name: &str) -> ResetCause {
+    let name = name.to_ascii_uppercase();
+    if name.contains("BROWN") || name.contains("GLITCH") {
+        ResetCause::Power
+    } else if name.starts_with("POWERON") {
+        ResetCause::PowerOn
+    } else if name.contains("DEEPSLEEP") {
+        ResetCause::DeepSleepWake
+    } else if name.contains("WDT") {
+        ResetCause::Watchdog
+    } else if name.contains("SW_") {
+        ResetCause::Software
+    } else if name.contains("USB") || name.contains("JTAG") || name.starts_with("EXT") {
+        ResetCause::External
+    } else {
+        ResetCause::Unknown
+    }
+}
+
+// Cause given away by a line the firmware prints before it resets
+fn banner_cause(line: &str) -> Option<ResetCause> {
+    // An interrupt watchdog shows as a panic, so watchdogs go first
+    if BROWNOUT.is_match(line) {
+        Some(ResetCause::Power)
+    } else if WATCHDOG.is_match(line) {
+        Some(ResetCause::Watchdog)
+    } else if PANIC.is_match(line) {
+        Some(ResetCause::Panic)
+    } else {
+        None
+    }
+}
+
+/// Follows a serial stream and reports each reset at the ROM's reason
+/// line, explained by the first banner seen since the reset before.
+#[derive(Default)]
+pub struct ResetAnalyzer {
+    line: Vec<u8>,
+    banner: Option<(ResetCause, String)>,
+}
+
+impl ResetAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds output from the board; returns a reset it just showed.
+    pub fn push(&mut self, bytes: &[u8]) -> Option<ResetReason> {
+        let mut found = None;
+        for &byte in bytes {
+            if byte != b'\n' {
+                if self.line.len() < MAX_LINE {
+                    self.line.push(byte);
+                }
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&line);
+            if let Some(reset) = self.line_done(line.trim_end_matches('\r')) {
+                found = Some(reset);
+            }
+        }
+        found
+    }
+
+    fn line_done(&mut self, line: &str) -> Option<ResetReason> {
+        let Some(caps) = ROM_REASON.captures(line) else {
+            if self.banner.is_none() {
+                self.banner = banner_cause(line).map(|cause| (cause, line.trim().to_string()));
+            }
+            return None;
+        };
+        let rom = rom_cause(&caps[2]);
+        let banner = self.banner.take();
+        // A banner from a crash the firmware survived says nothing about
+        // the board being unplugged or woken later
+        let explained = !matches!(
+            rom,
+            ResetCause::PowerOn | ResetCause::DeepSleepWake | ResetCause::External
+        );
+        let (cause, banner) = match banner {
+            Some((cause, line)) if explained => (cause, Some(line)),
+            _ => (rom, None),
+        };
+        Some(ResetReason {
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            cause,
+            rom_reason: format!("{} ({})", &caps[1], &caps[2]),
+            banner,
+        })
+    }
+}
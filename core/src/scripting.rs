@@ -0,0 +1,137 @@
+use crate::console::Console;
+use crate::esp_interaction;
+use crate::models::AppError;
+use crate::tasks::TaskHandle;
+use rhai::{Dynamic, Engine, EvalAltResult};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+// Used by `expect(pattern)` when the script doesn't pass a timeout
+pub(crate) const DEFAULT_EXPECT_TIMEOUT_MS: u64 = 5000;
+
+/// Where a script runs and the defaults for its `flash` calls.
+pub struct ScriptOptions {
+    pub port_name: String,
+    pub baud_rate: u32,
+    pub flash_address: u32,
+    pub flash_baud: u32,
+}
+
+type RhaiResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn throw<T>(result: Result<T, AppError>) -> RhaiResult<T> {
+    // Carry the AppError through rhai so callers still see its kind
+    result.map_err(|e| EvalAltResult::ErrorRuntime(Dynamic::from(e), rhai::Position::NONE).into())
+}
+
+fn into_app_error(e: EvalAltResult) -> AppError {
+    match e {
+        EvalAltResult::ErrorRuntime(ref value, _) if value.is::<AppError>() => {
+            value.clone().cast::<AppError>()
+        }
+        EvalAltResult::ErrorInFunctionCall(_, _, inner, _) => into_app_error(*inner),
+        EvalAltResult::ErrorTerminated(..) => AppError::Cancelled,
+        other => AppError::Script(other.to_string()),
+    }
+}
+
+fn millis(ms: i64) -> Duration {
+    Duration::from_millis(u64::try_from(ms).unwrap_or(0))
+}
+
+/// Runs a rhai automation script against `options.port_name`.
+///
+/// Scripts drive the board with `send(text)`, `send_raw(text)`,
+/// `expect(regex[, timeout_ms])`, `wait(ms)`, `clear()`, `reset()`,
+/// `erase()` and `flash(path[, address])`. Serial output and `print`
+/// lines are passed to `output` as they arrive.
+pub fn run(
+    source: &str,
+    options: &ScriptOptions,
+    task: &TaskHandle,
+    output: impl Fn(&str) + Send + Sync + 'static,
+) -> Result<String, AppError> {
+    let output: Arc<dyn Fn(&str) + Send + Sync> = Arc::new(output);
+
+    let mut engine = Engine::new();
+    // Compile first so syntax errors don't touch the device
+    let ast = engine
+        .compile(source)
+        .map_err(|e| AppError::Script(e.to_string()))?;
+
+    task.phase("connecting");
+    let echo = output.clone();
+    let console = Rc::new(Console::open(
+        &options.port_name,
+        options.baud_rate,
+        task.clone(),
+        move |bytes| echo(&String::from_utf8_lossy(bytes)),
+    )?);
+
+    let print = output.clone();
+    engine.on_print(move |text| print(&format!("{}\n", text)));
+
+    let cancel = task.clone();
+    engine.on_progress(move |_| cancel.is_cancelled().then_some(Dynamic::UNIT));
+
+    let c = console.clone();
+    engine.register_fn("send", move |text: &str| -> RhaiResult<()> {
+        throw(c.send(&format!("{}\r\n", text)))
+    });
+    let c = console.clone();
+    engine.register_fn("send_raw", move |text: &str| -> RhaiResult<()> {
+        throw(c.send(text))
+    });
+    let c = console.clone();
+    engine.register_fn("expect", move |pattern: &str| -> RhaiResult<String> {
+        throw(c.expect(pattern, Duration::from_millis(DEFAULT_EXPECT_TIMEOUT_MS)))
+    });
+    let c = console.clone();
+    engine.register_fn(
+        "expect",
+        move |pattern: &str, timeout_ms: i64| -> RhaiResult<String> {
+            throw(c.expect(pattern, millis(timeout_ms)))
+        },
+    );
+    let c = console.clone();
+    engine.register_fn("wait", move |ms: i64| -> RhaiResult<()> {
+        throw(c.wait(millis(ms)))
+    });
+    let c = console.clone();
+    engine.register_fn("clear", move || c.clear());
+    let c = console.clone();
+    engine.register_fn("reset", move || -> RhaiResult<()> { throw(c.reset()) });
+    let c = console.clone();
+    engine.register_fn("erase", move || -> RhaiResult<String> { throw(c.erase()) });
+    let (c, address, baud) = (console.clone(), options.flash_address, options.flash_baud);
+    engine.register_fn("flash", move |path: &str| -> RhaiResult<String> {
+        throw(c.flash(path, address, baud))
+    });
+    let (c, baud) = (console.clone(), options.flash_baud);
+    engine.register_fn(
+        "flash",
+        move |path: &str, address: &str| -> RhaiResult<String> {
+            let address = throw(esp_interaction::parse_flash_address(address))?;
+            throw(c.flash(path, address, baud))
+        },
+    );
+    let (c, baud) = (console.clone(), options.flash_baud);
+    engine.register_fn(
+        "flash",
+        move |path: &str, address: i64| -> RhaiResult<String> {
+            let address = throw(u32::try_from(address).map_err(|_| {
+                AppError::InvalidInput(format!("Invalid flash address: {}", address))
+            }))?;
+            throw(c.flash(path, address, baud))
+        },
+    );
+
+    task.phase("running");
+    info!("Running script on {}", options.port_name);
+    engine.run_ast(&ast).map_err(|e| into_app_error(*e))?;
+    console.task().checkpoint()?;
+
+    Ok("Script finished".to_string())
+}
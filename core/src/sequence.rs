@@ -0,0 +1,180 @@
+//! Test sequences: a fixed list of steps (flash, reset, send, expect, ...)
+//! run against a board, with a pass/fail result per step. The declarative
+//! counterpart of [`scripting`](crate::scripting), for firmware QA runs.
+
+use crate::console::Console;
+use crate::esp_interaction;
+use crate::models::{AppError, StepResult, StepStatus, TestReport, TestSequence, TestStep};
+use crate::scripting::{ScriptOptions, DEFAULT_EXPECT_TIMEOUT_MS};
+use crate::tasks::TaskHandle;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Reads a sequence file. Relative image paths are taken from the file's
+/// folder, so a sequence can sit next to the images it flashes.
+pub fn load(path: &Path) -> Result<TestSequence, AppError> {
+    let text = std::fs::read_to_string(path)?;
+    let mut sequence: TestSequence = serde_json::from_str(&text)
+        .map_err(|e| AppError::InvalidInput(format!("{}: {}", path.display(), e)))?;
+    if sequence.steps.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "{} has no steps",
+            path.display()
+        )));
+    }
+    if sequence.name.is_empty() {
+        sequence.name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+    }
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for step in &mut sequence.steps {
+        if let TestStep::Flash { path, .. } = step {
+            if Path::new(path.as_str()).is_relative() {
+                *path = dir.join(path.as_str()).display().to_string();
+            }
+        }
+    }
+    Ok(sequence)
+}
+
+/// One-line summary of a step for reports, e.g. `expect /READY/`.
+pub fn describe(step: &TestStep) -> String {
+    match step {
+        TestStep::Flash {
+            path,
+            address: Some(address),
+        } => format!("flash {} @ {}", path, address),
+        TestStep::Flash { path, .. } => format!("flash {}", path),
+        TestStep::Erase => "erase".to_string(),
+        TestStep::Reset => "reset".to_string(),
+        TestStep::Send { text } => format!("send {:?}", text),
+        TestStep::Expect { pattern, .. } => format!("expect /{}/", pattern),
+        TestStep::Wait { ms } => format!("wait {} ms", ms),
+    }
+}
+
+impl TestReport {
+    /// The report as a task result: failed sequences are errors naming
+    /// the first failing step.
+    pub fn outcome(&self) -> Result<String, AppError> {
+        let failed = self
+            .steps
+            .iter()
+            .position(|s| s.status == StepStatus::Failed);
+        match failed {
+            None => Ok(format!("All {} steps passed", self.steps.len())),
+            Some(i) => {
+                let step = &self.steps[i];
+                Err(AppError::Script(format!(
+                    "Step {} ({}) failed: {}",
+                    i + 1,
+                    step.description,
+                    step.detail.as_deref().unwrap_or("no detail")
+                )))
+            }
+        }
+    }
+}
+
+fn execute(
+    console: &Console,
+    step: &TestStep,
+    options: &ScriptOptions,
+) -> Result<Option<String>, AppError> {
+    match step {
+        TestStep::Flash { path, address } => {
+            let address = match address {
+                Some(address) => esp_interaction::parse_flash_address(address)?,
+                None => options.flash_address,
+            };
+            console.flash(path, address, options.flash_baud).map(Some)
+        }
+        TestStep::Erase => console.erase().map(Some),
+        TestStep::Reset => console.reset().map(|_| None),
+        TestStep::Send { text } => console.send(&format!("{}\r\n", text)).map(|_| None),
+        TestStep::Expect {
+            pattern,
+            timeout_ms,
+        } => {
+            let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_EXPECT_TIMEOUT_MS));
+            console.expect(pattern, timeout).map(Some)
+        }
+        TestStep::Wait { ms } => console.wait(Duration::from_millis(*ms)).map(|_| None),
+    }
+}
+
+/// Runs `sequence` against `options.port_name`. Steps after the first
+/// failure are skipped; device output and step headers go to `output`.
+///
+/// Only cancellation or failing to open the port is an error, a failed
+/// step is reported in the returned [`TestReport`].
+pub fn run(
+    sequence: &TestSequence,
+    options: &ScriptOptions,
+    task: &TaskHandle,
+    output: impl Fn(&str) + Send + Sync + 'static,
+) -> Result<TestReport, AppError> {
+    let output = std::sync::Arc::new(output);
+    let started = Instant::now();
+
+    task.phase("connecting");
+    let echo = output.clone();
+    let console = Console::open(
+        &options.port_name,
+        sequence.baud_rate.unwrap_or(options.baud_rate),
+        task.clone(),
+        move |bytes| echo(&String::from_utf8_lossy(bytes)),
+    )?;
+
+    task.phase("running");
+    info!(
+        "Running test sequence {:?} on {}",
+        sequence.name, options.port_name
+    );
+    let total = sequence.steps.len() as u64;
+    let mut failed = false;
+    let mut steps = Vec::with_capacity(sequence.steps.len());
+    for (i, step) in sequence.steps.iter().enumerate() {
+        let description = describe(step);
+        if failed {
+            steps.push(StepResult {
+                description,
+                status: StepStatus::Skipped,
+                duration_ms: 0,
+                detail: None,
+            });
+            continue;
+        }
+
+        task.progress(i as u64, total);
+        output(&format!("\n== [{}/{}] {} ==\n", i + 1, total, description));
+        let step_started = Instant::now();
+        let (status, detail) = match execute(&console, step, options) {
+            Ok(detail) => (StepStatus::Passed, detail),
+            Err(AppError::Cancelled) => return Err(AppError::Cancelled),
+            Err(e) => {
+                failed = true;
+                (StepStatus::Failed, Some(e.message()))
+            }
+        };
+        steps.push(StepResult {
+            description,
+            status,
+            duration_ms: step_started.elapsed().as_millis() as u64,
+            detail,
+        });
+    }
+    task.progress(total, total);
+
+    Ok(TestReport {
+        name: sequence.name.clone(),
+        port_name: options.port_name.clone(),
+        passed: !failed,
+        duration_ms: started.elapsed().as_millis() as u64,
+        steps,
+    })
+}
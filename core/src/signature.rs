@@ -0,0 +1,326 @@
+//! Secure Boot v2 signature blocks. `espsecure.py sign_data` pads an app or
+//! bootloader image to a 4 KB boundary and appends one sector holding up
+//! to three signature blocks; a chip with Secure Boot enabled refuses to
+//! boot an image without one, so flashing an unsigned build only bricks
+//! the slot until it is rewritten.
+
+use crate::checksum::crc32_le;
+use crate::models::{AppError, SignedImage};
+use rand::rngs::OsRng;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Pss, RsaPrivateKey};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const SECTOR: usize = 4096;
+const BLOCK_LEN: usize = 1216;
+const MAX_BLOCKS: usize = 3;
+const BLOCK_MAGIC: u8 = 0xe7;
+const IMAGE_MAGIC: u8 = 0xe9;
+const RSA_VERSION: u8 = 0x02;
+const ECDSA_VERSION: u8 = 0x03;
+const IMAGE_DIGEST: std::ops::Range<usize> = 4..36;
+const KEY_AT: usize = 36;
+const CRC_COVERS: usize = 1196;
+/// Modulus, exponent and the two Montgomery constants the ROM wants
+const RSA_KEY_LEN: usize = 776;
+const RSA_BYTES: usize = 384;
+const PSS_SALT_LEN: usize = 32;
+
+/// Coordinate length in the block for the ROM's ECDSA curve ids. The
+/// ROM's `ets_ecdsa_pubkey` pads P-192 coordinates to P-256's 32 bytes,
+/// and the key digest covers the padding too.
+fn coordinate_len(curve_id: u8) -> Option<usize> {
+    match curve_id {
+        1 | 2 => Some(32),
+        3 => Some(48),
+        _ => None,
+    }
+}
+
+/// How an image was signed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub scheme: &'static str,
+    /// SHA-256 of each signing public key, the value burned into the
+    /// eFuse key block that has to match
+    pub key_digests: Vec<[u8; 32]>,
+}
+
+impl SignatureInfo {
+    /// Short hex prefixes of the key digests, for logs and messages.
+    pub fn keys(&self) -> String {
+        self.key_digests
+            .iter()
+            .map(short_hex)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn short_hex(digest: &[u8; 32]) -> String {
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Reads the signature sector of `image`. `None` means the image carries
+/// no signature blocks at all; blocks that are present but corrupt, or
+/// signed over different contents, are an error.
+pub fn inspect(image: &[u8]) -> Result<Option<SignatureInfo>, AppError> {
+    let Some(contents) = signed_contents(image) else {
+        return Ok(None);
+    };
+    let sector = &image[contents.len()..];
+    let blocks: Vec<&[u8]> = sector
+        .chunks_exact(BLOCK_LEN)
+        .take(MAX_BLOCKS)
+        .take_while(|block| block[0] == BLOCK_MAGIC)
+        .collect();
+
+    let scheme = match blocks[0][1] {
+        RSA_VERSION => "RSA-PSS",
+        ECDSA_VERSION => "ECDSA",
+        v => {
+            return Err(AppError::SecureBoot(format!(
+                "Unknown signature block version {}",
+                v
+            )))
+        }
+    };
+    let digest: [u8; 32] = Sha256::digest(contents).into();
+    let mut key_digests = Vec::with_capacity(blocks.len());
+    for (i, block) in blocks.iter().enumerate() {
+        let stored = u32::from_le_bytes(block[CRC_COVERS..CRC_COVERS + 4].try_into().unwrap());
+        if crc32_le(0, &block[..CRC_COVERS]) != stored {
+            return Err(AppError::SecureBoot(format!(
+                "Signature block {} is corrupt (CRC mismatch)",
+                i
+            )));
+        }
+        if block[1] != blocks[0][1] {
+            return Err(AppError::SecureBoot(
+                "Signature blocks mix RSA and ECDSA".to_string(),
+            ));
+        }
+        if block[IMAGE_DIGEST] != digest {
+            return Err(AppError::SecureBoot(format!(
+                "Signature block {} was made for a different image",
+                i
+            )));
+        }
+        let key_len = match block[1] {
+            RSA_VERSION => RSA_KEY_LEN,
+            _ => {
+                let len = coordinate_len(block[KEY_AT]).ok_or_else(|| {
+                    AppError::SecureBoot(format!("Signature block {} has an unknown curve", i))
+                })?;
+                1 + 2 * len
+            }
+        };
+        key_digests.push(Sha256::digest(&block[KEY_AT..KEY_AT + key_len]).into());
+    }
+    Ok(Some(SignatureInfo {
+        scheme,
+        key_digests,
+    }))
+}
+
+/// The image without its signature sector, if it has one.
+fn signed_contents(image: &[u8]) -> Option<&[u8]> {
+    if image.len() < 2 * SECTOR || !image.len().is_multiple_of(SECTOR) {
+        return None;
+    }
+    let (contents, sector) = image.split_at(image.len() - SECTOR);
+    (sector[0] == BLOCK_MAGIC).then_some(contents)
+}
+
+/// A Secure Boot v2 private key: RSA-3072, or ECDSA on P-256 or P-384 for
+/// the chips that accept it.
+pub enum SigningKey {
+    Rsa(Box<RsaPrivateKey>),
+    P256(p256::ecdsa::SigningKey),
+    P384(p384::ecdsa::SigningKey),
+}
+
+impl SigningKey {
+    /// Parses a PEM key as `espsecure.py generate_signing_key` writes it
+    /// (PKCS#1 / SEC1), or as PKCS#8.
+    pub fn from_pem(pem: &str) -> Result<Self, AppError> {
+        let pem = pem.trim();
+        if let Ok(key) =
+            RsaPrivateKey::from_pkcs1_pem(pem).or_else(|_| RsaPrivateKey::from_pkcs8_pem(pem))
+        {
+            if key.size() != RSA_BYTES {
+                return Err(AppError::InvalidInput(format!(
+                    "Secure Boot needs a 3072-bit RSA key, this one has {} bits",
+                    key.size() * 8
+                )));
+            }
+            return Ok(SigningKey::Rsa(Box::new(key)));
+        }
+        if let Ok(key) =
+            p256::SecretKey::from_sec1_pem(pem).or_else(|_| p256::SecretKey::from_pkcs8_pem(pem))
+        {
+            return Ok(SigningKey::P256(key.into()));
+        }
+        if let Ok(key) =
+            p384::SecretKey::from_sec1_pem(pem).or_else(|_| p384::SecretKey::from_pkcs8_pem(pem))
+        {
+            return Ok(SigningKey::P384(key.into()));
+        }
+        Err(AppError::InvalidInput(
+            "Not an RSA-3072 or ECDSA P-256/P-384 private key in PEM format".to_string(),
+        ))
+    }
+
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            SigningKey::Rsa(_) => "RSA-PSS",
+            SigningKey::P256(_) | SigningKey::P384(_) => "ECDSA",
+        }
+    }
+
+    /// SHA-256 of the public key, what goes into the eFuse key block.
+    pub fn key_digest(&self) -> [u8; 32] {
+        Sha256::digest(self.public_key()).into()
+    }
+
+    /// Short hex prefix of the key digest, as [`SignatureInfo::keys`]
+    /// shows it.
+    pub fn key_id(&self) -> String {
+        short_hex(&self.key_digest())
+    }
+
+    fn version(&self) -> u8 {
+        match self {
+            SigningKey::Rsa(_) => RSA_VERSION,
+            SigningKey::P256(_) | SigningKey::P384(_) => ECDSA_VERSION,
+        }
+    }
+
+    /// The public key as laid out in the block, all numbers little-endian.
+    fn public_key(&self) -> Vec<u8> {
+        match self {
+            SigningKey::Rsa(key) => {
+                let n = key.n();
+                // R^2 mod n and -n^-1 mod 2^32 save the ROM computing them
+                let rinv = (BigUint::from(1u32) << (2 * RSA_BYTES * 8)) % n;
+                let n_le = n.to_bytes_le();
+                let n0 = u32::from_le_bytes(n_le[..4].try_into().unwrap());
+                let mut inv = n0;
+                for _ in 0..5 {
+                    inv = inv.wrapping_mul(2u32.wrapping_sub(n0.wrapping_mul(inv)));
+                }
+                let mut out = le_padded(&n_le, RSA_BYTES);
+                out.extend(le_padded(&key.e().to_bytes_le(), 4));
+                out.extend(le_padded(&rinv.to_bytes_le(), RSA_BYTES));
+                out.extend(inv.wrapping_neg().to_le_bytes());
+                out
+            }
+            SigningKey::P256(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                ecdsa_key(2, point.x().unwrap(), point.y().unwrap())
+            }
+            SigningKey::P384(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                ecdsa_key(3, point.x().unwrap(), point.y().unwrap())
+            }
+        }
+    }
+
+    /// Signs the image digest, returning the signature in block byte order.
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<Vec<u8>, AppError> {
+        use p256::ecdsa::signature::hazmat::PrehashSigner;
+
+        let failed =
+            |e: &dyn std::fmt::Display| AppError::Internal(format!("Signing failed: {}", e));
+        match self {
+            SigningKey::Rsa(key) => {
+                let padding = Pss::new_with_salt::<Sha256>(PSS_SALT_LEN);
+                let mut signature = key
+                    .sign_with_rng(&mut OsRng, padding, digest)
+                    .map_err(|e| failed(&e))?;
+                signature.reverse();
+                Ok(signature)
+            }
+            SigningKey::P256(key) => {
+                let signature: p256::ecdsa::Signature =
+                    key.sign_prehash(digest).map_err(|e| failed(&e))?;
+                let (r, s) = signature.split_bytes();
+                Ok([reversed(&r), reversed(&s)].concat())
+            }
+            SigningKey::P384(key) => {
+                let signature: p384::ecdsa::Signature =
+                    key.sign_prehash(digest).map_err(|e| failed(&e))?;
+                let (r, s) = signature.split_bytes();
+                Ok([reversed(&r), reversed(&s)].concat())
+            }
+        }
+    }
+}
+
+fn le_padded(bytes: &[u8], len: usize) -> Vec<u8> {
+    let mut out = bytes.to_vec();
+    out.resize(len, 0);
+    out
+}
+
+fn reversed(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().rev().copied().collect()
+}
+
+fn ecdsa_key(curve_id: u8, x: &[u8], y: &[u8]) -> Vec<u8> {
+    [vec![curve_id], reversed(x), reversed(y)].concat()
+}
+
+/// Signs an app or bootloader image the way `espsecure.py sign_data
+/// --version 2` does. A signature sector already on the image is
+/// replaced, so re-signing with another key doesn't stack sectors.
+pub fn sign(image: &[u8], key: &SigningKey) -> Result<Vec<u8>, AppError> {
+    if image.first() != Some(&IMAGE_MAGIC) {
+        return Err(AppError::InvalidInput(
+            "Not an ESP app or bootloader image".to_string(),
+        ));
+    }
+    let mut signed = signed_contents(image).unwrap_or(image).to_vec();
+    let padded = signed.len().div_ceil(SECTOR) * SECTOR;
+    signed.resize(padded, 0xff);
+    let digest: [u8; 32] = Sha256::digest(&signed).into();
+
+    let public_key = key.public_key();
+    let signature = key.sign_digest(&digest)?;
+    let mut block = vec![0u8; BLOCK_LEN];
+    block[0] = BLOCK_MAGIC;
+    block[1] = key.version();
+    block[IMAGE_DIGEST].copy_from_slice(&digest);
+    block[KEY_AT..KEY_AT + public_key.len()].copy_from_slice(&public_key);
+    let signature_at = KEY_AT + public_key.len();
+    block[signature_at..signature_at + signature.len()].copy_from_slice(&signature);
+    let crc = crc32_le(0, &block[..CRC_COVERS]);
+    block[CRC_COVERS..CRC_COVERS + 4].copy_from_slice(&crc.to_le_bytes());
+
+    signed.extend(block);
+    signed.resize(padded + SECTOR, 0xff);
+    Ok(signed)
+}
+
+/// Signs the image at `path` into `output`, by default a `-signed.bin`
+/// next to it.
+pub fn sign_file(
+    path: &Path,
+    key: &SigningKey,
+    output: Option<&Path>,
+) -> Result<SignedImage, AppError> {
+    let signed = sign(&std::fs::read(path)?, key)?;
+    let output = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{}-signed.bin", stem))
+    });
+    std::fs::write(&output, signed)?;
+    Ok(SignedImage {
+        path: output.display().to_string(),
+        scheme: key.scheme().to_string(),
+        key_id: key.key_id(),
+    })
+}
@@ -0,0 +1,366 @@
+//! Fake ESP32-S3 for working on the app without hardware, enabled with the
+//! `simulator` feature. It shows up in device scans as [`PORT_NAME`], answers
+//! chip queries with fixed details and its serial port streams a boot log
+//! followed by a periodic heartbeat. Lines sent to it are echoed back.
+
+use crate::chips;
+use crate::models::{AppError, ChipDetails, DeviceStatus, MemoryInfo};
+use crate::tasks::TaskHandle;
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+pub const PORT_NAME: &str = "SIMULATOR";
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+
+const BOOT_LOG: [&str; 12] = [
+    "ESP-ROM:esp32s3-20210327",
+    "Build:Mar 27 2021",
+    "SPIWP:0xee",
+    "mode:DIO, clock div:1",
+    "entry 0x403c98d0",
+    "I (25) boot: ESP-IDF v5.1.2 2nd stage bootloader",
+    "I (31) boot: chip revision: v0.2",
+    "I (35) boot.esp32s3: SPI Flash Size : 8MB",
+    "I (188) cpu_start: Pro cpu start user code",
+    "I (215) cpu_start: Starting scheduler on PRO CPU.",
+    "I (230) main_task: Calling app_main()",
+    "I (240) app: Simulated board ready",
+];
+
+struct Board {
+    output: VecDeque<u8>,
+    input: Vec<u8>,
+    booted_at: Instant,
+    heartbeats: u64,
+    rts: bool,
+}
+
+impl Board {
+    fn reset(&mut self, reason: &str) {
+        self.output.clear();
+        self.input.clear();
+        self.line(&format!("rst:{},boot:0x8 (SPI_FAST_FLASH_BOOT)", reason));
+        for line in BOOT_LOG {
+            self.line(line);
+        }
+        self.booted_at = Instant::now();
+        self.heartbeats = 0;
+    }
+
+    fn line(&mut self, text: &str) {
+        self.output.extend(text.as_bytes());
+        self.output.extend(b"\r\n");
+    }
+
+    fn uptime_ms(&self) -> u128 {
+        self.booted_at.elapsed().as_millis()
+    }
+
+    fn pump(&mut self) {
+        while self.booted_at.elapsed() >= HEARTBEAT_INTERVAL * (self.heartbeats as u32 + 1) {
+            self.heartbeats += 1;
+            let text = format!(
+                "I ({}) app: heartbeat {}",
+                self.uptime_ms(),
+                self.heartbeats
+            );
+            self.line(&text);
+        }
+    }
+}
+
+// Set while a flasher session holds the port, as a real one would
+static FLASHER_OPEN: AtomicBool = AtomicBool::new(false);
+
+static BOARD: LazyLock<Mutex<Board>> = LazyLock::new(|| {
+    let mut board = Board {
+        output: VecDeque::new(),
+        input: Vec::new(),
+        booted_at: Instant::now(),
+        heartbeats: 0,
+        rts: false,
+    };
+    board.reset("0x1 (POWERON)");
+    Mutex::new(board)
+});
+
+pub fn is_simulated(port_name: &str) -> bool {
+    port_name == PORT_NAME
+}
+
+pub fn device_status() -> DeviceStatus {
+    DeviceStatus {
+        code: "ok".to_string(),
+        message: format!("Connected ({})", PORT_NAME),
+        port_name: Some(PORT_NAME.to_string()),
+        product_name: Some("ESP32-S3 Simulator".to_string()),
+        serial_number: Some("SIM-0001".to_string()),
+        vid_pid: Some("303A:1001".to_string()),
+        connection_type: Some("native_usb".to_string()),
+    }
+}
+
+pub fn chip_details() -> ChipDetails {
+    ChipDetails {
+        chip_model: Some("ESP32-S3".to_string()),
+        mac_address: Some("7c:df:a1:00:00:01".to_string()),
+        flash_size: Some("8 MB".to_string()),
+        flash_size_bytes: Some(8 * 1024 * 1024),
+        chip_revision: Some("v0.2".to_string()),
+        crystal_frequency: Some("40 MHz".to_string()),
+        features: Some("WiFi, BLE".to_string()),
+        wafer_major: Some(0),
+        wafer_minor: Some(2),
+        package: Some("ESP32-S3 (QFN56)".to_string()),
+        rom_version: Some("ECO0".to_string()),
+        sdk_version: Some("v5.1.2".to_string()),
+        crystal_warning: None,
+        flash: Some(MemoryInfo {
+            embedded: false,
+            size: None,
+            vendor: None,
+            mode: Some("quad".to_string()),
+        }),
+        psram: Some(MemoryInfo {
+            embedded: true,
+            size: Some("8 MB".to_string()),
+            vendor: Some("AP_1v8".to_string()),
+            mode: Some("octal".to_string()),
+        }),
+        capabilities: chips::capabilities("ESP32-S3"),
+        secure_download_mode: false,
+    }
+}
+
+/// Marks the port as held by a cached flasher session.
+pub fn hold_flasher() {
+    FLASHER_OPEN.store(true, Ordering::SeqCst);
+}
+
+pub fn release_flasher() {
+    FLASHER_OPEN.store(false, Ordering::SeqCst);
+}
+
+/// Fails like opening a real port a flasher session still holds.
+pub fn open_exclusive() -> Result<(), AppError> {
+    if FLASHER_OPEN.load(Ordering::SeqCst) {
+        return Err(AppError::PortBusy(format!(
+            "{} is held by a flasher session",
+            PORT_NAME
+        )));
+    }
+    Ok(())
+}
+
+/// Reboots the board, as the auto-reset circuit would.
+pub fn reset() {
+    BOARD.lock().unwrap().reset("0x15 (USB_UART_CHIP_RESET)");
+    info!("Simulator reset");
+}
+
+/// Pretends to write `len` bytes, reporting progress on `task` at roughly
+/// the speed of a 460800 baud stub.
+pub fn flash(len: u64, task: &TaskHandle) -> Result<(), AppError> {
+    const CHUNK: u64 = 16 * 1024;
+    task.phase("connecting");
+    std::thread::sleep(Duration::from_millis(300));
+    task.phase("writing");
+    let mut done = 0;
+    while done < len {
+        task.checkpoint()?;
+        std::thread::sleep(Duration::from_millis(40));
+        done = (done + CHUNK).min(len);
+        task.progress(done, len);
+    }
+    task.phase("resetting");
+    reset();
+    Ok(())
+}
+
+pub fn erase(task: &TaskHandle) -> Result<(), AppError> {
+    task.phase("connecting");
+    std::thread::sleep(Duration::from_millis(300));
+    task.checkpoint()?;
+    task.phase("erasing");
+    std::thread::sleep(Duration::from_secs(2));
+    Ok(())
+}
+
+/// Opens a handle on the simulated board. Every handle shares the same
+/// output, like reopening a real port.
+pub fn open(baud_rate: u32) -> Box<dyn SerialPort> {
+    Box::new(SimPort {
+        baud_rate,
+        timeout: Duration::from_millis(10),
+    })
+}
+
+struct SimPort {
+    baud_rate: u32,
+    timeout: Duration,
+}
+
+impl Read for SimPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            {
+                let mut board = BOARD.lock().unwrap();
+                board.pump();
+                if !board.output.is_empty() {
+                    let len = buf.len().min(board.output.len());
+                    for (dst, src) in buf.iter_mut().zip(board.output.drain(..len)) {
+                        *dst = src;
+                    }
+                    return Ok(len);
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+impl Write for SimPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut board = BOARD.lock().unwrap();
+        for &byte in buf {
+            match byte {
+                b'\r' => {}
+                b'\n' => {
+                    let line = String::from_utf8_lossy(&board.input).to_string();
+                    board.input.clear();
+                    let text = format!("I ({}) console: > {}", board.uptime_ms(), line);
+                    board.line(&text);
+                }
+                _ => board.input.push(byte),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SerialPort for SimPort {
+    fn name(&self) -> Option<String> {
+        Some(PORT_NAME.to_string())
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.baud_rate)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(DataBits::Eight)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(Parity::None)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(StopBits::One)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, _: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, _: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_parity(&mut self, _: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, _: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        // RTS drives EN: releasing it boots the chip again
+        let mut board = BOARD.lock().unwrap();
+        if board.rts && !level {
+            board.reset("0x1 (POWERON)");
+        }
+        board.rts = level;
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(BOARD.lock().unwrap().output.len() as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if matches!(buffer_to_clear, ClearBuffer::Input | ClearBuffer::All) {
+            BOARD.lock().unwrap().output.clear();
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(open(self.baud_rate))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
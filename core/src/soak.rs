@@ -0,0 +1,184 @@
+//! Soak testing: reset a board over and over, keep each boot log and flag
+//! boots that miss an expected marker or crash, to catch intermittent boot
+//! problems a single run won't show.
+
+use crate::console::Console;
+use crate::models::{AppError, BootFault, BootRecord, SoakReport};
+use crate::tasks::TaskHandle;
+use regex::Regex;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+// Boot output kept per cycle; the start of a boot log is what matters
+const BOOT_LOG_LIMIT: usize = 16 * 1024;
+// How often a waiting cycle looks for the marker
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub(crate) static PANIC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Guru Meditation Error|panic'ed|abort\(\) was called|Backtrace: ?0x").unwrap()
+});
+pub(crate) static BROWNOUT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)brownout detector was triggered|BROWNOUT_RST").unwrap());
+
+pub struct SoakOptions {
+    pub port_name: String,
+    pub baud_rate: u32,
+    pub cycles: u32,
+    /// Regex every good boot prints, e.g. `READY`
+    pub marker: Option<String>,
+    /// How long to wait for the marker, or to watch a boot without one
+    pub boot_timeout: Duration,
+    /// Time from one reset to the next; at least the time to the marker
+    pub interval: Option<Duration>,
+}
+
+/// Faults shown by the output of one boot. `marker` is `None` when no
+/// marker is expected.
+pub fn classify(log: &str, marker: Option<&Regex>) -> Vec<BootFault> {
+    let mut faults = Vec::new();
+    if marker.is_some_and(|marker| !marker.is_match(log)) {
+        faults.push(BootFault::MissingMarker);
+    }
+    if PANIC.is_match(log) {
+        faults.push(BootFault::Panic);
+    }
+    if BROWNOUT.is_match(log) {
+        faults.push(BootFault::Brownout);
+    }
+    faults
+}
+
+/// One line per run, e.g. `50 boots, 3 failed (6.0%): 2 missing marker,
+/// 1 panic, 0 brownout`.
+pub fn summary(report: &SoakReport) -> String {
+    format!(
+        "{} boots, {} failed ({:.1}%): {} missing marker, {} panic, {} brownout",
+        report.boots.len(),
+        report.failed(),
+        report.failure_rate() * 100.0,
+        report.count(BootFault::MissingMarker),
+        report.count(BootFault::Panic),
+        report.count(BootFault::Brownout),
+    )
+}
+
+/// Resets the board `options.cycles` times and watches each boot. Device
+/// output and a line per boot go to `output`.
+pub fn run(
+    options: &SoakOptions,
+    task: &TaskHandle,
+    output: impl Fn(&str) + Send + Sync + 'static,
+) -> Result<SoakReport, AppError> {
+    if options.cycles == 0 {
+        return Err(AppError::InvalidInput(
+            "Soak test needs at least one cycle".into(),
+        ));
+    }
+    let marker = options
+        .marker
+        .as_deref()
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| {
+                AppError::InvalidInput(format!("Invalid pattern {:?}: {}", pattern, e))
+            })
+        })
+        .transpose()?;
+
+    let output = Arc::new(output);
+    let boot_log = Arc::new(Mutex::new(String::new()));
+
+    task.phase("connecting");
+    let (echo, sink) = (output.clone(), boot_log.clone());
+    let console = Console::open(
+        &options.port_name,
+        options.baud_rate,
+        task.clone(),
+        move |bytes| {
+            let text = String::from_utf8_lossy(bytes);
+            echo(&text);
+            let mut log = sink.lock().unwrap();
+            if log.len() < BOOT_LOG_LIMIT {
+                log.push_str(&text);
+            }
+        },
+    )?;
+
+    task.phase("running");
+    info!(
+        "Soak test: {} resets of {}",
+        options.cycles, options.port_name
+    );
+    let mut boots = Vec::new();
+    for cycle in 1..=options.cycles {
+        task.progress(u64::from(cycle - 1), u64::from(options.cycles));
+        let duration = watch_boot(&console, &boot_log, marker.as_ref(), options)?;
+        let mut log = std::mem::take(&mut *boot_log.lock().unwrap());
+        let faults = classify(&log, marker.as_ref());
+        if log.len() > BOOT_LOG_LIMIT {
+            let mut cut = BOOT_LOG_LIMIT;
+            while !log.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            log.truncate(cut);
+        }
+        let verdict = if faults.is_empty() {
+            "ok".to_string()
+        } else {
+            let names: Vec<&str> = faults.iter().map(|f| f.as_str()).collect();
+            names.join(", ")
+        };
+        output(&format!(
+            "\n== Boot {}/{}: {} ({} ms) ==\n",
+            cycle,
+            options.cycles,
+            verdict,
+            duration.as_millis()
+        ));
+        boots.push(BootRecord {
+            cycle,
+            faults,
+            duration_ms: duration.as_millis() as u64,
+            log,
+        });
+    }
+    task.progress(u64::from(options.cycles), u64::from(options.cycles));
+
+    Ok(SoakReport {
+        port_name: options.port_name.clone(),
+        boots,
+    })
+}
+
+/// Resets the board and waits for the marker, then out the rest of the
+/// interval. Returns how long the boot was watched before the verdict.
+fn watch_boot(
+    console: &Console,
+    boot_log: &Mutex<String>,
+    marker: Option<&Regex>,
+    options: &SoakOptions,
+) -> Result<Duration, AppError> {
+    boot_log.lock().unwrap().clear();
+    console.reset()?;
+    let started = Instant::now();
+
+    let duration = loop {
+        let elapsed = started.elapsed();
+        let seen = marker.is_some_and(|marker| marker.is_match(&boot_log.lock().unwrap()));
+        if seen || elapsed >= options.boot_timeout {
+            break elapsed;
+        }
+        console.wait(POLL_INTERVAL.min(options.boot_timeout - elapsed))?;
+    };
+
+    // Crashes shortly after boot count too, so keep listening until the
+    // next reset is due
+    if let Some(rest) = options
+        .interval
+        .and_then(|i| i.checked_sub(started.elapsed()))
+    {
+        console.wait(rest)?;
+    }
+    Ok(duration)
+}
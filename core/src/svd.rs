@@ -0,0 +1,371 @@
+//! CMSIS-SVD register descriptions, which Espressif publishes for each
+//! chip: the peripherals, their registers and bitfields, for reading
+//! registers off a chip halted in [`crate::gdb`] and naming what is set.
+
+use crate::chips;
+use crate::gdb::Gdb;
+use crate::models::{AppError, FieldValue, RegisterValue, SvdField, SvdPeripheral, SvdRegister};
+use roxmltree::{Document, Node};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::info;
+
+const SOURCE: &str = "https://raw.githubusercontent.com/espressif/svd/main/svd";
+const TIMEOUT: Duration = Duration::from_secs(120);
+// The largest, the ESP32-P4's, is some 20 MB
+const MAX_SVD: u64 = 64 * 1024 * 1024;
+// Bytes read from the chip in one go
+const MAX_SPAN: u32 = 256;
+
+/// Chips Espressif publishes an SVD file for.
+const CHIPS: [&str; 10] = [
+    "esp32", "esp32s2", "esp32s3", "esp32c2", "esp32c3", "esp32c5", "esp32c6", "esp32c61",
+    "esp32h2", "esp32p4",
+];
+
+/// Register properties a device or peripheral sets for what it contains.
+#[derive(Clone)]
+struct Defaults {
+    size: u32,
+    access: Option<String>,
+    reset_value: Option<u32>,
+}
+
+impl Defaults {
+    fn apply(&self, node: Node) -> Defaults {
+        Defaults {
+            size: number_of(node, "size").map_or(self.size, |size| size as u32),
+            access: text(node, "access").or_else(|| self.access.clone()),
+            reset_value: number_of(node, "resetValue")
+                .map(|value| value as u32)
+                .or(self.reset_value),
+        }
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|c| c.has_tag_name(tag))
+}
+
+/// Text of the child `tag`, its whitespace collapsed.
+fn text(node: Node, tag: &str) -> Option<String> {
+    let text = child(node, tag)?.text()?;
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// A number as SVD writes them: `0x` hex, `#` binary or decimal.
+fn number(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = text.strip_prefix('#') {
+        u64::from_str_radix(binary, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn number_of(node: Node, tag: &str) -> Option<u64> {
+    number(&text(node, tag)?)
+}
+
+/// Name and address step of each instance of an element, several when it
+/// is an array (`dim`) with `%s` in its name.
+fn instances(node: Node, name: &str) -> Vec<(String, u64)> {
+    let Some(dim) = number_of(node, "dim") else {
+        return vec![(name.to_string(), 0)];
+    };
+    let increment = number_of(node, "dimIncrement").unwrap_or(0);
+    let range = text(node, "dimIndex").and_then(|list| {
+        let (first, last) = list.split_once('-')?;
+        Some(number(first)?..=number(last)?)
+    });
+    let indices: Vec<String> = match (range, text(node, "dimIndex")) {
+        (Some(range), _) => range.map(|i| i.to_string()).collect(),
+        (None, Some(list)) => list.split(',').map(|i| i.trim().to_string()).collect(),
+        (None, None) => (0..dim).map(|i| i.to_string()).collect(),
+    };
+    indices
+        .into_iter()
+        .take(dim as usize)
+        .enumerate()
+        .map(|(i, index)| (name.replace("%s", &index), i as u64 * increment))
+        .collect()
+}
+
+/// Bit position and width of a field, whichever way it is given.
+fn bits(node: Node) -> Option<(u32, u32)> {
+    if let (Some(lsb), Some(width)) = (number_of(node, "bitOffset"), number_of(node, "bitWidth")) {
+        return Some((lsb as u32, width as u32));
+    }
+    let (lsb, msb) = match (number_of(node, "lsb"), number_of(node, "msb")) {
+        (Some(lsb), Some(msb)) => (lsb, msb),
+        _ => {
+            // `[msb:lsb]`
+            let range = text(node, "bitRange")?;
+            let (msb, lsb) = range.trim_matches(['[', ']']).split_once(':')?;
+            (number(lsb)?, number(msb)?)
+        }
+    };
+    Some((lsb as u32, (msb.checked_sub(lsb)? + 1) as u32))
+}
+
+fn fields(register: Node) -> Vec<SvdField> {
+    let Some(list) = child(register, "fields") else {
+        return Vec::new();
+    };
+    let mut fields = Vec::new();
+    for node in list.children().filter(|n| n.has_tag_name("field")) {
+        let (Some(name), Some((lsb, width))) = (text(node, "name"), bits(node)) else {
+            continue;
+        };
+        for (name, step) in instances(node, &name) {
+            fields.push(SvdField {
+                name,
+                description: text(node, "description"),
+                lsb: lsb + step as u32,
+                width,
+                access: text(node, "access"),
+            });
+        }
+    }
+    fields.sort_by_key(|f| f.lsb);
+    fields
+}
+
+/// The registers in `parent` (a `registers` list or a `cluster`), placed
+/// from `base`. Registers in clusters are named `CLUSTER.REGISTER`.
+fn registers(
+    parent: Node,
+    base: u64,
+    prefix: &str,
+    defaults: &Defaults,
+    found: &mut Vec<SvdRegister>,
+) {
+    for node in parent.children().filter(|n| n.is_element()) {
+        let cluster = node.has_tag_name("cluster");
+        if !cluster && !node.has_tag_name("register") {
+            continue;
+        }
+        let Some(name) = text(node, "name") else {
+            continue;
+        };
+        let offset = number_of(node, "addressOffset").unwrap_or(0);
+        let defaults = defaults.apply(node);
+        for (name, step) in instances(node, &name) {
+            let address = base + offset + step;
+            let name = format!("{}{}", prefix, name);
+            if cluster {
+                registers(node, address, &format!("{}.", name), &defaults, found);
+                continue;
+            }
+            found.push(SvdRegister {
+                name,
+                description: text(node, "description"),
+                address: address as u32,
+                size: defaults.size,
+                access: defaults.access.clone(),
+                reset_value: defaults.reset_value,
+                read_action: text(node, "readAction"),
+                fields: fields(node),
+            });
+        }
+    }
+}
+
+/// The peripherals an SVD file describes, in its order. A peripheral
+/// `derivedFrom` another gets a copy of its registers at its own base.
+pub fn parse(xml: &str) -> Result<Vec<SvdPeripheral>, AppError> {
+    let doc = Document::parse(xml)
+        .map_err(|e| AppError::InvalidInput(format!("Unreadable SVD file: {}", e)))?;
+    let device = doc.root_element();
+    let defaults = Defaults {
+        size: 32,
+        access: None,
+        reset_value: None,
+    }
+    .apply(device);
+    let list = child(device, "peripherals")
+        .ok_or_else(|| AppError::InvalidInput("The SVD file lists no peripherals".to_string()))?;
+
+    let mut peripherals = Vec::new();
+    let mut derived = Vec::new();
+    for node in list.children().filter(|n| n.has_tag_name("peripheral")) {
+        let Some(name) = text(node, "name") else {
+            continue;
+        };
+        let base = number_of(node, "baseAddress").unwrap_or(0);
+        let mut found = Vec::new();
+        if let Some(list) = child(node, "registers") {
+            registers(list, base, "", &defaults.apply(node), &mut found);
+        }
+        if let Some(from) = node.attribute("derivedFrom") {
+            derived.push((peripherals.len(), from.to_string()));
+        }
+        peripherals.push(SvdPeripheral {
+            name,
+            description: text(node, "description"),
+            base_address: base as u32,
+            registers: found,
+        });
+    }
+    for (index, from) in derived {
+        let Some(source) = peripherals.iter().find(|p| p.name == from).cloned() else {
+            continue;
+        };
+        let peripheral = &mut peripherals[index];
+        if peripheral.registers.is_empty() {
+            peripheral.registers = source
+                .registers
+                .into_iter()
+                .map(|register| SvdRegister {
+                    address: register
+                        .address
+                        .wrapping_sub(source.base_address)
+                        .wrapping_add(peripheral.base_address),
+                    ..register
+                })
+                .collect();
+        }
+        if peripheral.description.is_none() {
+            peripheral.description = source.description;
+        }
+    }
+    Ok(peripherals)
+}
+
+/// Whether reading `register` leaves the chip as it was. Espressif's files
+/// don't mark FIFO data registers, where a read pops a byte, so those are
+/// told by name.
+pub fn readable(register: &SvdRegister) -> bool {
+    let name = register.name.to_ascii_uppercase();
+    register.access.as_deref() != Some("write-only")
+        && register.read_action.is_none()
+        && !name.ends_with("FIFO")
+        && name != "DATA"
+}
+
+/// Blocks of consecutive `registers` to read, as address and length.
+pub fn spans(registers: &[&SvdRegister]) -> Vec<(u32, u32)> {
+    let mut sorted: Vec<(u32, u32)> = registers
+        .iter()
+        .map(|r| (r.address, (r.size / 8).max(1)))
+        .collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut spans: Vec<(u32, u32)> = Vec::new();
+    for (address, len) in sorted {
+        match spans.last_mut() {
+            Some((start, span)) if *start + *span == address && *span + len <= MAX_SPAN => {
+                *span += len;
+            }
+            _ => spans.push((address, len)),
+        }
+    }
+    spans
+}
+
+/// `register` holding `value`, each bitfield taken out.
+pub fn decode(register: &SvdRegister, value: u32) -> RegisterValue {
+    let fields = register
+        .fields
+        .iter()
+        .map(|field| {
+            let mask = if field.width >= 32 {
+                u32::MAX
+            } else {
+                (1 << field.width) - 1
+            };
+            FieldValue {
+                name: field.name.clone(),
+                value: value.checked_shr(field.lsb).unwrap_or(0) & mask,
+            }
+        })
+        .collect();
+    RegisterValue {
+        name: register.name.clone(),
+        address: register.address,
+        value,
+        fields,
+    }
+}
+
+/// Reads every register of `peripheral` that is safe to read off the chip
+/// `gdb` has halted.
+pub fn read(gdb: &mut Gdb, peripheral: &SvdPeripheral) -> Result<Vec<RegisterValue>, AppError> {
+    let registers: Vec<&SvdRegister> = peripheral
+        .registers
+        .iter()
+        .filter(|r| readable(r))
+        .collect();
+    let mut values = Vec::new();
+    for (start, len) in spans(&registers) {
+        let bytes = gdb.read_memory(start, len)?;
+        for register in registers
+            .iter()
+            .filter(|r| (start..start + len).contains(&r.address))
+        {
+            let at = (register.address - start) as usize;
+            let mut word = [0; 4];
+            let size = ((register.size / 8) as usize).clamp(1, 4);
+            let Some(raw) = bytes.get(at..at + size) else {
+                continue;
+            };
+            word[..size].copy_from_slice(raw);
+            values.push(decode(register, u32::from_le_bytes(word)));
+        }
+    }
+    Ok(values)
+}
+
+/// Where the SVD file for `chip` is bundled with the app.
+pub fn resource(chip: &str) -> String {
+    format!("firmware/svd/{}.svd", chips::model_key(chip))
+}
+
+/// Espressif's SVD file for `chip`, downloaded into `cache` unless it is
+/// there already.
+pub fn fetch(cache: &Path, chip: &str) -> Result<PathBuf, AppError> {
+    let key = chips::model_key(chip);
+    if !CHIPS.contains(&key.as_str()) {
+        return Err(AppError::InvalidInput(format!("No SVD file for {}", chip)));
+    }
+    let path = cache.join(format!("{}.svd", key));
+    if path.is_file() {
+        return Ok(path);
+    }
+    let url = format!("{}/{}.svd", SOURCE, key);
+    info!("Downloading {}", url);
+    let mut data = Vec::new();
+    ureq::get(&url)
+        .timeout(TIMEOUT)
+        .call()
+        .map_err(|e| AppError::Connection(format!("{}: {}", url, e)))?
+        .into_reader()
+        .take(MAX_SVD)
+        .read_to_end(&mut data)
+        .map_err(|e| AppError::Connection(e.to_string()))?;
+    // A partial download must not pass for the file later
+    let partial = path.with_extension("svd.part");
+    std::fs::create_dir_all(cache)
+        .and_then(|_| std::fs::write(&partial, &data))
+        .and_then(|_| std::fs::rename(&partial, &path))
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(path)
+}
+
+/// The peripherals of `chip`, from the `bundled` SVD file when the app
+/// ships one, else from Espressif's, downloaded into `cache`.
+pub fn load(
+    bundled: Option<&Path>,
+    cache: &Path,
+    chip: &str,
+) -> Result<Vec<SvdPeripheral>, AppError> {
+    let path = match bundled.filter(|path| path.is_file()) {
+        Some(path) => path.to_path_buf(),
+        None => fetch(cache, chip)?,
+    };
+    let xml = std::fs::read_to_string(&path).map_err(|e| AppError::Io(e.to_string()))?;
+    parse(&xml)
+}
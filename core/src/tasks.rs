@@ -0,0 +1,212 @@
+use crate::models::{AppError, TaskInfo};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Finished tasks kept around so late `list_tasks` callers can see results
+const MAX_FINISHED: usize = 20;
+// Progress updates closer together than this are coalesced into one notification
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+type Notify = Arc<dyn Fn(&TaskInfo) + Send + Sync>;
+
+/// Registry of long-running operations (flash, erase, ...).
+///
+/// Every operation gets an id, a cancel flag and uniform progress
+/// notifications, instead of each command inventing its own status
+/// reporting. The app forwards notifications as `task-progress` events,
+/// the CLI prints them.
+#[derive(Clone)]
+pub struct TaskManager {
+    tasks: Arc<Mutex<HashMap<u64, TaskEntry>>>,
+    next_id: Arc<AtomicU64>,
+    notify: Notify,
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        TaskManager::new(|_| {})
+    }
+}
+
+impl TaskManager {
+    pub fn new(notify: impl Fn(&TaskInfo) + Send + Sync + 'static) -> Self {
+        TaskManager {
+            tasks: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(0)),
+            notify: Arc::new(notify),
+        }
+    }
+
+    pub fn start(&self, kind: &str, port_name: Option<&str>) -> TaskHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let info = TaskInfo {
+            id,
+            kind: kind.to_string(),
+            state: "running".to_string(),
+            phase: "starting".to_string(),
+            current: 0,
+            total: 0,
+            port_name: port_name.map(|p| p.to_string()),
+            message: None,
+            bytes_per_sec: None,
+            eta_secs: None,
+        };
+
+        self.tasks.lock().unwrap().insert(
+            id,
+            TaskEntry {
+                info: info.clone(),
+                cancel: cancel.clone(),
+            },
+        );
+        (self.notify)(&info);
+
+        TaskHandle {
+            id,
+            manager: self.clone(),
+            cancel,
+            last_emit: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let mut list: Vec<TaskInfo> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|t| t.info.clone())
+            .collect();
+        list.sort_by_key(|t| t.id);
+        list
+    }
+
+    pub fn cancel(&self, id: u64) -> Result<(), AppError> {
+        let tasks = self.tasks.lock().unwrap();
+        let entry = tasks.get(&id).ok_or(AppError::TaskNotFound(id))?;
+        entry.cancel.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut TaskInfo)) -> Option<TaskInfo> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let entry = tasks.get_mut(&id)?;
+        f(&mut entry.info);
+        Some(entry.info.clone())
+    }
+
+    fn prune(&self) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let mut finished: Vec<u64> = tasks
+            .values()
+            .filter(|t| t.info.state != "running")
+            .map(|t| t.info.id)
+            .collect();
+        if finished.len() > MAX_FINISHED {
+            finished.sort_unstable();
+            for id in &finished[..finished.len() - MAX_FINISHED] {
+                tasks.remove(id);
+            }
+        }
+    }
+}
+
+/// Worker-side view of a task, handed to the blocking operation. Clones
+/// share the same task, e.g. for callbacks that outlive a borrow.
+#[derive(Clone)]
+pub struct TaskHandle {
+    id: u64,
+    manager: TaskManager,
+    cancel: Arc<AtomicBool>,
+    last_emit: Arc<Mutex<Instant>>,
+}
+
+impl TaskHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Cancellation point: returns `AppError::Cancelled` once cancel was requested.
+    pub fn checkpoint(&self) -> Result<(), AppError> {
+        if self.is_cancelled() {
+            Err(AppError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enters a new phase; always notified.
+    pub fn phase(&self, phase: &str) {
+        if let Some(info) = self
+            .manager
+            .update(self.id, |t| t.phase = phase.to_string())
+        {
+            *self.last_emit.lock().unwrap() = Instant::now();
+            (self.manager.notify)(&info);
+        }
+    }
+
+    /// Reports progress within the current phase; rate limited.
+    pub fn progress(&self, current: u64, total: u64) {
+        let info = self.manager.update(self.id, |t| {
+            t.current = current;
+            t.total = total;
+        });
+
+        let mut last = self.last_emit.lock().unwrap();
+        if last.elapsed() >= EMIT_INTERVAL || current >= total {
+            *last = Instant::now();
+            if let Some(info) = info {
+                (self.manager.notify)(&info);
+            }
+        }
+    }
+
+    /// Records the effective rate of the work `progress` counts; sent with
+    /// the next notification.
+    pub fn throughput(&self, bytes_per_sec: u64) {
+        self.manager
+            .update(self.id, |t| t.bytes_per_sec = Some(bytes_per_sec));
+    }
+
+    /// Records the seconds the task should still take; sent with the next
+    /// notification.
+    pub fn eta(&self, secs: Option<u64>) {
+        self.manager.update(self.id, |t| t.eta_secs = secs);
+    }
+
+    /// The task as last reported, e.g. to read its final rate.
+    pub fn info(&self) -> Option<TaskInfo> {
+        self.manager
+            .tasks
+            .lock()
+            .unwrap()
+            .get(&self.id)
+            .map(|t| t.info.clone())
+    }
+
+    /// Records the outcome and sends the final notification.
+    pub fn finish<T>(self, result: &Result<T, AppError>) {
+        let (state, message) = match result {
+            Ok(_) => ("completed", None),
+            Err(AppError::Cancelled) => ("cancelled", None),
+            Err(e) => ("failed", Some(e.message())),
+        };
+        if let Some(info) = self.manager.update(self.id, |t| {
+            t.state = state.to_string();
+            t.message = message;
+            t.eta_secs = None;
+        }) {
+            (self.manager.notify)(&info);
+        }
+        self.manager.prune();
+    }
+}
@@ -0,0 +1,285 @@
+//! A small VT100 emulator for interactive consoles. esp_console and other
+//! linenoise shells redraw the prompt line with cursor movement and erase
+//! sequences, which an append-only log turns into garbage; this keeps the
+//! screen they think they are drawing on, plus the lines scrolled off it.
+
+use crate::models::{TerminalScreen, TextEncoding};
+use crate::monitor::TextStream;
+use std::collections::VecDeque;
+
+// Lines kept after they scroll off the top of the screen
+const SCROLLBACK_LINES: usize = 1000;
+const TAB_WIDTH: usize = 8;
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Ground,
+    Escape,
+    // Control sequence: `ESC [` parameters, final byte
+    Csi,
+    // Operating system command, e.g. a window title: skipped up to BEL or ST
+    Osc,
+    OscEscape,
+}
+
+pub struct Screen {
+    rows: usize,
+    cols: usize,
+    cells: Vec<Vec<char>>,
+    scrollback: VecDeque<String>,
+    row: usize,
+    col: usize,
+    saved: (usize, usize),
+    // Printed into the last column: the next character wraps first
+    wrap_pending: bool,
+    cursor_visible: bool,
+    state: State,
+    params: String,
+    decoder: TextStream,
+    // Answers to status queries, to be sent back to the device
+    replies: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        Screen {
+            rows,
+            cols,
+            cells: vec![vec![' '; cols]; rows],
+            scrollback: VecDeque::new(),
+            row: 0,
+            col: 0,
+            saved: (0, 0),
+            wrap_pending: false,
+            cursor_visible: true,
+            state: State::Ground,
+            params: String::new(),
+            decoder: TextStream::default(),
+            replies: Vec::new(),
+        }
+    }
+
+    /// Decodes output received from now on as `encoding`.
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.decoder = TextStream::new(encoding);
+    }
+
+    /// Applies output of the device. Returns the bytes the terminal answers
+    /// with, e.g. the cursor position linenoise asks for to learn the width.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<u8> {
+        for c in self.decoder.decode(bytes).chars() {
+            self.input(c);
+        }
+        std::mem::take(&mut self.replies)
+    }
+
+    pub fn snapshot(&self) -> TerminalScreen {
+        let text = |line: &Vec<char>| line.iter().collect::<String>().trim_end().to_string();
+        TerminalScreen {
+            rows: self.rows,
+            cols: self.cols,
+            scrollback: self.scrollback.iter().cloned().collect(),
+            lines: self.cells.iter().map(text).collect(),
+            cursor_row: self.row,
+            cursor_col: self.col,
+            cursor_visible: self.cursor_visible,
+        }
+    }
+
+    fn input(&mut self, c: char) {
+        match self.state {
+            State::Ground => self.ground(c),
+            State::Escape => self.escape(c),
+            State::Csi => match c {
+                '0'..='9' | ';' | '?' | '>' => self.params.push(c),
+                '\u{40}'..='\u{7e}' => {
+                    self.state = State::Ground;
+                    self.csi(c);
+                }
+                // Malformed: drop the sequence
+                _ => self.state = State::Ground,
+            },
+            State::Osc => match c {
+                '\u{7}' => self.state = State::Ground,
+                '\u{1b}' => self.state = State::OscEscape,
+                _ => (),
+            },
+            State::OscEscape => self.state = State::Ground,
+        }
+    }
+
+    fn ground(&mut self, c: char) {
+        match c {
+            '\u{1b}' => self.state = State::Escape,
+            '\r' => self.move_to(self.row, 0),
+            '\n' => {
+                self.wrap_pending = false;
+                self.line_feed();
+            }
+            '\u{8}' => self.move_to(self.row, self.col.saturating_sub(1)),
+            '\t' => {
+                let next = (self.col / TAB_WIDTH + 1) * TAB_WIDTH;
+                self.move_to(self.row, next.min(self.cols - 1));
+            }
+            c if c.is_control() => (),
+            c => {
+                if self.wrap_pending {
+                    self.wrap_pending = false;
+                    self.col = 0;
+                    self.line_feed();
+                }
+                self.cells[self.row][self.col] = c;
+                if self.col + 1 == self.cols {
+                    self.wrap_pending = true;
+                } else {
+                    self.col += 1;
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self, c: char) {
+        self.state = State::Ground;
+        match c {
+            '[' => {
+                self.params.clear();
+                self.state = State::Csi;
+            }
+            ']' => self.state = State::Osc,
+            '7' => self.saved = (self.row, self.col),
+            '8' => self.move_to(self.saved.0, self.saved.1),
+            'D' => self.line_feed(),
+            'M' => self.reverse_line_feed(),
+            'c' => {
+                // A reset clears the screen, not the scrollback or encoding
+                let scrollback = std::mem::take(&mut self.scrollback);
+                let decoder = std::mem::take(&mut self.decoder);
+                *self = Screen {
+                    scrollback,
+                    decoder,
+                    ..Screen::new(self.rows, self.cols)
+                };
+            }
+            _ => (),
+        }
+    }
+
+    fn csi(&mut self, command: char) {
+        let private = self.params.starts_with('?');
+        let params: Vec<usize> = self
+            .params
+            .trim_start_matches(['?', '>'])
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let arg = |i: usize| params.get(i).copied().unwrap_or(0);
+        // Counts and positions treat 0 as 1
+        let count = |i: usize| arg(i).max(1);
+
+        match command {
+            'A' => self.move_to(self.row.saturating_sub(count(0)), self.col),
+            'B' => self.move_to(self.row + count(0), self.col),
+            'C' => self.move_to(self.row, self.col + count(0)),
+            'D' => self.move_to(self.row, self.col.saturating_sub(count(0))),
+            'E' => self.move_to(self.row + count(0), 0),
+            'F' => self.move_to(self.row.saturating_sub(count(0)), 0),
+            'G' => self.move_to(self.row, count(0) - 1),
+            'd' => self.move_to(count(0) - 1, self.col),
+            'H' | 'f' => self.move_to(count(0) - 1, count(1) - 1),
+            'J' => match arg(0) {
+                0 => {
+                    self.clear_line(self.row, self.col, self.cols);
+                    for row in self.row + 1..self.rows {
+                        self.clear_line(row, 0, self.cols);
+                    }
+                }
+                1 => {
+                    for row in 0..self.row {
+                        self.clear_line(row, 0, self.cols);
+                    }
+                    self.clear_line(self.row, 0, self.col + 1);
+                }
+                _ => {
+                    for row in 0..self.rows {
+                        self.clear_line(row, 0, self.cols);
+                    }
+                }
+            },
+            'K' => match arg(0) {
+                0 => self.clear_line(self.row, self.col, self.cols),
+                1 => self.clear_line(self.row, 0, self.col + 1),
+                _ => self.clear_line(self.row, 0, self.cols),
+            },
+            'X' => {
+                let end = (self.col + count(0)).min(self.cols);
+                self.clear_line(self.row, self.col, end);
+            }
+            'P' => {
+                let line = &mut self.cells[self.row];
+                let n = count(0).min(self.cols - self.col);
+                line.drain(self.col..self.col + n);
+                line.resize(self.cols, ' ');
+            }
+            '@' => {
+                let line = &mut self.cells[self.row];
+                let n = count(0).min(self.cols - self.col);
+                for _ in 0..n {
+                    line.insert(self.col, ' ');
+                }
+                line.truncate(self.cols);
+            }
+            'n' => match arg(0) {
+                5 => self.replies.extend_from_slice(b"\x1b[0n"),
+                6 => {
+                    let report = format!("\x1b[{};{}R", self.row + 1, self.col + 1);
+                    self.replies.extend_from_slice(report.as_bytes());
+                }
+                _ => (),
+            },
+            's' => self.saved = (self.row, self.col),
+            'u' => self.move_to(self.saved.0, self.saved.1),
+            'h' | 'l' if private && params.contains(&25) => {
+                self.cursor_visible = command == 'h';
+            }
+            // Colours and other attributes aren't kept
+            _ => (),
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.wrap_pending = false;
+        self.row = row.min(self.rows - 1);
+        self.col = col.min(self.cols - 1);
+    }
+
+    fn clear_line(&mut self, row: usize, from: usize, to: usize) {
+        for cell in &mut self.cells[row][from..to.min(self.cols)] {
+            *cell = ' ';
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.rows {
+            self.row += 1;
+            return;
+        }
+        let top = self.cells.remove(0);
+        self.cells.push(vec![' '; self.cols]);
+        self.scrollback
+            .push_back(top.iter().collect::<String>().trim_end().to_string());
+        if self.scrollback.len() > SCROLLBACK_LINES {
+            self.scrollback.pop_front();
+        }
+    }
+
+    fn reverse_line_feed(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            return;
+        }
+        self.cells.pop();
+        self.cells.insert(0, vec![' '; self.cols]);
+    }
+}
@@ -0,0 +1,71 @@
+//! Setting a board's clock from the host, for firmware with a console
+//! command for its RTC. The command is a template filled in with the host
+//! time: `{unix}` seconds and `{unix_ms}` milliseconds since the epoch,
+//! `{date}` as `YYYY-MM-DD` and `{time}` as `HH:MM:SS`, both UTC.
+
+use crate::label::format_date;
+use crate::models::AppError;
+
+pub const DEFAULT_COMMAND: &str = "time set {unix}";
+
+const PLACEHOLDERS: [&str; 4] = ["unix", "unix_ms", "date", "time"];
+
+// Splits a template into literal text and placeholder names
+fn parts(template: &str) -> Result<Vec<(&str, Option<&str>)>, AppError> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..].find('}').map(|i| open + i).ok_or_else(|| {
+            AppError::InvalidInput(format!("Unclosed `{{` in the time command: {}", template))
+        })?;
+        let name = &rest[open + 1..close];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown placeholder {{{}}} in the time command, use one of {{{}}}",
+                name,
+                PLACEHOLDERS.join("}, {")
+            )));
+        }
+        parts.push((&rest[..open], Some(name)));
+        rest = &rest[close + 1..];
+    }
+    parts.push((rest, None));
+    Ok(parts)
+}
+
+/// Refuses templates that can't be filled in or carry no time.
+pub fn check(template: &str) -> Result<(), AppError> {
+    if parts(template)?.iter().all(|(_, name)| name.is_none()) {
+        return Err(AppError::InvalidInput(format!(
+            "The time command has no placeholder for the time: {:?}",
+            template
+        )));
+    }
+    Ok(())
+}
+
+/// The command for the time `unix_ms`, in milliseconds since the epoch.
+pub fn render(template: &str, unix_ms: u64) -> Result<String, AppError> {
+    check(template)?;
+    let secs = unix_ms / 1000;
+    let mut command = String::new();
+    for (text, name) in parts(template)? {
+        command.push_str(text);
+        match name {
+            Some("unix") => command.push_str(&secs.to_string()),
+            Some("unix_ms") => command.push_str(&unix_ms.to_string()),
+            Some("date") => command.push_str(&format_date(secs)),
+            Some(_) => {
+                let day = secs % 86_400;
+                command.push_str(&format!(
+                    "{:02}:{:02}:{:02}",
+                    day / 3600,
+                    day / 60 % 60,
+                    day % 60
+                ));
+            }
+            None => (),
+        }
+    }
+    Ok(command)
+}
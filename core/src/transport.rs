@@ -0,0 +1,406 @@
+//! Links to a board other than a local serial port, for flashing boards on
+//! a remote lab bench. A port name `rfc2217://host:port` reaches an RFC 2217
+//! server (esptool's `esp_rfc2217_server.py`, ser2net) that also carries
+//! the baud rate and the DTR/RTS lines driving reset; `tcp://host:port` is a
+//! plain byte pipe, so the board has to be put in download mode by hand.
+//! Such names work wherever a local port name does in `esp_interaction`.
+
+use crate::models::AppError;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use tracing::debug;
+
+const RFC2217_SCHEME: &str = "rfc2217://";
+const TCP_SCHEME: &str = "tcp://";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Telnet, RFC 854, and the options RFC 2217 builds on
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const BINARY: u8 = 0;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const COM_PORT_OPTION: u8 = 44;
+// COM-PORT-OPTION commands and SET-CONTROL values
+const SET_BAUDRATE: u8 = 1;
+const SET_CONTROL: u8 = 5;
+const DTR_ON: u8 = 8;
+const DTR_OFF: u8 = 9;
+const RTS_ON: u8 = 11;
+const RTS_OFF: u8 = 12;
+
+/// What the flasher and reset paths need from a link to a board.
+pub trait SerialLike: Read + Write + Send {
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), AppError>;
+    /// DTR, wired to IO0 by the usual auto-reset circuit.
+    fn set_dtr(&mut self, level: bool) -> Result<(), AppError>;
+    /// RTS, wired to EN by the usual auto-reset circuit.
+    fn set_rts(&mut self, level: bool) -> Result<(), AppError>;
+    /// How long a read waits for data before failing with `TimedOut`.
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), AppError>;
+    /// A second handle on the same link, e.g. to read and write from
+    /// different threads.
+    fn try_clone(&self) -> Result<Box<dyn SerialLike>, AppError>;
+}
+
+impl SerialLike for Box<dyn serialport::SerialPort> {
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), AppError> {
+        Ok(serialport::SerialPort::set_baud_rate(
+            self.as_mut(),
+            baud_rate,
+        )?)
+    }
+
+    fn set_dtr(&mut self, level: bool) -> Result<(), AppError> {
+        Ok(self.write_data_terminal_ready(level)?)
+    }
+
+    fn set_rts(&mut self, level: bool) -> Result<(), AppError> {
+        Ok(self.write_request_to_send(level)?)
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), AppError> {
+        Ok(serialport::SerialPort::set_timeout(self.as_mut(), timeout)?)
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn SerialLike>, AppError> {
+        Ok(Box::new(serialport::SerialPort::try_clone(self.as_ref())?))
+    }
+}
+
+/// Whether `port_name` names a network link rather than a local port.
+pub fn is_network(port_name: &str) -> bool {
+    port_name.starts_with(RFC2217_SCHEME) || port_name.starts_with(TCP_SCHEME)
+}
+
+/// Whether the link on `port_name` can drive DTR and RTS, and so reset the
+/// board into download mode.
+pub fn has_control_lines(port_name: &str) -> bool {
+    !port_name.starts_with(TCP_SCHEME)
+}
+
+/// Opens the link `port_name` names at `baud_rate`, a local serial port
+/// unless it has one of the network schemes.
+pub fn open(port_name: &str, baud_rate: u32) -> Result<Box<dyn SerialLike>, AppError> {
+    if let Some(address) = port_name.strip_prefix(RFC2217_SCHEME) {
+        let mut port = NetworkPort::connect(address, true)?;
+        port.set_baud_rate(baud_rate)?;
+        return Ok(Box::new(port));
+    }
+    if let Some(address) = port_name.strip_prefix(TCP_SCHEME) {
+        return Ok(Box::new(NetworkPort::connect(address, false)?));
+    }
+    let port = serialport::new(port_name, baud_rate).open()?;
+    Ok(Box::new(port))
+}
+
+// Where the Telnet decoder is in the incoming stream
+#[derive(Clone, Copy)]
+enum Telnet {
+    Data,
+    Iac,
+    // After DO, DONT, WILL or WONT
+    Option(u8),
+    Sub,
+    SubIac,
+}
+
+/// A board behind a TCP socket, speaking RFC 2217 or raw bytes.
+pub struct NetworkPort {
+    stream: TcpStream,
+    rfc2217: bool,
+    state: Telnet,
+}
+
+impl NetworkPort {
+    /// Connects to `address` (`host:port`), negotiating RFC 2217 when
+    /// `rfc2217` is set.
+    pub fn connect(address: &str, rfc2217: bool) -> Result<Self, AppError> {
+        let connect = || {
+            let mut last = io::Error::new(io::ErrorKind::NotFound, "no address");
+            for addr in address.to_socket_addrs()? {
+                match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                    Ok(stream) => return Ok(stream),
+                    Err(e) => last = e,
+                }
+            }
+            Err(last)
+        };
+        let stream = connect().map_err(|e| AppError::Connection(format!("{}: {}", address, e)))?;
+        stream.set_nodelay(true)?;
+        let mut port = NetworkPort {
+            stream,
+            rfc2217,
+            state: Telnet::Data,
+        };
+        if rfc2217 {
+            debug!("Negotiating RFC 2217 with {}", address);
+            let options = [
+                (WILL, BINARY),
+                (DO, BINARY),
+                (WILL, SUPPRESS_GO_AHEAD),
+                (DO, SUPPRESS_GO_AHEAD),
+                (WILL, COM_PORT_OPTION),
+            ];
+            for (verb, option) in options {
+                port.stream.write_all(&[IAC, verb, option])?;
+            }
+        }
+        Ok(port)
+    }
+
+    // A COM-PORT-OPTION subnegotiation, with IAC in the value doubled
+    fn command(&mut self, command: u8, value: &[u8]) -> Result<(), AppError> {
+        if !self.rfc2217 {
+            return Err(AppError::InvalidInput(
+                "A tcp:// link has no control lines; use an rfc2217:// server".to_string(),
+            ));
+        }
+        let mut frame = vec![IAC, SB, COM_PORT_OPTION, command];
+        for &b in value {
+            frame.push(b);
+            if b == IAC {
+                frame.push(IAC);
+            }
+        }
+        frame.extend_from_slice(&[IAC, SE]);
+        self.stream.write_all(&frame)?;
+        Ok(())
+    }
+
+    // Drops Telnet commands from `buf[..len]` in place, answering option
+    // requests this side didn't make; returns the data length left
+    fn decode(&mut self, buf: &mut [u8], len: usize) -> io::Result<usize> {
+        let mut out = 0;
+        let mut replies = Vec::new();
+        for i in 0..len {
+            let b = buf[i];
+            self.state = match (self.state, b) {
+                (Telnet::Data, IAC) => Telnet::Iac,
+                (Telnet::Data, _) => {
+                    buf[out] = b;
+                    out += 1;
+                    Telnet::Data
+                }
+                (Telnet::Iac, IAC) => {
+                    buf[out] = IAC;
+                    out += 1;
+                    Telnet::Data
+                }
+                (Telnet::Iac, DO | DONT | WILL | WONT) => Telnet::Option(b),
+                (Telnet::Iac, SB) => Telnet::Sub,
+                (Telnet::Iac, _) => Telnet::Data,
+                (Telnet::Option(verb), option) => {
+                    let ours = matches!(option, BINARY | SUPPRESS_GO_AHEAD | COM_PORT_OPTION);
+                    match verb {
+                        DO if !ours => replies.extend_from_slice(&[IAC, WONT, option]),
+                        WILL if !ours => replies.extend_from_slice(&[IAC, DONT, option]),
+                        _ => {}
+                    }
+                    Telnet::Data
+                }
+                // Acknowledgements and line state notifications, not needed
+                (Telnet::Sub, IAC) => Telnet::SubIac,
+                (Telnet::Sub, _) => Telnet::Sub,
+                (Telnet::SubIac, SE) => Telnet::Data,
+                (Telnet::SubIac, _) => Telnet::Sub,
+            };
+        }
+        if !replies.is_empty() {
+            self.stream.write_all(&replies)?;
+        }
+        Ok(out)
+    }
+}
+
+impl Read for NetworkPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let len = match self.stream.read(buf) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::BrokenPipe,
+                        "The server closed the connection",
+                    ))
+                }
+                Ok(len) => len,
+                // Serial ports report an expired timeout as TimedOut
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, e))
+                }
+                Err(e) => return Err(e),
+            };
+            if !self.rfc2217 {
+                return Ok(len);
+            }
+            // A read of nothing but Telnet commands keeps waiting
+            let data = self.decode(buf, len)?;
+            if data > 0 {
+                return Ok(data);
+            }
+        }
+    }
+}
+
+impl Write for NetworkPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.rfc2217 || !buf.contains(&IAC) {
+            return self.stream.write(buf);
+        }
+        let mut escaped = Vec::with_capacity(buf.len() + 8);
+        for &b in buf {
+            escaped.push(b);
+            if b == IAC {
+                escaped.push(IAC);
+            }
+        }
+        self.stream.write_all(&escaped)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl SerialLike for NetworkPort {
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), AppError> {
+        if !self.rfc2217 {
+            // The bridge's rate is set on its side
+            return Ok(());
+        }
+        self.command(SET_BAUDRATE, &baud_rate.to_be_bytes())
+    }
+
+    fn set_dtr(&mut self, level: bool) -> Result<(), AppError> {
+        self.command(SET_CONTROL, &[if level { DTR_ON } else { DTR_OFF }])
+    }
+
+    fn set_rts(&mut self, level: bool) -> Result<(), AppError> {
+        self.command(SET_CONTROL, &[if level { RTS_ON } else { RTS_OFF }])
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<(), AppError> {
+        // A zero timeout would mean blocking forever
+        let timeout = timeout.max(Duration::from_millis(1));
+        self.stream.set_read_timeout(Some(timeout))?;
+        Ok(())
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn SerialLike>, AppError> {
+        Ok(Box::new(NetworkPort {
+            stream: self.stream.try_clone()?,
+            rfc2217: self.rfc2217,
+            state: Telnet::Data,
+        }))
+    }
+}
+
+/// Pulses the board into its ROM download mode the way esptool's classic
+/// reset does: EN low, then released while IO0 is held low.
+pub fn enter_download_mode(link: &mut dyn SerialLike) -> Result<(), AppError> {
+    link.set_dtr(false)?;
+    link.set_rts(true)?;
+    std::thread::sleep(Duration::from_millis(100));
+    link.set_dtr(true)?;
+    link.set_rts(false)?;
+    std::thread::sleep(Duration::from_millis(50));
+    link.set_dtr(false)?;
+    Ok(())
+}
+
+/// Restarts the application: EN is pulsed low through RTS while IO0 (DTR)
+/// stays high.
+pub fn reset_to_app(link: &mut dyn SerialLike) -> Result<(), AppError> {
+    link.set_dtr(false)?;
+    link.set_rts(true)?;
+    std::thread::sleep(Duration::from_millis(100));
+    link.set_rts(false)?;
+    Ok(())
+}
+
+/// Lets the board run with both lines released. RTS goes first: DTR low
+/// with RTS high resets the chip, on the auto-reset circuit as on the
+/// USB-Serial/JTAG, and ports open with both lines high.
+pub fn release_lines(link: &mut dyn SerialLike) -> Result<(), AppError> {
+    link.set_rts(false)?;
+    link.set_dtr(false)?;
+    Ok(())
+}
+
+/// [`reset_to_app`] through the USB-Serial/JTAG of the C3, S3 and later
+/// chips, which resets the chip itself while RTS is high and DTR low. The
+/// chip needs a while after the reset before it follows the lines again,
+/// and Windows' usbser.sys only sends their state along with a DTR change,
+/// so DTR is set again after each RTS change.
+pub fn reset_usb_jtag_to_app(link: &mut dyn SerialLike) -> Result<(), AppError> {
+    release_lines(link)?;
+    link.set_rts(true)?;
+    link.set_dtr(false)?;
+    std::thread::sleep(Duration::from_millis(200));
+    link.set_rts(false)?;
+    link.set_dtr(false)?;
+    std::thread::sleep(Duration::from_millis(200));
+    Ok(())
+}
+
+/// Hands `link` to code that needs a native serial port, espflash's
+/// connection, through a pseudo terminal: bytes are copied both ways until
+/// the returned end is closed. Baud rate and control line changes on that
+/// end don't reach the link.
+#[cfg(unix)]
+pub(crate) fn bridge(link: Box<dyn SerialLike>) -> Result<serialport::TTYPort, AppError> {
+    use serialport::SerialPort;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    const POLL: Duration = Duration::from_millis(50);
+
+    let (mut master, slave) = serialport::TTYPort::pair()?;
+    master.set_timeout(POLL)?;
+    let mut to_host = master.try_clone_native()?;
+    let mut from_board = link;
+    from_board.set_timeout(POLL)?;
+    let mut to_board = from_board.try_clone()?;
+    let closed = Arc::new(AtomicBool::new(false));
+
+    let done = closed.clone();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            match master.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => {
+                    if to_board.write_all(&buf[..len]).is_err() {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+                // The other end was closed
+                Err(_) => break,
+            }
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while !closed.load(Ordering::Relaxed) {
+            match from_board.read(&mut buf) {
+                Ok(len) => {
+                    if to_host.write_all(&buf[..len]).is_err() {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+                Err(_) => break,
+            }
+        }
+        debug!("Network bridge closed");
+    });
+    Ok(slave)
+}
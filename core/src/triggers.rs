@@ -0,0 +1,109 @@
+//! Monitor triggers: user regexes checked against serial output as it
+//! arrives, each paired with an action for the app to carry out, e.g.
+//! answering a `[y/n]` prompt or raising an alert on a brownout.
+
+use crate::models::{AppError, MonitorTrigger, TriggerAction};
+use regex::Regex;
+
+// Longest partial line kept while waiting for its newline
+const MAX_LINE: usize = 4096;
+
+/// A trigger whose pattern matched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerHit {
+    pub pattern: String,
+    pub action: TriggerAction,
+    /// The output line it matched, without the line ending
+    pub line: String,
+}
+
+struct Rule {
+    regex: Regex,
+    trigger: MonitorTrigger,
+    // Already fired on the current line
+    fired: bool,
+}
+
+/// The enabled triggers of a set, following one output stream.
+#[derive(Default)]
+pub struct Triggers {
+    rules: Vec<Rule>,
+    line: Vec<u8>,
+}
+
+impl Triggers {
+    /// Compiles the enabled triggers; fails on the first bad pattern.
+    pub fn new(triggers: &[MonitorTrigger]) -> Result<Self, AppError> {
+        let rules = triggers
+            .iter()
+            .filter(|t| t.enabled)
+            .map(|trigger| {
+                let regex = Regex::new(&trigger.pattern).map_err(|e| {
+                    AppError::InvalidInput(format!(
+                        "Invalid trigger pattern {:?}: {}",
+                        trigger.pattern, e
+                    ))
+                })?;
+                Ok(Rule {
+                    regex,
+                    trigger: trigger.clone(),
+                    fired: false,
+                })
+            })
+            .collect::<Result<_, AppError>>()?;
+        Ok(Triggers {
+            rules,
+            line: Vec::new(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Forgets a partial line, e.g. when a new session starts.
+    pub fn reset(&mut self) {
+        self.line.clear();
+        self.rules.iter_mut().for_each(|r| r.fired = false);
+    }
+
+    /// Feeds received output; returns the triggers it set off, in order.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<TriggerHit> {
+        let mut hits = Vec::new();
+        if self.rules.is_empty() {
+            return hits;
+        }
+        for &byte in bytes {
+            if byte != b'\n' {
+                if self.line.len() < MAX_LINE {
+                    self.line.push(byte);
+                }
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            self.check(&line, &mut hits);
+            self.rules.iter_mut().for_each(|r| r.fired = false);
+        }
+        // Prompts wait for an answer without ending their line
+        if !self.line.is_empty() {
+            let line = self.line.clone();
+            self.check(&line, &mut hits);
+        }
+        hits
+    }
+
+    fn check(&mut self, line: &[u8], hits: &mut Vec<TriggerHit>) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches('\r');
+        for rule in self.rules.iter_mut().filter(|r| !r.fired) {
+            if rule.regex.is_match(line) {
+                rule.fired = true;
+                hits.push(TriggerHit {
+                    pattern: rule.trigger.pattern.clone(),
+                    action: rule.trigger.action.clone(),
+                    line: line.to_string(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! MicroPython WebREPL client: the REPL runs over text frames of a
+//! websocket, file transfers over binary frames with the `WA`/`WB` records
+//! of `webrepl_cli.py`.
+
+use crate::models::AppError;
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+use tungstenite::{Message, WebSocket};
+
+/// Port `webrepl.start()` listens on.
+pub const DEFAULT_PORT: u16 = 8266;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5);
+// Short enough that the monitor reader never holds the session for long
+const POLL_TIMEOUT: Duration = Duration::from_millis(10);
+// Flash writes on the device can stall a transfer for a while
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(10);
+const CHUNK_SIZE: usize = 1024;
+
+// Request record: "WA", op, 0, 0u64, size u32, name length u16, name[64]
+const OP_PUT: u8 = 1;
+const OP_GET: u8 = 2;
+const MAX_NAME: usize = 64;
+
+fn ws_error(e: tungstenite::Error) -> AppError {
+    match e {
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            AppError::Connection("WebREPL connection closed".to_string())
+        }
+        e => AppError::Connection(format!("WebREPL: {}", e)),
+    }
+}
+
+fn would_block(e: &tungstenite::Error) -> bool {
+    matches!(e, tungstenite::Error::Io(e)
+        if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut))
+}
+
+/// An authenticated WebREPL session.
+pub struct WebRepl {
+    socket: WebSocket<TcpStream>,
+    address: String,
+    // REPL output that arrived while we waited for something else
+    text: Vec<u8>,
+    // Binary payload not consumed yet
+    binary: VecDeque<u8>,
+}
+
+impl WebRepl {
+    /// Connects to `host` (port 8266 unless given as `host:port`) and logs
+    /// in with `password`.
+    pub fn connect(host: &str, password: &str) -> Result<Self, AppError> {
+        let address = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{}:{}", host, DEFAULT_PORT)
+        };
+        let unreachable = |e: std::io::Error| AppError::Connection(format!("{}: {}", address, e));
+        let target = address
+            .to_socket_addrs()
+            .map_err(unreachable)?
+            .next()
+            .ok_or_else(|| AppError::Connection(format!("{}: no address", address)))?;
+        let stream = TcpStream::connect_timeout(&target, CONNECT_TIMEOUT).map_err(unreachable)?;
+        stream
+            .set_read_timeout(Some(LOGIN_TIMEOUT))
+            .map_err(unreachable)?;
+        stream.set_nodelay(true).ok();
+
+        let (socket, _) = tungstenite::client(format!("ws://{}/", address), stream)
+            .map_err(|e| AppError::Connection(format!("WebREPL handshake failed: {}", e)))?;
+        socket
+            .get_ref()
+            .set_read_timeout(Some(POLL_TIMEOUT))
+            .map_err(unreachable)?;
+        let mut repl = WebRepl {
+            socket,
+            address,
+            text: Vec::new(),
+            binary: VecDeque::new(),
+        };
+        repl.login(password)?;
+        info!("WebREPL connected to {}", repl.address);
+        Ok(repl)
+    }
+
+    /// `host:port` of the device.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Answers the password prompt. What follows the login message is kept
+    /// for [`WebRepl::read_text`].
+    fn login(&mut self, password: &str) -> Result<(), AppError> {
+        self.wait_for_text("Password: ")?;
+        self.text.clear();
+        self.send_text(format!("{}\r", password).as_bytes())?;
+        let deadline = Instant::now() + LOGIN_TIMEOUT;
+        loop {
+            let text = String::from_utf8_lossy(&self.text).into_owned();
+            if let Some(at) = text.find("WebREPL connected") {
+                self.text.drain(..at);
+                return Ok(());
+            }
+            if text.contains("Access denied") {
+                return Err(AppError::InvalidInput(
+                    "The device refused the WebREPL password".to_string(),
+                ));
+            }
+            if Instant::now() >= deadline {
+                return Err(AppError::Timeout(
+                    "No answer to the WebREPL login".to_string(),
+                ));
+            }
+            self.poll()?;
+        }
+    }
+
+    fn wait_for_text(&mut self, expected: &str) -> Result<(), AppError> {
+        let deadline = Instant::now() + LOGIN_TIMEOUT;
+        while !String::from_utf8_lossy(&self.text).contains(expected) {
+            if Instant::now() >= deadline {
+                return Err(AppError::Timeout(format!(
+                    "{} did not prompt for the WebREPL password",
+                    self.address
+                )));
+            }
+            self.poll()?;
+        }
+        Ok(())
+    }
+
+    /// Reads at most one frame into the buffers. Returns whether one came.
+    fn poll(&mut self) -> Result<bool, AppError> {
+        match self.socket.read() {
+            Ok(Message::Text(text)) => self.text.extend_from_slice(text.as_bytes()),
+            Ok(Message::Binary(data)) => self.binary.extend(data),
+            Ok(Message::Close(_)) => {
+                return Err(AppError::Connection(
+                    "The device closed the WebREPL connection".to_string(),
+                ))
+            }
+            // Pings are answered by tungstenite itself
+            Ok(_) => {}
+            Err(ref e) if would_block(e) => return Ok(false),
+            Err(e) => return Err(ws_error(e)),
+        }
+        Ok(true)
+    }
+
+    /// REPL output received since the last call, empty if none arrived
+    /// within a few milliseconds.
+    pub fn read_text(&mut self) -> Result<Vec<u8>, AppError> {
+        if self.text.is_empty() {
+            self.poll()?;
+        }
+        Ok(std::mem::take(&mut self.text))
+    }
+
+    /// Types `data` into the REPL.
+    pub fn send_text(&mut self, data: &[u8]) -> Result<(), AppError> {
+        let text = String::from_utf8_lossy(data).into_owned();
+        self.socket.send(Message::Text(text)).map_err(ws_error)?;
+        debug!("WebREPL send: {} bytes", data.len());
+        Ok(())
+    }
+
+    fn send_binary(&mut self, data: &[u8]) -> Result<(), AppError> {
+        self.socket
+            .send(Message::Binary(data.to_vec()))
+            .map_err(ws_error)
+    }
+
+    fn read_binary(&mut self, len: usize) -> Result<Vec<u8>, AppError> {
+        let deadline = Instant::now() + TRANSFER_TIMEOUT;
+        while self.binary.len() < len {
+            if !self.poll()? && Instant::now() >= deadline {
+                return Err(AppError::Timeout(
+                    "The device stopped answering the file transfer".to_string(),
+                ));
+            }
+        }
+        Ok(self.binary.drain(..len).collect())
+    }
+
+    /// Reads a `WB` reply; anything but status 0 is an error.
+    fn read_status(&mut self, op: &str, name: &str) -> Result<(), AppError> {
+        let reply = self.read_binary(4)?;
+        if &reply[..2] != b"WB" {
+            return Err(AppError::Connection(
+                "Unexpected WebREPL file transfer reply".to_string(),
+            ));
+        }
+        match u16::from_le_bytes([reply[2], reply[3]]) {
+            0 => Ok(()),
+            // The device's OSError, e.g. a missing directory
+            code => Err(AppError::InvalidInput(format!(
+                "{} {} failed on the device (error {})",
+                op, name, code
+            ))),
+        }
+    }
+
+    fn request(&mut self, op: u8, size: u32, name: &str) -> Result<(), AppError> {
+        if name.is_empty() || name.len() > MAX_NAME {
+            return Err(AppError::InvalidInput(format!(
+                "Device path must be 1 to {} bytes: {:?}",
+                MAX_NAME, name
+            )));
+        }
+        self.binary.clear();
+        let mut record = Vec::with_capacity(82);
+        record.extend_from_slice(b"WA");
+        record.extend_from_slice(&[op, 0]);
+        record.extend_from_slice(&0u64.to_le_bytes());
+        record.extend_from_slice(&size.to_le_bytes());
+        record.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        record.extend_from_slice(name.as_bytes());
+        record.resize(82, 0);
+        // Split like webrepl_cli.py does; some ports mishandle one large frame
+        self.send_binary(&record[..10])?;
+        self.send_binary(&record[10..])
+    }
+
+    /// Writes `data` to the file `name` on the device, replacing it.
+    pub fn put(&mut self, name: &str, data: &[u8]) -> Result<(), AppError> {
+        let size = u32::try_from(data.len())
+            .map_err(|_| AppError::InvalidInput("File too large".to_string()))?;
+        self.request(OP_PUT, size, name)?;
+        self.read_status("Upload of", name)?;
+        for chunk in data.chunks(CHUNK_SIZE) {
+            self.send_binary(chunk)?;
+        }
+        self.read_status("Upload of", name)?;
+        info!("WebREPL put {} ({} bytes)", name, data.len());
+        Ok(())
+    }
+
+    /// Reads the file `name` from the device.
+    pub fn get(&mut self, name: &str) -> Result<Vec<u8>, AppError> {
+        self.request(OP_GET, 0, name)?;
+        self.read_status("Download of", name)?;
+        let mut data = Vec::new();
+        loop {
+            // Each zero byte asks for the next block: u16 length, then data
+            self.send_binary(&[0])?;
+            let header = self.read_binary(2)?;
+            let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+            if len == 0 {
+                break;
+            }
+            data.extend(self.read_binary(len)?);
+        }
+        self.read_status("Download of", name)?;
+        info!("WebREPL get {} ({} bytes)", name, data.len());
+        Ok(data)
+    }
+
+    pub fn close(mut self) {
+        self.socket.close(None).ok();
+        // Let tungstenite send the close frame
+        self.socket.flush().ok();
+    }
+}
@@ -0,0 +1,208 @@
+//! Protocomm over Bluetooth LE via btleplug. Each endpoint is a
+//! characteristic named by its user description descriptor; the request is
+//! written to it and the reply read back from it.
+
+use super::Transport;
+use crate::models::{AppError, ProvDevice};
+use btleplug::api::bleuuid::uuid_from_u16;
+use btleplug::api::{
+    BDAddr, Central, Characteristic, Manager as _, Peripheral as _, ScanFilter, WriteType,
+};
+use btleplug::platform::{Adapter, Manager, Peripheral};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tracing::{debug, info, warn};
+
+// Characteristic User Description
+const USER_DESCRIPTION: u16 = 0x2901;
+// Without descriptors, endpoints are told apart by bytes 2-3 of the UUID
+const ENDPOINT_IDS: [(u16, &str); 4] = [
+    (0xff50, "prov-scan"),
+    (0xff51, "prov-session"),
+    (0xff52, "prov-config"),
+    (0xff53, "proto-ver"),
+];
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub(crate) fn ble_error(e: btleplug::Error) -> AppError {
+    AppError::Connection(format!("Bluetooth: {}", e))
+}
+
+pub(crate) fn runtime() -> Result<Runtime, AppError> {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Runs one btleplug call, giving up after [`OPERATION_TIMEOUT`].
+pub(crate) async fn timed<T>(
+    what: &str,
+    op: impl Future<Output = Result<T, btleplug::Error>>,
+) -> Result<T, AppError> {
+    match tokio::time::timeout(OPERATION_TIMEOUT, op).await {
+        Ok(result) => result.map_err(ble_error),
+        Err(_) => Err(AppError::Timeout(format!("Bluetooth {} timed out", what))),
+    }
+}
+
+pub(crate) async fn adapter() -> Result<Adapter, AppError> {
+    let manager = Manager::new().await.map_err(ble_error)?;
+    manager
+        .adapters()
+        .await
+        .map_err(ble_error)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::Connection("No Bluetooth adapter found".to_string()))
+}
+
+/// How a device is picked again later: its address, or its name on
+/// platforms that hide addresses (macOS).
+fn device_id(address: BDAddr, name: &str) -> String {
+    if address == BDAddr::default() {
+        name.to_string()
+    } else {
+        address.to_string()
+    }
+}
+
+pub(crate) async fn scan_peripherals(
+    adapter: &Adapter,
+    duration: Duration,
+) -> Result<Vec<(Peripheral, ProvDevice)>, AppError> {
+    adapter
+        .start_scan(ScanFilter::default())
+        .await
+        .map_err(ble_error)?;
+    tokio::time::sleep(duration).await;
+    let peripherals = adapter.peripherals().await.map_err(ble_error)?;
+    adapter.stop_scan().await.ok();
+
+    let mut found = Vec::new();
+    for peripheral in peripherals {
+        let Ok(Some(properties)) = peripheral.properties().await else {
+            continue;
+        };
+        // Provisioning firmware always advertises a name (PROV_xxxxxx by default)
+        let Some(name) = properties.local_name else {
+            continue;
+        };
+        let device = ProvDevice {
+            id: device_id(properties.address, &name),
+            name,
+            rssi: properties.rssi,
+        };
+        found.push((peripheral, device));
+    }
+    found.sort_by_key(|(_, device)| std::cmp::Reverse(device.rssi.unwrap_or(i16::MIN)));
+    Ok(found)
+}
+
+/// Lists named BLE devices nearby, strongest signal first.
+pub fn scan(duration: Duration) -> Result<Vec<ProvDevice>, AppError> {
+    runtime()?.block_on(async {
+        let adapter = adapter().await?;
+        let found = scan_peripherals(&adapter, duration).await?;
+        Ok(found.into_iter().map(|(_, device)| device).collect())
+    })
+}
+
+/// A connection to one provisioning device.
+pub struct BleTransport {
+    runtime: Runtime,
+    peripheral: Peripheral,
+    endpoints: HashMap<String, Characteristic>,
+}
+
+impl BleTransport {
+    /// Connects to the device `id` (see [`ProvDevice::id`]) and maps its
+    /// endpoints.
+    pub fn connect(id: &str) -> Result<Self, AppError> {
+        let runtime = runtime()?;
+        let (peripheral, endpoints) = runtime.block_on(async {
+            let adapter = adapter().await?;
+            let peripheral = scan_peripherals(&adapter, Duration::from_secs(3))
+                .await?
+                .into_iter()
+                .find(|(_, device)| device.id == id || device.name == id)
+                .map(|(peripheral, _)| peripheral)
+                .ok_or_else(|| AppError::PortNotFound(format!("{} is not advertising", id)))?;
+
+            timed("connect", peripheral.connect()).await?;
+            timed("service discovery", peripheral.discover_services()).await?;
+
+            let mut endpoints = HashMap::new();
+            for characteristic in peripheral.characteristics() {
+                let description = characteristic
+                    .descriptors
+                    .iter()
+                    .find(|d| d.uuid == uuid_from_u16(USER_DESCRIPTION));
+                let name = match description {
+                    Some(descriptor) => timed("read", peripheral.read_descriptor(descriptor))
+                        .await
+                        .ok()
+                        .map(|name| {
+                            String::from_utf8_lossy(&name)
+                                .trim_end_matches('\0')
+                                .to_string()
+                        }),
+                    None => None,
+                };
+                let name = name.or_else(|| {
+                    let bytes = characteristic.uuid.as_bytes();
+                    let id = u16::from_be_bytes([bytes[2], bytes[3]]);
+                    ENDPOINT_IDS
+                        .iter()
+                        .find(|(known, _)| *known == id)
+                        .map(|(_, name)| name.to_string())
+                });
+                if let Some(name) = name {
+                    debug!("Endpoint {} at {}", name, characteristic.uuid);
+                    endpoints.insert(name, characteristic);
+                }
+            }
+            Ok::<_, AppError>((peripheral, endpoints))
+        })?;
+
+        if !endpoints.contains_key(super::security::SESSION_ENDPOINT) {
+            runtime.block_on(peripheral.disconnect()).ok();
+            return Err(AppError::WrongChip(format!(
+                "{} does not offer Wi-Fi provisioning",
+                id
+            )));
+        }
+        info!("BLE provisioning connected to {}", id);
+        Ok(BleTransport {
+            runtime,
+            peripheral,
+            endpoints,
+        })
+    }
+
+    pub fn disconnect(&self) {
+        if let Err(e) = self.runtime.block_on(self.peripheral.disconnect()) {
+            warn!("BLE disconnect failed: {}", e);
+        }
+    }
+}
+
+impl Transport for BleTransport {
+    fn exchange(&mut self, endpoint: &str, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        let characteristic = self.endpoints.get(endpoint).ok_or_else(|| {
+            AppError::Connection(format!("The device has no {} endpoint", endpoint))
+        })?;
+        let peripheral = &self.peripheral;
+        self.runtime.block_on(async {
+            timed(
+                "write",
+                peripheral.write(characteristic, data, WriteType::WithResponse),
+            )
+            .await?;
+            timed("read", peripheral.read(characteristic)).await
+        })
+    }
+}
@@ -0,0 +1,168 @@
+//! Wi-Fi provisioning of boards running Espressif's `wifi_provisioning`
+//! manager (the IDF `provisioning` example, RainMaker and friends) over
+//! Bluetooth LE, speaking the same protocomm messages as `esp_prov.py`.
+
+pub(crate) mod ble;
+pub mod proto;
+pub mod security;
+
+use crate::models::{AppError, ProvOutcome};
+use crate::tasks::TaskHandle;
+use proto::{Message, Writer};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+pub use ble::{scan, BleTransport};
+
+/// Username the IDF example registers for security 2.
+pub const DEFAULT_USERNAME: &str = "wifiprov";
+
+pub const VERSION_ENDPOINT: &str = "proto-ver";
+pub const CONFIG_ENDPOINT: &str = "prov-config";
+
+// `WiFiConfigPayload` message types and fields (wifi_config.proto)
+const MSG: u32 = 1;
+const CMD_GET_STATUS: u64 = 0;
+const CMD_SET_CONFIG: u64 = 2;
+const CMD_APPLY_CONFIG: u64 = 4;
+const FIELD_CMD_GET_STATUS: u32 = 10;
+const FIELD_RESP_GET_STATUS: u32 = 11;
+const FIELD_CMD_SET_CONFIG: u32 = 12;
+const FIELD_RESP_SET_CONFIG: u32 = 13;
+const FIELD_CMD_APPLY_CONFIG: u32 = 14;
+const FIELD_RESP_APPLY_CONFIG: u32 = 15;
+
+// `WifiStationState`
+const STA_CONNECTED: u64 = 0;
+const STA_FAILED: u64 = 3;
+
+// Joining the network, DHCP included
+const JOIN_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One endpoint round trip: write the request, read the reply.
+pub trait Transport {
+    fn exchange(&mut self, endpoint: &str, data: &[u8]) -> Result<Vec<u8>, AppError>;
+}
+
+pub struct ProvOptions<'a> {
+    /// Address or advertised name, as listed by [`scan`].
+    pub device: &'a str,
+    /// Security scheme; `None` takes the one the device announces.
+    pub security: Option<u8>,
+    /// Proof of possession, empty when the device doesn't ask for one.
+    pub pop: &'a str,
+    pub username: &'a str,
+    pub ssid: &'a str,
+    pub passphrase: &'a str,
+}
+
+/// Security version the device announces on `proto-ver`, if any. Old
+/// firmware answers without `sec_ver`, or not at all.
+fn announced_security(transport: &mut dyn Transport) -> Option<u8> {
+    let reply = transport.exchange(VERSION_ENDPOINT, b"---").ok()?;
+    let info: serde_json::Value = serde_json::from_slice(&reply).ok()?;
+    debug!("Provisioning version info: {}", info);
+    info["prov"]["sec_ver"].as_u64().map(|v| v as u8)
+}
+
+/// Sends one `prov-config` command through the session and returns the
+/// response message in `field`.
+fn config_request(
+    transport: &mut dyn Transport,
+    session: &mut security::Session,
+    request: Writer,
+    field: u32,
+) -> Result<Message, AppError> {
+    let encrypted = session.encrypt(&request.finish())?;
+    let reply = session.decrypt(&transport.exchange(CONFIG_ENDPOINT, &encrypted)?)?;
+    let response = Message::parse(&reply)?.message(field)?;
+    match response.varint(1) {
+        0 => Ok(response),
+        status => Err(AppError::InvalidInput(format!(
+            "The device rejected the Wi-Fi configuration (status {})",
+            status
+        ))),
+    }
+}
+
+/// Provisions over any transport; [`provision`] runs it over BLE.
+pub fn provision_with(
+    transport: &mut dyn Transport,
+    options: &ProvOptions,
+    task: &TaskHandle,
+) -> Result<ProvOutcome, AppError> {
+    let version = match options.security {
+        Some(version) => version,
+        None => announced_security(transport).unwrap_or(1),
+    };
+    task.checkpoint()?;
+    let mut session = security::establish(transport, version, options.pop, options.username)?;
+    task.checkpoint()?;
+
+    task.phase("provisioning");
+    let set = Writer::new().varint(MSG, CMD_SET_CONFIG).message(
+        FIELD_CMD_SET_CONFIG,
+        Writer::new()
+            .bytes(1, options.ssid.as_bytes())
+            .bytes(2, options.passphrase.as_bytes()),
+    );
+    config_request(transport, &mut session, set, FIELD_RESP_SET_CONFIG)?;
+    let apply = Writer::new()
+        .varint(MSG, CMD_APPLY_CONFIG)
+        .message(FIELD_CMD_APPLY_CONFIG, Writer::new());
+    config_request(transport, &mut session, apply, FIELD_RESP_APPLY_CONFIG)?;
+
+    let deadline = Instant::now() + JOIN_TIMEOUT;
+    loop {
+        task.checkpoint()?;
+        let status = Writer::new()
+            .varint(MSG, CMD_GET_STATUS)
+            .message(FIELD_CMD_GET_STATUS, Writer::new());
+        let status = config_request(transport, &mut session, status, FIELD_RESP_GET_STATUS)?;
+        match status.varint(2) {
+            STA_CONNECTED => {
+                let connected = status.message(11)?;
+                let ip = String::from_utf8_lossy(connected.bytes(1)).into_owned();
+                info!(
+                    "Provisioned {} onto {} ({})",
+                    options.device, options.ssid, ip
+                );
+                return Ok(ProvOutcome {
+                    ip: Some(ip).filter(|ip| !ip.is_empty()),
+                });
+            }
+            STA_FAILED => {
+                // `WifiConnectFailedReason`: 0 auth error, 1 network not found
+                let reason = if status.varint(10) == 0 {
+                    "wrong Wi-Fi password"
+                } else {
+                    "network not found"
+                };
+                return Err(AppError::Connection(format!(
+                    "The device could not join {:?}: {}",
+                    options.ssid, reason
+                )));
+            }
+            // Connecting, or disconnected and retrying on its own
+            _ => {}
+        }
+        if Instant::now() >= deadline {
+            return Err(AppError::Timeout(format!(
+                "The device did not join {:?} within {} s",
+                options.ssid,
+                JOIN_TIMEOUT.as_secs()
+            )));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Connects to `options.device` over BLE and hands it the network.
+pub fn provision(options: &ProvOptions, task: &TaskHandle) -> Result<ProvOutcome, AppError> {
+    task.phase("connecting");
+    let mut transport = BleTransport::connect(options.device)?;
+    let result = provision_with(&mut transport, options, task);
+    transport.disconnect();
+    result
+}
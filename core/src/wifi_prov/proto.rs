@@ -0,0 +1,131 @@
+//! Just enough protobuf for the protocomm messages: varints and
+//! length-delimited fields, encoded and decoded without a schema. Field
+//! numbers come from the `.proto` files in ESP-IDF's `protocomm` and
+//! `wifi_provisioning` components.
+
+use crate::models::AppError;
+
+/// A message under construction.
+#[derive(Default)]
+pub struct Writer(Vec<u8>);
+
+impl Writer {
+    pub fn new() -> Self {
+        Writer::default()
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint_raw(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn varint_raw(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    pub fn varint(mut self, field: u32, value: u64) -> Self {
+        self.key(field, 0);
+        self.varint_raw(value);
+        self
+    }
+
+    pub fn bytes(mut self, field: u32, data: &[u8]) -> Self {
+        self.key(field, 2);
+        self.varint_raw(data.len() as u64);
+        self.0.extend_from_slice(data);
+        self
+    }
+
+    /// Embeds `message`; an empty one still selects its `oneof` branch.
+    pub fn message(self, field: u32, message: Writer) -> Self {
+        self.bytes(field, &message.0)
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+enum Value {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+/// A decoded message; absent fields read as their proto3 defaults.
+pub struct Message(Vec<(u32, Value)>);
+
+fn malformed() -> AppError {
+    AppError::Connection("Malformed protobuf reply from the device".to_string())
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Result<u64, AppError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos).ok_or_else(malformed)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(malformed())
+}
+
+impl Message {
+    pub fn parse(data: &[u8]) -> Result<Message, AppError> {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while pos < data.len() {
+            let key = read_varint(data, &mut pos)?;
+            let field = (key >> 3) as u32;
+            let value = match key & 7 {
+                0 => Value::Varint(read_varint(data, &mut pos)?),
+                2 => {
+                    let len = read_varint(data, &mut pos)? as usize;
+                    let end = pos.checked_add(len).filter(|end| *end <= data.len());
+                    let end = end.ok_or_else(malformed)?;
+                    let bytes = data[pos..end].to_vec();
+                    pos = end;
+                    Value::Bytes(bytes)
+                }
+                // Fixed-width fields don't occur in these messages
+                _ => return Err(malformed()),
+            };
+            fields.push((field, value));
+        }
+        Ok(Message(fields))
+    }
+
+    pub fn varint(&self, field: u32) -> u64 {
+        self.0
+            .iter()
+            .rev()
+            .find_map(|(f, v)| match v {
+                Value::Varint(value) if *f == field => Some(*value),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn bytes(&self, field: u32) -> &[u8] {
+        self.0
+            .iter()
+            .rev()
+            .find_map(|(f, v)| match v {
+                Value::Bytes(bytes) if *f == field => Some(bytes.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn has(&self, field: u32) -> bool {
+        self.0.iter().any(|(f, _)| *f == field)
+    }
+
+    pub fn message(&self, field: u32) -> Result<Message, AppError> {
+        Message::parse(self.bytes(field))
+    }
+}
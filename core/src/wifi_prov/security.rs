@@ -0,0 +1,331 @@
+//! Protocomm session security: the handshakes on the `prov-session`
+//! endpoint and the cipher that protects every message afterwards.
+//!
+//! - Security 0: no encryption.
+//! - Security 1: X25519 key exchange, the shared key XORed with the
+//!   SHA-256 of the proof of possession (PoP), then AES-256-CTR.
+//! - Security 2: SRP6a (3072-bit group, SHA-512) with the PoP as password,
+//!   then AES-256-GCM.
+
+use super::proto::{Message, Writer};
+use super::Transport;
+use crate::models::AppError;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha512};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+pub const SESSION_ENDPOINT: &str = "prov-session";
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+
+// RFC 5054 3072-bit group, the one ESP-IDF's SRP6a uses
+const SRP_N: &str = concat!(
+    "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+    "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+    "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+    "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+    "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+    "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+    "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+    "3995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33",
+    "A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7",
+    "ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864",
+    "D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E2",
+    "08E24FA074E5AB3143DB5BFCE0FD108E4B82D120A93AD2CAFFFFFFFFFFFFFFFF",
+);
+const SRP_G: u32 = 5;
+
+// `SessionData` fields (session.proto)
+const SEC_VER: u32 = 2;
+const SEC0_PAYLOAD: u32 = 10;
+const SEC1_PAYLOAD: u32 = 11;
+const SEC2_PAYLOAD: u32 = 12;
+// Fields shared by the Sec1/Sec2 payloads
+const MSG: u32 = 1;
+const CMD0: u32 = 20;
+const RESP0: u32 = 21;
+const CMD1: u32 = 22;
+const RESP1: u32 = 23;
+const MSG_COMMAND1: u64 = 2;
+
+enum Cipher {
+    Plain,
+    Ctr(Box<Aes256Ctr>),
+    Gcm { cipher: Box<Aes256Gcm>, nonce: [u8; 12] },
+}
+
+/// An established session. All endpoints other than `proto-ver` go
+/// through `encrypt`/`decrypt`.
+pub struct Session {
+    cipher: Cipher,
+}
+
+impl Session {
+    pub fn encrypt(&mut self, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        match &mut self.cipher {
+            Cipher::Plain => Ok(data.to_vec()),
+            // One keystream for both directions, like the device keeps it
+            Cipher::Ctr(ctr) => {
+                let mut out = data.to_vec();
+                ctr.apply_keystream(&mut out);
+                Ok(out)
+            }
+            Cipher::Gcm { cipher, nonce } => cipher
+                .encrypt(Nonce::from_slice(nonce), data)
+                .map_err(|_| AppError::Internal("AES-GCM encryption failed".to_string())),
+        }
+    }
+
+    pub fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        if let Cipher::Gcm { cipher, nonce } = &self.cipher {
+            return cipher
+                .decrypt(Nonce::from_slice(nonce), data)
+                .map_err(|_| AppError::Connection("Reply failed authentication".to_string()));
+        }
+        // CTR and plain text are their own inverse
+        self.encrypt(data)
+    }
+}
+
+fn check_status(message: &Message, step: &str) -> Result<(), AppError> {
+    match message.varint(1) {
+        0 => Ok(()),
+        status => Err(AppError::InvalidInput(format!(
+            "The device refused the {} (status {})",
+            step, status
+        ))),
+    }
+}
+
+/// Sends one session command and returns the matching response message.
+fn exchange(
+    transport: &mut dyn Transport,
+    version: u64,
+    payload_field: u32,
+    payload: Writer,
+    response_field: u32,
+) -> Result<Message, AppError> {
+    let request = Writer::new()
+        .varint(SEC_VER, version)
+        .message(payload_field, payload)
+        .finish();
+    let reply = Message::parse(&transport.exchange(SESSION_ENDPOINT, &request)?)?;
+    let response = reply.message(payload_field)?;
+    if !response.has(response_field) {
+        return Err(AppError::Connection(
+            "The device answered with a different security scheme".to_string(),
+        ));
+    }
+    response.message(response_field)
+}
+
+/// Runs the handshake for security `version`. `pop` is the proof of
+/// possession, `username` only matters to security 2.
+pub fn establish(
+    transport: &mut dyn Transport,
+    version: u8,
+    pop: &str,
+    username: &str,
+) -> Result<Session, AppError> {
+    match version {
+        0 => sec0(transport),
+        1 => sec1(transport, pop),
+        2 => sec2(transport, pop, username),
+        v => Err(AppError::InvalidInput(format!(
+            "Unsupported provisioning security version {}",
+            v
+        ))),
+    }
+}
+
+fn sec0(transport: &mut dyn Transport) -> Result<Session, AppError> {
+    let payload = Writer::new().varint(MSG, 0).message(CMD0, Writer::new());
+    let request = Writer::new()
+        .varint(SEC_VER, 0)
+        .message(SEC0_PAYLOAD, payload)
+        .finish();
+    transport.exchange(SESSION_ENDPOINT, &request)?;
+    Ok(Session {
+        cipher: Cipher::Plain,
+    })
+}
+
+fn sec1(transport: &mut dyn Transport, pop: &str) -> Result<Session, AppError> {
+    let secret = EphemeralSecret::random_from_rng(OsRng);
+    let client_key = PublicKey::from(&secret);
+
+    let payload = Writer::new()
+        .varint(MSG, 0)
+        .message(CMD0, Writer::new().bytes(1, client_key.as_bytes()));
+    let resp0 = exchange(transport, 1, SEC1_PAYLOAD, payload, RESP0)?;
+    check_status(&resp0, "session")?;
+    let device_key: [u8; 32] = resp0
+        .bytes(2)
+        .try_into()
+        .map_err(|_| AppError::Connection("Bad device public key".to_string()))?;
+    let device_random: [u8; 16] = resp0
+        .bytes(3)
+        .try_into()
+        .map_err(|_| AppError::Connection("Bad device random".to_string()))?;
+
+    let mut key = *secret
+        .diffie_hellman(&PublicKey::from(device_key))
+        .as_bytes();
+    if !pop.is_empty() {
+        for (k, p) in key.iter_mut().zip(Sha256::digest(pop.as_bytes())) {
+            *k ^= p;
+        }
+    }
+    let mut session = Session {
+        cipher: Cipher::Ctr(Box::new(Aes256Ctr::new(&key.into(), &device_random.into()))),
+    };
+
+    // Each side proves it has the key by encrypting the other's public key
+    let verify = session.encrypt(&device_key)?;
+    let payload = Writer::new()
+        .varint(MSG, MSG_COMMAND1)
+        .message(CMD1, Writer::new().bytes(2, &verify));
+    let resp1 = exchange(transport, 1, SEC1_PAYLOAD, payload, RESP1)?;
+    check_status(&resp1, "proof of possession")?;
+    if session.decrypt(resp1.bytes(3))? != client_key.as_bytes() {
+        return Err(AppError::InvalidInput(
+            "Wrong proof of possession".to_string(),
+        ));
+    }
+    Ok(session)
+}
+
+fn sha512(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+/// Left-pads `value` to the byte length of N.
+fn pad(value: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut out = vec![0u8; len.saturating_sub(bytes.len())];
+    out.extend(bytes);
+    out
+}
+
+/// Client half of SRP6a as ESP-IDF's `esp_srp` computes it.
+pub struct SrpClient {
+    n: BigUint,
+    g: BigUint,
+    a: BigUint,
+    pub public: Vec<u8>,
+}
+
+/// Outcome of [`SrpClient::process`]: our proof, the one expected back and
+/// the session key.
+pub struct SrpProofs {
+    pub client: Vec<u8>,
+    pub device: Vec<u8>,
+    pub key: Vec<u8>,
+}
+
+impl SrpClient {
+    pub fn new() -> Self {
+        let mut a = [0u8; 32];
+        OsRng.fill_bytes(&mut a);
+        let n = BigUint::parse_bytes(SRP_N.as_bytes(), 16).expect("valid SRP group");
+        let g = BigUint::from(SRP_G);
+        let a = BigUint::from_bytes_be(&a);
+        let public = g.modpow(&a, &n).to_bytes_be();
+        SrpClient { n, g, a, public }
+    }
+
+    pub fn process(
+        &self,
+        username: &str,
+        password: &str,
+        salt: &[u8],
+        device_public: &[u8],
+    ) -> Result<SrpProofs, AppError> {
+        let len = self.n.to_bytes_be().len();
+        let big_a = BigUint::from_bytes_be(&self.public);
+        let big_b = BigUint::from_bytes_be(device_public);
+        if (&big_b % &self.n) == BigUint::from(0u32) {
+            return Err(AppError::Connection("Bad SRP public key".to_string()));
+        }
+
+        let k = BigUint::from_bytes_be(&sha512(&[&self.n.to_bytes_be(), &pad(&self.g, len)]));
+        let u = BigUint::from_bytes_be(&sha512(&[&pad(&big_a, len), &pad(&big_b, len)]));
+        let inner = sha512(&[username.as_bytes(), b":", password.as_bytes()]);
+        let x = BigUint::from_bytes_be(&sha512(&[salt, &inner]));
+
+        // S = (B - k * g^x) ^ (a + u * x) mod N
+        let kv = (k * self.g.modpow(&x, &self.n)) % &self.n;
+        let base = ((&big_b % &self.n) + &self.n - kv) % &self.n;
+        let s = base.modpow(&(&self.a + u * x), &self.n);
+        let key = sha512(&[&s.to_bytes_be()]);
+
+        let hn = sha512(&[&self.n.to_bytes_be()]);
+        let hg = sha512(&[&self.g.to_bytes_be()]);
+        let hng: Vec<u8> = hn.iter().zip(&hg).map(|(a, b)| a ^ b).collect();
+        let client = sha512(&[
+            &hng,
+            &sha512(&[username.as_bytes()]),
+            salt,
+            &self.public,
+            device_public,
+            &key,
+        ]);
+        let device = sha512(&[&self.public, &client, &key]);
+        Ok(SrpProofs {
+            client,
+            device,
+            key,
+        })
+    }
+}
+
+impl Default for SrpClient {
+    fn default() -> Self {
+        SrpClient::new()
+    }
+}
+
+fn sec2(transport: &mut dyn Transport, pop: &str, username: &str) -> Result<Session, AppError> {
+    let srp = SrpClient::new();
+    let payload = Writer::new().varint(MSG, 0).message(
+        CMD0,
+        Writer::new()
+            .bytes(1, username.as_bytes())
+            .bytes(2, &srp.public),
+    );
+    let resp0 = exchange(transport, 2, SEC2_PAYLOAD, payload, RESP0)?;
+    check_status(&resp0, "session")?;
+    let proofs = srp.process(username, pop, resp0.bytes(3), resp0.bytes(2))?;
+
+    let payload = Writer::new()
+        .varint(MSG, MSG_COMMAND1)
+        .message(CMD1, Writer::new().bytes(1, &proofs.client));
+    let resp1 = exchange(transport, 2, SEC2_PAYLOAD, payload, RESP1)?;
+    check_status(&resp1, "proof of possession")?;
+    if resp1.bytes(2) != proofs.device.as_slice() {
+        return Err(AppError::InvalidInput(
+            "Wrong username or proof of possession".to_string(),
+        ));
+    }
+    let nonce: [u8; 12] = resp1
+        .bytes(3)
+        .try_into()
+        .map_err(|_| AppError::Connection("Bad device nonce".to_string()))?;
+    let cipher = Aes256Gcm::new_from_slice(&proofs.key[..32])
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(Session {
+        cipher: Cipher::Gcm {
+            cipher: Box::new(cipher),
+            nonce,
+        },
+    })
+}
@@ -0,0 +1,37 @@
+use esp32dev_core::api_auth::{bearer_matches, host_allowed, new_token, origin_allowed};
+
+#[test]
+fn only_the_loopback_host_on_our_port_is_served() {
+    assert!(host_allowed(Some("127.0.0.1:8765"), 8765));
+    assert!(host_allowed(Some("LOCALHOST:8765"), 8765));
+    // A rebinding page keeps its own name in `Host`
+    assert!(!host_allowed(Some("evil.example:8765"), 8765));
+    assert!(!host_allowed(Some("127.0.0.1:80"), 8765));
+    assert!(!host_allowed(Some("127.0.0.1"), 8765));
+    assert!(!host_allowed(None, 8765));
+}
+
+#[test]
+fn foreign_origins_are_refused() {
+    assert!(origin_allowed(None));
+    assert!(origin_allowed(Some("http://localhost:1420")));
+    assert!(origin_allowed(Some("http://tauri.localhost")));
+    assert!(!origin_allowed(Some("https://evil.example")));
+    assert!(!origin_allowed(Some("null")));
+}
+
+#[test]
+fn tokens_are_checked_whole() {
+    let token = new_token();
+    assert_eq!(token.len(), 64);
+    assert_ne!(token, new_token());
+    assert!(bearer_matches(Some(&format!("Bearer {}", token)), &token));
+    assert!(bearer_matches(Some(&format!("bearer  {} ", token)), &token));
+    assert!(!bearer_matches(
+        Some(&format!("Bearer {}", &token[1..])),
+        &token
+    ));
+    assert!(!bearer_matches(Some(&format!("Basic {}", token)), &token));
+    assert!(!bearer_matches(None, &token));
+    assert!(!bearer_matches(Some("Bearer "), ""));
+}
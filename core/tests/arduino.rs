@@ -0,0 +1,144 @@
+mod common;
+
+use common::scratch;
+use esp32dev_core::arduino;
+use esp32dev_core::models::{AppError, TaskInfo};
+use esp32dev_core::tasks::TaskManager;
+use std::path::Path;
+
+fn write(path: &Path, data: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, data).unwrap();
+}
+
+/// Image header of a DIO, 4 MB build for an ESP32.
+fn image() -> Vec<u8> {
+    let mut data = vec![0xe9, 0x03, 0x02, 0x2f];
+    data.resize(64, 0);
+    data
+}
+
+/// A partition table with a factory app at 0x10000.
+fn partitions() -> Vec<u8> {
+    let mut entry = vec![0xaa, 0x50, 0, 0];
+    entry.extend_from_slice(&0x10000u32.to_le_bytes());
+    entry.extend_from_slice(&0x100000u32.to_le_bytes());
+    entry.resize(32, 0);
+    entry
+}
+
+/// Output of `arduino-cli compile --output-dir out` for sketch `name`.
+fn build_output(out: &Path, name: &str) {
+    write(&out.join(format!("{}.ino.bootloader.bin", name)), &image());
+    write(
+        &out.join(format!("{}.ino.partitions.bin", name)),
+        &partitions(),
+    );
+    write(&out.join(format!("{}.ino.bin", name)), &image());
+}
+
+#[test]
+fn fqbn_is_the_dev_module_of_the_chip() {
+    assert_eq!(
+        arduino::fqbn("ESP32-S3").as_deref(),
+        Some("esp32:esp32:esp32s3")
+    );
+    assert_eq!(arduino::fqbn("esp32").as_deref(), Some("esp32:esp32:esp32"));
+    assert_eq!(arduino::fqbn("ESP8266"), None);
+}
+
+#[test]
+fn sketch_is_a_folder_with_a_matching_ino() {
+    let root = scratch("arduino-sketch");
+    let blink = root.join("Blink");
+    assert!(arduino::sketch(&blink).is_err());
+
+    write(
+        &blink.join("Blink.ino"),
+        b"void setup() {}\nvoid loop() {}\n",
+    );
+    let (dir, name) = arduino::sketch(&blink).unwrap();
+    assert_eq!((dir.as_path(), name.as_str()), (blink.as_path(), "Blink"));
+    // The .ino itself names the same sketch
+    assert_eq!(
+        arduino::sketch(&blink.join("Blink.ino")).unwrap().1,
+        "Blink"
+    );
+
+    write(&root.join("Other/main.ino"), b"");
+    assert!(arduino::sketch(&root.join("Other")).is_err());
+    std::fs::remove_dir_all(&root).ok();
+}
+
+#[test]
+fn build_output_becomes_a_flash_plan() {
+    let out = scratch("arduino-out");
+    assert!(arduino::load(&out, "Blink").is_err());
+
+    build_output(&out, "Blink");
+    let plan = arduino::load(&out, "Blink").unwrap();
+    assert_eq!(plan.chip.as_deref(), Some("esp32"));
+    assert_eq!(plan.flash_size.as_deref(), Some("4MB"));
+    let first = plan.segments.first().unwrap();
+    assert_eq!(first.address, 0x1000);
+    assert!(first.path.ends_with("Blink.ino.bootloader.bin"));
+    let app = plan.segments.last().unwrap();
+    assert_eq!(app.address, 0x10000);
+    assert!(app.path.ends_with("Blink.ino.bin"));
+    std::fs::remove_dir_all(&out).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn compile_streams_output_and_reports_failures() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = scratch("arduino-compile");
+    let blink = root.join("Blink");
+    write(&blink.join("Blink.ino"), b"");
+    let out = root.join("out");
+    build_output(&out, "Blink");
+    let fake_cli = |name: &str, exit: u8| {
+        let path = root.join(name);
+        let script = format!(
+            "#!/bin/sh\necho \"$1 $2 $3\"\necho 'warning: unused variable' >&2\nexit {}\n",
+            exit
+        );
+        write(&path, script.as_bytes());
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    };
+
+    let tasks = TaskManager::new(|_: &TaskInfo| {});
+    let task = tasks.start("arduino_build", None);
+    let mut log = Vec::new();
+    let plan = arduino::compile(
+        &fake_cli("ok", 0),
+        &blink,
+        "esp32:esp32:esp32",
+        &out,
+        &task,
+        |line| log.push(line.to_string()),
+    )
+    .unwrap();
+    assert!(plan.segments.len() >= 3);
+    log.sort();
+    assert_eq!(
+        log,
+        [
+            "compile --fqbn esp32:esp32:esp32\n",
+            "warning: unused variable\n"
+        ]
+    );
+
+    let failed = arduino::compile(
+        &fake_cli("broken", 1),
+        &blink,
+        "esp32:esp32:esp32",
+        &out,
+        &task,
+        |_| {},
+    );
+    assert!(matches!(failed, Err(AppError::InvalidInput(_))));
+    std::fs::remove_dir_all(&root).ok();
+}
@@ -0,0 +1,81 @@
+use esp32dev_core::benchmark;
+use esp32dev_core::models::{AppError, BenchmarkRun};
+use esp32dev_core::otadata::Partition;
+
+fn partition(label: &str, offset: u32, size: u32) -> Partition {
+    Partition {
+        label: label.to_string(),
+        kind: 0,
+        subtype: 0,
+        offset,
+        size,
+    }
+}
+
+fn run(baud_rate: u32, write_rate: u64, verified: bool) -> BenchmarkRun {
+    BenchmarkRun {
+        baud_rate,
+        write_rate: Some(write_rate),
+        read_rate: Some(write_rate * 2),
+        verified,
+        error: (!verified).then(|| "timed out".to_string()),
+    }
+}
+
+#[test]
+fn patterns_differ_per_seed_and_do_not_repeat() {
+    let a = benchmark::pattern(4096, 115200);
+    assert_eq!(a.len(), 4096);
+    assert_eq!(a, benchmark::pattern(4096, 115200));
+    assert_ne!(a, benchmark::pattern(4096, 921600));
+    // Not a fill the stub's compression would shrink to nothing
+    assert_ne!(a[..16], a[16..32]);
+    assert_eq!(benchmark::pattern(5, 0).len(), 5);
+}
+
+#[test]
+fn scratch_defaults_to_the_end_of_the_flash() {
+    let table = [
+        partition("nvs", 0x9000, 0x6000),
+        partition("factory", 0x10000, 0x100000),
+    ];
+    let offset = benchmark::scratch_region(&table, Some(0x400000), 0x10000, None).unwrap();
+    assert_eq!(offset, 0x3f0000);
+    let offset = benchmark::scratch_region(&table, Some(0x400000), 0x10000, Some(0x200000));
+    assert_eq!(offset.unwrap(), 0x200000);
+}
+
+#[test]
+fn scratch_regions_in_use_are_refused() {
+    let table = [
+        partition("factory", 0x10000, 0x100000),
+        partition("spiffs", 0x110000, 0x2f0000),
+    ];
+    let result = benchmark::scratch_region(&table, Some(0x400000), 0x10000, None);
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("spiffs")));
+    // Bootloader, unaligned, past the end, odd size, unknown flash size
+    assert!(benchmark::scratch_region(&[], Some(0x400000), 0x1000, Some(0x1000)).is_err());
+    assert!(benchmark::scratch_region(&[], Some(0x400000), 0x1000, Some(0x200100)).is_err());
+    assert!(benchmark::scratch_region(&[], Some(0x400000), 0x2000, Some(0x3ff000)).is_err());
+    assert!(benchmark::scratch_region(&[], Some(0x400000), 100, None).is_err());
+    assert!(benchmark::scratch_region(&[], None, 0x1000, None).is_err());
+}
+
+#[test]
+fn fastest_verified_rate_is_recommended() {
+    let runs = [
+        run(115200, 11_000, true),
+        run(921600, 80_000, true),
+        run(2000000, 150_000, false),
+    ];
+    assert_eq!(benchmark::fastest(&runs).unwrap().baud_rate, 921600);
+    assert_eq!(
+        benchmark::summary(&runs),
+        "Fastest reliable rate: 921600 baud (78.1 KB/s write, 156.2 KB/s read); \
+         failed at 2000000"
+    );
+    assert_eq!(
+        benchmark::summary(&runs[2..]),
+        "No rate passed; failed at 2000000"
+    );
+}
@@ -0,0 +1,82 @@
+//! Sanity checks on the bundled board definitions, which are hand-written
+//! JSON and only parsed at runtime.
+
+use esp32dev_core::boards;
+use std::collections::HashSet;
+
+const CHIPS: [&str; 5] = ["esp32s3", "esp32c6", "esp32h2", "esp32c2", "esp32p4"];
+
+#[test]
+fn every_board_parses() {
+    for chip in CHIPS {
+        assert!(
+            boards::for_chip(chip).is_some(),
+            "no definition for {}",
+            chip
+        );
+    }
+    assert_eq!(boards::all().len(), CHIPS.len());
+}
+
+#[test]
+fn lookup_accepts_both_spellings() {
+    let definition = boards::for_chip("ESP32-S3").expect("ESP32-S3");
+    assert_eq!(definition.chip, "esp32s3");
+    assert!(boards::for_chip("esp8266").is_none());
+}
+
+#[test]
+fn pins_are_unique() {
+    for definition in boards::all() {
+        let mut ids = HashSet::new();
+        let mut numbers = HashSet::new();
+        for pin in &definition.pins {
+            assert!(
+                ids.insert(&pin.svg_id),
+                "{}: duplicate {}",
+                definition.board,
+                pin.svg_id
+            );
+            assert!(
+                numbers.insert(pin.number),
+                "{}: duplicate pin {}",
+                definition.board,
+                pin.number
+            );
+        }
+    }
+}
+
+#[test]
+fn reserved_pins_cover_strapping_usb_and_flash() {
+    let definition = boards::for_chip("esp32s3").expect("ESP32-S3");
+    let reserved = boards::reserved_pins(&definition);
+    let gpios = |kind: &str| {
+        reserved
+            .iter()
+            .filter(|pin| pin.kind == kind)
+            .map(|pin| pin.gpio)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(gpios("strapping"), [0, 3, 45, 46]);
+    assert_eq!(gpios("usb"), [19, 20]);
+    assert_eq!(gpios("flash"), [26, 27, 28, 29, 30, 31, 32]);
+    assert_eq!(gpios("psram"), [33, 34, 35, 36, 37]);
+    // Strapping pins come first, as the panel lists them
+    assert_eq!(reserved[0].kind, "strapping");
+}
+
+#[test]
+fn internal_pins_are_not_on_the_header() {
+    for definition in boards::all() {
+        let header: HashSet<_> = definition.pins.iter().filter_map(|p| p.gpio).collect();
+        for pin in &definition.internal {
+            assert!(
+                !header.contains(&pin.gpio),
+                "{}: GPIO{} is on the header",
+                definition.board,
+                pin.gpio
+            );
+        }
+    }
+}
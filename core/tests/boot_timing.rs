@@ -0,0 +1,54 @@
+use esp32dev_core::boot_timing::BootTimer;
+use std::time::{Duration, Instant};
+
+const ROM: &str = "ESP-ROM:esp32s3-20210327\r\nBuild:Mar 27 2021\r\n\
+    rst:0x1 (POWERON),boot:0x8 (SPI_FAST_FLASH_BOOT)\r\n";
+const BOOTLOADER: &str = "I (27) boot: ESP-IDF v5.1.2 2nd stage bootloader\r\n";
+const APP_MAIN: &str = "I (301) main_task: Calling app_main()\r\n";
+
+#[test]
+fn milestones_are_timed_from_the_rom_banner() {
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut timer = BootTimer::new(None).unwrap();
+
+    assert!(timer.push(ROM.as_bytes(), at(0)).is_none());
+    assert!(timer.push(BOOTLOADER.as_bytes(), at(40)).is_none());
+    // Split mid-line: the line counts when it is complete
+    assert!(timer.push(b"I (301) main_task: Call", at(300)).is_none());
+    let timing = timer.push(b"ing app_main()\r\n", at(320)).unwrap();
+    assert_eq!(timing.bootloader_ms, Some(40));
+    assert_eq!(timing.app_main_ms, Some(320));
+    assert_eq!(timing.marker_ms, None);
+
+    // Reported once per boot
+    assert!(timer.push(APP_MAIN.as_bytes(), at(400)).is_none());
+    // The next boot starts over
+    timer.push(ROM.as_bytes(), at(1000));
+    let timing = timer.push(APP_MAIN.as_bytes(), at(1250)).unwrap();
+    assert_eq!(timing.app_main_ms, Some(250));
+    assert_eq!(timing.bootloader_ms, None);
+}
+
+#[test]
+fn a_marker_ends_the_boot_instead_of_app_main() {
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut timer = BootTimer::new(Some("WiFi connected")).unwrap();
+
+    timer.push(ROM.as_bytes(), at(0));
+    timer.push(BOOTLOADER.as_bytes(), at(35));
+    assert!(timer.push(APP_MAIN.as_bytes(), at(310)).is_none());
+    let timing = timer
+        .push(b"WiFi connected, ip 10.0.0.7\n", at(2100))
+        .unwrap();
+    assert_eq!(timing.bootloader_ms, Some(35));
+    assert_eq!(timing.app_main_ms, Some(310));
+    assert_eq!(timing.marker_ms, Some(2100));
+}
+
+#[test]
+fn output_before_any_banner_is_ignored() {
+    let mut timer = BootTimer::new(None).unwrap();
+    assert!(timer.push(APP_MAIN.as_bytes(), Instant::now()).is_none());
+}
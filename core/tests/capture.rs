@@ -0,0 +1,88 @@
+use esp32dev_core::capture::{self, CaptureEvent, Direction};
+use esp32dev_core::models::AppError;
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::tasks::TaskManager;
+use std::time::{Duration, Instant};
+
+fn event(at_ms: u64, direction: Direction, data: &[u8]) -> CaptureEvent {
+    CaptureEvent {
+        at_ms,
+        direction,
+        data: data.to_vec(),
+    }
+}
+
+#[test]
+fn lines_round_trip() {
+    let events = [
+        event(0, Direction::Rx, b"boot\r\n"),
+        event(1532, Direction::Tx, b"help\r\n"),
+        event(1600, Direction::Rx, &[0x00, 0xff]),
+    ];
+    let text: String = events.iter().map(|e| e.to_line() + "\n").collect();
+    assert!(text.starts_with("0 rx 626f6f740d0a\n"));
+    assert_eq!(capture::parse(&text).unwrap(), events);
+}
+
+#[test]
+fn comments_are_skipped_and_bad_lines_named() {
+    let events = capture::parse("# esp32dev capture v1\n\n10 tx 41\n").unwrap();
+    assert_eq!(events, [event(10, Direction::Tx, b"A")]);
+
+    let result = capture::parse("10 rx 41\n20 rx 4\n");
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("line 2")));
+    assert!(capture::parse("10 up 41\n").is_err());
+}
+
+#[test]
+fn monitor_records_received_bytes() {
+    let path = std::env::temp_dir().join(format!("esp32dev-{}-capture", std::process::id()));
+    let monitor = Monitor::default();
+    monitor.start_capture(&path).unwrap();
+    assert_eq!(monitor.capture_path().as_deref(), Some(path.as_path()));
+    monitor.output(b"ready\n");
+
+    let (saved, count) = monitor.stop_capture().unwrap().unwrap();
+    assert_eq!((saved.as_path(), count), (path.as_path(), 1));
+    assert!(monitor.capture_path().is_none());
+    let events = capture::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].direction, Direction::Rx);
+    assert_eq!(events[0].data, b"ready\n");
+}
+
+#[test]
+fn replay_keeps_the_spacing_scaled_by_speed() {
+    let tasks = TaskManager::default();
+    let task = tasks.start("replay", None);
+    let events = [
+        event(1000, Direction::Rx, b"a"),
+        event(1200, Direction::Rx, b"b"),
+    ];
+    let start = Instant::now();
+    let mut played = Vec::new();
+    let count = capture::replay(&events, 2.0, &task, |e| {
+        played.push((e.data.clone(), start.elapsed()));
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(count, 2);
+    // The first event plays at once, the second 200 ms / 2 later
+    assert!(played[0].1 < Duration::from_millis(50));
+    assert!(played[1].1 >= Duration::from_millis(100));
+    assert!(played[1].1 < Duration::from_millis(1000));
+}
+
+#[test]
+fn cancelled_replays_stop() {
+    let tasks = TaskManager::default();
+    let task = tasks.start("replay", None);
+    tasks.cancel(task.info().unwrap().id).unwrap();
+    let events = [event(0, Direction::Rx, b"a")];
+    let result = capture::replay(&events, 1.0, &task, |_| Ok(()));
+    assert!(matches!(result, Err(AppError::Cancelled)));
+    let result = capture::replay(&events, 0.0, &task, |_| Ok(()));
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+}
@@ -0,0 +1,14 @@
+use esp32dev_core::checksum::crc32_le;
+
+#[test]
+fn crc_from_zero_matches_zlib() {
+    assert_eq!(crc32_le(0, b"123456789"), 0xcbf4_3926);
+    assert_eq!(crc32_le(0, b""), 0);
+}
+
+#[test]
+fn crc_from_all_ones_matches_the_rom() {
+    // What the bootloader computes for otadata sequence number 1
+    assert_eq!(crc32_le(u32::MAX, &1u32.to_le_bytes()), 0x4743_989a);
+    assert_eq!(crc32_le(u32::MAX, b""), u32::MAX);
+}
@@ -0,0 +1,120 @@
+//! CIRCUITPY drives are plain directories here: a fake drive with a
+//! `boot_out.txt` and a project folder next to it.
+
+mod common;
+
+use common::scratch;
+use esp32dev_core::circuitpython::{self, SyncWatch};
+use esp32dev_core::models::CircuitPyDrive;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+const BOOT_OUT: &str = "Adafruit CircuitPython 9.0.0 on 2024-03-19; Adafruit Feather ESP32-S3 No PSRAM with ESP32S3\r\nBoard ID:adafruit_feather_esp32s3_nopsram\r\nUUID:7CDFA1E2B3C4\r\n";
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn drive(uuid: Option<&str>) -> CircuitPyDrive {
+    CircuitPyDrive {
+        path: format!("/media/me/{}", uuid.unwrap_or("OLD")),
+        version: "8.2.0".to_string(),
+        board: String::new(),
+        board_id: None,
+        uuid: uuid.map(str::to_string),
+    }
+}
+
+#[test]
+fn boot_out_describes_the_drive() {
+    let root = scratch("circuitpy-drive");
+    assert_eq!(circuitpython::drive_at(&root), None);
+    write(&root.join("boot_out.txt"), BOOT_OUT);
+    let found = circuitpython::drive_at(&root).unwrap();
+    assert_eq!(found.version, "9.0.0");
+    assert_eq!(
+        found.board,
+        "Adafruit Feather ESP32-S3 No PSRAM with ESP32S3"
+    );
+    assert_eq!(
+        found.board_id.as_deref(),
+        Some("adafruit_feather_esp32s3_nopsram")
+    );
+    assert_eq!(found.uuid.as_deref(), Some("7CDFA1E2B3C4"));
+    std::fs::remove_dir_all(&root).ok();
+}
+
+#[test]
+fn drives_are_matched_by_serial_number() {
+    let two = || vec![drive(Some("AAAA")), drive(Some("7CDFA1E2B3C4"))];
+    let found = circuitpython::pick(two(), Some("7cdfa1e2b3c4")).unwrap();
+    assert_eq!(found.uuid.as_deref(), Some("7CDFA1E2B3C4"));
+    // Another board's drive is not this board's
+    assert_eq!(circuitpython::pick(two(), Some("BBBB")), None);
+    assert_eq!(
+        circuitpython::pick(vec![drive(Some("AAAA"))], Some("BBBB")),
+        None
+    );
+    // Nothing to tell them apart by
+    assert_eq!(circuitpython::pick(two(), None), None);
+    assert!(circuitpython::pick(vec![drive(None)], Some("BBBB")).is_some());
+}
+
+#[test]
+fn sync_copies_only_changed_project_files() {
+    let project = scratch("circuitpy-project");
+    let root = scratch("circuitpy-target");
+    write(&project.join("code.py"), "print('hi')\n");
+    write(&project.join("lib/sensor/__init__.py"), "X = 1\n");
+    write(&project.join("lib/__pycache__/junk.mpy"), "");
+    write(&project.join(".git/HEAD"), "ref: main\n");
+    write(&project.join("notes.txt"), "not for the board\n");
+
+    assert_eq!(
+        circuitpython::sync(&project, &root).unwrap(),
+        ["code.py", "lib/sensor/__init__.py"]
+    );
+    assert!(!root.join("notes.txt").exists());
+    assert!(!root.join("lib/__pycache__").exists());
+    assert!(circuitpython::sync(&project, &root).unwrap().is_empty());
+
+    write(&project.join("code.py"), "print('bye')\n");
+    assert_eq!(circuitpython::sync(&project, &root).unwrap(), ["code.py"]);
+    assert_eq!(
+        std::fs::read_to_string(root.join("code.py")).unwrap(),
+        "print('bye')\n"
+    );
+
+    let empty = scratch("circuitpy-empty");
+    assert!(circuitpython::sync(&empty, &root).is_err());
+    for dir in [project, root, empty] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[test]
+fn watch_copies_edits() {
+    let project = scratch("circuitpy-watched");
+    let root = scratch("circuitpy-watched-drive");
+    write(&project.join("code.py"), "v = 1\n");
+    let (tx, rx) = mpsc::channel();
+    let watch = SyncWatch::start(project.clone(), root.clone(), move |result| {
+        let _ = tx.send(result.unwrap());
+    });
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        ["code.py"]
+    );
+
+    write(&project.join("boot.py"), "import storage\n");
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        ["boot.py"]
+    );
+    watch.stop();
+    for dir in [project, root] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,11 @@
+//! Helpers shared by the integration tests.
+
+use std::path::PathBuf;
+
+/// An empty directory for the test `name`, under the system temp dir.
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("esp32dev-{}-{}", std::process::id(), name));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,112 @@
+use esp32dev_core::config_bundle::{parse, shareable_settings, ConfigBundle, CONFIG_FORMAT};
+use esp32dev_core::models::{
+    AppError, DesktopNotifications, DevicePrefs, FlashSettings, KioskSettings, Settings,
+    TextEncoding,
+};
+use std::collections::HashMap;
+
+#[test]
+fn bundles_round_trip() {
+    let settings = Settings {
+        theme: "light".to_string(),
+        flash: FlashSettings {
+            baud_rate: 921600,
+            ..FlashSettings::default()
+        },
+        ..Settings::default()
+    };
+    let prefs = HashMap::from([(
+        "A1B2C3".to_string(),
+        DevicePrefs {
+            port_name: "/dev/ttyUSB0".to_string(),
+            baud_rate: 74880,
+            boot_marker: Some("app_main".to_string()),
+            local_echo: Some(false),
+            encoding: Some(TextEncoding::Gbk),
+        },
+    )]);
+
+    let bundle = parse(&ConfigBundle::new(settings, prefs).to_json()).unwrap();
+    assert_eq!(bundle.format, CONFIG_FORMAT);
+    assert_eq!(bundle.settings.theme, "light");
+    assert_eq!(bundle.settings.flash.baud_rate, 921600);
+    assert_eq!(bundle.device_prefs["A1B2C3"].baud_rate, 74880);
+    assert_eq!(
+        bundle.device_prefs["A1B2C3"].encoding,
+        Some(TextEncoding::Gbk)
+    );
+}
+
+#[test]
+fn missing_fields_take_the_defaults() {
+    let bundle = parse(r#"{"format": 1, "settings": {"theme": "light"}}"#).unwrap();
+    assert_eq!(bundle.settings.theme, "light");
+    assert_eq!(
+        bundle.settings.default_baud,
+        Settings::default().default_baud
+    );
+    assert!(bundle.device_prefs.is_empty());
+}
+
+#[test]
+fn partial_notification_settings_keep_the_other_defaults() {
+    let bundle = parse(
+        r#"{"format": 1, "settings": {"desktop_notifications": {"device_connected": true}}}"#,
+    )
+    .unwrap();
+    let wanted = bundle.settings.desktop_notifications;
+    assert!(wanted.device_connected);
+    assert_eq!(
+        wanted.flash_failed,
+        DesktopNotifications::default().flash_failed
+    );
+}
+
+#[test]
+fn newer_or_foreign_files_are_refused() {
+    let newer = format!(r#"{{"format": {}, "settings": {{}}}}"#, CONFIG_FORMAT + 1);
+    assert!(matches!(parse(&newer), Err(AppError::Config(_))));
+    assert!(matches!(parse("[1, 2]"), Err(AppError::Config(_))));
+}
+
+#[test]
+fn production_mode_is_neither_exported_nor_imported() {
+    let kiosk = |enabled: bool, golden_hash: &str| KioskSettings {
+        enabled,
+        golden_hash: golden_hash.to_string(),
+        password_hash: "salt$hash".to_string(),
+        ..KioskSettings::default()
+    };
+    let exported = Settings {
+        kiosk: kiosk(true, "aaaa"),
+        ..Settings::default()
+    };
+    let json = ConfigBundle::new(exported.clone(), HashMap::new()).to_json();
+    assert!(!json.contains("goldenHash") && !json.contains("golden_hash"));
+    // Nor does it go into diagnostics bundles
+    let shared = shareable_settings(&exported);
+    assert!(shared.get("kiosk").is_none());
+    assert!(shared.get("theme").is_some());
+
+    // A bundle edited to carry a kiosk block doesn't change the bench's
+    let mut bundle = parse(&json).unwrap();
+    bundle.settings.theme = "light".to_string();
+    bundle.settings.kiosk = kiosk(false, "bbbb");
+    let current = Settings {
+        kiosk: kiosk(false, "cccc"),
+        ..Settings::default()
+    };
+    let imported = bundle.settings_over(&current).unwrap();
+    assert_eq!(imported.theme, "light");
+    assert_eq!(imported.kiosk.golden_hash, "cccc");
+    assert_eq!(imported.kiosk.password_hash, "salt$hash");
+
+    let locked = Settings {
+        kiosk: kiosk(true, "cccc"),
+        ..Settings::default()
+    };
+    assert!(matches!(
+        bundle.settings_over(&locked),
+        Err(AppError::InvalidInput(_))
+    ));
+}
@@ -0,0 +1,355 @@
+use esp32dev_core::coredump::{self, CoreFrame};
+use esp32dev_core::models::AppError;
+use sha2::{Digest, Sha256};
+
+const EM_XTENSA: u16 = 94;
+const EM_RISCV: u16 = 243;
+const MAIN_TCB: u32 = 0x3ffb_0000;
+const IDLE_TCB: u32 = 0x3ffb_2000;
+
+/// An app ELF whose only contents are function symbols.
+fn app_elf(functions: &[(&str, u32, u32)]) -> Vec<u8> {
+    let mut strtab = vec![0u8];
+    let mut symtab = vec![0u8; 16];
+    for (name, address, size) in functions {
+        symtab.extend((strtab.len() as u32).to_le_bytes());
+        symtab.extend(address.to_le_bytes());
+        symtab.extend(size.to_le_bytes());
+        // Global function, in some section
+        symtab.extend([0x12, 0, 1, 0]);
+        strtab.extend(name.as_bytes());
+        strtab.push(0);
+    }
+    let mut file = vec![0u8; 52];
+    file[..6].copy_from_slice(b"\x7fELF\x01\x01");
+    let symtab_at = file.len();
+    file.extend(&symtab);
+    let strtab_at = file.len();
+    file.extend(&strtab);
+    let headers_at = file.len();
+    // type, offset, size, link
+    for (kind, offset, size, link) in [
+        (0u32, 0, 0, 0u32),
+        (2, symtab_at, symtab.len(), 2),
+        (3, strtab_at, strtab.len(), 0),
+    ] {
+        let mut header = [0u8; 40];
+        header[4..8].copy_from_slice(&kind.to_le_bytes());
+        header[16..20].copy_from_slice(&(offset as u32).to_le_bytes());
+        header[20..24].copy_from_slice(&(size as u32).to_le_bytes());
+        header[24..28].copy_from_slice(&link.to_le_bytes());
+        file.extend(header);
+    }
+    file[32..36].copy_from_slice(&(headers_at as u32).to_le_bytes());
+    file[46..48].copy_from_slice(&40u16.to_le_bytes());
+    file[48..50].copy_from_slice(&3u16.to_le_bytes());
+    file
+}
+
+fn app() -> Vec<u8> {
+    app_elf(&[
+        ("app_main", 0x400d_1000, 0x40),
+        ("do_crash", 0x400d_1100, 0x20),
+        ("main_task", 0x400d_2000, 0x80),
+        ("vTaskDelay", 0x4008_2000, 0x30),
+    ])
+}
+
+fn note(name: &str, kind: u32, desc: &[u8]) -> Vec<u8> {
+    let pad = |data: &mut Vec<u8>| data.resize((data.len() + 3) & !3, 0);
+    let mut note = Vec::new();
+    note.extend((name.len() as u32 + 1).to_le_bytes());
+    note.extend((desc.len() as u32).to_le_bytes());
+    note.extend(kind.to_le_bytes());
+    note.extend(name.as_bytes());
+    note.push(0);
+    pad(&mut note);
+    note.extend(desc);
+    pad(&mut note);
+    note
+}
+
+/// A task's `NT_PRSTATUS` note: the TCB as its pid, then the registers.
+fn task(tcb: u32, registers: &[u32]) -> Vec<u8> {
+    let mut desc = vec![0u8; 72];
+    desc[24..28].copy_from_slice(&tcb.to_le_bytes());
+    for value in registers {
+        desc.extend(value.to_le_bytes());
+    }
+    note("CORE", 1, &desc)
+}
+
+fn words(words: &[(usize, u32)], len: usize) -> Vec<u8> {
+    let mut data = vec![0u8; len];
+    for &(at, value) in words {
+        data[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    data
+}
+
+fn tcb(name: &str) -> Vec<u8> {
+    let mut tcb = vec![0u8; 0x60];
+    tcb[52..52 + name.len()].copy_from_slice(name.as_bytes());
+    tcb
+}
+
+/// A core ELF with one note segment and memory `segments`.
+fn core_elf(machine: u16, notes: &[Vec<u8>], segments: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let notes = notes.concat();
+    let count = 1 + segments.len();
+    let mut file = vec![0u8; 52];
+    file[..6].copy_from_slice(b"\x7fELF\x01\x01");
+    file[16..18].copy_from_slice(&4u16.to_le_bytes());
+    file[18..20].copy_from_slice(&machine.to_le_bytes());
+    file[28..32].copy_from_slice(&52u32.to_le_bytes());
+    file[42..44].copy_from_slice(&32u16.to_le_bytes());
+    file[44..46].copy_from_slice(&(count as u16).to_le_bytes());
+
+    let mut contents = Vec::new();
+    let mut at = 52 + count * 32;
+    let mut header = |kind: u32, address: u32, data: &[u8]| {
+        let mut entry = [0u8; 32];
+        entry[0..4].copy_from_slice(&kind.to_le_bytes());
+        entry[4..8].copy_from_slice(&(at as u32).to_le_bytes());
+        entry[8..12].copy_from_slice(&address.to_le_bytes());
+        entry[16..20].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        entry[20..24].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        at += data.len();
+        contents.extend_from_slice(data);
+        entry
+    };
+    let mut headers = header(4, 0, &notes).to_vec();
+    for (address, data) in segments {
+        headers.extend(header(1, *address, data));
+    }
+    file.extend(headers);
+    file.extend(contents);
+    file
+}
+
+/// `main` crashed in `do_crash`, called by `app_main` from `main_task`;
+/// `IDLE` waits in `vTaskDelay`. IDLE is dumped first.
+fn xtensa_core(elf_sha256: &str) -> Vec<u8> {
+    let mut main = vec![0u32; 128];
+    main[0] = 0x400d_1110;
+    // a0 carries the caller's window increment in its top bits
+    main[64] = 0x800d_1020;
+    main[65] = 0x3ffb_1040;
+    let mut idle = vec![0u32; 128];
+    idle[0] = 0x4008_2008;
+    idle[65] = 0x3ffb_3010;
+
+    let mut extra = MAIN_TCB.to_le_bytes().to_vec();
+    for (index, value) in [(232u32, 29u32), (238, 0), (177, 0x400d_1110), (0, 0)] {
+        extra.extend(index.to_le_bytes());
+        extra.extend(value.to_le_bytes());
+    }
+    let mut info = 0x0002_0201u32.to_le_bytes().to_vec();
+    info.extend(elf_sha256.as_bytes());
+    info.push(0);
+    core_elf(
+        EM_XTENSA,
+        &[
+            note("ESP_CORE_DUMP_INFO", 8266, &info),
+            task(IDLE_TCB, &idle),
+            task(MAIN_TCB, &main),
+            note("EXTRA_INFO", 677, &extra),
+            note(
+                "ESP_PANIC_DETAILS",
+                1,
+                b"Guru Meditation Error: Core  0 panic'ed (StoreProhibited).\0",
+            ),
+        ],
+        &[
+            (MAIN_TCB, tcb("main")),
+            // app_main's caller: its return address and stack pointer
+            // below app_main's stack pointer, then the end of the chain
+            (
+                0x3ffb_1000,
+                words(&[(0x30, 0x800d_2050), (0x34, 0x3ffb_1080)], 0x100),
+            ),
+            (IDLE_TCB, tcb("IDLE")),
+            (0x3ffb_3000, vec![0; 0x40]),
+        ],
+    )
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The core ELF as ESP-IDF stores it: a header, then the ELF, then a CRC.
+fn partition(elf: &[u8]) -> Vec<u8> {
+    let mut data = ((24 + elf.len() + 4) as u32).to_le_bytes().to_vec();
+    for word in [0x0002_0201u32, 2, 0x5c, 4, 3] {
+        data.extend(word.to_le_bytes());
+    }
+    data.extend(elf);
+    data.extend([0x12, 0x34, 0x56, 0x78]);
+    data
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::new();
+    for chunk in data.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, &b)| bits | (u32::from(b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn names(frames: &[CoreFrame]) -> Vec<Option<&str>> {
+    frames.iter().map(|f| f.function.as_deref()).collect()
+}
+
+#[test]
+fn dumps_come_out_of_uart_output_and_partitions() {
+    let elf = xtensa_core("");
+    let encoded = base64(&partition(&elf));
+    let mut output = String::from("E (1234) esp_core_dump_uart: Print core dump to uart...\r\n");
+    output.push_str("================= CORE DUMP START =================\r\n");
+    for line in encoded.as_bytes().chunks(76) {
+        output.push_str(std::str::from_utf8(line).unwrap());
+        output.push_str("\r\n");
+    }
+    output.push_str("================= CORE DUMP END =================\r\n");
+    output.push_str("Rebooting...\r\n");
+
+    assert_eq!(coredump::extract(output.as_bytes()).unwrap(), elf);
+    assert_eq!(coredump::extract(&partition(&elf)).unwrap(), elf);
+    assert_eq!(coredump::extract(encoded.as_bytes()).unwrap(), elf);
+    assert_eq!(coredump::extract(&elf).unwrap(), elf);
+}
+
+#[test]
+fn empty_cut_and_binary_dumps_are_refused() {
+    let empty = vec![0xff; 4096];
+    assert!(matches!(
+        coredump::extract(&empty),
+        Err(AppError::InvalidInput(m)) if m.contains("empty")
+    ));
+    let cut = "==== CORE DUMP START ====\nf0VMRgEB\n";
+    assert!(coredump::extract(cut.as_bytes()).is_err());
+    // The legacy binary format has no ELF behind its header
+    let binary = [20u32, 0x0002_0002, 2, 0x5c, 4]
+        .iter()
+        .flat_map(|w| w.to_le_bytes())
+        .collect::<Vec<u8>>();
+    assert!(matches!(
+        coredump::extract(&binary),
+        Err(AppError::InvalidInput(m)) if m.contains("FORMAT_ELF")
+    ));
+}
+
+#[test]
+fn xtensa_crash_is_unwound_and_named() {
+    let app = app();
+    let report = coredump::analyze(&xtensa_core(&sha256_hex(&app)[..16]), &app).unwrap();
+    assert_eq!(report.arch, "xtensa");
+    assert_eq!(report.elf_matches, Some(true));
+    assert_eq!(
+        report.panic_reason.as_deref(),
+        Some("Guru Meditation Error: Core  0 panic'ed (StoreProhibited).")
+    );
+    let cause = &report.exception[0];
+    assert_eq!(
+        (cause.name.as_str(), cause.value, cause.meaning.as_deref()),
+        ("exccause", 29, Some("StoreProhibited"))
+    );
+    assert_eq!(report.exception[2].name, "epc1");
+    assert_eq!(report.exception.len(), 3);
+
+    let main = &report.tasks[0];
+    assert_eq!((main.name.as_str(), main.crashed), ("main", true));
+    assert_eq!(
+        names(&main.frames),
+        [
+            Some("do_crash+0x10"),
+            Some("app_main+0x1d"),
+            Some("main_task+0x4d")
+        ]
+    );
+    assert_eq!(main.frames[1].address, 0x400d_101d);
+    assert_eq!(main.stack_used, Some(0xc0));
+    assert!(main
+        .registers
+        .iter()
+        .any(|r| r.name == "a1" && r.value == 0x3ffb_1040));
+
+    let idle = &report.tasks[1];
+    assert_eq!((idle.name.as_str(), idle.crashed), ("IDLE", false));
+    assert_eq!(names(&idle.frames), [Some("vTaskDelay+0x8")]);
+    assert_eq!(report.regions.len(), 4);
+
+    let text = report.to_text();
+    assert!(text.contains("Crashed task: 'main' (TCB 0x3ffb0000)"));
+    assert!(text.contains("exccause       0x0000001d (StoreProhibited)"));
+    assert!(text.contains("#1  0x400d101d in app_main+0x1d"));
+    assert!(!text.contains("WARNING"));
+}
+
+#[test]
+fn another_builds_elf_is_flagged() {
+    let report = coredump::analyze(&xtensa_core("0123456789abcdef"), &app()).unwrap();
+    assert_eq!(report.elf_matches, Some(false));
+    assert!(report.to_text().contains("WARNING"));
+}
+
+#[test]
+fn riscv_has_pc_ra_and_stack_guesses() {
+    let mut registers = vec![0u32; 32];
+    registers[0] = 0x400d_1104;
+    registers[1] = 0x400d_1010;
+    registers[2] = 0x3ffb_1000;
+    let core = core_elf(
+        EM_RISCV,
+        &[task(MAIN_TCB, &registers)],
+        &[
+            (MAIN_TCB, tcb("main")),
+            (
+                0x3ffb_1000,
+                words(
+                    &[(0x04, 0x400d_1010), (0x08, 0x1234), (0x0c, 0x400d_2060)],
+                    0x20,
+                ),
+            ),
+        ],
+    );
+    let report = coredump::analyze(&core, &app()).unwrap();
+    assert_eq!(report.arch, "riscv");
+    let main = &report.tasks[0];
+    // Without extra info the first task is the crashed one
+    assert!(main.crashed);
+    assert_eq!(
+        names(&main.frames),
+        [
+            Some("do_crash+0x4"),
+            Some("app_main+0xf"),
+            Some("main_task+0x5f")
+        ]
+    );
+    assert!(main.frames[2].guessed && !main.frames[1].guessed);
+    assert!(main
+        .registers
+        .iter()
+        .any(|r| r.name == "sp" && r.value == 0x3ffb_1000));
+}
+
+#[test]
+fn stripped_elfs_are_refused() {
+    let app = app_elf(&[]);
+    assert!(coredump::analyze(&xtensa_core(""), &app).is_err());
+}
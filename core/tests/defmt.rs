@@ -0,0 +1,228 @@
+use esp32dev_core::defmt::{self, Chunk, DefmtStream, Table};
+use std::sync::Arc;
+
+/// A 32-bit little-endian ELF with a `.defmt` section holding `strings` at
+/// their indices, the way the defmt linker script lays them out.
+fn elf(strings: &[(u16, &str, &str)]) -> Vec<u8> {
+    let mut strtab = vec![0u8];
+    let mut symtab = vec![0u8; 16];
+    for (index, tag, data) in strings {
+        let name = format!(
+            r#"{{"package":"app","tag":"{}","data":"{}","disambiguator":"1","crate_name":"app"}}"#,
+            tag, data
+        );
+        symtab.extend((strtab.len() as u32).to_le_bytes());
+        symtab.extend(u32::from(*index).to_le_bytes());
+        symtab.extend(1u32.to_le_bytes());
+        symtab.extend([0, 0]);
+        // Section 1 is `.defmt`
+        symtab.extend(1u16.to_le_bytes());
+        strtab.extend(name.as_bytes());
+        strtab.push(0);
+    }
+    let shstrtab = b"\0.defmt\0.symtab\0.strtab\0.shstrtab\0".to_vec();
+
+    let mut file = vec![0u8; 52];
+    file[..7].copy_from_slice(b"\x7fELF\x01\x01\x01");
+    let symtab_at = file.len();
+    file.extend(&symtab);
+    let strtab_at = file.len();
+    file.extend(&strtab);
+    let shstrtab_at = file.len();
+    file.extend(&shstrtab);
+    let headers_at = file.len();
+    // name, type, offset, size, link
+    let sections: [(u32, u32, usize, usize, u32); 5] = [
+        (0, 0, 0, 0, 0),
+        (1, 1, 0, 0, 0),
+        (8, 2, symtab_at, symtab.len(), 3),
+        (16, 3, strtab_at, strtab.len(), 0),
+        (24, 3, shstrtab_at, shstrtab.len(), 0),
+    ];
+    for (name, kind, offset, size, link) in sections {
+        let mut header = [0u8; 40];
+        header[0..4].copy_from_slice(&name.to_le_bytes());
+        header[4..8].copy_from_slice(&kind.to_le_bytes());
+        header[16..20].copy_from_slice(&(offset as u32).to_le_bytes());
+        header[20..24].copy_from_slice(&(size as u32).to_le_bytes());
+        header[24..28].copy_from_slice(&link.to_le_bytes());
+        file.extend(header);
+    }
+    file[32..36].copy_from_slice(&(headers_at as u32).to_le_bytes());
+    file[46..48].copy_from_slice(&40u16.to_le_bytes());
+    file[48..50].copy_from_slice(&5u16.to_le_bytes());
+    file[50..52].copy_from_slice(&4u16.to_le_bytes());
+    file
+}
+
+/// defmt's rzCOBS encoder, frame terminator included.
+fn rzcobs(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let (mut run, mut zeros) = (0u8, 0u8);
+    for &byte in data {
+        if run < 7 {
+            if byte == 0 {
+                zeros |= 1 << run;
+            } else {
+                out.push(byte);
+            }
+            run += 1;
+            if run == 7 && zeros != 0 {
+                out.push(zeros);
+                run = 0;
+                zeros = 0;
+            }
+        } else if byte == 0 {
+            out.push((run - 7) | 0x80);
+            run = 0;
+            zeros = 0;
+        } else {
+            out.push(byte);
+            run += 1;
+            if run == 134 {
+                out.push(0xFF);
+                run = 0;
+                zeros = 0;
+            }
+        }
+    }
+    match run {
+        0 => {}
+        1..=6 => out.push((zeros | (0xFF << run)) & 0x7F),
+        _ => out.push((run - 7) | 0x80),
+    }
+    out.push(0);
+    out
+}
+
+fn table() -> Table {
+    Table::parse(&elf(&[
+        (1, "defmt_info", "booted in {=u32} ms, heap {=usize:#x}"),
+        (2, "defmt_warn", "sensor {=str} said {=[u8]:#04x}"),
+        (3, "defmt_error", "state {}"),
+        (4, "defmt_derived", "Idle|Busy({=u8})|Failed"),
+        (5, "defmt_println", "{0=istr} flags {1=0..4} {1=4..8:b}"),
+        (6, "defmt_str", "radio"),
+        (7, "defmt_debug", "temperature {=f32}, delta {=i16}"),
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn rzcobs_round_trips() {
+    for data in [
+        &[0x01][..],
+        &[0x00, 0x01],
+        &[0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88],
+        &[0, 0, 0, 0, 0, 0x44, 0, 0, 0, 0, 0, 0, 0, 0xFF],
+        &[0xAB; 300],
+    ] {
+        let encoded = rzcobs(data);
+        let decoded = defmt::rzcobs_decode(&encoded[..encoded.len() - 1]).unwrap();
+        // Padding of the last group comes back as zeros
+        assert_eq!(&decoded[..data.len()], data);
+        assert!(decoded[data.len()..].iter().all(|&b| b == 0));
+    }
+}
+
+#[test]
+fn frames_become_log_lines() {
+    let table = table();
+    let mut frame = vec![1, 0];
+    frame.extend(1234u32.to_le_bytes());
+    frame.extend(0x3ffc_0000u32.to_le_bytes());
+    assert_eq!(
+        table.decode(&frame).as_deref(),
+        Some("[INFO ] booted in 1234 ms, heap 0x3ffc0000")
+    );
+
+    let mut frame = vec![2, 0];
+    frame.extend(3u32.to_le_bytes());
+    frame.extend(b"bme");
+    frame.extend(2u32.to_le_bytes());
+    frame.extend([0x0A, 0xFF]);
+    assert_eq!(
+        table.decode(&frame).as_deref(),
+        Some("[WARN ] sensor bme said [0x0a, 0xff]")
+    );
+
+    let mut frame = vec![7, 0];
+    frame.extend(21.5f32.to_bits().to_le_bytes());
+    frame.extend((-3i16).to_le_bytes());
+    assert_eq!(
+        table.decode(&frame).as_deref(),
+        Some("[DEBUG] temperature 21.5, delta -3")
+    );
+}
+
+#[test]
+fn derived_enums_interned_strings_and_bitfields() {
+    let table = table();
+    // `Busy(7)`: the enum's string, the variant, its field
+    assert_eq!(
+        table.decode(&[3, 0, 4, 0, 1, 7]).as_deref(),
+        Some("[ERROR] state Busy(7)")
+    );
+    assert_eq!(
+        table.decode(&[3, 0, 4, 0, 2]).as_deref(),
+        Some("[ERROR] state Failed")
+    );
+    assert_eq!(
+        table.decode(&[5, 0, 6, 0, 0xA3]).as_deref(),
+        Some("radio flags 3 1010")
+    );
+    // Cut short, or not from this ELF
+    assert_eq!(table.decode(&[1, 0, 1]), None);
+    assert_eq!(table.decode(&[9, 0]), None);
+}
+
+#[test]
+fn timestamps_go_in_front() {
+    let table = Table::parse(&elf(&[
+        (1, "defmt_timestamp", "{=u64:us}"),
+        (2, "defmt_info", "tick"),
+    ]))
+    .unwrap();
+    let mut frame = vec![2, 0];
+    frame.extend(1_500_042u64.to_le_bytes());
+    assert_eq!(
+        table.decode(&frame).as_deref(),
+        Some("1.500042 [INFO ] tick")
+    );
+}
+
+#[test]
+fn frames_are_picked_out_of_text() {
+    let mut stream = DefmtStream::new(Arc::new(table()));
+    let mut wire = b"ESP-ROM:esp32c3\r\n".to_vec();
+    // The encoder's separator comes before its first frame only
+    wire.extend([0xFF, 0x00, 0x00]);
+    wire.extend(rzcobs(&[3, 0, 4, 0, 0]));
+    wire.extend(b"plain\n");
+    wire.extend([0xFF, 0x00]);
+    wire.extend(rzcobs(&[3, 0, 4, 0, 2]));
+
+    // Split mid-marker and mid-frame
+    let mut chunks = stream.feed(&wire[..18]);
+    chunks.extend(stream.feed(&wire[18..23]));
+    chunks.extend(stream.feed(&wire[23..]));
+    assert_eq!(
+        chunks,
+        [
+            Chunk::Text(b"ESP-ROM:esp32c3\r\n".to_vec()),
+            Chunk::Line("[ERROR] state Idle".to_string()),
+            Chunk::Text(b"plain\n".to_vec()),
+            Chunk::Line("[ERROR] state Failed".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn an_elf_without_defmt_is_refused() {
+    assert!(Table::parse(b"not an elf at all, just some text here to be long enough").is_err());
+    let mut file = elf(&[]);
+    // Rename `.defmt`
+    let at = file.windows(6).position(|w| w == b".defmt").unwrap();
+    file[at + 1] = b'x';
+    assert!(Table::parse(&file).is_err());
+}
@@ -0,0 +1,289 @@
+use esp32dev_core::device_db::{self, DeviceDb};
+use esp32dev_core::models::{AppError, DeviceRecord, FailureReason, RecordQuery};
+
+fn temp_db(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("esp32dev-{}-{}.db", std::process::id(), name));
+    std::fs::remove_file(&path).ok();
+    path
+}
+
+const MAC_A: &str = "f4:12:fa:01:02:03";
+const MAC_B: &str = "f4:12:fa:0a:0b:0c";
+const MAC_C: &str = "f4:12:fa:0d:0e:0f";
+
+fn flashed(at: u64, mac: &str, operator: &str, passed: bool) -> DeviceRecord {
+    let detail = if passed {
+        "Flashed 4096 bytes"
+    } else {
+        "Timed out"
+    };
+    DeviceRecord {
+        at,
+        action: "flash".to_string(),
+        mac_address: Some(mac.to_string()),
+        serial_number: Some("F4:12:FA:01:02:03".to_string()),
+        chip_model: Some("ESP32-S3".to_string()),
+        firmware_version: Some("1.2.0".to_string()),
+        checksum: Some("ab".repeat(32)),
+        operator: operator.to_string(),
+        passed,
+        detail: detail.to_string(),
+        ..DeviceRecord::default()
+    }
+}
+
+/// An ESP32-S3 app image whose descriptor says `version`.
+fn app_image(version: &str) -> Vec<u8> {
+    let mut desc = vec![0; 256];
+    desc[..4].copy_from_slice(&0xabcd_5432u32.to_le_bytes());
+    desc[16..16 + version.len()].copy_from_slice(version.as_bytes());
+    let mut data = vec![0xe9, 1, 0x02, 0x2f];
+    data.extend_from_slice(&0x4037_5a04u32.to_le_bytes());
+    data.extend_from_slice(&[0xee, 0, 0, 0]);
+    data.extend_from_slice(&9u16.to_le_bytes());
+    data.extend_from_slice(&[0, 0, 0, 99, 0, 0, 0, 0, 0, 0]);
+    data.extend_from_slice(&0x3c02_0020u32.to_le_bytes());
+    data.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+    let checksum = desc.iter().fold(0xef, |sum, b| sum ^ b);
+    data.extend_from_slice(&desc);
+    while data.len() % 16 != 15 {
+        data.push(0);
+    }
+    data.push(checksum);
+    data
+}
+
+#[test]
+fn records_survive_reopening_and_come_back_newest_first() {
+    let path = temp_db("devices-reopen");
+    {
+        let db = DeviceDb::open(&path).unwrap();
+        let first = db.record(&flashed(100, MAC_A, "Kim", true)).unwrap();
+        let second = db.record(&flashed(200, MAC_B, "Lee", false)).unwrap();
+        assert!(second > first);
+    }
+
+    let db = DeviceDb::open(&path).unwrap();
+    let all = db.query(&RecordQuery::default()).unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].at, 200);
+    assert!(!all[0].passed);
+    assert_eq!(
+        all[1],
+        DeviceRecord {
+            id: all[1].id,
+            ..flashed(100, MAC_A, "Kim", true)
+        }
+    );
+}
+
+#[test]
+fn queries_filter_by_device_operator_result_and_time() {
+    let db = DeviceDb::open(&temp_db("devices-query")).unwrap();
+    db.record(&flashed(100, MAC_A, "Kim", true)).unwrap();
+    db.record(&flashed(200, MAC_B, "Kim", false)).unwrap();
+    db.record(&flashed(300, MAC_B, "Lee", true)).unwrap();
+    db.record(&DeviceRecord {
+        at: 400,
+        action: "improv".to_string(),
+        operator: "Lee".to_string(),
+        passed: true,
+        detail: "http://192.168.1.40".to_string(),
+        ..DeviceRecord::default()
+    })
+    .unwrap();
+
+    let times = |query: RecordQuery| -> Vec<u64> {
+        db.query(&query).unwrap().iter().map(|r| r.at).collect()
+    };
+    let text = |t: &str| Some(t.to_string());
+    let any = RecordQuery::default;
+    // Part of the MAC, in any case
+    let device = text("0A:0B");
+    assert_eq!(times(RecordQuery { device, ..any() }), [300, 200]);
+    let operator = text(" kim ");
+    assert_eq!(times(RecordQuery { operator, ..any() }), [200, 100]);
+    let passed = Some(false);
+    assert_eq!(times(RecordQuery { passed, ..any() }), [200]);
+    let action = text("improv");
+    assert_eq!(times(RecordQuery { action, ..any() }), [400]);
+    let (since, until) = (Some(200), Some(300));
+    assert_eq!(
+        times(RecordQuery {
+            since,
+            until,
+            ..any()
+        }),
+        [300, 200]
+    );
+    let limit = Some(1);
+    assert_eq!(times(RecordQuery { limit, ..any() }), [400]);
+    // Blank filters match everything
+    let device = text("  ");
+    assert_eq!(times(RecordQuery { device, ..any() }).len(), 4);
+}
+
+#[test]
+fn firmware_version_comes_from_the_app_slot() {
+    let app = vec![(0x10000, app_image("1.2.0"))];
+    assert_eq!(device_db::firmware_version(&app).as_deref(), Some("1.2.0"));
+
+    // A merged image, with the partition table putting the app at 0x20000
+    let mut merged = vec![0xff; 0x20000];
+    let mut entry = vec![0xaa, 0x50, 0x00, 0x00];
+    entry.extend_from_slice(&0x20000u32.to_le_bytes());
+    entry.extend_from_slice(&0x100000u32.to_le_bytes());
+    entry.resize(32, 0);
+    merged[0x8000..0x8020].copy_from_slice(&entry);
+    merged.extend_from_slice(&app_image("2.0.1"));
+    let full = vec![(0x0, merged)];
+    assert_eq!(device_db::firmware_version(&full).as_deref(), Some("2.0.1"));
+
+    assert_eq!(device_db::firmware_version(&[(0x0, vec![0xff; 64])]), None);
+}
+
+#[test]
+fn checksum_covers_contents_and_addresses() {
+    let images = vec![(0x10000, app_image("1.2.0"))];
+    let checksum = device_db::checksum(&images);
+    assert_eq!(checksum.len(), 64);
+    assert_eq!(checksum, device_db::checksum(&images));
+    assert_ne!(
+        checksum,
+        device_db::checksum(&[(0x20000, app_image("1.2.0"))])
+    );
+    assert_ne!(
+        checksum,
+        device_db::checksum(&[(0x10000, app_image("1.2.1"))])
+    );
+}
+
+#[test]
+fn csv_export_has_a_row_per_record() {
+    let mut record = flashed(86_400 + 3_661, MAC_A, "Kim", false);
+    record.detail = "Timed out, retry".to_string();
+    record.golden_hash = Some("cd".repeat(32));
+    let csv = device_db::to_csv(&[record]);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], device_db::CSV_COLUMNS.join(","));
+    assert!(lines[1].starts_with("1970-01-02 01:01:01,flash,f4:12:fa:01:02:03,"));
+    let end = format!(",Kim,,fail,\"Timed out, retry\",,{}", "cd".repeat(32));
+    assert!(lines[1].ends_with(&end));
+}
+
+#[test]
+fn files_from_before_runs_are_migrated() {
+    let path = temp_db("devices-v1");
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE records (id INTEGER PRIMARY KEY AUTOINCREMENT, at INTEGER NOT NULL, \
+             action TEXT NOT NULL, mac_address TEXT, serial_number TEXT, chip_model TEXT, \
+             firmware_version TEXT, checksum TEXT, operator TEXT NOT NULL, \
+             passed INTEGER NOT NULL, detail TEXT NOT NULL);
+             INSERT INTO records (at, action, mac_address, operator, passed, detail) \
+             VALUES (100, 'flash', 'f4:12:fa:01:02:03', 'Kim', 1, 'ok');
+             PRAGMA user_version = 1;",
+        )
+        .unwrap();
+    }
+
+    let db = DeviceDb::open(&path).unwrap();
+    let old = &db.query(&RecordQuery::default()).unwrap()[0];
+    assert_eq!((old.run.as_str(), old.duration_ms), ("", None));
+    db.record(&DeviceRecord {
+        at: 200,
+        run: "B-0142".to_string(),
+        duration_ms: Some(8_200),
+        golden_hash: Some("cd".repeat(32)),
+        ..flashed(200, MAC_B, "Lee", true)
+    })
+    .unwrap();
+    let new = &db.query(&RecordQuery::default()).unwrap()[0];
+    assert_eq!((new.run.as_str(), new.duration_ms), ("B-0142", Some(8_200)));
+    assert_eq!(new.golden_hash, Some("cd".repeat(32)));
+    assert_eq!(old.golden_hash, None);
+}
+
+#[test]
+fn files_from_a_newer_version_are_refused() {
+    let path = temp_db("devices-newer");
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute_batch("PRAGMA user_version = 99;")
+        .unwrap();
+    assert!(matches!(
+        DeviceDb::open(&path),
+        Err(AppError::Io(m)) if m.contains("newer")
+    ));
+}
+
+fn in_run(run: &str, ms: u64, record: DeviceRecord) -> DeviceRecord {
+    DeviceRecord {
+        run: run.to_string(),
+        duration_ms: Some(ms),
+        ..record
+    }
+}
+
+#[test]
+fn runs_are_listed_and_summarized() {
+    let db = DeviceDb::open(&temp_db("devices-runs")).unwrap();
+    db.record(&flashed(50, MAC_A, "Kim", true)).unwrap();
+    // Board A fails once then passes, board B fails twice, C passes
+    for record in [
+        in_run("B-0142", 3_000, flashed(100, MAC_A, "Kim", false)),
+        in_run("B-0142", 8_000, flashed(200, MAC_A, "Kim", true)),
+        in_run("B-0142", 2_000, flashed(300, MAC_B, "Lee", false)),
+        in_run("B-0142", 2_500, flashed(400, MAC_B, "Lee", false)),
+        in_run("B-0142", 10_000, flashed(500, MAC_C, "Lee", true)),
+        in_run("B-0143", 9_000, flashed(600, MAC_A, "Lee", true)),
+    ] {
+        db.record(&record).unwrap();
+    }
+
+    let runs = db.runs().unwrap();
+    let names: Vec<&str> = runs.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["B-0143", "B-0142", ""]);
+    assert_eq!(
+        (runs[1].first_at, runs[1].last_at, runs[1].records),
+        (100, 500, 5)
+    );
+
+    let report = db.run_report("B-0142").unwrap();
+    assert_eq!((report.attempts, report.units), (5, 3));
+    assert_eq!((report.passed, report.failed), (2, 1));
+    assert_eq!(
+        report.failures,
+        [FailureReason {
+            reason: "Timed out".to_string(),
+            count: 3
+        }]
+    );
+    let durations = report.durations.clone().unwrap();
+    assert_eq!((durations.min_ms, durations.max_ms), (8_000, 10_000));
+    assert_eq!((durations.median_ms, durations.mean_ms), (10_000, 9_000));
+    assert_eq!(report.operators, ["Kim", "Lee"]);
+    assert_eq!(report.firmware_versions, ["1.2.0"]);
+
+    let text = report.to_text();
+    assert!(text.contains("Production run: B-0142"));
+    assert!(text.contains("Yield:    66.7%"));
+    assert!(text.contains("min 8.0 s, median 10.0 s, mean 9.0 s, max 10.0 s"));
+    assert!(text.contains("    3  Timed out"));
+
+    let csv = device_db::to_csv(
+        &db.query(&RecordQuery {
+            run: Some("B-0143".to_string()),
+            ..RecordQuery::default()
+        })
+        .unwrap(),
+    );
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.ends_with(",B-0143,pass,Flashed 4096 bytes,9000,\n"));
+
+    let empty = db.run_report("B-9999").unwrap();
+    assert_eq!(empty.units, 0);
+    assert!(empty.durations.is_none());
+    assert!(empty.to_text().contains("Units:    0"));
+}
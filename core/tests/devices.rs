@@ -0,0 +1,57 @@
+use esp32dev_core::devices;
+use esp32dev_core::models::DeviceStatus;
+
+fn port(name: &str, serial: Option<&str>) -> DeviceStatus {
+    DeviceStatus {
+        code: "ok".to_string(),
+        message: format!("Connected ({})", name),
+        port_name: Some(name.to_string()),
+        product_name: None,
+        serial_number: serial.map(str::to_string),
+        vid_pid: Some("303A:1001".to_string()),
+        connection_type: Some("native_usb".to_string()),
+    }
+}
+
+#[test]
+fn a_re_enumerated_board_is_found_by_serial_number() {
+    let serial = "F4:12:FA:01:02:03";
+
+    // Still where it was
+    let found = [
+        port("/dev/ttyACM0", Some(serial)),
+        port("/dev/ttyACM1", Some("other")),
+    ];
+    assert_eq!(
+        devices::relocate("/dev/ttyACM0", serial, &found).as_deref(),
+        Some("/dev/ttyACM0")
+    );
+
+    // Back under another name after a reset into download mode
+    let found = [
+        port("/dev/ttyACM1", Some("other")),
+        port("/dev/ttyACM2", Some(serial)),
+    ];
+    assert_eq!(
+        devices::relocate("/dev/ttyACM0", serial, &found).as_deref(),
+        Some("/dev/ttyACM2")
+    );
+
+    // Not back yet
+    let found = [port("/dev/ttyACM1", Some("other"))];
+    assert_eq!(devices::relocate("/dev/ttyACM0", serial, &found), None);
+}
+
+#[test]
+fn a_shared_serial_number_is_not_followed() {
+    let found = [
+        port("/dev/ttyUSB1", Some("0001")),
+        port("/dev/ttyUSB2", Some("0001")),
+        port("/dev/ttyUSB3", None),
+    ];
+    assert_eq!(devices::relocate("/dev/ttyUSB0", "0001", &found), None);
+    assert_eq!(
+        devices::relocate("/dev/ttyUSB2", "0001", &found).as_deref(),
+        Some("/dev/ttyUSB2")
+    );
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::scratch;
+use esp32dev_core::elf2image::{self, ImageOptions};
+use esp32dev_core::image_info;
+use esp32dev_core::models::AppError;
+use sha2::{Digest, Sha256};
+
+fn options(chip: &str) -> ImageOptions {
+    ImageOptions {
+        chip: chip.to_string(),
+        flash_mode: "dio".to_string(),
+        flash_size: "4MB".to_string(),
+        flash_freq: "40m".to_string(),
+    }
+}
+
+fn app_desc() -> Vec<u8> {
+    let mut desc = vec![0; 256];
+    desc[..4].copy_from_slice(&0xabcd_5432u32.to_le_bytes());
+    desc[16..21].copy_from_slice(b"0.3.0");
+    desc[48..54].copy_from_slice(b"sensor");
+    desc
+}
+
+/// A RISC-V ELF with sections of (name, type, address, data); the section
+/// name table is added last.
+fn elf(sections: &[(&str, u32, u32, Vec<u8>)]) -> Vec<u8> {
+    let mut names = vec![0u8];
+    let mut data = vec![0u8; 52];
+    let mut headers = vec![0u8; 40];
+    // name, type, flags, address, offset, size; then link, info, alignment
+    // and entry size
+    let mut header = |fields: [u32; 6]| {
+        for value in fields.into_iter().chain([0, 0, 4, 0]) {
+            headers.extend_from_slice(&value.to_le_bytes());
+        }
+    };
+    for (name, kind, address, contents) in sections {
+        let name_at = names.len() as u32;
+        names.extend_from_slice(name.as_bytes());
+        names.push(0);
+        let offset = data.len() as u32;
+        if *kind != 8 {
+            data.extend_from_slice(contents);
+        }
+        header([name_at, *kind, 0x2, *address, offset, contents.len() as u32]);
+    }
+    let name_at = names.len() as u32;
+    names.extend_from_slice(b".shstrtab\0");
+    header([name_at, 3, 0, 0, data.len() as u32, names.len() as u32]);
+    data.extend_from_slice(&names);
+    while !data.len().is_multiple_of(4) {
+        data.push(0);
+    }
+
+    let table = data.len();
+    let count = sections.len() as u16 + 2;
+    data[..4].copy_from_slice(b"\x7fELF");
+    data[4] = 1;
+    data[5] = 1;
+    data[6] = 1;
+    data[16..18].copy_from_slice(&2u16.to_le_bytes());
+    data[18..20].copy_from_slice(&243u16.to_le_bytes());
+    data[24..28].copy_from_slice(&0x4038_0080u32.to_le_bytes());
+    data[32..36].copy_from_slice(&(table as u32).to_le_bytes());
+    data[40..42].copy_from_slice(&52u16.to_le_bytes());
+    data[46..48].copy_from_slice(&40u16.to_le_bytes());
+    data[48..50].copy_from_slice(&count.to_le_bytes());
+    data[50..52].copy_from_slice(&(count - 1).to_le_bytes());
+    data.extend_from_slice(&headers);
+    data
+}
+
+fn app_elf() -> Vec<u8> {
+    elf(&[
+        (".flash.appdesc", 1, 0x3c00_0020, app_desc()),
+        (".flash.rodata", 1, 0x3c00_0120, vec![0x11; 0x60]),
+        (".flash.text", 1, 0x4200_0020, vec![0x13; 0x402]),
+        (".iram0.text", 1, 0x4038_0000, vec![0x97; 0x200]),
+        (".dram0.data", 1, 0x3fc8_0000, vec![0x55; 0x40]),
+        (".dram0.bss", 8, 0x3fc9_0000, vec![0; 0x100]),
+    ])
+}
+
+#[test]
+fn flash_segments_line_up_with_their_mmu_pages() {
+    let elf = app_elf();
+    let image = elf2image::convert(&elf, &options("esp32c3")).unwrap();
+    let info = image_info::parse(&image).unwrap();
+
+    assert_eq!(info.chip.as_deref(), Some("esp32c3"));
+    assert_eq!(info.entry_point, 0x4038_0080);
+    assert_eq!(info.flash_mode.as_deref(), Some("dio"));
+    assert_eq!(info.flash_size.as_deref(), Some("4MB"));
+    assert_eq!(info.flash_freq.as_deref(), Some("40m"));
+    assert!(info.checksum_valid);
+    assert_eq!(info.hash_valid, Some(true));
+    assert!(info.problems.is_empty(), "{:?}", info.problems);
+    assert_eq!(info.image_len, image.len());
+
+    // App description and rodata merged, first, with the ELF's hash filled in
+    let first = &info.segments[0];
+    assert_eq!(first.load_address, 0x3c00_0020);
+    assert_eq!(first.len, 0x160);
+    let app = info.app.unwrap();
+    assert_eq!(app.project_name, "sensor");
+    assert_eq!(
+        app.elf_sha256,
+        Sha256::digest(&elf)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    );
+
+    let is_flash = |address: u32| {
+        (0x3c00_0000..0x3c80_0000).contains(&address)
+            || (0x4200_0000..0x4280_0000).contains(&address)
+    };
+    let flash: Vec<_> = info
+        .segments
+        .iter()
+        .filter(|s| is_flash(s.load_address))
+        .collect();
+    assert_eq!(flash.len(), 2);
+    for segment in flash {
+        assert_eq!(
+            segment.file_offset % 0x10000,
+            segment.load_address as usize % 0x10000
+        );
+    }
+    // All of the RAM sections are loaded, some as padding before .flash.text
+    let ram: u32 = info
+        .segments
+        .iter()
+        .filter(|s| (0x3fc8_0000..0x4040_0000).contains(&s.load_address))
+        .map(|s| s.len)
+        .sum();
+    assert_eq!(ram, 0x240);
+    assert!(info.segments.iter().all(|s| s.load_address != 0x3fc9_0000));
+}
+
+#[test]
+fn mismatched_or_unknown_targets_are_refused() {
+    let elf = app_elf();
+    let result = elf2image::convert(&elf, &options("esp32s3"));
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("RISC-V")));
+
+    let mut slow = options("esp32c2");
+    slow.flash_freq = "40m".to_string();
+    let result = elf2image::convert(&elf, &slow);
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("60m, 30m")));
+
+    let mut odd = options("esp32c3");
+    odd.flash_mode = "octal".to_string();
+    assert!(elf2image::convert(&elf, &odd).is_err());
+    assert!(elf2image::convert(&elf, &options("esp8266")).is_err());
+    assert!(elf2image::convert(b"\xe9\x03 not an elf", &options("esp32c3")).is_err());
+    assert!(elf2image::convert(&elf[..200], &options("esp32c3")).is_err());
+}
+
+#[test]
+fn sections_sharing_a_flash_page_are_refused() {
+    let elf = elf(&[
+        (".flash.rodata", 1, 0x3c00_0020, vec![1; 0x20]),
+        (".flash.rodata2", 1, 0x3c00_1000, vec![2; 0x20]),
+    ]);
+    let result = elf2image::convert(&elf, &options("esp32c3"));
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("64 KB")));
+}
+
+#[test]
+fn the_image_is_written_next_to_the_elf() {
+    let dir = scratch("elf2image");
+    let path = dir.join("sensor.elf");
+    std::fs::write(&path, app_elf()).unwrap();
+
+    let output = elf2image::convert_file(&path, None, &options("esp32c3")).unwrap();
+    assert_eq!(output, dir.join("sensor.bin"));
+    assert!(image_info::check(&std::fs::read(&output).unwrap()).is_ok());
+
+    let named = dir.join("app.bin");
+    let output = elf2image::convert_file(&path, Some(&named), &options("esp32c3")).unwrap();
+    assert_eq!(output, named);
+    assert!(named.is_file());
+}
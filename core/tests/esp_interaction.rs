@@ -0,0 +1,94 @@
+use esp32dev_core::esp_interaction::{
+    changed_ranges, check_aligned, check_fits, parse_flash_address,
+};
+use esp32dev_core::models::{AppError, FlashSegment};
+use std::ops::Range;
+
+// Ranges of `image` that differ from `flash`, and how many ranges were hashed
+fn diff(image: &[u8], flash: &[u8]) -> (Vec<Range<usize>>, usize) {
+    let mut asked = 0;
+    let ranges = changed_ranges(image.len(), |range| {
+        asked += 1;
+        Ok(image[range.clone()] != flash[range])
+    })
+    .unwrap();
+    (ranges, asked)
+}
+
+#[test]
+fn unchanged_images_cost_one_hash_per_block() {
+    let image = vec![0x5a; 200 * 1024];
+    let (ranges, asked) = diff(&image, &image);
+    assert!(ranges.is_empty());
+    assert_eq!(asked, 4);
+}
+
+#[test]
+fn changed_sectors_are_found_and_merged() {
+    let flash = vec![0xff; 130 * 1024];
+    let mut image = flash.clone();
+    // Two neighbouring sectors in the first block, one in the last
+    image[5000] = 0;
+    image[8192] = 0;
+    image[129 * 1024] = 0;
+    let (ranges, asked) = diff(&image, &flash);
+    assert_eq!(ranges, [4096..12288, 128 * 1024..130 * 1024]);
+    // Three blocks, then the sixteen sectors of the first and the one of the last
+    assert_eq!(asked, 3 + 16 + 1);
+}
+
+#[test]
+fn a_short_last_sector_is_cut_at_the_image_end() {
+    let flash = vec![0; 5000];
+    let mut image = flash.clone();
+    image[4999] = 1;
+    let (ranges, _) = diff(&image, &flash);
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0], 4096..5000);
+}
+
+fn segment(address: u32) -> FlashSegment {
+    FlashSegment {
+        path: "app.bin".to_string(),
+        address,
+    }
+}
+
+#[test]
+fn addresses_are_hex_or_decimal_digits_only() {
+    assert_eq!(parse_flash_address(" 0x10000 ").unwrap(), 0x10000);
+    assert_eq!(parse_flash_address("4096").unwrap(), 4096);
+    for bad in ["", "0x", "0x+10", "+10", "10000h", "0x100000000"] {
+        assert!(
+            matches!(parse_flash_address(bad), Err(AppError::InvalidInput(_))),
+            "{:?}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn images_start_on_a_sector() {
+    assert!(check_aligned(&segment(0x10000)).is_ok());
+    assert!(matches!(
+        check_aligned(&segment(0x10800)),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn overflowing_the_flash_reports_by_how_much() {
+    let four_mb = 4 * 1024 * 1024;
+    assert!(check_fits(&segment(0x3f_0000), 0x1_0000, four_mb).is_ok());
+
+    let e = check_fits(&segment(0x3f_0000), 0x1_0123, four_mb).unwrap_err();
+    assert!(matches!(e, AppError::FlashOverflow { end: 0x40_0123, .. }));
+    assert_eq!(e.overflow(), Some(0x123));
+    assert!(e
+        .message()
+        .contains("291 bytes past the end of the 4 MB flash"));
+
+    let json = serde_json::to_value(e).unwrap();
+    assert_eq!(json["kind"], "FlashOverflow");
+    assert_eq!(json["overflow"], 0x123);
+}
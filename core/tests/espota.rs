@@ -0,0 +1,102 @@
+//! ArduinoOTA uploads against a fake board on localhost that answers the
+//! invitation, checks the password and pulls the image like the real one.
+
+use esp32dev_core::espota::{self, OtaOptions, OtaTarget};
+use esp32dev_core::models::AppError;
+use esp32dev_core::tasks::TaskManager;
+use std::io::{Read, Write};
+use std::net::{TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::thread;
+
+const NONCE: &str = "0123456789abcdef0123456789abcdef";
+
+fn image_file(name: &str, len: usize) -> (PathBuf, Vec<u8>) {
+    let image: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+    let path = std::env::temp_dir().join(format!("esp32dev-{}-{}", std::process::id(), name));
+    std::fs::write(&path, &image).unwrap();
+    (path, image)
+}
+
+/// Plays the board: returns its port and a handle yielding what it received,
+/// or `None` when it turned the upload down.
+fn fake_board(password: &'static str) -> (u16, thread::JoinHandle<Option<Vec<u8>>>) {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+    let board = thread::spawn(move || {
+        let mut buf = [0u8; 256];
+        let (n, host) = socket.recv_from(&mut buf).unwrap();
+        let invitation = String::from_utf8_lossy(&buf[..n]).to_string();
+        let fields: Vec<&str> = invitation.split_whitespace().collect();
+        assert_eq!(fields[0], "0");
+        let (tcp_port, size): (u16, usize) =
+            (fields[1].parse().unwrap(), fields[2].parse().unwrap());
+
+        socket
+            .send_to(format!("AUTH {}", NONCE).as_bytes(), host)
+            .unwrap();
+        let n = socket.recv(&mut buf).unwrap();
+        let answer = String::from_utf8_lossy(&buf[..n]).to_string();
+        let fields: Vec<&str> = answer.split_whitespace().collect();
+        assert_eq!(fields[0], "200");
+        if fields[2] != espota::auth_response(password, NONCE, fields[1]) {
+            socket.send_to(b"Authentication Failed", host).unwrap();
+            return None;
+        }
+        socket.send_to(b"OK", host).unwrap();
+
+        // One acknowledgement per 1 KiB chunk, then the verdict
+        let mut stream = TcpStream::connect(("127.0.0.1", tcp_port)).unwrap();
+        let mut received = Vec::new();
+        while received.len() < size {
+            let mut chunk = vec![0u8; (size - received.len()).min(1024)];
+            stream.read_exact(&mut chunk).unwrap();
+            received.extend_from_slice(&chunk);
+            stream
+                .write_all(chunk.len().to_string().as_bytes())
+                .unwrap();
+        }
+        stream.write_all(b"OK").unwrap();
+        Some(received)
+    });
+    (port, board)
+}
+
+#[test]
+fn uploads_with_password() {
+    let (path, image) = image_file("ota.bin", 5000);
+    let (port, board) = fake_board("secret");
+    let task = TaskManager::default().start("ota", Some("127.0.0.1"));
+    let options = OtaOptions {
+        host: "127.0.0.1",
+        port,
+        password: Some("secret"),
+        target: OtaTarget::Firmware,
+    };
+
+    espota::upload(&options, &path, &task).expect("upload");
+    assert_eq!(board.join().unwrap(), Some(image));
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn wrong_password_is_reported() {
+    let (path, _) = image_file("ota-denied.bin", 100);
+    let (port, board) = fake_board("secret");
+    let task = TaskManager::default().start("ota", Some("127.0.0.1"));
+    let options = OtaOptions {
+        host: "127.0.0.1",
+        port,
+        password: Some("guess"),
+        target: OtaTarget::Firmware,
+    };
+
+    let result = espota::upload(&options, &path, &task);
+    assert!(
+        matches!(result, Err(AppError::InvalidInput(_))),
+        "{:?}",
+        result
+    );
+    assert_eq!(board.join().unwrap(), None);
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,46 @@
+use esp32dev_core::esptool;
+use esp32dev_core::models::FlashSegment;
+
+fn segment(path: &str, address: u32) -> FlashSegment {
+    FlashSegment {
+        path: path.to_string(),
+        address,
+    }
+}
+
+#[test]
+fn write_flash_lists_every_segment() {
+    let segments = [
+        segment("build/bootloader/bootloader.bin", 0x0),
+        segment("build/partition_table/partition-table.bin", 0x8000),
+        segment("build/app.bin", 0x10000),
+    ];
+    assert_eq!(
+        esptool::write_flash(Some("esp32s3"), "/dev/ttyACM0", 460800, &segments),
+        "esptool.py --chip esp32s3 --port /dev/ttyACM0 --baud 460800 \
+         --before default_reset --after hard_reset write_flash \
+         0x0 build/bootloader/bootloader.bin \
+         0x8000 build/partition_table/partition-table.bin \
+         0x10000 build/app.bin"
+    );
+}
+
+#[test]
+fn paths_are_quoted_for_the_shell() {
+    let segments = [segment("/home/me/My Projects/it's.bin", 0x1000)];
+    let command = esptool::write_flash(Some("ESP32-C3"), "COM3", 115200, &segments);
+    assert!(command.starts_with("esptool.py --chip esp32c3 --port COM3 --baud 115200"));
+    assert!(command.ends_with(r"write_flash 0x1000 '/home/me/My Projects/it'\''s.bin'"));
+}
+
+#[test]
+fn erase_and_flash_id_stay_at_the_sync_baud() {
+    assert_eq!(
+        esptool::erase_flash(None, "/dev/ttyUSB0"),
+        "esptool.py --chip auto --port /dev/ttyUSB0 --baud 115200 erase_flash"
+    );
+    assert_eq!(
+        esptool::flash_id(Some("esp32"), "/dev/cu.usbserial-110"),
+        "esptool.py --chip esp32 --port /dev/cu.usbserial-110 --baud 115200 flash_id"
+    );
+}
@@ -0,0 +1,23 @@
+//! The example catalog must only offer images a chip can run.
+
+use esp32dev_core::examples;
+
+#[test]
+fn wifi_scan_needs_a_radio() {
+    assert_eq!(
+        examples::for_chip("ESP32-S3"),
+        ["hello-world", "blink", "wifi-scan"]
+    );
+    assert_eq!(examples::for_chip("esp32h2"), ["hello-world", "blink"]);
+    assert!(examples::for_chip("esp8266").is_empty());
+}
+
+#[test]
+fn image_paths_use_the_espflash_spelling() {
+    assert_eq!(
+        examples::image_path("blink", "ESP32-C6").unwrap(),
+        "firmware/examples/blink-esp32c6.bin"
+    );
+    assert!(examples::image_path("wifi-scan", "esp32p4").is_err());
+    assert!(examples::image_path("../gpio-helper/esp32", "esp32").is_err());
+}
@@ -0,0 +1,126 @@
+use esp32dev_core::flash_encryption::{decrypt, encrypt, encrypt_file};
+use esp32dev_core::models::AppError;
+
+fn key(len: u8) -> Vec<u8> {
+    (0..len).collect()
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn matches_espsecure_output() {
+    let data: Vec<u8> = (0..32).collect();
+    assert_eq!(
+        encrypt("esp32s3", &data, 0x10000, &key(32)).unwrap(),
+        hex("2c2a288e9e96647587883012b81d17396a80540eebdd2f9e323e7017d6d650de")
+    );
+    assert_eq!(
+        encrypt("esp32s3", &data, 0x10000, &key(64)).unwrap(),
+        hex("ed032950be116e860729b191d93e3cc2541e5722eec0a990b48e4eb691b0acf5")
+    );
+}
+
+#[test]
+fn matches_espsecure_output_across_units() {
+    // From espsecure.py's encrypt_flash_data AES-XTS routine for 0x60 bytes
+    // at 0x10030, which straddle the 128-byte unit at 0x10080
+    let data: Vec<u8> = (0..0x60u32).map(|i| (i * 7) as u8).collect();
+    assert_eq!(
+        encrypt("esp32s3", &data, 0x10030, &key(32)).unwrap(),
+        hex(concat!(
+            "b780de381860874bffc5c46db0dbcbed1c9930c0252054e771674fc049ceb84f",
+            "240b0667c3a45e9d54bb24d1537c0d66a100ee36df420e24868d534033a9df60",
+            "5ac6328a66cc32aed023f07335081168865871f9f30cf5f3fc34a8ff354328d6",
+        ))
+    );
+    assert_eq!(
+        encrypt("esp32c6", &data, 0x10030, &key(64)).unwrap(),
+        hex(concat!(
+            "d143fbf61117a50eb4f4c584332ba50f7605bf1c6a61c100ea329b9704cbce24",
+            "7748cd3a51d7d091b7a563de91160d68091109e06ec610ff5c109cf9ea480e90",
+            "85975874c18b1c2b3de29b45b660434d1ac9292a56febf02a87fc81d50d1f8cc",
+        ))
+    );
+}
+
+#[test]
+fn chips_with_another_scheme_are_refused_by_name() {
+    let data = [0u8; 32];
+    for chip in ["esp32", "ESP32-C2"] {
+        match encrypt(chip, &data, 0x10000, &key(32)) {
+            Err(AppError::InvalidInput(message)) => assert!(message.contains(chip), "{}", message),
+            other => panic!("{} was not refused: {:?}", chip, other.map(|_| ())),
+        }
+    }
+    assert!(matches!(
+        encrypt("esp8266", &data, 0x10000, &key(32)),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn round_trips_at_any_block_offset() {
+    let data: Vec<u8> = (0..512u32).map(|i| (i * 7) as u8).collect();
+    for address in [0x10000, 0x10030, 0x1ff0] {
+        let encrypted = encrypt("esp32s3", &data, address, &key(64)).unwrap();
+        assert_ne!(encrypted, data);
+        assert_eq!(
+            decrypt("esp32s3", &encrypted, address, &key(64)).unwrap(),
+            data
+        );
+    }
+}
+
+#[test]
+fn ciphertext_depends_only_on_the_flash_address() {
+    let data = vec![0x5a; 0x200];
+    let whole = encrypt("esp32s3", &data, 0x10000, &key(32)).unwrap();
+    // The same bytes written in two parts come out identical
+    let tail = encrypt("esp32s3", &data[0x90..], 0x10090, &key(32)).unwrap();
+    assert_eq!(whole[0x90..], tail[..]);
+    // Identical plaintext units differ between addresses
+    assert_ne!(whole[..0x80], whole[0x80..0x100]);
+}
+
+#[test]
+fn rejects_bad_keys_offsets_and_lengths() {
+    let data = [0u8; 32];
+    assert!(matches!(
+        encrypt("esp32s3", &data, 0x10008, &key(32)),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        encrypt("esp32s3", &data[..20], 0x10000, &key(32)),
+        Err(AppError::InvalidInput(_))
+    ));
+    for len in [16, 24, 33] {
+        assert!(matches!(
+            encrypt("esp32s3", &data, 0x10000, &key(len)),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+}
+
+#[test]
+fn encrypted_files_are_padded_to_whole_blocks() {
+    let dir = std::env::temp_dir().join(format!("esp32dev-encrypt-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let image = dir.join("app.bin");
+    let key_file = dir.join("key.bin");
+    std::fs::write(&image, [0xe9u8; 20]).unwrap();
+    std::fs::write(&key_file, key(32)).unwrap();
+
+    let written = encrypt_file("esp32s3", &image, 0x20000, &key_file, None).unwrap();
+    assert!(written.ends_with("app-encrypted.bin"));
+    let encrypted = std::fs::read(&written).unwrap();
+    assert_eq!(encrypted.len(), 32);
+    let plain = decrypt("esp32s3", &encrypted, 0x20000, &key(32)).unwrap();
+    assert_eq!(plain[..20], [0xe9; 20]);
+    assert!(plain[20..].iter().all(|&b| b == 0xff));
+    std::fs::remove_dir_all(dir).unwrap();
+}
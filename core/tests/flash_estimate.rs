@@ -0,0 +1,46 @@
+use esp32dev_core::flash_estimate::{compressed_len, estimate, estimate_secs, remaining_secs};
+
+#[test]
+fn padding_costs_little_on_the_wire() {
+    let blank = vec![0xff; 64 * 1024];
+    // xorshift32, which zlib can't shrink
+    let noise: Vec<u8> = std::iter::successors(Some(0x2545_f491u32), |&x| {
+        let x = x ^ (x << 13);
+        let x = x ^ (x >> 17);
+        Some(x ^ (x << 5))
+    })
+    .skip(1)
+    .take(64 * 1024)
+    .map(|x| (x >> 24) as u8)
+    .collect();
+    assert!(compressed_len(&blank) < 1024);
+    assert!(compressed_len(&noise) > 60 * 1024);
+}
+
+#[test]
+fn the_slower_of_wire_and_flash_chip_counts() {
+    // 1 MB compressed to 512 KB at 115200 baud: about 46 s on the wire
+    assert_eq!(estimate_secs(1 << 20, 512 * 1024, 115_200, false), 46);
+    // At 2 Mbaud the chip programming 200 KB/s is what takes the time
+    assert_eq!(estimate_secs(1 << 20, 512 * 1024, 2_000_000, false), 6);
+    // Connecting and loading the stub adds a couple of seconds
+    assert_eq!(estimate_secs(1 << 20, 512 * 1024, 2_000_000, true), 8);
+}
+
+#[test]
+fn estimates_add_up_the_images() {
+    let app = vec![0xff; 8192];
+    let table = vec![0xff; 3072];
+    let total = estimate([app.as_slice(), table.as_slice()], 460_800);
+    assert_eq!(total.bytes, 8192 + 3072);
+    assert!(total.compressed < total.bytes);
+    assert_eq!(total.baud_rate, 460_800);
+    assert_eq!(total.secs, 3);
+}
+
+#[test]
+fn time_left_follows_the_measured_rate() {
+    assert_eq!(remaining_secs(1000 * 1024, 100 * 1024), Some(10));
+    assert_eq!(remaining_secs(1, 100 * 1024), Some(1));
+    assert_eq!(remaining_secs(1024, 0), None);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::scratch;
+use esp32dev_core::flash_watch::{Rebuilds, SETTLE};
+use std::time::{Duration, Instant};
+
+#[test]
+fn a_rebuild_is_reported_once_it_settles() {
+    let dir = scratch("flash-watch");
+    let app = dir.join("app.bin");
+    let bootloader = dir.join("bootloader.bin");
+    std::fs::write(&app, [0xe9; 16]).unwrap();
+    std::fs::write(&bootloader, [0xe9; 8]).unwrap();
+
+    let mut rebuilds = Rebuilds::new(vec![app.clone(), bootloader.clone()]);
+    let start = Instant::now();
+    // What was there when watching started counts as flashed
+    assert!(!rebuilds.poll(start));
+    assert!(!rebuilds.poll(start + SETTLE * 2));
+
+    // Still being written: nothing until it holds still
+    std::fs::write(&app, [0xe9; 32]).unwrap();
+    assert!(!rebuilds.poll(start));
+    std::fs::write(&app, [0xe9; 48]).unwrap();
+    assert!(!rebuilds.poll(start + SETTLE));
+    assert!(!rebuilds.poll(start + SETTLE + Duration::from_millis(10)));
+    assert!(rebuilds.poll(start + SETTLE * 2));
+    assert!(!rebuilds.poll(start + SETTLE * 4));
+
+    // A clean build removes the images first
+    std::fs::remove_file(&bootloader).unwrap();
+    assert!(!rebuilds.poll(start));
+    assert!(!rebuilds.poll(start + SETTLE * 2));
+    std::fs::write(&bootloader, [0xe9; 24]).unwrap();
+    assert!(!rebuilds.poll(start + SETTLE * 2));
+    assert!(rebuilds.poll(start + SETTLE * 3));
+}
@@ -0,0 +1,207 @@
+use esp32dev_core::elf2image::ImageOptions;
+use esp32dev_core::full_image;
+use esp32dev_core::image_info;
+use esp32dev_core::models::AppError;
+use esp32dev_core::otadata;
+use md5::{Digest, Md5};
+use sha2::Sha256;
+
+fn options(chip: &str) -> ImageOptions {
+    ImageOptions {
+        chip: chip.to_string(),
+        flash_mode: "qio".to_string(),
+        flash_size: "4MB".to_string(),
+        flash_freq: "80m".to_string(),
+    }
+}
+
+/// A bootloader image for chip id `chip_id` with a single RAM segment, the
+/// SHA-256 appended, and dout 2MB 40m flash parameters.
+fn bootloader(chip_id: u16) -> Vec<u8> {
+    let mut data = vec![0xe9, 1, 0x03, 0x10];
+    data.extend_from_slice(&0x403c_e000u32.to_le_bytes());
+    data.extend_from_slice(&[0xee, 0, 0, 0]);
+    data.extend_from_slice(&chip_id.to_le_bytes());
+    data.extend_from_slice(&[0; 9]);
+    data.push(1);
+    let contents = [0x42u8; 64];
+    data.extend_from_slice(&0x403c_e000u32.to_le_bytes());
+    data.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+    data.extend_from_slice(&contents);
+    while data.len() % 16 != 15 {
+        data.push(0);
+    }
+    data.push(contents.iter().fold(0xef, |sum, b| sum ^ b));
+    let digest = Sha256::digest(&data);
+    data.extend_from_slice(&digest);
+    data
+}
+
+/// A RISC-V ELF with one flash-mapped code section and one RAM section.
+fn elf() -> Vec<u8> {
+    let sections: [(u32, u32, Vec<u8>); 2] = [
+        (1, 0x4200_0020, vec![0x13; 0x100]),
+        (1, 0x3fc8_0000, vec![0x55; 0x40]),
+    ];
+    let mut data = vec![0u8; 52];
+    let mut headers = vec![0u8; 40];
+    for (kind, address, contents) in &sections {
+        let offset = data.len() as u32;
+        data.extend_from_slice(contents);
+        for value in [
+            0,
+            *kind,
+            0x2,
+            *address,
+            offset,
+            contents.len() as u32,
+            0,
+            0,
+            4,
+            0,
+        ] {
+            headers.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    // An empty section name table
+    let names = data.len() as u32;
+    data.push(0);
+    for value in [0, 3, 0, 0, names, 1, 0, 0, 1, 0] {
+        headers.extend_from_slice(&value.to_le_bytes());
+    }
+    while !data.len().is_multiple_of(4) {
+        data.push(0);
+    }
+    let table = data.len() as u32;
+    data[..7].copy_from_slice(b"\x7fELF\x01\x01\x01");
+    data[16..18].copy_from_slice(&2u16.to_le_bytes());
+    data[18..20].copy_from_slice(&243u16.to_le_bytes());
+    data[24..28].copy_from_slice(&0x4038_0080u32.to_le_bytes());
+    data[32..36].copy_from_slice(&table.to_le_bytes());
+    data[46..48].copy_from_slice(&40u16.to_le_bytes());
+    data[48..50].copy_from_slice(&4u16.to_le_bytes());
+    data[50..52].copy_from_slice(&3u16.to_le_bytes());
+    data.extend_from_slice(&headers);
+    data
+}
+
+#[test]
+fn csv_tables_are_laid_out_like_gen_esp32part() {
+    let csv = "\
+# Name, Type, SubType, Offset, Size, Flags
+nvs,      data, nvs,     ,        0x6000,
+otadata,  data, ota,     ,        0x2000,
+phy_init, data, phy,     ,        4K,
+ota_0,    app,  ota_0,   ,        1M,     encrypted
+ota_1,    app,  ota_1,   ,        1M,
+storage,  data, 0x99,    0x300000, 0x100000, readonly
+";
+    let table = full_image::partition_table(csv).unwrap();
+    assert_eq!(table.len(), otadata::PARTITION_TABLE_LEN as usize);
+
+    let partitions = otadata::partitions(&table);
+    let layout: Vec<(&str, u8, u8, u32, u32)> = partitions
+        .iter()
+        .map(|p| (p.label.as_str(), p.kind, p.subtype, p.offset, p.size))
+        .collect();
+    assert_eq!(
+        layout,
+        [
+            ("nvs", 1, 0x02, 0x9000, 0x6000),
+            ("otadata", 1, 0x00, 0xf000, 0x2000),
+            ("phy_init", 1, 0x01, 0x11000, 0x1000),
+            ("ota_0", 0, 0x10, 0x20000, 0x100000),
+            ("ota_1", 0, 0x11, 0x120000, 0x100000),
+            ("storage", 1, 0x99, 0x300000, 0x100000),
+        ]
+    );
+    assert_eq!(table[3 * 32 + 28], 1);
+    assert_eq!(table[5 * 32 + 28], 2);
+
+    // The MD5 entry covers the entries before it
+    let md5 = &table[6 * 32..7 * 32];
+    assert_eq!(md5[..2], [0xeb, 0xeb]);
+    assert_eq!(md5[16..], Md5::digest(&table[..6 * 32])[..]);
+    assert!(table[7 * 32..].iter().all(|&b| b == 0xff));
+
+    let default = full_image::partition_table(full_image::DEFAULT_PARTITIONS_CSV).unwrap();
+    assert_eq!(otadata::partitions(&default).len(), 3);
+}
+
+#[test]
+fn bad_csv_rows_name_their_line() {
+    let cases = [
+        ("nvs, data, nvs, 0x9000\n", "line 1"),
+        ("nvs, data, nvs, 0x8000, 0x6000\n", "partition table"),
+        ("\nfactory, app, factory, 0x18000, 1M\n", "line 2: offset"),
+        (
+            "a, data, nvs, , 0x6000\nb, data, phy, 0xa000, 4K\n",
+            "b overlaps a",
+        ),
+        ("nvs, data, bogus, , 0x6000\n", "subtype"),
+        ("a_name_far_too_long, data, nvs, , 0x6000\n", "name"),
+        ("nvs, data, nvs, , 0x6000, secret\n", "flag"),
+        ("# nothing\n", "no partitions"),
+    ];
+    for (csv, expected) in cases {
+        let result = full_image::partition_table(csv);
+        assert!(
+            matches!(&result, Err(AppError::InvalidInput(m)) if m.contains(expected)),
+            "{:?}: {:?}",
+            csv,
+            result.map(|_| ())
+        );
+    }
+}
+
+#[test]
+fn bootloader_table_and_app_land_at_their_offsets() {
+    let image = full_image::assemble(
+        &elf(),
+        &bootloader(0x0005),
+        full_image::DEFAULT_PARTITIONS_CSV,
+        &options("esp32c3"),
+    )
+    .unwrap();
+
+    // The bootloader takes the app's flash parameters, hash refreshed
+    let boot = image_info::parse(&image).unwrap();
+    assert_eq!(boot.flash_mode.as_deref(), Some("qio"));
+    assert_eq!(boot.flash_size.as_deref(), Some("4MB"));
+    assert_eq!(boot.flash_freq.as_deref(), Some("80m"));
+    assert_eq!(boot.hash_valid, Some(true));
+    assert!(image[boot.image_len..0x8000].iter().all(|&b| b == 0xff));
+
+    assert_eq!(otadata::partitions(&image[0x8000..0x8c00]).len(), 3);
+    let app = image_info::parse(&image[0x10000..]).unwrap();
+    assert_eq!(app.chip.as_deref(), Some("esp32c3"));
+    assert!(app.problems.is_empty());
+    assert_eq!(image.len(), 0x10000 + app.image_len);
+}
+
+#[test]
+fn mismatches_are_refused() {
+    let csv = full_image::DEFAULT_PARTITIONS_CSV;
+    let result = full_image::assemble(&elf(), &bootloader(0x0009), csv, &options("esp32c3"));
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("esp32s3")));
+
+    let mut corrupt = bootloader(0x0005);
+    corrupt[40] ^= 1;
+    assert!(full_image::assemble(&elf(), &corrupt, csv, &options("esp32c3")).is_err());
+
+    let tiny = "factory, app, factory, 0x10000, 0x100\n";
+    let result = full_image::assemble(&elf(), &bootloader(0x0005), tiny, &options("esp32c3"));
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("doesn't fit")));
+
+    let mut small = options("esp32c3");
+    small.flash_size = "1MB".to_string();
+    let result = full_image::assemble(&elf(), &bootloader(0x0005), csv, &small);
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("factory")));
+
+    assert_eq!(
+        full_image::bootloader_resource("ESP32-S3", "v5.3").unwrap(),
+        "firmware/bootloaders/bootloader-esp32s3-v5.3.bin"
+    );
+    assert!(full_image::bootloader_resource("esp32s3", "v4.4").is_err());
+    assert!(full_image::bootloader_resource("esp8266", "v5.3").is_err());
+}
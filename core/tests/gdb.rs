@@ -0,0 +1,99 @@
+use esp32dev_core::gdb::{self, MiRecord, MiValue};
+use esp32dev_core::models::StackFrame;
+
+#[test]
+fn a_breakpoint_stop_carries_the_frame() {
+    let line = r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",frame={addr="0x42008b2a",func="app_main",args=[],file="../main/hello_world_main.c",fullname="/home/dev/hello/main/hello_world_main.c",line="17",arch="esp32s3"},thread-id="1",stopped-threads="all""#;
+    let MiRecord::Exec { class, results } = gdb::parse_record(line) else {
+        panic!("not an exec record");
+    };
+    assert_eq!(class, "stopped");
+    assert_eq!(results.str("reason"), Some("breakpoint-hit"));
+    assert_eq!(
+        gdb::frame(results.get("frame").unwrap()),
+        StackFrame {
+            level: 0,
+            address: "0x42008b2a".to_string(),
+            function: Some("app_main".to_string()),
+            file: Some("../main/hello_world_main.c".to_string()),
+            line: Some(17),
+        }
+    );
+}
+
+#[test]
+fn a_backtrace_lists_every_frame() {
+    let line = r#"7^done,stack=[frame={level="0",addr="0x4037a4c6",func="esp_restart_noos",file="port/esp_system_chip.c",line="92"},frame={level="1",addr="0x42002f10",func="??"}]"#;
+    let MiRecord::Result {
+        token,
+        class,
+        results,
+    } = gdb::parse_record(line)
+    else {
+        panic!("not a result record");
+    };
+    assert_eq!((token, class.as_str()), (Some(7), "done"));
+    let frames = gdb::frames(&results);
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].function.as_deref(), Some("esp_restart_noos"));
+    assert_eq!(frames[0].line, Some(92));
+    // No symbols for the caller
+    assert_eq!(frames[1].level, 1);
+    assert_eq!(frames[1].function, None);
+    assert_eq!(frames[1].file, None);
+}
+
+#[test]
+fn streams_and_errors_are_unescaped() {
+    assert_eq!(
+        gdb::parse_record(r#"~"Breakpoint 1 at 0x42008b2a: file \"main.c\", line 17.\n""#),
+        MiRecord::Stream("Breakpoint 1 at 0x42008b2a: file \"main.c\", line 17.\n".to_string())
+    );
+    // Bytes outside ASCII come as octal escapes
+    assert_eq!(
+        gdb::parse_record(r#"@"\302\260C\n""#),
+        MiRecord::Stream("°C\n".to_string())
+    );
+    assert_eq!(
+        gdb::parse_record(r#"3^error,msg="No symbol table is loaded.  Use the \"file\" command.""#),
+        MiRecord::Result {
+            token: Some(3),
+            class: "error".to_string(),
+            results: MiValue::Tuple(vec![(
+                "msg".to_string(),
+                MiValue::Const("No symbol table is loaded.  Use the \"file\" command.".to_string())
+            )]),
+        }
+    );
+    assert_eq!(gdb::parse_record("(gdb) "), MiRecord::Prompt);
+    assert_eq!(
+        gdb::parse_record("I (312) app: hello"),
+        MiRecord::Other("I (312) app: hello".to_string())
+    );
+}
+
+#[test]
+fn each_architecture_has_its_gdb() {
+    assert_eq!(
+        gdb::program("ESP32-S3"),
+        Some(("xtensa-esp-elf-gdb", "xtensa-esp32s3-elf-gdb".to_string()))
+    );
+    assert_eq!(
+        gdb::program("esp32c6"),
+        Some(("riscv32-esp-elf-gdb", "riscv32-esp-elf-gdb".to_string()))
+    );
+    assert_eq!(gdb::program("esp8266"), None);
+}
+
+#[test]
+fn read_memory_answers_decode_to_bytes() {
+    let line = r#"3^done,memory=[{begin="0x60000000",offset="0x00000000",end="0x60000008",contents="1c0000007f00ff00"}]"#;
+    let MiRecord::Result { results, .. } = gdb::parse_record(line) else {
+        panic!("not a result record");
+    };
+    assert_eq!(
+        gdb::memory(&results),
+        Some(vec![0x1c, 0, 0, 0, 0x7f, 0, 0xff, 0])
+    );
+    assert_eq!(gdb::memory(&MiValue::Tuple(Vec::new())), None);
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::scratch;
+use esp32dev_core::idf;
+use std::path::Path;
+
+/// As written by `idf.py build` for hello_world on an ESP32-S3.
+const FLASHER_ARGS: &str = r#"{
+    "write_flash_args" : [ "--flash_mode", "dio",
+                           "--flash_size", "2MB",
+                           "--flash_freq", "80m" ],
+    "flash_settings" : {
+        "flash_mode": "dio",
+        "flash_size": "2MB",
+        "flash_freq": "80m"
+    },
+    "flash_files" : {
+        "0x0" : "bootloader/bootloader.bin",
+        "0x10000" : "hello_world.bin",
+        "0x8000" : "partition_table/partition-table.bin"
+    },
+    "bootloader" : { "offset" : "0x0", "file" : "bootloader/bootloader.bin", "encrypted" : "false" },
+    "app" : { "offset" : "0x10000", "file" : "hello_world.bin", "encrypted" : "false" },
+    "partition-table" : { "offset" : "0x8000", "file" : "partition_table/partition-table.bin", "encrypted" : "false" },
+    "extra_esptool_args" : {
+        "after"  : "hard_reset",
+        "before" : "default_reset",
+        "stub"   : true,
+        "chip"   : "esp32s3"
+    }
+}"#;
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn flasher_args_list_images_by_offset() {
+    let plan = idf::parse_flasher_args(FLASHER_ARGS, Path::new("/work/build")).unwrap();
+    assert_eq!(plan.chip.as_deref(), Some("esp32s3"));
+    assert_eq!(plan.flash_mode.as_deref(), Some("dio"));
+    assert_eq!(plan.flash_size.as_deref(), Some("2MB"));
+    assert_eq!(plan.flash_freq.as_deref(), Some("80m"));
+    let segments: Vec<_> = plan
+        .segments
+        .iter()
+        .map(|s| (s.address, s.path.as_str()))
+        .collect();
+    let root = Path::new("/work/build");
+    assert_eq!(
+        segments,
+        [
+            (
+                0x0,
+                root.join("bootloader/bootloader.bin").to_str().unwrap()
+            ),
+            (
+                0x8000,
+                root.join("partition_table/partition-table.bin")
+                    .to_str()
+                    .unwrap()
+            ),
+            (0x10000, root.join("hello_world.bin").to_str().unwrap()),
+        ]
+    );
+}
+
+#[test]
+fn encrypted_builds_are_refused() {
+    let encrypted = FLASHER_ARGS.replace(
+        r#""file" : "hello_world.bin", "encrypted" : "false""#,
+        r#""file" : "hello_world.bin", "encrypted" : "true""#,
+    );
+    assert!(idf::parse_flasher_args(&encrypted, Path::new("build")).is_err());
+    assert!(idf::parse_flasher_args("{}", Path::new("build")).is_err());
+}
+
+#[test]
+fn project_or_build_dir_can_be_loaded() {
+    let project = scratch("idf-project");
+    assert!(idf::load(&project).is_err());
+
+    let build = project.join("build");
+    write(&build.join("flasher_args.json"), FLASHER_ARGS);
+    write(&build.join("bootloader/bootloader.bin"), "boot");
+    write(&build.join("partition_table/partition-table.bin"), "table");
+    // The app is missing until it is built
+    assert!(idf::load(&project).is_err());
+
+    write(&build.join("hello_world.bin"), "app");
+    assert_eq!(idf::load(&project).unwrap().segments.len(), 3);
+    assert_eq!(
+        idf::load(&build).unwrap().build_dir,
+        build.display().to_string()
+    );
+    std::fs::remove_dir_all(&project).ok();
+}
+
+#[test]
+fn the_app_elf_is_found_from_the_project() {
+    let project = scratch("idf-elf");
+    let build = project.join("build");
+    write(&build.join("flasher_args.json"), FLASHER_ARGS);
+    write(
+        &build.join("project_description.json"),
+        r#"{ "project_name": "hello_world", "app_elf": "hello_world.elf", "app_bin": "hello_world.bin" }"#,
+    );
+    // Described, but not linked yet
+    assert!(idf::app_elf(&project).is_err());
+
+    write(&build.join("hello_world.elf"), "\x7fELF");
+    assert_eq!(
+        idf::app_elf(&project).unwrap(),
+        build.join("hello_world.elf")
+    );
+    assert_eq!(idf::app_elf(&build).unwrap(), build.join("hello_world.elf"));
+    std::fs::remove_dir_all(&project).ok();
+}
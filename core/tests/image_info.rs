@@ -0,0 +1,151 @@
+use esp32dev_core::image_info;
+use esp32dev_core::models::AppError;
+use sha2::{Digest, Sha256};
+
+/// An ESP32-S3 image of `segments` as (load address, data), with the
+/// checksum and, when `hash` is set, the SHA-256 appended.
+fn image(segments: &[(u32, Vec<u8>)], hash: bool) -> Vec<u8> {
+    let mut data = vec![0xe9, segments.len() as u8, 0x02, 0x2f];
+    data.extend_from_slice(&0x4037_5a04u32.to_le_bytes());
+    data.extend_from_slice(&[0xee, 0, 0, 0]);
+    data.extend_from_slice(&9u16.to_le_bytes());
+    data.push(0);
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&99u16.to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.push(hash as u8);
+    let mut checksum = 0xef;
+    for (address, contents) in segments {
+        data.extend_from_slice(&address.to_le_bytes());
+        data.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        data.extend_from_slice(contents);
+        checksum = contents.iter().fold(checksum, |sum, b| sum ^ b);
+    }
+    while data.len() % 16 != 15 {
+        data.push(0);
+    }
+    data.push(checksum);
+    if hash {
+        let digest = Sha256::digest(&data);
+        data.extend_from_slice(&digest);
+    }
+    data
+}
+
+fn app_desc() -> Vec<u8> {
+    let mut desc = vec![0; 256];
+    desc[..4].copy_from_slice(&0xabcd_5432u32.to_le_bytes());
+    desc[16..21].copy_from_slice(b"1.2.0");
+    desc[48..55].copy_from_slice(b"blinker");
+    desc[80..88].copy_from_slice(b"10:12:40");
+    desc[96..107].copy_from_slice(b"Mar  4 2025");
+    desc[112..118].copy_from_slice(b"v5.3.1");
+    desc
+}
+
+#[test]
+fn headers_segments_and_app_description_are_decoded() {
+    let data = image(
+        &[(0x3c02_0020, app_desc()), (0x4037_0000, vec![7; 40])],
+        true,
+    );
+    let info = image_info::parse(&data).unwrap();
+
+    assert_eq!(info.chip.as_deref(), Some("esp32s3"));
+    assert_eq!(info.entry_point, 0x4037_5a04);
+    assert_eq!(info.flash_mode.as_deref(), Some("dio"));
+    assert_eq!(info.flash_size.as_deref(), Some("4MB"));
+    assert_eq!(info.flash_freq.as_deref(), Some("80m"));
+    assert_eq!(info.min_chip_revision, "v0.0");
+    assert_eq!(info.max_chip_revision, "v0.99");
+    assert_eq!(info.segments.len(), 2);
+    assert_eq!(info.segments[0].file_offset, 32);
+    assert_eq!(info.segments[1].load_address, 0x4037_0000);
+    assert_eq!(info.image_len, data.len());
+    assert!(info.checksum_valid);
+    assert_eq!(info.hash_valid, Some(true));
+    assert!(info.problems.is_empty());
+
+    let app = info.app.unwrap();
+    assert_eq!(app.project_name, "blinker");
+    assert_eq!(app.version, "1.2.0");
+    assert_eq!(app.idf_version, "v5.3.1");
+    assert_eq!(app.compiled, "Mar  4 2025 10:12:40");
+}
+
+#[test]
+fn corruption_is_listed_and_refused_for_flashing() {
+    let mut data = image(&[(0x4037_0000, vec![1, 2, 3, 4])], false);
+    data[33] ^= 0xff;
+    let info = image_info::parse(&data).unwrap();
+    assert!(!info.checksum_valid);
+    assert_eq!(info.hash_valid, None);
+    assert!(info.problems[0].contains("Checksum"));
+    assert!(image_info::check(&data).is_err());
+
+    let mut data = image(&[(0x4037_0000, vec![1, 2, 3, 4])], true);
+    let last = data.len() - 1;
+    data[last] ^= 1;
+    assert_eq!(image_info::parse(&data).unwrap().hash_valid, Some(false));
+    assert!(image_info::check(&data).is_err());
+}
+
+#[test]
+fn truncated_files_are_errors() {
+    let data = image(&[(0x4037_0000, vec![5; 100])], true);
+    for len in [10, 40, 100, 135, data.len() - 1] {
+        let result = image_info::parse(&data[..len]);
+        assert!(
+            matches!(&result, Err(AppError::InvalidInput(m)) if m.contains("truncated")),
+            "{} bytes: {:?}",
+            len,
+            result
+        );
+    }
+    assert!(image_info::parse(b"\xaa\x50 partition table").is_err());
+}
+
+#[test]
+fn only_images_for_known_chips_are_checked() {
+    // A FAT image may start with the same byte as a jump instruction
+    let mut fat = vec![0xe9, 0x00, 0x00, 0x00];
+    fat.resize(512, 0);
+    fat[11..13].copy_from_slice(&512u16.to_le_bytes());
+    fat[13] = 1;
+    assert!(!image_info::is_image(&fat));
+    assert!(image_info::check(&fat).is_ok());
+
+    // Merged binaries carry more after the first image
+    let mut merged = image(&[(0x4037_0000, vec![1; 16])], false);
+    merged.resize(0x8000, 0xff);
+    assert!(image_info::check(&merged).is_ok());
+}
+
+#[test]
+fn images_for_later_chip_revisions_are_refused() {
+    let mut data = image(&[(0x4037_0000, vec![1, 2, 3, 4])], false);
+    // Needs v0.2 and up, runs on up to v0.99
+    data[15..17].copy_from_slice(&2u16.to_le_bytes());
+
+    let result = image_info::check_revision(&data, Some((0, 1)));
+    assert!(matches!(result, Err(AppError::WrongChip(m)) if m.contains("v0.2 or later")));
+    assert!(image_info::check_revision(&data, Some((0, 2)))
+        .unwrap()
+        .is_none());
+    let warning = image_info::check_revision(&data, Some((1, 0))).unwrap();
+    assert!(warning.unwrap().contains("up to v0.99"));
+    let warning = image_info::check_revision(&data, None).unwrap();
+    assert!(warning.unwrap().contains("couldn't be read"));
+
+    // Images from before ESP-IDF 5.0 only give the major revision
+    data[14] = 3;
+    data[15..17].copy_from_slice(&0u16.to_le_bytes());
+    data[17..19].copy_from_slice(&u16::MAX.to_le_bytes());
+    assert!(image_info::check_revision(&data, Some((2, 1))).is_err());
+    assert!(image_info::check_revision(&data, Some((3, 1)))
+        .unwrap()
+        .is_none());
+    assert!(image_info::check_revision(b"not an image", None)
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,79 @@
+use esp32dev_core::images;
+use esp32dev_core::models::{AppError, ImageKind};
+
+/// An ESP32-S3 image of one segment holding `contents`, with its checksum.
+fn image(contents: &[u8]) -> Vec<u8> {
+    let mut data = vec![0xe9, 1, 0x02, 0x2f];
+    data.extend_from_slice(&0x4037_5a04u32.to_le_bytes());
+    data.extend_from_slice(&[0xee, 0, 0, 0]);
+    data.extend_from_slice(&9u16.to_le_bytes());
+    data.push(0);
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&99u16.to_le_bytes());
+    data.extend_from_slice(&[0; 5]);
+    data.extend_from_slice(&0x3c02_0020u32.to_le_bytes());
+    data.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+    data.extend_from_slice(contents);
+    while data.len() % 16 != 15 {
+        data.push(0);
+    }
+    data.push(contents.iter().fold(0xef, |sum, b| sum ^ b));
+    data
+}
+
+fn app_desc() -> Vec<u8> {
+    let mut desc = vec![0; 256];
+    desc[..4].copy_from_slice(&0xabcd_5432u32.to_le_bytes());
+    desc[48..55].copy_from_slice(b"blinker");
+    desc
+}
+
+fn partition_table() -> Vec<u8> {
+    let mut entry = vec![0xaa, 0x50, 0x00, 0x00];
+    entry.extend_from_slice(&0x10000u32.to_le_bytes());
+    entry.extend_from_slice(&0x100000u32.to_le_bytes());
+    entry.resize(32, 0);
+    entry
+}
+
+#[test]
+fn apps_and_bootloaders_are_told_apart() {
+    let (kind, address, chip) = images::place(&image(&app_desc())).unwrap();
+    assert_eq!(kind, ImageKind::App);
+    assert_eq!(address, 0x10000);
+    assert_eq!(chip, Some("esp32s3"));
+
+    let (kind, address, _) = images::place(&image(&[7; 64])).unwrap();
+    assert_eq!(kind, ImageKind::Bootloader);
+    assert_eq!(address, 0x0);
+}
+
+#[test]
+fn partition_tables_and_merged_images_are_placed() {
+    let (kind, address, chip) = images::place(&partition_table()).unwrap();
+    assert_eq!(kind, ImageKind::PartitionTable);
+    assert_eq!(address, 0x8000);
+    assert_eq!(chip, None);
+
+    let mut merged = image(&[7; 64]);
+    merged.resize(0x8000, 0xff);
+    merged.extend_from_slice(&partition_table());
+    let (kind, address, chip) = images::place(&merged).unwrap();
+    assert_eq!(kind, ImageKind::Merged);
+    assert_eq!(address, 0x0);
+    assert_eq!(chip, Some("esp32s3"));
+}
+
+#[test]
+fn unknown_or_corrupt_files_are_refused() {
+    assert!(matches!(
+        images::place(b"PK\x03\x04 not firmware"),
+        Err(AppError::InvalidInput(_))
+    ));
+    let mut corrupt = image(&app_desc());
+    corrupt[40] ^= 0xff;
+    assert!(matches!(
+        images::place(&corrupt),
+        Err(AppError::InvalidInput(_))
+    ));
+}
@@ -0,0 +1,147 @@
+//! Improv serial exchanges against a fake device that answers each RPC the
+//! way ESPHome does, with log output mixed in between the frames.
+
+use esp32dev_core::improv::{self, Improv};
+use esp32dev_core::models::{AppError, ImprovDevice};
+use esp32dev_core::tasks::TaskManager;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+/// Plays the device: parses each RPC written to it and queues the reply.
+struct FakeDevice {
+    // Network the device manages to join
+    network: (&'static str, &'static str),
+    written: Vec<u8>,
+    output: VecDeque<u8>,
+}
+
+impl FakeDevice {
+    fn new(ssid: &'static str, password: &'static str) -> Self {
+        FakeDevice {
+            network: (ssid, password),
+            written: Vec::new(),
+            output: b"[I][app:102]: Running through setup()\r\n"
+                .iter()
+                .copied()
+                .collect(),
+        }
+    }
+
+    fn reply(&mut self, kind: u8, data: &[u8]) {
+        self.output.extend(b"[D][wifi:290]: scanning\r\n");
+        self.output.extend(improv::frame(kind, data));
+    }
+
+    fn result(&mut self, command: u8, strings: &[&str]) {
+        let strings = improv::encode_strings(strings);
+        let mut data = vec![command, strings.len() as u8];
+        data.extend(strings);
+        self.reply(improv::TYPE_RPC_RESULT, &data);
+    }
+
+    fn handle(&mut self, command: u8, args: &[u8]) {
+        match command {
+            improv::CMD_CURRENT_STATE => {
+                self.reply(improv::TYPE_CURRENT_STATE, &[improv::STATE_AUTHORIZED])
+            }
+            improv::CMD_DEVICE_INFO => self.result(
+                command,
+                &["ESPHome", "2024.6.0", "ESP32-C3", "kitchen-light"],
+            ),
+            improv::CMD_WIFI_SETTINGS => {
+                let ssid_len = args[0] as usize;
+                let ssid = &args[1..1 + ssid_len];
+                let password = &args[2 + ssid_len..];
+                self.reply(improv::TYPE_CURRENT_STATE, &[improv::STATE_PROVISIONING]);
+                if (ssid, password) == (self.network.0.as_bytes(), self.network.1.as_bytes()) {
+                    self.reply(improv::TYPE_CURRENT_STATE, &[improv::STATE_PROVISIONED]);
+                    self.result(command, &["http://192.168.1.42"]);
+                } else {
+                    // Unable to connect
+                    self.reply(improv::TYPE_ERROR_STATE, &[0x03]);
+                }
+            }
+            _ => self.reply(improv::TYPE_ERROR_STATE, &[0x02]),
+        }
+    }
+}
+
+impl Write for FakeDevice {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        // Header: magic, version, type, length; then command, length, args
+        while self.written.len() >= 9 && self.written.len() >= 10 + self.written[8] as usize {
+            let len = self.written[8] as usize;
+            let frame: Vec<u8> = self.written.drain(..10 + len).collect();
+            assert_eq!(&frame[..6], b"IMPROV");
+            assert_eq!(frame[7], improv::TYPE_RPC);
+            let checksum = frame[..9 + len].iter().fold(0u8, |s, b| s.wrapping_add(*b));
+            assert_eq!(frame[9 + len], checksum);
+            self.handle(frame[9], &frame[11..9 + len]);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for FakeDevice {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Dribble the output out to exercise partial frames
+        let n = buf.len().min(self.output.len()).min(7);
+        for (slot, byte) in buf.iter_mut().zip(self.output.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+}
+
+#[test]
+fn frame_checksum_covers_header_and_payload() {
+    let frame = improv::frame(improv::TYPE_RPC, &[0x02, 0x00]);
+    assert_eq!(&frame[..6], b"IMPROV");
+    assert_eq!(frame[6..9], [1, improv::TYPE_RPC, 2]);
+    let sum = frame[..frame.len() - 1]
+        .iter()
+        .fold(0u8, |s, b| s.wrapping_add(*b));
+    assert_eq!(*frame.last().unwrap(), sum);
+}
+
+#[test]
+fn provisions_and_reports_the_device_url() {
+    let task = TaskManager::default().start("improv", Some("fake"));
+    let mut session = Improv::new(FakeDevice::new("home", "hunter22"));
+
+    assert_eq!(
+        session.current_state(&task).unwrap(),
+        improv::STATE_AUTHORIZED
+    );
+    assert_eq!(
+        session.device_info(&task).unwrap(),
+        ImprovDevice {
+            firmware: "ESPHome".to_string(),
+            version: "2024.6.0".to_string(),
+            chip: "ESP32-C3".to_string(),
+            device_name: "kitchen-light".to_string(),
+        }
+    );
+    let urls = session
+        .send_wifi_settings("home", "hunter22", &task)
+        .unwrap();
+    assert_eq!(urls, ["http://192.168.1.42"]);
+}
+
+#[test]
+fn failed_join_is_reported() {
+    let task = TaskManager::default().start("improv", Some("fake"));
+    let mut session = Improv::new(FakeDevice::new("home", "hunter22"));
+
+    let result = session.send_wifi_settings("home", "wrong", &task);
+    assert!(
+        matches!(result, Err(AppError::Connection(_))),
+        "{:?}",
+        result
+    );
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use common::scratch;
+use esp32dev_core::inventory::{self, Inventory};
+use esp32dev_core::models::{
+    AppError, ChipDetails, DeviceSnapshot, DeviceState, DeviceStatus, InventoryBatch,
+};
+
+fn batch() -> InventoryBatch {
+    InventoryBatch {
+        batch: "B-0142".to_string(),
+        operator: "Kim".to_string(),
+        note: "rev C, \"v2\" antenna".to_string(),
+    }
+}
+
+fn board(
+    port: &str,
+    mac: &str,
+    state: DeviceState,
+    previous: Option<DeviceState>,
+) -> DeviceSnapshot {
+    DeviceSnapshot {
+        key: port.to_string(),
+        state,
+        previous,
+        status: DeviceStatus {
+            code: "ok".to_string(),
+            message: String::new(),
+            port_name: Some(port.to_string()),
+            product_name: Some("USB JTAG/serial debug unit".to_string()),
+            serial_number: Some("F4:12:FA:01:02:03".to_string()),
+            vid_pid: Some("303a:1001".to_string()),
+            connection_type: Some("usb".to_string()),
+        },
+        chip: Some(ChipDetails {
+            chip_model: Some("ESP32-S3".to_string()),
+            mac_address: Some(mac.to_string()),
+            flash_size: Some("8MB".to_string()),
+            chip_revision: Some("v0.2".to_string()),
+            ..Default::default()
+        }),
+        error: None,
+    }
+}
+
+#[test]
+fn identified_boards_are_appended_once() {
+    let dir = scratch("inventory");
+    let path = dir.join("batch.csv");
+    let mut inventory = Inventory::open(&path, batch()).unwrap();
+    assert_eq!(inventory.count(), 0);
+
+    let first = board(
+        "/dev/ttyACM0",
+        "f4:12:fa:01:02:03",
+        DeviceState::Identified,
+        Some(DeviceState::Detected),
+    );
+    let row = inventory.record(&first, 1_700_000_000).unwrap().unwrap();
+    assert_eq!(row.recorded_at, "2023-11-14 22:13:20");
+    assert_eq!(row.chip_model.as_deref(), Some("ESP32-S3"));
+    assert!(!row.duplicate);
+
+    // Not a new identification: detection, re-probes, monitoring
+    let detected = board(
+        "/dev/ttyACM1",
+        "f4:12:fa:0a:0b:0c",
+        DeviceState::Detected,
+        None,
+    );
+    assert!(inventory.record(&detected, 1).unwrap().is_none());
+    let reprobed = board(
+        "/dev/ttyACM0",
+        "f4:12:fa:01:02:03",
+        DeviceState::Identified,
+        Some(DeviceState::Identified),
+    );
+    assert!(inventory.record(&reprobed, 1).unwrap().is_none());
+    let monitoring = board(
+        "/dev/ttyACM0",
+        "f4:12:fa:01:02:03",
+        DeviceState::Monitoring,
+        Some(DeviceState::Identified),
+    );
+    assert!(inventory.record(&monitoring, 1).unwrap().is_none());
+
+    // Plugged in again, remembered from before
+    let again = board(
+        "/dev/ttyACM2",
+        "F4:12:FA:01:02:03",
+        DeviceState::Identified,
+        None,
+    );
+    assert!(inventory.record(&again, 1).unwrap().unwrap().duplicate);
+
+    let second = board(
+        "/dev/ttyACM1",
+        "f4:12:fa:0a:0b:0c",
+        DeviceState::Identified,
+        Some(DeviceState::Detected),
+    );
+    assert!(
+        !inventory
+            .record(&second, 1_700_000_060)
+            .unwrap()
+            .unwrap()
+            .duplicate
+    );
+    assert_eq!(inventory.count(), 2);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], inventory::COLUMNS.join(","));
+    assert_eq!(
+        lines[1],
+        "2023-11-14 22:13:20,f4:12:fa:01:02:03,ESP32-S3,v0.2,8MB,F4:12:FA:01:02:03,\
+         /dev/ttyACM0,B-0142,Kim,\"rev C, \"\"v2\"\" antenna\""
+    );
+}
+
+#[test]
+fn an_existing_inventory_is_carried_on() {
+    let dir = scratch("inventory-resume");
+    let path = dir.join("batch.csv");
+    let mut inventory = Inventory::open(&path, batch()).unwrap();
+    let first = board(
+        "COM3",
+        "f4:12:fa:01:02:03",
+        DeviceState::Identified,
+        Some(DeviceState::Detected),
+    );
+    inventory.record(&first, 0).unwrap();
+
+    let next = InventoryBatch {
+        batch: "B-0143".to_string(),
+        ..Default::default()
+    };
+    let mut inventory = Inventory::open(&path, next).unwrap();
+    assert_eq!(inventory.count(), 1);
+    assert!(inventory.record(&first, 0).unwrap().unwrap().duplicate);
+    let second = board("COM4", "f4:12:fa:0a:0b:0c", DeviceState::Identified, None);
+    assert!(!inventory.record(&second, 0).unwrap().unwrap().duplicate);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text.lines().count(), 3);
+    assert!(text.lines().last().unwrap().ends_with(",COM4,B-0143,,"));
+
+    // Some other CSV is left alone
+    let other = dir.join("parts.csv");
+    std::fs::write(&other, "part,qty\nR1,10\n").unwrap();
+    let result = Inventory::open(&other, batch());
+    assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("not an inventory")));
+    assert_eq!(
+        std::fs::read_to_string(&other).unwrap(),
+        "part,qty\nR1,10\n"
+    );
+}
@@ -0,0 +1,66 @@
+use esp32dev_core::junit;
+use esp32dev_core::models::{
+    BootFault, BootRecord, SoakReport, StepResult, StepStatus, TestReport,
+};
+
+fn step(description: &str, status: StepStatus, detail: Option<&str>) -> StepResult {
+    StepResult {
+        description: description.to_string(),
+        status,
+        duration_ms: 1500,
+        detail: detail.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_reports_become_one_case_per_step() {
+    let report = TestReport {
+        name: "smoke".to_string(),
+        port_name: "/dev/ttyUSB0".to_string(),
+        passed: false,
+        duration_ms: 4500,
+        steps: vec![
+            step("reset", StepStatus::Passed, None),
+            step(
+                "expect /<READY>/",
+                StepStatus::Failed,
+                Some("Timed out waiting for \"<READY>\""),
+            ),
+            step("send \"ping\"", StepStatus::Skipped, None),
+        ],
+    };
+    let xml = junit::test_report(&report);
+    assert!(xml.contains(
+        "<testsuite name=\"smoke\" hostname=\"/dev/ttyUSB0\" tests=\"3\" failures=\"1\" skipped=\"1\" time=\"4.500\">"
+    ));
+    assert!(xml.contains("<testcase classname=\"smoke\" name=\"1. reset\" time=\"1.500\"/>"));
+    assert!(xml.contains("<failure message=\"Timed out waiting for &quot;&lt;READY&gt;&quot;\"/>"));
+    assert!(xml.contains("name=\"3. send &quot;ping&quot;\" time=\"1.500\">\n      <skipped/>"));
+}
+
+#[test]
+fn soak_reports_keep_the_log_of_failed_boots() {
+    let boot = |cycle, faults: Vec<BootFault>, log: &str| BootRecord {
+        cycle,
+        faults,
+        duration_ms: 250,
+        log: log.to_string(),
+    };
+    let report = SoakReport {
+        port_name: "COM3".to_string(),
+        boots: vec![
+            boot(1, vec![], "READY\n"),
+            boot(
+                2,
+                vec![BootFault::Panic],
+                "\u{1b}[0;31mGuru Meditation Error\n",
+            ),
+        ],
+    };
+    let xml = junit::soak_report(&report);
+    assert!(xml.contains("tests=\"2\" failures=\"1\" time=\"0.500\""));
+    assert!(xml.contains("<testcase classname=\"soak\" name=\"boot 1\" time=\"0.250\"/>"));
+    assert!(xml.contains("<failure message=\"panic\"/>"));
+    // Control characters are dropped, XML 1.0 can't carry them
+    assert!(xml.contains("<system-out>[0;31mGuru Meditation Error\n</system-out>"));
+}
@@ -0,0 +1,111 @@
+use esp32dev_core::kiosk::{
+    check_golden, check_unlocked, enter, hash_password, image_hash, unlocks,
+};
+use esp32dev_core::models::{AppError, KioskSettings, Settings};
+
+const GOLDEN: [u8; 64] = [0xE9; 64];
+
+fn golden_image(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("esp32dev-{}-{}", std::process::id(), name));
+    std::fs::write(&path, GOLDEN).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn golden_hash() -> String {
+    image_hash(&GOLDEN)
+}
+
+#[test]
+fn only_the_password_unlocks() {
+    let kiosk = enter(&golden_image("kiosk-pw"), "0x0", &golden_hash(), "line 3").unwrap();
+    assert!(kiosk.enabled);
+    assert!(!kiosk.password_hash.contains("line 3"));
+    assert!(unlocks(&kiosk, "line 3"));
+    assert!(!unlocks(&kiosk, "line 4"));
+    assert!(!unlocks(&kiosk, ""));
+
+    // Salted: the same password hashes differently each time
+    assert_ne!(hash_password("line 3"), hash_password("line 3"));
+
+    let open = KioskSettings {
+        password_hash: hash_password(""),
+        ..kiosk.clone()
+    };
+    assert!(unlocks(&open, "anything"));
+    let broken = KioskSettings {
+        password_hash: "not a hash".to_string(),
+        ..kiosk
+    };
+    assert!(!unlocks(&broken, "not a hash"));
+}
+
+#[test]
+fn entering_checks_the_image_and_address() {
+    let image = golden_image("kiosk-check");
+    let kiosk = enter(&image, " 0x10000 ", &golden_hash(), "").unwrap();
+    assert_eq!(kiosk.address, "0x10000");
+    assert_eq!(kiosk.golden_hash, golden_hash());
+    assert_eq!((kiosk.passed, kiosk.failed), (0, 0));
+
+    assert!(matches!(
+        enter("/no/such/golden.bin", "0x0", &golden_hash(), ""),
+        Err(AppError::InvalidInput(m)) if m.contains("not found")
+    ));
+    assert!(enter(&image, "ten", &golden_hash(), "").is_err());
+}
+
+#[test]
+fn entering_registers_the_golden_hash() {
+    let image = golden_image("kiosk-register");
+    // As `sha256sum` prints it, in either case
+    let line = format!("{}  golden.bin", golden_hash().to_uppercase());
+    assert_eq!(
+        enter(&image, "0x0", &line, "").unwrap().golden_hash,
+        golden_hash()
+    );
+
+    assert!(matches!(
+        enter(&image, "0x0", &image_hash(b"other"), ""),
+        Err(AppError::InvalidInput(m)) if m.contains(&golden_hash())
+    ));
+    assert!(enter(&image, "0x0", "", "").is_err());
+    assert!(enter(&image, "0x0", "e3b0c442", "").is_err());
+}
+
+#[test]
+fn only_the_golden_image_passes_the_check() {
+    let kiosk = enter(&golden_image("kiosk-gate"), "0x0", &golden_hash(), "").unwrap();
+    assert!(check_golden(&kiosk, &GOLDEN).is_ok());
+
+    let mut swapped = GOLDEN;
+    swapped[63] = 0;
+    assert!(matches!(
+        check_golden(&kiosk, &swapped),
+        Err(AppError::InvalidInput(m)) if m.contains("no longer matches")
+    ));
+
+    // Kiosk mode entered before hashes were registered
+    let unregistered = KioskSettings {
+        golden_hash: String::new(),
+        ..kiosk
+    };
+    assert!(check_golden(&unregistered, &GOLDEN).is_err());
+}
+
+#[test]
+fn older_settings_leave_kiosk_mode_off() {
+    let settings: Settings = serde_json::from_str(r#"{"theme": "light"}"#).unwrap();
+    assert!(!settings.kiosk.enabled);
+    assert!(unlocks(&settings.kiosk, ""));
+}
+
+#[test]
+fn other_flashing_is_refused_in_production_mode() {
+    let mut kiosk = KioskSettings::default();
+    assert!(check_unlocked(&kiosk).is_ok());
+    kiosk.enabled = true;
+    assert!(matches!(
+        check_unlocked(&kiosk),
+        Err(AppError::InvalidInput(_))
+    ));
+}
@@ -0,0 +1,64 @@
+use esp32dev_core::label::{format_date, label_size, render_png, render_svg, DeviceLabel};
+use esp32dev_core::models::AppError;
+
+fn label() -> DeviceLabel {
+    DeviceLabel {
+        mac: Some("7c:df:a1:00:00:01".to_string()),
+        serial_number: Some("A1B2C3".to_string()),
+        flashed_at: Some(1_700_000_000),
+    }
+}
+
+#[test]
+fn dates_are_utc_days() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(1_700_000_000), "2023-11-14");
+    assert_eq!(format_date(951_782_400), "2000-02-29");
+}
+
+#[test]
+fn payload_has_a_line_per_known_field() {
+    assert_eq!(
+        label().payload(),
+        "MAC:7c:df:a1:00:00:01\nSN:A1B2C3\nFLASHED:2023-11-14"
+    );
+    let partial = DeviceLabel {
+        serial_number: Some("A1B2C3".to_string()),
+        ..DeviceLabel::default()
+    };
+    assert_eq!(partial.payload(), "SN:A1B2C3");
+}
+
+#[test]
+fn empty_labels_are_refused() {
+    let size = label_size("dk11201").unwrap();
+    let result = render_svg(&DeviceLabel::default(), &size);
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+}
+
+#[test]
+fn svg_prints_the_fields_beside_the_code_on_wide_labels() {
+    let svg = render_svg(&label(), &label_size("dk11201").unwrap()).unwrap();
+    assert!(svg.contains("width=\"90mm\" height=\"29mm\""));
+    assert!(svg.contains("<path transform="));
+    assert!(svg.contains(">SN:A1B2C3</text>"));
+
+    let square = render_svg(&label(), &label_size("square25").unwrap()).unwrap();
+    assert!(!square.contains("<text"));
+}
+
+#[test]
+fn png_is_the_whole_label_at_the_printer_resolution() {
+    let png = render_png(&label(), &label_size("dk11201").unwrap(), 300).unwrap();
+    let decoder = png::Decoder::new(png.as_slice());
+    let reader = decoder.read_info().unwrap();
+    let info = reader.info();
+    assert_eq!((info.width, info.height), (1063, 343));
+    assert_eq!(info.color_type, png::ColorType::Grayscale);
+}
+
+#[test]
+fn png_codes_too_small_to_print_are_refused() {
+    let result = render_png(&label(), &label_size("dk11204").unwrap(), 20);
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+}
@@ -0,0 +1,83 @@
+use esp32dev_core::lifecycle::DeviceTracker;
+use esp32dev_core::models::{ChipDetails, DeviceState, DeviceStatus};
+
+fn board(port: &str, serial: Option<&str>) -> DeviceStatus {
+    DeviceStatus {
+        code: "ok".to_string(),
+        message: String::new(),
+        port_name: Some(port.to_string()),
+        product_name: None,
+        serial_number: serial.map(str::to_string),
+        vid_pid: Some("303A:1001".to_string()),
+        connection_type: None,
+    }
+}
+
+fn chip(mac: &str) -> ChipDetails {
+    ChipDetails {
+        chip_model: Some("ESP32-S3".to_string()),
+        mac_address: Some(mac.to_string()),
+        ..Default::default()
+    }
+}
+
+fn state(tracker: &DeviceTracker, port: &str) -> Option<DeviceState> {
+    tracker
+        .list()
+        .into_iter()
+        .find(|d| d.key == port)
+        .map(|d| d.state)
+}
+
+#[test]
+fn returning_board_is_identified_from_the_cache() {
+    let tracker = DeviceTracker::default();
+    tracker.sync(vec![board("/dev/ttyACM0", Some("AB12"))]);
+    assert_eq!(tracker.unidentified(), ["/dev/ttyACM0"]);
+    assert!(tracker.cached_chip("/dev/ttyACM0").is_none());
+
+    tracker.identified("/dev/ttyACM0", &Ok(chip("aa:bb")));
+    assert!(tracker.unidentified().is_empty());
+
+    // Unplugged, then back on another port
+    tracker.sync(vec![]);
+    tracker.sync(vec![board("/dev/ttyACM1", Some("AB12"))]);
+    assert_eq!(
+        state(&tracker, "/dev/ttyACM1"),
+        Some(DeviceState::Identified)
+    );
+    let cached = tracker.cached_chip("/dev/ttyACM1").unwrap();
+    assert_eq!(cached.mac_address.as_deref(), Some("aa:bb"));
+    // Still re-probed once in the background
+    assert_eq!(tracker.unidentified(), ["/dev/ttyACM1"]);
+    tracker.identified("/dev/ttyACM1", &Ok(chip("aa:bb")));
+    assert!(tracker.unidentified().is_empty());
+
+    tracker.refresh("/dev/ttyACM1");
+    assert_eq!(tracker.unidentified(), ["/dev/ttyACM1"]);
+}
+
+#[test]
+fn other_boards_are_not_taken_for_cached_ones() {
+    let tracker = DeviceTracker::default();
+    tracker.sync(vec![board("/dev/ttyACM0", Some("AB12"))]);
+    tracker.identified("/dev/ttyACM0", &Ok(chip("aa:bb")));
+    tracker.sync(vec![]);
+
+    tracker.sync(vec![board("/dev/ttyACM0", Some("CD34"))]);
+    assert_eq!(state(&tracker, "/dev/ttyACM0"), Some(DeviceState::Detected));
+    assert!(tracker.cached_chip("/dev/ttyACM0").is_none());
+}
+
+#[test]
+fn boards_without_a_serial_number_are_not_remembered() {
+    let tracker = DeviceTracker::default();
+    tracker.sync(vec![board("/dev/ttyUSB0", None)]);
+    tracker.identified("/dev/ttyUSB0", &Ok(chip("aa:bb")));
+    tracker.sync(vec![]);
+
+    // Could be another board behind the same bridge
+    tracker.sync(vec![board("/dev/ttyUSB0", None)]);
+    assert_eq!(state(&tracker, "/dev/ttyUSB0"), Some(DeviceState::Detected));
+    assert!(tracker.cached_chip("/dev/ttyUSB0").is_none());
+}
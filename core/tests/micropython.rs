@@ -0,0 +1,213 @@
+//! Raw REPL exchanges against a fake MicroPython board. The fake doesn't
+//! run Python; it keeps the snippets it was sent and answers each with
+//! canned output, framed the way the real raw REPL frames it.
+
+use esp32dev_core::micropython::RawRepl;
+use esp32dev_core::models::{AppError, MpyEntry};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+type Answer = fn(&str) -> (String, String);
+
+const NAME_ERROR: &str = "Traceback (most recent call last):\r\n  \
+    File \"<stdin>\", line 1, in <module>\r\nNameError: name 'x' isn't defined\r\n";
+
+struct FakeBoard {
+    // `None` plays a board without a REPL
+    answer: Option<Answer>,
+    raw: bool,
+    code: Vec<u8>,
+    executed: Vec<String>,
+    output: VecDeque<u8>,
+}
+
+impl FakeBoard {
+    fn new(answer: Option<Answer>) -> Self {
+        FakeBoard {
+            answer,
+            raw: false,
+            code: Vec::new(),
+            executed: Vec::new(),
+            // A program printing away until interrupted
+            output: b"tick\r\ntick\r\n".iter().copied().collect(),
+        }
+    }
+}
+
+impl Write for FakeBoard {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(answer) = self.answer else {
+            return Ok(buf.len());
+        };
+        for &b in buf {
+            match (self.raw, b) {
+                (_, 0x03) => self.output.extend(b"\r\nKeyboardInterrupt\r\n>>> "),
+                (false, 0x01) => {
+                    self.raw = true;
+                    self.output.extend(b"\r\nraw REPL; CTRL-B to exit\r\n>");
+                }
+                (true, 0x02) => {
+                    self.raw = false;
+                    self.output.extend(b"\r\nMicroPython v1.23.0\r\n>>> ");
+                }
+                (true, 0x04) => {
+                    let code = String::from_utf8(std::mem::take(&mut self.code)).unwrap();
+                    let (stdout, stderr) = answer(&code);
+                    self.executed.push(code);
+                    self.output.extend(b"OK");
+                    self.output.extend(stdout.as_bytes());
+                    self.output.push_back(0x04);
+                    self.output.extend(stderr.as_bytes());
+                    self.output.extend(b"\x04>");
+                }
+                (true, b'\r') if self.code.is_empty() => {}
+                (true, b) => self.code.push(b),
+                (false, _) => {}
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for FakeBoard {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Dribble the output out to exercise split markers
+        let n = buf.len().min(self.output.len()).min(5);
+        for (slot, byte) in buf.iter_mut().zip(self.output.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+}
+
+/// Undoes the bytes literal of a `w(b'...')` snippet.
+fn written_bytes(snippet: &str) -> Vec<u8> {
+    let literal = snippet
+        .strip_prefix("w(b'")
+        .and_then(|s| s.strip_suffix("')"))
+        .unwrap();
+    let mut data = Vec::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            data.push(c as u8);
+            continue;
+        }
+        match chars.next().unwrap() {
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                data.push(u8::from_str_radix(&hex, 16).unwrap());
+            }
+            c => data.push(c as u8),
+        }
+    }
+    data
+}
+
+fn session(answer: Answer) -> RawRepl<FakeBoard> {
+    let mut repl = RawRepl::new(FakeBoard::new(Some(answer)));
+    repl.enter().unwrap();
+    repl
+}
+
+#[test]
+fn exec_returns_output_and_maps_exceptions() {
+    let mut repl = session(|code| match code {
+        "print(1 + 1)" => ("2\r\n".to_string(), String::new()),
+        _ => (String::new(), NAME_ERROR.to_string()),
+    });
+    assert_eq!(repl.exec("print(1 + 1)").unwrap(), "2\r\n");
+    match repl.exec("x") {
+        Err(AppError::InvalidInput(message)) => {
+            assert_eq!(message, "MicroPython: NameError: name 'x' isn't defined")
+        }
+        other => panic!("{:?}", other.map_err(|e| e.to_string())),
+    }
+    // Still in sync after the error
+    assert_eq!(repl.exec("print(1 + 1)").unwrap(), "2\r\n");
+}
+
+#[test]
+fn run_streams_output_and_traceback() {
+    let mut repl = session(|code| {
+        assert!(code.starts_with("import time\n"), "{}", code);
+        ("tick\r\ntock\r\n".to_string(), NAME_ERROR.to_string())
+    });
+    let mut chunks = 0;
+    let mut output = Vec::new();
+    let result = repl.run(
+        "import time\nprint('tick')\nprint('tock')\nx",
+        || false,
+        |bytes| {
+            chunks += 1;
+            output.extend_from_slice(bytes);
+        },
+    );
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+    let text = String::from_utf8(output).unwrap();
+    assert_eq!(text, format!("tick\r\ntock\r\n{}", NAME_ERROR));
+    // Passed on as it came, not all at the end
+    assert!(chunks > 2);
+}
+
+#[test]
+fn listing_puts_directories_first() {
+    let mut repl = session(|code| {
+        assert!(code.contains("p='/lib'"), "{}", code);
+        (
+            "0\t1432\tmain.py\r\n1\t0\tdrivers\r\n0\t88\tmy file.txt\r\n".to_string(),
+            String::new(),
+        )
+    });
+    let entry = |name: &str, is_dir, size| MpyEntry {
+        name: name.to_string(),
+        is_dir,
+        size,
+    };
+    assert_eq!(
+        repl.list("/lib").unwrap(),
+        [
+            entry("drivers", true, 0),
+            entry("main.py", false, 1432),
+            entry("my file.txt", false, 88),
+        ]
+    );
+}
+
+#[test]
+fn files_are_read_from_hex() {
+    let mut repl = session(|_| ("68656c6c6f\r\n0a00ff\r\n".to_string(), String::new()));
+    assert_eq!(repl.read_file("/boot.py").unwrap(), b"hello\n\x00\xff");
+}
+
+#[test]
+fn files_are_written_in_chunks() {
+    let mut board = FakeBoard::new(Some(|_: &str| (String::new(), String::new())));
+    let data: Vec<u8> = (0..600u32).map(|i| (i * 7 % 256) as u8).collect();
+    let progress = RefCell::new(Vec::new());
+    {
+        let mut repl = RawRepl::new(&mut board);
+        repl.enter().unwrap();
+        repl.write_file("/it's.bin", &data, |n| progress.borrow_mut().push(n))
+            .unwrap();
+    }
+    assert_eq!(*progress.borrow(), [256, 512, 600]);
+    assert_eq!(board.executed[0], "f=open('/it\\'s.bin','wb')\nw=f.write");
+    assert_eq!(board.executed.last().unwrap(), "f.close()");
+    let sent: Vec<u8> = board.executed[1..board.executed.len() - 1]
+        .iter()
+        .flat_map(|snippet| written_bytes(snippet))
+        .collect();
+    assert_eq!(sent, data);
+}
+
+#[test]
+fn silent_port_is_not_micropython() {
+    let mut repl = RawRepl::new(FakeBoard::new(None));
+    assert!(matches!(repl.enter(), Err(AppError::Timeout(_))));
+}
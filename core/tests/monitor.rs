@@ -0,0 +1,102 @@
+use esp32dev_core::models::TextEncoding;
+use esp32dev_core::monitor::Monitor;
+
+#[test]
+fn characters_split_across_reads_stay_whole() {
+    let monitor = Monitor::default();
+    let text = "température: 21 °C\n";
+    for byte in text.as_bytes().chunks(1) {
+        monitor.output(byte);
+    }
+    // Binary noise doesn't swallow the text after it
+    monitor.output(&[0xff, 0xfe, b'o', b'k', b'\n']);
+    monitor.output(&[0xe2, 0x82]);
+    monitor.output(&[0xac, b'\n']);
+
+    let lines = monitor.lines(None, 10).lines;
+    assert_eq!(lines, ["température: 21 °C", "\u{fffd}\u{fffd}ok", "€"]);
+}
+
+#[test]
+fn repeated_lines_fold_into_one_with_their_raw_copies() {
+    let monitor = Monitor::default();
+    monitor.output(b"I (10) app: start\r\n");
+    monitor.output(b"E (5010) task_wdt: Task watchdog got triggered\r\n");
+    monitor.output(b"E (10010) task_wdt: Task watchdog got triggered\r\n");
+    monitor.output(b"E (15010) task_wdt: Task watchdog got triggered\r\n");
+    monitor.output(b"E (15010) task_wdt: - IDLE (CPU 0)\r\nok\nok\n");
+
+    let window = monitor.lines(None, 10);
+    assert_eq!(
+        window.lines,
+        [
+            "I (10) app: start",
+            "E (5010) task_wdt: Task watchdog got triggered",
+            "E (15010) task_wdt: - IDLE (CPU 0)",
+            "ok",
+        ]
+    );
+    assert_eq!(window.repeats, [0, 2, 0, 1]);
+    assert_eq!(window.end, 4);
+    assert_eq!(
+        monitor.repeats(1),
+        [
+            "E (5010) task_wdt: Task watchdog got triggered",
+            "E (10010) task_wdt: Task watchdog got triggered",
+            "E (15010) task_wdt: Task watchdog got triggered",
+        ]
+    );
+    assert!(monitor.repeats(9).is_empty());
+}
+
+#[test]
+fn a_note_finishes_the_partial_line_before_it() {
+    let monitor = Monitor::default();
+    monitor.output(b"tick\ntick");
+    monitor.note("> status");
+
+    // The cut-off repeat still folds into the line before
+    let window = monitor.lines(None, 10);
+    assert_eq!(window.lines, ["tick", "> status"]);
+    assert_eq!(window.repeats, [1, 0]);
+}
+
+#[test]
+fn lines_differing_outside_the_timestamp_stay_apart() {
+    let monitor = Monitor::default();
+    monitor.output(b"I (100) sensor: 21.5\nI (200) sensor: 21.6\nI (300) sensor: 21.6\n");
+    let window = monitor.lines(None, 10);
+    assert_eq!(
+        window.lines,
+        ["I (100) sensor: 21.5", "I (200) sensor: 21.6"]
+    );
+    assert_eq!(window.repeats, [0, 1]);
+}
+
+#[test]
+fn local_echo_is_on_until_turned_off() {
+    let monitor = Monitor::default();
+    assert!(monitor.status().local_echo);
+    monitor.set_local_echo(false);
+    assert!(!monitor.local_echo());
+    assert!(!monitor.status().local_echo);
+    // Nothing to send to: no echo of a line that never went out
+    assert!(monitor.send_line("help").is_err());
+    assert!(monitor.lines(None, 10).lines.is_empty());
+}
+
+#[test]
+fn output_is_decoded_in_the_session_encoding() {
+    let monitor = Monitor::default();
+    monitor.set_encoding(TextEncoding::Gbk);
+    assert_eq!(monitor.status().encoding, TextEncoding::Gbk);
+    // "中文", split inside a character
+    monitor.output(&[0xd6, 0xd0, 0xce]);
+    monitor.output(&[0xc4, b'\n']);
+
+    monitor.set_encoding(TextEncoding::Latin1);
+    monitor.output(b"caf\xe9\n");
+
+    let lines = monitor.lines(None, 10).lines;
+    assert_eq!(lines, ["中文", "café"]);
+}
@@ -0,0 +1,104 @@
+//! MicroPython downloads must pick the right board and offset, and never
+//! keep an image that fails its checks.
+
+use esp32dev_core::models::{AppError, MpyRelease};
+use esp32dev_core::mpy_firmware;
+
+const PAGE: &str = r#"
+<h2>Firmware (Nightly builds)</h2>
+<a href="/resources/firmware/ESP32_GENERIC_S3-20240626-v1.24.0-preview.39.g411d66586.bin">v1.24.0-preview.39</a>
+<h2>Firmware (Releases)</h2>
+<a href="/resources/firmware/ESP32_GENERIC_S3-20240222-v1.22.2.bin">v1.22.2</a>
+<a href="/resources/firmware/ESP32_GENERIC_S3-20240602-v1.23.0.bin">v1.23.0</a>
+<a href="/resources/firmware/ESP32_GENERIC_S3-20240602-v1.23.0.bin">(.bin)</a>
+<a href="/resources/firmware/ESP32_GENERIC_S3-20240602-v1.23.0.app-bin">(.app-bin)</a>
+<a href="/resources/firmware/ESP32_GENERIC_S3_SPIRAM_OCT-20240602-v1.23.0.bin">(.bin)</a>
+"#;
+
+fn release() -> MpyRelease {
+    MpyRelease {
+        version: "v1.23.0".to_string(),
+        date: "20240602".to_string(),
+        file_name: "ESP32_GENERIC_S3-20240602-v1.23.0.bin".to_string(),
+        url: String::new(),
+        preview: false,
+    }
+}
+
+/// Header of a merged image built for chip id `chip_id`.
+fn image(chip_id: u16) -> Vec<u8> {
+    let mut data = vec![0xe9, 3, 2, 0x2f, 0, 0, 0, 0, 0xee, 0, 0, 0];
+    data.extend_from_slice(&chip_id.to_le_bytes());
+    data.resize(64, 0xff);
+    data
+}
+
+fn sha256(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[test]
+fn boards_and_offsets_follow_the_chip() {
+    assert_eq!(mpy_firmware::board("ESP32"), Some("ESP32_GENERIC"));
+    assert_eq!(mpy_firmware::board("esp32s3"), Some("ESP32_GENERIC_S3"));
+    assert_eq!(mpy_firmware::board("ESP32-C3"), Some("ESP32_GENERIC_C3"));
+    assert_eq!(mpy_firmware::offset("ESP32"), Some(0x1000));
+    assert_eq!(mpy_firmware::offset("ESP32-S3"), Some(0x0));
+    assert_eq!(mpy_firmware::board("esp32h2"), None);
+}
+
+#[test]
+fn releases_come_before_previews_newest_first() {
+    let releases = mpy_firmware::parse_releases("ESP32_GENERIC_S3", PAGE);
+    let versions: Vec<&str> = releases.iter().map(|r| r.version.as_str()).collect();
+    assert_eq!(
+        versions,
+        ["v1.23.0", "v1.22.2", "v1.24.0-preview.39.g411d66586"]
+    );
+    assert!(releases[2].preview);
+    assert_eq!(
+        releases[0].url,
+        "https://micropython.org/resources/firmware/ESP32_GENERIC_S3-20240602-v1.23.0.bin"
+    );
+}
+
+#[test]
+fn images_for_another_chip_are_refused() {
+    assert!(mpy_firmware::verify_image(&image(9), "ESP32-S3").is_ok());
+    assert!(matches!(
+        mpy_firmware::verify_image(&image(5), "ESP32-S3"),
+        Err(AppError::WrongChip(_))
+    ));
+    assert!(matches!(
+        mpy_firmware::verify_image(b"<html>Not Found</html>", "ESP32-S3"),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn checksums_are_verified_before_caching() {
+    let cache = std::env::temp_dir().join(format!("esp32dev-{}-mpy", std::process::id()));
+    let data = image(9);
+
+    let wrong = "00".repeat(32);
+    let refused = mpy_firmware::store(&cache, &release(), &data, Some(&wrong), "ESP32-S3");
+    assert!(matches!(refused, Err(AppError::InvalidInput(_))));
+    assert!(!cache.join(release().file_name).exists());
+
+    let stored = mpy_firmware::store(
+        &cache,
+        &release(),
+        &data,
+        Some(&sha256(&data).to_uppercase()),
+        "ESP32-S3",
+    )
+    .unwrap();
+    assert_eq!(stored.sha256, sha256(&data));
+    assert_eq!(stored.offset, 0);
+    assert_eq!(std::fs::read(&stored.path).unwrap(), data);
+    std::fs::remove_dir_all(&cache).ok();
+}
@@ -0,0 +1,80 @@
+//! Monitor network mode: the test plays a board forwarding its logs to the
+//! listener over localhost.
+
+use esp32dev_core::models::{AppError, NetLogProtocol};
+use esp32dev_core::monitor::Monitor;
+use std::io::Write;
+use std::net::{TcpStream, UdpSocket};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const WAIT: Duration = Duration::from_secs(3);
+
+/// Collects monitor output until `needle` shows up.
+fn receive_until(rx: &mpsc::Receiver<Vec<u8>>, needle: &str) -> String {
+    let deadline = Instant::now() + WAIT;
+    let mut text = String::new();
+    while !text.contains(needle) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(left) {
+            Ok(bytes) => text.push_str(&String::from_utf8_lossy(&bytes)),
+            Err(_) => panic!("no {:?} in {:?}", needle, text),
+        }
+    }
+    text
+}
+
+fn listen(monitor: &Monitor, protocol: NetLogProtocol) -> (u16, mpsc::Receiver<Vec<u8>>) {
+    let (tx, rx) = mpsc::channel();
+    let port = monitor
+        .listen(protocol, 0, move |bytes| {
+            let _ = tx.send(bytes.to_vec());
+        })
+        .expect("monitor listen");
+    (port, rx)
+}
+
+#[test]
+fn udp_messages_become_lines() {
+    let monitor = Monitor::default();
+    let (port, rx) = listen(&monitor, NetLogProtocol::Udp);
+    assert_eq!(monitor.status().state, "listening");
+    assert_eq!(monitor.status().port_name, Some(format!("udp:{}", port)));
+    assert_eq!(monitor.active_port(), None);
+
+    let board = UdpSocket::bind("127.0.0.1:0").unwrap();
+    board
+        .send_to(b"<14>I (240) app: ready", ("127.0.0.1", port))
+        .unwrap();
+    board
+        .send_to(b"W (250) wifi: retry\n", ("127.0.0.1", port))
+        .unwrap();
+    receive_until(&rx, "retry");
+    assert_eq!(
+        monitor.lines(None, 10).lines,
+        ["I (240) app: ready", "W (250) wifi: retry"]
+    );
+
+    // Nothing to write to on the other end
+    assert!(matches!(monitor.send(b"x"), Err(AppError::NotConnected)));
+    monitor.disconnect().unwrap();
+    assert_eq!(monitor.status().state, "disconnected");
+}
+
+#[test]
+fn tcp_clients_are_read_until_they_leave() {
+    let monitor = Monitor::default();
+    let (port, rx) = listen(&monitor, NetLogProtocol::Tcp);
+
+    let mut first = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    first.write_all(b"boot\r\nready\r\n").unwrap();
+    receive_until(&rx, "ready");
+    drop(first);
+
+    let mut second = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    second.write_all(b"again\r\n").unwrap();
+    receive_until(&rx, "again");
+    assert_eq!(monitor.lines(None, 10).lines, ["boot", "ready", "again"]);
+
+    monitor.disconnect().unwrap();
+}
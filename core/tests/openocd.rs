@@ -0,0 +1,80 @@
+use esp32dev_core::models::{AppError, JtagProbe};
+use esp32dev_core::openocd;
+
+fn probe(kind: &str, serial: Option<&str>) -> JtagProbe {
+    JtagProbe {
+        kind: kind.to_string(),
+        vid_pid: if kind == "usb_jtag" {
+            "303A:1001"
+        } else {
+            "0403:6010"
+        }
+        .to_string(),
+        serial_number: serial.map(str::to_string),
+        product_name: None,
+    }
+}
+
+#[test]
+fn the_adapter_fits_the_chip() {
+    let found = [probe("esp_prog", None), probe("usb_jtag", None)];
+    assert_eq!(
+        openocd::pick_probe(&found, "ESP32-S3").unwrap().kind,
+        "usb_jtag"
+    );
+    assert_eq!(
+        openocd::pick_probe(&found, "esp32").unwrap().kind,
+        "esp_prog"
+    );
+
+    // The classic ESP32 has no USB, and another board's USB-JTAG won't do
+    let found = [probe("usb_jtag", None)];
+    assert!(openocd::pick_probe(&found, "ESP32").is_none());
+    assert_eq!(
+        openocd::pick_probe(&found, "esp32c3").unwrap().kind,
+        "usb_jtag"
+    );
+}
+
+#[test]
+fn config_sources_the_interface_and_target() {
+    let cfg = openocd::config(
+        "ESP32-C3",
+        &probe("usb_jtag", Some("F4:12:FA:01:02:03")),
+        3333,
+    )
+    .unwrap();
+    let lines: Vec<&str> = cfg.lines().skip(1).collect();
+    assert_eq!(
+        lines,
+        [
+            "source [find interface/esp_usb_jtag.cfg]",
+            "adapter serial F4:12:FA:01:02:03",
+            "adapter speed 40000",
+            "source [find target/esp32c3.cfg]",
+            "gdb_port 3333",
+            "telnet_port 4444",
+        ]
+    );
+
+    let cfg = openocd::config("esp32", &probe("esp_prog", None), 3334).unwrap();
+    assert!(cfg.contains("source [find interface/ftdi/esp32_devkitj_v1.cfg]\n"));
+    assert!(cfg.contains("source [find target/esp32.cfg]\n"));
+    assert!(cfg.contains("gdb_port 3334\n"));
+    assert!(!cfg.contains("adapter serial"));
+
+    let err = openocd::config("esp32s2", &probe("usb_jtag", None), 3333).unwrap_err();
+    assert!(matches!(err, AppError::WrongChip(_)));
+    let err = openocd::config("esp8266", &probe("esp_prog", None), 3333).unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput(_)));
+}
+
+#[test]
+fn ready_once_gdb_can_attach() {
+    assert!(openocd::is_ready(
+        "Info : Listening on port 3333 for gdb connections"
+    ));
+    assert!(!openocd::is_ready(
+        "Info : Listening on port 4444 for telnet connections"
+    ));
+}
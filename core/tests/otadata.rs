@@ -0,0 +1,112 @@
+use esp32dev_core::models::AppError;
+use esp32dev_core::otadata::{partitions, select_entry_crc, OtaLayout};
+
+fn entry(kind: u8, subtype: u8, offset: u32, size: u32, label: &str) -> Vec<u8> {
+    let mut entry = vec![0xaa, 0x50, kind, subtype];
+    entry.extend_from_slice(&offset.to_le_bytes());
+    entry.extend_from_slice(&size.to_le_bytes());
+    let mut name = [0u8; 16];
+    name[..label.len()].copy_from_slice(label.as_bytes());
+    entry.extend_from_slice(&name);
+    entry.extend_from_slice(&[0; 4]);
+    entry
+}
+
+// The default "two OTA definitions" table, erased after the last entry
+fn table(with_otadata: bool) -> Vec<u8> {
+    let mut table = entry(0x01, 0x02, 0x9000, 0x4000, "nvs");
+    if with_otadata {
+        table.extend(entry(0x01, 0x00, 0xd000, 0x2000, "otadata"));
+    }
+    table.extend(entry(0x01, 0x01, 0xf000, 0x1000, "phy_init"));
+    table.extend(entry(0x00, 0x10, 0x10000, 0x100000, "ota_0"));
+    table.extend(entry(0x00, 0x11, 0x110000, 0x100000, "ota_1"));
+    table.resize(0xc00, 0xff);
+    table
+}
+
+fn seq(sector: &[u8]) -> u32 {
+    u32::from_le_bytes(sector[..4].try_into().unwrap())
+}
+
+fn crc(sector: &[u8]) -> u32 {
+    u32::from_le_bytes(sector[28..32].try_into().unwrap())
+}
+
+#[test]
+fn partition_entries_are_read_up_to_the_erased_end() {
+    let parts = partitions(&table(true));
+    let labels: Vec<_> = parts.iter().map(|p| p.label.as_str()).collect();
+    assert_eq!(labels, ["nvs", "otadata", "phy_init", "ota_0", "ota_1"]);
+    assert_eq!(parts[3].offset, 0x10000);
+    assert_eq!(parts[3].size, 0x100000);
+}
+
+#[test]
+fn select_entry_crc_matches_the_bootloader() {
+    assert_eq!(select_entry_crc(1), 0x4743_989a);
+    assert_eq!(select_entry_crc(2), 0x55f6_3774);
+    assert_eq!(select_entry_crc(3), 0xed4a_5011);
+}
+
+#[test]
+fn updates_alternate_between_the_slots() {
+    let layout = OtaLayout::from_table(&table(true)).unwrap();
+    let mut otadata = vec![0xff; 0x2000];
+    assert_eq!(layout.active_slot(&otadata), None);
+
+    // Blank otadata boots ota_0, so the first update goes there
+    let first = layout.update(&otadata, 0x1000).unwrap();
+    assert_eq!((first.slot, first.label.as_str()), (0, "ota_0"));
+    assert_eq!(first.app_offset, 0x10000);
+    assert_eq!(first.otadata_offset, 0xd000);
+    assert_eq!(first.otadata.len(), 0x1000);
+    assert_eq!(seq(&first.otadata), 1);
+    assert_eq!(crc(&first.otadata), 0x4743_989a);
+    otadata[..0x1000].copy_from_slice(&first.otadata);
+    assert_eq!(layout.active_slot(&otadata), Some(0));
+
+    let second = layout.update(&otadata, 0x1000).unwrap();
+    assert_eq!((second.slot, second.label.as_str()), (1, "ota_1"));
+    assert_eq!(second.app_offset, 0x110000);
+    assert_eq!(second.otadata_offset, 0xe000);
+    assert_eq!(seq(&second.otadata), 2);
+    assert_eq!(crc(&second.otadata), 0x55f6_3774);
+    otadata[0x1000..].copy_from_slice(&second.otadata);
+    assert_eq!(layout.active_slot(&otadata), Some(1));
+
+    let third = layout.update(&otadata, 0x1000).unwrap();
+    assert_eq!(third.slot, 0);
+    assert_eq!(third.otadata_offset, 0xd000);
+    assert_eq!(seq(&third.otadata), 3);
+    assert_eq!(crc(&third.otadata), 0xed4a_5011);
+}
+
+#[test]
+fn entries_with_a_bad_crc_are_ignored() {
+    let layout = OtaLayout::from_table(&table(true)).unwrap();
+    let mut otadata = vec![0xff; 0x2000];
+    otadata[..4].copy_from_slice(&2u32.to_le_bytes());
+    otadata[28..32].copy_from_slice(&0u32.to_le_bytes());
+    assert_eq!(layout.active_slot(&otadata), None);
+    assert_eq!(layout.update(&otadata, 0x1000).unwrap().slot, 0);
+}
+
+#[test]
+fn apps_bigger_than_the_slot_are_refused() {
+    let layout = OtaLayout::from_table(&table(true)).unwrap();
+    let result = layout.update(&[0xff; 0x2000], 0x100001);
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+}
+
+#[test]
+fn tables_without_otadata_are_refused() {
+    assert!(matches!(
+        OtaLayout::from_table(&table(false)),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        OtaLayout::from_table(&[0xff; 0xc00]),
+        Err(AppError::InvalidInput(_))
+    ));
+}
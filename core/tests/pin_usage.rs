@@ -0,0 +1,59 @@
+use esp32dev_core::models::{AppError, PinRole, PinUsage};
+use esp32dev_core::pin_usage::{from_sdkconfig, parse};
+
+#[test]
+fn sdkconfig_options_naming_pins_are_picked_up() {
+    let sdkconfig = "\
+# Example Configuration
+CONFIG_EXAMPLE_I2C_MASTER_SDA=21
+CONFIG_EXAMPLE_I2C_MASTER_FREQ_HZ=400000
+CONFIG_EXAMPLE_UART_TXD=4
+CONFIG_ESP_CONSOLE_UART_NUM=0
+CONFIG_ESP_CONSOLE_UART_TX_GPIO=-1
+CONFIG_ESP_WIFI_TX_BUFFER_TYPE=1
+CONFIG_EXAMPLE_PIN_MOSI=11
+CONFIG_LEDC_OUTPUT_IO=5
+CONFIG_BLINK_GPIO=8
+CONFIG_BLINK_LED_STRIP=y
+";
+    let pins: Vec<_> = from_sdkconfig(sdkconfig)
+        .into_iter()
+        .map(|p| (p.gpio, p.role, p.signal))
+        .collect();
+    assert_eq!(
+        pins,
+        [
+            (21, PinRole::I2c, "EXAMPLE_I2C_MASTER_SDA".to_string()),
+            (4, PinRole::Uart, "EXAMPLE_UART_TXD".to_string()),
+            (11, PinRole::Spi, "EXAMPLE_PIN_MOSI".to_string()),
+            (5, PinRole::Pwm, "LEDC_OUTPUT_IO".to_string()),
+            (8, PinRole::Gpio, "BLINK_GPIO".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn json_lists_are_read_as_is() {
+    let pins =
+        parse(r#"[{"gpio": 21, "role": "i2c", "signal": "SDA"}, {"gpio": 2, "role": "gpio"}]"#)
+            .unwrap();
+    assert_eq!(
+        pins,
+        [
+            PinUsage {
+                gpio: 21,
+                role: PinRole::I2c,
+                signal: "SDA".to_string(),
+            },
+            PinUsage {
+                gpio: 2,
+                role: PinRole::Gpio,
+                signal: String::new(),
+            },
+        ]
+    );
+    assert!(matches!(
+        parse(r#"[{"gpio": 21, "role": "can"}]"#),
+        Err(AppError::InvalidInput(_))
+    ));
+}
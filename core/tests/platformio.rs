@@ -0,0 +1,129 @@
+mod common;
+
+use common::scratch;
+use esp32dev_core::{images, platformio};
+use std::path::Path;
+
+const INI: &str = "\
+; PlatformIO Project Configuration File
+[platformio]
+default_envs = release, debug
+
+[env]
+platform = espressif32
+framework = espidf
+upload_speed = 921600
+
+[env:debug]
+board = esp32-s3-devkitc-1
+build_flags =
+    -DDEBUG=1
+    -Os
+
+[env:release]
+extends = env:debug
+upload_speed = 460800 ; faster cables only
+";
+
+fn write(path: &Path, data: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, data).unwrap();
+}
+
+/// Image header of a DIO, 8 MB build for `chip_id`.
+fn image(chip_id: u16) -> Vec<u8> {
+    let mut data = vec![0xe9, 0x03, 0x02, 0x3f];
+    data.resize(12, 0);
+    data.extend_from_slice(&chip_id.to_le_bytes());
+    data.resize(64, 0);
+    data
+}
+
+/// A partition table entry.
+fn partition(kind: u8, subtype: u8, offset: u32, label: &str) -> Vec<u8> {
+    let mut entry = vec![0xaa, 0x50, kind, subtype];
+    entry.extend_from_slice(&offset.to_le_bytes());
+    entry.extend_from_slice(&0x100000u32.to_le_bytes());
+    let mut name = label.as_bytes().to_vec();
+    name.resize(16, 0);
+    entry.extend(name);
+    entry.extend_from_slice(&[0; 4]);
+    entry
+}
+
+#[test]
+fn ini_values_follow_extends_and_the_env_section() {
+    let ini = platformio::parse_ini(INI);
+    assert_eq!(
+        platformio::default_env(INI, &ini).as_deref(),
+        Some("release")
+    );
+    assert_eq!(
+        platformio::env_value(&ini, "release", "upload_speed").as_deref(),
+        Some("460800")
+    );
+    assert_eq!(
+        platformio::env_value(&ini, "release", "board").as_deref(),
+        Some("esp32-s3-devkitc-1")
+    );
+    assert_eq!(
+        platformio::env_value(&ini, "debug", "upload_speed").as_deref(),
+        Some("921600")
+    );
+    assert_eq!(
+        platformio::env_value(&ini, "debug", "build_flags").as_deref(),
+        Some("-DDEBUG=1\n-Os")
+    );
+
+    let plain = "[env:uno]\nboard = uno\n\n[env:esp32dev]\nboard = esp32dev\n";
+    let ini = platformio::parse_ini(plain);
+    assert_eq!(platformio::default_env(plain, &ini).as_deref(), Some("uno"));
+}
+
+#[test]
+fn app_goes_to_the_factory_or_first_ota_partition() {
+    let nvs = partition(1, 2, 0x9000, "nvs");
+    let ota_0 = partition(0, 0x10, 0x20000, "ota_0");
+    let factory = partition(0, 0, 0x10000, "factory");
+    assert_eq!(
+        images::app_offset(&[nvs.clone(), ota_0.clone(), factory].concat()),
+        Some(0x10000)
+    );
+    assert_eq!(
+        images::app_offset(&[nvs.clone(), ota_0].concat()),
+        Some(0x20000)
+    );
+    assert_eq!(images::app_offset(&nvs), None);
+}
+
+#[test]
+fn build_output_becomes_a_flash_plan() {
+    let project = scratch("pio-project");
+    assert!(platformio::load(&project, None).is_err());
+
+    write(&project.join("platformio.ini"), INI.as_bytes());
+    let build = project.join(".pio/build/release");
+    // Not built yet
+    assert!(platformio::load(&project, None).is_err());
+
+    let table = [
+        partition(1, 2, 0x9000, "nvs"),
+        partition(0, 0x10, 0x20000, "app0"),
+    ]
+    .concat();
+    write(&build.join("bootloader.bin"), &image(0x0009));
+    write(&build.join("partitions.bin"), &table);
+    write(&build.join("firmware.bin"), &image(0x0009));
+
+    let plan = platformio::load(&project, None).unwrap();
+    assert_eq!(plan.chip.as_deref(), Some("esp32s3"));
+    assert_eq!(plan.flash_mode.as_deref(), Some("dio"));
+    assert_eq!(plan.flash_size.as_deref(), Some("8MB"));
+    assert_eq!(plan.baud_rate, Some(460800));
+    let offsets: Vec<u32> = plan.segments.iter().map(|s| s.address).collect();
+    assert_eq!(offsets, [0x0, 0x8000, 0x20000]);
+    assert!(plan.segments[2].path.ends_with("firmware.bin"));
+
+    assert!(platformio::load(&project, Some("nightly")).is_err());
+    std::fs::remove_dir_all(&project).ok();
+}
@@ -0,0 +1,75 @@
+use esp32dev_core::models::ChipDetails;
+use esp32dev_core::otadata::Partition;
+use esp32dev_core::report::{DeviceReport, FlashId, SecuritySummary};
+
+fn report() -> DeviceReport {
+    DeviceReport {
+        generated_at: 1_700_000_000,
+        port_name: "/dev/ttyUSB0".to_string(),
+        status: None,
+        chip: ChipDetails {
+            chip_model: Some("ESP32-S3".to_string()),
+            mac_address: Some("7c:df:a1:00:00:01".to_string()),
+            ..ChipDetails::default()
+        },
+        security: Some(SecuritySummary::new(0x1, 0x7, &[4, 0, 0, 0, 0, 0, 0])),
+        partitions: vec![
+            Partition {
+                label: "nvs".to_string(),
+                kind: 0x01,
+                subtype: 0x02,
+                offset: 0x9000,
+                size: 0x6000,
+            },
+            Partition {
+                label: "<app>".to_string(),
+                kind: 0x00,
+                subtype: 0x11,
+                offset: 0x110000,
+                size: 0x100000,
+            },
+        ],
+        flash_id: Some(FlashId::from_raw(0x1640ef)),
+    }
+}
+
+#[test]
+fn flash_id_is_split_like_esptool() {
+    let id = FlashId::from_raw(0x1640ef);
+    assert_eq!(id.manufacturer, 0xef);
+    assert_eq!(id.device, 0x4016);
+    assert_eq!(id.to_string(), "EF 4016");
+}
+
+#[test]
+fn security_flags_are_decoded() {
+    let summary = SecuritySummary::new(0x81, 0x3, &[]);
+    assert!(summary.secure_boot);
+    assert!(summary.jtag_disabled);
+    assert!(!summary.usb_disabled);
+    // Bits 0 and 7: Secure Boot and hard JTAG disable; two counter bits
+    // set means encryption was turned on and off again
+    assert!(!summary.flash_encryption);
+    assert!(SecuritySummary::new(0, 0x1, &[]).flash_encryption);
+}
+
+#[test]
+fn json_report_uses_camel_case() {
+    let json: serde_json::Value = serde_json::from_str(&report().to_json()).unwrap();
+    assert_eq!(json["portName"], "/dev/ttyUSB0");
+    assert_eq!(json["flashId"]["manufacturer"], 0xef);
+    assert_eq!(json["security"]["flashEncryption"], true);
+    assert_eq!(json["partitions"][0]["label"], "nvs");
+    assert_eq!(json["chip"]["chip_model"], "ESP32-S3");
+}
+
+#[test]
+fn html_report_lists_partitions_escaped() {
+    let html = report().to_html();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>ESP32-S3 on /dev/ttyUSB0</title>"));
+    assert!(html.contains("7c:df:a1:00:00:01"));
+    assert!(html.contains("EF 4016"));
+    assert!(html.contains("<td>&lt;app&gt;</td><td>app, ota_1</td><td>0x110000</td>"));
+    assert!(html.contains("<td>nvs</td><td>data, nvs</td>"));
+}
@@ -0,0 +1,112 @@
+use esp32dev_core::models::ResetCause;
+use esp32dev_core::reset_reason::{rom_cause, ResetAnalyzer};
+
+const POWER_ON: &str = "ESP-ROM:esp32s3-20210327\r\nBuild:Mar 27 2021\r\n\
+    rst:0x1 (POWERON),boot:0x8 (SPI_FAST_FLASH_BOOT)\r\n";
+const PANIC: &str = "Guru Meditation Error: Core  0 panic'ed (LoadProhibited). \
+    Exception was unhandled.\r\nBacktrace: 0x42008f1e:0x3fc98a70\r\nRebooting...\r\n";
+
+fn rst(code: &str, name: &str) -> String {
+    format!(
+        "ets Jun  8 2016 00:22:57\r\n\r\nrst:{} ({}),boot:0x13 (SPI_FAST_FLASH_BOOT)\r\n",
+        code, name
+    )
+}
+
+#[test]
+fn rom_names_are_classified_across_chips() {
+    let cases = [
+        ("POWERON_RESET", ResetCause::PowerOn),
+        ("POWERON", ResetCause::PowerOn),
+        ("BROWNOUT_RST", ResetCause::Power),
+        ("RTCWDT_BROWN_OUT_RESET", ResetCause::Power),
+        ("PWR_GLITCH_RESET", ResetCause::Power),
+        ("TG0WDT_SYS_RESET", ResetCause::Watchdog),
+        ("RTCWDT_RTC_RESET", ResetCause::Watchdog),
+        ("SUPER_WDT_RESET", ResetCause::Watchdog),
+        ("DEEPSLEEP_RESET", ResetCause::DeepSleepWake),
+        ("SW_CPU_RESET", ResetCause::Software),
+        ("RTC_SW_SYS_RST", ResetCause::Software),
+        ("USB_UART_CHIP_RESET", ResetCause::External),
+        ("JTAG_RESET", ResetCause::External),
+        ("EFUSE_RESET", ResetCause::Unknown),
+    ];
+    for (name, cause) in cases {
+        assert_eq!(rom_cause(name), cause, "{}", name);
+    }
+}
+
+#[test]
+fn the_rom_reason_is_reported_at_each_boot() {
+    let mut analyzer = ResetAnalyzer::new();
+    let reset = analyzer.push(POWER_ON.as_bytes()).unwrap();
+    assert_eq!(reset.cause, ResetCause::PowerOn);
+    assert_eq!(reset.rom_reason, "0x1 (POWERON)");
+    assert_eq!(reset.banner, None);
+
+    // Split mid-line: the line counts when it is complete
+    let line = rst("0xf", "BROWNOUT_RST");
+    let (start, end) = line.split_at(line.len() - 10);
+    assert!(analyzer.push(start.as_bytes()).is_none());
+    assert_eq!(
+        analyzer.push(end.as_bytes()).unwrap().cause,
+        ResetCause::Power
+    );
+
+    assert!(analyzer
+        .push(b"I (301) main_task: Calling app_main()\r\n")
+        .is_none());
+}
+
+#[test]
+fn a_banner_before_the_reset_explains_it() {
+    let mut analyzer = ResetAnalyzer::new();
+    analyzer.push(PANIC.as_bytes());
+    let reset = analyzer
+        .push(rst("0xc", "SW_CPU_RESET").as_bytes())
+        .unwrap();
+    assert_eq!(reset.cause, ResetCause::Panic);
+    assert!(reset.banner.unwrap().starts_with("Guru Meditation Error"));
+
+    // The first banner wins: a task watchdog goes on to abort()
+    analyzer.push(b"E (5312) task_wdt: Task watchdog got triggered.\r\n");
+    analyzer.push(b"abort() was called at PC 0x42013b1f on core 0\r\n");
+    let reset = analyzer
+        .push(rst("0xc", "SW_CPU_RESET").as_bytes())
+        .unwrap();
+    assert_eq!(reset.cause, ResetCause::Watchdog);
+
+    // An interrupt watchdog is printed as a panic
+    analyzer.push(b"Guru Meditation Error: Core  1 panic'ed (Interrupt wdt timeout on CPU1).\r\n");
+    let reset = analyzer
+        .push(rst("0x4", "RTC_SW_CPU_RST").as_bytes())
+        .unwrap();
+    assert_eq!(reset.cause, ResetCause::Watchdog);
+
+    // Some chips restart in software after a brownout
+    analyzer.push(b"\r\nBrownout detector was triggered\r\n\r\n");
+    let reset = analyzer
+        .push(rst("0xc", "SW_CPU_RESET").as_bytes())
+        .unwrap();
+    assert_eq!(reset.cause, ResetCause::Power);
+
+    // Nothing carries over to the next reset
+    let reset = analyzer
+        .push(rst("0xc", "SW_CPU_RESET").as_bytes())
+        .unwrap();
+    assert_eq!((reset.cause, reset.banner), (ResetCause::Software, None));
+}
+
+#[test]
+fn a_crash_the_firmware_survived_does_not_explain_a_power_cycle() {
+    let mut analyzer = ResetAnalyzer::new();
+    analyzer.push(b"E (5312) task_wdt: Task watchdog got triggered.\r\n");
+    let reset = analyzer.push(POWER_ON.as_bytes()).unwrap();
+    assert_eq!((reset.cause, reset.banner), (ResetCause::PowerOn, None));
+
+    analyzer.push(PANIC.as_bytes());
+    let reset = analyzer
+        .push(rst("0x5", "DEEPSLEEP_RESET").as_bytes())
+        .unwrap();
+    assert_eq!(reset.cause, ResetCause::DeepSleepWake);
+}
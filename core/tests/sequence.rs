@@ -0,0 +1,102 @@
+mod common;
+
+use common::scratch;
+use esp32dev_core::models::{AppError, StepResult, StepStatus, TestReport, TestSequence, TestStep};
+use esp32dev_core::sequence;
+
+#[test]
+fn sequences_load_with_paths_next_to_the_file() {
+    let dir = scratch("sequence-load");
+    let file = dir.join("smoke.json");
+    std::fs::write(
+        &file,
+        r#"{
+            "baud_rate": 74880,
+            "steps": [
+                {"step": "flash", "path": "app.bin", "address": "0x10000"},
+                {"step": "reset"},
+                {"step": "expect", "pattern": "READY", "timeout_ms": 8000},
+                {"step": "send", "text": "ping"},
+                {"step": "expect", "pattern": "pong"}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let loaded = sequence::load(&file).unwrap();
+    assert_eq!(
+        loaded,
+        TestSequence {
+            name: "smoke".to_string(),
+            baud_rate: Some(74880),
+            steps: vec![
+                TestStep::Flash {
+                    path: dir.join("app.bin").display().to_string(),
+                    address: Some("0x10000".to_string()),
+                },
+                TestStep::Reset,
+                TestStep::Expect {
+                    pattern: "READY".to_string(),
+                    timeout_ms: Some(8000),
+                },
+                TestStep::Send {
+                    text: "ping".to_string(),
+                },
+                TestStep::Expect {
+                    pattern: "pong".to_string(),
+                    timeout_ms: None,
+                },
+            ],
+        }
+    );
+    assert_eq!(sequence::describe(&loaded.steps[2]), "expect /READY/");
+}
+
+#[test]
+fn unknown_steps_and_empty_sequences_are_rejected() {
+    let dir = scratch("sequence-invalid");
+    let file = dir.join("bad.json");
+    std::fs::write(&file, r#"{"steps": [{"step": "dance"}]}"#).unwrap();
+    assert!(matches!(
+        sequence::load(&file),
+        Err(AppError::InvalidInput(_))
+    ));
+    std::fs::write(&file, r#"{"name": "empty", "steps": []}"#).unwrap();
+    assert!(matches!(
+        sequence::load(&file),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn failed_reports_name_the_first_failing_step() {
+    let step = |description: &str, status| StepResult {
+        description: description.to_string(),
+        status,
+        duration_ms: 0,
+        detail: (status == StepStatus::Failed).then(|| "timed out".to_string()),
+    };
+    let mut report = TestReport {
+        name: "smoke".to_string(),
+        port_name: "/dev/ttyUSB0".to_string(),
+        passed: true,
+        duration_ms: 0,
+        steps: vec![
+            step("reset", StepStatus::Passed),
+            step("expect /READY/", StepStatus::Passed),
+        ],
+    };
+    assert_eq!(report.outcome().unwrap(), "All 2 steps passed");
+
+    report.passed = false;
+    report.steps[1] = step("expect /READY/", StepStatus::Failed);
+    report
+        .steps
+        .push(step("send \"ping\"", StepStatus::Skipped));
+    match report.outcome() {
+        Err(AppError::Script(message)) => {
+            assert_eq!(message, "Step 2 (expect /READY/) failed: timed out")
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,355 @@
+//! Serial path tests against a pseudo-terminal pair: the test holds the
+//! master end and plays the device, the code under test opens the slave by
+//! name exactly like a real port.
+#![cfg(unix)]
+
+use esp32dev_core::console::Console;
+use esp32dev_core::gpio;
+use esp32dev_core::models::{AppError, PinReading, TaskInfo};
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::scripting::{self, ScriptOptions};
+use esp32dev_core::tasks::TaskManager;
+use serialport::{SerialPort, TTYPort};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const BAUD: u32 = 115200;
+const WAIT: Duration = Duration::from_secs(3);
+
+/// Returns the device end of a fresh pty pair, the port name to open and
+/// a plain handle on the port. Writes to the device end fail while nothing
+/// has the port open, so the caller keeps that handle alive; unlike a
+/// serial port handle it takes no lock.
+fn virtual_port() -> (TTYPort, String, File) {
+    let (mut device, port) = TTYPort::pair().expect("pty pair");
+    device.set_timeout(Duration::from_millis(50)).unwrap();
+    let name = port.name().expect("pty name");
+    let line = File::open(&name).expect("pty open");
+    drop(port);
+    (device, name, line)
+}
+
+/// Reads from the device end until `needle` shows up.
+fn read_until(device: &mut TTYPort, needle: &str) -> String {
+    let deadline = Instant::now() + WAIT;
+    let mut text = String::new();
+    let mut buf = [0u8; 256];
+    while !text.contains(needle) {
+        assert!(Instant::now() < deadline, "no {:?} in {:?}", needle, text);
+        if let Ok(n) = device.read(&mut buf) {
+            text.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+    }
+    text
+}
+
+/// Collects monitor output until `needle` shows up.
+fn receive_until(rx: &mpsc::Receiver<Vec<u8>>, needle: &str) -> String {
+    let deadline = Instant::now() + WAIT;
+    let mut text = String::new();
+    while !text.contains(needle) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(left) {
+            Ok(bytes) => text.push_str(&String::from_utf8_lossy(&bytes)),
+            Err(_) => panic!("no {:?} in {:?}", needle, text),
+        }
+    }
+    text
+}
+
+fn wait_for_state(monitor: &Monitor, state: &str) {
+    let deadline = Instant::now() + WAIT;
+    while monitor.status().state != state {
+        assert!(Instant::now() < deadline, "monitor never got {:?}", state);
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn connect(monitor: &Monitor, port: &str) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    monitor
+        .connect(port, BAUD, move |bytes| {
+            let _ = tx.send(bytes.to_vec());
+        })
+        .expect("monitor connect");
+    rx
+}
+
+#[test]
+fn monitor_forwards_received_bytes() {
+    let (mut device, port, _line) = virtual_port();
+    let monitor = Monitor::default();
+    let rx = connect(&monitor, &port);
+
+    device.write_all(b"I (240) app: ready\r\n").unwrap();
+    receive_until(&rx, "app: ready");
+    assert!(String::from_utf8_lossy(&monitor.recent_output()).contains("app: ready"));
+
+    monitor.disconnect().unwrap();
+}
+
+#[test]
+fn monitor_splits_output_into_lines() {
+    let (mut device, port, _line) = virtual_port();
+    let monitor = Monitor::default();
+    let rx = connect(&monitor, &port);
+
+    device.write_all(b"boot\r\nready\r\npar").unwrap();
+    receive_until(&rx, "par");
+    device.write_all(b"tial\r\n").unwrap();
+    receive_until(&rx, "tial");
+    let window = monitor.lines(None, 10);
+    assert_eq!(window.lines, ["boot", "ready", "partial"]);
+    assert_eq!((window.first, window.start, window.end), (0, 0, 3));
+    assert_eq!(monitor.lines(Some(1), 1).lines, ["ready"]);
+
+    // Numbering carries on after a clear
+    monitor.clear_lines();
+    monitor.note("> status");
+    let window = monitor.lines(None, 10);
+    assert_eq!(window.lines, ["> status"]);
+    assert_eq!((window.first, window.end), (3, 4));
+
+    monitor.disconnect().unwrap();
+}
+
+#[test]
+fn monitor_send_reaches_device() {
+    let (mut device, port, _line) = virtual_port();
+    let monitor = Monitor::default();
+    let _rx = connect(&monitor, &port);
+
+    monitor.send(b"status\r\n").unwrap();
+    read_until(&mut device, "status\r\n");
+
+    monitor.disconnect().unwrap();
+}
+
+#[test]
+fn gpio_helper_requests_skip_log_output() {
+    let (mut device, port, _line) = virtual_port();
+    let monitor = Monitor::default();
+    let _rx = connect(&monitor, &port);
+
+    // Plays the helper, with a stray log line before each reply
+    let helper = std::thread::spawn(move || {
+        read_until(&mut device, "PING\r\n");
+        device
+            .write_all(b"I (120) app: tick\r\nOK gpio-helper 1\r\n")
+            .unwrap();
+        read_until(&mut device, "MODE 4 out\r\n");
+        device.write_all(b"OK\r\n").unwrap();
+        read_until(&mut device, "SET 5 1\r\n");
+        device.write_all(b"ERR not an output\r\n").unwrap();
+        read_until(&mut device, "POLL\r\n");
+        device
+            .write_all(b"I (130) app: tick\r\nOK 5:1 34:1650\r\n")
+            .unwrap();
+        device
+    });
+
+    assert_eq!(gpio::ping(&monitor).unwrap(), gpio::PROTOCOL);
+    gpio::set_mode(&monitor, 4, "out").unwrap();
+    // Rejected locally before the device sees it
+    assert!(matches!(
+        gpio::set_mode(&monitor, 5, "fast"),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        gpio::write(&monitor, 5, true),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert_eq!(
+        gpio::poll(&monitor).unwrap(),
+        [
+            PinReading { gpio: 5, value: 1 },
+            PinReading {
+                gpio: 34,
+                value: 1650
+            }
+        ]
+    );
+    let _device = helper.join().unwrap();
+
+    monitor.disconnect().unwrap();
+}
+
+#[test]
+fn disconnect_releases_port() {
+    let (_device, port, _line) = virtual_port();
+    let monitor = Monitor::default();
+    let _rx = connect(&monitor, &port);
+    assert_eq!(monitor.status().state, "connected");
+    assert_eq!(monitor.status().port_name.as_deref(), Some(port.as_str()));
+
+    monitor.disconnect().unwrap();
+    assert_eq!(monitor.status().state, "disconnected");
+    assert!(matches!(monitor.send(b"x"), Err(AppError::NotConnected)));
+    // Ports are opened exclusively, so this fails while any handle is left
+    serialport::new(&port, BAUD)
+        .open()
+        .expect("port still held after disconnect");
+}
+
+#[test]
+fn with_port_lends_port_and_resumes() {
+    let (mut device, port, _line) = virtual_port();
+    let monitor = Monitor::default();
+    let rx = connect(&monitor, &port);
+
+    let states = std::sync::Mutex::new(Vec::new());
+    monitor
+        .with_port(
+            &port,
+            "flash",
+            |event| states.lock().unwrap().push(event.state),
+            || {
+                assert_eq!(monitor.status().state, "suspended");
+                let mut lent = serialport::new(&port, BAUD).open()?;
+                lent.write_all(b"sync\r\n")?;
+                Ok(())
+            },
+        )
+        .unwrap();
+    assert_eq!(*states.lock().unwrap(), ["suspended", "resumed"]);
+    read_until(&mut device, "sync");
+
+    wait_for_state(&monitor, "connected");
+    device.write_all(b"back\r\n").unwrap();
+    receive_until(&rx, "back");
+
+    monitor.disconnect().unwrap();
+}
+
+#[test]
+fn monitor_reports_reconnecting_when_device_drops() {
+    let (device, port, _line) = virtual_port();
+    let monitor = Monitor::default();
+    let _rx = connect(&monitor, &port);
+
+    // Closing the master end is what unplugging looks like to the reader
+    drop(device);
+    wait_for_state(&monitor, "reconnecting");
+
+    monitor.disconnect().unwrap();
+    assert_eq!(monitor.status().state, "disconnected");
+}
+
+#[test]
+fn reconnect_replaces_previous_session() {
+    let (mut first, first_port, _first_line) = virtual_port();
+    let (mut second, second_port, _second_line) = virtual_port();
+    let monitor = Monitor::default();
+    let _old = connect(&monitor, &first_port);
+    let rx = connect(&monitor, &second_port);
+    assert_eq!(
+        monitor.status().port_name.as_deref(),
+        Some(second_port.as_str())
+    );
+
+    monitor.send(b"hello\r\n").unwrap();
+    read_until(&mut second, "hello");
+    // The first port was handed back
+    serialport::new(&first_port, BAUD).open().unwrap();
+
+    first.write_all(b"stale\r\n").unwrap();
+    second.write_all(b"fresh\r\n").unwrap();
+    assert!(!receive_until(&rx, "fresh").contains("stale"));
+
+    monitor.disconnect().unwrap();
+}
+
+fn console(port: &str) -> Console {
+    let tasks = TaskManager::new(|_: &TaskInfo| {});
+    let task = tasks.start("script", Some(port));
+    Console::open(port, BAUD, task, |_| {}).expect("console open")
+}
+
+#[test]
+fn console_expect_matches_across_reads() {
+    let (mut device, port, _line) = virtual_port();
+    let console = console(&port);
+
+    // A line split over several reads still matches as a whole
+    device.write_all(b"AC").unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    device.write_all(b"K 42\r\n").unwrap();
+    let matched = console.expect(r"ACK \d+", WAIT).unwrap();
+    assert_eq!(matched, "ACK 42");
+}
+
+#[test]
+fn console_expect_consumes_output() {
+    let (mut device, port, _line) = virtual_port();
+    let console = console(&port);
+
+    device.write_all(b"READY 1\r\nREADY 2\r\n").unwrap();
+    assert_eq!(console.expect(r"READY \d", WAIT).unwrap(), "READY 1");
+    assert_eq!(console.expect(r"READY \d", WAIT).unwrap(), "READY 2");
+    assert!(matches!(
+        console.expect("READY", Duration::from_millis(200)),
+        Err(AppError::Timeout(_))
+    ));
+}
+
+#[test]
+fn console_rejects_bad_pattern() {
+    let (_device, port, _line) = virtual_port();
+    let console = console(&port);
+    assert!(matches!(
+        console.expect("(", WAIT),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn script_talks_to_device() {
+    let (mut device, port, _line) = virtual_port();
+
+    // Answers CONFIG with an ACK, like a firmware's console would
+    let responder = std::thread::spawn(move || {
+        device.write_all(b"READY\r\n").unwrap();
+        read_until(&mut device, "CONFIG\r\n");
+        device.write_all(b"ACK 42\r\n").unwrap();
+        // Keep the port open until the script is done with it
+        std::thread::sleep(Duration::from_millis(500));
+    });
+
+    let options = ScriptOptions {
+        port_name: port.clone(),
+        baud_rate: BAUD,
+        flash_address: 0x10000,
+        flash_baud: BAUD,
+    };
+    let tasks = TaskManager::new(|_: &TaskInfo| {});
+    let task = tasks.start("script", Some(&port));
+    let source = r#"
+        expect("READY", 3000);
+        send("CONFIG");
+        let ack = expect("ACK \\d+", 3000);
+        if ack != "ACK 42" { throw "bad ack: " + ack; }
+    "#;
+    scripting::run(source, &options, &task, |_| {}).unwrap();
+    responder.join().unwrap();
+}
+
+#[test]
+fn script_errors_are_reported() {
+    let (_device, port, _line) = virtual_port();
+    let options = ScriptOptions {
+        port_name: port.clone(),
+        baud_rate: BAUD,
+        flash_address: 0x10000,
+        flash_baud: BAUD,
+    };
+    let tasks = TaskManager::new(|_: &TaskInfo| {});
+    let task = tasks.start("script", Some(&port));
+
+    let result = scripting::run(r#"expect("NEVER", 100);"#, &options, &task, |_| {});
+    assert!(matches!(result, Err(AppError::Timeout(_))));
+
+    let result = scripting::run("this is not rhai", &options, &task, |_| {});
+    assert!(matches!(result, Err(AppError::Script(_))));
+}
@@ -0,0 +1,24 @@
+//! Run with `cargo test -p esp32dev-core --features simulator`.
+#![cfg(feature = "simulator")]
+
+use esp32dev_core::esp_interaction::{self, FlasherSessions};
+use esp32dev_core::models::AppError;
+use esp32dev_core::simulator::{self, PORT_NAME};
+use std::io::Read;
+use std::time::Duration;
+
+#[test]
+fn reset_right_after_a_probe_closes_the_flasher_session() {
+    let sessions = FlasherSessions::new(Duration::from_secs(30));
+    esp_interaction::connect_and_get_info(&sessions, PORT_NAME).unwrap();
+    // The cached session still holds the port
+    assert!(matches!(
+        esp_interaction::hard_reset(PORT_NAME),
+        Err(AppError::PortBusy(_))
+    ));
+
+    esp_interaction::reset(&sessions, PORT_NAME).unwrap();
+    let mut boot = [0; 64];
+    let len = simulator::open(115200).read(&mut boot).unwrap();
+    assert!(String::from_utf8_lossy(&boot[..len]).contains("USB_UART_CHIP_RESET"));
+}
@@ -0,0 +1,54 @@
+use esp32dev_core::models::{BootFault, BootRecord, SoakReport};
+use esp32dev_core::soak;
+use regex::Regex;
+
+const GOOD_BOOT: &str = "rst:0x1 (POWERON_RESET),boot:0x13 (SPI_FAST_FLASH_BOOT)\n\
+    I (312) app: starting\nREADY\n";
+
+const PANIC_BOOT: &str = "rst:0xc (SW_CPU_RESET),boot:0x13 (SPI_FAST_FLASH_BOOT)\n\
+    Guru Meditation Error: Core  1 panic'ed (LoadProhibited). Exception was unhandled.\n\
+    Backtrace: 0x400d1234:0x3ffb1f80\n";
+
+const BROWNOUT_BOOT: &str = "rst:0xf (BROWNOUT_RST),boot:0x13 (SPI_FAST_FLASH_BOOT)\n\
+    Brownout detector was triggered\n";
+
+fn boot(cycle: u32, faults: Vec<BootFault>) -> BootRecord {
+    BootRecord {
+        cycle,
+        faults,
+        duration_ms: 800,
+        log: String::new(),
+    }
+}
+
+#[test]
+fn boots_are_flagged_by_their_log() {
+    let marker = Regex::new("READY").unwrap();
+    assert!(soak::classify(GOOD_BOOT, Some(&marker)).is_empty());
+    assert_eq!(
+        soak::classify(PANIC_BOOT, Some(&marker)),
+        [BootFault::MissingMarker, BootFault::Panic]
+    );
+    assert_eq!(soak::classify(BROWNOUT_BOOT, None), [BootFault::Brownout]);
+    // Without a marker only crashes count
+    assert!(soak::classify("garbage", None).is_empty());
+}
+
+#[test]
+fn summary_gives_failure_rate_and_causes() {
+    let report = SoakReport {
+        port_name: "/dev/ttyUSB0".to_string(),
+        boots: vec![
+            boot(1, vec![]),
+            boot(2, vec![BootFault::MissingMarker, BootFault::Panic]),
+            boot(3, vec![]),
+            boot(4, vec![BootFault::Brownout]),
+        ],
+    };
+    assert_eq!(report.failed(), 2);
+    assert_eq!(report.failure_rate(), 0.5);
+    assert_eq!(
+        soak::summary(&report),
+        "4 boots, 2 failed (50.0%): 1 missing marker, 1 panic, 1 brownout"
+    );
+}
@@ -0,0 +1,125 @@
+use esp32dev_core::svd;
+
+const SVD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<device schemaVersion="1.1">
+  <name>ESP32-C3</name>
+  <size>0x20</size>
+  <access>read-write</access>
+  <peripherals>
+    <peripheral>
+      <name>UART0</name>
+      <description>UART (Universal Asynchronous Receiver-Transmitter)
+        Controller 0</description>
+      <baseAddress>0x60000000</baseAddress>
+      <registers>
+        <register>
+          <name>FIFO</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field><name>RXFIFO_RD_BYTE</name><bitOffset>0</bitOffset><bitWidth>8</bitWidth></field>
+          </fields>
+        </register>
+        <register>
+          <name>INT_RAW</name>
+          <description>Raw interrupt status</description>
+          <addressOffset>0x4</addressOffset>
+          <access>read-only</access>
+          <resetValue>0x00000002</resetValue>
+          <fields>
+            <field><name>TXFIFO_EMPTY_INT_RAW</name><lsb>1</lsb><msb>1</msb></field>
+            <field><name>RXFIFO_FULL_INT_RAW</name><bitRange>[0:0]</bitRange></field>
+          </fields>
+        </register>
+        <register>
+          <dim>2</dim>
+          <dimIncrement>0x4</dimIncrement>
+          <name>CONF%s</name>
+          <addressOffset>0x20</addressOffset>
+          <fields>
+            <field><name>RXFIFO_FULL_THRHD</name><bitOffset>0</bitOffset><bitWidth>9</bitWidth></field>
+            <field><name>RX_TOUT_EN</name><bitOffset>#1010</bitOffset><bitWidth>1</bitWidth></field>
+          </fields>
+        </register>
+      </registers>
+    </peripheral>
+    <peripheral derivedFrom="UART0">
+      <name>UART1</name>
+      <baseAddress>0x60010000</baseAddress>
+    </peripheral>
+  </peripherals>
+</device>
+"#;
+
+#[test]
+fn peripherals_registers_and_fields_are_read() {
+    let peripherals = svd::parse(SVD).unwrap();
+    assert_eq!(peripherals.len(), 2);
+    let uart0 = &peripherals[0];
+    assert_eq!(
+        uart0.description.as_deref(),
+        Some("UART (Universal Asynchronous Receiver-Transmitter) Controller 0")
+    );
+    let names: Vec<&str> = uart0.registers.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["FIFO", "INT_RAW", "CONF0", "CONF1"]);
+
+    let int_raw = &uart0.registers[1];
+    assert_eq!(int_raw.address, 0x6000_0004);
+    // The register's own size and access win over the device's
+    assert_eq!(int_raw.size, 0x20);
+    assert_eq!(int_raw.access.as_deref(), Some("read-only"));
+    assert_eq!(int_raw.reset_value, Some(2));
+    let bits: Vec<(&str, u32, u32)> = int_raw
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.lsb, f.width))
+        .collect();
+    assert_eq!(
+        bits,
+        [
+            ("RXFIFO_FULL_INT_RAW", 0, 1),
+            ("TXFIFO_EMPTY_INT_RAW", 1, 1)
+        ]
+    );
+
+    assert_eq!(uart0.registers[3].address, 0x6000_0024);
+    assert_eq!(uart0.registers[3].fields[1].lsb, 10);
+}
+
+#[test]
+fn a_derived_peripheral_has_the_registers_at_its_own_base() {
+    let peripherals = svd::parse(SVD).unwrap();
+    let uart1 = &peripherals[1];
+    assert_eq!(uart1.registers.len(), 4);
+    assert_eq!(uart1.registers[1].name, "INT_RAW");
+    assert_eq!(uart1.registers[1].address, 0x6001_0004);
+    assert_eq!(uart1.description, peripherals[0].description);
+}
+
+#[test]
+fn fifos_are_left_out_of_contiguous_reads() {
+    let peripherals = svd::parse(SVD).unwrap();
+    let readable: Vec<_> = peripherals[0]
+        .registers
+        .iter()
+        .filter(|r| svd::readable(r))
+        .collect();
+    assert_eq!(readable.len(), 3);
+    assert_eq!(svd::spans(&readable), [(0x6000_0004, 4), (0x6000_0020, 8)]);
+}
+
+#[test]
+fn a_value_is_split_into_its_fields() {
+    let peripherals = svd::parse(SVD).unwrap();
+    let conf0 = &peripherals[0].registers[2];
+    let value = svd::decode(conf0, 0x0000_0460);
+    assert_eq!(value.name, "CONF0");
+    assert_eq!(value.fields[0].name, "RXFIFO_FULL_THRHD");
+    assert_eq!(value.fields[0].value, 0x60);
+    assert_eq!(value.fields[1].value, 1);
+}
+
+#[test]
+fn unknown_chips_have_no_svd() {
+    let cache = std::env::temp_dir();
+    assert!(svd::load(None, &cache, "ESP8266").is_err());
+}
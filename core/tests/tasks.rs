@@ -0,0 +1,40 @@
+use esp32dev_core::esp_interaction::format_rate;
+use esp32dev_core::models::{AppError, TaskInfo};
+use esp32dev_core::tasks::TaskManager;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn throughput_is_reported_with_the_final_notification() {
+    let seen: Arc<Mutex<Vec<TaskInfo>>> = Arc::default();
+    let sink = seen.clone();
+    let tasks = TaskManager::new(move |info| sink.lock().unwrap().push(info.clone()));
+
+    let task = tasks.start("flash", Some("/dev/ttyUSB0"));
+    task.throughput(320 * 1024);
+    assert_eq!(task.info().unwrap().bytes_per_sec, Some(320 * 1024));
+    task.finish(&Ok::<_, AppError>(()));
+
+    let last = seen.lock().unwrap().last().cloned().unwrap();
+    assert_eq!(last.state, "completed");
+    assert_eq!(last.bytes_per_sec, Some(320 * 1024));
+}
+
+#[test]
+fn rates_read_like_esptool() {
+    assert_eq!(format_rate(320 * 1024), "320.0 KB/s");
+    assert_eq!(format_rate(1536), "1.5 KB/s");
+}
+
+#[test]
+fn time_left_is_sent_while_running_only() {
+    let seen: Arc<Mutex<Vec<TaskInfo>>> = Arc::default();
+    let sink = seen.clone();
+    let tasks = TaskManager::new(move |info| sink.lock().unwrap().push(info.clone()));
+
+    let task = tasks.start("flash", Some("/dev/ttyUSB0"));
+    task.eta(Some(40));
+    task.phase("writing");
+    assert_eq!(seen.lock().unwrap().last().unwrap().eta_secs, Some(40));
+    task.finish(&Ok::<_, AppError>(()));
+    assert_eq!(seen.lock().unwrap().last().unwrap().eta_secs, None);
+}
@@ -0,0 +1,62 @@
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::terminal::Screen;
+
+#[test]
+fn linenoise_redraws_leave_only_the_final_line() {
+    let mut screen = Screen::new(4, 20);
+    // Prompt, typed text, then a history recall redrawing the line
+    screen.feed(b"esp32> hel");
+    screen.feed(b"\r\x1b[0Kesp32> help\x1b[0K\r\x1b[11C");
+
+    let snapshot = screen.snapshot();
+    assert_eq!(snapshot.lines[0], "esp32> help");
+    assert_eq!((snapshot.cursor_row, snapshot.cursor_col), (0, 11));
+}
+
+#[test]
+fn status_queries_are_answered() {
+    let mut screen = Screen::new(24, 80);
+    assert_eq!(screen.feed(b"\x1b[5n"), b"\x1b[0n");
+    // linenoise measures the width by moving far right and asking
+    assert_eq!(screen.feed(b"\x1b[999C\x1b[6n"), b"\x1b[1;80R");
+    assert!(screen.feed(b"plain text").is_empty());
+}
+
+#[test]
+fn erases_and_positioning() {
+    let mut screen = Screen::new(3, 10);
+    screen.feed(b"abcdef\r\nghijkl\r\nmnop");
+    screen.feed(b"\x1b[2;3H\x1b[1K\x1b[1;4H\x1b[2P\x1b[3;1H\x1b[J");
+
+    let lines = screen.snapshot().lines;
+    assert_eq!(lines, ["abcf", "   jkl", ""]);
+}
+
+#[test]
+fn full_screens_scroll_into_the_scrollback() {
+    let mut screen = Screen::new(2, 4);
+    screen.feed(b"one\r\ntwo\r\nthree, wrapped");
+
+    let snapshot = screen.snapshot();
+    assert_eq!(snapshot.scrollback, ["one", "two", "thre", "e, w"]);
+    assert_eq!(snapshot.lines, ["rapp", "ed"]);
+    // Colours and titles don't print
+    screen.feed(b"\x1b[2J\x1b[H\x1b[1;32mok\x1b[0m\x1b]0;title\x07");
+    assert_eq!(screen.snapshot().lines, ["ok", ""]);
+}
+
+#[test]
+fn monitor_keeps_the_screen_only_in_terminal_mode() {
+    let monitor = Monitor::default();
+    monitor.output(b"before\r\n");
+    assert!(monitor.screen().is_none());
+
+    monitor.set_terminal(Some((24, 80)));
+    monitor.output(b"abc\x08\x1b[K");
+    assert_eq!(monitor.screen().unwrap().lines[0], "ab");
+    // The line log still gets everything
+    assert_eq!(monitor.lines(None, 10).lines[0], "before");
+
+    monitor.set_terminal(None);
+    assert!(monitor.screen().is_none());
+}
@@ -0,0 +1,27 @@
+use esp32dev_core::models::{AppError, Settings};
+use esp32dev_core::time_sync::{check, render};
+
+#[test]
+fn placeholders_are_filled_with_the_host_time() {
+    let now_ms = 1_700_000_123_456;
+    assert_eq!(
+        render(&Settings::default().time_command, now_ms).unwrap(),
+        "time set 1700000123"
+    );
+    assert_eq!(
+        render("rtc {date} {time} ({unix_ms})", now_ms).unwrap(),
+        "rtc 2023-11-14 22:15:23 (1700000123456)"
+    );
+}
+
+#[test]
+fn unusable_templates_are_refused() {
+    for template in ["time set", "time set {now}", "time set {unix"] {
+        assert!(
+            matches!(check(template), Err(AppError::InvalidInput(_))),
+            "{}",
+            template
+        );
+    }
+    assert!(check("date -s @{unix}").is_ok());
+}
@@ -0,0 +1,197 @@
+use esp32dev_core::models::AppError;
+use esp32dev_core::transport;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+// Telnet bytes as the server sees them
+const IAC: u8 = 255;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const WILL: u8 = 251;
+const DO: u8 = 253;
+const DONT: u8 = 254;
+const WONT: u8 = 252;
+
+fn server() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    (listener, address)
+}
+
+fn read_exact(stream: &mut impl Read, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn port_names_pick_the_transport() {
+    assert!(transport::is_network("rfc2217://lab-pi:4000"));
+    assert!(transport::is_network("tcp://10.0.0.7:3333"));
+    assert!(!transport::is_network("/dev/ttyUSB0"));
+    assert!(!transport::is_network("COM4"));
+    assert!(transport::has_control_lines("rfc2217://lab-pi:4000"));
+    assert!(!transport::has_control_lines("tcp://10.0.0.7:3333"));
+
+    let result = transport::open("rfc2217://127.0.0.1:1", 115200);
+    assert!(matches!(result, Err(AppError::Connection(m)) if m.contains("127.0.0.1:1")));
+}
+
+#[test]
+fn rfc2217_carries_control_lines_and_escapes_data() {
+    let (listener, address) = server();
+    let mut link = transport::open(&format!("rfc2217://{}", address), 460800).unwrap();
+    let (mut board, _) = listener.accept().unwrap();
+    board
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    // Option negotiation, then SET-BAUDRATE
+    let negotiation = read_exact(&mut board, 15);
+    assert_eq!(&negotiation[12..], &[IAC, WILL, 44]);
+    let baud = read_exact(&mut board, 10);
+    assert_eq!(baud, [IAC, SB, 44, 1, 0x00, 0x07, 0x08, 0x00, IAC, SE]);
+
+    link.set_rts(true).unwrap();
+    link.set_dtr(false).unwrap();
+    assert_eq!(read_exact(&mut board, 6), [IAC, SB, 44, 5, 11, IAC]);
+    assert_eq!(read_exact(&mut board, 7), [SE, IAC, SB, 44, 5, 9, IAC]);
+    assert_eq!(read_exact(&mut board, 1), [SE]);
+
+    // 0xFF in data is doubled on the wire
+    link.write_all(&[0xc0, 0xff, 0x01]).unwrap();
+    assert_eq!(read_exact(&mut board, 4), [0xc0, 0xff, 0xff, 0x01]);
+
+    // Server options and notifications are filtered out of the data, and
+    // options this side didn't ask for are refused
+    board
+        .write_all(&[
+            IAC, WILL, 44, b'o', IAC, IAC, IAC, SB, 44, 107, 0x30, IAC, SE, b'k', IAC, DO, 1,
+        ])
+        .unwrap();
+    link.set_timeout(Duration::from_secs(5)).unwrap();
+    let mut received = Vec::new();
+    while received.len() < 3 {
+        let mut buf = [0; 64];
+        let len = link.read(&mut buf).unwrap();
+        received.extend_from_slice(&buf[..len]);
+    }
+    assert_eq!(received, [b'o', 0xff, b'k']);
+    assert_eq!(read_exact(&mut board, 3), [IAC, WONT, 1]);
+
+    drop(board);
+    let mut buf = [0; 16];
+    assert!(link.read(&mut buf).is_err());
+}
+
+#[test]
+fn raw_tcp_has_no_control_lines() {
+    let (listener, address) = server();
+    let mut link = transport::open(&format!("tcp://{}", address), 115200).unwrap();
+    let (mut board, _) = listener.accept().unwrap();
+    board
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    assert!(matches!(link.set_dtr(true), Err(AppError::InvalidInput(_))));
+    link.write_all(&[0xff, IAC, DONT]).unwrap();
+    assert_eq!(read_exact(&mut board, 3), [0xff, IAC, DONT]);
+
+    // Nothing arrives: the read gives up like a serial port's
+    link.set_timeout(Duration::from_millis(20)).unwrap();
+    let mut buf = [0; 4];
+    let err = link.read(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+}
+
+/// A board's control lines, each change recorded as `(dtr, rts, call)`.
+struct Lines {
+    dtr: bool,
+    rts: bool,
+    seen: Vec<(bool, bool, &'static str)>,
+}
+
+impl Lines {
+    /// Both lines high, as a port is after opening.
+    fn opened() -> Self {
+        Lines {
+            dtr: true,
+            rts: true,
+            seen: Vec::new(),
+        }
+    }
+
+    // DTR low with RTS high holds the chip in reset
+    fn resets(&self) -> usize {
+        self.seen
+            .iter()
+            .filter(|(dtr, rts, _)| !dtr && *rts)
+            .count()
+    }
+}
+
+impl Read for Lines {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl Write for Lines {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl transport::SerialLike for Lines {
+    fn set_baud_rate(&mut self, _: u32) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn set_dtr(&mut self, level: bool) -> Result<(), AppError> {
+        self.dtr = level;
+        self.seen.push((self.dtr, self.rts, "dtr"));
+        Ok(())
+    }
+
+    fn set_rts(&mut self, level: bool) -> Result<(), AppError> {
+        self.rts = level;
+        self.seen.push((self.dtr, self.rts, "rts"));
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, _: Duration) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn transport::SerialLike>, AppError> {
+        Err(AppError::Internal("not supported".to_string()))
+    }
+}
+
+#[test]
+fn releasing_the_lines_never_resets_the_board() {
+    let mut lines = Lines::opened();
+    transport::release_lines(&mut lines).unwrap();
+    assert_eq!((lines.dtr, lines.rts), (false, false));
+    assert_eq!(lines.resets(), 0);
+}
+
+#[test]
+fn usb_jtag_reset_sends_dtr_after_every_rts_change() {
+    let mut lines = Lines::opened();
+    transport::reset_usb_jtag_to_app(&mut lines).unwrap();
+    assert_eq!((lines.dtr, lines.rts), (false, false));
+    // Held in reset across the RTS change and the DTR write that carries it
+    assert_eq!(lines.resets(), 2);
+    for pair in lines.seen.windows(2) {
+        if pair[0].2 == "rts" {
+            assert_eq!(pair[1].2, "dtr");
+        }
+    }
+    assert_eq!(lines.seen.last().unwrap().2, "dtr");
+}
@@ -0,0 +1,92 @@
+use esp32dev_core::models::{AppError, MonitorTrigger, TriggerAction};
+use esp32dev_core::triggers::Triggers;
+
+fn trigger(pattern: &str, action: TriggerAction) -> MonitorTrigger {
+    MonitorTrigger {
+        pattern: pattern.to_string(),
+        action,
+        enabled: true,
+    }
+}
+
+#[test]
+fn fires_on_complete_lines_split_across_reads() {
+    let mut triggers = Triggers::new(&[trigger("Brownout detector", TriggerAction::Stop)]).unwrap();
+    assert!(triggers.push(b"I (10) boot: ok\r\nBrownout det").is_empty());
+    let hits = triggers.push(b"ector was triggered\r\n");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].action, TriggerAction::Stop);
+    assert_eq!(hits[0].line, "Brownout detector was triggered");
+    // The next occurrence fires again
+    assert_eq!(triggers.push(b"Brownout detector was triggered\n").len(), 1);
+}
+
+#[test]
+fn answers_prompts_once_before_their_newline() {
+    let send = TriggerAction::Send {
+        text: "y".to_string(),
+    };
+    let mut triggers = Triggers::new(&[trigger(r"\[y/n\]", send.clone())]).unwrap();
+    let hits = triggers.push(b"Erase NVS? [y/n] ");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].action, send);
+    // Echo and newline finish the same line without firing again
+    assert!(triggers.push(b"y").is_empty());
+    assert!(triggers.push(b"\r\n").is_empty());
+    assert_eq!(triggers.push(b"Really? [y/n] ").len(), 1);
+}
+
+#[test]
+fn several_triggers_match_one_line_in_order() {
+    let mark = TriggerAction::Mark {
+        label: "panic".to_string(),
+    };
+    let notify = TriggerAction::Notify {
+        message: String::new(),
+    };
+    let mut triggers = Triggers::new(&[
+        trigger("Guru Meditation", mark.clone()),
+        trigger("Meditation Error", notify.clone()),
+    ])
+    .unwrap();
+    let hits = triggers.push(b"Guru Meditation Error: Core 0 panic'ed\n");
+    let actions: Vec<_> = hits.into_iter().map(|h| h.action).collect();
+    assert_eq!(actions, vec![mark, notify]);
+}
+
+#[test]
+fn disabled_triggers_are_skipped_and_bad_patterns_rejected() {
+    let mut off = trigger("anything", TriggerAction::Stop);
+    off.enabled = false;
+    let mut triggers = Triggers::new(&[off]).unwrap();
+    assert!(triggers.is_empty());
+    assert!(triggers.push(b"anything\n").is_empty());
+
+    assert!(matches!(
+        Triggers::new(&[trigger("(unclosed", TriggerAction::Stop)]),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn reset_drops_a_partial_line() {
+    let mut triggers = Triggers::new(&[trigger("^READY$", TriggerAction::Stop)]).unwrap();
+    assert!(triggers.push(b"garbage ").is_empty());
+    triggers.reset();
+    assert_eq!(triggers.push(b"READY\r\n").len(), 1);
+}
+
+#[test]
+fn actions_round_trip_through_settings_json() {
+    let json = r#"{"pattern": "\\[y/n\\]", "action": {"kind": "send", "text": "y"}}"#;
+    let parsed: MonitorTrigger = serde_json::from_str(json).unwrap();
+    assert!(parsed.enabled);
+    assert_eq!(
+        parsed.action,
+        TriggerAction::Send {
+            text: "y".to_string()
+        }
+    );
+    let stop: TriggerAction = serde_json::from_str(r#"{"kind": "stop"}"#).unwrap();
+    assert_eq!(stop, TriggerAction::Stop);
+}
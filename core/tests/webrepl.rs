@@ -0,0 +1,171 @@
+//! WebREPL sessions against a fake MicroPython board: a websocket server on
+//! localhost that asks for a password, echoes REPL input and keeps files
+//! in memory.
+
+use esp32dev_core::models::AppError;
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::webrepl::WebRepl;
+use std::collections::HashMap;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tungstenite::{Message, WebSocket};
+
+const PASSWORD: &str = "micropy";
+
+type Files = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+fn status(code: u16) -> Message {
+    let mut reply = b"WB".to_vec();
+    reply.extend_from_slice(&code.to_le_bytes());
+    Message::Binary(reply)
+}
+
+/// Reads binary frames until `len` bytes came in.
+fn read_binary(socket: &mut WebSocket<TcpStream>, len: usize) -> Vec<u8> {
+    let mut data = Vec::new();
+    while data.len() < len {
+        match socket.read().unwrap() {
+            Message::Binary(chunk) => data.extend(chunk),
+            other => panic!("expected binary data, got {:?}", other),
+        }
+    }
+    data
+}
+
+fn serve(stream: TcpStream, files: Files) {
+    let mut socket = tungstenite::accept(stream).unwrap();
+    socket.send(Message::Text("Password: ".into())).unwrap();
+    match socket.read().unwrap() {
+        Message::Text(password) if password == format!("{}\r", PASSWORD) => socket
+            .send(Message::Text("\r\nWebREPL connected\r\n>>> ".into()))
+            .unwrap(),
+        _ => {
+            socket
+                .send(Message::Text("\r\nAccess denied\r\n".into()))
+                .unwrap();
+            return;
+        }
+    }
+
+    while let Ok(message) = socket.read() {
+        match message {
+            Message::Text(line) => {
+                let reply = format!("{}\r\n>>> ", line.trim_end());
+                socket.send(Message::Text(reply)).unwrap();
+            }
+            Message::Binary(head) => {
+                let mut record = head;
+                record.extend(read_binary(&mut socket, 82 - record.len()));
+                assert_eq!(&record[..2], b"WA");
+                let size = u32::from_le_bytes(record[12..16].try_into().unwrap()) as usize;
+                let name_len = u16::from_le_bytes([record[16], record[17]]) as usize;
+                let name = String::from_utf8(record[18..18 + name_len].to_vec()).unwrap();
+                match record[2] {
+                    1 => {
+                        socket.send(status(0)).unwrap();
+                        let data = read_binary(&mut socket, size);
+                        files.lock().unwrap().insert(name, data);
+                        socket.send(status(0)).unwrap();
+                    }
+                    2 => {
+                        let Some(data) = files.lock().unwrap().get(&name).cloned() else {
+                            // ENOENT
+                            socket.send(status(2)).unwrap();
+                            continue;
+                        };
+                        socket.send(status(0)).unwrap();
+                        for chunk in data.chunks(256).chain([&[][..]]) {
+                            assert_eq!(read_binary(&mut socket, 1), [0]);
+                            let mut block = (chunk.len() as u16).to_le_bytes().to_vec();
+                            block.extend_from_slice(chunk);
+                            socket.send(Message::Binary(block)).unwrap();
+                        }
+                        socket.send(status(0)).unwrap();
+                    }
+                    op => panic!("unexpected op {}", op),
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+}
+
+/// Starts a fake board; returns its `host:port`.
+fn board(files: Files) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let files = files.clone();
+            std::thread::spawn(move || serve(stream.unwrap(), files));
+        }
+    });
+    address
+}
+
+#[test]
+fn files_round_trip() {
+    let files = Files::default();
+    let address = board(files.clone());
+    let mut repl = WebRepl::connect(&address, PASSWORD).unwrap();
+
+    // Spans several blocks on the way back
+    let script: Vec<u8> = (0..3000u32).map(|i| (i % 251) as u8).collect();
+    repl.put("main.py", &script).unwrap();
+    assert_eq!(files.lock().unwrap()["main.py"], script);
+    assert_eq!(repl.get("main.py").unwrap(), script);
+
+    let missing = repl.get("nope.py");
+    assert!(matches!(missing, Err(AppError::InvalidInput(_))));
+    let too_long = repl.put(&"x".repeat(65), b"");
+    assert!(matches!(too_long, Err(AppError::InvalidInput(_))));
+    repl.close();
+}
+
+#[test]
+fn wrong_password_is_refused() {
+    let address = board(Files::default());
+    let result = WebRepl::connect(&address, "guess");
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+}
+
+#[test]
+fn monitor_talks_to_the_repl() {
+    let files = Files::default();
+    let address = board(files.clone());
+    let monitor = Monitor::default();
+    let (tx, rx) = mpsc::channel();
+    monitor
+        .webrepl(&address, PASSWORD, move |bytes| {
+            let _ = tx.send(bytes.to_vec());
+        })
+        .unwrap();
+    assert_eq!(monitor.status().state, "webrepl");
+    assert_eq!(
+        monitor.status().port_name,
+        Some(format!("ws://{}", address))
+    );
+
+    monitor.send(b"print(1 + 1)\r").unwrap();
+    let deadline = Instant::now() + Duration::from_secs(3);
+    let mut text = String::new();
+    while !text.contains("print(1 + 1)") {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let bytes = rx.recv_timeout(left).expect("REPL output");
+        text.push_str(&String::from_utf8_lossy(&bytes));
+    }
+    assert!(text.starts_with("WebREPL connected"), "{:?}", text);
+
+    // Transfers share the session with the reader
+    monitor
+        .with_webrepl(|repl| repl.put("boot.py", b"# boot"))
+        .unwrap();
+    assert_eq!(files.lock().unwrap()["boot.py"], b"# boot");
+
+    monitor.disconnect().unwrap();
+    assert_eq!(monitor.status().state, "disconnected");
+    let closed = monitor.with_webrepl(|repl| repl.get("boot.py"));
+    assert!(matches!(closed, Err(AppError::NotConnected)));
+}
@@ -0,0 +1,194 @@
+//! Wi-Fi provisioning against a fake device that plays Espressif's
+//! provisioning manager with security 1, over an in-memory transport.
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use esp32dev_core::models::AppError;
+use esp32dev_core::tasks::TaskManager;
+use esp32dev_core::wifi_prov::proto::{Message, Writer};
+use esp32dev_core::wifi_prov::{self, ProvOptions, Transport};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+
+const DEVICE_RANDOM: [u8; 16] = [7; 16];
+
+struct FakeDevice {
+    pop: &'static str,
+    network: (&'static str, &'static str),
+    secret: Option<EphemeralSecret>,
+    public: PublicKey,
+    client_key: [u8; 32],
+    cipher: Option<Aes256Ctr>,
+    received: Option<(Vec<u8>, Vec<u8>)>,
+    polls: u32,
+}
+
+impl FakeDevice {
+    fn new(pop: &'static str, ssid: &'static str, password: &'static str) -> Self {
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        FakeDevice {
+            pop,
+            network: (ssid, password),
+            public: PublicKey::from(&secret),
+            secret: Some(secret),
+            client_key: [0; 32],
+            cipher: None,
+            received: None,
+            polls: 0,
+        }
+    }
+
+    fn crypt(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = data.to_vec();
+        self.cipher.as_mut().unwrap().apply_keystream(&mut out);
+        out
+    }
+
+    fn session(&mut self, request: &[u8]) -> Vec<u8> {
+        let payload = Message::parse(request).unwrap().message(11).unwrap();
+        let device_key = self.public;
+        let response = match payload.varint(1) {
+            0 => {
+                let command = payload.message(20).unwrap();
+                self.client_key = command.bytes(1).try_into().unwrap();
+                let shared = self
+                    .secret
+                    .take()
+                    .unwrap()
+                    .diffie_hellman(&PublicKey::from(self.client_key));
+                let mut key = *shared.as_bytes();
+                // Without a proof of possession the key is used as is
+                if !self.pop.is_empty() {
+                    for (k, p) in key.iter_mut().zip(Sha256::digest(self.pop.as_bytes())) {
+                        *k ^= p;
+                    }
+                }
+                self.cipher = Some(Aes256Ctr::new(&key.into(), &DEVICE_RANDOM.into()));
+                Writer::new().varint(1, 1).message(
+                    21,
+                    Writer::new()
+                        .varint(1, 0)
+                        .bytes(2, device_key.as_bytes())
+                        .bytes(3, &DEVICE_RANDOM),
+                )
+            }
+            _ => {
+                let verify = payload.message(22).unwrap().bytes(2).to_vec();
+                if self.crypt(&verify) != device_key.as_bytes() {
+                    // CryptoError
+                    Writer::new()
+                        .varint(1, 3)
+                        .message(23, Writer::new().varint(1, 6))
+                } else {
+                    let client_key = self.client_key;
+                    let proof = self.crypt(&client_key);
+                    Writer::new()
+                        .varint(1, 3)
+                        .message(23, Writer::new().varint(1, 0).bytes(3, &proof))
+                }
+            }
+        };
+        Writer::new().varint(2, 1).message(11, response).finish()
+    }
+
+    fn config(&mut self, request: &[u8]) -> Vec<u8> {
+        let request = Message::parse(&self.crypt(request)).unwrap();
+        let response = match request.varint(1) {
+            2 => {
+                let config = request.message(12).unwrap();
+                self.received = Some((config.bytes(1).to_vec(), config.bytes(2).to_vec()));
+                Writer::new()
+                    .varint(1, 3)
+                    .message(13, Writer::new().varint(1, 0))
+            }
+            4 => Writer::new()
+                .varint(1, 5)
+                .message(15, Writer::new().varint(1, 0)),
+            _ => {
+                self.polls += 1;
+                let (ssid, password) = self.received.clone().unwrap();
+                let status = if self.polls == 1 {
+                    // Connecting
+                    Writer::new().varint(1, 0).varint(2, 1)
+                } else if (ssid.as_slice(), password.as_slice())
+                    == (self.network.0.as_bytes(), self.network.1.as_bytes())
+                {
+                    Writer::new()
+                        .varint(1, 0)
+                        .varint(2, 0)
+                        .message(11, Writer::new().bytes(1, b"192.168.1.50"))
+                } else {
+                    // Failed with an auth error
+                    Writer::new().varint(1, 0).varint(2, 3).varint(10, 0)
+                };
+                Writer::new().varint(1, 1).message(11, status)
+            }
+        };
+        self.crypt(&response.finish())
+    }
+}
+
+impl Transport for FakeDevice {
+    fn exchange(&mut self, endpoint: &str, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        Ok(match endpoint {
+            "proto-ver" => br#"{"prov":{"ver":"v1.1","sec_ver":1,"cap":["wifi_scan"]}}"#.to_vec(),
+            "prov-session" => self.session(data),
+            "prov-config" => self.config(data),
+            other => panic!("unexpected endpoint {}", other),
+        })
+    }
+}
+
+fn options<'a>(pop: &'a str, passphrase: &'a str) -> ProvOptions<'a> {
+    ProvOptions {
+        device: "PROV_FAKE",
+        security: None,
+        pop,
+        username: wifi_prov::DEFAULT_USERNAME,
+        ssid: "home",
+        passphrase,
+    }
+}
+
+#[test]
+fn security1_provisioning_reports_the_address() {
+    let task = TaskManager::default().start("ble_provision", Some("PROV_FAKE"));
+    let mut device = FakeDevice::new("abcd1234", "home", "hunter22");
+
+    let outcome =
+        wifi_prov::provision_with(&mut device, &options("abcd1234", "hunter22"), &task).unwrap();
+    assert_eq!(outcome.ip.as_deref(), Some("192.168.1.50"));
+    assert_eq!(
+        device.received,
+        Some((b"home".to_vec(), b"hunter22".to_vec()))
+    );
+}
+
+#[test]
+fn wrong_pop_is_rejected() {
+    let task = TaskManager::default().start("ble_provision", Some("PROV_FAKE"));
+    let mut device = FakeDevice::new("abcd1234", "home", "hunter22");
+
+    let result = wifi_prov::provision_with(&mut device, &options("guess", "hunter22"), &task);
+    assert!(
+        matches!(result, Err(AppError::InvalidInput(_))),
+        "{:?}",
+        result.map(|o| o.ip)
+    );
+    assert_eq!(device.received, None);
+}
+
+#[test]
+fn failed_join_is_reported() {
+    let task = TaskManager::default().start("ble_provision", Some("PROV_FAKE"));
+    let mut device = FakeDevice::new("", "home", "hunter22");
+
+    let result = wifi_prov::provision_with(&mut device, &options("", "wrong"), &task);
+    assert!(
+        matches!(result, Err(AppError::Connection(_))),
+        "{:?}",
+        result.map(|o| o.ip)
+    );
+}
@@ -0,0 +1,173 @@
+// GPIO helper: line protocol on the console UART to drive and read pins.
+// See ../README.md for the requests and replies.
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <strings.h>
+
+#include "driver/gpio.h"
+#include "driver/uart.h"
+#include "esp_adc/adc_cali.h"
+#include "esp_adc/adc_cali_scheme.h"
+#include "esp_adc/adc_oneshot.h"
+#include "freertos/FreeRTOS.h"
+
+#define PROTOCOL "gpio-helper 1"
+#define CONSOLE_UART UART_NUM_0
+#define LINE_MAX 64
+
+typedef enum { MODE_OFF, MODE_IN, MODE_PULLUP, MODE_OUT, MODE_ADC } pin_mode_t;
+
+static pin_mode_t modes[GPIO_NUM_MAX];
+static adc_oneshot_unit_handle_t adc_units[2];
+static adc_cali_handle_t adc_cali[2];
+
+static adc_oneshot_unit_handle_t adc_unit(adc_unit_t unit)
+{
+    if (adc_units[unit] == NULL) {
+        adc_oneshot_unit_init_cfg_t cfg = {.unit_id = unit};
+        if (adc_oneshot_new_unit(&cfg, &adc_units[unit]) != ESP_OK) {
+            return NULL;
+        }
+#if ADC_CALI_SCHEME_CURVE_FITTING_SUPPORTED
+        adc_cali_curve_fitting_config_t cali = {
+            .unit_id = unit, .atten = ADC_ATTEN_DB_12, .bitwidth = ADC_BITWIDTH_DEFAULT};
+        adc_cali_create_scheme_curve_fitting(&cali, &adc_cali[unit]);
+#elif ADC_CALI_SCHEME_LINE_FITTING_SUPPORTED
+        adc_cali_line_fitting_config_t cali = {
+            .unit_id = unit, .atten = ADC_ATTEN_DB_12, .bitwidth = ADC_BITWIDTH_DEFAULT};
+        adc_cali_create_scheme_line_fitting(&cali, &adc_cali[unit]);
+#endif
+    }
+    return adc_units[unit];
+}
+
+static const char *set_mode(int pin, const char *mode)
+{
+    gpio_reset_pin(pin);
+    if (strcasecmp(mode, "off") == 0) {
+        modes[pin] = MODE_OFF;
+    } else if (strcasecmp(mode, "in") == 0 || strcasecmp(mode, "pullup") == 0) {
+        gpio_set_direction(pin, GPIO_MODE_INPUT);
+        modes[pin] = strcasecmp(mode, "in") == 0 ? MODE_IN : MODE_PULLUP;
+        gpio_set_pull_mode(pin, modes[pin] == MODE_PULLUP ? GPIO_PULLUP_ONLY : GPIO_FLOATING);
+    } else if (strcasecmp(mode, "out") == 0) {
+        gpio_set_direction(pin, GPIO_MODE_INPUT_OUTPUT);
+        modes[pin] = MODE_OUT;
+    } else if (strcasecmp(mode, "adc") == 0) {
+        adc_unit_t unit;
+        adc_channel_t channel;
+        if (adc_oneshot_io_to_channel(pin, &unit, &channel) != ESP_OK) {
+            return "not an ADC pin";
+        }
+        adc_oneshot_unit_handle_t handle = adc_unit(unit);
+        adc_oneshot_chan_cfg_t cfg = {.atten = ADC_ATTEN_DB_12, .bitwidth = ADC_BITWIDTH_DEFAULT};
+        if (handle == NULL || adc_oneshot_config_channel(handle, channel, &cfg) != ESP_OK) {
+            return "ADC unavailable";
+        }
+        modes[pin] = MODE_ADC;
+    } else {
+        return "unknown mode";
+    }
+    return NULL;
+}
+
+static int read_mv(int pin)
+{
+    adc_unit_t unit;
+    adc_channel_t channel;
+    int raw = 0;
+    int mv = 0;
+    adc_oneshot_io_to_channel(pin, &unit, &channel);
+    if (adc_oneshot_read(adc_units[unit], channel, &raw) != ESP_OK) {
+        return 0;
+    }
+    if (adc_cali[unit] == NULL || adc_cali_raw_to_voltage(adc_cali[unit], raw, &mv) != ESP_OK) {
+        // Uncalibrated: full scale is roughly 3.1 V at 12 dB
+        mv = raw * 3100 / 4095;
+    }
+    return mv;
+}
+
+static void poll(void)
+{
+    printf("OK");
+    for (int pin = 0; pin < GPIO_NUM_MAX; pin++) {
+        switch (modes[pin]) {
+        case MODE_IN:
+        case MODE_PULLUP:
+            printf(" %d:%d", pin, gpio_get_level(pin));
+            break;
+        case MODE_ADC:
+            printf(" %d:%d", pin, read_mv(pin));
+            break;
+        default:
+            break;
+        }
+    }
+    printf("\n");
+}
+
+static void handle(char *line)
+{
+    char *saveptr = NULL;
+    char *cmd = strtok_r(line, " \t", &saveptr);
+    char *arg1 = strtok_r(NULL, " \t", &saveptr);
+    char *arg2 = strtok_r(NULL, " \t", &saveptr);
+    if (cmd == NULL) {
+        return;
+    }
+
+    if (strcasecmp(cmd, "PING") == 0) {
+        printf("OK " PROTOCOL "\n");
+        return;
+    }
+    if (strcasecmp(cmd, "POLL") == 0) {
+        poll();
+        return;
+    }
+
+    int pin = arg1 ? atoi(arg1) : -1;
+    if (strcasecmp(cmd, "MODE") != 0 && strcasecmp(cmd, "SET") != 0) {
+        printf("ERR unknown command\n");
+    } else if (arg2 == NULL || !GPIO_IS_VALID_GPIO(pin)) {
+        printf("ERR invalid pin\n");
+    } else if (strcasecmp(cmd, "MODE") == 0) {
+        const char *err = set_mode(pin, arg2);
+        if (err) {
+            printf("ERR %s\n", err);
+        } else {
+            printf("OK\n");
+        }
+    } else if (modes[pin] != MODE_OUT || !GPIO_IS_VALID_OUTPUT_GPIO(pin)) {
+        printf("ERR not an output\n");
+    } else {
+        gpio_set_level(pin, atoi(arg2) ? 1 : 0);
+        printf("OK\n");
+    }
+}
+
+void app_main(void)
+{
+    uart_driver_install(CONSOLE_UART, 256, 0, 0, NULL, 0);
+    printf("READY " PROTOCOL "\n");
+    fflush(stdout);
+
+    char line[LINE_MAX];
+    size_t len = 0;
+    for (;;) {
+        uint8_t c;
+        if (uart_read_bytes(CONSOLE_UART, &c, 1, portMAX_DELAY) != 1) {
+            continue;
+        }
+        if (c == '\r' || c == '\n') {
+            line[len] = '\0';
+            handle(line);
+            len = 0;
+            fflush(stdout);
+        } else if (len < LINE_MAX - 1) {
+            line[len++] = (char)c;
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use crate::settings::SettingsStore;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use esp32dev_core::api_auth;
+use esp32dev_core::lifecycle::DeviceTracker;
+use esp32dev_core::models::{
+    ApiSettings, AppError, ChipDetails, DeviceSnapshot, DeviceStatus, MonitorStatus,
+    SerialPortEntry, TaskInfo,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{EventId, Listener, Manager};
+use tokio::sync::{broadcast, oneshot};
+use tracing::{info, warn};
+
+// Backend events mirrored to WebSocket clients; `serial-read` carries
+// base64 of the received bytes
+const RELAYED_EVENTS: [&str; 9] = [
+    "serial-read",
+    "task-progress",
+    "monitor-state",
+    "script-output",
+    "test-output",
+    "soak-output",
+    "boot-timing",
+    "device-state",
+    "notify",
+];
+
+// Holds the install's bearer token, see `api_auth`
+const TOKEN_FILE: &str = "api_token";
+
+struct Running {
+    settings: ApiSettings,
+    shutdown: oneshot::Sender<()>,
+    listeners: Vec<EventId>,
+}
+
+/// Optional localhost control API so editors and test rigs can drive the
+/// app. Every route goes through the same command functions as the GUI,
+/// and the GUI keeps receiving the events those commands emit.
+#[derive(Default)]
+pub struct ApiServer {
+    running: Mutex<Option<Running>>,
+}
+
+impl ApiServer {
+    /// Starts, stops or restarts the server to match `settings`.
+    pub fn apply(&self, app: &tauri::AppHandle, settings: &ApiSettings) -> Result<(), AppError> {
+        let mut running = self.running.lock().unwrap();
+        let wanted = settings.enabled.then_some(settings);
+        if running.as_ref().map(|r| &r.settings) == wanted {
+            return Ok(());
+        }
+
+        if let Some(old) = running.take() {
+            let _ = old.shutdown.send(());
+            for id in old.listeners {
+                app.unlisten(id);
+            }
+            info!("API server stopped");
+        }
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        let token = token(app)?;
+        // Bind up front so a taken port is reported to the caller
+        let listener = std::net::TcpListener::bind(("127.0.0.1", settings.port))
+            .and_then(|l| l.set_nonblocking(true).map(|_| l))
+            .map_err(|e| {
+                AppError::Config(format!("API port {} unavailable: {}", settings.port, e))
+            })?;
+
+        let (events, _) = broadcast::channel::<String>(256);
+        let listeners = RELAYED_EVENTS
+            .iter()
+            .map(|&name| {
+                let events = events.clone();
+                app.listen_any(name, move |event| {
+                    let _ = events.send(format!(
+                        "{{\"event\":\"{}\",\"payload\":{}}}",
+                        name,
+                        event.payload()
+                    ));
+                })
+            })
+            .collect();
+
+        let state = ApiState {
+            app: app.clone(),
+            events,
+        };
+        let router = Router::new()
+            .route("/api/status", get(status))
+            .route("/api/chip", get(cached_chip).post(chip_info))
+            .route("/api/devices", get(devices))
+            .route("/api/ports", get(ports))
+            .route("/api/tasks", get(list_tasks))
+            .route("/api/tasks/{id}/cancel", post(cancel_task))
+            .route("/api/flash", post(flash))
+            .route("/api/erase", post(erase))
+            .route("/api/monitor/connect", post(monitor_connect))
+            .route("/api/monitor/disconnect", post(monitor_disconnect))
+            .route("/api/monitor/send", post(monitor_send))
+            .route("/api/monitor/status", get(monitor_status))
+            .route("/api/script", post(run_script))
+            .route("/api/events", get(events_socket))
+            .layer(middleware::from_fn_with_state(
+                Guard {
+                    port: settings.port,
+                    token: token.into(),
+                },
+                local_only,
+            ))
+            .with_state(state);
+
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let port = settings.port;
+        tauri::async_runtime::spawn(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(l) => l,
+                Err(e) => {
+                    warn!("API server failed to start: {}", e);
+                    return;
+                }
+            };
+            info!("API server listening on 127.0.0.1:{}", port);
+            let server = axum::serve(listener, router).with_graceful_shutdown(async {
+                stopped.await.ok();
+            });
+            if let Err(e) = server.await {
+                warn!("API server error: {}", e);
+            }
+        });
+
+        *running = Some(Running {
+            settings: settings.clone(),
+            shutdown,
+            listeners,
+        });
+        Ok(())
+    }
+}
+
+fn token_path(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::Config(format!("Config Dir Error: {}", e)))?;
+    Ok(dir.join(TOKEN_FILE))
+}
+
+/// The token clients send as `Authorization: Bearer <token>`, created on
+/// first use and kept for the install. It isn't part of the settings, so
+/// exported configurations don't carry it.
+pub fn token(app: &tauri::AppHandle) -> Result<String, AppError> {
+    let path = token_path(app)?;
+    if let Ok(token) = std::fs::read_to_string(&path) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let token = api_auth::new_token();
+    std::fs::write(&path, &token)?;
+    info!("API token created in {}", path.display());
+    Ok(token)
+}
+
+/// Starts the server at launch if it was left enabled.
+pub fn init(app: &tauri::AppHandle) {
+    let api = app.state::<SettingsStore>().get().api;
+    if let Err(e) = app.state::<ApiServer>().apply(app, &api) {
+        warn!("{}", e);
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    app: tauri::AppHandle,
+    events: broadcast::Sender<String>,
+}
+
+struct ApiError(AppError);
+
+impl From<AppError> for ApiError {
+    fn from(e: AppError) -> Self {
+        ApiError(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self.0 {
+            AppError::InvalidInput(_) | AppError::Script(_) | AppError::FlashOverflow { .. } => {
+                StatusCode::BAD_REQUEST
+            }
+            AppError::PortNotFound(_) | AppError::TaskNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::PortBusy(_) | AppError::NotConnected | AppError::Cancelled => {
+                StatusCode::CONFLICT
+            }
+            AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            AppError::SecureDownload(_) | AppError::SecureBoot(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(self.0)).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Clone)]
+struct Guard {
+    port: u16,
+    token: Arc<str>,
+}
+
+/// Refuses requests from web pages, see `api_auth`; anything but a GET
+/// needs the token too.
+async fn local_only(State(guard): State<Guard>, req: Request, next: Next) -> Response {
+    let header = |name: header::HeaderName| req.headers().get(name).and_then(|v| v.to_str().ok());
+    let host = header(header::HOST).or_else(|| req.uri().authority().map(|a| a.as_str()));
+    if !api_auth::host_allowed(host, guard.port) {
+        return (StatusCode::FORBIDDEN, "Unexpected Host").into_response();
+    }
+    if !api_auth::origin_allowed(header(header::ORIGIN)) {
+        return (
+            StatusCode::FORBIDDEN,
+            "Cross-origin requests are not allowed",
+        )
+            .into_response();
+    }
+    if req.method() != Method::GET
+        && !api_auth::bearer_matches(header(header::AUTHORIZATION), &guard.token)
+    {
+        return (StatusCode::UNAUTHORIZED, "Missing or wrong API token").into_response();
+    }
+    next.run(req).await
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PortQuery {
+    port_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashBody {
+    port_name: String,
+    firmware_path: String,
+    flash_address: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectBody {
+    port_name: String,
+    baud_rate: Option<u32>,
+    boot_marker: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SendBody {
+    data: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptBody {
+    port_name: String,
+    baud_rate: Option<u32>,
+    script: String,
+}
+
+async fn status(State(s): State<ApiState>) -> Json<DeviceStatus> {
+    Json(crate::check_device_status(s.app.state()))
+}
+
+/// Details from the last probe; a GET never talks to the board.
+async fn cached_chip(
+    State(s): State<ApiState>,
+    Query(q): Query<PortQuery>,
+) -> ApiResult<ChipDetails> {
+    let chip = s
+        .app
+        .state::<DeviceTracker>()
+        .cached_chip(&q.port_name)
+        .ok_or_else(|| {
+            AppError::PortNotFound(format!(
+                "{} hasn't been probed, POST /api/chip to probe it",
+                q.port_name
+            ))
+        })?;
+    Ok(Json(chip))
+}
+
+async fn chip_info(
+    State(s): State<ApiState>,
+    Json(body): Json<PortQuery>,
+) -> ApiResult<ChipDetails> {
+    Ok(Json(
+        crate::get_chip_info(s.app.clone(), s.app.state(), body.port_name).await?,
+    ))
+}
+
+async fn devices(State(s): State<ApiState>) -> Json<Vec<DeviceSnapshot>> {
+    Json(crate::list_devices(s.app.state()))
+}
+
+async fn ports(State(s): State<ApiState>) -> Json<Vec<SerialPortEntry>> {
+    Json(crate::list_serial_ports(s.app.state()))
+}
+
+async fn list_tasks(State(s): State<ApiState>) -> Json<Vec<TaskInfo>> {
+    Json(crate::list_tasks(s.app.state()))
+}
+
+async fn cancel_task(State(s): State<ApiState>, Path(id): Path<u64>) -> ApiResult<()> {
+    Ok(Json(crate::cancel_task(s.app.state(), id)?))
+}
+
+async fn flash(State(s): State<ApiState>, Json(body): Json<FlashBody>) -> ApiResult<String> {
+    let address = body
+        .flash_address
+        .unwrap_or_else(|| s.app.state::<SettingsStore>().get().flash.address);
+    let app = s.app.clone();
+    Ok(Json(
+        crate::flash_firmware(
+            app.clone(),
+            app.state(),
+            app.state(),
+            body.port_name,
+            body.firmware_path,
+            address,
+        )
+        .await?,
+    ))
+}
+
+async fn erase(State(s): State<ApiState>, Json(body): Json<PortQuery>) -> ApiResult<String> {
+    let app = s.app.clone();
+    Ok(Json(
+        crate::erase_flash(app.clone(), app.state(), body.port_name).await?,
+    ))
+}
+
+async fn monitor_connect(
+    State(s): State<ApiState>,
+    Json(body): Json<ConnectBody>,
+) -> ApiResult<String> {
+    let app = s.app.clone();
+    let baud_rate = body
+        .baud_rate
+        .unwrap_or_else(|| app.state::<SettingsStore>().get().default_baud);
+    Ok(Json(
+        crate::monitor_connect(
+            app.clone(),
+            app.state(),
+            app.state(),
+            body.port_name,
+            baud_rate,
+            body.boot_marker,
+        )
+        .await?,
+    ))
+}
+
+async fn monitor_disconnect(State(s): State<ApiState>) -> ApiResult<String> {
+    Ok(Json(
+        crate::monitor_disconnect(s.app.state(), s.app.state()).await?,
+    ))
+}
+
+async fn monitor_send(State(s): State<ApiState>, Json(body): Json<SendBody>) -> ApiResult<String> {
+    Ok(Json(crate::monitor_send(s.app.state(), body.data).await?))
+}
+
+async fn monitor_status(State(s): State<ApiState>) -> Json<MonitorStatus> {
+    Json(crate::monitor_status(s.app.state()))
+}
+
+async fn run_script(State(s): State<ApiState>, Json(body): Json<ScriptBody>) -> ApiResult<String> {
+    let app = s.app.clone();
+    let baud_rate = body
+        .baud_rate
+        .unwrap_or_else(|| app.state::<SettingsStore>().get().default_baud);
+    Ok(Json(
+        crate::run_script(
+            app.clone(),
+            app.state(),
+            app.state(),
+            app.state(),
+            body.port_name,
+            baud_rate,
+            body.script,
+        )
+        .await?,
+    ))
+}
+
+async fn events_socket(ws: WebSocketUpgrade, State(s): State<ApiState>) -> Response {
+    let events = s.events.subscribe();
+    ws.on_upgrade(move |socket| stream_events(socket, events))
+}
+
+async fn stream_events(mut socket: WebSocket, mut events: broadcast::Receiver<String>) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(text) => {
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client just misses some output
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
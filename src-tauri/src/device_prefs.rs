@@ -0,0 +1,51 @@
+use esp32dev_core::models::{AppError, DevicePrefs};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+const PREFS_FILE: &str = "device_prefs.json";
+
+fn prefs_path(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::Config(format!("Config Dir Error: {}", e)))?;
+    Ok(dir.join(PREFS_FILE))
+}
+
+pub fn load_all(app: &tauri::AppHandle) -> HashMap<String, DevicePrefs> {
+    // A missing or corrupt file just means nothing has been remembered yet
+    prefs_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn load(app: &tauri::AppHandle, serial_number: &str) -> Option<DevicePrefs> {
+    load_all(app).remove(serial_number)
+}
+
+pub fn save(
+    app: &tauri::AppHandle,
+    serial_number: &str,
+    prefs: DevicePrefs,
+) -> Result<(), AppError> {
+    merge(app, HashMap::from([(serial_number.to_string(), prefs)]))
+}
+
+/// Adds `prefs` to the remembered ones, replacing those of the same boards.
+pub fn merge(app: &tauri::AppHandle, prefs: HashMap<String, DevicePrefs>) -> Result<(), AppError> {
+    let path = prefs_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut all = load_all(app);
+    all.extend(prefs);
+
+    let text = serde_json::to_string_pretty(&all).map_err(|e| AppError::Internal(e.to_string()))?;
+    fs::write(&path, text)?;
+    Ok(())
+}
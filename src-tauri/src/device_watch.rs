@@ -0,0 +1,77 @@
+use crate::settings::SettingsStore;
+use esp32dev_core::devices;
+use esp32dev_core::esp_interaction::{self, FlasherSessions};
+use esp32dev_core::lifecycle::DeviceTracker;
+use esp32dev_core::models::MonitorStateEvent;
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::tasks::TaskManager;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tracing::debug;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Spawns the thread that drives `DeviceTracker`: scans the bus and
+/// identifies newly detected boards.
+pub fn start(app: &tauri::AppHandle) {
+    let settings = app.state::<SettingsStore>().inner().clone();
+    let tracker = app.state::<DeviceTracker>().inner().clone();
+    let monitor = app.state::<Monitor>().inner().clone();
+    let tasks = app.state::<TaskManager>().inner().clone();
+    let sessions = app.state::<FlasherSessions>().inner().clone();
+    let app = app.clone();
+
+    let mut monitored = None::<String>;
+    std::thread::spawn(move || loop {
+        let found = devices::scan(&settings.get().vid_allowlist);
+        let ports: Vec<String> = found.iter().filter_map(|d| d.port_name.clone()).collect();
+        let serials: HashMap<String, String> = found
+            .iter()
+            .filter_map(|d| Some((d.port_name.clone()?, d.serial_number.clone()?)))
+            .collect();
+        // A re-plugged board needs a fresh handshake
+        sessions.retain(&ports);
+        tracker.sync(found);
+
+        // The monitor follows a native USB board that re-enumerated
+        let active = monitor.active_port();
+        if let (Some(before), Some(now)) = (&monitored, &active) {
+            if before != now {
+                tracker.monitoring(now, true);
+                let _ = app.emit(
+                    "monitor-state",
+                    MonitorStateEvent {
+                        state: "moved".to_string(),
+                        port_name: now.clone(),
+                        reason: before.clone(),
+                    },
+                );
+            }
+        }
+        monitored = active;
+
+        // Probing resets the chip, never do it under a running operation
+        let busy = tasks.list().iter().any(|t| t.state == "running");
+        if !busy {
+            for port in tracker.unidentified() {
+                if monitor.active_port().as_deref() == Some(port.as_str()) {
+                    // Came back while the monitor was waiting for it
+                    tracker.monitoring(&port, true);
+                    continue;
+                }
+                let following = monitor.usb_serial();
+                if following.is_some() && following.as_ref() == serials.get(&port) {
+                    // Came back under another name and the monitor picks it
+                    // up shortly; probing would reset the board under it
+                    continue;
+                }
+                debug!("Identifying {}", port);
+                let result = esp_interaction::connect_and_get_info(&sessions, &port);
+                tracker.identified(&port, &result);
+            }
+        }
+
+        std::thread::sleep(SCAN_INTERVAL);
+    });
+}
@@ -0,0 +1,150 @@
+use crate::logging::RecentLogs;
+use crate::settings::SettingsStore;
+use esp32dev_core::models::AppError;
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::tasks::TaskManager;
+use esp32dev_core::{config_bundle, devices, esp_interaction};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use tauri::Manager;
+use tracing::info;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+// Lines of the in-memory log tail included next to the rotated files
+const LOG_TAIL_LINES: usize = 2000;
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("\"{}\"", e))
+}
+
+fn system_info(app: &tauri::AppHandle) -> String {
+    let package = app.package_info();
+    format!(
+        "app: {} {}\nos: {} ({})\narch: {}\n",
+        package.name,
+        package.version,
+        std::env::consts::OS,
+        std::env::consts::FAMILY,
+        std::env::consts::ARCH,
+    )
+}
+
+/// Serial ports as the OS reports them, then every USB device on the bus so
+/// boards without a working driver still show up.
+fn usb_topology() -> String {
+    let mut text = String::from("# Serial ports\n");
+    match serialport::available_ports() {
+        Ok(ports) => {
+            for p in ports {
+                let _ = writeln!(text, "{} {:?}", p.port_name, p.port_type);
+            }
+        }
+        Err(e) => {
+            let _ = writeln!(text, "error: {}", e);
+        }
+    }
+
+    text.push_str("\n# USB devices\n");
+    match nusb::list_devices() {
+        Ok(list) => {
+            for dev in list {
+                let _ = writeln!(
+                    text,
+                    "bus {:03} addr {:03} {:04X}:{:04X} {} / {} (serial {})",
+                    dev.bus_number(),
+                    dev.device_address(),
+                    dev.vendor_id(),
+                    dev.product_id(),
+                    dev.manufacturer_string().unwrap_or("-"),
+                    dev.product_string().unwrap_or("-"),
+                    dev.serial_number().unwrap_or("-"),
+                );
+            }
+        }
+        Err(e) => {
+            let _ = writeln!(text, "error: {}", e);
+        }
+    }
+    text
+}
+
+/// Writes a zip with everything useful for a bug report: system and USB
+/// info, device status, chip details, settings (production mode left
+/// out), tasks, recent monitor output and the backend logs.
+pub fn write_bundle(app: &tauri::AppHandle, path: &Path, driver_ok: bool) -> Result<(), AppError> {
+    let settings = app.state::<SettingsStore>().get();
+    let monitor = app.state::<Monitor>().inner().clone();
+
+    let status = devices::detect(&settings.vid_allowlist);
+    // Probing resets the board, so the monitor is paused around it like for flashing
+    let chip = status.port_name.as_ref().map(|port| {
+        crate::with_flasher_port(app, port, "diagnostics", |sessions| {
+            esp_interaction::connect_and_get_info(sessions, port)
+        })
+    });
+    let chip_json = match chip {
+        Some(Ok(details)) => to_json(&details),
+        Some(Err(e)) => to_json(&e),
+        None => "null".to_string(),
+    };
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        ("system.txt".to_string(), system_info(app).into_bytes()),
+        ("usb.txt".to_string(), usb_topology().into_bytes()),
+        (
+            "device_status.json".to_string(),
+            to_json(&status).into_bytes(),
+        ),
+        ("chip_details.json".to_string(), chip_json.into_bytes()),
+        (
+            "driver.json".to_string(),
+            format!("{{ \"ch34x_driver\": {} }}", driver_ok).into_bytes(),
+        ),
+        (
+            "settings.json".to_string(),
+            to_json(&config_bundle::shareable_settings(&settings)).into_bytes(),
+        ),
+        (
+            "tasks.json".to_string(),
+            to_json(&app.state::<TaskManager>().list()).into_bytes(),
+        ),
+        ("monitor.txt".to_string(), monitor.recent_output()),
+        (
+            "backend.log".to_string(),
+            app.state::<RecentLogs>()
+                .tail(LOG_TAIL_LINES)
+                .join("\n")
+                .into_bytes(),
+        ),
+    ];
+
+    // Rotated log files from previous days
+    if let Ok(dir) = app.path().app_log_dir() {
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("esp32dev") {
+                    if let Ok(data) = fs::read(entry.path()) {
+                        files.push((format!("logs/{}", name), data));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default();
+    for (name, data) in files {
+        zip.start_file(name, options)
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        zip.write_all(&data)?;
+    }
+    zip.finish().map_err(|e| AppError::Io(e.to_string()))?;
+
+    info!("Diagnostics written to {}", path.display());
+    Ok(())
+}
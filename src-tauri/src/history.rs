@@ -0,0 +1,58 @@
+use esp32dev_core::models::AppError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// Speeds of past flashes, `FlashRecord`s
+pub const FLASH_HISTORY: &str = "flash_history.json";
+/// Milestones of past boots, `BootTiming`s
+pub const BOOT_HISTORY: &str = "boot_history.json";
+// Entries remembered per device, oldest dropped first
+const MAX_RECORDS: usize = 20;
+
+fn history_path(app: &tauri::AppHandle, file: &str) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::Config(format!("Config Dir Error: {}", e)))?;
+    Ok(dir.join(file))
+}
+
+fn load_all<T: DeserializeOwned>(app: &tauri::AppHandle, file: &str) -> HashMap<String, Vec<T>> {
+    // A missing or corrupt file just means nothing was recorded yet
+    history_path(app, file)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Entries of the device `key` in `file`, oldest first.
+pub fn load<T: DeserializeOwned>(app: &tauri::AppHandle, file: &str, key: &str) -> Vec<T> {
+    load_all(app, file).remove(key).unwrap_or_default()
+}
+
+pub fn record<T: Serialize + DeserializeOwned>(
+    app: &tauri::AppHandle,
+    file: &str,
+    key: &str,
+    entry: T,
+) -> Result<(), AppError> {
+    let path = history_path(app, file)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut all = load_all::<T>(app, file);
+    let entries = all.entry(key.to_string()).or_default();
+    entries.push(entry);
+    let excess = entries.len().saturating_sub(MAX_RECORDS);
+    entries.drain(..excess);
+
+    let text = serde_json::to_string_pretty(&all).map_err(|e| AppError::Internal(e.to_string()))?;
+    fs::write(&path, text)?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::notifications;
+use esp32dev_core::inventory::Inventory;
+use esp32dev_core::lifecycle::DeviceTracker;
+use esp32dev_core::models::{
+    AppError, DeviceSnapshot, DeviceState, InventoryBatch, InventoryStatus, Notification,
+};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager};
+use tracing::warn;
+
+/// Inventory mode. Device events are fed in from the tracker, so boards
+/// are recorded whatever page the window shows.
+#[derive(Default)]
+pub struct InventoryCollector {
+    inventory: Mutex<Option<Inventory>>,
+}
+
+impl InventoryCollector {
+    /// Starts writing boards to `path`, beginning with those already
+    /// identified on the bus.
+    pub fn start(
+        &self,
+        app: &tauri::AppHandle,
+        path: &Path,
+        batch: InventoryBatch,
+    ) -> Result<InventoryStatus, AppError> {
+        *self.inventory.lock().unwrap() = Some(Inventory::open(path, batch)?);
+        for mut device in app.state::<DeviceTracker>().list() {
+            if device.state == DeviceState::Identified {
+                device.previous = None;
+                self.observe(app, &device);
+            }
+        }
+        Ok(self.status())
+    }
+
+    pub fn stop(&self) {
+        *self.inventory.lock().unwrap() = None;
+    }
+
+    pub fn status(&self) -> InventoryStatus {
+        match &*self.inventory.lock().unwrap() {
+            Some(inventory) => InventoryStatus {
+                path: Some(inventory.path().display().to_string()),
+                batch: Some(inventory.batch().clone()),
+                count: inventory.count(),
+            },
+            None => InventoryStatus::default(),
+        }
+    }
+
+    /// Records `device` if inventory mode is on and it was just identified.
+    pub fn observe(&self, app: &tauri::AppHandle, device: &DeviceSnapshot) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let result = match self.inventory.lock().unwrap().as_mut() {
+            Some(inventory) => inventory.record(device, now),
+            None => return,
+        };
+        let (level, kind, message) = match result {
+            Ok(Some(row)) => {
+                let _ = app.emit("inventory-row", &row);
+                if row.duplicate {
+                    ("info", "inventory_duplicate", row.mac_address)
+                } else {
+                    ("success", "inventory_recorded", row.mac_address)
+                }
+            }
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Inventory: {}", e);
+                ("error", "inventory_failed", e.message())
+            }
+        };
+        notifications::emit(
+            app,
+            Some(Notification {
+                level: level.to_string(),
+                kind: kind.to_string(),
+                port_name: device.status.port_name.clone(),
+                message: Some(message),
+            }),
+        );
+    }
+}
@@ -1,8 +1,83 @@
-mod esp_interaction;
-mod models;
+mod api_server;
+mod device_prefs;
+mod device_watch;
+mod diagnostics;
+mod history;
+mod inventory;
+mod locales;
+mod logging;
+mod notifications;
+mod ota_server;
+mod settings;
+mod shortcuts;
+mod triggers;
 
-use models::{ChipDetails, DeviceStatus};
-use serialport::SerialPortType;
+use api_server::ApiServer;
+use esp32dev_core::arduino;
+use esp32dev_core::benchmark::{self, BenchmarkOptions};
+use esp32dev_core::ble_uart;
+use esp32dev_core::boards;
+use esp32dev_core::boot_timing::BootTimer;
+use esp32dev_core::capture::{self, Direction, ReplayTarget};
+use esp32dev_core::circuitpython::{self, SyncWatch};
+use esp32dev_core::config_bundle::{self, ConfigBundle};
+use esp32dev_core::coredump::{self, CoreDumpReport};
+use esp32dev_core::device_db::{self, DeviceDb};
+use esp32dev_core::elf2image;
+use esp32dev_core::esp_interaction::{self, FlasherSessions};
+use esp32dev_core::espota::{self, OtaOptions, OtaTarget};
+use esp32dev_core::esptool;
+use esp32dev_core::examples;
+use esp32dev_core::flash_encryption;
+use esp32dev_core::flash_estimate;
+use esp32dev_core::flash_watch::FlashWatch;
+use esp32dev_core::full_image;
+use esp32dev_core::gdb::{self, Gdb, GdbEvent};
+use esp32dev_core::gpio;
+use esp32dev_core::idf;
+use esp32dev_core::improv;
+use esp32dev_core::kiosk;
+use esp32dev_core::label::{self, DeviceLabel, LabelSize};
+use esp32dev_core::lifecycle::DeviceTracker;
+use esp32dev_core::micropython::{self, RawRepl};
+use esp32dev_core::models::{
+    AppError, ArduinoStatus, BenchmarkRun, BoardDefinition, BootTiming, ChipDetails,
+    CircuitPyDrive, DevicePrefs, DeviceRecord, DeviceSnapshot, DeviceStatus, EsptoolCommands,
+    ExampleFirmware, FlashEstimate, FlashPlan, FlashRecord, FlashSegment, GdbStatus, ImageInfo,
+    ImprovOutcome, InventoryBatch, InventoryStatus, JtagProbe, KioskRun, KioskSettings,
+    MonitorLines, MonitorStateEvent, MonitorStatus, MpyEntry, MpyInfo, MpyRelease, NetLogProtocol,
+    Notification, OpenOcdStatus, PinReading, PinUsage, PlacedImage, ProductionRun, ProvDevice,
+    ProvOutcome, RecordQuery, RegisterValue, ReservedPin, RunReport, SerialPortEntry, Settings,
+    SignedImage, SoakReport, StackFrame, SvdPeripheral, TaskInfo, TerminalScreen, TestReport,
+    TextEncoding,
+};
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::mpy_firmware;
+use esp32dev_core::openocd::{self, OpenOcd};
+use esp32dev_core::pin_usage;
+use esp32dev_core::platformio;
+use esp32dev_core::reset_reason::ResetAnalyzer;
+use esp32dev_core::scripting::{self, ScriptOptions};
+use esp32dev_core::sequence;
+use esp32dev_core::signature::{self, SigningKey};
+use esp32dev_core::soak::{self, SoakOptions};
+use esp32dev_core::svd;
+use esp32dev_core::tasks::{TaskHandle, TaskManager};
+use esp32dev_core::time_sync;
+use esp32dev_core::wifi_prov::{self, ProvOptions};
+use inventory::InventoryCollector;
+use logging::RecentLogs;
+use ota_server::{OtaServer, OtaServerStatus};
+use serde::Deserialize;
+use settings::SettingsStore;
+use shortcuts::Shortcuts;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::path::BaseDirectory;
+use tauri::{Emitter, Manager, State};
+use tracing::{debug, info, warn};
+use triggers::MonitorTriggers;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -10,69 +85,109 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-fn check_device_status() -> DeviceStatus {
-    // 1. Try to find ESP32 in COM ports
-    if let Ok(ports) = serialport::available_ports() {
-        for p in ports {
-            if let SerialPortType::UsbPort(info) = p.port_type {
-                // Check for common ESP32 USB to UART bridge Vendor IDs
-                if [0x10C4, 0x1A86, 0x303A, 0x0403].contains(&info.vid) {
-                    return DeviceStatus {
-                        code: "ok".to_string(),
-                        message: format!("Connected ({})", p.port_name),
-                        port_name: Some(p.port_name),
-                        product_name: info.product,
-                        serial_number: info.serial_number,
-                        vid_pid: Some(format!("{:04X}:{:04X}", info.vid, info.pid)),
-                        connection_type: Some(if info.vid == 0x303A {
-                            "native_usb".to_string()
-                        } else {
-                            "uart_bridge".to_string()
-                        }),
-                    };
-                }
-            }
-        }
-    }
+fn check_device_status(settings: State<'_, SettingsStore>) -> DeviceStatus {
+    esp32dev_core::devices::detect(&settings.get().vid_allowlist)
+}
 
-    // 2. If no COM port found, check USB bus for missing drivers
-    if let Ok(devices) = nusb::list_devices() {
-        for dev in devices {
-            let vid = dev.vendor_id();
-            let pid = dev.product_id();
-            if [0x10C4, 0x1A86, 0x303A, 0x0403].contains(&vid) {
-                return DeviceStatus {
-                    code: "missing_driver".to_string(),
-                    message: "Driver Missing".to_string(),
-                    port_name: None,
-                    product_name: dev.product_string().map(|s| s.to_string()),
-                    serial_number: dev.serial_number().map(|s| s.to_string()),
-                    vid_pid: Some(format!("{:04X}:{:04X}", vid, pid)),
-                    connection_type: Some(if vid == 0x303A {
-                        "native_usb".to_string()
-                    } else {
-                        "uart_bridge".to_string()
-                    }),
-                };
-            }
+#[tauri::command]
+fn list_serial_ports(settings: State<'_, SettingsStore>) -> Vec<SerialPortEntry> {
+    esp32dev_core::devices::list_ports(&settings.get().vid_allowlist)
+}
+
+// How long a connected flasher stays open between operations
+const FLASHER_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+// Longest a chip may take to identify, reset retries and stub upload included
+const CHIP_INFO_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Runs `op` with whichever monitor holds `port_name`, the main one or the
+/// second pane of the split view, paused.
+fn with_monitors_paused<T>(
+    app: &tauri::AppHandle,
+    port_name: &str,
+    reason: &str,
+    op: impl FnOnce() -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let main = app.state::<Monitor>();
+    let split = app.state::<SplitMonitor>();
+    main.with_port(port_name, reason, emit_monitor_state(app), || {
+        split
+            .0
+            .with_port(port_name, reason, emit_split_state(app), op)
+    })
+}
+
+/// Runs a flasher operation with the monitor paused on `port_name`. When
+/// the monitor wants the port back, the cached session is closed first.
+pub(crate) fn with_flasher_port<T>(
+    app: &tauri::AppHandle,
+    port_name: &str,
+    reason: &str,
+    op: impl FnOnce(&FlasherSessions) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let sessions = app.state::<FlasherSessions>();
+    let shared = [
+        app.state::<Monitor>().active_port(),
+        app.state::<SplitMonitor>().0.active_port(),
+    ]
+    .iter()
+    .any(|held| held.as_deref() == Some(port_name));
+    with_monitors_paused(app, port_name, reason, || {
+        let result = op(&sessions);
+        if shared {
+            sessions.release(port_name);
         }
-    }
+        result
+    })
+}
 
-    // 3. No device found
-    DeviceStatus {
-        code: "none".to_string(),
-        message: "Disconnected".to_string(),
-        port_name: None,
-        product_name: None,
-        serial_number: None,
-        vid_pid: None,
-        connection_type: None,
+#[tauri::command]
+async fn get_chip_info(
+    app: tauri::AppHandle,
+    devices: State<'_, DeviceTracker>,
+    port_name: String,
+) -> Result<ChipDetails, AppError> {
+    // Known board: answer now, the watcher re-probes and pushes any change
+    if let Some(chip) = devices.cached_chip(&port_name) {
+        devices.refresh(&port_name);
+        return Ok(chip);
     }
+    let port = port_name.clone();
+    let handshake = tauri::async_runtime::spawn_blocking(move || {
+        with_flasher_port(&app, &port, "identify", |sessions| {
+            esp_interaction::connect_and_get_info(sessions, &port)
+        })
+    });
+    // A wedged chip keeps the blocking thread (and the port) until the
+    // serial reads give up, but the caller gets its answer now
+    let result = match tokio::time::timeout(CHIP_INFO_TIMEOUT, handshake).await {
+        Ok(joined) => joined.map_err(|e| AppError::Internal(e.to_string()))?,
+        Err(_) => Err(AppError::Timeout(format!(
+            "{} did not identify within {}s",
+            port_name,
+            CHIP_INFO_TIMEOUT.as_secs()
+        ))),
+    };
+    devices.identified(&port_name, &result);
+    result
+}
+
+/// Pin metadata for the Board View, `None` for chips without a drawing.
+#[tauri::command]
+fn get_board_definition(chip_model: String) -> Option<BoardDefinition> {
+    boards::for_chip(&chip_model)
 }
 
+/// Pins of the chip's board to keep buttons and loads off.
 #[tauri::command]
-async fn get_chip_info(port_name: String) -> ChipDetails {
-    esp_interaction::connect_and_get_info(&port_name)
+fn get_reserved_pins(chip_model: String) -> Vec<ReservedPin> {
+    boards::for_chip(&chip_model)
+        .map(|definition| boards::reserved_pins(&definition))
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+fn list_devices(devices: State<'_, DeviceTracker>) -> Vec<DeviceSnapshot> {
+    devices.list()
 }
 
 #[tauri::command]
@@ -104,209 +219,3138 @@ async fn check_ch34x_driver() -> bool {
 
 #[tauri::command]
 async fn flash_firmware(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
     port_name: String,
     firmware_path: String,
     flash_address: String,
-) -> Result<String, String> {
-    // Placeholder for actual flashing logic
-    // This requires spawning a separate task and managing state
-    println!(
-        "Flashing request: {} -> {} @ {}",
-        firmware_path, port_name, flash_address
+) -> Result<String, AppError> {
+    let segment = FlashSegmentArgs {
+        firmware_path,
+        flash_address,
+    };
+    flash_segments(app, tasks, settings, port_name, vec![segment], None, None).await
+}
+
+/// A row of the multi-file flash form; the address is still user text.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashSegmentArgs {
+    firmware_path: String,
+    flash_address: String,
+}
+
+#[tauri::command]
+async fn flash_segments(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    segments: Vec<FlashSegmentArgs>,
+    expected_chip: Option<String>,
+    baud_rate: Option<u32>,
+) -> Result<String, AppError> {
+    let flash_baud = baud_rate.unwrap_or(settings.get().flash.baud_rate);
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("flash", Some(&port_name));
+        // Parsed under the task so a bad address is reported like any failure
+        let parsed = segments
+            .into_iter()
+            .map(|s| {
+                Ok(FlashSegment {
+                    address: esp_interaction::parse_flash_address(&s.flash_address)?,
+                    path: s.firmware_path,
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>();
+        let result = parsed.and_then(|segments| {
+            flash_build(
+                &app,
+                &port_name,
+                &segments,
+                expected_chip.as_deref(),
+                flash_baud,
+                &task,
+                None,
+            )
+        });
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// How long flashing `segments` should take, shown before starting.
+#[tauri::command]
+async fn estimate_flash(
+    settings: State<'_, SettingsStore>,
+    segments: Vec<FlashSegmentArgs>,
+    baud_rate: Option<u32>,
+) -> Result<FlashEstimate, AppError> {
+    let flash_baud = baud_rate.unwrap_or(settings.get().flash.baud_rate);
+    tauri::async_runtime::spawn_blocking(move || {
+        let images = segments
+            .iter()
+            .map(|s| Ok(std::fs::read(&s.firmware_path)?))
+            .collect::<Result<Vec<_>, AppError>>()?;
+        Ok(flash_estimate::estimate(
+            images.iter().map(Vec::as_slice),
+            flash_baud,
+        ))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Fails while production mode is on; anything writing flash other than
+/// `kiosk_flash` checks this first.
+fn check_unlocked(app: &tauri::AppHandle) -> Result<(), AppError> {
+    kiosk::check_unlocked(&app.state::<SettingsStore>().get().kiosk)
+}
+
+/// Writes `segments` with the monitor on the port paused, and records the
+/// speed in the board's flash history. With `golden`, the kiosk's, each
+/// image has to match its golden hash as read for writing.
+fn flash_build(
+    app: &tauri::AppHandle,
+    port_name: &str,
+    segments: &[FlashSegment],
+    expected_chip: Option<&str>,
+    flash_baud: u32,
+    task: &TaskHandle,
+    golden: Option<&KioskSettings>,
+) -> Result<String, AppError> {
+    if golden.is_none() {
+        check_unlocked(app)?;
+    }
+    let started = Instant::now();
+    let devices = app.state::<DeviceTracker>();
+    devices.begin_operation(port_name);
+    // What was read for writing, for the device database
+    let mut images: Vec<(u32, Vec<u8>)> = Vec::new();
+    // The board actually connected, not whatever was last seen on the port
+    let mut identity = ChipDetails::default();
+    let result = with_flasher_port(app, port_name, "flash", |sessions| {
+        let read = esp_interaction::read_segments(segments, task)?;
+        if let Some(kiosk) = golden {
+            for (_, data) in &read {
+                kiosk::check_golden(kiosk, data)?;
+            }
+        }
+        images = read
+            .iter()
+            .map(|(s, data)| (s.address, data.clone()))
+            .collect();
+        task.phase("connecting");
+        identity = esp_interaction::chip_identity(sessions, port_name)?;
+        // Images from a build only run on the chip they were built for
+        if let Some(chip) = expected_chip {
+            esp_interaction::check_chip(sessions, port_name, chip)?;
+        }
+        esp_interaction::write_images(sessions, port_name, read, flash_baud, task)
+    });
+    devices.end_operation(port_name, &result);
+    if let (Ok(_), Some(info)) = (&result, task.info()) {
+        record_flash(app, &devices, port_name, &info, flash_baud);
+    }
+    record_device(
+        app,
+        Some(port_name),
+        DeviceRecord {
+            action: task
+                .info()
+                .map_or_else(|| "flash".to_string(), |info| info.kind),
+            mac_address: identity.mac_address,
+            chip_model: identity.chip_model,
+            firmware_version: device_db::firmware_version(&images),
+            checksum: (!images.is_empty()).then(|| device_db::checksum(&images)),
+            duration_ms: Some(started.elapsed().as_millis() as u64),
+            golden_hash: golden.map(|kiosk| kiosk.golden_hash.clone()),
+            ..outcome_record(&result, String::clone)
+        },
     );
-    // Simulate delay
-    std::thread::sleep(std::time::Duration::from_millis(500));
-    Ok("Flash started (Stub)".to_string())
+    result
 }
 
+/// The build re-flashed whenever it is rebuilt, if any.
+type BuildWatch = std::sync::Mutex<Option<FlashWatch>>;
+
+/// Flashes `segments` to `port_name` again each time a build elsewhere
+/// rewrites them. A monitor on the port pauses meanwhile and comes back on
+/// the new firmware; each flash is announced as a notification.
 #[tauri::command]
-async fn erase_flash(port_name: String) -> Result<String, String> {
-    // Run in a blocking task because it blocks the thread
-    tauri::async_runtime::spawn_blocking(move || esp_interaction::erase_flash(&port_name))
-        .await
-        .map_err(|e| e.to_string())?
+fn flash_watch(
+    app: tauri::AppHandle,
+    watch: State<'_, BuildWatch>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    segments: Vec<FlashSegmentArgs>,
+    expected_chip: Option<String>,
+    baud_rate: Option<u32>,
+) -> Result<(), AppError> {
+    let flash_baud = baud_rate.unwrap_or(settings.get().flash.baud_rate);
+    let segments = segments
+        .into_iter()
+        .map(|s| {
+            Ok(FlashSegment {
+                address: esp_interaction::parse_flash_address(&s.flash_address)?,
+                path: s.firmware_path,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    if let Some(missing) = segments
+        .iter()
+        .find(|s| !std::path::Path::new(&s.path).is_file())
+    {
+        return Err(AppError::InvalidInput(format!(
+            "{} is not a file",
+            missing.path
+        )));
+    }
+
+    let paths = segments.iter().map(|s| s.path.clone().into()).collect();
+    let handle = app.clone();
+    let started = FlashWatch::start(paths, move || {
+        let tasks = handle.state::<TaskManager>();
+        let task = tasks.start("flash", Some(&port_name));
+        let result = flash_build(
+            &handle,
+            &port_name,
+            &segments,
+            expected_chip.as_deref(),
+            flash_baud,
+            &task,
+            None,
+        );
+        task.finish(&result);
+        let (level, kind, message) = match result {
+            Ok(summary) => ("success", "flash_watch_flashed", summary),
+            Err(e) => ("error", "flash_watch_failed", e.message()),
+        };
+        notifications::emit(
+            &handle,
+            Some(Notification {
+                level: level.to_string(),
+                kind: kind.to_string(),
+                port_name: Some(port_name.clone()),
+                message: Some(message),
+            }),
+        );
+    });
+    // Replacing a watch stops the old one
+    *watch.lock().unwrap() = Some(started);
+    Ok(())
 }
 
-use std::io::{Read, Write};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tauri::{Emitter, State};
+/// Stops re-flashing on rebuilds, after a flash under way has finished.
+#[tauri::command]
+async fn flash_unwatch(app: tauri::AppHandle) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let stopped = app.state::<BuildWatch>().lock().unwrap().take();
+        drop(stopped);
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))
+}
 
-pub struct SerialState {
-    port: Arc<Mutex<Option<Box<dyn serialport::SerialPort>>>>,
-    should_run: Arc<Mutex<bool>>,
+/// Whether a build is being watched, for the flash form to pick up.
+#[tauri::command]
+fn flash_watch_status(watch: State<'_, BuildWatch>) -> bool {
+    watch.lock().unwrap().is_some()
 }
 
+/// Flashes an app into the OTA slot after the running one and selects it,
+/// leaving the running app as the fallback.
 #[tauri::command]
-async fn monitor_connect(
+async fn flash_ota_slot(
     app: tauri::AppHandle,
-    state: State<'_, SerialState>,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
     port_name: String,
-    baud_rate: u32,
-) -> Result<String, String> {
-    let mut serial_port = serialport::new(&port_name, baud_rate)
-        .timeout(Duration::from_millis(10))
-        .open()
-        .map_err(|e| format!("Failed to open port: {}", e))?;
+    firmware_path: String,
+    expected_chip: Option<String>,
+    baud_rate: Option<u32>,
+) -> Result<String, AppError> {
+    check_unlocked(&app)?;
+    let flash_baud = baud_rate.unwrap_or(settings.get().flash.baud_rate);
+    let tasks = tasks.inner().clone();
+    let devices = app.state::<DeviceTracker>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("flash", Some(&port_name));
+        devices.begin_operation(&port_name);
+        let result = with_flasher_port(&app, &port_name, "flash", |sessions| {
+            if let Some(chip) = &expected_chip {
+                task.phase("connecting");
+                esp_interaction::check_chip(sessions, &port_name, chip)?;
+            }
+            esp_interaction::flash_ota_slot(sessions, &port_name, &firmware_path, flash_baud, &task)
+        });
+        devices.end_operation(&port_name, &result);
+        if let (Ok(_), Some(info)) = (&result, task.info()) {
+            record_flash(&app, &devices, &port_name, &info, flash_baud);
+        }
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
 
-    // ESP32 requires DTR=false, RTS=false to run normally
-    serial_port.write_data_terminal_ready(false).ok();
-    serial_port.write_request_to_send(false).ok();
+/// Flash and boot history is kept per board: by USB serial number where the bridge
+/// reports one, otherwise by port.
+fn history_key(devices: &DeviceTracker, port_name: &str) -> String {
+    devices
+        .list()
+        .into_iter()
+        .find(|d| d.key == port_name)
+        .and_then(|d| d.status.serial_number)
+        .unwrap_or_else(|| port_name.to_string())
+}
 
-    // Set run flag
-    {
-        let mut run = state.should_run.lock().unwrap();
-        *run = true;
+fn record_flash(
+    app: &tauri::AppHandle,
+    devices: &DeviceTracker,
+    port_name: &str,
+    info: &TaskInfo,
+    baud_rate: u32,
+) {
+    let Some(bytes_per_sec) = info.bytes_per_sec else {
+        return;
+    };
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let record = FlashRecord {
+        at,
+        bytes: info.total,
+        bytes_per_sec,
+        baud_rate,
+    };
+    // Losing a history entry isn't worth failing the flash over
+    if let Err(e) = history::record(
+        app,
+        history::FLASH_HISTORY,
+        &history_key(devices, port_name),
+        record,
+    ) {
+        debug!("Could not record flash history: {}", e);
     }
+}
 
-    // Store port (wrap in Arc/Mutex logic)
-    {
-        let mut port_guard = state.port.lock().unwrap();
-        *port_guard = Some(serial_port);
+/// The device database, opened on first use.
+type DeviceRecords = Mutex<Option<DeviceDb>>;
+
+const DEVICE_DB: &str = "devices.db";
+
+fn with_device_db<T>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&DeviceDb) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let state = app.state::<DeviceRecords>();
+    let mut db = state.lock().unwrap();
+    let db = match &mut *db {
+        Some(db) => db,
+        closed => {
+            let dir = app
+                .path()
+                .app_config_dir()
+                .map_err(|e| AppError::Config(format!("Config Dir Error: {}", e)))?;
+            std::fs::create_dir_all(&dir)?;
+            closed.insert(DeviceDb::open(&dir.join(DEVICE_DB))?)
+        }
+    };
+    f(db)
+}
+
+/// A record of whether `result` passed, detailed by the error or by what
+/// `detail` makes of the value.
+fn outcome_record<T>(result: &Result<T, AppError>, detail: impl Fn(&T) -> String) -> DeviceRecord {
+    DeviceRecord {
+        passed: result.is_ok(),
+        detail: match result {
+            Ok(value) => detail(value),
+            Err(e) => e.message(),
+        },
+        ..DeviceRecord::default()
     }
+}
 
-    // Clone Arcs for thread (cheap clone)
-    let port_clone = state.port.clone();
-    let run_clone = state.should_run.clone();
-    let port_name_thread = port_name.clone();
-    let baud_rate_thread = baud_rate;
-
-    // Spawn read thread
-    std::thread::spawn(move || {
-        let mut serial_buf: Vec<u8> = vec![0; 1000];
-        loop {
-            // Check run flag
-            if !*run_clone.lock().unwrap() {
-                break;
-            }
+/// Adds a flashing or provisioning to the device database, filling in the
+/// time, the operator and run and, for a board on `port_name`, its USB
+/// serial number. The MAC and chip come from the caller, as read from the
+/// board during the operation.
+fn record_device(app: &tauri::AppHandle, port_name: Option<&str>, mut record: DeviceRecord) {
+    if let Some(port_name) = port_name {
+        let devices = app.state::<DeviceTracker>();
+        record.serial_number = devices
+            .list()
+            .into_iter()
+            .find(|d| d.key == port_name)
+            .and_then(|d| d.status.serial_number);
+    }
+    record.at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let settings = app.state::<SettingsStore>().get();
+    record.operator = settings.operator;
+    record.run = settings.production_run;
+    // The board is done either way; a missing record shows in the log
+    if let Err(e) = with_device_db(app, |db| db.record(&record)) {
+        warn!(
+            "Could not record {} in the device database: {}",
+            record.action, e
+        );
+    }
+}
 
-            let mut fatal_error = false;
-            let mut got_data = false;
-            let mut read_len = 0;
-
-            // Scope for lock
-            {
-                let mut guard = port_clone.lock().unwrap();
-                if let Some(port) = guard.as_mut() {
-                    match port.read(serial_buf.as_mut_slice()) {
-                        Ok(t) => {
-                            if t > 0 {
-                                got_data = true;
-                                read_len = t;
-                            }
-                        }
-                        Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => (),
-                        Err(e) => {
-                            println!("Monitor Error: {:?} - triggering reconnect", e);
-                            fatal_error = true;
-                        }
-                    }
-                } else {
-                    // Port is None, need reconnect
-                    fatal_error = true;
-                }
+/// Flashings and provisionings in the device database matching `query`,
+/// most recent first.
+#[tauri::command]
+fn device_records(
+    app: tauri::AppHandle,
+    query: RecordQuery,
+) -> Result<Vec<DeviceRecord>, AppError> {
+    with_device_db(&app, |db| db.query(&query))
+}
 
-                if fatal_error {
-                    *guard = None;
-                }
-            }
+/// Writes `contents` to a file picked on this machine, offering
+/// `file_name`. Returns where it went, `None` if the dialog was cancelled.
+fn save_export(
+    app: &tauri::AppHandle,
+    (filter, extension): (&str, &str),
+    file_name: &str,
+    contents: impl FnOnce() -> Result<String, AppError>,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
 
-            if got_data {
-                println!("Serial Read {} bytes", read_len);
-                let data = String::from_utf8_lossy(&serial_buf[..read_len]).to_string();
-                let _ = app.emit("serial-read", data);
-            }
+    let Some(picked) = app
+        .dialog()
+        .file()
+        .add_filter(filter, &[extension])
+        .set_file_name(file_name)
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    std::fs::write(&path, contents()?)?;
+    Ok(Some(path.display().to_string()))
+}
 
-            if fatal_error {
-                // Wait before retrying
-                std::thread::sleep(Duration::from_millis(500));
-
-                println!("Attempting reconnect to {}...", port_name_thread);
-                match serialport::new(&port_name_thread, baud_rate_thread)
-                    .timeout(Duration::from_millis(10))
-                    .open()
-                {
-                    Ok(mut new_port) => {
-                        new_port.write_data_terminal_ready(false).ok();
-                        new_port.write_request_to_send(false).ok();
-
-                        let mut guard = port_clone.lock().unwrap();
-                        *guard = Some(new_port);
-                        println!("Reconnected successfully!");
-                    }
-                    Err(_) => {
-                        // Reconnect failed, just retry next loop
-                    }
-                }
+// A production run's name as part of a file name
+fn run_file_name(run: &str, extension: &str) -> String {
+    let name: String = run
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
             } else {
-                std::thread::sleep(Duration::from_millis(5));
+                '_'
             }
-        }
-        println!("Monitor thread stopped");
-    });
+        })
+        .collect();
+    if name.is_empty() {
+        format!("devices.{}", extension)
+    } else {
+        format!("run-{}.{}", name, extension)
+    }
+}
 
-    println!("Monitor connect: {} @ {}", port_name, baud_rate);
-    Ok("Connected".to_string())
+/// Saves the records matching `query` as CSV to a file picked on this
+/// machine. Returns where it went, `None` if the dialog was cancelled.
+#[tauri::command]
+async fn export_device_records(
+    app: tauri::AppHandle,
+    query: RecordQuery,
+) -> Result<Option<String>, AppError> {
+    save_export(&app, ("CSV", "csv"), "devices.csv", || {
+        let records = with_device_db(&app, |db| db.query(&query))?;
+        Ok(device_db::to_csv(&records))
+    })
 }
 
+/// Production runs in the device database, the most recently active first.
 #[tauri::command]
-async fn monitor_disconnect(state: State<'_, SerialState>) -> Result<String, String> {
-    *state.should_run.lock().unwrap() = false;
-    *state.port.lock().unwrap() = None;
-    println!("Monitor disconnect");
-    Ok("Disconnected".to_string())
+fn production_runs(app: tauri::AppHandle) -> Result<Vec<ProductionRun>, AppError> {
+    with_device_db(&app, |db| db.runs())
 }
 
+/// Summary of the production run `run`.
 #[tauri::command]
-async fn monitor_send(state: State<'_, SerialState>, data: String) -> Result<String, String> {
-    let mut guard = state.port.lock().unwrap();
-    if let Some(port) = guard.as_mut() {
-        let data_bytes = format!("{}\r\n", data); // Add newline for convenience
-        port.write_all(data_bytes.as_bytes())
-            .map_err(|e| e.to_string())?;
-        println!("Monitor send: {}", data);
-        Ok("Sent".to_string())
-    } else {
-        Err("Not connected".to_string())
+fn run_report(app: tauri::AppHandle, run: String) -> Result<RunReport, AppError> {
+    with_device_db(&app, |db| db.run_report(&run))
+}
+
+/// Saves every record of the production run `run` as CSV, like
+/// `export_device_records`.
+#[tauri::command]
+async fn export_run_csv(app: tauri::AppHandle, run: String) -> Result<Option<String>, AppError> {
+    let query = RecordQuery {
+        run: Some(run.clone()),
+        ..RecordQuery::default()
+    };
+    save_export(&app, ("CSV", "csv"), &run_file_name(&run, "csv"), || {
+        let records = with_device_db(&app, |db| db.query(&query))?;
+        Ok(device_db::to_csv(&records))
+    })
+}
+
+/// Saves the summary report of the production run `run` as text.
+#[tauri::command]
+async fn export_run_report(app: tauri::AppHandle, run: String) -> Result<Option<String>, AppError> {
+    save_export(&app, ("Text", "txt"), &run_file_name(&run, "txt"), || {
+        Ok(with_device_db(&app, |db| db.run_report(&run))?.to_text())
+    })
+}
+
+/// Recent flashes of the board on `port_name`, oldest first.
+#[tauri::command]
+fn get_flash_history(
+    app: tauri::AppHandle,
+    devices: State<'_, DeviceTracker>,
+    port_name: String,
+) -> Vec<FlashRecord> {
+    history::load(
+        &app,
+        history::FLASH_HISTORY,
+        &history_key(&devices, &port_name),
+    )
+}
+
+#[tauri::command]
+fn get_label_sizes() -> Vec<LabelSize> {
+    label::LABEL_SIZES.to_vec()
+}
+
+/// Label fields of the board on `port_name`: the MAC address once it was
+/// identified, the USB serial number and the last flash from the history.
+#[tauri::command]
+fn get_device_label(
+    app: tauri::AppHandle,
+    devices: State<'_, DeviceTracker>,
+    port_name: String,
+) -> DeviceLabel {
+    let flashes: Vec<FlashRecord> = history::load(
+        &app,
+        history::FLASH_HISTORY,
+        &history_key(&devices, &port_name),
+    );
+    let snapshot = devices.list().into_iter().find(|d| d.key == port_name);
+    DeviceLabel {
+        mac: devices
+            .cached_chip(&port_name)
+            .and_then(|chip| chip.mac_address),
+        serial_number: snapshot.and_then(|d| d.status.serial_number),
+        flashed_at: flashes.last().map(|record| record.at),
     }
 }
 
+fn label_size(size: &str) -> Result<LabelSize, AppError> {
+    label::label_size(size)
+        .ok_or_else(|| AppError::InvalidInput(format!("Unknown label size: {}", size)))
+}
+
+#[tauri::command]
+fn render_label_svg(label: DeviceLabel, size: String) -> Result<String, AppError> {
+    label::render_svg(&label, &label_size(&size)?)
+}
+
+/// Saves `label` as PNG (at `dpi`) or SVG, by the extension of the file
+/// picked. Returns the path, `None` if the dialog was cancelled.
 #[tauri::command]
-async fn pick_firmware_file(app: tauri::AppHandle) -> Result<Option<String>, String> {
-    println!("Command 'pick_firmware_file' invoked!");
+async fn export_label(
+    app: tauri::AppHandle,
+    label: DeviceLabel,
+    size: String,
+    dpi: u32,
+) -> Result<Option<String>, AppError> {
     use tauri_plugin_dialog::DialogExt;
 
-    println!("Opening dialog...");
-    let file_path = app
+    let size = label_size(&size)?;
+    let name = label
+        .mac
+        .as_deref()
+        .or(label.serial_number.as_deref())
+        .unwrap_or("device")
+        .replace(':', "");
+    let picked = app
         .dialog()
         .file()
-        .add_filter("Firmware", &["bin"])
-        .blocking_pick_file();
+        .add_filter("PNG", &["png"])
+        .add_filter("SVG", &["svg"])
+        .set_file_name(format!("label-{}.png", name))
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let svg = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    let data = if svg {
+        label::render_svg(&label, &size)?.into_bytes()
+    } else {
+        label::render_png(&label, &size, dpi)?
+    };
+    std::fs::write(&path, data)?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Pushes an image over Wi-Fi to a board running ArduinoOTA; progress is
+/// reported as an `ota` task keyed by `host`.
+#[tauri::command]
+async fn ota_upload(
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    host: String,
+    port: Option<u16>,
+    password: Option<String>,
+    firmware_path: String,
+    filesystem: bool,
+) -> Result<(), AppError> {
+    kiosk::check_unlocked(&settings.get().kiosk)?;
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = OtaOptions {
+            host: &host,
+            port: port.unwrap_or(espota::DEFAULT_PORT),
+            password: password.as_deref().filter(|p| !p.is_empty()),
+            target: if filesystem {
+                OtaTarget::Filesystem
+            } else {
+                OtaTarget::Firmware
+            },
+        };
+        let task = tasks.start("ota", Some(&host));
+        let result = espota::upload(&options, firmware_path.as_ref(), &task);
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Sends Wi-Fi credentials to Improv firmware (ESPHome, WLED) over the
+/// cable; progress is reported as an `improv` task.
+#[tauri::command]
+async fn improv_provision(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    port_name: String,
+    ssid: String,
+    password: String,
+) -> Result<ImprovOutcome, AppError> {
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let task = tasks.start("improv", Some(&port_name));
+        let result = with_flasher_port(&app, &port_name, "improv", |sessions| {
+            sessions.release(&port_name);
+            improv::provision(&port_name, &ssid, &password, &task)
+        });
+        task.finish(&result);
+        let device = result.as_ref().ok().and_then(|o| o.device.as_ref());
+        record_device(
+            &app,
+            Some(&port_name),
+            DeviceRecord {
+                action: "improv".to_string(),
+                // Improv reports the chip family but no MAC
+                chip_model: device.map(|d| d.chip.clone()),
+                firmware_version: device.map(|d| format!("{} {}", d.firmware, d.version)),
+                duration_ms: Some(started.elapsed().as_millis() as u64),
+                ..outcome_record(&result, |o| o.url.clone().unwrap_or_default())
+            },
+        );
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Named BLE devices nearby, for picking a board to provision.
+#[tauri::command]
+async fn ble_scan() -> Result<Vec<ProvDevice>, AppError> {
+    tauri::async_runtime::spawn_blocking(|| wifi_prov::scan(Duration::from_secs(5)))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Provisions Wi-Fi over BLE to a board running Espressif's provisioning
+/// manager; progress is reported as a `ble_provision` task keyed by device.
+#[tauri::command]
+async fn ble_provision(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    device: String,
+    security: Option<u8>,
+    pop: String,
+    username: Option<String>,
+    ssid: String,
+    passphrase: String,
+) -> Result<ProvOutcome, AppError> {
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = ProvOptions {
+            device: &device,
+            security,
+            pop: &pop,
+            username: username
+                .as_deref()
+                .filter(|u| !u.is_empty())
+                .unwrap_or(wifi_prov::DEFAULT_USERNAME),
+            ssid: &ssid,
+            passphrase: &passphrase,
+        };
+        let started = Instant::now();
+        let task = tasks.start("ble_provision", Some(&device));
+        let result = wifi_prov::provision(&options, &task);
+        task.finish(&result);
+        let outcome = outcome_record(&result, |o| o.ip.clone().unwrap_or_default());
+        record_device(
+            &app,
+            None,
+            DeviceRecord {
+                action: "ble_provision".to_string(),
+                // Known by its BLE name or address only
+                detail: format!("{}: {}", device, outcome.detail),
+                duration_ms: Some(started.elapsed().as_millis() as u64),
+                ..outcome
+            },
+        );
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Serves `firmware_path` to boards on the LAN until stopped.
+#[tauri::command]
+fn ota_server_start(
+    app: tauri::AppHandle,
+    server: State<'_, OtaServer>,
+    firmware_path: String,
+    port: u16,
+) -> Result<OtaServerStatus, AppError> {
+    check_unlocked(&app)?;
+    server.start(&app, &firmware_path, port)
+}
+
+#[tauri::command]
+fn ota_server_stop(server: State<'_, OtaServer>) -> OtaServerStatus {
+    server.stop();
+    server.status()
+}
+
+#[tauri::command]
+fn ota_server_status(server: State<'_, OtaServer>) -> OtaServerStatus {
+    server.status()
+}
+
+/// The OpenOCD the debug panel started, kept after it exits for its log.
+type JtagDebugger = Mutex<Option<OpenOcd>>;
+
+/// JTAG adapters plugged in: USB-JTAG ports and ESP-Progs.
+#[tauri::command]
+fn openocd_probes() -> Vec<JtagProbe> {
+    openocd::probes()
+}
+
+/// Starts OpenOCD for a `chip_model` board on the adapter that fits it,
+/// replacing one already running. Its output goes out as `openocd-log`.
+#[tauri::command]
+fn openocd_start(
+    app: tauri::AppHandle,
+    debugger: State<'_, JtagDebugger>,
+    chip_model: String,
+) -> Result<OpenOcdStatus, AppError> {
+    let program = openocd::find().ok_or_else(|| {
+        AppError::Config("openocd not found; install openocd-esp32 or add it to PATH".to_string())
+    })?;
+    let found = openocd::probes();
+    let probe = openocd::pick_probe(&found, &chip_model)
+        .cloned()
+        .ok_or_else(|| {
+            AppError::PortNotFound(format!("No JTAG adapter for {} is connected", chip_model))
+        })?;
+    let mut slot = debugger.lock().unwrap();
+    drop(slot.take());
+    let emitter = app.clone();
+    let mut running = OpenOcd::start(&program, &chip_model, probe, move |line| {
+        let _ = emitter.emit("openocd-log", line);
+    })?;
+    let status = running.status();
+    *slot = Some(running);
+    Ok(status)
+}
+
+#[tauri::command]
+fn openocd_stop(debugger: State<'_, JtagDebugger>) -> OpenOcdStatus {
+    let mut slot = debugger.lock().unwrap();
+    slot.as_mut()
+        .map(|running| {
+            running.stop();
+            running.status()
+        })
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+fn openocd_status(debugger: State<'_, JtagDebugger>) -> OpenOcdStatus {
+    let mut slot = debugger.lock().unwrap();
+    slot.as_mut().map(OpenOcd::status).unwrap_or_default()
+}
+
+/// The GDB the debug panel attached to OpenOCD, and its status as last
+/// read, which `gdb_status` answers with while a command holds the session.
+#[derive(Default)]
+struct GdbSession {
+    gdb: Mutex<Option<Gdb>>,
+    last_status: Mutex<GdbStatus>,
+}
+
+impl GdbSession {
+    /// Reads the status of the held session and keeps it as the snapshot.
+    fn snapshot(&self, slot: &mut Option<Gdb>) -> GdbStatus {
+        let status = slot.as_mut().map(Gdb::status).unwrap_or_default();
+        *self.last_status.lock().unwrap() = status.clone();
+        status
+    }
+}
+
+/// Starts GDB for a `chip_model` board on `elf_path`, an ELF or an ESP-IDF
+/// project, and attaches it to the running OpenOCD. Console output goes out
+/// as `gdb-log`, halts and resumes as `gdb-state`.
+#[tauri::command]
+async fn gdb_start(
+    app: tauri::AppHandle,
+    elf_path: String,
+    chip_model: String,
+) -> Result<GdbStatus, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let program = gdb::find(&chip_model)?;
+        let path = std::path::Path::new(&elf_path);
+        let elf = if path.is_dir() {
+            idf::app_elf(path)?
+        } else {
+            path.to_path_buf()
+        };
+        drop(app.state::<GdbSession>().gdb.lock().unwrap().take());
+        let emitter = app.clone();
+        let mut session = Gdb::start(&program, &elf, openocd::GDB_PORT, move |event| {
+            let _ = match event {
+                GdbEvent::Output(line) => emitter.emit("gdb-log", line),
+                GdbEvent::Target(target) => emitter.emit("gdb-state", target),
+            };
+        })?;
+        let state = app.state::<GdbSession>();
+        let mut slot = state.gdb.lock().unwrap();
+        *slot = Some(session);
+        Ok(state.snapshot(&mut slot))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
 
-    println!("Dialog result: {:?}", file_path);
+/// Runs `action` on the attached GDB off the async runtime.
+async fn with_gdb<T: Send + 'static>(
+    app: tauri::AppHandle,
+    action: impl FnOnce(&mut Gdb) -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let session = app.state::<GdbSession>();
+        let mut slot = session.gdb.lock().unwrap();
+        let result = action(slot.as_mut().ok_or(AppError::NotConnected)?);
+        session.snapshot(&mut slot);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn gdb_stop(app: tauri::AppHandle) -> Result<GdbStatus, AppError> {
+    with_gdb(app, |session| {
+        session.stop();
+        Ok(session.status())
+    })
+    .await
+}
+
+/// Status of the GDB session. A command can hold the session for seconds,
+/// so meanwhile this answers with the status it left last time.
+#[tauri::command]
+async fn gdb_status(session: State<'_, GdbSession>) -> Result<GdbStatus, AppError> {
+    let Ok(mut slot) = session.gdb.try_lock() else {
+        return Ok(session.last_status.lock().unwrap().clone());
+    };
+    Ok(session.snapshot(&mut slot))
+}
+
+#[tauri::command]
+async fn gdb_break(app: tauri::AppHandle, location: String) -> Result<(), AppError> {
+    with_gdb(app, move |session| session.break_at(&location)).await
+}
+
+#[tauri::command]
+async fn gdb_continue(app: tauri::AppHandle) -> Result<(), AppError> {
+    with_gdb(app, Gdb::resume).await
+}
+
+#[tauri::command]
+async fn gdb_halt(app: tauri::AppHandle) -> Result<(), AppError> {
+    with_gdb(app, Gdb::halt).await
+}
+
+#[tauri::command]
+async fn gdb_reset(app: tauri::AppHandle) -> Result<(), AppError> {
+    with_gdb(app, Gdb::reset).await
+}
+
+#[tauri::command]
+async fn gdb_backtrace(app: tauri::AppHandle) -> Result<Vec<StackFrame>, AppError> {
+    with_gdb(app, Gdb::backtrace).await
+}
+
+/// Runs a command typed at the GDB console; its output arrives as
+/// `gdb-log`.
+#[tauri::command]
+async fn gdb_console(app: tauri::AppHandle, command: String) -> Result<(), AppError> {
+    with_gdb(app, move |session| session.console(&command)).await
+}
+
+/// The SVD peripherals of the chip last asked about, parsed once.
+type SvdCache = Mutex<Option<(String, Arc<Vec<SvdPeripheral>>)>>;
+
+/// The peripherals of `chip_model`, from the bundled SVD file or one
+/// downloaded into the app cache.
+async fn svd_load(
+    app: tauri::AppHandle,
+    chip_model: String,
+) -> Result<Arc<Vec<SvdPeripheral>>, AppError> {
+    let cached = app.state::<SvdCache>().lock().unwrap().clone();
+    if let Some((_, peripherals)) = cached.filter(|(chip, _)| *chip == chip_model) {
+        return Ok(peripherals);
+    }
+    let bundled = app
+        .path()
+        .resolve(svd::resource(&chip_model), BaseDirectory::Resource)
+        .ok();
+    let cache = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .join("svd");
+    let chip = chip_model.clone();
+    let peripherals =
+        tauri::async_runtime::spawn_blocking(move || svd::load(bundled.as_deref(), &cache, &chip))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??;
+    let peripherals = Arc::new(peripherals);
+    *app.state::<SvdCache>().lock().unwrap() = Some((chip_model, peripherals.clone()));
+    Ok(peripherals)
+}
+
+fn svd_find<'a>(
+    peripherals: &'a [SvdPeripheral],
+    name: &str,
+) -> Result<&'a SvdPeripheral, AppError> {
+    peripherals
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| AppError::InvalidInput(format!("No peripheral {}", name)))
+}
+
+#[tauri::command]
+async fn svd_peripherals(
+    app: tauri::AppHandle,
+    chip_model: String,
+) -> Result<Vec<String>, AppError> {
+    let peripherals = svd_load(app, chip_model).await?;
+    Ok(peripherals.iter().map(|p| p.name.clone()).collect())
+}
+
+#[tauri::command]
+async fn svd_peripheral(
+    app: tauri::AppHandle,
+    chip_model: String,
+    name: String,
+) -> Result<SvdPeripheral, AppError> {
+    let peripherals = svd_load(app, chip_model).await?;
+    svd_find(&peripherals, &name).cloned()
+}
+
+/// Reads the registers of peripheral `name` off the chip GDB has halted.
+#[tauri::command]
+async fn svd_read(
+    app: tauri::AppHandle,
+    chip_model: String,
+    name: String,
+) -> Result<Vec<RegisterValue>, AppError> {
+    let peripherals = svd_load(app.clone(), chip_model).await?;
+    with_gdb(app, move |session| {
+        svd::read(session, svd_find(&peripherals, &name)?)
+    })
+    .await
+}
+
+/// Crash report from a core dump the monitor has printed, against the
+/// app's ELF.
+#[tauri::command]
+async fn coredump_from_monitor(
+    monitor: State<'_, Monitor>,
+    elf_path: String,
+) -> Result<CoreDumpReport, AppError> {
+    let output = monitor.recent_output();
+    tauri::async_runtime::spawn_blocking(move || coredump::report(&output, elf_path.as_ref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Crash report from the board's coredump partition, read as a `read` task.
+#[tauri::command]
+async fn coredump_from_flash(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    port_name: String,
+    elf_path: String,
+) -> Result<CoreDumpReport, AppError> {
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("read", Some(&port_name));
+        let dump = with_flasher_port(&app, &port_name, "coredump", |sessions| {
+            esp_interaction::read_coredump(sessions, &port_name, &task)
+        });
+        task.finish(&dump);
+        coredump::report(&dump?, elf_path.as_ref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Crash report from a saved dump: UART output, base64, partition or ELF.
+#[tauri::command]
+async fn coredump_from_file(path: String, elf_path: String) -> Result<CoreDumpReport, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let dump = std::fs::read(&path)?;
+        coredump::report(&dump, elf_path.as_ref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn pick_coredump_file(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    // No filter: dumps are saved as logs, `.b64`, `.bin` or `.elf`
+    let file_path = app.dialog().file().blocking_pick_file();
     Ok(file_path.map(|path| path.to_string()))
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .manage(SerialState {
-            port: Arc::new(Mutex::new(None)),
-            should_run: Arc::new(Mutex::new(false)),
+/// Appends each board identified from now on to a CSV picked by the user,
+/// with `batch` on every row. Picking an earlier inventory carries it on.
+/// `None` if the dialog was cancelled.
+#[tauri::command]
+async fn inventory_start(
+    app: tauri::AppHandle,
+    batch: InventoryBatch,
+) -> Result<Option<InventoryStatus>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let name = match batch.batch.trim() {
+        "" => "inventory".to_string(),
+        name => format!("inventory-{}", name.replace(['/', '\\', ':'], "-")),
+    };
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("CSV", &["csv"])
+        .set_file_name(format!("{}.csv", name))
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let collector = app.state::<InventoryCollector>();
+    collector.start(&app, &path, batch).map(Some)
+}
+
+#[tauri::command]
+fn inventory_stop(collector: State<'_, InventoryCollector>) -> InventoryStatus {
+    collector.stop();
+    collector.status()
+}
+
+#[tauri::command]
+fn inventory_status(collector: State<'_, InventoryCollector>) -> InventoryStatus {
+    collector.status()
+}
+
+/// Restarts the board through the auto-reset circuit, like the RST button.
+#[tauri::command]
+async fn reset_device(app: tauri::AppHandle, port_name: String) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        with_flasher_port(&app, &port_name, "reset", |sessions| {
+            esp_interaction::reset(sessions, &port_name)
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn erase_flash(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    port_name: String,
+) -> Result<String, AppError> {
+    check_unlocked(&app)?;
+    let tasks = tasks.inner().clone();
+    let devices = app.state::<DeviceTracker>().inner().clone();
+    // Run in a blocking task because it blocks the thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("erase", Some(&port_name));
+        devices.begin_operation(&port_name);
+        let result = with_flasher_port(&app, &port_name, "erase", |sessions| {
+            esp_interaction::erase_flash(sessions, &port_name, &task)
+        });
+        devices.end_operation(&port_name, &result);
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Decodes a bootloader or app image the way `esptool.py image_info` does,
+/// to catch truncated or corrupted files before flashing them.
+#[tauri::command]
+async fn image_info(path: String) -> Result<ImageInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        esp32dev_core::image_info::load(std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Checks a firmware file dropped onto the window and suggests where it
+/// goes. An ELF is converted for the board on `port_name` first, as it was
+/// last identified.
+#[tauri::command]
+async fn place_image(
+    devices: State<'_, DeviceTracker>,
+    path: String,
+    port_name: Option<String>,
+) -> Result<PlacedImage, AppError> {
+    let board = port_name.and_then(|port| devices.cached_chip(&port));
+    tauri::async_runtime::spawn_blocking(move || {
+        esp32dev_core::images::place_file(std::path::Path::new(&path), board.as_ref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Asks for an ELF and converts it into a bootable image with `options`,
+/// written next to the ELF. Returns the image's path, `None` if the dialog
+/// was cancelled.
+#[tauri::command]
+async fn elf2image_pick(
+    app: tauri::AppHandle,
+    options: elf2image::ImageOptions,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let Some(picked) = app.dialog().file().blocking_pick_file() else {
+        return Ok(None);
+    };
+    let elf = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let output = elf2image::convert_file(&elf, None, &options)?;
+    Ok(Some(output.display().to_string()))
+}
+
+/// Asks for an ELF and where to save, and assembles a full flash image from
+/// it: the bundled bootloader for the chip and `idf_version` unless
+/// `bootloader_path` is given, and the partition table from
+/// `partitions_path` or the single-app default. Returns the image's path,
+/// `None` if a dialog was cancelled.
+#[tauri::command]
+async fn assemble_full_image(
+    app: tauri::AppHandle,
+    options: elf2image::ImageOptions,
+    idf_version: String,
+    partitions_path: Option<String>,
+    bootloader_path: Option<String>,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let bootloader = match bootloader_path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            let resource = full_image::bootloader_resource(&options.chip, &idf_version)?;
+            let path = app
+                .path()
+                .resolve(&resource, BaseDirectory::Resource)
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            if !path.is_file() {
+                return Err(AppError::InvalidInput(format!(
+                    "{} is not bundled with this build; choose a bootloader",
+                    resource
+                )));
+            }
+            path
+        }
+    };
+    let Some(picked) = app.dialog().file().blocking_pick_file() else {
+        return Ok(None);
+    };
+    let elf = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let name = elf
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "firmware".to_string());
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("Firmware", &["bin"])
+        .set_file_name(format!("{}-full.bin", name))
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let output = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let partitions = partitions_path.map(std::path::PathBuf::from);
+    tauri::async_runtime::spawn_blocking(move || {
+        full_image::assemble_file(&elf, &bootloader, partitions.as_deref(), &options, &output)
+            .map(|_| Some(output.display().to_string()))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Times writing and reading a scratch region of flash at each baud rate,
+/// `benchmark::DEFAULT_BAUD_RATES` when none are given.
+#[tauri::command]
+async fn run_flash_benchmark(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    port_name: String,
+    baud_rates: Option<Vec<u32>>,
+    offset: Option<String>,
+    len: Option<u32>,
+) -> Result<Vec<BenchmarkRun>, AppError> {
+    check_unlocked(&app)?;
+    let options = BenchmarkOptions {
+        port_name: port_name.clone(),
+        baud_rates: baud_rates.unwrap_or_else(|| benchmark::DEFAULT_BAUD_RATES.to_vec()),
+        offset: offset
+            .as_deref()
+            .map(esp_interaction::parse_flash_address)
+            .transpose()?,
+        len: len.unwrap_or(benchmark::DEFAULT_LEN),
+    };
+    let tasks = tasks.inner().clone();
+    let devices = app.state::<DeviceTracker>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("benchmark", Some(&port_name));
+        devices.begin_operation(&port_name);
+        let result = with_flasher_port(&app, &port_name, "benchmark", |sessions| {
+            esp_interaction::flash_benchmark(sessions, &options, &task)
+        });
+        devices.end_operation(&port_name, &result);
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// The `esptool.py` lines that redo what the flashing form would do, for
+/// scripts and bug reports. Rows that don't parse leave out `write_flash`.
+#[tauri::command]
+fn esptool_commands(
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    chip_model: Option<String>,
+    segments: Vec<FlashSegmentArgs>,
+    baud_rate: Option<u32>,
+) -> EsptoolCommands {
+    let chip_model = chip_model.as_deref();
+    let parsed = segments
+        .into_iter()
+        .map(|s| {
+            let address = esp_interaction::parse_flash_address(&s.flash_address).ok()?;
+            let path = Some(s.firmware_path).filter(|p| !p.trim().is_empty())?;
+            Some(FlashSegment { path, address })
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|segments| !segments.is_empty());
+    EsptoolCommands {
+        write_flash: parsed.map(|segments| {
+            let baud = baud_rate.unwrap_or(settings.get().flash.baud_rate);
+            esptool::write_flash(chip_model, &port_name, baud, &segments)
+        }),
+        erase_flash: esptool::erase_flash(chip_model, &port_name),
+        flash_id: esptool::flash_id(chip_model, &port_name),
+    }
+}
+
+#[tauri::command]
+async fn run_script(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    baud_rate: u32,
+    script: String,
+) -> Result<String, AppError> {
+    check_unlocked(&app)?;
+    let flash = settings.get().flash;
+    let options = ScriptOptions {
+        port_name: port_name.clone(),
+        baud_rate,
+        flash_address: esp_interaction::parse_flash_address(&flash.address)?,
+        flash_baud: flash.baud_rate,
+    };
+    let tasks = tasks.inner().clone();
+    let devices = app.state::<DeviceTracker>().inner().clone();
+    // The script console opens the port itself
+    app.state::<FlasherSessions>().release(&port_name);
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("script", Some(&port_name));
+        devices.begin_operation(&port_name);
+        let output = app.clone();
+        let result = with_monitors_paused(&app, &port_name, "script", || {
+            scripting::run(&script, &options, &task, move |text| {
+                let _ = output.emit("script-output", text.to_string());
+            })
+        });
+        devices.end_operation(&port_name, &result);
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn pick_test_sequence(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    Ok(app
+        .dialog()
+        .file()
+        .add_filter("Test sequence", &["json"])
+        .blocking_pick_file()
+        .map(|path| path.to_string()))
+}
+
+/// Runs the test sequence in `path` on the board, streaming device output
+/// as `test-output` events. A failed step fails the task but still returns
+/// the report, so the UI can show which step it was.
+#[tauri::command]
+async fn run_test_sequence(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    baud_rate: u32,
+    path: String,
+) -> Result<TestReport, AppError> {
+    check_unlocked(&app)?;
+    let test = sequence::load(std::path::Path::new(&path))?;
+    let flash = settings.get().flash;
+    let options = ScriptOptions {
+        port_name: port_name.clone(),
+        baud_rate,
+        flash_address: esp_interaction::parse_flash_address(&flash.address)?,
+        flash_baud: flash.baud_rate,
+    };
+    let tasks = tasks.inner().clone();
+    let devices = app.state::<DeviceTracker>().inner().clone();
+    // The test console opens the port itself
+    app.state::<FlasherSessions>().release(&port_name);
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("test", Some(&port_name));
+        devices.begin_operation(&port_name);
+        let output = app.clone();
+        let result = with_monitors_paused(&app, &port_name, "test", || {
+            sequence::run(&test, &options, &task, move |text| {
+                let _ = output.emit("test-output", text.to_string());
+            })
+        });
+        devices.end_operation(&port_name, &result);
+        match &result {
+            Ok(report) => task.finish(&report.outcome()),
+            Err(_) => task.finish(&result),
+        }
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Resets the board `cycles` times and reports each boot, streaming device
+/// output as `soak-output` events.
+#[tauri::command]
+async fn run_soak_test(
+    app: tauri::AppHandle,
+    port_name: String,
+    baud_rate: u32,
+    cycles: u32,
+    marker: Option<String>,
+    timeout_ms: u64,
+    interval_ms: Option<u64>,
+) -> Result<SoakReport, AppError> {
+    let options = SoakOptions {
+        port_name: port_name.clone(),
+        baud_rate,
+        cycles,
+        marker,
+        boot_timeout: Duration::from_millis(timeout_ms),
+        interval: interval_ms.map(Duration::from_millis),
+    };
+    // Taken from the app rather than as arguments to keep the list short
+    let tasks = app.state::<TaskManager>().inner().clone();
+    let devices = app.state::<DeviceTracker>().inner().clone();
+    app.state::<FlasherSessions>().release(&port_name);
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("soak", Some(&port_name));
+        devices.begin_operation(&port_name);
+        let output = app.clone();
+        let result = with_monitors_paused(&app, &port_name, "soak", || {
+            soak::run(&options, &task, move |text| {
+                let _ = output.emit("soak-output", text.to_string());
+            })
+        });
+        devices.end_operation(&port_name, &result);
+        match &result {
+            Ok(report) if report.failed() > 0 => {
+                task.finish(&Err::<(), _>(AppError::Script(soak::summary(report))))
+            }
+            _ => task.finish(&result),
+        }
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+fn get_settings(settings: State<'_, SettingsStore>) -> Settings {
+    settings.get()
+}
+
+#[tauri::command]
+fn update_settings(
+    app: tauri::AppHandle,
+    settings: State<'_, SettingsStore>,
+    api: State<'_, ApiServer>,
+    shortcuts: State<'_, Shortcuts>,
+    triggers: State<'_, MonitorTriggers>,
+    mut new_settings: Settings,
+) -> Result<Settings, AppError> {
+    time_sync::check(&new_settings.time_command)?;
+    // Kiosk mode is left with its password only, see `kiosk_exit`
+    new_settings.kiosk = settings.get().kiosk;
+    // Refuse the change if the API can't bind or a key can't be registered,
+    // instead of saving a broken setup
+    api.apply(&app, &new_settings.api)?;
+    shortcuts.apply(&app, &new_settings.shortcuts)?;
+    triggers.apply(&new_settings.triggers)?;
+    settings.set(new_settings)?;
+    Ok(settings.get())
+}
+
+/// Switches to kiosk mode, flashing `image_path` at `address` on each
+/// press as long as it matches `golden_hash`; leaving takes `password`.
+#[tauri::command]
+fn kiosk_enter(
+    settings: State<'_, SettingsStore>,
+    image_path: String,
+    address: String,
+    golden_hash: String,
+    password: String,
+) -> Result<Settings, AppError> {
+    settings.update(|current| {
+        // Changing the image or password means unlocking first
+        if current.kiosk.enabled {
+            return Err(AppError::InvalidInput(
+                "Kiosk mode is already on, exit it with the password first".to_string(),
+            ));
+        }
+        current.kiosk = kiosk::enter(&image_path, &address, &golden_hash, &password)?;
+        info!(
+            "Entering kiosk mode with {} (SHA-256 {})",
+            image_path, current.kiosk.golden_hash
+        );
+        Ok(())
+    })?;
+    Ok(settings.get())
+}
+
+#[tauri::command]
+fn kiosk_exit(settings: State<'_, SettingsStore>, password: String) -> Result<Settings, AppError> {
+    settings.update(|current| {
+        if !kiosk::unlocks(&current.kiosk, &password) {
+            return Err(AppError::InvalidInput("Wrong password".to_string()));
+        }
+        // The image and address stay for the next time
+        current.kiosk.enabled = false;
+        info!(
+            "Leaving kiosk mode after {} passed, {} failed",
+            current.kiosk.passed, current.kiosk.failed
+        );
+        Ok(())
+    })?;
+    Ok(settings.get())
+}
+
+/// Flashes the golden image to the board plugged in and counts the unit
+/// as passed or failed.
+#[tauri::command]
+async fn kiosk_flash(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+) -> Result<KioskRun, AppError> {
+    let current = settings.get();
+    if !current.kiosk.enabled {
+        return Err(AppError::InvalidInput("Kiosk mode is off".to_string()));
+    }
+    let tasks = tasks.inner().clone();
+    let store = settings.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("kiosk", None);
+        let result = esp32dev_core::devices::detect(&current.vid_allowlist)
+            .port_name
+            .ok_or_else(|| AppError::PortNotFound("No board plugged in".to_string()))
+            .and_then(|port_name| {
+                let segment = FlashSegment {
+                    path: current.kiosk.image_path.clone(),
+                    address: esp_interaction::parse_flash_address(&current.kiosk.address)?,
+                };
+                flash_build(
+                    &app,
+                    &port_name,
+                    &[segment],
+                    None,
+                    current.flash.baud_rate,
+                    &task,
+                    Some(&current.kiosk),
+                )
+            });
+        task.finish(&result);
+
+        let (passed, failed) = store.update(|updated| {
+            match &result {
+                Ok(_) => updated.kiosk.passed += 1,
+                Err(_) => updated.kiosk.failed += 1,
+            }
+            Ok((updated.kiosk.passed, updated.kiosk.failed))
+        })?;
+        Ok(KioskRun {
+            summary: result.as_ref().ok().cloned(),
+            error: result.err(),
+            passed,
+            failed,
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Saves the settings and remembered board preferences to one file picked
+/// by the user. Returns the path, `None` if the dialog was cancelled.
+#[tauri::command]
+async fn export_config(
+    app: tauri::AppHandle,
+    settings: State<'_, SettingsStore>,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let bundle = ConfigBundle::new(settings.get(), device_prefs::load_all(&app));
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("JSON", &["json"])
+        .set_file_name("esp32dev-config.json")
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    std::fs::write(&path, bundle.to_json())?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Replaces the settings, production mode aside, with those of a file
+/// written by `export_config` and adds its board preferences. Returns the new settings, `None` if the
+/// dialog was cancelled.
+#[tauri::command]
+async fn import_config(
+    app: tauri::AppHandle,
+    settings: State<'_, SettingsStore>,
+    api: State<'_, ApiServer>,
+    shortcuts: State<'_, Shortcuts>,
+    triggers: State<'_, MonitorTriggers>,
+) -> Result<Option<Settings>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("JSON", &["json"])
+        .blocking_pick_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let bundle = config_bundle::parse(&std::fs::read_to_string(&path)?)?;
+    let imported = bundle.settings_over(&settings.get())?;
+    info!("Importing configuration from {}", path.display());
+
+    // Same checks as a change made in the app
+    api.apply(&app, &imported.api)?;
+    shortcuts.apply(&app, &imported.shortcuts)?;
+    triggers.apply(&imported.triggers)?;
+    settings.set(imported)?;
+    device_prefs::merge(&app, bundle.device_prefs)?;
+    Ok(Some(settings.get()))
+}
+
+/// The local API's bearer token, to paste into scripts and test rigs.
+#[tauri::command]
+fn get_api_token(app: tauri::AppHandle) -> Result<String, AppError> {
+    api_server::token(&app)
+}
+
+#[tauri::command]
+fn get_recent_logs(logs: State<'_, RecentLogs>, limit: Option<usize>) -> Vec<String> {
+    logs.tail(limit.unwrap_or(500))
+}
+
+#[tauri::command]
+fn list_tasks(tasks: State<'_, TaskManager>) -> Vec<TaskInfo> {
+    tasks.list()
+}
+
+#[tauri::command]
+fn cancel_task(tasks: State<'_, TaskManager>, task_id: u64) -> Result<(), AppError> {
+    tasks.cancel(task_id)
+}
+
+/// Forwards monitor suspend/resume notifications as `monitor-state` events.
+fn emit_monitor_state(app: &tauri::AppHandle) -> impl Fn(MonitorStateEvent) {
+    let app = app.clone();
+    move |event| {
+        let _ = app.emit("monitor-state", event);
+    }
+}
+
+/// Forwards bytes from the board as a `serial-read` event. The payload is
+/// base64 of the raw bytes, so binary output and characters split across
+/// reads reach the UI intact.
+fn emit_serial(app: &tauri::AppHandle, bytes: &[u8]) {
+    use base64::Engine;
+
+    let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+    let _ = app.emit("serial-read", data);
+}
+
+#[tauri::command]
+async fn monitor_connect(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+    devices: State<'_, DeviceTracker>,
+    port_name: String,
+    baud_rate: u32,
+    boot_marker: Option<String>,
+) -> Result<String, AppError> {
+    if app.state::<SplitMonitor>().0.active_port().as_deref() == Some(port_name.as_str()) {
+        return Err(AppError::PortBusy(format!(
+            "{} is open in the split view",
+            port_name
+        )));
+    }
+    let timer = Mutex::new(BootTimer::new(boot_marker.as_deref())?);
+    let resets = Mutex::new(ResetAnalyzer::new());
+    // Connecting replaces any running session
+    if let Some(previous) = monitor.active_port() {
+        devices.monitoring(&previous, false);
+    }
+    app.state::<FlasherSessions>().release(&port_name);
+    let key = history_key(&devices, &port_name);
+    app.state::<MonitorTriggers>().reset();
+    let port = port_name.clone();
+    monitor.connect(&port_name, baud_rate, move |bytes| {
+        emit_serial(&app, bytes);
+        app.state::<MonitorTriggers>().feed(&app, &port, bytes);
+        let finished = timer.lock().unwrap().push(bytes, Instant::now());
+        if let Some(timing) = finished {
+            let _ = app.emit("boot-timing", &timing);
+            if let Err(e) = history::record(&app, history::BOOT_HISTORY, &key, timing) {
+                debug!("Could not record boot timing: {}", e);
+            }
+        }
+        if let Some(reset) = resets.lock().unwrap().push(bytes) {
+            info!(
+                "{} reset: {} ({})",
+                port,
+                reset.cause.as_str(),
+                reset.rom_reason
+            );
+            let _ = app.emit("reset-reason", &reset);
+        }
+    })?;
+    devices.monitoring(&port_name, true);
+    Ok("Connected".to_string())
+}
+
+/// Recent boots of the board on `port_name`, oldest first.
+#[tauri::command]
+fn get_boot_history(
+    app: tauri::AppHandle,
+    devices: State<'_, DeviceTracker>,
+    port_name: String,
+) -> Vec<BootTiming> {
+    history::load(
+        &app,
+        history::BOOT_HISTORY,
+        &history_key(&devices, &port_name),
+    )
+}
+
+/// Runs `op` in the raw REPL of the MicroPython board on `port_name`, with
+/// the monitor paused if it holds the port.
+async fn with_raw_repl<T: Send + 'static>(
+    app: tauri::AppHandle,
+    port_name: String,
+    op: impl FnOnce(&mut RawRepl<Box<dyn serialport::SerialPort>>) -> Result<T, AppError>
+        + Send
+        + 'static,
+) -> Result<T, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        with_flasher_port(&app, &port_name, "micropython", |sessions| {
+            sessions.release(&port_name);
+            micropython::session(&port_name, op)
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// MicroPython version and board on `port_name`, `None` without a REPL.
+#[tauri::command]
+async fn mpy_detect(app: tauri::AppHandle, port_name: String) -> Result<Option<MpyInfo>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        with_flasher_port(&app, &port_name, "micropython", |sessions| {
+            sessions.release(&port_name);
+            micropython::detect(&port_name)
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn mpy_list(
+    app: tauri::AppHandle,
+    port_name: String,
+    path: String,
+) -> Result<Vec<MpyEntry>, AppError> {
+    with_raw_repl(app, port_name, move |repl| repl.list(&path)).await
+}
+
+#[tauri::command]
+async fn mpy_mkdir(app: tauri::AppHandle, port_name: String, path: String) -> Result<(), AppError> {
+    with_raw_repl(app, port_name, move |repl| repl.mkdir(&path)).await
+}
+
+/// Deletes a file, or an empty directory.
+#[tauri::command]
+async fn mpy_remove(
+    app: tauri::AppHandle,
+    port_name: String,
+    path: String,
+) -> Result<(), AppError> {
+    with_raw_repl(app, port_name, move |repl| repl.remove(&path)).await
+}
+
+/// Copies a file picked on this machine into `directory` on the board.
+/// Returns its path there, `None` if nothing was picked.
+#[tauri::command]
+async fn mpy_upload(
+    app: tauri::AppHandle,
+    port_name: String,
+    directory: String,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let Some(picked) = app.dialog().file().blocking_pick_file() else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| AppError::InvalidInput(format!("{} is not a file", path.display())))?;
+    let data = std::fs::read(&path)?;
+    let remote = micropython::join(&directory, &name);
+    let target = remote.clone();
+    with_raw_repl(app, port_name, move |repl| {
+        repl.write_file(&target, &data, |_| {})
+    })
+    .await?;
+    Ok(Some(remote))
+}
+
+/// Saves `remote_path` from the board to a file picked on this machine.
+/// Returns where it went, `None` if the dialog was cancelled.
+#[tauri::command]
+async fn mpy_download(
+    app: tauri::AppHandle,
+    port_name: String,
+    remote_path: String,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let file_name = remote_path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let Some(picked) = app
+        .dialog()
+        .file()
+        .set_file_name(file_name)
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let data = with_raw_repl(app, port_name, move |repl| repl.read_file(&remote_path)).await?;
+    std::fs::write(&path, data)?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Runs a local .py file on the MicroPython board on `port_name` without
+/// saving it there. Its output goes to the monitor view between two marker
+/// lines; the run is an `mpy_run` task, cancelled with Ctrl-C.
+#[tauri::command]
+async fn mpy_run(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+    tasks: State<'_, TaskManager>,
+    port_name: String,
+    file_path: String,
+) -> Result<(), AppError> {
+    let code = std::fs::read_to_string(&file_path)?;
+    let name = std::path::Path::new(&file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or(file_path);
+    let monitor = monitor.inner().clone();
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("mpy_run", Some(&port_name));
+        let show = |text: &str| {
+            monitor.note(text);
+            emit_serial(&app, format!("{}\n", text).as_bytes());
+        };
+        show(&format!("=== run {} ===", name));
+        let result = with_flasher_port(&app, &port_name, "micropython", |sessions| {
+            sessions.release(&port_name);
+            micropython::session(&port_name, |repl| {
+                repl.run(
+                    &code,
+                    || task.is_cancelled(),
+                    |bytes| {
+                        monitor.output(bytes);
+                        emit_serial(&app, bytes);
+                    },
+                )
+            })
+        });
+        match &result {
+            Ok(()) => show(&format!("=== {} finished ===", name)),
+            Err(e) => show(&format!("=== {}: {} ===", name, e.message())),
+        }
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn pick_python_file(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    Ok(app
+        .dialog()
+        .file()
+        .add_filter("MicroPython", &["py"])
+        .blocking_pick_file()
+        .map(|path| path.to_string()))
+}
+
+/// Watches logs a board forwards over the network in the monitor view, in
+/// place of any serial session.
+#[tauri::command]
+async fn monitor_listen(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+    devices: State<'_, DeviceTracker>,
+    protocol: NetLogProtocol,
+    port: u16,
+) -> Result<MonitorStatus, AppError> {
+    if let Some(previous) = monitor.active_port() {
+        devices.monitoring(&previous, false);
+    }
+    let monitor = monitor.inner().clone();
+    let status = monitor.clone();
+    // Stopping a serial session waits for its reader thread
+    tauri::async_runtime::spawn_blocking(move || {
+        monitor.listen(protocol, port, move |bytes| emit_serial(&app, bytes))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(status.status())
+}
+
+/// Opens the REPL of a MicroPython board over Wi-Fi in the monitor view,
+/// in place of any serial session.
+#[tauri::command]
+async fn monitor_webrepl(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+    devices: State<'_, DeviceTracker>,
+    host: String,
+    password: String,
+) -> Result<MonitorStatus, AppError> {
+    if let Some(previous) = monitor.active_port() {
+        devices.monitoring(&previous, false);
+    }
+    let monitor = monitor.inner().clone();
+    let status = monitor.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        monitor.webrepl(&host, &password, move |bytes| emit_serial(&app, bytes))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(status.status())
+}
+
+/// Copies a file picked on this machine to the WebREPL board, under the
+/// same name. Returns the name on the device, `None` if nothing was picked.
+#[tauri::command]
+async fn webrepl_upload(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let Some(picked) = app.dialog().file().blocking_pick_file() else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| AppError::InvalidInput(format!("{} is not a file", path.display())))?;
+    let data = std::fs::read(&path)?;
+    let monitor = monitor.inner().clone();
+    let remote = name.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        monitor.with_webrepl(|repl| repl.put(&remote, &data))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(Some(name))
+}
+
+/// Saves `remote_path` from the WebREPL board to a file picked on this
+/// machine. Returns where it went, `None` if the dialog was cancelled.
+#[tauri::command]
+async fn webrepl_download(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+    remote_path: String,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let file_name = remote_path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let Some(picked) = app
+        .dialog()
+        .file()
+        .set_file_name(file_name)
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let monitor = monitor.inner().clone();
+    let data = tauri::async_runtime::spawn_blocking(move || {
+        monitor.with_webrepl(|repl| repl.get(&remote_path))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    std::fs::write(&path, data)?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// BLE devices nearby offering the Nordic UART service.
+#[tauri::command]
+async fn monitor_ble_scan() -> Result<Vec<ProvDevice>, AppError> {
+    tauri::async_runtime::spawn_blocking(|| ble_uart::scan(Duration::from_secs(5)))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Follows a BLE UART logger in the monitor view, in place of any serial
+/// session.
+#[tauri::command]
+async fn monitor_ble(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+    devices: State<'_, DeviceTracker>,
+    device: String,
+) -> Result<MonitorStatus, AppError> {
+    if let Some(previous) = monitor.active_port() {
+        devices.monitoring(&previous, false);
+    }
+    let monitor = monitor.inner().clone();
+    let status = monitor.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        monitor.ble_uart(&device, move |bytes| emit_serial(&app, bytes))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(status.status())
+}
+
+#[tauri::command]
+async fn monitor_disconnect(
+    monitor: State<'_, Monitor>,
+    devices: State<'_, DeviceTracker>,
+) -> Result<String, AppError> {
+    let port = monitor.active_port();
+    let monitor = monitor.inner().clone();
+    // Waits for the reader thread to let go of the port
+    tauri::async_runtime::spawn_blocking(move || monitor.disconnect())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    if let Some(port) = port {
+        devices.monitoring(&port, false);
+    }
+    Ok("Disconnected".to_string())
+}
+
+#[tauri::command]
+fn monitor_status(monitor: State<'_, Monitor>) -> MonitorStatus {
+    monitor.status()
+}
+
+/// A window of monitor output; the UI only asks for the lines it shows.
+#[tauri::command]
+fn monitor_lines(monitor: State<'_, Monitor>, start: Option<u64>, count: usize) -> MonitorLines {
+    monitor.lines(start, count)
+}
+
+/// The raw lines a folded monitor line stands for.
+#[tauri::command]
+fn monitor_repeats(monitor: State<'_, Monitor>, number: u64) -> Vec<String> {
+    monitor.repeats(number)
+}
+
+#[tauri::command]
+fn monitor_clear(monitor: State<'_, Monitor>) {
+    monitor.clear_lines();
+}
+
+/// Switches the monitor's terminal mode for interactive consoles on with a
+/// screen of `rows` by `cols`, or off.
+#[tauri::command]
+fn monitor_terminal(monitor: State<'_, Monitor>, enabled: bool, rows: usize, cols: usize) {
+    monitor.set_terminal(enabled.then_some((rows, cols)));
+}
+
+/// Turns echoing sent lines into the monitor output on or off.
+#[tauri::command]
+fn monitor_set_echo(monitor: State<'_, Monitor>, enabled: bool) -> MonitorStatus {
+    monitor.set_local_echo(enabled);
+    monitor.status()
+}
+
+/// Decodes monitor output received from now on as `encoding`.
+#[tauri::command]
+fn monitor_set_encoding(monitor: State<'_, Monitor>, encoding: TextEncoding) -> MonitorStatus {
+    monitor.set_encoding(encoding);
+    monitor.status()
+}
+
+/// Decodes defmt frames in the monitor output with the table of the ELF at
+/// `elf_path`, or stops decoding them when it is `None`.
+#[tauri::command]
+async fn monitor_set_defmt(
+    monitor: State<'_, Monitor>,
+    elf_path: Option<String>,
+) -> Result<MonitorStatus, AppError> {
+    let monitor = monitor.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let elf = elf_path.map(std::path::PathBuf::from);
+        monitor.set_defmt(elf.as_deref())?;
+        Ok(monitor.status())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Sends the time command from the settings with the host's current time.
+/// Returns the line sent.
+#[tauri::command]
+async fn sync_device_time(
+    monitor: State<'_, Monitor>,
+    settings: State<'_, SettingsStore>,
+) -> Result<String, AppError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let command = time_sync::render(&settings.get().time_command, now.as_millis() as u64)?;
+    monitor.send_line(&command)?;
+    Ok(command)
+}
+
+#[tauri::command]
+fn monitor_screen(monitor: State<'_, Monitor>) -> Option<TerminalScreen> {
+    monitor.screen()
+}
+
+/// Sends keystrokes of the terminal view as they are, without a newline or
+/// an echo into the log.
+#[tauri::command]
+async fn monitor_send_keys(monitor: State<'_, Monitor>, data: String) -> Result<(), AppError> {
+    monitor.send(data.as_bytes())
+}
+
+#[tauri::command]
+async fn monitor_send(monitor: State<'_, Monitor>, data: String) -> Result<String, AppError> {
+    monitor.send_line(&data)?;
+    debug!("Monitor send: {}", data);
+    Ok("Sent".to_string())
+}
+
+/// Starts recording the raw traffic of the monitor to a file picked by the
+/// user. Returns its path, `None` if the dialog was cancelled.
+#[tauri::command]
+async fn monitor_start_capture(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("Capture", &["cap", "txt"])
+        .set_file_name("serial.cap")
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    monitor.start_capture(&path)?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Stops the capture and returns its path.
+#[tauri::command]
+fn monitor_stop_capture(monitor: State<'_, Monitor>) -> Result<Option<String>, AppError> {
+    Ok(monitor
+        .stop_capture()?
+        .map(|(path, _)| path.display().to_string()))
+}
+
+#[tauri::command]
+fn monitor_capture_path(monitor: State<'_, Monitor>) -> Option<String> {
+    monitor
+        .capture_path()
+        .map(|path| path.display().to_string())
+}
+
+/// Plays a capture back at its recorded timing, `speed` times faster: into
+/// the monitor view, or its sent bytes out of the monitored port. Picks the
+/// file when `path` is `None`; the replay is a `replay` task. Returns the
+/// number of chunks played, `None` if the dialog was cancelled.
+#[tauri::command]
+async fn replay_capture(
+    app: tauri::AppHandle,
+    monitor: State<'_, Monitor>,
+    tasks: State<'_, TaskManager>,
+    path: Option<String>,
+    target: ReplayTarget,
+    speed: f64,
+) -> Result<Option<usize>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            let picked = app
+                .dialog()
+                .file()
+                .add_filter("Capture", &["cap", "txt"])
+                .blocking_pick_file();
+            let Some(picked) = picked else {
+                return Ok(None);
+            };
+            picked
+                .into_path()
+                .map_err(|e| AppError::Internal(e.to_string()))?
+        }
+    };
+    let events = capture::load(&path)?;
+    let port_name = monitor.active_port();
+    if target == ReplayTarget::Port && port_name.is_none() {
+        return Err(AppError::NotConnected);
+    }
+    let monitor = monitor.inner().clone();
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("replay", port_name.as_deref());
+        let events: Vec<_> = match target {
+            ReplayTarget::Monitor => events,
+            ReplayTarget::Port => events
+                .into_iter()
+                .filter(|event| event.direction == Direction::Tx)
+                .collect(),
+        };
+        let result = capture::replay(&events, speed, &task, |event| match target {
+            ReplayTarget::Monitor if event.direction == Direction::Rx => {
+                monitor.output(&event.data);
+                emit_serial(&app, &event.data);
+                Ok(())
+            }
+            ReplayTarget::Monitor => {
+                let text = String::from_utf8_lossy(&event.data);
+                monitor.note(&format!("> {}", text.trim_end()));
+                emit_serial(&app, format!("> {}\n", text.trim_end()).as_bytes());
+                Ok(())
+            }
+            ReplayTarget::Port => monitor.send(&event.data),
+        });
+        task.finish(&result);
+        result.map(Some)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Second serial session of the split view, for watching two boards (a
+/// gateway and a node, say) side by side. It has its own log and events and
+/// never shares a port with the main monitor.
+#[derive(Default)]
+struct SplitMonitor(Monitor);
+
+/// Forwards suspend/resume of the split view's session as
+/// `split-monitor-state` events.
+fn emit_split_state(app: &tauri::AppHandle) -> impl Fn(MonitorStateEvent) {
+    let app = app.clone();
+    move |event| {
+        let _ = app.emit("split-monitor-state", event);
+    }
+}
+
+#[tauri::command]
+async fn split_monitor_connect(
+    app: tauri::AppHandle,
+    split: State<'_, SplitMonitor>,
+    devices: State<'_, DeviceTracker>,
+    port_name: String,
+    baud_rate: u32,
+) -> Result<String, AppError> {
+    if app.state::<Monitor>().active_port().as_deref() == Some(port_name.as_str()) {
+        return Err(AppError::PortBusy(format!(
+            "{} is open in the main monitor",
+            port_name
+        )));
+    }
+    if let Some(previous) = split.0.active_port() {
+        devices.monitoring(&previous, false);
+    }
+    app.state::<FlasherSessions>().release(&port_name);
+    split.0.connect(&port_name, baud_rate, move |bytes| {
+        use base64::Engine;
+
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+        let _ = app.emit("split-serial-read", data);
+    })?;
+    devices.monitoring(&port_name, true);
+    Ok("Connected".to_string())
+}
+
+#[tauri::command]
+async fn split_monitor_disconnect(
+    split: State<'_, SplitMonitor>,
+    devices: State<'_, DeviceTracker>,
+) -> Result<String, AppError> {
+    let port = split.0.active_port();
+    let monitor = split.0.clone();
+    tauri::async_runtime::spawn_blocking(move || monitor.disconnect())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    if let Some(port) = port {
+        devices.monitoring(&port, false);
+    }
+    Ok("Disconnected".to_string())
+}
+
+#[tauri::command]
+fn split_monitor_status(split: State<'_, SplitMonitor>) -> MonitorStatus {
+    split.0.status()
+}
+
+#[tauri::command]
+fn split_monitor_lines(
+    split: State<'_, SplitMonitor>,
+    start: Option<u64>,
+    count: usize,
+) -> MonitorLines {
+    split.0.lines(start, count)
+}
+
+#[tauri::command]
+fn split_monitor_clear(split: State<'_, SplitMonitor>) {
+    split.0.clear_lines();
+}
+
+#[tauri::command]
+async fn split_monitor_send(
+    split: State<'_, SplitMonitor>,
+    data: String,
+) -> Result<String, AppError> {
+    split.0.send_line(&data)?;
+    Ok("Sent".to_string())
+}
+
+/// Flashes a merged image shipped under the app resources at 0x0.
+async fn flash_bundled(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    image: &str,
+) -> Result<String, AppError> {
+    let path = app
+        .path()
+        .resolve(image, BaseDirectory::Resource)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if !path.is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "{} is not bundled with this build",
+            image
+        )));
+    }
+    let segment = FlashSegmentArgs {
+        firmware_path: path.display().to_string(),
+        flash_address: "0x0".to_string(),
+    };
+    flash_segments(app, tasks, settings, port_name, vec![segment], None, None).await
+}
+
+/// Flashes the bundled GPIO helper image for `chip_model` at 0x0.
+#[tauri::command]
+async fn flash_gpio_helper(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    chip_model: String,
+) -> Result<String, AppError> {
+    let image = format!(
+        "firmware/gpio-helper/{}.bin",
+        chip_model.to_lowercase().replace('-', "")
+    );
+    flash_bundled(app, tasks, settings, port_name, &image).await
+}
+
+/// Catalog examples that run on `chip_model`, and whether each is bundled.
+#[tauri::command]
+fn list_examples(app: tauri::AppHandle, chip_model: String) -> Vec<ExampleFirmware> {
+    examples::for_chip(&chip_model)
+        .into_iter()
+        .map(|id| ExampleFirmware {
+            id: id.to_string(),
+            bundled: examples::image_path(id, &chip_model)
+                .ok()
+                .and_then(|image| app.path().resolve(image, BaseDirectory::Resource).ok())
+                .is_some_and(|path| path.is_file()),
+        })
+        .collect()
+}
+
+#[tauri::command]
+async fn flash_example(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    chip_model: String,
+    example: String,
+) -> Result<String, AppError> {
+    let image = examples::image_path(&example, &chip_model)?;
+    flash_bundled(app, tasks, settings, port_name, &image).await
+}
+
+/// Official MicroPython builds for `chip_model`, newest first.
+#[tauri::command]
+async fn mpy_firmware_releases(chip_model: String) -> Result<Vec<MpyRelease>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || mpy_firmware::releases(&chip_model))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Downloads MicroPython `version` (the newest release when `None`) for
+/// `chip_model` into the app cache, checks it against `sha256` when given
+/// and flashes it at the chip's bootloader offset.
+#[tauri::command]
+async fn flash_micropython(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    chip_model: String,
+    version: Option<String>,
+    sha256: Option<String>,
+) -> Result<String, AppError> {
+    check_unlocked(&app)?;
+    let cache = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .join("micropython");
+    let flash_baud = settings.get().flash.baud_rate;
+    let tasks = tasks.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        // One task from the download on, so it can be followed and cancelled
+        let task = tasks.start("flash", Some(&port_name));
+        let result = mpy_firmware::fetch(
+            &cache,
+            &chip_model,
+            version.as_deref(),
+            sha256.as_deref().filter(|s| !s.trim().is_empty()),
+            &task,
+        )
+        .and_then(|image| {
+            let segment = FlashSegment {
+                address: image.offset,
+                path: image.path.display().to_string(),
+            };
+            flash_build(&app, &port_name, &[segment], None, flash_baud, &task, None)
+        });
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// The project being copied to a CIRCUITPY drive on every change, if any.
+type CircuitPyWatch = std::sync::Mutex<Option<SyncWatch>>;
+
+/// The CIRCUITPY drive of the board with `serial_number`, or the only one
+/// mounted.
+#[tauri::command]
+async fn circuitpy_find(serial_number: Option<String>) -> Result<Option<CircuitPyDrive>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        circuitpython::pick(circuitpython::drives(), serial_number.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+#[tauri::command]
+async fn circuitpy_pick_project(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    Ok(app
+        .dialog()
+        .file()
+        .blocking_pick_folder()
+        .map(|path| path.to_string()))
+}
+
+/// Asks for an ESP-IDF project or build directory and reads what
+/// `idf.py flash` would write from it.
+#[tauri::command]
+async fn idf_pick_build(app: tauri::AppHandle) -> Result<Option<FlashPlan>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let Some(dir) = app.dialog().file().blocking_pick_folder() else {
+        return Ok(None);
+    };
+    let dir = dir
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    idf::load(&dir).map(Some)
+}
+
+/// Asks for a PlatformIO project and reads the build of its default
+/// environment.
+#[tauri::command]
+async fn pio_pick_build(app: tauri::AppHandle) -> Result<Option<FlashPlan>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let Some(dir) = app.dialog().file().blocking_pick_folder() else {
+        return Ok(None);
+    };
+    let dir = dir
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    platformio::load(&dir, None).map(Some)
+}
+
+/// The installed `arduino-cli` and the FQBN for `chip_model`.
+#[tauri::command]
+async fn arduino_status(chip_model: Option<String>) -> Result<ArduinoStatus, AppError> {
+    tauri::async_runtime::spawn_blocking(move || ArduinoStatus {
+        cli_path: arduino::find_cli().map(|path| path.display().to_string()),
+        fqbn: chip_model.as_deref().and_then(arduino::fqbn),
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+#[tauri::command]
+async fn arduino_pick_sketch(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    Ok(app
+        .dialog()
+        .file()
+        .blocking_pick_folder()
+        .map(|path| path.to_string()))
+}
+
+/// Compiles `sketch_path` for `fqbn` into the app cache as an
+/// `arduino_build` task, streaming compiler output as `build-output`
+/// events, then flashes the result when `flash` is set.
+#[tauri::command]
+async fn arduino_build(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    settings: State<'_, SettingsStore>,
+    port_name: String,
+    sketch_path: String,
+    fqbn: String,
+    flash: bool,
+) -> Result<FlashPlan, AppError> {
+    let cli = arduino::find_cli()
+        .ok_or_else(|| AppError::Config("arduino-cli was not found on the PATH".to_string()))?;
+    let (_, name) = arduino::sketch(sketch_path.as_ref())?;
+    let out_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .join("arduino")
+        .join(name);
+    let build_tasks = tasks.inner().clone();
+    let port = port_name.clone();
+    let output = app.clone();
+    let plan = tauri::async_runtime::spawn_blocking(move || {
+        let task = build_tasks.start("arduino_build", Some(&port));
+        let result = arduino::compile(&cli, sketch_path.as_ref(), &fqbn, &out_dir, &task, |text| {
+            let _ = output.emit("build-output", text.to_string());
+        });
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    if flash {
+        let segments = plan
+            .segments
+            .iter()
+            .map(|segment| FlashSegmentArgs {
+                firmware_path: segment.path.clone(),
+                flash_address: format!("{:#x}", segment.address),
+            })
+            .collect();
+        flash_segments(
+            app,
+            tasks,
+            settings,
+            port_name,
+            segments,
+            plan.chip.clone(),
+            None,
+        )
+        .await?;
+    }
+    Ok(plan)
+}
+
+/// Copies the changed files of `project_dir` to the drive; returns them.
+#[tauri::command]
+async fn circuitpy_sync(project_dir: String, drive_path: String) -> Result<Vec<String>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        circuitpython::sync(project_dir.as_ref(), drive_path.as_ref())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+/// Keeps the drive in step with `project_dir`; each copy is announced as a
+/// notification.
+#[tauri::command]
+fn circuitpy_watch(
+    app: tauri::AppHandle,
+    watch: State<'_, CircuitPyWatch>,
+    project_dir: String,
+    drive_path: String,
+) -> Result<(), AppError> {
+    if !std::path::Path::new(&drive_path).is_dir() {
+        return Err(AppError::NotConnected);
+    }
+    let handle = app.clone();
+    let started = SyncWatch::start(project_dir.into(), drive_path.into(), move |result| {
+        let (level, kind, message) = match result {
+            Ok(copied) => ("success", "circuitpy_synced", copied.join(", ")),
+            Err(e) => ("error", "circuitpy_sync_failed", e.message()),
+        };
+        notifications::emit(
+            &handle,
+            Some(Notification {
+                level: level.to_string(),
+                kind: kind.to_string(),
+                port_name: None,
+                message: Some(message),
+            }),
+        );
+    });
+    // Replacing a watch stops the old one
+    *watch.lock().unwrap() = Some(started);
+    Ok(())
+}
+
+#[tauri::command]
+fn circuitpy_unwatch(watch: State<'_, CircuitPyWatch>) {
+    watch.lock().unwrap().take();
+}
+
+/// Runs a GPIO helper request on the blocking pool; replies take a while.
+async fn gpio_request<T: Send + 'static>(
+    monitor: &Monitor,
+    op: impl FnOnce(&Monitor) -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    let monitor = monitor.clone();
+    tauri::async_runtime::spawn_blocking(move || op(&monitor))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn gpio_ping(monitor: State<'_, Monitor>) -> Result<String, AppError> {
+    gpio_request(&monitor, gpio::ping).await
+}
+
+#[tauri::command]
+async fn gpio_set_mode(
+    monitor: State<'_, Monitor>,
+    gpio: u32,
+    mode: String,
+) -> Result<(), AppError> {
+    gpio_request(&monitor, move |m| gpio::set_mode(m, gpio, &mode)).await
+}
+
+#[tauri::command]
+async fn gpio_write(monitor: State<'_, Monitor>, gpio: u32, high: bool) -> Result<(), AppError> {
+    gpio_request(&monitor, move |m| gpio::write(m, gpio, high)).await
+}
+
+#[tauri::command]
+async fn gpio_poll(monitor: State<'_, Monitor>) -> Result<Vec<PinReading>, AppError> {
+    gpio_request(&monitor, gpio::poll).await
+}
+
+#[tauri::command]
+fn list_locales(app: tauri::AppHandle) -> Vec<locales::LocaleInfo> {
+    locales::list(&app)
+}
+
+#[tauri::command]
+fn get_locale(app: tauri::AppHandle, code: String) -> Result<HashMap<String, String>, AppError> {
+    locales::load(&app, &code)
+}
+
+#[tauri::command]
+async fn get_device_prefs(
+    app: tauri::AppHandle,
+    serial_number: String,
+) -> Result<Option<DevicePrefs>, AppError> {
+    Ok(device_prefs::load(&app, &serial_number))
+}
+
+#[tauri::command]
+async fn save_device_prefs(
+    app: tauri::AppHandle,
+    serial_number: String,
+    prefs: DevicePrefs,
+) -> Result<(), AppError> {
+    device_prefs::save(&app, &serial_number, prefs)
+}
+
+#[tauri::command]
+async fn pick_firmware_file(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    debug!("Command 'pick_firmware_file' invoked!");
+    use tauri_plugin_dialog::DialogExt;
+
+    debug!("Opening dialog...");
+    let file_path = app
+        .dialog()
+        .file()
+        .add_filter("Firmware", &["bin"])
+        .blocking_pick_file();
+
+    debug!("Dialog result: {:?}", file_path);
+    Ok(file_path.map(|path| path.to_string()))
+}
+
+/// Asks for an ELF to debug.
+#[tauri::command]
+async fn pick_elf_file(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let file_path = app
+        .dialog()
+        .file()
+        .add_filter("ELF", &["elf"])
+        .blocking_pick_file();
+    Ok(file_path.map(|path| path.to_string()))
+}
+
+#[tauri::command]
+async fn pick_signing_key(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let file_path = app
+        .dialog()
+        .file()
+        .add_filter("Private key", &["pem", "key"])
+        .blocking_pick_file();
+    Ok(file_path.map(|path| path.to_string()))
+}
+
+/// Signs `firmware_path` with the key file at `key_path`, or with a PEM
+/// key pasted into the app, and writes a `-signed.bin` next to it.
+#[tauri::command]
+async fn sign_firmware(
+    firmware_path: String,
+    key_path: Option<String>,
+    key_pem: Option<String>,
+) -> Result<SignedImage, AppError> {
+    debug!("Command 'sign_firmware' invoked for {}", firmware_path);
+    tauri::async_runtime::spawn_blocking(move || {
+        let pem = match (key_pem, key_path) {
+            (Some(pem), _) if !pem.trim().is_empty() => pem,
+            (_, Some(path)) => std::fs::read_to_string(path)?,
+            _ => return Err(AppError::InvalidInput("No signing key given".to_string())),
+        };
+        let key = SigningKey::from_pem(&pem)?;
+        let signed = signature::sign_file(firmware_path.as_ref(), &key, None)?;
+        info!(
+            "Signed {} with {} key {}",
+            firmware_path, signed.scheme, signed.key_id
+        );
+        Ok(signed)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn pick_encryption_key(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let file_path = app
+        .dialog()
+        .file()
+        .add_filter("Flash encryption key", &["bin", "key"])
+        .blocking_pick_file();
+    Ok(file_path.map(|path| path.to_string()))
+}
+
+/// Encrypts `firmware_path` for `flash_address` of `chip` with a flash
+/// encryption key and writes an `-encrypted.bin` next to it, returning its
+/// path.
+#[tauri::command]
+async fn encrypt_firmware(
+    chip: String,
+    firmware_path: String,
+    key_path: String,
+    flash_address: String,
+) -> Result<String, AppError> {
+    debug!("Command 'encrypt_firmware' invoked for {}", firmware_path);
+    let address = esp_interaction::parse_flash_address(&flash_address)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let written = flash_encryption::encrypt_file(
+            &chip,
+            firmware_path.as_ref(),
+            address,
+            key_path.as_ref(),
+            None,
+        )?;
+        info!("Encrypted {} for 0x{:X}", firmware_path, address);
+        Ok(written)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn pick_pin_usage(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    // No filter: `sdkconfig` has no extension
+    let file_path = app.dialog().file().blocking_pick_file();
+    Ok(file_path.map(|path| path.to_string()))
+}
+
+/// Pins a project uses, read from its `sdkconfig` or a JSON pin list.
+#[tauri::command]
+async fn load_pin_usage(path: String) -> Result<Vec<PinUsage>, AppError> {
+    debug!("Command 'load_pin_usage' invoked for {}", path);
+    tauri::async_runtime::spawn_blocking(move || pin_usage::load(path.as_ref()))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+}
+
+#[tauri::command]
+async fn collect_diagnostics(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("Zip", &["zip"])
+        .set_file_name(format!("esp32dev-diagnostics-{}.zip", stamp))
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let driver_ok = check_ch34x_driver().await;
+    let target = path.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        diagnostics::write_bundle(&app, &target, driver_ok)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    Ok(Some(path.display().to_string()))
+}
+
+/// Saves a report of the board on `port_name` where the user picks: HTML
+/// for printing when the name ends in .html, JSON otherwise. Returns the
+/// path, `None` if the dialog was cancelled.
+#[tauri::command]
+async fn export_device_report(
+    app: tauri::AppHandle,
+    port_name: String,
+) -> Result<Option<String>, AppError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let picked = app
+        .dialog()
+        .file()
+        .add_filter("JSON", &["json"])
+        .add_filter("HTML", &["html", "htm"])
+        .set_file_name(format!("device-report-{}.json", stamp))
+        .blocking_save_file();
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let devices = app.state::<DeviceTracker>().inner().clone();
+    let status = devices
+        .list()
+        .into_iter()
+        .find(|d| d.key == port_name)
+        .map(|d| d.status);
+    let target = path.clone();
+    let tasks = app.state::<TaskManager>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = tasks.start("read", Some(&port_name));
+        let report = with_flasher_port(&app, &port_name, "identify", |sessions| {
+            esp_interaction::device_report(sessions, &port_name, status, &task)
+        });
+        task.finish(&report);
+        let report = report?;
+        let html = target
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+        let text = if html {
+            report.to_html()
+        } else {
+            report.to_json()
+        };
+        std::fs::write(&target, text)?;
+        Ok::<_, AppError>(())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    Ok(Some(path.display().to_string()))
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .manage(Monitor::default())
+        .manage(SplitMonitor::default())
+        .manage(ApiServer::default())
+        .manage(Shortcuts::default())
+        .manage(MonitorTriggers::default())
+        .manage(OtaServer::default())
+        .manage(JtagDebugger::default())
+        .manage(GdbSession::default())
+        .manage(SvdCache::default())
+        .manage(InventoryCollector::default())
+        .manage(CircuitPyWatch::default())
+        .manage(BuildWatch::default())
+        .manage(DeviceRecords::default())
+        .manage(FlasherSessions::new(FLASHER_IDLE_TIMEOUT))
+        .setup(|app| {
+            app.manage(logging::init(app.handle()));
+            let handle = app.handle().clone();
+            app.manage(TaskManager::new(move |info: &TaskInfo| {
+                let _ = handle.emit("task-progress", info);
+                notifications::emit(&handle, notifications::for_task(info));
+            }));
+            let handle = app.handle().clone();
+            app.manage(DeviceTracker::new(move |device: &DeviceSnapshot| {
+                let _ = handle.emit("device-state", device);
+                notifications::emit(&handle, notifications::for_device(device));
+                if let Some(connected) = notifications::connected(device) {
+                    notifications::desktop(&handle, &connected);
+                }
+                handle
+                    .state::<InventoryCollector>()
+                    .observe(&handle, device);
+            }));
+            app.manage(SettingsStore::load(app.handle()));
+            device_watch::start(app.handle());
+            api_server::init(app.handle());
+            shortcuts::init(app.handle());
+            triggers::init(app.handle());
+            Ok(())
         })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             greet,
             check_device_status,
+            list_serial_ports,
             get_chip_info,
+            get_board_definition,
+            get_reserved_pins,
+            list_devices,
             check_ch34x_driver,
             flash_firmware,
+            flash_segments,
+            estimate_flash,
+            flash_watch,
+            flash_unwatch,
+            flash_watch_status,
+            flash_ota_slot,
+            export_device_report,
+            get_label_sizes,
+            export_config,
+            import_config,
+            get_device_label,
+            render_label_svg,
+            export_label,
             monitor_connect,
+            monitor_listen,
+            mpy_detect,
+            mpy_list,
+            mpy_mkdir,
+            mpy_remove,
+            mpy_upload,
+            mpy_download,
+            mpy_run,
+            pick_python_file,
+            monitor_webrepl,
+            webrepl_upload,
+            webrepl_download,
+            monitor_ble_scan,
+            monitor_ble,
             monitor_disconnect,
+            monitor_status,
+            monitor_lines,
+            monitor_repeats,
+            monitor_clear,
             monitor_send,
+            monitor_set_echo,
+            monitor_set_encoding,
+            monitor_set_defmt,
+            sync_device_time,
+            monitor_terminal,
+            monitor_screen,
+            monitor_send_keys,
+            monitor_start_capture,
+            monitor_stop_capture,
+            monitor_capture_path,
+            replay_capture,
+            split_monitor_connect,
+            split_monitor_disconnect,
+            split_monitor_status,
+            split_monitor_lines,
+            split_monitor_clear,
+            split_monitor_send,
+            flash_gpio_helper,
+            list_examples,
+            flash_example,
+            mpy_firmware_releases,
+            flash_micropython,
+            circuitpy_find,
+            circuitpy_pick_project,
+            idf_pick_build,
+            pio_pick_build,
+            arduino_status,
+            arduino_pick_sketch,
+            arduino_build,
+            circuitpy_sync,
+            circuitpy_watch,
+            circuitpy_unwatch,
+            gpio_ping,
+            gpio_set_mode,
+            gpio_write,
+            gpio_poll,
             pick_firmware_file,
-            erase_flash
+            pick_signing_key,
+            sign_firmware,
+            pick_encryption_key,
+            encrypt_firmware,
+            pick_pin_usage,
+            load_pin_usage,
+            erase_flash,
+            run_flash_benchmark,
+            image_info,
+            place_image,
+            elf2image_pick,
+            assemble_full_image,
+            esptool_commands,
+            reset_device,
+            ota_upload,
+            improv_provision,
+            ble_scan,
+            ble_provision,
+            ota_server_start,
+            ota_server_stop,
+            ota_server_status,
+            openocd_probes,
+            openocd_start,
+            openocd_stop,
+            openocd_status,
+            gdb_start,
+            gdb_stop,
+            gdb_status,
+            gdb_break,
+            gdb_continue,
+            gdb_halt,
+            gdb_reset,
+            gdb_backtrace,
+            gdb_console,
+            svd_peripherals,
+            svd_peripheral,
+            svd_read,
+            pick_elf_file,
+            coredump_from_monitor,
+            coredump_from_flash,
+            coredump_from_file,
+            pick_coredump_file,
+            inventory_start,
+            inventory_stop,
+            inventory_status,
+            get_device_prefs,
+            save_device_prefs,
+            get_flash_history,
+            device_records,
+            export_device_records,
+            production_runs,
+            run_report,
+            export_run_csv,
+            export_run_report,
+            get_boot_history,
+            list_tasks,
+            cancel_task,
+            run_script,
+            pick_test_sequence,
+            run_test_sequence,
+            run_soak_test,
+            get_settings,
+            update_settings,
+            kiosk_enter,
+            kiosk_exit,
+            kiosk_flash,
+            get_recent_logs,
+            get_api_token,
+            list_locales,
+            get_locale,
+            collect_diagnostics
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,93 @@
+use esp32dev_core::models::AppError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+use tracing::warn;
+
+// Shipped with the app; `<app config>/locales/<code>.json` adds more or
+// overrides these without a rebuild
+const BUNDLED: [(&str, &str); 2] = [
+    ("en", include_str!("../../locales/en.json")),
+    ("zh", include_str!("../../locales/zh.json")),
+];
+// Key holding a locale's own name, shown in the language picker
+const NAME_KEY: &str = "language_name";
+
+/// A UI language as listed by `list_locales`.
+#[derive(Serialize, Clone)]
+pub struct LocaleInfo {
+    pub code: String,
+    pub name: String,
+}
+
+fn user_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join("locales"))
+}
+
+fn parse(code: &str, text: &str) -> Result<HashMap<String, String>, AppError> {
+    serde_json::from_str(text)
+        .map_err(|e| AppError::Config(format!("Locale {} is not valid: {}", code, e)))
+}
+
+/// Bundled locales followed by user ones, each once.
+pub fn list(app: &tauri::AppHandle) -> Vec<LocaleInfo> {
+    let mut codes: Vec<String> = BUNDLED.iter().map(|(code, _)| code.to_string()).collect();
+    let mut extra: Vec<String> = user_dir(app)
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().into_owned())
+        })
+        .filter(|code| !codes.contains(code))
+        .collect();
+    extra.sort();
+    codes.extend(extra);
+
+    codes
+        .into_iter()
+        .filter_map(|code| match load(app, &code) {
+            Ok(strings) => Some(LocaleInfo {
+                name: strings
+                    .get(NAME_KEY)
+                    .cloned()
+                    .unwrap_or_else(|| code.clone()),
+                code,
+            }),
+            Err(e) => {
+                warn!("Skipping locale {}: {}", code, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Strings of `code`. A user file wins over the bundled one; keys either
+/// lacks are filled in from English by the UI.
+pub fn load(app: &tauri::AppHandle, code: &str) -> Result<HashMap<String, String>, AppError> {
+    // Codes become file names
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(AppError::InvalidInput(format!(
+            "Bad locale code {:?}",
+            code
+        )));
+    }
+    if let Some(path) = user_dir(app).map(|dir| dir.join(format!("{}.json", code))) {
+        if let Ok(text) = fs::read_to_string(&path) {
+            return parse(code, &text);
+        }
+    }
+    match BUNDLED.iter().find(|(bundled, _)| *bundled == code) {
+        Some((_, text)) => parse(code, text),
+        None => Err(AppError::Config(format!("No locale {}", code))),
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+// Lines kept in memory for the debug panel
+const RECENT_CAPACITY: usize = 2000;
+// Daily log files kept on disk
+const MAX_LOG_FILES: usize = 7;
+
+/// In-memory tail of the backend log, filled by a tracing layer.
+#[derive(Clone, Default)]
+pub struct RecentLogs {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl RecentLogs {
+    pub fn tail(&self, limit: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap();
+        let skip = lines.len().saturating_sub(limit);
+        lines.iter().skip(skip).cloned().collect()
+    }
+
+    fn push(&self, text: &str) {
+        let mut lines = self.lines.lock().unwrap();
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            if lines.len() == RECENT_CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+}
+
+/// Buffers one formatted event and hands it to `RecentLogs` when dropped.
+pub struct RecentWriter {
+    logs: RecentLogs,
+    buf: Vec<u8>,
+}
+
+impl Write for RecentWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecentWriter {
+    fn drop(&mut self) {
+        self.logs.push(&String::from_utf8_lossy(&self.buf));
+    }
+}
+
+impl<'a> MakeWriter<'a> for RecentLogs {
+    type Writer = RecentWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentWriter {
+            logs: self.clone(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// Installs the global subscriber: stdout, rotated files in the app log dir
+/// and the in-memory tail. Also captures `log` records from espflash.
+pub fn init(app: &tauri::AppHandle) -> RecentLogs {
+    let recent = RecentLogs::default();
+
+    let file_appender = app.path().app_log_dir().ok().and_then(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix("esp32dev")
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+            .ok()
+    });
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let result = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_appender.map(|appender| {
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(appender)
+        }))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(recent.clone()),
+        )
+        .try_init();
+    if let Err(e) = result {
+        eprintln!("Logger already initialized: {}", e);
+    }
+
+    recent
+}
@@ -0,0 +1,119 @@
+use crate::locales;
+use crate::settings::SettingsStore;
+use esp32dev_core::esp_interaction;
+use esp32dev_core::models::{
+    DesktopNotifications, DeviceSnapshot, DeviceState, Notification, TaskInfo,
+};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::debug;
+
+/// Announces the end of a task. Progress updates stay silent.
+pub fn for_task(info: &TaskInfo) -> Option<Notification> {
+    let level = match info.state.as_str() {
+        "completed" => "success",
+        "failed" => "error",
+        "cancelled" => "info",
+        _ => return None,
+    };
+    Some(Notification {
+        level: level.to_string(),
+        kind: format!("{}_{}", info.kind, info.state),
+        port_name: info.port_name.clone(),
+        // A finished transfer says how fast it went
+        message: info.message.clone().or_else(|| {
+            let rate = info.bytes_per_sec.filter(|_| info.state == "completed")?;
+            Some(esp_interaction::format_rate(rate))
+        }),
+    })
+}
+
+/// Announces a board going away or failing its first probe. Failed
+/// operations are already reported through their task.
+pub fn for_device(device: &DeviceSnapshot) -> Option<Notification> {
+    let (level, kind, message) = match (device.state, device.previous) {
+        (DeviceState::Disconnected, previous) => {
+            // Losing a board mid-session is worth more attention
+            let busy = matches!(
+                previous,
+                Some(DeviceState::Monitoring) | Some(DeviceState::Flashing)
+            );
+            let level = if busy { "warning" } else { "info" };
+            (level, "device_lost", device.status.product_name.clone())
+        }
+        (DeviceState::Error, Some(DeviceState::Detected)) => (
+            "warning",
+            "device_error",
+            device.error.as_ref().map(|e| e.message()),
+        ),
+        _ => return None,
+    };
+    Some(Notification {
+        level: level.to_string(),
+        kind: kind.to_string(),
+        port_name: device.status.port_name.clone(),
+        message,
+    })
+}
+
+/// A board showing up for the first time. Only ever an OS notification,
+/// the device list already shows it in the app.
+pub fn connected(device: &DeviceSnapshot) -> Option<Notification> {
+    if device.previous.is_some() {
+        return None;
+    }
+    Some(Notification {
+        level: "info".to_string(),
+        kind: "device_connected".to_string(),
+        port_name: device.status.port_name.clone(),
+        message: device.status.product_name.clone(),
+    })
+}
+
+/// Whether `kind` is raised as an OS notification under `wanted`. Kinds
+/// without a setting stay in the app.
+pub fn wanted_on_desktop(wanted: &DesktopNotifications, kind: &str) -> bool {
+    match kind {
+        "device_connected" => wanted.device_connected,
+        "device_lost" => wanted.device_disconnected,
+        "flash_completed" => wanted.flash_completed,
+        "flash_failed" => wanted.flash_failed,
+        "trigger" => wanted.monitor_triggers,
+        _ => false,
+    }
+}
+
+/// Raises `notification` as an OS notification when the settings ask for
+/// its kind, titled like its toast in the UI language.
+pub fn desktop(app: &tauri::AppHandle, notification: &Notification) {
+    // Events can arrive while the app is still being set up
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return;
+    };
+    let settings = store.get();
+    if !wanted_on_desktop(&settings.desktop_notifications, &notification.kind) {
+        return;
+    }
+    let key = format!("toast_{}", notification.kind);
+    let title = [settings.language.as_str(), "en"]
+        .into_iter()
+        .find_map(|code| locales::load(app, code).ok()?.remove(&key))
+        .unwrap_or_else(|| notification.kind.clone());
+    let body = match (&notification.port_name, &notification.message) {
+        (Some(port), Some(message)) => format!("{}: {}", port, message),
+        (Some(text), None) | (None, Some(text)) => text.clone(),
+        (None, None) => String::new(),
+    };
+    let shown = app.notification().builder().title(title).body(body).show();
+    if let Err(e) = shown {
+        debug!("Desktop notification failed: {}", e);
+    }
+}
+
+/// Shows `notification` in the app, and on the desktop when enabled.
+pub fn emit(app: &tauri::AppHandle, notification: Option<Notification>) {
+    if let Some(notification) = notification {
+        desktop(app, &notification);
+        let _ = app.emit("notify", notification);
+    }
+}
@@ -0,0 +1,319 @@
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use esp32dev_core::models::AppError;
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::Serialize;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::JoinHandle;
+use tauri::Emitter;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+// Path the image is served under, whatever the file is called locally
+const IMAGE_PATH: &str = "/firmware.bin";
+// Download attempts kept for the UI
+const LOG_LIMIT: usize = 100;
+// How long downloads under way may take to finish when the server stops
+const STOP_GRACE: Duration = Duration::from_secs(2);
+
+/// One request a board (or anything else) made to the server.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OtaDownload {
+    pub time: u64, // seconds since the epoch
+    pub client: String,
+    pub path: String,
+    pub range: Option<String>,
+    pub status: u16,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OtaServerStatus {
+    pub running: bool,
+    pub url: Option<String>,
+    /// The URL as a QR code, for pasting into a provisioning app.
+    pub qr_svg: Option<String>,
+    pub file_name: Option<String>,
+    pub size: u64,
+    pub downloads: Vec<OtaDownload>,
+}
+
+struct Running {
+    status: OtaServerStatus,
+    shutdown: oneshot::Sender<()>,
+    // Owns the listener; the port is free again once it has ended
+    task: JoinHandle<()>,
+}
+
+#[derive(Clone)]
+struct ServeState {
+    app: tauri::AppHandle,
+    image: Arc<Vec<u8>>,
+    downloads: Arc<Mutex<Vec<OtaDownload>>>,
+}
+
+/// LAN HTTP endpoint serving one firmware file to boards that update
+/// themselves with `HTTPUpdate`, or `esp_https_ota` built with
+/// `CONFIG_ESP_HTTPS_OTA_ALLOW_HTTP` (it refuses plain HTTP otherwise).
+/// Unlike the API server it listens on every interface, and only while
+/// switched on.
+#[derive(Default)]
+pub struct OtaServer {
+    running: Mutex<Option<Running>>,
+    downloads: Arc<Mutex<Vec<OtaDownload>>>,
+}
+
+impl OtaServer {
+    /// Serves `firmware_path` on `port`, replacing whatever was served.
+    pub fn start(
+        &self,
+        app: &tauri::AppHandle,
+        firmware_path: &str,
+        port: u16,
+    ) -> Result<OtaServerStatus, AppError> {
+        self.stop();
+        let image = std::fs::read(firmware_path).map_err(|e| AppError::Io(e.to_string()))?;
+        let listener = std::net::TcpListener::bind(("0.0.0.0", port))
+            .and_then(|l| l.set_nonblocking(true).map(|_| l))
+            .map_err(|e| AppError::Config(format!("OTA port {} unavailable: {}", port, e)))?;
+
+        let url = format!("http://{}:{}{}", lan_address(), port, IMAGE_PATH);
+        let qr_svg = QrCode::new(url.as_bytes())
+            .ok()
+            .map(|code| code.render::<svg::Color>().min_dimensions(160, 160).build());
+        let file_name = std::path::Path::new(firmware_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        self.downloads.lock().unwrap().clear();
+        let status = OtaServerStatus {
+            running: true,
+            url: Some(url.clone()),
+            qr_svg,
+            file_name,
+            size: image.len() as u64,
+            downloads: Vec::new(),
+        };
+
+        let state = ServeState {
+            app: app.clone(),
+            image: Arc::new(image),
+            downloads: self.downloads.clone(),
+        };
+        let router = Router::new().fallback(serve).with_state(state);
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let task = tauri::async_runtime::spawn(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(l) => l,
+                Err(e) => {
+                    warn!("OTA server failed to start: {}", e);
+                    return;
+                }
+            };
+            info!("OTA server serving {}", url);
+            let server = axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(async {
+                stopped.await.ok();
+            });
+            if let Err(e) = server.await {
+                warn!("OTA server error: {}", e);
+            }
+        });
+
+        *self.running.lock().unwrap() = Some(Running {
+            status: status.clone(),
+            shutdown,
+            task,
+        });
+        Ok(status)
+    }
+
+    /// Stops serving and waits for the listener to close, so the port can
+    /// be bound again right away. Not to be called from async code.
+    pub fn stop(&self) {
+        let Some(old) = self.running.lock().unwrap().take() else {
+            return;
+        };
+        let _ = old.shutdown.send(());
+        let mut task = old.task;
+        tauri::async_runtime::block_on(async {
+            // A board still downloading would hold off a graceful stop
+            if tokio::time::timeout(STOP_GRACE, &mut task).await.is_err() {
+                task.abort();
+                let _ = task.await;
+            }
+        });
+        info!("OTA server stopped");
+    }
+
+    pub fn status(&self) -> OtaServerStatus {
+        let mut status = self
+            .running
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|r| r.status.clone())
+            .unwrap_or_default();
+        // The log outlives a stop so the last attempts stay readable
+        status.downloads = self.downloads.lock().unwrap().clone();
+        status
+    }
+}
+
+/// Address other machines on the LAN reach us at. Connecting a UDP socket
+/// sends nothing; it only makes the OS pick the outgoing interface.
+fn lan_address() -> String {
+    UdpSocket::bind(("0.0.0.0", 0))
+        .and_then(|socket| {
+            socket.connect(("8.8.8.8", 80))?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|_| "127.0.0.1".to_string())
+}
+
+/// First and last byte of a `Range: bytes=...` header; `None` when it
+/// can't be satisfied.
+fn parse_range(range: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = range.strip_prefix("bytes=")?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = if start.is_empty() {
+        // Suffix form: the last N bytes
+        let suffix: u64 = end.parse().ok()?;
+        (len.checked_sub(suffix.min(len))?, len.checked_sub(1)?)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() {
+            len.checked_sub(1)?
+        } else {
+            end.parse::<u64>().ok()?.min(len.checked_sub(1)?)
+        };
+        (start, end)
+    };
+    (start <= end && end < len).then_some((start, end))
+}
+
+async fn serve(
+    State(state): State<ServeState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    request: Request,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let range = header_text(request.headers(), header::RANGE);
+    let len = state.image.len() as u64;
+
+    let response = if path != IMAGE_PATH || !matches!(*request.method(), Method::GET | Method::HEAD)
+    {
+        (StatusCode::NOT_FOUND, Body::empty()).into_response()
+    } else {
+        let (status, start, end) = match range.as_deref() {
+            None => (StatusCode::OK, 0, len.saturating_sub(1)),
+            Some(range) => match parse_range(range, len) {
+                Some((start, end)) => (StatusCode::PARTIAL_CONTENT, start, end),
+                None => {
+                    let mut response =
+                        (StatusCode::RANGE_NOT_SATISFIABLE, Body::empty()).into_response();
+                    insert(
+                        &mut response,
+                        header::CONTENT_RANGE,
+                        format!("bytes */{}", len),
+                    );
+                    return log(&state, client, path, Some(range.to_string()), response);
+                }
+            },
+        };
+        let body = if len == 0 || *request.method() == Method::HEAD {
+            Vec::new()
+        } else {
+            state.image[start as usize..=end as usize].to_vec()
+        };
+        let content_length = if len == 0 { 0 } else { end - start + 1 };
+        let mut response = (status, body).into_response();
+        insert(
+            &mut response,
+            header::CONTENT_TYPE,
+            "application/octet-stream".to_string(),
+        );
+        insert(
+            &mut response,
+            header::CONTENT_LENGTH,
+            content_length.to_string(),
+        );
+        insert(&mut response, header::ACCEPT_RANGES, "bytes".to_string());
+        insert(&mut response, header::CACHE_CONTROL, "no-store".to_string());
+        insert(
+            &mut response,
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"firmware.bin\"".to_string(),
+        );
+        if status == StatusCode::PARTIAL_CONTENT {
+            insert(
+                &mut response,
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, len),
+            );
+        }
+        response
+    };
+    log(&state, client, path, range, response)
+}
+
+fn header_text(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn insert(response: &mut Response, name: header::HeaderName, value: String) {
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        response.headers_mut().insert(name, value);
+    }
+}
+
+/// Records the attempt and tells the UI about it.
+fn log(
+    state: &ServeState,
+    client: SocketAddr,
+    path: String,
+    range: Option<String>,
+    response: Response,
+) -> Response {
+    let bytes = header_text(response.headers(), header::CONTENT_LENGTH)
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(0);
+    let entry = OtaDownload {
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        client: client.ip().to_string(),
+        path,
+        range,
+        status: response.status().as_u16(),
+        bytes,
+    };
+    info!(
+        "OTA request from {} for {} -> {}",
+        entry.client, entry.path, entry.status
+    );
+    {
+        let mut downloads = state.downloads.lock().unwrap();
+        if downloads.len() >= LOG_LIMIT {
+            downloads.remove(0);
+        }
+        downloads.push(entry.clone());
+    }
+    let _ = state.app.emit("ota-download", entry);
+    response
+}
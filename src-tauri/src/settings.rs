@@ -0,0 +1,73 @@
+use esp32dev_core::models::{AppError, Settings};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Settings held in memory and mirrored to `settings.json` in the app config dir.
+#[derive(Clone)]
+pub struct SettingsStore {
+    settings: Arc<Mutex<Settings>>,
+    path: Option<PathBuf>,
+}
+
+impl SettingsStore {
+    pub fn load(app: &tauri::AppHandle) -> Self {
+        let path = app
+            .path()
+            .app_config_dir()
+            .ok()
+            .map(|dir| dir.join(SETTINGS_FILE));
+
+        // Missing or unreadable settings are not fatal, start from defaults
+        let settings = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        SettingsStore {
+            settings: Arc::new(Mutex::new(settings)),
+            path,
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, settings: Settings) -> Result<(), AppError> {
+        self.update(|current| {
+            *current = settings;
+            Ok(())
+        })
+    }
+
+    /// Changes the settings under the lock, so a read-modify-write can't
+    /// lose a change made at the same time. Nothing is saved if `change`
+    /// fails.
+    pub fn update<R>(
+        &self,
+        change: impl FnOnce(&mut Settings) -> Result<R, AppError>,
+    ) -> Result<R, AppError> {
+        let mut settings = self.settings.lock().unwrap();
+        let mut updated = settings.clone();
+        let out = change(&mut updated)?;
+
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| AppError::Config("No config directory available".to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(&updated)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        fs::write(path, text)?;
+
+        *settings = updated;
+        Ok(out)
+    }
+}
@@ -0,0 +1,116 @@
+use crate::settings::SettingsStore;
+use esp32dev_core::models::{AppError, ShortcutSettings};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{debug, warn};
+
+/// Keyboard shortcuts from the settings, registered through the global
+/// shortcut plugin while the main window has focus so they never steal
+/// keys from other applications. A press is emitted to the UI as a
+/// `shortcut` event carrying the action name.
+#[derive(Default)]
+pub struct Shortcuts {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    bindings: ShortcutSettings,
+    focused: bool,
+}
+
+impl Shortcuts {
+    /// Switches to `bindings`, failing on an accelerator that can't be parsed
+    /// or is taken by another application.
+    pub fn apply(
+        &self,
+        app: &tauri::AppHandle,
+        bindings: &ShortcutSettings,
+    ) -> Result<(), AppError> {
+        // Catch typos even while the keys aren't registered
+        for (action, accelerator) in bindings.bindings() {
+            if !accelerator.is_empty() {
+                accelerator.parse::<Shortcut>().map_err(|e| {
+                    AppError::InvalidInput(format!(
+                        "Shortcut {:?} for {}: {}",
+                        accelerator, action, e
+                    ))
+                })?;
+            }
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.focused {
+            if let Err(e) = register(app, bindings) {
+                // Put the previous set back so the keys keep working
+                let _ = register(app, &state.bindings);
+                return Err(e);
+            }
+        }
+        state.bindings = bindings.clone();
+        Ok(())
+    }
+
+    /// Registers the bindings while the window has focus and drops them
+    /// when it loses it.
+    pub fn set_focused(&self, app: &tauri::AppHandle, focused: bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.focused == focused {
+            return;
+        }
+        state.focused = focused;
+        let result = if focused {
+            register(app, &state.bindings)
+        } else {
+            app.global_shortcut()
+                .unregister_all()
+                .map_err(|e| AppError::Internal(e.to_string()))
+        };
+        if let Err(e) = result {
+            warn!("Shortcuts: {}", e);
+        }
+    }
+}
+
+fn register(app: &tauri::AppHandle, bindings: &ShortcutSettings) -> Result<(), AppError> {
+    let shortcuts = app.global_shortcut();
+    shortcuts
+        .unregister_all()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    for (action, accelerator) in bindings.bindings() {
+        if accelerator.is_empty() {
+            continue;
+        }
+        shortcuts
+            .on_shortcut(accelerator, move |app, _, event| {
+                if event.state == ShortcutState::Pressed {
+                    debug!("Shortcut {}", action);
+                    let _ = app.emit("shortcut", action);
+                }
+            })
+            .map_err(|e| {
+                AppError::InvalidInput(format!("Shortcut {:?} for {}: {}", accelerator, action, e))
+            })?;
+    }
+    Ok(())
+}
+
+/// Loads the bindings saved in the settings and follows the main window's
+/// focus from then on.
+pub fn init(app: &tauri::AppHandle) {
+    let bindings = app.state::<SettingsStore>().get().shortcuts;
+    let shortcuts = app.state::<Shortcuts>();
+    if let Err(e) = shortcuts.apply(app, &bindings) {
+        warn!("{}", e);
+    }
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    shortcuts.set_focused(app, window.is_focused().unwrap_or(false));
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Focused(focused) = event {
+            handle.state::<Shortcuts>().set_focused(&handle, *focused);
+        }
+    });
+}
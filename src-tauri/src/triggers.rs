@@ -0,0 +1,100 @@
+use crate::notifications;
+use crate::settings::SettingsStore;
+use esp32dev_core::lifecycle::DeviceTracker;
+use esp32dev_core::models::{
+    AppError, MonitorStateEvent, MonitorTrigger, Notification, TriggerAction,
+};
+use esp32dev_core::monitor::Monitor;
+use esp32dev_core::triggers::{TriggerHit, Triggers};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tracing::{debug, info, warn};
+
+/// Monitor triggers from the settings. Output is fed in from the monitor's
+/// reader thread, so actions run in the backend even when the window is
+/// closed or showing another page.
+#[derive(Default)]
+pub struct MonitorTriggers {
+    triggers: Mutex<Triggers>,
+}
+
+impl MonitorTriggers {
+    /// Switches to `triggers`, failing on a pattern that doesn't compile.
+    pub fn apply(&self, triggers: &[MonitorTrigger]) -> Result<(), AppError> {
+        *self.triggers.lock().unwrap() = Triggers::new(triggers)?;
+        Ok(())
+    }
+
+    /// Starts a new stream, e.g. when the monitor connects.
+    pub fn reset(&self) {
+        self.triggers.lock().unwrap().reset();
+    }
+
+    /// Checks output received from `port_name` and carries out what it set
+    /// off.
+    pub fn feed(&self, app: &tauri::AppHandle, port_name: &str, bytes: &[u8]) {
+        let hits = self.triggers.lock().unwrap().push(bytes);
+        for hit in hits {
+            run(app, port_name, hit);
+        }
+    }
+}
+
+fn run(app: &tauri::AppHandle, port_name: &str, hit: TriggerHit) {
+    debug!("Trigger {:?} matched {:?}", hit.pattern, hit.line);
+    let monitor = app.state::<Monitor>();
+    match hit.action {
+        TriggerAction::Send { text } => match monitor.send(format!("{}\r\n", text).as_bytes()) {
+            Ok(()) => monitor.note(&format!("> {}", text)),
+            Err(e) => warn!("Trigger {:?} could not send: {}", hit.pattern, e),
+        },
+        TriggerAction::Mark { label } => monitor.note(&format!("── {} ──", label)),
+        TriggerAction::Notify { message } => {
+            let message = if message.is_empty() {
+                hit.line
+            } else {
+                message
+            };
+            // Also raised on the desktop unless turned off in the settings
+            notifications::emit(
+                app,
+                Some(Notification {
+                    level: "warning".to_string(),
+                    kind: "trigger".to_string(),
+                    port_name: Some(port_name.to_string()),
+                    message: Some(message),
+                }),
+            );
+        }
+        TriggerAction::Stop => {
+            info!("Trigger {:?} stops the monitor", hit.pattern);
+            monitor.note(&format!("── {} ──", hit.line));
+            // Disconnecting waits for the reader thread this runs on
+            let app = app.clone();
+            let port_name = port_name.to_string();
+            std::thread::spawn(move || {
+                if let Err(e) = app.state::<Monitor>().disconnect() {
+                    warn!("{}", e);
+                    return;
+                }
+                app.state::<DeviceTracker>().monitoring(&port_name, false);
+                let _ = app.emit(
+                    "monitor-state",
+                    MonitorStateEvent {
+                        state: "stopped".to_string(),
+                        port_name,
+                        reason: "trigger".to_string(),
+                    },
+                );
+            });
+        }
+    }
+}
+
+/// Loads the triggers saved in the settings.
+pub fn init(app: &tauri::AppHandle) {
+    let triggers = app.state::<SettingsStore>().get().triggers;
+    if let Err(e) = app.state::<MonitorTriggers>().apply(&triggers) {
+        warn!("{}", e);
+    }
+}
@@ -1,9 +1,12 @@
 #![allow(non_snake_case)]
 
-use crate::components::Layout;
-use crate::i18n::Language;
+use crate::components::{use_toasts_provider, Layout, ToastHost};
+use crate::i18n::{list_locales, load_locale, Dict};
 use crate::pages::devices::Devices;
 use crate::pages::home::Home;
+use crate::pages::kiosk::Kiosk;
+use crate::pages::onboarding::Onboarding;
+use crate::settings::{load_settings, save_settings, Settings};
 use dioxus::prelude::*;
 use wasm_bindgen::prelude::*;
 
@@ -18,6 +21,12 @@ extern "C" {
     fn set_theme(theme: &str);
 }
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
 #[derive(Clone, Copy, PartialEq)]
 enum Theme {
     Light,
@@ -29,8 +38,11 @@ pub enum Route {
     #[layout(AppLayout)]
     #[route("/")]
     Home {},
-    #[route("/devices")]
-    Devices {},
+    // `tab` and `port` pre-select a Devices tab and port, e.g. from Home
+    #[route("/devices?:tab&:port")]
+    Devices { tab: String, port: String },
+    #[route("/onboarding")]
+    Onboarding {},
     #[end_layout]
     #[route("/:..route")]
     PageNotFound { route: Vec<String> },
@@ -54,11 +66,23 @@ pub fn App() -> Element {
 #[component]
 fn AppLayout() -> Element {
     let mut theme = use_signal(|| Theme::Dark);
-    let mut lang = use_context_provider(|| Signal::new(Language::Zh));
+    let mut lang = use_context_provider(|| Signal::new(Dict::default()));
+    let mut settings = use_context_provider(|| Signal::new(Settings::default()));
+    let toasts = use_toasts_provider();
 
-    // Apply initial theme
+    // Apply initial theme, then hydrate from the persisted settings
     use_effect(move || {
         set_theme("dark");
+        spawn(async move {
+            if let Some(saved) = load_settings().await {
+                if saved.theme == "light" {
+                    theme.set(Theme::Light);
+                    set_theme("light");
+                }
+                lang.set(load_locale(&saved.language).await);
+                settings.set(saved);
+            }
+        });
     });
 
     let toggle_theme = move |_| {
@@ -73,22 +97,79 @@ fn AppLayout() -> Element {
             Theme::Dark => "dark",
         };
         set_theme(theme_str);
+
+        settings.write().theme = theme_str.to_string();
+        let updated = settings.read().clone();
+        spawn(save_settings(updated));
     };
 
+    // Cycles through the locales the backend offers
     let toggle_lang = move |_| {
-        let new_lang = match *lang.read() {
-            Language::En => Language::Zh,
-            Language::Zh => Language::En,
-        };
-        lang.set(new_lang);
+        spawn(async move {
+            let locales = list_locales().await;
+            let current = locales
+                .iter()
+                .position(|l| l.code == lang.peek().code())
+                .unwrap_or(locales.len() - 1);
+            let next = locales[(current + 1) % locales.len()].code.clone();
+            lang.set(load_locale(&next).await);
+
+            settings.write().language = next;
+            let updated = settings.read().clone();
+            save_settings(updated).await;
+        });
     };
 
+    let toggle_api = move |_| {
+        let enabled = !settings.read().api.enabled;
+        settings.write().api.enabled = enabled;
+        let updated = settings.read().clone();
+        spawn(async move {
+            // The backend refuses when the port is taken; show what it kept
+            save_settings(updated).await;
+            if let Some(saved) = load_settings().await {
+                settings.set(saved);
+            }
+        });
+    };
+
+    // For scripts: every request that changes something needs the token
+    let copy_api_token = move |_| {
+        spawn(async move {
+            let token = invoke("get_api_token", JsValue::NULL)
+                .await
+                .ok()
+                .and_then(|v| v.as_string());
+            let Some((token, window)) = token.zip(web_sys::window()) else {
+                toasts.push("error", "api_token_failed", None);
+                return;
+            };
+            let promise = window.navigator().clipboard().write_text(&token);
+            match wasm_bindgen_futures::JsFuture::from(promise).await {
+                Ok(_) => toasts.push("success", "api_token_copied", None),
+                Err(_) => toasts.push("error", "api_token_failed", None),
+            }
+        });
+    };
+
+    // Kiosk mode hides the rest of the app, navigation included
+    if settings.read().kiosk.enabled {
+        return rsx! {
+            Kiosk {}
+            ToastHost {}
+        };
+    }
+
     rsx! {
         Layout {
             on_theme_toggle: toggle_theme,
             on_lang_toggle: toggle_lang,
+            on_api_toggle: toggle_api,
+            on_api_token: copy_api_token,
             is_dark: *theme.read() == Theme::Dark,
+            api_enabled: settings.read().api.enabled,
             Outlet::<Route> {}
         }
+        ToastHost {}
     }
 }
@@ -0,0 +1,283 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ArduinoStatus {
+    cli_path: Option<String>,
+    fqbn: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArduinoStatusArgs {
+    chip_model: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArduinoBuildArgs {
+    port_name: String,
+    sketch_path: String,
+    fqbn: String,
+    flash: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelTaskArgs {
+    task_id: u64,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    id: u64,
+    kind: String,
+    state: String,
+}
+
+/// Builds a sketch with the installed `arduino-cli` for the board on
+/// `port_name` and optionally flashes it, showing the compiler output.
+/// The FQBN defaults to the dev module of `chip_model`.
+#[component]
+pub fn ArduinoPanel(port_name: String, chip_model: Option<String>) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut sketch = use_signal(String::new);
+    let mut fqbn = use_signal(String::new);
+    let mut output = use_signal(String::new);
+    let mut is_running = use_signal(|| false);
+    let mut task_id = use_signal(|| None::<u64>);
+
+    let status = use_resource(use_reactive!(|chip_model| async move {
+        let args = serde_wasm_bindgen::to_value(&ArduinoStatusArgs { chip_model }).ok()?;
+        let val = invoke("arduino_status", args).await.ok()?;
+        serde_wasm_bindgen::from_value::<ArduinoStatus>(val).ok()
+    }));
+    let status = status.read().clone().flatten();
+    let cli_path = status.as_ref().and_then(|s| s.cli_path.clone());
+    let detected_fqbn = status.and_then(|s| s.fqbn).unwrap_or_default();
+
+    struct ListenerGuard {
+        unlisten: Vec<js_sys::Function>,
+        _closures: Vec<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            for f in &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: Vec::new(),
+        _closures: Vec::new(),
+    });
+
+    // Compiler output while a build runs
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: String,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    output.write().push_str(&e.payload);
+                }
+            });
+            match listen("build-output", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // Remember the build (then flash) task so Stop can cancel it
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    let ours = e.payload.kind == "arduino_build" || e.payload.kind == "flash";
+                    if ours && e.payload.state == "running" && *is_running.peek() {
+                        task_id.set(Some(e.payload.id));
+                    }
+                }
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let pick_sketch = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("arduino_pick_sketch", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    sketch.set(path);
+                }
+            }
+        });
+    };
+
+    let board = if fqbn.read().trim().is_empty() {
+        detected_fqbn.clone()
+    } else {
+        fqbn.read().trim().to_string()
+    };
+    let mut build = {
+        let port_name = port_name.clone();
+        let board = board.clone();
+        move |flash: bool| {
+            let port_name = port_name.clone();
+            let fqbn = board.clone();
+            is_running.set(true);
+            output.set(String::new());
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&ArduinoBuildArgs {
+                    port_name,
+                    sketch_path: sketch.read().clone(),
+                    fqbn,
+                    flash,
+                })
+                .unwrap_or(JsValue::NULL);
+                let result = invoke("arduino_build", args).await;
+
+                let dict = lang.peek().clone();
+                let summary = match result {
+                    Ok(_) if flash => dict.arduino_flashed().to_string(),
+                    Ok(_) => dict.arduino_built().to_string(),
+                    Err(e) => match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    },
+                };
+                output.write().push_str(&format!("\n-- {} --\n", summary));
+                is_running.set(false);
+                task_id.set(None);
+            });
+        }
+    };
+    let mut build_only = build.clone();
+
+    let stop = move |_| {
+        if let Some(id) = *task_id.read() {
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&CancelTaskArgs { task_id: id })
+                    .unwrap_or(JsValue::NULL);
+                invoke("cancel_task", args).await.ok();
+            });
+        }
+    };
+
+    let ready = cli_path.is_some()
+        && !sketch.read().is_empty()
+        && !board.is_empty()
+        && !port_name.is_empty();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            div { style: "font-size: 0.85em; color: var(--md-sys-color-on-surface-variant);",
+                if let Some(path) = &cli_path {
+                    "{dict.arduino_cli_found()} {path}"
+                } else {
+                    "{dict.arduino_cli_missing()}"
+                }
+            }
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: *is_running.read(),
+                    onclick: pick_sketch,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.arduino_btn_pick_sketch()}" }
+                }
+                span { style: "flex: 1; font-size: 0.85em; word-break: break-all;",
+                    if sketch.read().is_empty() {
+                        "{dict.arduino_no_sketch()}"
+                    } else {
+                        "{sketch}"
+                    }
+                }
+            }
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                label { style: "font-size: 0.85em;", "{dict.arduino_fqbn()}" }
+                input {
+                    class: "md-input",
+                    style: "flex: 1; font-family: monospace;",
+                    placeholder: "{detected_fqbn}",
+                    value: "{fqbn}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| fqbn.set(evt.value()),
+                }
+            }
+            div { style: "display: flex; justify-content: flex-end; gap: 8px;",
+                if *is_running.read() {
+                    button {
+                        class: "md-button btn-text",
+                        onclick: stop,
+                        span { class: "material-symbols-outlined icon", "stop" }
+                        span { class: "label", "{dict.arduino_btn_stop()}" }
+                    }
+                } else {
+                    button {
+                        class: "md-button btn-text",
+                        disabled: !ready,
+                        onclick: move |_| build_only(false),
+                        span { class: "material-symbols-outlined icon", "build" }
+                        span { class: "label", "{dict.arduino_btn_build()}" }
+                    }
+                    button {
+                        class: "md-button btn-filled",
+                        disabled: !ready,
+                        onclick: move |_| build(true),
+                        span { class: "material-symbols-outlined icon", "upload" }
+                        span { class: "label", "{dict.arduino_btn_build_flash()}" }
+                    }
+                }
+            }
+            div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.8em; padding: 12px; border-radius: 8px; height: 240px; overflow-y: auto; white-space: pre-wrap; word-wrap: break-word;",
+                if output.read().is_empty() {
+                    span { style: "color: #666;", "{dict.arduino_log_placeholder()}" }
+                }
+                "{output}"
+            }
+        }
+    }
+}
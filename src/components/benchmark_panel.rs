@@ -0,0 +1,324 @@
+use crate::i18n::Dict;
+use crate::settings::{update_settings, Settings};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+const DEFAULT_BAUD_RATES: &str = "115200, 460800, 921600, 1500000, 2000000";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunFlashBenchmarkArgs {
+    port_name: String,
+    baud_rates: Vec<u32>,
+    offset: Option<String>,
+    len: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelTaskArgs {
+    task_id: u64,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskInfo {
+    id: u64,
+    kind: String,
+    state: String,
+    phase: String,
+    current: u64,
+    total: u64,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct BenchmarkRun {
+    baud_rate: u32,
+    write_rate: Option<u64>,
+    read_rate: Option<u64>,
+    verified: bool,
+    error: Option<String>,
+}
+
+fn format_rate(bytes_per_sec: Option<u64>) -> String {
+    match bytes_per_sec {
+        Some(rate) => format!("{:.1} KB/s", rate as f64 / 1024.0),
+        None => "-".to_string(),
+    }
+}
+
+/// Times flash writes and reads on `port_name` at several baud rates, and
+/// offers the fastest one that read back intact as the flashing rate.
+#[component]
+pub fn BenchmarkPanel(port_name: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    let mut baud_rates = use_signal(|| DEFAULT_BAUD_RATES.to_string());
+    let mut size_kb = use_signal(|| "64".to_string());
+    let mut offset = use_signal(String::new);
+    let mut runs = use_signal(|| None::<Vec<BenchmarkRun>>);
+    let mut error = use_signal(|| None::<String>);
+    let mut is_running = use_signal(|| false);
+    let mut task_id = use_signal(|| None::<u64>);
+    let mut progress = use_signal(|| 0.0f64);
+    let mut phase = use_signal(|| "connecting".to_string());
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    // Progress of the run, and its task id so Stop can cancel it
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                let task = e.payload;
+                if task.kind != "benchmark" || task.state != "running" {
+                    return;
+                }
+                task_id.set(Some(task.id));
+                if task.total > 0 {
+                    progress.set(task.current as f64 * 100.0 / task.total as f64);
+                }
+                phase.set(task.phase);
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let run = {
+        let port_name = port_name.clone();
+        move |_| {
+            let port_name = port_name.clone();
+            let rates: Vec<u32> = baud_rates
+                .read()
+                .split(',')
+                .filter_map(|rate| rate.trim().parse().ok())
+                .collect();
+            let len = size_kb.read().trim().parse::<u32>().unwrap_or(64) * 1024;
+            let offset = offset.read().trim().to_string();
+            is_running.set(true);
+            progress.set(0.0);
+            phase.set("connecting".to_string());
+            runs.set(None);
+            error.set(None);
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&RunFlashBenchmarkArgs {
+                    port_name,
+                    baud_rates: rates,
+                    offset: (!offset.is_empty()).then_some(offset),
+                    len,
+                })
+                .unwrap_or(JsValue::NULL);
+                match invoke("run_flash_benchmark", args).await {
+                    Ok(val) => runs.set(serde_wasm_bindgen::from_value(val).ok()),
+                    Err(e) => {
+                        let dict = lang.peek().clone();
+                        let message = match serde_wasm_bindgen::from_value::<AppError>(e) {
+                            Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                            Err(_) => dict.error_internal().to_string(),
+                        };
+                        error.set(Some(message));
+                    }
+                }
+                is_running.set(false);
+                task_id.set(None);
+            });
+        }
+    };
+
+    let stop = move |_| {
+        if let Some(id) = *task_id.read() {
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&CancelTaskArgs { task_id: id })
+                    .unwrap_or(JsValue::NULL);
+                invoke("cancel_task", args).await.ok();
+            });
+        }
+    };
+
+    // The verified run with the highest write rate, as the backend picks it
+    let fastest = runs.read().as_ref().and_then(|runs| {
+        runs.iter()
+            .filter(|run| run.verified)
+            .max_by_key(|run| run.write_rate.unwrap_or_default())
+            .map(|run| run.baud_rate)
+    });
+    let flash_baud = settings.read().flash.baud_rate;
+
+    let use_fastest = move |_| {
+        let Some(rate) = fastest else {
+            return;
+        };
+        let mut updated = settings.peek().clone();
+        updated.flash.baud_rate = rate;
+        error.set(None);
+        spawn(async move {
+            match update_settings(updated).await {
+                Ok(saved) => settings.set(saved),
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    let percent = progress.read().floor() as u64;
+    let phase_label = dict.flash_phase(&phase.read()).to_string();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            span { style: "font-size: 0.85em; color: var(--md-sys-color-on-surface-variant);", "{dict.benchmark_hint()}" }
+            div { style: "display: grid; grid-template-columns: auto 1fr; align-items: center; gap: 8px; font-size: 0.85em;",
+                label { "{dict.benchmark_baud_rates()}" }
+                input {
+                    class: "md-input",
+                    style: "font-family: monospace;",
+                    value: "{baud_rates}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| baud_rates.set(evt.value()),
+                }
+                label { "{dict.benchmark_size()}" }
+                input {
+                    class: "md-input",
+                    r#type: "number",
+                    min: "4",
+                    step: "4",
+                    value: "{size_kb}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| size_kb.set(evt.value()),
+                }
+                label { "{dict.benchmark_offset()}" }
+                input {
+                    class: "md-input",
+                    style: "font-family: monospace;",
+                    placeholder: "{dict.benchmark_offset_placeholder()}",
+                    value: "{offset}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| offset.set(evt.value()),
+                }
+            }
+            div { style: "display: flex; justify-content: flex-end; gap: 8px;",
+                if *is_running.read() {
+                    button {
+                        class: "md-button btn-text",
+                        onclick: stop,
+                        span { class: "material-symbols-outlined icon", "stop" }
+                        span { class: "label", "{dict.benchmark_btn_stop()}" }
+                    }
+                } else {
+                    button {
+                        class: "md-button btn-filled",
+                        disabled: port_name.is_empty(),
+                        onclick: run,
+                        span { class: "material-symbols-outlined icon", "speed" }
+                        span { class: "label", "{dict.benchmark_btn_run()}" }
+                    }
+                }
+            }
+            if *is_running.read() {
+                div { style: "display: flex; flex-direction: column; gap: 4px;",
+                    div { style: "display: flex; justify-content: space-between; font-size: 0.8em;",
+                        span { "{phase_label}" }
+                        span { "{percent}%" }
+                    }
+                    div { style: "height: 4px; background: var(--md-sys-color-surface-container-highest); border-radius: 2px; overflow: hidden;",
+                        div { style: "height: 100%; background: var(--md-sys-color-primary); width: {progress}%; transition: width 0.2s;" }
+                    }
+                }
+            }
+            if let Some(results) = &*runs.read() {
+                div { style: "display: grid; grid-template-columns: auto auto auto 1fr; gap: 4px 16px; font-size: 0.85em;",
+                    span { style: "font-weight: 500;", "{dict.benchmark_col_baud()}" }
+                    span { style: "font-weight: 500;", "{dict.benchmark_col_write()}" }
+                    span { style: "font-weight: 500;", "{dict.benchmark_col_read()}" }
+                    span { style: "font-weight: 500;", "{dict.benchmark_col_result()}" }
+                    for run in results.iter() {
+                        div { key: "{run.baud_rate}", style: "display: contents;",
+                            span { style: "font-family: monospace;", "{run.baud_rate}" }
+                            span { "{format_rate(run.write_rate)}" }
+                            span { "{format_rate(run.read_rate)}" }
+                            span {
+                                style: if run.verified { "color: var(--md-sys-color-primary);" } else { "color: var(--md-sys-color-error); word-break: break-word;" },
+                                if run.verified {
+                                    "{dict.benchmark_ok()}"
+                                } else {
+                                    {run.error.clone().unwrap_or_default()}
+                                }
+                            }
+                        }
+                    }
+                }
+                if let Some(rate) = fastest {
+                    div { style: "display: flex; align-items: center; justify-content: space-between; gap: 8px; font-size: 0.85em;",
+                        span { style: "font-weight: 500;", "{dict.benchmark_fastest()}: {rate}" }
+                        if rate == flash_baud {
+                            span { style: "color: var(--md-sys-color-on-surface-variant);", "{dict.benchmark_in_use()}" }
+                        } else {
+                            button {
+                                class: "md-button btn-tonal",
+                                onclick: use_fastest,
+                                span { class: "material-symbols-outlined icon", "check" }
+                                span { class: "label", "{dict.benchmark_btn_use()}" }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                div { style: "font-size: 0.85em; color: var(--md-sys-color-error); word-break: break-word;",
+                    "{message}"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct ProvDevice {
+    id: String,
+    name: String,
+    rssi: Option<i16>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct ProvOutcome {
+    ip: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct BleProvisionArgs {
+    device: String,
+    security: Option<u8>,
+    pop: String,
+    username: Option<String>,
+    ssid: String,
+    passphrase: String,
+}
+
+// "auto" takes the scheme the device announces
+const SECURITY: [&str; 4] = ["auto", "0", "1", "2"];
+
+/// Scans for boards running Espressif's provisioning manager and hands one
+/// the Wi-Fi network over Bluetooth LE.
+#[component]
+pub fn BleProvPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut devices = use_signal(Vec::<ProvDevice>::new);
+    let mut device = use_signal(String::new);
+    let mut scanning = use_signal(|| false);
+    let mut security = use_signal(|| "auto".to_string());
+    let mut pop = use_signal(String::new);
+    let mut username = use_signal(String::new);
+    let mut ssid = use_signal(String::new);
+    let mut passphrase = use_signal(String::new);
+    let mut busy = use_signal(|| false);
+    let mut outcome = use_signal(|| None::<ProvOutcome>);
+    let mut error = use_signal(|| None::<String>);
+
+    let describe = move |e: JsValue| {
+        let dict = lang.peek().clone();
+        match serde_wasm_bindgen::from_value::<AppError>(e) {
+            Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+            Err(_) => dict.error_internal().to_string(),
+        }
+    };
+
+    let scan = move |_| {
+        scanning.set(true);
+        error.set(None);
+        spawn(async move {
+            match invoke("ble_scan", JsValue::NULL).await {
+                Ok(val) => {
+                    let found: Vec<ProvDevice> =
+                        serde_wasm_bindgen::from_value(val).unwrap_or_default();
+                    let current = device.peek().clone();
+                    if !found.iter().any(|d| d.id == current) {
+                        device.set(found.first().map(|d| d.id.clone()).unwrap_or_default());
+                    }
+                    devices.set(found);
+                }
+                Err(e) => error.set(Some(describe(e))),
+            }
+            scanning.set(false);
+        });
+    };
+
+    let provision = move |_| {
+        let username = username.read().trim().to_string();
+        let args = serde_wasm_bindgen::to_value(&BleProvisionArgs {
+            device: device.read().clone(),
+            security: security.read().parse().ok(),
+            pop: pop.read().clone(),
+            username: Some(username).filter(|u| !u.is_empty()),
+            ssid: ssid.read().clone(),
+            passphrase: passphrase.read().clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        busy.set(true);
+        outcome.set(None);
+        error.set(None);
+        spawn(async move {
+            match invoke("ble_provision", args).await {
+                Ok(val) => outcome.set(serde_wasm_bindgen::from_value(val).ok()),
+                Err(e) => error.set(Some(describe(e))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let ready = !device.read().is_empty()
+        && !ssid.read().trim().is_empty()
+        && !*busy.read()
+        && !*scanning.read();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.ble_prov_hint()}" }
+            div { style: "display: flex; gap: 8px; align-items: center;",
+                select {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    disabled: devices.read().is_empty(),
+                    value: "{device}",
+                    onchange: move |evt| device.set(evt.value()),
+                    if devices.read().is_empty() {
+                        option { value: "", "{dict.ble_prov_no_devices()}" }
+                    }
+                    for d in devices.read().iter() {
+                        {
+                            let label = match d.rssi {
+                                Some(rssi) => format!("{} ({} dBm)", d.name, rssi),
+                                None => d.name.clone(),
+                            };
+                            rsx! {
+                                option { key: "{d.id}", value: "{d.id}", "{label}" }
+                            }
+                        }
+                    }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: *scanning.read() || *busy.read(),
+                    onclick: scan,
+                    span { class: "material-symbols-outlined icon", "bluetooth_searching" }
+                    span { class: "label",
+                        if *scanning.read() { "{dict.ble_prov_scanning()}" } else { "{dict.ble_prov_btn_scan()}" }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 8px; align-items: center;",
+                span { style: "font-size: 0.9em;", "{dict.ble_prov_label_security()}" }
+                select {
+                    class: "md-input",
+                    style: "width: 140px;",
+                    value: "{security}",
+                    onchange: move |evt| security.set(evt.value()),
+                    for s in SECURITY {
+                        option { key: "{s}", value: "{s}", "{dict.ble_prov_security(s)}" }
+                    }
+                }
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    r#type: "password",
+                    placeholder: "{dict.ble_prov_pop_placeholder()}",
+                    value: "{pop}",
+                    oninput: move |evt| pop.set(evt.value()),
+                }
+            }
+            if *security.read() == "2" {
+                input {
+                    class: "md-input",
+                    placeholder: "{dict.ble_prov_username_placeholder()}",
+                    value: "{username}",
+                    oninput: move |evt| username.set(evt.value()),
+                }
+            }
+            input {
+                class: "md-input",
+                placeholder: "{dict.improv_ssid_placeholder()}",
+                value: "{ssid}",
+                oninput: move |evt| ssid.set(evt.value()),
+            }
+            input {
+                class: "md-input",
+                r#type: "password",
+                placeholder: "{dict.improv_password_placeholder()}",
+                value: "{passphrase}",
+                oninput: move |evt| passphrase.set(evt.value()),
+            }
+            if *busy.read() {
+                span { style: "font-size: 0.9em;", "{dict.ble_prov_provisioning()}" }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(result) = &*outcome.read() {
+                span { style: "font-size: 0.9em;",
+                    match &result.ip {
+                        Some(ip) => format!("{} {}", dict.ble_prov_provisioned(), ip),
+                        None => dict.ble_prov_provisioned().to_string(),
+                    }
+                }
+            }
+            div { style: "display: flex; justify-content: flex-end;",
+                button {
+                    class: "md-button btn-filled",
+                    disabled: !ready,
+                    onclick: provision,
+                    span { class: "material-symbols-outlined icon", "bluetooth" }
+                    span { class: "label", "{dict.improv_btn_send()}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplayCaptureArgs {
+    path: Option<String>,
+    target: String,
+    speed: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelTaskArgs {
+    task_id: u64,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    id: u64,
+    kind: String,
+    state: String,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Records the raw traffic of the monitor to a file, and plays a recording
+/// back into the monitor view or out of the port, e.g. one sent in with a
+/// bug report from the field.
+#[component]
+pub fn CapturePanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut recording = use_signal(|| None::<String>);
+    let mut target = use_signal(|| "monitor".to_string());
+    let mut speed = use_signal(|| "1".to_string());
+    let mut task_id = use_signal(|| None::<u64>);
+    let mut is_replaying = use_signal(|| false);
+    let mut message = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<String>);
+
+    // A capture keeps running while the tab is closed
+    use_effect(move || {
+        spawn(async move {
+            if let Ok(val) = invoke("monitor_capture_path", JsValue::NULL).await {
+                if let Ok(path) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    recording.set(path);
+                }
+            }
+        });
+    });
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    // Remember the task id so Stop can cancel the replay
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    if e.payload.kind == "replay" && e.payload.state == "running" {
+                        task_id.set(Some(e.payload.id));
+                    }
+                }
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let toggle_recording = move |_| {
+        message.set(None);
+        error.set(None);
+        let running = recording.read().is_some();
+        spawn(async move {
+            let command = if running {
+                "monitor_stop_capture"
+            } else {
+                "monitor_start_capture"
+            };
+            match invoke(command, JsValue::NULL).await {
+                Ok(val) => {
+                    let path = serde_wasm_bindgen::from_value::<Option<String>>(val)
+                        .ok()
+                        .flatten();
+                    if running {
+                        recording.set(None);
+                        if let Some(path) = path {
+                            message.set(Some(format!("{} {}", lang.peek().capture_saved(), path)));
+                        }
+                    } else if path.is_some() {
+                        recording.set(path);
+                    }
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let replay = move |_| {
+        message.set(None);
+        error.set(None);
+        is_replaying.set(true);
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&ReplayCaptureArgs {
+                path: None,
+                target: target.read().clone(),
+                speed: speed.read().parse().unwrap_or(1.0),
+            })
+            .unwrap_or(JsValue::NULL);
+            match invoke("replay_capture", args).await {
+                Ok(val) => {
+                    if let Ok(Some(count)) = serde_wasm_bindgen::from_value::<Option<usize>>(val) {
+                        message.set(Some(format!(
+                            "{} {}",
+                            lang.peek().capture_replayed(),
+                            count
+                        )));
+                    }
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+            is_replaying.set(false);
+            task_id.set(None);
+        });
+    };
+
+    let stop = move |_| {
+        if let Some(id) = *task_id.read() {
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&CancelTaskArgs { task_id: id })
+                    .unwrap_or(JsValue::NULL);
+                invoke("cancel_task", args).await.ok();
+            });
+        }
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                button {
+                    class: if recording.read().is_some() { "md-button btn-tonal" } else { "md-button btn-text" },
+                    onclick: toggle_recording,
+                    span { class: "material-symbols-outlined icon",
+                        if recording.read().is_some() { "stop_circle" } else { "fiber_manual_record" }
+                    }
+                    span { class: "label",
+                        if recording.read().is_some() {
+                            "{dict.capture_btn_stop()}"
+                        } else {
+                            "{dict.capture_btn_record()}"
+                        }
+                    }
+                }
+                if let Some(path) = &*recording.read() {
+                    span { style: "font-size: 0.9em; color: var(--md-sys-color-error); word-break: break-all;",
+                        "{dict.capture_recording()} {path}"
+                    }
+                }
+            }
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                select {
+                    class: "md-select",
+                    title: "{dict.capture_target_hint()}",
+                    value: "{target}",
+                    disabled: *is_replaying.read(),
+                    onchange: move |evt| target.set(evt.value()),
+                    option { value: "monitor", "{dict.capture_target_monitor()}" }
+                    option { value: "port", "{dict.capture_target_port()}" }
+                }
+                select {
+                    class: "md-select",
+                    title: "{dict.capture_speed()}",
+                    value: "{speed}",
+                    disabled: *is_replaying.read(),
+                    onchange: move |evt| speed.set(evt.value()),
+                    option { value: "1", "1×" }
+                    option { value: "2", "2×" }
+                    option { value: "4", "4×" }
+                    option { value: "10", "10×" }
+                }
+                if *is_replaying.read() {
+                    button {
+                        class: "md-button btn-tonal",
+                        onclick: stop,
+                        span { class: "material-symbols-outlined icon", "stop" }
+                        span { class: "label", "{dict.capture_btn_stop_replay()}" }
+                    }
+                } else {
+                    button {
+                        class: "md-button btn-text",
+                        onclick: replay,
+                        span { class: "material-symbols-outlined icon", "replay" }
+                        span { class: "label", "{dict.capture_btn_replay()}" }
+                    }
+                }
+            }
+            if let Some(text) = &*message.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-primary); word-break: break-all;", "{text}" }
+            }
+            if let Some(text) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{text}" }
+            }
+        }
+    }
+}
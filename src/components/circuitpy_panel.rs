@@ -0,0 +1,212 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct CircuitPyDrive {
+    path: String,
+    version: String,
+    board: String,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CircuitpyFindArgs {
+    serial_number: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CircuitpySyncArgs {
+    project_dir: String,
+    drive_path: String,
+}
+
+fn describe_error(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Copies a CircuitPython project onto the board's CIRCUITPY drive, once
+/// or on every save. These boards take code as files, not over serial.
+#[component]
+pub fn CircuitPyPanel(serial_number: Option<String>) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut drive = use_signal(|| None::<CircuitPyDrive>);
+    let mut searched = use_signal(|| false);
+    let mut project = use_signal(String::new);
+    let mut watching = use_signal(|| false);
+    let mut busy = use_signal(|| false);
+    let mut status = use_signal(String::new);
+
+    // A watch only lives as long as the panel showing it
+    use_drop(move || {
+        if *watching.peek() {
+            spawn(async move {
+                invoke("circuitpy_unwatch", JsValue::NULL).await.ok();
+            });
+        }
+    });
+
+    let find = move |_| {
+        let args = serde_wasm_bindgen::to_value(&CircuitpyFindArgs {
+            serial_number: serial_number.clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        busy.set(true);
+        status.set(String::new());
+        spawn(async move {
+            match invoke("circuitpy_find", args).await {
+                Ok(val) => drive.set(
+                    serde_wasm_bindgen::from_value::<Option<CircuitPyDrive>>(val)
+                        .ok()
+                        .flatten(),
+                ),
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+            searched.set(true);
+            busy.set(false);
+        });
+    };
+
+    let choose_project = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("circuitpy_pick_project", JsValue::NULL).await {
+                if let Ok(Some(dir)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    project.set(dir);
+                }
+            }
+        });
+    };
+
+    let sync_args = move || {
+        let drive_path = drive.peek().as_ref()?.path.clone();
+        serde_wasm_bindgen::to_value(&CircuitpySyncArgs {
+            project_dir: project.peek().clone(),
+            drive_path,
+        })
+        .ok()
+    };
+
+    let copy_now = move |_| {
+        let Some(args) = sync_args() else {
+            return;
+        };
+        busy.set(true);
+        spawn(async move {
+            match invoke("circuitpy_sync", args).await {
+                Ok(val) => {
+                    let copied =
+                        serde_wasm_bindgen::from_value::<Vec<String>>(val).unwrap_or_default();
+                    let dict = lang.peek().clone();
+                    status.set(if copied.is_empty() {
+                        dict.circuitpy_up_to_date().to_string()
+                    } else {
+                        format!("{} {}", dict.circuitpy_copied(), copied.join(", "))
+                    });
+                }
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+            busy.set(false);
+        });
+    };
+
+    let toggle_watch = move |_| {
+        if *watching.peek() {
+            spawn(async move {
+                invoke("circuitpy_unwatch", JsValue::NULL).await.ok();
+                watching.set(false);
+            });
+            return;
+        }
+        let Some(args) = sync_args() else {
+            return;
+        };
+        spawn(async move {
+            match invoke("circuitpy_watch", args).await {
+                Ok(_) => {
+                    watching.set(true);
+                    status.set(lang.peek().circuitpy_watching().to_string());
+                }
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+        });
+    };
+
+    let found = drive.read().clone();
+    let ready = found.is_some() && !project.read().is_empty() && !*busy.read();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                span { style: "flex: 1; font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                    if let Some(found) = &found {
+                        "CircuitPython {found.version} · {found.board} · {found.path}"
+                    } else if *searched.read() {
+                        "{dict.circuitpy_no_drive()}"
+                    } else {
+                        "{dict.circuitpy_hint()}"
+                    }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: *busy.read() || *watching.read(),
+                    onclick: find,
+                    span { class: "material-symbols-outlined icon", "usb" }
+                    span { class: "label", "{dict.circuitpy_btn_find()}" }
+                }
+            }
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                span { style: "flex: 1; font-family: monospace; overflow: hidden; text-overflow: ellipsis;",
+                    if project.read().is_empty() { "{dict.circuitpy_no_project()}" } else { "{project}" }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: *watching.read(),
+                    onclick: choose_project,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+            }
+            if !status.read().is_empty() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{status}" }
+            }
+            div { style: "display: flex; justify-content: flex-end; gap: 8px;",
+                button {
+                    class: if *watching.read() { "md-button btn-tonal" } else { "md-button btn-text" },
+                    disabled: !ready && !*watching.read(),
+                    onclick: toggle_watch,
+                    span { class: "material-symbols-outlined icon", "sync" }
+                    span { class: "label",
+                        if *watching.read() { "{dict.circuitpy_btn_unwatch()}" } else { "{dict.circuitpy_btn_watch()}" }
+                    }
+                }
+                button {
+                    class: "md-button btn-filled",
+                    disabled: !ready || *watching.read(),
+                    onclick: copy_now,
+                    span { class: "material-symbols-outlined icon", "drive_file_move" }
+                    span { class: "label", "{dict.circuitpy_btn_copy()}" }
+                }
+            }
+        }
+    }
+}
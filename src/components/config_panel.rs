@@ -0,0 +1,95 @@
+use crate::i18n::Dict;
+use crate::settings::Settings;
+use dioxus::prelude::*;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Saves the whole configuration to a file, or takes it over from one
+/// exported on another bench.
+#[component]
+pub fn ConfigPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut message = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<String>);
+
+    let export = move |_| {
+        message.set(None);
+        error.set(None);
+        spawn(async move {
+            match invoke("export_config", JsValue::NULL).await {
+                Ok(val) => {
+                    if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                        message.set(Some(format!("{} {}", lang.peek().config_saved(), path)));
+                    }
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let import = move |_| {
+        message.set(None);
+        error.set(None);
+        spawn(async move {
+            match invoke("import_config", JsValue::NULL).await {
+                Ok(val) => {
+                    if let Ok(Some(_)) = serde_wasm_bindgen::from_value::<Option<Settings>>(val) {
+                        // Theme and language are applied at startup, like
+                        // the rest of the settings
+                        if let Some(window) = web_sys::window() {
+                            let _ = window.location().reload();
+                        }
+                    }
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.config_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                button {
+                    class: "md-button btn-tonal",
+                    onclick: export,
+                    span { class: "material-symbols-outlined icon", "download" }
+                    span { class: "label", "{dict.config_btn_export()}" }
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: import,
+                    span { class: "material-symbols-outlined icon", "upload" }
+                    span { class: "label", "{dict.config_btn_import()}" }
+                }
+            }
+            if let Some(text) = &*message.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-primary); word-break: break-all;", "{text}" }
+            }
+            if let Some(text) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{text}" }
+            }
+        }
+    }
+}
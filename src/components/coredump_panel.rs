@@ -0,0 +1,279 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct CoreRegister {
+    name: String,
+    value: u32,
+    meaning: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct CoreFrame {
+    address: u32,
+    function: Option<String>,
+    guessed: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct CoreTask {
+    handle: u32,
+    name: String,
+    crashed: bool,
+    registers: Vec<CoreRegister>,
+    frames: Vec<CoreFrame>,
+    stack_used: Option<u32>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct MemoryRegion {
+    size: u32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct CoreDumpReport {
+    arch: String,
+    panic_reason: Option<String>,
+    elf_matches: Option<bool>,
+    exception: Vec<CoreRegister>,
+    tasks: Vec<CoreTask>,
+    regions: Vec<MemoryRegion>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CoreDumpArgs {
+    elf_path: String,
+    port_name: Option<String>,
+    path: Option<String>,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+fn hex(value: u32) -> String {
+    format!("{:#010x}", value)
+}
+
+fn function_name(frame: &CoreFrame) -> &str {
+    frame.function.as_deref().unwrap_or("??")
+}
+
+/// Turns a core dump, from the monitor's output, the board's coredump
+/// partition or a saved file, into a crash report against the app's ELF:
+/// the crashed task, every task's backtrace and registers.
+#[component]
+pub fn CoreDumpPanel(port_name: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut elf_path = use_signal(String::new);
+    let mut report = use_signal(|| None::<CoreDumpReport>);
+    let mut expanded = use_signal(|| None::<u32>);
+    let mut busy = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    let browse = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_elf_file", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    elf_path.set(path);
+                }
+            }
+        });
+    };
+
+    let mut analyze = move |command: &'static str, port_name, path| {
+        let args = serde_wasm_bindgen::to_value(&CoreDumpArgs {
+            elf_path: elf_path.read().trim().to_string(),
+            port_name,
+            path,
+        })
+        .unwrap_or(JsValue::NULL);
+        busy.set(true);
+        error.set(None);
+        spawn(async move {
+            match invoke(command, args).await {
+                Ok(val) => {
+                    let parsed: Option<CoreDumpReport> = serde_wasm_bindgen::from_value(val).ok();
+                    // The crashed task's registers open
+                    expanded.set(
+                        parsed
+                            .as_ref()
+                            .and_then(|r| r.tasks.first())
+                            .map(|t| t.handle),
+                    );
+                    report.set(parsed);
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let flash_port = port_name.clone();
+    let from_file = move |_| {
+        spawn(async move {
+            let Ok(val) = invoke("pick_coredump_file", JsValue::NULL).await else {
+                return;
+            };
+            if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                analyze("coredump_from_file", None, Some(path));
+            }
+        });
+    };
+
+    let can_analyze = !elf_path.read().trim().is_empty() && !*busy.read();
+    let current = report.read().clone();
+    let dumped: u64 = current
+        .as_ref()
+        .map_or(0, |r| r.regions.iter().map(|m| u64::from(m.size)).sum());
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.coredump_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    placeholder: "{dict.gdb_placeholder_elf()}",
+                    value: "{elf_path}",
+                    oninput: move |evt| elf_path.set(evt.value()),
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: browse,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+            }
+            div { style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap;",
+                button {
+                    class: "md-button btn-filled",
+                    disabled: !can_analyze,
+                    title: "{dict.coredump_monitor_hint()}",
+                    onclick: move |_| analyze("coredump_from_monitor", None, None),
+                    span { class: "material-symbols-outlined icon", "terminal" }
+                    span { class: "label", "{dict.coredump_btn_monitor()}" }
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: !can_analyze || port_name.is_empty(),
+                    onclick: move |_| analyze("coredump_from_flash", Some(flash_port.clone()), None),
+                    span { class: "material-symbols-outlined icon", "memory" }
+                    span { class: "label", "{dict.coredump_btn_flash()}" }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: !can_analyze,
+                    onclick: from_file,
+                    span { class: "material-symbols-outlined icon", "upload_file" }
+                    span { class: "label", "{dict.coredump_btn_file()}" }
+                }
+                if *busy.read() {
+                    span { style: "color: var(--md-sys-color-outline);", "{dict.coredump_reading()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(r) = &current {
+                if r.elf_matches == Some(false) {
+                    span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{dict.coredump_elf_mismatch()}" }
+                }
+                if let Some(reason) = &r.panic_reason {
+                    span { style: "font-weight: 500;", "{reason}" }
+                }
+                div { style: "display: flex; flex-direction: column; gap: 8px; max-height: 480px; overflow-y: auto; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em;",
+                    if !r.exception.is_empty() {
+                        div {
+                            div { style: "color: var(--md-sys-color-outline);", "{dict.coredump_exception()}" }
+                            for register in r.exception.iter() {
+                                div { key: "{register.name}", style: "display: flex; gap: 12px;",
+                                    span { style: "width: 96px;", "{register.name}" }
+                                    span { style: "color: var(--md-sys-color-primary);", "{hex(register.value)}" }
+                                    if let Some(meaning) = &register.meaning {
+                                        span { style: "color: var(--md-sys-color-on-surface-variant);", "{meaning}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    for task in r.tasks.iter() {
+                        {
+                            let handle = task.handle;
+                            let open = *expanded.read() == Some(handle);
+                            rsx! {
+                                div { key: "{handle}",
+                                    div {
+                                        style: "display: flex; gap: 12px; cursor: pointer; padding: 2px 0;",
+                                        onclick: move |_| {
+                                            let next = if open { None } else { Some(handle) };
+                                            expanded.set(next);
+                                        },
+                                        span {
+                                            style: if task.crashed { "font-weight: 600; color: var(--md-sys-color-error);" } else { "font-weight: 600;" },
+                                            "{task.name}"
+                                        }
+                                        span { style: "color: var(--md-sys-color-outline);", "{hex(handle)}" }
+                                        if task.crashed {
+                                            span { style: "color: var(--md-sys-color-error);", "{dict.coredump_crashed()}" }
+                                        }
+                                        if let Some(used) = task.stack_used {
+                                            span { style: "color: var(--md-sys-color-on-surface-variant);", "{dict.coredump_stack_used()} {used} B" }
+                                        }
+                                    }
+                                    for (i, frame) in task.frames.iter().enumerate() {
+                                        div {
+                                            key: "{i}",
+                                            style: if frame.guessed { "display: flex; gap: 12px; padding-left: 12px; color: var(--md-sys-color-outline);" } else { "display: flex; gap: 12px; padding-left: 12px;" },
+                                            title: if frame.guessed { "{dict.coredump_guessed()}" } else { "" },
+                                            span { style: "width: 28px;", "#{i}" }
+                                            span { style: "width: 96px;", "{hex(frame.address)}" }
+                                            span { "{function_name(frame)}" }
+                                        }
+                                    }
+                                    if open {
+                                        div { style: "display: grid; grid-template-columns: repeat(auto-fill, minmax(180px, 1fr)); gap: 2px 12px; padding: 4px 0 0 12px; color: var(--md-sys-color-on-surface-variant);",
+                                            for register in task.registers.iter() {
+                                                span { key: "{register.name}",
+                                                    title: register.meaning.clone().unwrap_or_default(),
+                                                    "{register.name} {hex(register.value)}"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                    "{r.arch} · {dict.coredump_regions()} {r.regions.len()} · {dumped} B"
+                }
+            } else if !*busy.read() {
+                span { style: "color: var(--md-sys-color-outline);", "{dict.coredump_empty()}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct GetRecentLogsArgs {
+    limit: Option<usize>,
+}
+
+#[component]
+pub fn DebugLogPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut lines = use_signal(Vec::<String>::new);
+    let mut is_collecting = use_signal(|| false);
+    let mut bundle_msg = use_signal(String::new);
+
+    let refresh = move || {
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&GetRecentLogsArgs { limit: Some(500) })
+                .unwrap_or(JsValue::NULL);
+            match invoke("get_recent_logs", args).await {
+                Ok(res) => {
+                    if let Ok(fetched) = serde_wasm_bindgen::from_value::<Vec<String>>(res) {
+                        lines.set(fetched);
+                    }
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    };
+
+    let collect = move |_| {
+        is_collecting.set(true);
+        bundle_msg.set(String::new());
+        spawn(async move {
+            let dict = lang.peek().clone();
+            match invoke("collect_diagnostics", JsValue::NULL).await {
+                Ok(res) => {
+                    // None means the save dialog was cancelled
+                    if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(res) {
+                        bundle_msg.set(format!("{} {}", dict.debug_bundle_saved(), path));
+                    }
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e);
+                    bundle_msg.set(dict.debug_bundle_failed().to_string());
+                }
+            }
+            is_collecting.set(false);
+        });
+    };
+
+    // Load once when the panel opens
+    use_effect(refresh);
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            div { style: "display: flex; justify-content: flex-end; align-items: center; gap: 8px;",
+                if !bundle_msg.read().is_empty() {
+                    span { style: "flex: 1; font-size: 0.8em; color: var(--md-sys-color-primary); word-break: break-all;",
+                        "{bundle_msg}"
+                    }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: *is_collecting.read(),
+                    onclick: collect,
+                    span { class: "material-symbols-outlined icon", "folder_zip" }
+                    span { class: "label", "{dict.debug_btn_collect()}" }
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: move |_| refresh(),
+                    span { class: "material-symbols-outlined icon", "refresh" }
+                    span { class: "label", "{dict.debug_btn_refresh()}" }
+                }
+            }
+            div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.8em; padding: 12px; border-radius: 8px; height: 400px; overflow-y: auto; white-space: pre-wrap; word-wrap: break-word;",
+                if lines.read().is_empty() {
+                    span { style: "color: #666;", "{dict.devices_log_placeholder()}" }
+                }
+                for line in lines.read().iter() {
+                    div { "{line}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use crate::i18n::Dict;
+use crate::settings::{update_settings, Settings};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// Rows shown; the CSV export has them all
+const SHOWN: u32 = 200;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct DeviceRecord {
+    id: i64,
+    at: u64,
+    action: String,
+    mac_address: Option<String>,
+    serial_number: Option<String>,
+    chip_model: Option<String>,
+    firmware_version: Option<String>,
+    checksum: Option<String>,
+    operator: String,
+    passed: bool,
+    detail: String,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct RecordQuery {
+    device: Option<String>,
+    passed: Option<bool>,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct QueryArgs {
+    query: RecordQuery,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in local time.
+fn local_time(secs: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(secs as f64 * 1000.0));
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        date.get_full_year(),
+        date.get_month() + 1,
+        date.get_date(),
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds()
+    )
+}
+
+/// The device database: who flashed or provisioned which board, with what
+/// firmware and whether it worked, searchable and exportable as CSV. The
+/// operator entered here is written with every new record.
+#[component]
+pub fn DeviceRecordsPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    let mut operator = use_signal(|| settings.peek().operator.clone());
+    let mut device = use_signal(String::new);
+    // "", "pass" or "fail"
+    let mut result = use_signal(String::new);
+    let mut records = use_signal(Vec::<DeviceRecord>::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut exported = use_signal(|| None::<String>);
+
+    let query = move || RecordQuery {
+        device: Some(device.peek().trim().to_string()).filter(|d| !d.is_empty()),
+        passed: match result.peek().as_str() {
+            "pass" => Some(true),
+            "fail" => Some(false),
+            _ => None,
+        },
+        limit: Some(SHOWN),
+    };
+
+    let load = move || {
+        let args =
+            serde_wasm_bindgen::to_value(&QueryArgs { query: query() }).unwrap_or(JsValue::NULL);
+        spawn(async move {
+            match invoke("device_records", args).await {
+                Ok(val) => {
+                    if let Ok(found) = serde_wasm_bindgen::from_value(val) {
+                        records.set(found);
+                    }
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    use_effect(load);
+
+    let save_operator = move || {
+        let mut updated = settings.peek().clone();
+        updated.operator = operator.peek().trim().to_string();
+        if updated == *settings.peek() {
+            return;
+        }
+        spawn(async move {
+            match update_settings(updated).await {
+                Ok(saved) => settings.set(saved),
+                Err(e) => {
+                    operator.set(settings.peek().operator.clone());
+                    error.set(Some(error_text(&lang.peek(), e)));
+                }
+            }
+        });
+    };
+
+    let export = move |_| {
+        let query = RecordQuery {
+            limit: None,
+            ..query()
+        };
+        let args = serde_wasm_bindgen::to_value(&QueryArgs { query }).unwrap_or(JsValue::NULL);
+        exported.set(None);
+        spawn(async move {
+            match invoke("export_device_records", args).await {
+                // `None` when the file dialog was cancelled
+                Ok(val) => exported.set(serde_wasm_bindgen::from_value(val).unwrap_or(None)),
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.device_db_hint()}" }
+            div { style: "display: flex; gap: 8px; align-items: center; font-size: 0.9em;",
+                label { "{dict.device_db_operator()}" }
+                input {
+                    class: "md-input",
+                    style: "width: 200px;",
+                    value: "{operator}",
+                    oninput: move |evt| operator.set(evt.value()),
+                    onchange: move |_| save_operator(),
+                }
+            }
+            div { style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1; min-width: 160px;",
+                    placeholder: "{dict.device_db_search()}",
+                    value: "{device}",
+                    oninput: move |evt| device.set(evt.value()),
+                    onchange: move |_| load(),
+                }
+                select {
+                    class: "md-select",
+                    value: "{result}",
+                    onchange: move |evt| {
+                        result.set(evt.value());
+                        load();
+                    },
+                    option { value: "", "{dict.device_db_result_all()}" }
+                    option { value: "pass", "{dict.device_db_result_pass()}" }
+                    option { value: "fail", "{dict.device_db_result_fail()}" }
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: move |_| load(),
+                    span { class: "material-symbols-outlined icon", "refresh" }
+                    span { class: "label", "{dict.device_db_btn_refresh()}" }
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    onclick: export,
+                    span { class: "material-symbols-outlined icon", "download" }
+                    span { class: "label", "{dict.device_db_btn_export()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(path) = &*exported.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant); word-break: break-all;",
+                    "{dict.device_db_exported()} {path}"
+                }
+            }
+            div { style: "max-height: 320px; overflow-y: auto;",
+                if records.read().is_empty() {
+                    span { style: "color: var(--md-sys-color-outline);", "{dict.device_db_empty()}" }
+                } else {
+                    table { style: "width: 100%; border-collapse: collapse; font-size: 0.85em;",
+                        thead {
+                            tr { style: "text-align: left; color: var(--md-sys-color-on-surface-variant);",
+                                th { "{dict.device_db_col_time()}" }
+                                th { "{dict.device_db_col_action()}" }
+                                th { "{dict.device_db_col_device()}" }
+                                th { "{dict.device_db_col_firmware()}" }
+                                th { "{dict.device_db_col_operator()}" }
+                                th { "{dict.device_db_col_result()}" }
+                            }
+                        }
+                        tbody { style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;",
+                            for record in records.read().iter() {
+                                {
+                                    let board = record
+                                        .mac_address
+                                        .clone()
+                                        .or_else(|| record.serial_number.clone())
+                                        .unwrap_or_else(|| "-".to_string());
+                                    let board_title = [
+                                        record.chip_model.as_deref(),
+                                        record.serial_number.as_deref(),
+                                    ]
+                                    .into_iter()
+                                    .flatten()
+                                    .collect::<Vec<_>>()
+                                    .join(" · ");
+                                    let firmware = record.firmware_version.as_deref().unwrap_or("-");
+                                    rsx! {
+                                        tr { key: "{record.id}", title: "{record.detail}",
+                                            td { "{local_time(record.at)}" }
+                                            td { "{record.action}" }
+                                            td { title: "{board_title}", "{board}" }
+                                            td { title: record.checksum.clone().unwrap_or_default(),
+                                                "{firmware}"
+                                            }
+                                            td { "{record.operator}" }
+                                            td {
+                                                style: if record.passed { "color: var(--md-sys-color-primary);" } else { "color: var(--md-sys-color-error);" },
+                                                if record.passed { "{dict.device_db_result_pass()}" } else { "{dict.device_db_result_fail()}" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+const CHIPS: [&str; 10] = [
+    "esp32", "esp32s2", "esp32s3", "esp32c2", "esp32c3", "esp32c5", "esp32c6", "esp32c61",
+    "esp32h2", "esp32p4",
+];
+const FLASH_MODES: [&str; 4] = ["qio", "qout", "dio", "dout"];
+const FLASH_SIZES: [&str; 8] = ["1MB", "2MB", "4MB", "8MB", "16MB", "32MB", "64MB", "128MB"];
+/// ESP-IDF releases the backend bundles bootloaders from, newest first.
+const IDF_VERSIONS: [&str; 2] = ["v5.3", "v5.1"];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageOptions {
+    chip: String,
+    flash_mode: String,
+    flash_size: String,
+    flash_freq: String,
+}
+
+#[derive(Serialize)]
+struct Elf2imagePickArgs {
+    options: ImageOptions,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AssembleFullImageArgs {
+    options: ImageOptions,
+    idf_version: String,
+    partitions_path: Option<String>,
+    bootloader_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+/// Flash frequencies `chip` supports, fastest first, as the backend's
+/// `image_info::flash_freqs` has them; the second is the usual default.
+fn flash_freqs(chip: &str) -> &'static [&'static str] {
+    match chip {
+        "esp32c2" => &["60m", "30m", "20m", "15m"],
+        "esp32h2" => &["48m", "24m", "16m", "12m"],
+        "esp32c5" | "esp32c6" | "esp32c61" | "esp32p4" => &["80m", "40m", "20m"],
+        _ => &["80m", "40m", "26m", "20m"],
+    }
+}
+
+/// Converts an ELF the user picks into a bootable image for a chosen chip
+/// and flash parameters, or into a full flash image with bootloader and
+/// partition table, and hands the image's path and flash offset to
+/// `on_converted`.
+#[component]
+pub fn ElfImagePanel(
+    chip_model: Option<String>,
+    on_converted: EventHandler<(String, String)>,
+) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    // The connected chip, in espflash's spelling, when there is one
+    let detected = chip_model
+        .map(|model| model.to_ascii_lowercase().replace('-', ""))
+        .filter(|model| CHIPS.contains(&model.as_str()));
+    let initial = detected.unwrap_or_else(|| "esp32".to_string());
+    let mut flash_freq = use_signal(|| flash_freqs(&initial)[1].to_string());
+    let mut chip = use_signal(|| initial);
+    let mut flash_mode = use_signal(|| "dio".to_string());
+    let mut flash_size = use_signal(|| "4MB".to_string());
+    let mut full_image = use_signal(|| false);
+    let mut idf_version = use_signal(|| IDF_VERSIONS[0].to_string());
+    let mut partitions_path = use_signal(String::new);
+    let mut bootloader_path = use_signal(String::new);
+    let mut is_converting = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    let convert = move |_| {
+        let options = ImageOptions {
+            chip: chip.peek().clone(),
+            flash_mode: flash_mode.peek().clone(),
+            flash_size: flash_size.peek().clone(),
+            flash_freq: flash_freq.peek().clone(),
+        };
+        let optional = |path: String| {
+            let path = path.trim().to_string();
+            (!path.is_empty()).then_some(path)
+        };
+        // A full image goes at the start of flash, a lone app at the usual
+        // app offset
+        let (command, address, args) = if *full_image.peek() {
+            let args = AssembleFullImageArgs {
+                options,
+                idf_version: idf_version.peek().clone(),
+                partitions_path: optional(partitions_path.peek().clone()),
+                bootloader_path: optional(bootloader_path.peek().clone()),
+            };
+            (
+                "assemble_full_image",
+                "0x0",
+                serde_wasm_bindgen::to_value(&args),
+            )
+        } else {
+            let args = Elf2imagePickArgs { options };
+            (
+                "elf2image_pick",
+                "0x10000",
+                serde_wasm_bindgen::to_value(&args),
+            )
+        };
+        is_converting.set(true);
+        error.set(None);
+        spawn(async move {
+            match invoke(command, args.unwrap_or(JsValue::NULL)).await {
+                Ok(val) => {
+                    if let Some(path) = val.as_string() {
+                        on_converted.call((path, address.to_string()));
+                    }
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+            is_converting.set(false);
+        });
+    };
+
+    let freqs = flash_freqs(&chip.read());
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px; padding: 8px; border-radius: 8px; background: var(--md-sys-color-surface-container);",
+            span { style: "font-size: 0.85em; color: var(--md-sys-color-on-surface-variant);", "{dict.elf_image_hint()}" }
+            div { style: "display: grid; grid-template-columns: auto 1fr auto 1fr; align-items: center; gap: 8px; font-size: 0.85em;",
+                label { "{dict.elf_image_chip()}" }
+                select {
+                    class: "md-input",
+                    value: "{chip}",
+                    onchange: move |evt| {
+                        let value = evt.value();
+                        // Keep the frequency valid for the new chip
+                        if !flash_freqs(&value).contains(&flash_freq.peek().as_str()) {
+                            flash_freq.set(flash_freqs(&value)[1].to_string());
+                        }
+                        chip.set(value);
+                    },
+                    for name in CHIPS {
+                        option { key: "{name}", value: "{name}", "{name}" }
+                    }
+                }
+                label { "{dict.elf_image_flash_mode()}" }
+                select {
+                    class: "md-input",
+                    value: "{flash_mode}",
+                    onchange: move |evt| flash_mode.set(evt.value()),
+                    for mode in FLASH_MODES {
+                        option { key: "{mode}", value: "{mode}", "{mode}" }
+                    }
+                }
+                label { "{dict.elf_image_flash_size()}" }
+                select {
+                    class: "md-input",
+                    value: "{flash_size}",
+                    onchange: move |evt| flash_size.set(evt.value()),
+                    for size in FLASH_SIZES {
+                        option { key: "{size}", value: "{size}", "{size}" }
+                    }
+                }
+                label { "{dict.elf_image_flash_freq()}" }
+                select {
+                    class: "md-input",
+                    value: "{flash_freq}",
+                    onchange: move |evt| flash_freq.set(evt.value()),
+                    for freq in freqs.iter() {
+                        option { key: "{freq}", value: "{freq}", "{freq}" }
+                    }
+                }
+            }
+            label { style: "display: flex; align-items: center; gap: 8px; font-size: 0.85em;",
+                input {
+                    r#type: "checkbox",
+                    checked: *full_image.read(),
+                    onchange: move |evt| full_image.set(evt.checked()),
+                }
+                "{dict.elf_image_full()}"
+            }
+            if *full_image.read() {
+                div { style: "display: grid; grid-template-columns: auto 1fr; align-items: center; gap: 8px; font-size: 0.85em;",
+                    label { "{dict.elf_image_partitions()}" }
+                    input {
+                        class: "md-input",
+                        placeholder: "{dict.elf_image_partitions_placeholder()}",
+                        value: "{partitions_path}",
+                        oninput: move |evt| partitions_path.set(evt.value()),
+                    }
+                    label { "{dict.elf_image_bootloader()}" }
+                    input {
+                        class: "md-input",
+                        placeholder: "{dict.elf_image_bootloader_placeholder()}",
+                        value: "{bootloader_path}",
+                        oninput: move |evt| bootloader_path.set(evt.value()),
+                    }
+                    label { "{dict.elf_image_idf_version()}" }
+                    select {
+                        class: "md-input",
+                        value: "{idf_version}",
+                        disabled: !bootloader_path.read().trim().is_empty(),
+                        onchange: move |evt| idf_version.set(evt.value()),
+                        for version in IDF_VERSIONS {
+                            option { key: "{version}", value: "{version}", "{version}" }
+                        }
+                    }
+                }
+            }
+            div { style: "display: flex; justify-content: flex-end;",
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: *is_converting.read(),
+                    onclick: convert,
+                    span { class: "material-symbols-outlined icon", "transform" }
+                    span { class: "label", "{dict.elf_image_btn_convert()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                div { style: "font-size: 0.85em; color: var(--md-sys-color-error); word-break: break-word;",
+                    "{message}"
+                }
+            }
+        }
+    }
+}
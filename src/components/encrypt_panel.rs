@@ -0,0 +1,156 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// App offset of the default partition tables
+const DEFAULT_OFFSET: &str = "0x10000";
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptArgs {
+    chip: String,
+    firmware_path: String,
+    key_path: String,
+    flash_address: String,
+}
+
+async fn pick(command: &str) -> Option<String> {
+    let val = invoke(command, JsValue::NULL).await.ok()?;
+    serde_wasm_bindgen::from_value::<Option<String>>(val)
+        .ok()
+        .flatten()
+}
+
+/// Encrypts an image with a device's flash encryption key, for boards in
+/// release mode that only take ciphertext over serial. `chip_model` is the
+/// connected board's, whose scheme the ciphertext follows.
+#[component]
+pub fn EncryptPanel(chip_model: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut firmware_path = use_signal(String::new);
+    let mut key_path = use_signal(String::new);
+    let mut offset = use_signal(|| DEFAULT_OFFSET.to_string());
+    let mut encrypting = use_signal(|| false);
+    let mut written = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<String>);
+
+    let browse_firmware = move |_| {
+        spawn(async move {
+            if let Some(path) = pick("pick_firmware_file").await {
+                firmware_path.set(path);
+            }
+        });
+    };
+
+    let browse_key = move |_| {
+        spawn(async move {
+            if let Some(path) = pick("pick_encryption_key").await {
+                key_path.set(path);
+            }
+        });
+    };
+
+    let run = move |_| {
+        let args = serde_wasm_bindgen::to_value(&EncryptArgs {
+            chip: chip_model.clone(),
+            firmware_path: firmware_path.read().clone(),
+            key_path: key_path.read().clone(),
+            flash_address: offset.read().trim().to_string(),
+        })
+        .unwrap_or(JsValue::NULL);
+        encrypting.set(true);
+        written.set(None);
+        error.set(None);
+        spawn(async move {
+            match invoke("encrypt_firmware", args).await {
+                Ok(val) => written.set(serde_wasm_bindgen::from_value(val).ok()),
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+            encrypting.set(false);
+        });
+    };
+
+    let ready = !firmware_path.read().is_empty()
+        && !key_path.read().is_empty()
+        && !offset.read().trim().is_empty()
+        && !*encrypting.read();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.encrypt_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    readonly: true,
+                    placeholder: "{dict.devices_placeholder_firmware_file()}",
+                    value: "{firmware_path}",
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: browse_firmware,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+                input {
+                    class: "md-input",
+                    style: "width: 100px;",
+                    title: "{dict.encrypt_offset()}",
+                    value: "{offset}",
+                    oninput: move |evt| offset.set(evt.value()),
+                }
+            }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    readonly: true,
+                    placeholder: "{dict.encrypt_key_placeholder()}",
+                    value: "{key_path}",
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: browse_key,
+                    span { class: "material-symbols-outlined icon", "key" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(path) = &*written.read() {
+                span { style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.9em; user-select: all;", "{path}" }
+            }
+            div { style: "display: flex; justify-content: flex-end;",
+                button {
+                    class: "md-button btn-filled",
+                    disabled: !ready,
+                    onclick: run,
+                    span { class: "material-symbols-outlined icon", "lock" }
+                    span { class: "label", "{dict.encrypt_btn()}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct EsptoolCommands {
+    write_flash: Option<String>,
+    erase_flash: String,
+    flash_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashSegmentArgs {
+    firmware_path: String,
+    flash_address: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EsptoolCommandsArgs {
+    port_name: String,
+    chip_model: Option<String>,
+    segments: Vec<FlashSegmentArgs>,
+    baud_rate: Option<u32>,
+}
+
+async fn copy_to_clipboard(text: String) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let promise = window.navigator().clipboard().write_text(&text);
+    if let Err(e) = wasm_bindgen_futures::JsFuture::from(promise).await {
+        web_sys::console::error_1(&e);
+    }
+}
+
+/// The `esptool.py` lines matching the flashing form, to rerun the same
+/// operation from a script or paste into a bug report. `segments` are the
+/// form's (file, address) rows as typed; `baud_rate` overrides the flash
+/// baud from the settings.
+#[component]
+pub fn EsptoolPanel(
+    port_name: String,
+    chip_model: Option<String>,
+    segments: Vec<(String, String)>,
+    baud_rate: Option<u32>,
+) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let commands = use_resource(use_reactive!(
+        |port_name, chip_model, segments, baud_rate| async move {
+            if port_name.is_empty() {
+                return None;
+            }
+            let args = serde_wasm_bindgen::to_value(&EsptoolCommandsArgs {
+                port_name,
+                chip_model,
+                segments: segments
+                    .into_iter()
+                    .map(|(firmware_path, flash_address)| FlashSegmentArgs {
+                        firmware_path,
+                        flash_address,
+                    })
+                    .collect(),
+                baud_rate,
+            })
+            .ok()?;
+            let val = invoke("esptool_commands", args).await.ok()?;
+            serde_wasm_bindgen::from_value::<EsptoolCommands>(val).ok()
+        }
+    ));
+
+    let Some(commands) = commands.read().clone().flatten() else {
+        return rsx! {};
+    };
+    let lines = [
+        (dict.esptool_write_flash(), commands.write_flash),
+        (dict.esptool_erase_flash(), Some(commands.erase_flash)),
+        (dict.esptool_flash_id(), Some(commands.flash_id)),
+    ];
+
+    rsx! {
+        details { style: "font-size: 0.85em;",
+            summary { style: "cursor: pointer; color: var(--md-sys-color-on-surface-variant);",
+                "{dict.esptool_title()}"
+            }
+            div { style: "display: flex; flex-direction: column; gap: 8px; margin-top: 8px;",
+                for (label, line) in lines.into_iter() {
+                    div { key: "{label}", style: "display: flex; flex-direction: column; gap: 2px;",
+                        span { style: "color: var(--md-sys-color-on-surface-variant);", "{label}" }
+                        if let Some(line) = line {
+                            div { style: "display: flex; align-items: flex-start; gap: 4px;",
+                                code { style: "flex: 1; font-family: monospace; word-break: break-all; user-select: all;",
+                                    "{line}"
+                                }
+                                button {
+                                    class: "md-button btn-text",
+                                    title: "{dict.esptool_btn_copy()}",
+                                    onclick: {
+                                        let line = line.clone();
+                                        move |_| {
+                                            spawn(copy_to_clipboard(line.clone()));
+                                        }
+                                    },
+                                    span { class: "material-symbols-outlined icon", "content_copy" }
+                                }
+                            }
+                        } else {
+                            span { style: "color: var(--md-sys-color-on-surface-variant);",
+                                "{dict.esptool_incomplete()}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
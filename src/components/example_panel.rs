@@ -0,0 +1,130 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct ExampleFirmware {
+    id: String,
+    bundled: bool,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListExamplesArgs {
+    chip_model: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashExampleArgs {
+    port_name: String,
+    chip_model: String,
+    example: String,
+}
+
+/// One-click flashing of the known-good test images for the detected chip.
+#[component]
+pub fn ExamplePanel(port_name: String, chip_model: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    // Example being flashed, if any
+    let mut flashing = use_signal(|| None::<String>);
+    let mut status = use_signal(String::new);
+
+    let examples = use_resource(use_reactive!(|chip_model| async move {
+        let args =
+            serde_wasm_bindgen::to_value(&ListExamplesArgs { chip_model }).unwrap_or(JsValue::NULL);
+        invoke("list_examples", args)
+            .await
+            .ok()
+            .and_then(|val| serde_wasm_bindgen::from_value::<Vec<ExampleFirmware>>(val).ok())
+            .unwrap_or_default()
+    }));
+    let examples = examples.read().clone().unwrap_or_default();
+
+    let flash = {
+        let port_name = port_name.clone();
+        let chip_model = chip_model.clone();
+        move |example: String| {
+            let args = serde_wasm_bindgen::to_value(&FlashExampleArgs {
+                port_name: port_name.clone(),
+                chip_model: chip_model.clone(),
+                example: example.clone(),
+            })
+            .unwrap_or(JsValue::NULL);
+            flashing.set(Some(example));
+            status.set(String::new());
+            spawn(async move {
+                // Completion and flasher failures also arrive as toasts
+                match invoke("flash_example", args).await {
+                    Ok(_) => status.set(lang.peek().examples_flashed().to_string()),
+                    Err(e) => {
+                        let dict = lang.peek().clone();
+                        status.set(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                            Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                            Err(_) => dict.error_internal().to_string(),
+                        });
+                    }
+                }
+                flashing.set(None);
+            });
+        }
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                if status.read().is_empty() { "{dict.examples_hint()}" } else { "{status}" }
+            }
+            if examples.is_empty() {
+                div { style: "color: var(--md-sys-color-outline); padding: 16px;", "{dict.examples_none()}" }
+            }
+            for example in examples {
+                {
+                    let key = example.id.replace('-', "_");
+                    let name = dict.t(&format!("example_{}", key)).to_string();
+                    let description = dict.t(&format!("example_{}_desc", key)).to_string();
+                    let busy = flashing.read().is_some();
+                    let this_one = flashing.read().as_deref() == Some(example.id.as_str());
+                    let id = example.id.clone();
+                    let mut flash = flash.clone();
+                    rsx! {
+                        div {
+                            key: "{example.id}",
+                            style: "display: flex; align-items: center; gap: 12px; padding: 12px; border-radius: 12px; background: var(--md-sys-color-surface-container);",
+                            div { style: "flex: 1; display: flex; flex-direction: column; gap: 4px;",
+                                span { style: "font-weight: 500;", "{name}" }
+                                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                                    if example.bundled { "{description}" } else { "{dict.examples_not_bundled()}" }
+                                }
+                            }
+                            button {
+                                class: "md-button btn-tonal",
+                                disabled: busy || !example.bundled || port_name.is_empty(),
+                                onclick: move |_| flash(id.clone()),
+                                span { class: "material-symbols-outlined icon", "bolt" }
+                                span { class: "label",
+                                    if this_one { "{dict.devices_flashing_status()}" } else { "{dict.devices_btn_start_flash()}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
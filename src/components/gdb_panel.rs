@@ -0,0 +1,388 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+// Matches what the backend keeps
+const LOG_LIMIT: usize = 500;
+const DEFAULT_BREAKPOINT: &str = "app_main";
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct StackFrame {
+    level: u32,
+    address: String,
+    function: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct GdbTarget {
+    state: String,
+    reason: Option<String>,
+    frame: Option<StackFrame>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct GdbStatus {
+    running: bool,
+    target: GdbTarget,
+    log: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GdbStartArgs {
+    elf_path: String,
+    chip_model: String,
+}
+
+#[derive(Serialize)]
+struct GdbBreakArgs {
+    location: String,
+}
+
+#[derive(Serialize)]
+struct GdbConsoleArgs {
+    command: String,
+}
+
+async fn fetch_status() -> Option<GdbStatus> {
+    let val = invoke("gdb_status", JsValue::NULL).await.ok()?;
+    serde_wasm_bindgen::from_value(val).ok()
+}
+
+/// `app_main at main.c:17`, or the address without symbols.
+fn describe(frame: &StackFrame) -> String {
+    let mut text = frame
+        .function
+        .clone()
+        .unwrap_or_else(|| frame.address.clone());
+    if let (Some(file), Some(line)) = (&frame.file, frame.line) {
+        text.push_str(&format!(" at {}:{}", file, line));
+    }
+    text
+}
+
+/// Debugs the board with GDB through the running OpenOCD: breakpoints,
+/// halt and resume, the backtrace, and a console for everything else.
+#[component]
+pub fn GdbPanel(chip_model: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut status = use_signal(GdbStatus::default);
+    let mut elf_path = use_signal(String::new);
+    let mut location = use_signal(|| DEFAULT_BREAKPOINT.to_string());
+    let mut console_input = use_signal(String::new);
+    let mut frames = use_signal(Vec::<StackFrame>::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let mut show_error = move |e: JsValue| {
+        let dict = lang.peek().clone();
+        error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+            Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+            Err(_) => dict.error_internal().to_string(),
+        }));
+    };
+
+    let load_backtrace = move || {
+        spawn(async move {
+            match invoke("gdb_backtrace", JsValue::NULL).await {
+                Ok(val) => frames.set(serde_wasm_bindgen::from_value(val).unwrap_or_default()),
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    // Runs a session command; what it changes arrives as events
+    let mut run = move |command: &'static str, args: JsValue| {
+        error.set(None);
+        spawn(async move {
+            if let Err(e) = invoke(command, args).await {
+                show_error(e);
+            }
+        });
+    };
+
+    let browse = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_elf_file", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    elf_path.set(path);
+                }
+            }
+        });
+    };
+
+    let start_chip = chip_model.clone();
+    let toggle = move |_| {
+        let running = status.read().running;
+        let args = serde_wasm_bindgen::to_value(&GdbStartArgs {
+            elf_path: elf_path.read().clone(),
+            chip_model: start_chip.clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        error.set(None);
+        spawn(async move {
+            let result = if running {
+                invoke("gdb_stop", JsValue::NULL).await
+            } else {
+                invoke("gdb_start", args).await
+            };
+            match result {
+                Ok(val) => {
+                    if let Ok(new_status) = serde_wasm_bindgen::from_value::<GdbStatus>(val) {
+                        let stopped = new_status.running && new_status.target.state == "stopped";
+                        status.set(new_status);
+                        if stopped {
+                            load_backtrace();
+                        } else {
+                            frames.set(Vec::new());
+                        }
+                    }
+                }
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let mut send_console = move || {
+        let command = console_input.read().trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        console_input.set(String::new());
+        let args =
+            serde_wasm_bindgen::to_value(&GdbConsoleArgs { command }).unwrap_or(JsValue::NULL);
+        run("gdb_console", args);
+    };
+
+    struct ListenerGuard {
+        unlisten: Vec<js_sys::Function>,
+        _closures: Vec<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            for f in &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: Vec::new(),
+        _closures: Vec::new(),
+    });
+
+    // The session outlives the tab; pick it up again
+    use_effect(move || {
+        spawn(async move {
+            if let Some(current) = fetch_status().await {
+                let stopped = current.running && current.target.state == "stopped";
+                status.set(current);
+                if stopped {
+                    load_backtrace();
+                }
+            }
+
+            let on_log = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: String,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    let mut current = status.write();
+                    if current.log.len() == LOG_LIMIT {
+                        current.log.remove(0);
+                    }
+                    current.log.push(e.payload);
+                }
+            });
+            let on_state = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: GdbTarget,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    let stopped = e.payload.state == "stopped";
+                    status.write().target = e.payload;
+                    if stopped {
+                        load_backtrace();
+                    } else {
+                        frames.set(Vec::new());
+                    }
+                }
+            });
+            for (name, closure) in [("gdb-log", on_log), ("gdb-state", on_state)] {
+                match listen(name, &closure).await {
+                    Ok(unlisten_js) => {
+                        let mut guard = listener_guard.write();
+                        if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                            guard.unlisten.push(f);
+                        }
+                        guard._closures.push(closure);
+                    }
+                    Err(e) => web_sys::console::error_1(&e),
+                }
+            }
+        });
+    });
+
+    let current = status.read().clone();
+    let attached = current.running;
+    let stopped = attached && current.target.state == "stopped";
+    let can_start = attached || (!elf_path.read().is_empty() && !chip_model.is_empty());
+    let state = if !attached {
+        String::new()
+    } else if stopped {
+        let mut text = dict.gdb_state_stopped().to_string();
+        if let Some(reason) = &current.target.reason {
+            text.push_str(&format!(" ({})", reason));
+        }
+        if let Some(frame) = &current.target.frame {
+            text.push_str(&format!(" · {}", describe(frame)));
+        }
+        text
+    } else if current.target.state == "running" {
+        dict.gdb_state_running().to_string()
+    } else {
+        dict.gdb_state_exited().to_string()
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.gdb_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    disabled: attached,
+                    placeholder: "{dict.gdb_placeholder_elf()}",
+                    value: "{elf_path}",
+                    oninput: move |evt| elf_path.set(evt.value()),
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: attached,
+                    onclick: browse,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+                button {
+                    class: if attached { "md-button btn-tonal" } else { "md-button btn-filled" },
+                    disabled: !can_start,
+                    title: if chip_model.is_empty() { "{dict.openocd_no_chip()}" } else { "" },
+                    onclick: toggle,
+                    span { class: "material-symbols-outlined icon", if attached { "link_off" } else { "link" } }
+                    span { class: "label",
+                        if attached { "{dict.gdb_btn_detach()}" } else { "{dict.gdb_btn_attach()}" }
+                    }
+                }
+            }
+            if attached {
+                div { style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap;",
+                    input {
+                        class: "md-input",
+                        style: "width: 160px; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;",
+                        value: "{location}",
+                        oninput: move |evt| location.set(evt.value()),
+                    }
+                    button {
+                        class: "md-button btn-tonal",
+                        disabled: location.read().trim().is_empty(),
+                        onclick: move |_| {
+                            let args = serde_wasm_bindgen::to_value(&GdbBreakArgs {
+                                location: location.read().trim().to_string(),
+                            })
+                            .unwrap_or(JsValue::NULL);
+                            run("gdb_break", args);
+                        },
+                        span { class: "material-symbols-outlined icon", "adjust" }
+                        span { class: "label", "{dict.gdb_btn_break()}" }
+                    }
+                    button {
+                        class: "md-button btn-text",
+                        onclick: move |_| run("gdb_reset", JsValue::NULL),
+                        span { class: "material-symbols-outlined icon", "restart_alt" }
+                        span { class: "label", "{dict.gdb_btn_reset()}" }
+                    }
+                    if stopped {
+                        button {
+                            class: "md-button btn-filled",
+                            onclick: move |_| run("gdb_continue", JsValue::NULL),
+                            span { class: "material-symbols-outlined icon", "play_arrow" }
+                            span { class: "label", "{dict.gdb_btn_continue()}" }
+                        }
+                    } else {
+                        button {
+                            class: "md-button btn-filled",
+                            onclick: move |_| run("gdb_halt", JsValue::NULL),
+                            span { class: "material-symbols-outlined icon", "pause" }
+                            span { class: "label", "{dict.gdb_btn_halt()}" }
+                        }
+                    }
+                }
+            }
+            if !state.is_empty() {
+                span {
+                    style: if stopped { "color: var(--md-sys-color-primary);" } else { "color: var(--md-sys-color-on-surface-variant);" },
+                    "{state}"
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if stopped && !frames.read().is_empty() {
+                div { style: "display: flex; flex-direction: column; gap: 2px; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em;",
+                    span { style: "font-family: inherit; color: var(--md-sys-color-on-surface-variant);", "{dict.gdb_backtrace()}" }
+                    for frame in frames.read().iter() {
+                        div { key: "{frame.level}",
+                            "#{frame.level}  {frame.address}  {describe(frame)}"
+                        }
+                    }
+                }
+            }
+            div { style: "display: flex; flex-direction: column; gap: 2px; max-height: 240px; overflow-y: auto; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em; white-space: pre-wrap;",
+                if current.log.is_empty() {
+                    span { style: "color: var(--md-sys-color-outline);", "{dict.gdb_no_log()}" }
+                }
+                for (i, line) in current.log.iter().enumerate() {
+                    div { key: "{i}", "{line}" }
+                }
+            }
+            if attached {
+                input {
+                    class: "md-input",
+                    style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;",
+                    placeholder: "{dict.gdb_console_placeholder()}",
+                    value: "{console_input}",
+                    oninput: move |evt| console_input.set(evt.value()),
+                    onkeypress: move |evt| {
+                        if evt.key() == Key::Enter {
+                            send_console();
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
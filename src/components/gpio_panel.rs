@@ -0,0 +1,326 @@
+use crate::components::PinoutView;
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// How often configured inputs are read back
+const POLL_MS: u32 = 500;
+// Modes offered per pin; "adc" only on ADC-capable pins
+const MODES: [&str; 5] = ["off", "in", "pullup", "out", "adc"];
+// Pins the console or flash memory depend on; driving them breaks the helper
+const RESERVED_TAGS: [&str; 3] = ["uart", "usb", "psram"];
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct BoardDefinition {
+    pins: Vec<PinInfo>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct PinInfo {
+    name: String,
+    gpio: Option<u32>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PinReading {
+    gpio: u32,
+    value: u32,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBoardDefinitionArgs {
+    chip_model: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashGpioHelperArgs {
+    port_name: String,
+    chip_model: String,
+}
+
+#[derive(Serialize)]
+struct GpioSetModeArgs {
+    gpio: u32,
+    mode: String,
+}
+
+#[derive(Serialize)]
+struct GpioWriteArgs {
+    gpio: u32,
+    high: bool,
+}
+
+fn describe_error(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Live pin control through the GPIO helper firmware, spoken over the
+/// monitor session of the Monitor tab.
+#[component]
+pub fn GpioPanel(
+    port_name: String,
+    chip_model: String,
+    connection_type: Option<String>,
+) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut helper_ready = use_signal(|| false);
+    let mut busy = use_signal(|| false);
+    let mut status = use_signal(String::new);
+    // Per GPIO: selected mode, output level and last reading
+    let mut modes = use_signal(HashMap::<u32, String>::new);
+    let mut outputs = use_signal(HashMap::<u32, bool>::new);
+    let mut readings = use_signal(HashMap::<u32, u32>::new);
+
+    let board = use_resource(use_reactive!(|chip_model| async move {
+        let args = serde_wasm_bindgen::to_value(&GetBoardDefinitionArgs { chip_model })
+            .unwrap_or(JsValue::NULL);
+        invoke("get_board_definition", args)
+            .await
+            .ok()
+            .and_then(|val| serde_wasm_bindgen::from_value::<Option<BoardDefinition>>(val).ok())
+            .flatten()
+    }));
+    let pins: Vec<(u32, PinInfo)> = board
+        .read()
+        .clone()
+        .flatten()
+        .map(|b| b.pins)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|pin| !pin.tags.iter().any(|t| RESERVED_TAGS.contains(&t.as_str())))
+        .filter_map(|pin| pin.gpio.map(|gpio| (gpio, pin)))
+        .collect();
+
+    let mut detect = move || {
+        busy.set(true);
+        spawn(async move {
+            match invoke("gpio_ping", JsValue::NULL).await {
+                Ok(_) => {
+                    helper_ready.set(true);
+                    status.set(lang.peek().gpio_helper_ready().to_string());
+                    // A fresh boot forgets every mode
+                    modes.write().clear();
+                    outputs.write().clear();
+                    readings.write().clear();
+                }
+                Err(e) => {
+                    helper_ready.set(false);
+                    let dict = lang.peek().clone();
+                    status.set(format!(
+                        "{} ({})",
+                        dict.gpio_helper_missing(),
+                        describe_error(&dict, e)
+                    ));
+                }
+            }
+            busy.set(false);
+        });
+    };
+
+    let flash_helper = {
+        let port_name = port_name.clone();
+        let chip_model = chip_model.clone();
+        move |_| {
+            let args = serde_wasm_bindgen::to_value(&FlashGpioHelperArgs {
+                port_name: port_name.clone(),
+                chip_model: chip_model.clone(),
+            })
+            .unwrap_or(JsValue::NULL);
+            busy.set(true);
+            status.set(lang.peek().gpio_flashing().to_string());
+            spawn(async move {
+                match invoke("flash_gpio_helper", args).await {
+                    Ok(_) => {
+                        // Give the board time to boot the helper
+                        gloo_timers::future::TimeoutFuture::new(1500).await;
+                        detect();
+                    }
+                    Err(e) => {
+                        status.set(describe_error(&lang.peek(), e));
+                        busy.set(false);
+                    }
+                }
+            });
+        }
+    };
+
+    let set_mode = move |gpio: u32, mode: String| {
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&GpioSetModeArgs {
+                gpio,
+                mode: mode.clone(),
+            })
+            .unwrap_or(JsValue::NULL);
+            match invoke("gpio_set_mode", args).await {
+                Ok(_) => {
+                    readings.write().remove(&gpio);
+                    if mode == "out" {
+                        outputs.write().insert(gpio, false);
+                    } else {
+                        outputs.write().remove(&gpio);
+                    }
+                    modes.write().insert(gpio, mode);
+                }
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+        });
+    };
+
+    let toggle = move |gpio: u32| {
+        let high = !outputs.peek().get(&gpio).copied().unwrap_or(false);
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&GpioWriteArgs { gpio, high })
+                .unwrap_or(JsValue::NULL);
+            match invoke("gpio_write", args).await {
+                Ok(_) => {
+                    outputs.write().insert(gpio, high);
+                }
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+        });
+    };
+
+    // Read inputs back while the helper answers; a failed read means it is gone
+    use_future(move || async move {
+        loop {
+            gloo_timers::future::TimeoutFuture::new(POLL_MS).await;
+            let watching = modes
+                .peek()
+                .values()
+                .any(|mode| matches!(mode.as_str(), "in" | "pullup" | "adc"));
+            if !*helper_ready.peek() || !watching {
+                continue;
+            }
+            match invoke("gpio_poll", JsValue::NULL).await {
+                Ok(val) => {
+                    if let Ok(values) = serde_wasm_bindgen::from_value::<Vec<PinReading>>(val) {
+                        readings.set(values.into_iter().map(|r| (r.gpio, r.value)).collect());
+                    }
+                }
+                Err(e) => {
+                    helper_ready.set(false);
+                    status.set(describe_error(&lang.peek(), e));
+                }
+            }
+        }
+    });
+
+    // Digital levels for the drawing; ADC pins have no level to show
+    let levels: HashMap<u32, bool> = modes
+        .read()
+        .iter()
+        .filter_map(|(gpio, mode)| match mode.as_str() {
+            "out" => Some((*gpio, outputs.read().get(gpio).copied().unwrap_or(false))),
+            "in" | "pullup" => readings.read().get(gpio).map(|v| (*gpio, *v != 0)),
+            _ => None,
+        })
+        .collect();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                span {
+                    class: "material-symbols-outlined",
+                    style: if *helper_ready.read() { "color: var(--md-sys-color-primary);" } else { "color: var(--md-sys-color-outline);" },
+                    if *helper_ready.read() { "check_circle" } else { "help" }
+                }
+                span { style: "flex: 1; font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                    if status.read().is_empty() { "{dict.gpio_hint()}" } else { "{status}" }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: *busy.read(),
+                    onclick: move |_| detect(),
+                    span { class: "material-symbols-outlined icon", "sensors" }
+                    span { class: "label", "{dict.gpio_btn_detect()}" }
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: *busy.read() || port_name.is_empty(),
+                    onclick: flash_helper,
+                    span { class: "material-symbols-outlined icon", "bolt" }
+                    span { class: "label", "{dict.gpio_btn_flash()}" }
+                }
+            }
+
+            div { style: "display: flex; gap: 12px; height: 400px;",
+                div { style: "flex: 1; min-width: 240px; overflow-y: auto; display: flex; flex-direction: column; gap: 4px;",
+                    if pins.is_empty() {
+                        div { style: "color: var(--md-sys-color-outline); padding: 16px;", "{dict.gpio_no_pins()}" }
+                    }
+                    for (gpio, pin) in pins {
+                        {
+                            let mode = modes.read().get(&gpio).cloned().unwrap_or_else(|| "off".to_string());
+                            let adc = pin.tags.iter().any(|t| t == "adc");
+                            let high = outputs.read().get(&gpio).copied().unwrap_or(false);
+                            let reading = match (mode.as_str(), readings.read().get(&gpio)) {
+                                ("adc", Some(mv)) => format!("{} mV", mv),
+                                ("in" | "pullup", Some(0)) => dict.gpio_low().to_string(),
+                                ("in" | "pullup", Some(_)) => dict.gpio_high().to_string(),
+                                ("in" | "pullup" | "adc", None) => "…".to_string(),
+                                _ => String::new(),
+                            };
+                            rsx! {
+                                div {
+                                    key: "{gpio}",
+                                    style: "display: flex; align-items: center; gap: 8px; font-size: 0.9em;",
+                                    span { style: "width: 72px; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;", "{pin.name}" }
+                                    select {
+                                        class: "md-input",
+                                        style: "width: 110px; padding: 4px 8px;",
+                                        disabled: !*helper_ready.read(),
+                                        value: "{mode}",
+                                        onchange: move |evt| set_mode(gpio, evt.value()),
+                                        for m in MODES.iter().filter(|m| adc || **m != "adc") {
+                                            option { key: "{m}", value: "{m}", "{dict.gpio_mode(m)}" }
+                                        }
+                                    }
+                                    if mode == "out" {
+                                        button {
+                                            class: if high { "md-button btn-filled" } else { "md-button btn-text" },
+                                            style: "min-width: 64px;",
+                                            onclick: move |_| toggle(gpio),
+                                            if high { "{dict.gpio_high()}" } else { "{dict.gpio_low()}" }
+                                        }
+                                    } else {
+                                        span { style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;", "{reading}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { style: "flex: 1.2;",
+                    PinoutView {
+                        chip_model: chip_model.clone(),
+                        connection_type: connection_type.clone(),
+                        gpio_levels: levels,
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct ImageInfoArgs {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ImageSegment {
+    load_address: u32,
+    len: u32,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct AppDescription {
+    project_name: String,
+    version: String,
+    idf_version: String,
+    compiled: String,
+}
+
+/// The backend's decoded image, see `image_info`.
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ImageInfo {
+    chip: Option<String>,
+    entry_point: u32,
+    flash_mode: Option<String>,
+    flash_size: Option<String>,
+    flash_freq: Option<String>,
+    min_chip_revision: String,
+    max_chip_revision: String,
+    segments: Vec<ImageSegment>,
+    checksum_valid: bool,
+    hash_valid: Option<bool>,
+    app: Option<AppDescription>,
+    signature: Option<String>,
+    problems: Vec<String>,
+}
+
+/// Header, segments and checksums of the image at `path`, to spot a
+/// truncated or corrupted file before flashing it.
+#[component]
+pub fn ImageInfoView(path: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let info = use_resource(use_reactive!(|path| async move {
+        let args = serde_wasm_bindgen::to_value(&ImageInfoArgs { path }).unwrap_or(JsValue::NULL);
+        match invoke("image_info", args).await {
+            Ok(val) => serde_wasm_bindgen::from_value::<ImageInfo>(val)
+                .map_err(|_| lang.peek().error_internal().to_string()),
+            Err(e) => {
+                let dict = lang.peek().clone();
+                Err(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                    Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                    Err(_) => dict.error_internal().to_string(),
+                })
+            }
+        }
+    }));
+
+    let box_style = "font-size: 0.8em; padding: 8px; border-radius: 8px; background: var(--md-sys-color-surface-container); display: flex; flex-direction: column; gap: 2px;";
+    let info = info.read().clone();
+    match info {
+        None => rsx! {
+            div { style: "{box_style}", "{dict.image_info_loading()}" }
+        },
+        Some(Err(message)) => rsx! {
+            div { style: "{box_style} color: var(--md-sys-color-error); word-break: break-word;", "{message}" }
+        },
+        Some(Ok(info)) => {
+            let valid = |valid: bool| {
+                if valid {
+                    dict.image_info_valid()
+                } else {
+                    dict.image_info_invalid()
+                }
+            };
+            let chip = info.chip.clone().unwrap_or_else(|| "?".to_string());
+            let flash = format!(
+                "{} {} {}",
+                info.flash_mode.as_deref().unwrap_or("?"),
+                info.flash_size.as_deref().unwrap_or("?"),
+                info.flash_freq.as_deref().unwrap_or("?")
+            );
+            let entry = format!("0x{:08x}", info.entry_point);
+            let checksum = valid(info.checksum_valid);
+            let segments: Vec<String> = info
+                .segments
+                .iter()
+                .map(|segment| format!("0x{:08x}  {} B", segment.load_address, segment.len))
+                .collect();
+            let hash = match info.hash_valid {
+                Some(hash_valid) => valid(hash_valid),
+                None => dict.image_info_not_appended(),
+            };
+            rsx! {
+                div { style: "{box_style}",
+                    span { "{dict.image_info_chip()}: {chip} ({info.min_chip_revision} – {info.max_chip_revision})" }
+                    span { "{dict.image_info_entry()}: {entry} · {dict.image_info_flash()}: {flash}" }
+                    if let Some(app) = &info.app {
+                        span { "{app.project_name} {app.version} · ESP-IDF {app.idf_version} · {app.compiled}" }
+                    }
+                    span { "{dict.image_info_segments()}: {segments.len()}" }
+                    for (i, segment) in segments.iter().enumerate() {
+                        span { key: "{i}", style: "font-family: monospace; padding-left: 12px;", "{segment}" }
+                    }
+                    span { "{dict.image_info_checksum()}: {checksum} · SHA-256: {hash}" }
+                    if let Some(signature) = &info.signature {
+                        span { "{dict.image_info_signature()}: {signature}" }
+                    }
+                    for (i, problem) in info.problems.iter().enumerate() {
+                        span { key: "p{i}", style: "color: var(--md-sys-color-error);", "{problem}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ImprovDevice {
+    firmware: String,
+    version: String,
+    chip: String,
+    device_name: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct ImprovOutcome {
+    device: Option<ImprovDevice>,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImprovProvisionArgs {
+    port_name: String,
+    ssid: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+struct OpenUrlArgs {
+    url: String,
+}
+
+/// Hands Wi-Fi credentials to freshly flashed Improv firmware (ESPHome,
+/// WLED) over the cable, like the web installers do.
+#[component]
+pub fn ImprovPanel(port_name: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut ssid = use_signal(String::new);
+    let mut password = use_signal(String::new);
+    let mut busy = use_signal(|| false);
+    let mut outcome = use_signal(|| None::<ImprovOutcome>);
+    let mut error = use_signal(|| None::<String>);
+
+    let provision = {
+        let port_name = port_name.clone();
+        move |_| {
+            let args = serde_wasm_bindgen::to_value(&ImprovProvisionArgs {
+                port_name: port_name.clone(),
+                ssid: ssid.read().clone(),
+                password: password.read().clone(),
+            })
+            .unwrap_or(JsValue::NULL);
+            busy.set(true);
+            outcome.set(None);
+            error.set(None);
+            spawn(async move {
+                match invoke("improv_provision", args).await {
+                    Ok(val) => outcome.set(serde_wasm_bindgen::from_value(val).ok()),
+                    Err(e) => {
+                        let dict = lang.peek().clone();
+                        error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                            Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                            Err(_) => dict.error_internal().to_string(),
+                        }));
+                    }
+                }
+                busy.set(false);
+            });
+        }
+    };
+
+    let open_url = move |url: String| {
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&OpenUrlArgs { url }).unwrap_or(JsValue::NULL);
+            invoke("plugin:opener|open_url", args).await.ok();
+        });
+    };
+
+    let ready = !port_name.is_empty() && !ssid.read().trim().is_empty() && !*busy.read();
+    let result = outcome.read().clone();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.improv_hint()}" }
+            input {
+                class: "md-input",
+                placeholder: "{dict.improv_ssid_placeholder()}",
+                value: "{ssid}",
+                oninput: move |evt| ssid.set(evt.value()),
+            }
+            input {
+                class: "md-input",
+                r#type: "password",
+                placeholder: "{dict.improv_password_placeholder()}",
+                value: "{password}",
+                oninput: move |evt| password.set(evt.value()),
+            }
+            if *busy.read() {
+                span { style: "font-size: 0.9em;", "{dict.improv_provisioning()}" }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(result) = result {
+                div { style: "display: flex; flex-direction: column; gap: 4px; font-size: 0.9em;",
+                    span { "{dict.improv_provisioned()}" }
+                    if let Some(device) = result.device {
+                        {
+                            let line = format!(
+                                "{} · {} {} · {}",
+                                device.device_name, device.firmware, device.version, device.chip
+                            );
+                            rsx! {
+                                span { style: "color: var(--md-sys-color-on-surface-variant);", "{line}" }
+                            }
+                        }
+                    }
+                    if let Some(url) = result.url {
+                        {
+                            let target = url.clone();
+                            rsx! {
+                                button {
+                                    class: "md-button btn-text",
+                                    style: "align-self: flex-start;",
+                                    onclick: move |_| open_url(target.clone()),
+                                    span { class: "material-symbols-outlined icon", "open_in_new" }
+                                    span { class: "label", "{url}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { style: "display: flex; justify-content: flex-end;",
+                button {
+                    class: "md-button btn-filled",
+                    disabled: !ready,
+                    onclick: provision,
+                    span { class: "material-symbols-outlined icon", "wifi_password" }
+                    span { class: "label", "{dict.improv_btn_send()}" }
+                }
+            }
+        }
+    }
+}
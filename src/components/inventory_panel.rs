@@ -0,0 +1,228 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct InventoryBatch {
+    batch: String,
+    operator: String,
+    note: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct InventoryRow {
+    recorded_at: String,
+    mac_address: String,
+    chip_model: Option<String>,
+    serial_number: Option<String>,
+    duplicate: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct InventoryStatus {
+    path: Option<String>,
+    batch: Option<InventoryBatch>,
+    count: usize,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct InventoryStartArgs {
+    batch: InventoryBatch,
+}
+
+async fn fetch_status() -> Option<InventoryStatus> {
+    let val = invoke("inventory_status", JsValue::NULL).await.ok()?;
+    serde_wasm_bindgen::from_value(val).ok()
+}
+
+/// Switches inventory mode on and off: every board identified while it is
+/// on is appended to a CSV with the batch details entered here.
+#[component]
+pub fn InventoryPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut status = use_signal(InventoryStatus::default);
+    let mut batch = use_signal(InventoryBatch::default);
+    let mut rows = use_signal(Vec::<InventoryRow>::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let toggle = move |_| {
+        let running = status.read().path.is_some();
+        let args = serde_wasm_bindgen::to_value(&InventoryStartArgs {
+            batch: batch.read().clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        error.set(None);
+        spawn(async move {
+            let result = if running {
+                invoke("inventory_stop", JsValue::NULL).await
+            } else {
+                invoke("inventory_start", args).await
+            };
+            match result {
+                Ok(val) => {
+                    // Starting gives `None` when the file dialog was cancelled
+                    if let Ok(Some(new_status)) =
+                        serde_wasm_bindgen::from_value::<Option<InventoryStatus>>(val)
+                    {
+                        if !running {
+                            rows.write().clear();
+                        }
+                        status.set(new_status);
+                    }
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    // Inventory mode keeps recording when the tab is left; pick it up again
+    use_effect(move || {
+        spawn(async move {
+            if let Some(current) = fetch_status().await {
+                if let Some(current_batch) = &current.batch {
+                    batch.set(current_batch.clone());
+                }
+                status.set(current);
+            }
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: InventoryRow,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    if !e.payload.duplicate {
+                        status.write().count += 1;
+                    }
+                    rows.write().push(e.payload);
+                }
+            });
+            match listen("inventory-row", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let current = status.read().clone();
+    let running = current.path.is_some();
+    let fields = batch.read().clone();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.inventory_hint()}" }
+            div { style: "display: grid; grid-template-columns: auto 1fr; align-items: center; gap: 8px; font-size: 0.9em;",
+                label { "{dict.inventory_batch()}" }
+                input {
+                    class: "md-input",
+                    disabled: running,
+                    value: "{fields.batch}",
+                    oninput: move |evt| batch.write().batch = evt.value(),
+                }
+                label { "{dict.inventory_operator()}" }
+                input {
+                    class: "md-input",
+                    disabled: running,
+                    value: "{fields.operator}",
+                    oninput: move |evt| batch.write().operator = evt.value(),
+                }
+                label { "{dict.inventory_note()}" }
+                input {
+                    class: "md-input",
+                    disabled: running,
+                    value: "{fields.note}",
+                    oninput: move |evt| batch.write().note = evt.value(),
+                }
+            }
+            div { style: "display: flex; gap: 8px; align-items: center;",
+                button {
+                    class: if running { "md-button btn-tonal" } else { "md-button btn-filled" },
+                    onclick: toggle,
+                    span { class: "material-symbols-outlined icon", if running { "stop" } else { "play_arrow" } }
+                    span { class: "label",
+                        if running { "{dict.inventory_btn_stop()}" } else { "{dict.inventory_btn_start()}" }
+                    }
+                }
+                if let Some(path) = &current.path {
+                    span { style: "flex: 1; font-size: 0.85em; color: var(--md-sys-color-on-surface-variant); word-break: break-all;",
+                        "{path} · {dict.inventory_count()} {current.count}"
+                    }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            div { style: "display: flex; flex-direction: column; gap: 2px; max-height: 200px; overflow-y: auto; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em;",
+                if running && rows.read().is_empty() {
+                    span { style: "color: var(--md-sys-color-outline);", "{dict.inventory_waiting()}" }
+                }
+                for (i, row) in rows.read().iter().enumerate().rev() {
+                    {
+                        let line = format!(
+                            "{}  {}  {}  {}",
+                            row.recorded_at,
+                            row.mac_address,
+                            row.chip_model.as_deref().unwrap_or("-"),
+                            row.serial_number.as_deref().unwrap_or("-")
+                        );
+                        rsx! {
+                            div {
+                                key: "{i}",
+                                style: if row.duplicate { "color: var(--md-sys-color-outline);" } else { "" },
+                                title: if row.duplicate { "{dict.inventory_duplicate()}" } else { "" },
+                                "{line}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::i18n::Dict;
+use crate::settings::Settings;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KioskEnterArgs {
+    image_path: String,
+    address: String,
+    golden_hash: String,
+    password: String,
+}
+
+/// Sets up kiosk mode for the production line: the golden image, its
+/// address and SHA-256, and the password for leaving. Entering swaps the whole app for
+/// the kiosk screen.
+#[component]
+pub fn KioskPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    // The last golden image, else the flashing defaults
+    let mut image_path = use_signal(|| settings.peek().kiosk.image_path.clone());
+    let mut address = use_signal(|| {
+        let current = settings.peek();
+        if current.kiosk.address.is_empty() {
+            current.flash.address.clone()
+        } else {
+            current.kiosk.address.clone()
+        }
+    });
+    let mut golden_hash = use_signal(|| settings.peek().kiosk.golden_hash.clone());
+    let mut password = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let browse = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_firmware_file", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    image_path.set(path);
+                }
+            }
+        });
+    };
+
+    let enter = move |_| {
+        let args = serde_wasm_bindgen::to_value(&KioskEnterArgs {
+            image_path: image_path.read().trim().to_string(),
+            address: address.read().trim().to_string(),
+            golden_hash: golden_hash.read().trim().to_string(),
+            password: password.read().clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        error.set(None);
+        spawn(async move {
+            match invoke("kiosk_enter", args).await {
+                Ok(val) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<Settings>(val) {
+                        settings.set(saved);
+                    }
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.kiosk_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    placeholder: "{dict.kiosk_image()}",
+                    value: "{image_path}",
+                    oninput: move |evt| image_path.set(evt.value()),
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: browse,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+            }
+            input {
+                class: "md-input",
+                style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;",
+                placeholder: "{dict.kiosk_golden_hash()}",
+                value: "{golden_hash}",
+                oninput: move |evt| golden_hash.set(evt.value()),
+            }
+            div { style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap;",
+                input {
+                    class: "md-input",
+                    style: "width: 120px; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;",
+                    value: "{address}",
+                    oninput: move |evt| address.set(evt.value()),
+                }
+                input {
+                    class: "md-input",
+                    style: "width: 200px;",
+                    r#type: "password",
+                    placeholder: "{dict.kiosk_password()}",
+                    value: "{password}",
+                    oninput: move |evt| password.set(evt.value()),
+                }
+                button {
+                    class: "md-button btn-filled",
+                    disabled: image_path.read().trim().is_empty() || golden_hash.read().trim().is_empty(),
+                    onclick: enter,
+                    span { class: "material-symbols-outlined icon", "factory" }
+                    span { class: "label", "{dict.kiosk_btn_enter()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+        }
+    }
+}
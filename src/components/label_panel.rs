@@ -0,0 +1,216 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// Common thermal label printer resolutions
+const DPIS: [u32; 3] = [203, 300, 600];
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct LabelSize {
+    key: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct DeviceLabel {
+    mac: Option<String>,
+    serial_number: Option<String>,
+    flashed_at: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PortArgs {
+    port_name: String,
+}
+
+#[derive(Serialize)]
+struct RenderArgs {
+    label: DeviceLabel,
+    size: String,
+}
+
+#[derive(Serialize)]
+struct ExportArgs {
+    label: DeviceLabel,
+    size: String,
+    dpi: u32,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+// Empty input clears the field
+fn field(value: String) -> Option<String> {
+    let value = value.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// `YYYY-MM-DD` in UTC, as the label prints it.
+fn date(secs: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(secs as f64 * 1000.0));
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.get_utc_full_year(),
+        date.get_utc_month() + 1,
+        date.get_utc_date()
+    )
+}
+
+/// A QR code label for the board on `port_name` with its MAC address,
+/// serial number and last flash date, previewed and saved as PNG or SVG
+/// for a label printer.
+#[component]
+pub fn LabelPanel(port_name: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut label = use_signal(DeviceLabel::default);
+    let mut size = use_signal(|| "dk11201".to_string());
+    let mut dpi = use_signal(|| 300u32);
+    let mut message = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<String>);
+
+    let sizes = use_resource(|| async {
+        match invoke("get_label_sizes", JsValue::NULL).await {
+            Ok(val) => serde_wasm_bindgen::from_value::<Vec<LabelSize>>(val).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    });
+
+    // Fields of the selected board, refetched when it changes
+    use_effect(use_reactive!(|port_name| {
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&PortArgs { port_name }).unwrap();
+            if let Ok(val) = invoke("get_device_label", args).await {
+                if let Ok(fields) = serde_wasm_bindgen::from_value::<DeviceLabel>(val) {
+                    label.set(fields);
+                }
+            }
+        });
+    }));
+
+    let preview = use_resource(move || async move {
+        let args = RenderArgs {
+            label: label(),
+            size: size(),
+        };
+        let args = serde_wasm_bindgen::to_value(&args).unwrap();
+        // No fields yet is the usual reason, shown by the empty preview
+        invoke("render_label_svg", args)
+            .await
+            .ok()
+            .and_then(|val| val.as_string())
+    });
+
+    let export = move |_| {
+        message.set(None);
+        error.set(None);
+        spawn(async move {
+            let args = ExportArgs {
+                label: label.peek().clone(),
+                size: size.peek().clone(),
+                dpi: *dpi.peek(),
+            };
+            let args = serde_wasm_bindgen::to_value(&args).unwrap();
+            match invoke("export_label", args).await {
+                Ok(val) => {
+                    if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                        message.set(Some(format!("{} {}", lang.peek().label_saved(), path)));
+                    }
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let current = label.read().clone();
+    let flashed = current.flashed_at.map(date);
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            div { style: "display: grid; grid-template-columns: auto 1fr; gap: 8px; align-items: center; font-size: 0.9em;",
+                span { "{dict.mac_address()}" }
+                input {
+                    class: "md-input",
+                    value: "{current.mac.clone().unwrap_or_default()}",
+                    oninput: move |evt| label.write().mac = field(evt.value()),
+                }
+                span { "{dict.serial_number()}" }
+                input {
+                    class: "md-input",
+                    value: "{current.serial_number.clone().unwrap_or_default()}",
+                    oninput: move |evt| label.write().serial_number = field(evt.value()),
+                }
+                span { "{dict.label_flashed()}" }
+                span { style: "color: var(--md-sys-color-on-surface-variant);",
+                    "{flashed.clone().unwrap_or_else(|| dict.label_never_flashed().to_string())}"
+                }
+            }
+            div { style: "display: flex; gap: 8px; flex-wrap: wrap;",
+                select {
+                    class: "md-select",
+                    value: "{size}",
+                    onchange: move |evt| size.set(evt.value()),
+                    for option_size in sizes.read().clone().unwrap_or_default() {
+                        option { key: "{option_size.key}", value: "{option_size.key}", "{option_size.name}" }
+                    }
+                }
+                select {
+                    class: "md-select",
+                    title: "{dict.label_dpi_hint()}",
+                    value: "{dpi}",
+                    onchange: move |evt| {
+                        if let Ok(value) = evt.value().parse() {
+                            dpi.set(value);
+                        }
+                    },
+                    for d in DPIS {
+                        option { key: "{d}", value: "{d}", "{d} dpi" }
+                    }
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: preview.read().clone().flatten().is_none(),
+                    onclick: export,
+                    span { class: "material-symbols-outlined icon", "qr_code_2" }
+                    span { class: "label", "{dict.label_btn_export()}" }
+                }
+            }
+            if let Some(svg) = preview.read().clone().flatten() {
+                div {
+                    style: "align-self: flex-start; max-width: 100%; border: 1px solid var(--md-sys-color-outline-variant); line-height: 0;",
+                    dangerous_inner_html: "{svg}",
+                }
+            } else {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-outline);", "{dict.label_empty()}" }
+            }
+            span { style: "font-size: 0.8em; color: var(--md-sys-color-outline);", "{dict.label_png_note()}" }
+            if let Some(text) = &*message.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-primary); word-break: break-all;", "{text}" }
+            }
+            if let Some(text) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{text}" }
+            }
+        }
+    }
+}
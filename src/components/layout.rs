@@ -6,7 +6,10 @@ pub fn Layout(
     children: Element,
     on_theme_toggle: Option<EventHandler<MouseEvent>>,
     on_lang_toggle: Option<EventHandler<MouseEvent>>,
+    on_api_toggle: Option<EventHandler<MouseEvent>>,
+    on_api_token: Option<EventHandler<MouseEvent>>,
     is_dark: bool,
+    api_enabled: bool,
 ) -> Element {
     rsx! {
         div {
@@ -14,7 +17,10 @@ pub fn Layout(
             Sidebar {
                 on_theme_toggle: on_theme_toggle,
                 on_lang_toggle: on_lang_toggle,
-                is_dark: is_dark
+                on_api_toggle: on_api_toggle,
+                on_api_token: on_api_token,
+                is_dark: is_dark,
+                api_enabled: api_enabled
             }
             main {
                 class: "md-main-content",
@@ -1,11 +1,87 @@
+pub mod arduino_panel;
+pub mod benchmark_panel;
+pub mod ble_prov_panel;
 pub mod buttons;
+pub mod capture_panel;
 pub mod cards;
+pub mod circuitpy_panel;
+pub mod config_panel;
+pub mod coredump_panel;
+pub mod debug_log;
+pub mod device_records_panel;
+pub mod elf_image_panel;
+pub mod encrypt_panel;
+pub mod esptool_panel;
+pub mod example_panel;
+pub mod gdb_panel;
+pub mod gpio_panel;
+pub mod image_info_view;
+pub mod improv_panel;
+pub mod inventory_panel;
+pub mod kiosk_panel;
+pub mod label_panel;
 pub mod layout;
+pub mod mpy_files_panel;
+pub mod mpy_firmware_panel;
+pub mod notifications_panel;
+pub mod openocd_panel;
+pub mod ota_panel;
+pub mod ota_server_panel;
+pub mod pin_usage_panel;
+pub mod production_run_panel;
+pub mod register_panel;
+pub mod reserved_pins_panel;
+pub mod script_panel;
 pub mod sidebar;
+pub mod signing_panel;
+pub mod soak_panel;
+pub mod split_monitor_panel;
+pub mod terminal_view;
+pub mod test_panel;
+pub mod toast;
+pub mod triggers_panel;
 
+pub use arduino_panel::ArduinoPanel;
+pub use benchmark_panel::BenchmarkPanel;
+pub use ble_prov_panel::BleProvPanel;
 pub use buttons::Button;
+pub use capture_panel::CapturePanel;
 pub use cards::Card;
+pub use circuitpy_panel::CircuitPyPanel;
+pub use config_panel::ConfigPanel;
+pub use coredump_panel::CoreDumpPanel;
+pub use debug_log::DebugLogPanel;
+pub use device_records_panel::DeviceRecordsPanel;
+pub use elf_image_panel::ElfImagePanel;
+pub use encrypt_panel::EncryptPanel;
+pub use esptool_panel::EsptoolPanel;
+pub use example_panel::ExamplePanel;
+pub use gdb_panel::GdbPanel;
+pub use gpio_panel::GpioPanel;
+pub use image_info_view::ImageInfoView;
+pub use improv_panel::ImprovPanel;
+pub use inventory_panel::InventoryPanel;
+pub use kiosk_panel::KioskPanel;
+pub use label_panel::LabelPanel;
 pub use layout::Layout;
+pub use mpy_files_panel::MpyFilesPanel;
+pub use mpy_firmware_panel::MpyFirmwarePanel;
+pub use notifications_panel::NotificationsPanel;
+pub use openocd_panel::OpenOcdPanel;
+pub use ota_panel::OtaPanel;
+pub use ota_server_panel::OtaServerPanel;
+pub use pin_usage_panel::PinUsagePanel;
+pub use production_run_panel::ProductionRunPanel;
+pub use register_panel::RegisterPanel;
+pub use reserved_pins_panel::ReservedPinsPanel;
+pub use script_panel::ScriptPanel;
 pub use sidebar::Sidebar;
+pub use signing_panel::SigningPanel;
+pub use soak_panel::SoakPanel;
+pub use split_monitor_panel::SplitMonitorPanel;
+pub use terminal_view::TerminalView;
+pub use test_panel::TestPanel;
+pub use toast::{use_toasts, use_toasts_provider, ToastHost};
+pub use triggers_panel::TriggersPanel;
 pub mod pinout;
 pub use pinout::PinoutView;
@@ -0,0 +1,369 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct MpyInfo {
+    version: String,
+    machine: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct MpyEntry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MpyDetectArgs {
+    port_name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MpyPathArgs {
+    port_name: String,
+    path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MpyUploadArgs {
+    port_name: String,
+    directory: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MpyDownloadArgs {
+    port_name: String,
+    remote_path: String,
+}
+
+fn describe_error(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// `name` inside the board directory `dir`, as the backend joins them.
+fn join(dir: &str, name: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), name)
+}
+
+/// The directory above `dir`; the root stays the root.
+fn parent(dir: &str) -> String {
+    match dir.trim_end_matches('/').rsplit_once('/') {
+        Some((above, _)) if !above.is_empty() => above.to_string(),
+        _ => "/".to_string(),
+    }
+}
+
+/// Browses the filesystem of a MicroPython board through its raw REPL,
+/// like the Files view of Thonny. Every action opens the port on its own,
+/// so the monitor can stay connected in between.
+#[component]
+pub fn MpyFilesPanel(port_name: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut info = use_signal(|| None::<MpyInfo>);
+    let mut detected = use_signal(|| false);
+    let mut cwd = use_signal(|| "/".to_string());
+    let mut entries = use_signal(Vec::<MpyEntry>::new);
+    let mut new_folder = use_signal(String::new);
+    let mut busy = use_signal(|| false);
+    let mut status = use_signal(String::new);
+
+    // Actions take the port so they stay `Copy` for the entry rows
+    let mut list = move |port_name: String, path: String| {
+        busy.set(true);
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MpyPathArgs {
+                port_name,
+                path: path.clone(),
+            })
+            .unwrap_or(JsValue::NULL);
+            match invoke("mpy_list", args).await {
+                Ok(val) => {
+                    entries.set(serde_wasm_bindgen::from_value(val).unwrap_or_default());
+                    cwd.set(path);
+                    status.set(String::new());
+                }
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+            busy.set(false);
+        });
+    };
+
+    let detect = {
+        let port_name = port_name.clone();
+        move |_| {
+            let port_name = port_name.clone();
+            busy.set(true);
+            status.set(String::new());
+            entries.set(Vec::new());
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&MpyDetectArgs {
+                    port_name: port_name.clone(),
+                })
+                .unwrap_or(JsValue::NULL);
+                match invoke("mpy_detect", args).await {
+                    Ok(val) => {
+                        let found = serde_wasm_bindgen::from_value::<Option<MpyInfo>>(val)
+                            .ok()
+                            .flatten();
+                        detected.set(true);
+                        busy.set(false);
+                        if found.is_some() {
+                            info.set(found);
+                            list(port_name, "/".to_string());
+                        } else {
+                            info.set(None);
+                        }
+                    }
+                    Err(e) => {
+                        status.set(describe_error(&lang.peek(), e));
+                        busy.set(false);
+                    }
+                }
+            });
+        }
+    };
+
+    let mut remove = move |port_name: String, path: String| {
+        busy.set(true);
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MpyPathArgs {
+                port_name: port_name.clone(),
+                path,
+            })
+            .unwrap_or(JsValue::NULL);
+            match invoke("mpy_remove", args).await {
+                Ok(_) => list(port_name, cwd.peek().clone()),
+                Err(e) => {
+                    status.set(describe_error(&lang.peek(), e));
+                    busy.set(false);
+                }
+            }
+        });
+    };
+
+    let mut download = move |port_name: String, remote_path: String| {
+        busy.set(true);
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MpyDownloadArgs {
+                port_name,
+                remote_path,
+            })
+            .unwrap_or(JsValue::NULL);
+            match invoke("mpy_download", args).await {
+                Ok(val) => {
+                    if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                        status.set(format!("{} {}", lang.peek().mpy_files_saved(), path));
+                    }
+                }
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+            busy.set(false);
+        });
+    };
+
+    let upload = {
+        let port_name = port_name.clone();
+        move |_| {
+            let port_name = port_name.clone();
+            busy.set(true);
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&MpyUploadArgs {
+                    port_name: port_name.clone(),
+                    directory: cwd.peek().clone(),
+                })
+                .unwrap_or(JsValue::NULL);
+                match invoke("mpy_upload", args).await {
+                    Ok(_) => list(port_name, cwd.peek().clone()),
+                    Err(e) => {
+                        status.set(describe_error(&lang.peek(), e));
+                        busy.set(false);
+                    }
+                }
+            });
+        }
+    };
+
+    let mkdir = {
+        let port_name = port_name.clone();
+        move |_| {
+            let name = new_folder.read().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            let port_name = port_name.clone();
+            busy.set(true);
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&MpyPathArgs {
+                    port_name: port_name.clone(),
+                    path: join(&cwd.peek(), &name),
+                })
+                .unwrap_or(JsValue::NULL);
+                match invoke("mpy_mkdir", args).await {
+                    Ok(_) => {
+                        new_folder.set(String::new());
+                        list(port_name, cwd.peek().clone());
+                    }
+                    Err(e) => {
+                        status.set(describe_error(&lang.peek(), e));
+                        busy.set(false);
+                    }
+                }
+            });
+        }
+    };
+
+    let board = info.read().clone();
+    let at_root = cwd.read().as_str() == "/";
+    let idle = !port_name.is_empty() && !*busy.read();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                span { style: "flex: 1; font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                    if let Some(board) = &board {
+                        "MicroPython {board.version} · {board.machine}"
+                    } else if *detected.read() {
+                        "{dict.mpy_files_no_repl()}"
+                    } else {
+                        "{dict.mpy_files_hint()}"
+                    }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: !idle,
+                    onclick: detect,
+                    span { class: "material-symbols-outlined icon", "terminal" }
+                    span { class: "label", "{dict.mpy_files_btn_connect()}" }
+                }
+            }
+            if board.is_some() {
+                div { style: "display: flex; align-items: center; gap: 8px;",
+                    {
+                        let port = port_name.clone();
+                        rsx! {
+                            button {
+                                class: "md-button btn-text",
+                                disabled: !idle || at_root,
+                                onclick: move |_| list(port.clone(), parent(&cwd.peek())),
+                                span { class: "material-symbols-outlined icon", "arrow_upward" }
+                            }
+                        }
+                    }
+                    span { style: "flex: 1; font-family: monospace;", "{cwd}" }
+                    {
+                        let port = port_name.clone();
+                        rsx! {
+                            button {
+                                class: "md-button btn-text",
+                                disabled: !idle,
+                                onclick: move |_| list(port.clone(), cwd.peek().clone()),
+                                span { class: "material-symbols-outlined icon", "refresh" }
+                            }
+                        }
+                    }
+                    button {
+                        class: "md-button btn-tonal",
+                        disabled: !idle,
+                        onclick: upload,
+                        span { class: "material-symbols-outlined icon", "upload" }
+                        span { class: "label", "{dict.mpy_files_btn_upload()}" }
+                    }
+                }
+                div { style: "display: flex; flex-direction: column;",
+                    if entries.read().is_empty() {
+                        span { style: "font-size: 0.9em; color: var(--md-sys-color-outline);", "{dict.mpy_files_empty()}" }
+                    }
+                    for entry in entries.read().iter().cloned() {
+                        {
+                            let path = join(&cwd.read(), &entry.name);
+                            let (open_port, open_path) = (port_name.clone(), path.clone());
+                            let (get_port, get_path) = (port_name.clone(), path.clone());
+                            let (rm_port, rm_path) = (port_name.clone(), path);
+                            rsx! {
+                                div {
+                                    key: "{entry.name}",
+                                    style: "display: flex; align-items: center; gap: 8px; padding: 4px 0; border-bottom: 1px solid var(--md-sys-color-outline-variant);",
+                                    span { class: "material-symbols-outlined",
+                                        if entry.is_dir { "folder" } else { "description" }
+                                    }
+                                    if entry.is_dir {
+                                        span {
+                                            style: "flex: 1; cursor: pointer; color: var(--md-sys-color-primary);",
+                                            onclick: move |_| {
+                                                if idle {
+                                                    list(open_port.clone(), open_path.clone());
+                                                }
+                                            },
+                                            "{entry.name}"
+                                        }
+                                    } else {
+                                        span { style: "flex: 1;", "{entry.name}" }
+                                        span { style: "font-size: 0.85em; color: var(--md-sys-color-on-surface-variant);",
+                                            "{entry.size} B"
+                                        }
+                                        button {
+                                            class: "md-button btn-text",
+                                            disabled: !idle,
+                                            onclick: move |_| download(get_port.clone(), get_path.clone()),
+                                            span { class: "material-symbols-outlined icon", "download" }
+                                        }
+                                    }
+                                    button {
+                                        class: "md-button btn-text",
+                                        disabled: !idle,
+                                        onclick: move |_| remove(rm_port.clone(), rm_path.clone()),
+                                        span { class: "material-symbols-outlined icon", "delete" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { style: "display: flex; gap: 8px;",
+                    input {
+                        class: "md-input",
+                        style: "flex: 1;",
+                        placeholder: "{dict.mpy_files_folder_placeholder()}",
+                        value: "{new_folder}",
+                        oninput: move |evt| new_folder.set(evt.value()),
+                    }
+                    button {
+                        class: "md-button btn-text",
+                        disabled: !idle || new_folder.read().trim().is_empty(),
+                        onclick: mkdir,
+                        span { class: "material-symbols-outlined icon", "create_new_folder" }
+                        span { class: "label", "{dict.mpy_files_btn_mkdir()}" }
+                    }
+                }
+            }
+            if !status.read().is_empty() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{status}" }
+            }
+        }
+    }
+}
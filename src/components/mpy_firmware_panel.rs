@@ -0,0 +1,151 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct MpyRelease {
+    version: String,
+    date: String,
+    preview: bool,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MpyFirmwareReleasesArgs {
+    chip_model: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashMicropythonArgs {
+    port_name: String,
+    chip_model: String,
+    version: Option<String>,
+    sha256: Option<String>,
+}
+
+fn describe_error(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Fetches an official MicroPython build for the detected chip from
+/// micropython.org and flashes it at the right offset.
+#[component]
+pub fn MpyFirmwarePanel(port_name: String, chip_model: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    // Empty picks the newest release
+    let mut version = use_signal(String::new);
+    let mut sha256 = use_signal(String::new);
+    let mut busy = use_signal(|| false);
+    let mut status = use_signal(String::new);
+
+    let releases = use_resource(use_reactive!(|chip_model| async move {
+        if chip_model.is_empty() {
+            return Err(String::new());
+        }
+        let args = serde_wasm_bindgen::to_value(&MpyFirmwareReleasesArgs { chip_model })
+            .unwrap_or(JsValue::NULL);
+        match invoke("mpy_firmware_releases", args).await {
+            Ok(val) => {
+                Ok(serde_wasm_bindgen::from_value::<Vec<MpyRelease>>(val).unwrap_or_default())
+            }
+            Err(e) => Err(describe_error(&lang.peek(), e)),
+        }
+    }));
+    let (releases, load_error) = match releases.read().clone() {
+        Some(Ok(releases)) => (releases, None),
+        Some(Err(message)) => (Vec::new(), Some(message).filter(|m| !m.is_empty())),
+        None => (Vec::new(), None),
+    };
+
+    let flash = {
+        let port_name = port_name.clone();
+        let chip_model = chip_model.clone();
+        move |_| {
+            let args = serde_wasm_bindgen::to_value(&FlashMicropythonArgs {
+                port_name: port_name.clone(),
+                chip_model: chip_model.clone(),
+                version: Some(version.read().clone()).filter(|v| !v.is_empty()),
+                sha256: Some(sha256.read().trim().to_string()).filter(|s| !s.is_empty()),
+            })
+            .unwrap_or(JsValue::NULL);
+            busy.set(true);
+            status.set(lang.peek().mpy_firmware_downloading().to_string());
+            spawn(async move {
+                // Completion and flasher failures also arrive as toasts
+                match invoke("flash_micropython", args).await {
+                    Ok(_) => status.set(lang.peek().mpy_firmware_flashed().to_string()),
+                    Err(e) => status.set(describe_error(&lang.peek(), e)),
+                }
+                busy.set(false);
+            });
+        }
+    };
+
+    let ready = !port_name.is_empty() && !releases.is_empty() && !*busy.read();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                if !status.read().is_empty() {
+                    "{status}"
+                } else if let Some(message) = &load_error {
+                    "{message}"
+                } else {
+                    "{dict.mpy_firmware_hint()}"
+                }
+            }
+            select {
+                class: "md-input",
+                disabled: releases.is_empty() || *busy.read(),
+                value: "{version}",
+                onchange: move |evt| version.set(evt.value()),
+                option { value: "", "{dict.mpy_firmware_latest()}" }
+                for release in releases.iter() {
+                    option {
+                        key: "{release.version}",
+                        value: "{release.version}",
+                        if release.preview {
+                            "{release.version} ({release.date}, {dict.mpy_firmware_preview()})"
+                        } else {
+                            "{release.version} ({release.date})"
+                        }
+                    }
+                }
+            }
+            input {
+                class: "md-input",
+                placeholder: "{dict.mpy_firmware_sha256_placeholder()}",
+                value: "{sha256}",
+                oninput: move |evt| sha256.set(evt.value()),
+            }
+            div { style: "display: flex; justify-content: flex-end;",
+                button {
+                    class: "md-button btn-filled",
+                    disabled: !ready,
+                    onclick: flash,
+                    span { class: "material-symbols-outlined icon", "download" }
+                    span { class: "label", "{dict.mpy_firmware_btn_flash()}" }
+                }
+            }
+        }
+    }
+}
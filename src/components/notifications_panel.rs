@@ -0,0 +1,99 @@
+use crate::i18n::Dict;
+use crate::settings::{update_settings, DesktopNotifications, Settings};
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+/// Sets one event's switch in the wanted notifications
+type Toggle = fn(&mut DesktopNotifications, bool);
+
+/// Picks which events also raise an OS notification. The toasts inside the
+/// app are shown either way.
+#[component]
+pub fn NotificationsPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    let mut error = use_signal(|| None::<String>);
+
+    let mut save = move |wanted: DesktopNotifications| {
+        let mut updated = settings.peek().clone();
+        updated.desktop_notifications = wanted;
+        error.set(None);
+        spawn(async move {
+            match update_settings(updated).await {
+                Ok(saved) => settings.set(saved),
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    let wanted = settings.read().desktop_notifications.clone();
+    let events: [(&str, bool, Toggle); 5] = [
+        (
+            dict.notifications_device_connected(),
+            wanted.device_connected,
+            |w, on| w.device_connected = on,
+        ),
+        (
+            dict.notifications_device_disconnected(),
+            wanted.device_disconnected,
+            |w, on| w.device_disconnected = on,
+        ),
+        (
+            dict.notifications_flash_completed(),
+            wanted.flash_completed,
+            |w, on| w.flash_completed = on,
+        ),
+        (
+            dict.notifications_flash_failed(),
+            wanted.flash_failed,
+            |w, on| w.flash_failed = on,
+        ),
+        (
+            dict.notifications_monitor_triggers(),
+            wanted.monitor_triggers,
+            |w, on| w.monitor_triggers = on,
+        ),
+    ];
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.notifications_hint()}" }
+            for (text, checked, set) in events {
+                label {
+                    key: "{text}",
+                    style: "display: flex; align-items: center; gap: 8px; font-size: 0.9em;",
+                    input {
+                        r#type: "checkbox",
+                        checked,
+                        onchange: {
+                            let wanted = wanted.clone();
+                            move |evt: Event<FormData>| {
+                                let mut wanted = wanted.clone();
+                                set(&mut wanted, evt.checked());
+                                save(wanted);
+                            }
+                        },
+                    }
+                    "{text}"
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+// Matches what the backend keeps
+const LOG_LIMIT: usize = 500;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct JtagProbe {
+    kind: String,
+    vid_pid: String,
+    serial_number: Option<String>,
+    product_name: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct OpenOcdStatus {
+    running: bool,
+    ready: bool,
+    gdb_port: u16,
+    exit_code: Option<i32>,
+    log: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenOcdStartArgs {
+    chip_model: String,
+}
+
+async fn fetch_status() -> Option<OpenOcdStatus> {
+    let val = invoke("openocd_status", JsValue::NULL).await.ok()?;
+    serde_wasm_bindgen::from_value(val).ok()
+}
+
+async fn fetch_probes() -> Vec<JtagProbe> {
+    match invoke("openocd_probes", JsValue::NULL).await {
+        Ok(val) => serde_wasm_bindgen::from_value(val).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn probe_label(dict: &Dict, probe: &JtagProbe) -> String {
+    let kind = if probe.kind == "usb_jtag" {
+        dict.openocd_probe_usb_jtag()
+    } else {
+        dict.openocd_probe_esp_prog()
+    };
+    let mut label = format!("{} ({})", kind, probe.vid_pid);
+    if let Some(serial) = probe.serial_number.as_ref().or(probe.product_name.as_ref()) {
+        label.push_str(&format!(" · {}", serial));
+    }
+    label
+}
+
+/// Runs OpenOCD on the JTAG adapter of the board for GDB to attach to, and
+/// shows what it prints.
+#[component]
+pub fn OpenOcdPanel(chip_model: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut status = use_signal(OpenOcdStatus::default);
+    let mut probes = use_signal(Vec::<JtagProbe>::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let refresh = move |_| {
+        spawn(async move {
+            probes.set(fetch_probes().await);
+        });
+    };
+
+    let start_chip = chip_model.clone();
+    let toggle = move |_| {
+        let running = status.read().running;
+        let args = serde_wasm_bindgen::to_value(&OpenOcdStartArgs {
+            chip_model: start_chip.clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        error.set(None);
+        spawn(async move {
+            let result = if running {
+                invoke("openocd_stop", JsValue::NULL).await
+            } else {
+                invoke("openocd_start", args).await
+            };
+            match result {
+                Ok(val) => {
+                    if let Ok(new_status) = serde_wasm_bindgen::from_value(val) {
+                        status.set(new_status);
+                    }
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    // OpenOCD keeps running when the tab is left; pick it up again
+    use_effect(move || {
+        spawn(async move {
+            probes.set(fetch_probes().await);
+            if let Some(current) = fetch_status().await {
+                status.set(current);
+            }
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: String,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    let mut current = status.write();
+                    current.ready |= e.payload.contains("for gdb connections");
+                    if current.log.len() == LOG_LIMIT {
+                        current.log.remove(0);
+                    }
+                    current.log.push(e.payload);
+                }
+            });
+            match listen("openocd-log", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // The process may have exited on its own; the log says why
+    let poll_status = move |_| {
+        spawn(async move {
+            if let Some(current) = fetch_status().await {
+                status.set(current);
+            }
+        });
+    };
+
+    let current = status.read().clone();
+    let can_start = current.running || !chip_model.is_empty();
+    let state = if current.ready {
+        format!(
+            "{} · target extended-remote :{}",
+            dict.openocd_ready(),
+            current.gdb_port
+        )
+    } else if current.running {
+        dict.openocd_starting().to_string()
+    } else if let Some(code) = current.exit_code {
+        format!("{} ({})", dict.openocd_exited(), code)
+    } else {
+        String::new()
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.openocd_hint()}" }
+            div { style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap;",
+                if probes.read().is_empty() {
+                    span { style: "color: var(--md-sys-color-outline);", "{dict.openocd_no_probes()}" }
+                }
+                for (i, probe) in probes.read().iter().enumerate() {
+                    span {
+                        key: "{i}",
+                        style: "padding: 2px 8px; border-radius: 8px; background: var(--md-sys-color-surface-variant); font-size: 0.9em;",
+                        "{probe_label(&dict, probe)}"
+                    }
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: refresh,
+                    span { class: "material-symbols-outlined icon", "refresh" }
+                    span { class: "label", "{dict.openocd_btn_probes()}" }
+                }
+            }
+            div { style: "display: flex; gap: 8px; align-items: center;",
+                button {
+                    class: if current.running { "md-button btn-tonal" } else { "md-button btn-filled" },
+                    disabled: !can_start,
+                    title: if chip_model.is_empty() { "{dict.openocd_no_chip()}" } else { "" },
+                    onclick: toggle,
+                    span { class: "material-symbols-outlined icon", if current.running { "stop" } else { "bug_report" } }
+                    span { class: "label",
+                        if current.running { "{dict.openocd_btn_stop()}" } else { "{dict.openocd_btn_start()}" }
+                    }
+                }
+                if current.running {
+                    button {
+                        class: "md-button btn-text",
+                        title: "{dict.openocd_btn_status()}",
+                        onclick: poll_status,
+                        span { class: "material-symbols-outlined icon", "sync" }
+                    }
+                }
+                span {
+                    style: if current.ready { "color: var(--md-sys-color-primary);" } else { "color: var(--md-sys-color-on-surface-variant);" },
+                    "{state}"
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            div { style: "display: flex; flex-direction: column; gap: 2px; max-height: 240px; overflow-y: auto; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em; white-space: pre-wrap;",
+                if current.log.is_empty() {
+                    span { style: "color: var(--md-sys-color-outline);", "{dict.openocd_no_log()}" }
+                }
+                for (i, line) in current.log.iter().enumerate() {
+                    div {
+                        key: "{i}",
+                        style: if line.starts_with("Error") { "color: var(--md-sys-color-error);" } else { "" },
+                        "{line}"
+                    }
+                }
+            }
+        }
+    }
+}
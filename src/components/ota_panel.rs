@@ -0,0 +1,228 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+// Port ArduinoOTA listens on by default
+const DEFAULT_OTA_PORT: &str = "3232";
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    kind: String,
+    state: String,
+    phase: String,
+    current: u64,
+    total: u64,
+    port_name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OtaUploadArgs {
+    host: String,
+    port: Option<u16>,
+    password: Option<String>,
+    firmware_path: String,
+    filesystem: bool,
+}
+
+fn describe_error(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => match dict.error_hint(&err.kind) {
+            Some(hint) => format!("{}: {} ({})", dict.error_kind(&err.kind), err.message, hint),
+            None => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        },
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Uploads over Wi-Fi to boards running ArduinoOTA, no cable needed.
+#[component]
+pub fn OtaPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut host = use_signal(String::new);
+    let mut port = use_signal(|| DEFAULT_OTA_PORT.to_string());
+    let mut password = use_signal(String::new);
+    let mut firmware_path = use_signal(String::new);
+    let mut filesystem = use_signal(|| false);
+    let mut uploading = use_signal(|| false);
+    let mut progress = use_signal(|| 0.0);
+    let mut phase = use_signal(|| "connecting".to_string());
+    let mut status = use_signal(String::new);
+
+    let browse = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_firmware_file", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    firmware_path.set(path);
+                }
+            }
+        });
+    };
+
+    let upload = move |_| {
+        let args = serde_wasm_bindgen::to_value(&OtaUploadArgs {
+            host: host.read().trim().to_string(),
+            port: port.read().trim().parse().ok(),
+            password: Some(password.read().clone()).filter(|p| !p.is_empty()),
+            firmware_path: firmware_path.read().clone(),
+            filesystem: *filesystem.read(),
+        })
+        .unwrap_or(JsValue::NULL);
+        uploading.set(true);
+        progress.set(0.0);
+        phase.set("connecting".to_string());
+        status.set(String::new());
+        spawn(async move {
+            // Success and failure also arrive as `ota_*` toasts
+            match invoke("ota_upload", args).await {
+                Ok(_) => status.set(lang.peek().ota_uploaded().to_string()),
+                Err(e) => status.set(describe_error(&lang.peek(), e)),
+            }
+            uploading.set(false);
+        });
+    };
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                let task = e.payload;
+                if task.kind != "ota"
+                    || task.state != "running"
+                    || !*uploading.peek()
+                    || task.port_name.as_deref() != Some(host.peek().trim())
+                {
+                    return;
+                }
+                if task.total > 0 {
+                    progress.set(task.current as f64 * 100.0 / task.total as f64);
+                }
+                phase.set(task.phase);
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let percent = progress.read().floor() as u64;
+    let phase_label = dict.flash_phase(&phase.read()).to_string();
+    let ready = !host.read().trim().is_empty() && !firmware_path.read().is_empty();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.ota_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    placeholder: "{dict.ota_host_placeholder()}",
+                    value: "{host}",
+                    oninput: move |evt| host.set(evt.value()),
+                }
+                input {
+                    class: "md-input",
+                    style: "width: 80px;",
+                    title: "{dict.port()}",
+                    value: "{port}",
+                    oninput: move |evt| port.set(evt.value()),
+                }
+            }
+            input {
+                class: "md-input",
+                r#type: "password",
+                placeholder: "{dict.ota_password_placeholder()}",
+                value: "{password}",
+                oninput: move |evt| password.set(evt.value()),
+            }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    readonly: true,
+                    placeholder: "{dict.devices_placeholder_firmware_file()}",
+                    value: "{firmware_path}",
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: browse,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+            }
+            label { style: "display: flex; align-items: center; gap: 8px; font-size: 0.9em;",
+                input {
+                    r#type: "checkbox",
+                    checked: *filesystem.read(),
+                    onchange: move |evt| filesystem.set(evt.checked()),
+                }
+                "{dict.ota_filesystem()}"
+            }
+            if *uploading.read() {
+                div { style: "display: flex; flex-direction: column; gap: 4px;",
+                    span { style: "font-size: 0.9em;", "{phase_label} {percent}%" }
+                    div { style: "height: 4px; border-radius: 2px; background: var(--md-sys-color-surface-variant);",
+                        div { style: "height: 100%; width: {percent}%; border-radius: 2px; background: var(--md-sys-color-primary);" }
+                    }
+                }
+            } else if !status.read().is_empty() {
+                span { style: "font-size: 0.9em;", "{status}" }
+            }
+            div { style: "display: flex; justify-content: flex-end;",
+                button {
+                    class: "md-button btn-filled",
+                    disabled: *uploading.read() || !ready,
+                    onclick: upload,
+                    span { class: "material-symbols-outlined icon", "wifi" }
+                    span { class: "label", "{dict.ota_btn_upload()}" }
+                }
+            }
+        }
+    }
+}
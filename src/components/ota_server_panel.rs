@@ -0,0 +1,255 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+const DEFAULT_SERVER_PORT: &str = "8070";
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct OtaDownload {
+    time: u64,
+    client: String,
+    path: String,
+    range: Option<String>,
+    status: u16,
+    bytes: u64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct OtaServerStatus {
+    running: bool,
+    url: Option<String>,
+    qr_svg: Option<String>,
+    file_name: Option<String>,
+    size: u64,
+    downloads: Vec<OtaDownload>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OtaServerStartArgs {
+    firmware_path: String,
+    port: u16,
+}
+
+async fn fetch_status() -> Option<OtaServerStatus> {
+    let val = invoke("ota_server_status", JsValue::NULL).await.ok()?;
+    serde_wasm_bindgen::from_value(val).ok()
+}
+
+/// `HH:MM:SS` in local time.
+fn clock(secs: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(secs as f64 * 1000.0));
+    format!(
+        "{:02}:{:02}:{:02}",
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds()
+    )
+}
+
+/// Switches the LAN OTA server on and off and lists who downloaded what.
+#[component]
+pub fn OtaServerPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut status = use_signal(OtaServerStatus::default);
+    let mut firmware_path = use_signal(String::new);
+    let mut port = use_signal(|| DEFAULT_SERVER_PORT.to_string());
+    let mut error = use_signal(|| None::<String>);
+
+    let browse = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_firmware_file", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    firmware_path.set(path);
+                }
+            }
+        });
+    };
+
+    let toggle = move |_| {
+        let running = status.read().running;
+        let args = serde_wasm_bindgen::to_value(&OtaServerStartArgs {
+            firmware_path: firmware_path.read().clone(),
+            port: port.read().trim().parse().unwrap_or(8070),
+        })
+        .unwrap_or(JsValue::NULL);
+        error.set(None);
+        spawn(async move {
+            let result = if running {
+                invoke("ota_server_stop", JsValue::NULL).await
+            } else {
+                invoke("ota_server_start", args).await
+            };
+            match result {
+                Ok(val) => {
+                    if let Ok(new_status) = serde_wasm_bindgen::from_value(val) {
+                        status.set(new_status);
+                    }
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    // The server keeps running when the tab is left; pick it up again
+    use_effect(move || {
+        spawn(async move {
+            if let Some(current) = fetch_status().await {
+                status.set(current);
+            }
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: OtaDownload,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    status.write().downloads.push(e.payload);
+                }
+            });
+            match listen("ota-download", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let current = status.read().clone();
+    let can_start = current.running || !firmware_path.read().is_empty();
+    let served = format!(
+        "{} · {} B",
+        current.file_name.as_deref().unwrap_or(""),
+        current.size
+    );
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.ota_server_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    readonly: true,
+                    disabled: current.running,
+                    placeholder: "{dict.devices_placeholder_firmware_file()}",
+                    value: "{firmware_path}",
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: current.running,
+                    onclick: browse,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+                input {
+                    class: "md-input",
+                    style: "width: 80px;",
+                    title: "{dict.port()}",
+                    disabled: current.running,
+                    value: "{port}",
+                    oninput: move |evt| port.set(evt.value()),
+                }
+                button {
+                    class: if current.running { "md-button btn-tonal" } else { "md-button btn-filled" },
+                    disabled: !can_start,
+                    onclick: toggle,
+                    span { class: "material-symbols-outlined icon", if current.running { "stop" } else { "play_arrow" } }
+                    span { class: "label",
+                        if current.running { "{dict.ota_server_btn_stop()}" } else { "{dict.ota_server_btn_start()}" }
+                    }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let (true, Some(url)) = (current.running, &current.url) {
+                div { style: "display: flex; gap: 16px; align-items: center;",
+                    if let Some(svg) = &current.qr_svg {
+                        div {
+                            style: "background: white; padding: 8px; border-radius: 8px; line-height: 0;",
+                            dangerous_inner_html: "{svg}",
+                        }
+                    }
+                    div { style: "display: flex; flex-direction: column; gap: 4px;",
+                        span { style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; user-select: all;", "{url}" }
+                        span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                            "{served}"
+                        }
+                        span { style: "font-size: 0.85em; color: var(--md-sys-color-tertiary);", "{dict.ota_server_http_note()}" }
+                    }
+                }
+            }
+            div { style: "display: flex; flex-direction: column; gap: 2px; max-height: 200px; overflow-y: auto; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em;",
+                if current.downloads.is_empty() {
+                    span { style: "color: var(--md-sys-color-outline);", "{dict.ota_server_no_downloads()}" }
+                }
+                for (i, download) in current.downloads.iter().enumerate().rev() {
+                    {
+                        let line = format!(
+                            "{}  {}  {}  {}  {}  {} B",
+                            clock(download.time),
+                            download.client,
+                            download.status,
+                            download.path,
+                            download.range.as_deref().unwrap_or(""),
+                            download.bytes
+                        );
+                        rsx! {
+                            div {
+                                key: "{i}",
+                                style: if download.status < 300 { "" } else { "color: var(--md-sys-color-error);" },
+                                "{line}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::components::pinout::role_color;
+use crate::i18n::Dict;
+use crate::settings::{update_settings, PinUsage, Settings};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+const ROLES: [&str; 5] = ["i2c", "spi", "uart", "pwm", "gpio"];
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct LoadPinUsageArgs {
+    path: String,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Edits the pins the firmware uses, shown in colour on the pinout. The
+/// list is typed in or imported from a project's `sdkconfig` or a JSON
+/// pin list, and kept in the settings.
+#[component]
+pub fn PinUsagePanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    let mut gpio = use_signal(String::new);
+    let mut role = use_signal(|| "gpio".to_string());
+    let mut signal = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let mut save = move |pin_usage: Vec<PinUsage>| {
+        let mut updated = settings.peek().clone();
+        updated.pin_usage = pin_usage;
+        error.set(None);
+        spawn(async move {
+            match update_settings(updated).await {
+                Ok(saved) => settings.set(saved),
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let import = move |_| {
+        spawn(async move {
+            let picked = match invoke("pick_pin_usage", JsValue::NULL).await {
+                Ok(val) => serde_wasm_bindgen::from_value::<Option<String>>(val)
+                    .ok()
+                    .flatten(),
+                Err(_) => None,
+            };
+            let Some(path) = picked else {
+                return;
+            };
+            let args =
+                serde_wasm_bindgen::to_value(&LoadPinUsageArgs { path }).unwrap_or(JsValue::NULL);
+            match invoke("load_pin_usage", args).await {
+                Ok(val) => {
+                    if let Ok(pins) = serde_wasm_bindgen::from_value::<Vec<PinUsage>>(val) {
+                        save(pins);
+                    }
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let add = move |_| {
+        let Ok(number) = gpio.read().trim().parse::<u32>() else {
+            return;
+        };
+        let mut pins = settings.peek().pin_usage.clone();
+        pins.push(PinUsage {
+            gpio: number,
+            role: role.read().clone(),
+            signal: signal.read().trim().to_string(),
+        });
+        pins.sort_by_key(|p| p.gpio);
+        gpio.set(String::new());
+        signal.set(String::new());
+        save(pins);
+    };
+
+    let pins = settings.read().pin_usage.clone();
+    let ready = gpio.read().trim().parse::<u32>().is_ok();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.pin_usage_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                button {
+                    class: "md-button btn-tonal",
+                    onclick: import,
+                    span { class: "material-symbols-outlined icon", "upload_file" }
+                    span { class: "label", "{dict.pin_usage_btn_import()}" }
+                }
+                button {
+                    class: "md-button btn-text",
+                    disabled: pins.is_empty(),
+                    onclick: move |_| save(Vec::new()),
+                    span { class: "material-symbols-outlined icon", "clear_all" }
+                    span { class: "label", "{dict.pin_usage_btn_clear()}" }
+                }
+            }
+            if pins.is_empty() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-outline);", "{dict.pin_usage_empty()}" }
+            }
+            for (i, pin) in pins.iter().enumerate() {
+                div {
+                    key: "{i}",
+                    style: "display: flex; align-items: center; gap: 8px; font-size: 0.9em;",
+                    span { style: "width: 10px; height: 10px; border-radius: 50%; flex-shrink: 0; background: {role_color(&pin.role)};" }
+                    span { style: "width: 64px;", "GPIO{pin.gpio}" }
+                    span { style: "width: 64px; color: var(--md-sys-color-on-surface-variant);", "{dict.pin_role(&pin.role)}" }
+                    span { style: "flex: 1; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; word-break: break-all;",
+                        "{pin.signal}"
+                    }
+                    button {
+                        class: "md-button btn-text",
+                        onclick: {
+                            let pins = pins.clone();
+                            move |_| {
+                                let mut pins = pins.clone();
+                                pins.remove(i);
+                                save(pins);
+                            }
+                        },
+                        span { class: "material-symbols-outlined icon", "delete" }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "width: 90px;",
+                    r#type: "number",
+                    min: "0",
+                    placeholder: "GPIO",
+                    value: "{gpio}",
+                    oninput: move |evt| gpio.set(evt.value()),
+                }
+                select {
+                    class: "md-input",
+                    style: "width: 110px;",
+                    value: "{role}",
+                    onchange: move |evt| role.set(evt.value()),
+                    for r in ROLES {
+                        option { key: "{r}", value: "{r}", "{dict.pin_role(r)}" }
+                    }
+                }
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    placeholder: "{dict.pin_usage_signal_placeholder()}",
+                    value: "{signal}",
+                    oninput: move |evt| signal.set(evt.value()),
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: !ready,
+                    onclick: add,
+                    span { class: "material-symbols-outlined icon", "add" }
+                    span { class: "label", "{dict.pin_usage_btn_add()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+        }
+    }
+}
@@ -1,34 +1,95 @@
+use crate::i18n::Dict;
+use crate::settings::PinUsage;
 use dioxus::prelude::*;
+use dioxus::web::WebEventExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct BoardDefinition {
+    board: String,
+    pins: Vec<PinInfo>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct PinInfo {
+    svg_id: String,
+    number: u32,
+    name: String,
+    gpio: Option<u32>,
+    #[serde(default)]
+    functions: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    note: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBoardDefinitionArgs {
+    chip_model: String,
+}
+
+/// Drawing under public/boards for a chip model, in either the `esp32s3` or
+/// `ESP32-S3` spelling. Chips without one get no picture rather than
+/// another chip's pinout.
+fn board_svg(chip_model: &str) -> Option<&'static str> {
+    match chip_model.to_ascii_lowercase().replace('-', "").as_str() {
+        "esp32s3" => Some("esp32-s3.svg"),
+        "esp32c6" => Some("esp32-c6.svg"),
+        "esp32h2" => Some("esp32-h2.svg"),
+        "esp32c2" => Some("esp32-c2.svg"),
+        "esp32p4" => Some("esp32-p4.svg"),
+        _ => None,
+    }
+}
+
+/// Colour of a pin role on the pinout and in the pin usage list.
+pub fn role_color(role: &str) -> &'static str {
+    match role {
+        "i2c" => "#42a5f5",
+        "spi" => "#ab47bc",
+        "uart" => "#ffa726",
+        "pwm" => "#ef5350",
+        _ => "#26a69a",
+    }
+}
+
+/// `gpio_levels` colours pins by their live level (GPIO number to high),
+/// e.g. as read through the GPIO helper; `pin_usage` colours them by what
+/// the firmware uses them for. Live levels win where both apply.
 #[component]
-pub fn PinoutView(chip_model: String, connection_type: Option<String>) -> Element {
-    // Normalize chip model string for matching
-    let model_upper = chip_model.to_uppercase();
-
-    let svg_filename = if model_upper.contains("S3") {
-        "esp32-s3.svg"
-    } else if model_upper.contains("C3") {
-        "esp32-c3.svg"
-    } else if model_upper.contains("S2") {
-        "esp32-s2.svg"
-    } else if model_upper.contains("ESP32") {
-        "esp32-s3.svg"
-    } else {
-        "esp32-s3.svg"
-    };
+pub fn PinoutView(
+    chip_model: String,
+    connection_type: Option<String>,
+    #[props(default)] gpio_levels: HashMap<u32, bool>,
+    #[props(default)] pin_usage: Vec<PinUsage>,
+) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
 
     // Construct absolute path using window origin to avoid "RelativeUrlWithoutBase" error
     let origin = web_sys::window()
         .and_then(|w| w.location().origin().ok())
         .unwrap_or_else(|| "http://localhost:1420".to_string()); // Fallback for dev
 
-    let svg_path = format!("{}/boards/{}", origin, svg_filename);
+    let svg_path = board_svg(&chip_model).map(|file| format!("{}/boards/{}", origin, file));
+    let has_drawing = svg_path.is_some();
 
     // State to hold the fetched SVG content
     let mut svg_content = use_signal(|| "".to_string());
-    use_resource(move || {
-        let path = svg_path.clone();
-        async move {
+    use_resource(use_reactive!(|svg_path| async move {
+        svg_content.set("".to_string());
+        if let Some(path) = svg_path {
             web_sys::console::log_1(&format!("Fetching SVG from: {}", path).into());
             match reqwest::get(&path).await {
                 Ok(response) => {
@@ -50,11 +111,76 @@ pub fn PinoutView(chip_model: String, connection_type: Option<String>) -> Elemen
                 }
             }
         }
-    });
+    }));
+
+    // Pin metadata; boards without a definition stay a plain picture
+    let board = use_resource(use_reactive!(|chip_model| async move {
+        let args = serde_wasm_bindgen::to_value(&GetBoardDefinitionArgs { chip_model })
+            .unwrap_or(JsValue::NULL);
+        match invoke("get_board_definition", args).await {
+            Ok(val) => serde_wasm_bindgen::from_value::<Option<BoardDefinition>>(val)
+                .ok()
+                .flatten(),
+            Err(e) => {
+                web_sys::console::error_1(&e);
+                None
+            }
+        }
+    }));
+    // Group id of the clicked pin in the drawing
+    let mut selected = use_signal(|| None::<String>);
+
+    let (board_name, pins) = board
+        .read()
+        .clone()
+        .flatten()
+        .map(|b| (b.board, b.pins))
+        .unwrap_or_default();
+    let selected_pin = selected
+        .read()
+        .as_ref()
+        .and_then(|id| pins.iter().find(|pin| &pin.svg_id == id))
+        .cloned();
 
     let mut css_rules =
         "#pinout-container svg { width: 100%; height: 100%; object-fit: contain; } ".to_string();
 
+    if !pins.is_empty() {
+        let groups = pins
+            .iter()
+            .map(|pin| format!("#pinout-container #{}", pin.svg_id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        css_rules.push_str(&format!("{} {{ cursor: pointer; }} ", groups));
+    }
+    for pin in &pins {
+        if let Some(usage) = pin
+            .gpio
+            .and_then(|gpio| pin_usage.iter().find(|u| u.gpio == gpio))
+        {
+            css_rules.push_str(&format!(
+                "#pinout-container #{id} circle, #pinout-container #{id} rect {{ fill: {color} !important; stroke: {color} !important; }} #pinout-container #{id} text {{ fill: {color} !important; }} ",
+                id = pin.svg_id,
+                color = role_color(&usage.role)
+            ));
+        }
+    }
+    for pin in &pins {
+        if let Some(high) = pin.gpio.and_then(|gpio| gpio_levels.get(&gpio)) {
+            let color = if *high { "#4caf50" } else { "#757575" };
+            css_rules.push_str(&format!(
+                "#pinout-container #{id} circle {{ fill: {color} !important; stroke: {color} !important; r: 3.5px; }} ",
+                id = pin.svg_id
+            ));
+        }
+    }
+    if let Some(pin) = &selected_pin {
+        css_rules.push_str(&format!(
+            "#pinout-container #{id} text {{ fill: #ffb300 !important; font-weight: bold; }} #pinout-container #{id} path, #pinout-container #{id} circle {{ stroke: #ffb300 !important; stroke-width: 2px; }} ",
+            id = pin.svg_id
+        ));
+    }
+
     if let Some(conn) = connection_type {
         if conn == "native_usb" {
             css_rules.push_str("#USB rect { fill: #4caf50 !important; stroke: #81c784 !important; stroke-width: 2px; } #USB text { fill: #4caf50 !important; font-weight: bold; }");
@@ -70,13 +196,76 @@ pub fn PinoutView(chip_model: String, connection_type: Option<String>) -> Elemen
             // Inject dynamic styles for highlighting and sizing
             style { "{css_rules}" }
 
+            if !has_drawing {
+                div { style: "color: #999; padding: 32px;", "{dict.pinout_no_drawing()} {chip_model}" }
+            }
+
             div {
                 style: "width: 100%; height: 100%; padding: 16px; box-sizing: border-box; display: flex; justify-content: center;",
                 // Render SVG string
                 div {
                     id: "pinout-container",
                     dangerous_inner_html: "{svg_content}",
-                    style: "width: 100%; height: 100%; display: flex; justify-content: center; align-items: center;"
+                    style: "width: 100%; height: 100%; display: flex; justify-content: center; align-items: center;",
+                    onclick: move |evt| {
+                        // The drawing is injected markup, so the pin comes from the DOM target
+                        let id = evt
+                            .data()
+                            .try_as_web_event()
+                            .and_then(|e| e.target())
+                            .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                            .and_then(|el| el.closest("g[id]").ok().flatten())
+                            .map(|group| group.id());
+                        let known = board
+                            .read()
+                            .clone()
+                            .flatten()
+                            .is_some_and(|b| b.pins.iter().any(|pin| Some(&pin.svg_id) == id.as_ref()));
+                        selected.set(if known { id } else { None });
+                    },
+                }
+            }
+
+            if !pins.is_empty() {
+                div { style: "width: 220px; flex-shrink: 0; align-self: stretch; padding: 16px; box-sizing: border-box; border-left: 1px solid #333; color: #d4d4d4; display: flex; flex-direction: column; gap: 8px; font-size: 0.9em;",
+                    div { style: "color: #999; font-size: 0.8em;", "{board_name}" }
+                    if let Some(pin) = selected_pin {
+                        div { style: "font-size: 1.2em; font-weight: 500;",
+                            "{pin.number}. {pin.name}"
+                        }
+                        if let Some(gpio) = pin.gpio {
+                            div { style: "color: #999;", "GPIO{gpio}" }
+                            for (i, usage) in pin_usage.iter().filter(|u| u.gpio == gpio).enumerate() {
+                                div {
+                                    key: "{i}",
+                                    style: "display: flex; align-items: center; gap: 6px; font-size: 0.85em;",
+                                    span { style: "width: 10px; height: 10px; border-radius: 50%; background: {role_color(&usage.role)};" }
+                                    span { "{dict.pin_role(&usage.role)}" }
+                                    span { style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; color: #bbb; word-break: break-all;", "{usage.signal}" }
+                                }
+                            }
+                        }
+                        if !pin.tags.is_empty() {
+                            div { style: "display: flex; flex-wrap: wrap; gap: 4px;",
+                                for tag in pin.tags.iter() {
+                                    span { key: "{tag}", class: "pin-tag pin-tag-{tag}", "{dict.pin_tag(tag)}" }
+                                }
+                            }
+                        }
+                        if !pin.functions.is_empty() {
+                            div { style: "color: #999; font-size: 0.85em;", "{dict.pinout_functions()}" }
+                            div { style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em; display: flex; flex-direction: column; gap: 2px;",
+                                for function in pin.functions.iter() {
+                                    span { key: "{function}", "{function}" }
+                                }
+                            }
+                        }
+                        if let Some(note) = &pin.note {
+                            div { style: "font-size: 0.85em; color: #bbb;", "{note}" }
+                        }
+                    } else {
+                        div { style: "color: #999;", "{dict.pinout_hint()}" }
+                    }
                 }
             }
         }
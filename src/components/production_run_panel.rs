@@ -0,0 +1,261 @@
+use crate::i18n::Dict;
+use crate::settings::{update_settings, Settings};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ProductionRun {
+    name: String,
+    records: u32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct FailureReason {
+    reason: String,
+    count: u32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct DurationStats {
+    min_ms: u64,
+    median_ms: u64,
+    mean_ms: u64,
+    max_ms: u64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct RunReport {
+    attempts: u32,
+    units: u32,
+    passed: u32,
+    failed: u32,
+    failures: Vec<FailureReason>,
+    durations: Option<DurationStats>,
+}
+
+#[derive(Serialize)]
+struct RunArgs {
+    run: String,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+// `ms` as seconds with one decimal
+fn seconds(ms: u64) -> String {
+    format!("{:.1} s", ms as f64 / 1000.0)
+}
+
+/// Production runs in the device database: the run new records go to, and
+/// for a run picked here its yield, why boards failed and how long a
+/// passing attempt took, exportable as CSV and as a text report.
+#[component]
+pub fn ProductionRunPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    let mut current = use_signal(|| settings.peek().production_run.clone());
+    let mut runs = use_signal(Vec::<ProductionRun>::new);
+    let mut selected = use_signal(|| settings.peek().production_run.clone());
+    let mut report = use_signal(|| None::<RunReport>);
+    let mut error = use_signal(|| None::<String>);
+    let mut exported = use_signal(|| None::<String>);
+
+    let load_report = move || {
+        let args = serde_wasm_bindgen::to_value(&RunArgs {
+            run: selected.peek().clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        spawn(async move {
+            match invoke("run_report", args).await {
+                Ok(val) => {
+                    report.set(serde_wasm_bindgen::from_value(val).ok());
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let load = move || {
+        spawn(async move {
+            match invoke("production_runs", JsValue::NULL).await {
+                Ok(val) => {
+                    if let Ok(found) = serde_wasm_bindgen::from_value(val) {
+                        runs.set(found);
+                    }
+                    load_report();
+                }
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    use_effect(load);
+
+    let save_current = move || {
+        let mut updated = settings.peek().clone();
+        updated.production_run = current.peek().trim().to_string();
+        if updated == *settings.peek() {
+            return;
+        }
+        spawn(async move {
+            match update_settings(updated).await {
+                Ok(saved) => {
+                    selected.set(saved.production_run.clone());
+                    settings.set(saved);
+                    load();
+                }
+                Err(e) => {
+                    current.set(settings.peek().production_run.clone());
+                    error.set(Some(error_text(&lang.peek(), e)));
+                }
+            }
+        });
+    };
+
+    let mut export = move |command: &'static str| {
+        let args = serde_wasm_bindgen::to_value(&RunArgs {
+            run: selected.peek().clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        exported.set(None);
+        spawn(async move {
+            match invoke(command, args).await {
+                // `None` when the file dialog was cancelled
+                Ok(val) => exported.set(serde_wasm_bindgen::from_value(val).unwrap_or(None)),
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let run_label = |name: &str| {
+        if name.is_empty() {
+            dict.production_run_outside().to_string()
+        } else {
+            name.to_string()
+        }
+    };
+
+    // A run with no records yet has nothing to summarize
+    let shown = report.read().clone().filter(|r| r.attempts > 0);
+    let yield_text = shown.as_ref().map_or_else(String::new, |r| {
+        format!(
+            "{:.1}%",
+            f64::from(r.passed) * 100.0 / f64::from(r.units.max(1))
+        )
+    });
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.production_run_hint()}" }
+            div { style: "display: flex; gap: 8px; align-items: center; font-size: 0.9em;",
+                label { "{dict.production_run_current()}" }
+                input {
+                    class: "md-input",
+                    style: "width: 200px;",
+                    placeholder: "{dict.production_run_placeholder()}",
+                    value: "{current}",
+                    oninput: move |evt| current.set(evt.value()),
+                    onchange: move |_| save_current(),
+                }
+            }
+            div { style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap;",
+                select {
+                    class: "md-select",
+                    style: "flex: 1; min-width: 160px;",
+                    value: "{selected}",
+                    onchange: move |evt| {
+                        selected.set(evt.value());
+                        load_report();
+                    },
+                    for run in runs.read().iter() {
+                        option { key: "{run.name}", value: "{run.name}",
+                            "{run_label(&run.name)} ({run.records})"
+                        }
+                    }
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: move |_| load(),
+                    span { class: "material-symbols-outlined icon", "refresh" }
+                    span { class: "label", "{dict.production_run_btn_refresh()}" }
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    onclick: move |_| export("export_run_csv"),
+                    span { class: "material-symbols-outlined icon", "download" }
+                    span { class: "label", "{dict.production_run_btn_csv()}" }
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    onclick: move |_| export("export_run_report"),
+                    span { class: "material-symbols-outlined icon", "summarize" }
+                    span { class: "label", "{dict.production_run_btn_report()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(path) = &*exported.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant); word-break: break-all;",
+                    "{dict.production_run_exported()} {path}"
+                }
+            }
+            if let Some(report) = &shown {
+                div { style: "display: flex; gap: 24px; flex-wrap: wrap; font-size: 0.9em;",
+                    span { "{dict.production_run_units()}: {report.units}" }
+                    span { style: "color: var(--md-sys-color-primary);",
+                        "{dict.production_run_passed()}: {report.passed}"
+                    }
+                    span { style: "color: var(--md-sys-color-error);",
+                        "{dict.production_run_failed()}: {report.failed}"
+                    }
+                    span { "{dict.production_run_attempts()}: {report.attempts}" }
+                    span { "{dict.production_run_yield()}: {yield_text}" }
+                }
+                if let Some(d) = &report.durations {
+                    span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                        "{dict.production_run_duration()}: min {seconds(d.min_ms)}, median {seconds(d.median_ms)}, mean {seconds(d.mean_ms)}, max {seconds(d.max_ms)}"
+                    }
+                }
+                if !report.failures.is_empty() {
+                    div { style: "display: flex; flex-direction: column; gap: 4px; font-size: 0.85em;",
+                        span { style: "color: var(--md-sys-color-on-surface-variant);", "{dict.production_run_failures()}" }
+                        for failure in report.failures.iter() {
+                            div { key: "{failure.reason}", style: "display: flex; gap: 12px;",
+                                span { style: "min-width: 32px; text-align: right; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;",
+                                    "{failure.count}"
+                                }
+                                span { "{failure.reason}" }
+                            }
+                        }
+                    }
+                }
+            } else {
+                span { style: "color: var(--md-sys-color-outline);", "{dict.production_run_empty()}" }
+            }
+        }
+    }
+}
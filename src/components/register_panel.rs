@@ -0,0 +1,264 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SvdField {
+    name: String,
+    description: Option<String>,
+    lsb: u32,
+    width: u32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SvdRegister {
+    name: String,
+    description: Option<String>,
+    address: u32,
+    reset_value: Option<u32>,
+    fields: Vec<SvdField>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SvdPeripheral {
+    name: String,
+    description: Option<String>,
+    base_address: u32,
+    registers: Vec<SvdRegister>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct FieldValue {
+    name: String,
+    value: u32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct RegisterValue {
+    name: String,
+    value: u32,
+    fields: Vec<FieldValue>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SvdArgs {
+    chip_model: String,
+    name: Option<String>,
+}
+
+fn error_text(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+fn hex(value: u32) -> String {
+    format!("{:#010x}", value)
+}
+
+/// `[7:4]`, or `[3]` for a single bit.
+fn bit_range(field: &SvdField) -> String {
+    if field.width <= 1 {
+        format!("[{}]", field.lsb)
+    } else {
+        format!("[{}:{}]", field.lsb + field.width - 1, field.lsb)
+    }
+}
+
+/// Browses the peripherals in the chip's SVD file and reads their
+/// registers off the chip GDB has halted, each bitfield decoded.
+#[component]
+pub fn RegisterPanel(chip_model: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut names = use_signal(Vec::<String>::new);
+    let mut peripheral = use_signal(|| None::<SvdPeripheral>);
+    let mut values = use_signal(Vec::<RegisterValue>::new);
+    let mut filter = use_signal(String::new);
+    let mut expanded = use_signal(|| None::<String>);
+    let mut busy = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    let load_chip = chip_model.clone();
+    let load = move |_| {
+        let args = serde_wasm_bindgen::to_value(&SvdArgs {
+            chip_model: load_chip.clone(),
+            name: None,
+        })
+        .unwrap_or(JsValue::NULL);
+        busy.set(true);
+        error.set(None);
+        spawn(async move {
+            // The first time, this downloads the SVD file
+            match invoke("svd_peripherals", args).await {
+                Ok(val) => names.set(serde_wasm_bindgen::from_value(val).unwrap_or_default()),
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let select_chip = chip_model.clone();
+    let mut select = move |name: String| {
+        let args = serde_wasm_bindgen::to_value(&SvdArgs {
+            chip_model: select_chip.clone(),
+            name: Some(name),
+        })
+        .unwrap_or(JsValue::NULL);
+        values.set(Vec::new());
+        expanded.set(None);
+        error.set(None);
+        spawn(async move {
+            match invoke("svd_peripheral", args).await {
+                Ok(val) => peripheral.set(serde_wasm_bindgen::from_value(val).ok()),
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+        });
+    };
+
+    let read_chip = chip_model.clone();
+    let read = move |_| {
+        let Some(name) = peripheral.read().as_ref().map(|p| p.name.clone()) else {
+            return;
+        };
+        let args = serde_wasm_bindgen::to_value(&SvdArgs {
+            chip_model: read_chip.clone(),
+            name: Some(name),
+        })
+        .unwrap_or(JsValue::NULL);
+        busy.set(true);
+        error.set(None);
+        spawn(async move {
+            match invoke("svd_read", args).await {
+                Ok(val) => values.set(serde_wasm_bindgen::from_value(val).unwrap_or_default()),
+                Err(e) => error.set(Some(error_text(&lang.peek(), e))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let selected = peripheral.read().clone();
+    let needle = filter.read().trim().to_ascii_uppercase();
+    let current = values.read().clone();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.registers_hint()}" }
+            div { style: "display: flex; gap: 8px; align-items: center; flex-wrap: wrap;",
+                if names.read().is_empty() {
+                    button {
+                        class: "md-button btn-tonal",
+                        disabled: chip_model.is_empty() || *busy.read(),
+                        title: if chip_model.is_empty() { "{dict.openocd_no_chip()}" } else { "" },
+                        onclick: load,
+                        span { class: "material-symbols-outlined icon", "memory" }
+                        span { class: "label",
+                            if *busy.read() { "{dict.registers_loading()}" } else { "{dict.registers_btn_load()}" }
+                        }
+                    }
+                } else {
+                    select {
+                        class: "md-input",
+                        style: "min-width: 160px;",
+                        value: selected.as_ref().map(|p| p.name.clone()).unwrap_or_default(),
+                        onchange: move |evt| select(evt.value()),
+                        option { value: "", disabled: true, "{dict.registers_select()}" }
+                        for name in names.read().iter() {
+                            option { key: "{name}", value: "{name}", "{name}" }
+                        }
+                    }
+                    input {
+                        class: "md-input",
+                        style: "width: 160px;",
+                        placeholder: "{dict.registers_filter()}",
+                        value: "{filter}",
+                        oninput: move |evt| filter.set(evt.value()),
+                    }
+                    button {
+                        class: "md-button btn-filled",
+                        disabled: selected.is_none() || *busy.read(),
+                        onclick: read,
+                        span { class: "material-symbols-outlined icon", "download" }
+                        span { class: "label", "{dict.registers_btn_read()}" }
+                    }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(p) = &selected {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                    "{p.name} @ {hex(p.base_address)}"
+                    if let Some(description) = &p.description { " · {description}" }
+                }
+                div { style: "display: flex; flex-direction: column; gap: 2px; max-height: 420px; overflow-y: auto; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em;",
+                    for register in p.registers.iter().filter(|r| needle.is_empty() || r.name.contains(&needle)) {
+                        {
+                            let value = current.iter().find(|v| v.name == register.name).cloned();
+                            let open = expanded.read().as_deref() == Some(register.name.as_str());
+                            let name = register.name.clone();
+                            let shown = match (&value, register.reset_value) {
+                                (Some(v), _) => hex(v.value),
+                                (None, Some(reset)) => format!("{} {}", dict.registers_reset(), hex(reset)),
+                                (None, None) => String::new(),
+                            };
+                            rsx! {
+                                div { key: "{register.name}",
+                                    div {
+                                        style: "display: flex; gap: 12px; cursor: pointer; padding: 2px 0;",
+                                        title: register.description.clone().unwrap_or_default(),
+                                        onclick: move |_| {
+                                            let next = if open { None } else { Some(name.clone()) };
+                                            expanded.set(next);
+                                        },
+                                        span { style: "width: 88px; color: var(--md-sys-color-outline);", "{hex(register.address)}" }
+                                        span { style: "flex: 1;", "{register.name}" }
+                                        span {
+                                            style: if value.is_some() { "color: var(--md-sys-color-primary);" } else { "color: var(--md-sys-color-outline);" },
+                                            "{shown}"
+                                        }
+                                    }
+                                    if open {
+                                        for field in register.fields.iter().rev() {
+                                            div {
+                                                key: "{field.name}",
+                                                style: "display: flex; gap: 12px; padding-left: 100px; color: var(--md-sys-color-on-surface-variant);",
+                                                title: field.description.clone().unwrap_or_default(),
+                                                span { style: "width: 64px;", "{bit_range(field)}" }
+                                                span { style: "flex: 1;", "{field.name}" }
+                                                if let Some(v) = value.as_ref().and_then(|v| v.fields.iter().find(|f| f.name == field.name)) {
+                                                    span { style: "color: var(--md-sys-color-primary);", "{v.value}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            } else if names.read().is_empty() && !*busy.read() {
+                span { style: "color: var(--md-sys-color-outline);", "{dict.registers_empty()}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::i18n::Dict;
+use crate::settings::Settings;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ReservedPin {
+    gpio: u32,
+    kind: String,
+    note: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetReservedPinsArgs {
+    chip_model: String,
+}
+
+/// Strapping, USB and flash/PSRAM pins of the detected chip's board, from
+/// its board definition. Pins the firmware is set to use (see
+/// `PinUsagePanel`) that land on one of them are flagged.
+#[component]
+pub fn ReservedPinsPanel(chip_model: String) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let settings = use_context::<Signal<Settings>>();
+
+    let reserved = use_resource(use_reactive!(|chip_model| async move {
+        let args = serde_wasm_bindgen::to_value(&GetReservedPinsArgs { chip_model })
+            .unwrap_or(JsValue::NULL);
+        match invoke("get_reserved_pins", args).await {
+            Ok(val) => serde_wasm_bindgen::from_value::<Vec<ReservedPin>>(val).unwrap_or_default(),
+            Err(e) => {
+                web_sys::console::error_1(&e);
+                Vec::new()
+            }
+        }
+    }));
+
+    let pins = reserved.read().clone().unwrap_or_default();
+    let pin_usage = settings.read().pin_usage.clone();
+    // Kinds in the order the backend lists them
+    let mut kinds: Vec<String> = Vec::new();
+    for pin in &pins {
+        if !kinds.contains(&pin.kind) {
+            kinds.push(pin.kind.clone());
+        }
+    }
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px; font-size: 0.9em;",
+            if pins.is_empty() {
+                span { style: "color: var(--md-sys-color-outline);", "{dict.reserved_pins_none()} {chip_model}" }
+            }
+            for kind in kinds {
+                div { key: "{kind}", style: "display: flex; flex-direction: column; gap: 4px;",
+                    span { class: "pin-tag pin-tag-{kind}", style: "align-self: flex-start;", "{dict.pin_tag(&kind)}" }
+                    for pin in pins.iter().filter(|pin| pin.kind == kind) {
+                        div {
+                            key: "{pin.gpio}",
+                            style: "display: flex; gap: 8px; align-items: baseline;",
+                            span { style: "width: 64px; flex-shrink: 0; font-weight: 500;", "GPIO{pin.gpio}" }
+                            div { style: "display: flex; flex-direction: column; gap: 2px;",
+                                if let Some(note) = &pin.note {
+                                    span { style: "color: var(--md-sys-color-on-surface-variant);", "{note}" }
+                                }
+                                for (i, usage) in pin_usage.iter().filter(|u| u.gpio == pin.gpio).enumerate() {
+                                    span {
+                                        key: "{i}",
+                                        style: "display: flex; align-items: center; gap: 4px; color: var(--md-sys-color-error);",
+                                        span { class: "material-symbols-outlined", style: "font-size: 16px;", "warning" }
+                                        "{dict.reserved_pins_used()} {dict.pin_role(&usage.role)} {usage.signal}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
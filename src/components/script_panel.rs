@@ -0,0 +1,201 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+const EXAMPLE_SCRIPT: &str = r#"// flash("build/app.bin");
+reset();
+expect("READY", 10000);
+send("CONFIG");
+let ack = expect("ACK \\d+");
+print(`device answered: ${ack}`);
+"#;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunScriptArgs {
+    port_name: String,
+    baud_rate: u32,
+    script: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelTaskArgs {
+    task_id: u64,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    id: u64,
+    kind: String,
+    state: String,
+}
+
+#[component]
+pub fn ScriptPanel(port_name: String, baud_rate: u32) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut source = use_signal(|| EXAMPLE_SCRIPT.to_string());
+    let mut output = use_signal(String::new);
+    let mut is_running = use_signal(|| false);
+    let mut task_id = use_signal(|| None::<u64>);
+
+    struct ListenerGuard {
+        unlisten: Vec<js_sys::Function>,
+        _closures: Vec<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            for f in &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: Vec::new(),
+        _closures: Vec::new(),
+    });
+
+    // Device output and print() lines while the script runs
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: String,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    output.write().push_str(&e.payload);
+                }
+            });
+            match listen("script-output", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // Remember the task id so Stop can cancel it
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    if e.payload.kind == "script" && e.payload.state == "running" {
+                        task_id.set(Some(e.payload.id));
+                    }
+                }
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let run = move |_| {
+        let port_name = port_name.clone();
+        is_running.set(true);
+        output.set(String::new());
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&RunScriptArgs {
+                port_name,
+                baud_rate,
+                script: source.read().clone(),
+            })
+            .unwrap_or(JsValue::NULL);
+            let result = invoke("run_script", args).await;
+
+            let dict = lang.peek().clone();
+            let summary = match result {
+                Ok(_) => dict.script_finished().to_string(),
+                Err(e) => match serde_wasm_bindgen::from_value::<AppError>(e) {
+                    Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                    Err(_) => dict.error_internal().to_string(),
+                },
+            };
+            output.write().push_str(&format!("\n-- {} --\n", summary));
+            is_running.set(false);
+            task_id.set(None);
+        });
+    };
+
+    let stop = move |_| {
+        if let Some(id) = *task_id.read() {
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&CancelTaskArgs { task_id: id })
+                    .unwrap_or(JsValue::NULL);
+                invoke("cancel_task", args).await.ok();
+            });
+        }
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            textarea {
+                class: "md-input",
+                style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em; height: 160px; resize: vertical;",
+                spellcheck: false,
+                value: "{source}",
+                disabled: *is_running.read(),
+                oninput: move |evt| source.set(evt.value()),
+            }
+            div { style: "display: flex; justify-content: flex-end; gap: 8px;",
+                if *is_running.read() {
+                    button {
+                        class: "md-button btn-text",
+                        onclick: stop,
+                        span { class: "material-symbols-outlined icon", "stop" }
+                        span { class: "label", "{dict.script_btn_stop()}" }
+                    }
+                } else {
+                    button {
+                        class: "md-button btn-filled",
+                        onclick: run,
+                        span { class: "material-symbols-outlined icon", "play_arrow" }
+                        span { class: "label", "{dict.script_btn_run()}" }
+                    }
+                }
+            }
+            div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.8em; padding: 12px; border-radius: 8px; height: 240px; overflow-y: auto; white-space: pre-wrap; word-wrap: break-word;",
+                if output.read().is_empty() {
+                    span { style: "color: #666;", "{dict.devices_log_placeholder()}" }
+                }
+                "{output}"
+            }
+        }
+    }
+}
@@ -1,38 +1,62 @@
 use crate::app::Route;
-use crate::i18n::{get_dict, Language};
+use crate::i18n::Dict;
 use dioxus::prelude::*;
 
 #[component]
 pub fn Sidebar(
     on_theme_toggle: Option<EventHandler<MouseEvent>>,
     on_lang_toggle: Option<EventHandler<MouseEvent>>,
+    on_api_toggle: Option<EventHandler<MouseEvent>>,
+    on_api_token: Option<EventHandler<MouseEvent>>,
     is_dark: bool,
+    api_enabled: bool,
 ) -> Element {
     let theme_icon = if is_dark { "light_mode" } else { "dark_mode" };
+    let api_class = if api_enabled { "active" } else { "" };
     let current_route = use_route::<Route>();
-    let lang = use_context::<Signal<Language>>();
-    let dict = get_dict(*lang.read());
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
 
     rsx! {
         div {
             class: "md-sidebar",
             NavItem {
                 icon: "home".to_string(),
-                label: dict.home_nav.to_string(),
+                label: dict.home_nav().to_string(),
                 to: Route::Home {},
                 active: current_route == Route::Home {},
             }
             NavItem {
                 icon: "developer_board".to_string(),
-                label: dict.devices_nav.to_string(),
-                to: Route::Devices {},
-                active: current_route == Route::Devices {},
+                label: dict.devices_nav().to_string(),
+                to: Route::Devices { tab: String::new(), port: String::new() },
+                active: matches!(current_route, Route::Devices { .. }),
+            }
+            NavItem {
+                icon: "school".to_string(),
+                label: dict.onboarding_nav().to_string(),
+                to: Route::Onboarding {},
+                active: current_route == Route::Onboarding {},
             }
 
             // Spacer
             div { style: "flex: 1;" }
 
             // Bottom Actions
+            div {
+                class: "md-nav-item {api_class}",
+                title: if api_enabled { dict.api_server_on() } else { dict.api_server_off() },
+                onclick: move |evt| if let Some(h) = &on_api_toggle { h.call(evt) },
+                span { class: "material-symbols-outlined icon", "hub" }
+            }
+            if api_enabled {
+                div {
+                    class: "md-nav-item",
+                    title: "{dict.api_copy_token()}",
+                    onclick: move |evt| if let Some(h) = &on_api_token { h.call(evt) },
+                    span { class: "material-symbols-outlined icon", "key" }
+                }
+            }
             div {
                 class: "md-nav-item",
                 onclick: move |evt| if let Some(h) = &on_lang_toggle { h.call(evt) },
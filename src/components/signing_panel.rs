@@ -0,0 +1,162 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SignedImage {
+    path: String,
+    scheme: String,
+    key_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignArgs {
+    firmware_path: String,
+    key_path: Option<String>,
+    key_pem: Option<String>,
+}
+
+fn describe_error(dict: &Dict, e: JsValue) -> String {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+        Err(_) => dict.error_internal().to_string(),
+    }
+}
+
+/// Adds a Secure Boot v2 signature to an image so it can be flashed or
+/// served over OTA to boards with Secure Boot enabled.
+#[component]
+pub fn SigningPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut firmware_path = use_signal(String::new);
+    let mut key_path = use_signal(String::new);
+    let mut key_pem = use_signal(String::new);
+    let mut signing = use_signal(|| false);
+    let mut signed = use_signal(|| None::<SignedImage>);
+    let mut error = use_signal(|| None::<String>);
+
+    let browse_firmware = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_firmware_file", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    firmware_path.set(path);
+                }
+            }
+        });
+    };
+
+    let browse_key = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_signing_key", JsValue::NULL).await {
+                if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    key_path.set(path);
+                }
+            }
+        });
+    };
+
+    let sign = move |_| {
+        let pem = key_pem.read().trim().to_string();
+        let path = key_path.read().clone();
+        let args = serde_wasm_bindgen::to_value(&SignArgs {
+            firmware_path: firmware_path.read().clone(),
+            key_path: (!path.is_empty()).then_some(path),
+            key_pem: (!pem.is_empty()).then_some(pem),
+        })
+        .unwrap_or(JsValue::NULL);
+        signing.set(true);
+        signed.set(None);
+        error.set(None);
+        spawn(async move {
+            match invoke("sign_firmware", args).await {
+                Ok(val) => signed.set(serde_wasm_bindgen::from_value(val).ok()),
+                Err(e) => error.set(Some(describe_error(&lang.peek(), e))),
+            }
+            // The pasted key isn't kept around once it was used
+            key_pem.set(String::new());
+            signing.set(false);
+        });
+    };
+
+    let has_key = !key_path.read().is_empty() || !key_pem.read().trim().is_empty();
+    let ready = !firmware_path.read().is_empty() && has_key && !*signing.read();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.sign_hint()}" }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    readonly: true,
+                    placeholder: "{dict.devices_placeholder_firmware_file()}",
+                    value: "{firmware_path}",
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: browse_firmware,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+            }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    readonly: true,
+                    placeholder: "{dict.sign_key_placeholder()}",
+                    value: "{key_path}",
+                }
+                button {
+                    class: "md-button btn-text",
+                    onclick: browse_key,
+                    span { class: "material-symbols-outlined icon", "key" }
+                    span { class: "label", "{dict.devices_btn_browse()}" }
+                }
+            }
+            textarea {
+                class: "md-input",
+                style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.85em; height: 80px; resize: vertical;",
+                spellcheck: false,
+                placeholder: "{dict.sign_paste_placeholder()}",
+                value: "{key_pem}",
+                oninput: move |evt| key_pem.set(evt.value()),
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+            if let Some(image) = &*signed.read() {
+                div { style: "display: flex; flex-direction: column; gap: 4px; font-size: 0.9em;",
+                    span { "{dict.sign_done()} {image.scheme} · {image.key_id}" }
+                    span { style: "font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; user-select: all;", "{image.path}" }
+                }
+            }
+            div { style: "display: flex; justify-content: flex-end;",
+                button {
+                    class: "md-button btn-filled",
+                    disabled: !ready,
+                    onclick: sign,
+                    span { class: "material-symbols-outlined icon", "verified_user" }
+                    span { class: "label", "{dict.sign_btn()}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunSoakTestArgs {
+    port_name: String,
+    baud_rate: u32,
+    cycles: u32,
+    marker: Option<String>,
+    timeout_ms: u64,
+    interval_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelTaskArgs {
+    task_id: u64,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    id: u64,
+    kind: String,
+    state: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct BootRecord {
+    cycle: u32,
+    faults: Vec<String>, // "missing_marker", "panic", "brownout"
+    duration_ms: u64,
+    log: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SoakReport {
+    boots: Vec<BootRecord>,
+}
+
+/// Resets the board on `port_name` over and over and shows how many boots
+/// failed and why, with the log of each failed boot.
+#[component]
+pub fn SoakPanel(port_name: String, baud_rate: u32) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut cycles = use_signal(|| "20".to_string());
+    let mut marker = use_signal(String::new);
+    let mut timeout_ms = use_signal(|| "5000".to_string());
+    let mut interval_ms = use_signal(String::new);
+    let mut output = use_signal(String::new);
+    let mut report = use_signal(|| None::<SoakReport>);
+    let mut error = use_signal(|| None::<String>);
+    let mut is_running = use_signal(|| false);
+    let mut task_id = use_signal(|| None::<u64>);
+
+    struct ListenerGuard {
+        unlisten: Vec<js_sys::Function>,
+        _closures: Vec<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            for f in &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: Vec::new(),
+        _closures: Vec::new(),
+    });
+
+    // Boot output and a verdict line per boot while the test runs
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: String,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    output.write().push_str(&e.payload);
+                }
+            });
+            match listen("soak-output", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // Remember the task id so Stop can cancel it
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    if e.payload.kind == "soak" && e.payload.state == "running" {
+                        task_id.set(Some(e.payload.id));
+                    }
+                }
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let run = {
+        let port_name = port_name.clone();
+        move |_| {
+            let port_name = port_name.clone();
+            is_running.set(true);
+            output.set(String::new());
+            report.set(None);
+            error.set(None);
+            spawn(async move {
+                let marker = marker.read().trim().to_string();
+                let args = serde_wasm_bindgen::to_value(&RunSoakTestArgs {
+                    port_name,
+                    baud_rate,
+                    cycles: cycles.read().trim().parse().unwrap_or(20),
+                    marker: (!marker.is_empty()).then_some(marker),
+                    timeout_ms: timeout_ms.read().trim().parse().unwrap_or(5000),
+                    interval_ms: interval_ms.read().trim().parse().ok(),
+                })
+                .unwrap_or(JsValue::NULL);
+                match invoke("run_soak_test", args).await {
+                    Ok(val) => report.set(serde_wasm_bindgen::from_value::<SoakReport>(val).ok()),
+                    Err(e) => {
+                        let dict = lang.peek().clone();
+                        let message = match serde_wasm_bindgen::from_value::<AppError>(e) {
+                            Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                            Err(_) => dict.error_internal().to_string(),
+                        };
+                        error.set(Some(message));
+                    }
+                }
+                is_running.set(false);
+                task_id.set(None);
+            });
+        }
+    };
+
+    let stop = move |_| {
+        if let Some(id) = *task_id.read() {
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&CancelTaskArgs { task_id: id })
+                    .unwrap_or(JsValue::NULL);
+                invoke("cancel_task", args).await.ok();
+            });
+        }
+    };
+
+    let overview = report.read().as_ref().map(|report| {
+        let total = report.boots.len();
+        let failed = report.boots.iter().filter(|b| !b.faults.is_empty()).count();
+        let count = |fault: &str| {
+            report
+                .boots
+                .iter()
+                .filter(|b| b.faults.iter().any(|f| f == fault))
+                .count()
+        };
+        let rate = if total == 0 {
+            0.0
+        } else {
+            failed as f64 * 100.0 / total as f64
+        };
+        format!(
+            "{} {}/{} ({:.1}%) · {} {} · {} {} · {} {}",
+            dict.soak_failed_boots(),
+            failed,
+            total,
+            rate,
+            dict.soak_fault("missing_marker"),
+            count("missing_marker"),
+            dict.soak_fault("panic"),
+            count("panic"),
+            dict.soak_fault("brownout"),
+            count("brownout"),
+        )
+    });
+    let failed_boots: Vec<BootRecord> = report
+        .read()
+        .as_ref()
+        .map(|report| {
+            report
+                .boots
+                .iter()
+                .filter(|b| !b.faults.is_empty())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            div { style: "display: grid; grid-template-columns: auto 1fr auto 1fr; align-items: center; gap: 8px; font-size: 0.85em;",
+                label { "{dict.soak_cycles()}" }
+                input {
+                    class: "md-input",
+                    r#type: "number",
+                    min: "1",
+                    value: "{cycles}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| cycles.set(evt.value()),
+                }
+                label { "{dict.soak_marker()}" }
+                input {
+                    class: "md-input",
+                    style: "font-family: monospace;",
+                    placeholder: "READY",
+                    value: "{marker}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| marker.set(evt.value()),
+                }
+                label { "{dict.soak_timeout()}" }
+                input {
+                    class: "md-input",
+                    r#type: "number",
+                    min: "100",
+                    value: "{timeout_ms}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| timeout_ms.set(evt.value()),
+                }
+                label { "{dict.soak_interval()}" }
+                input {
+                    class: "md-input",
+                    r#type: "number",
+                    min: "0",
+                    value: "{interval_ms}",
+                    disabled: *is_running.read(),
+                    oninput: move |evt| interval_ms.set(evt.value()),
+                }
+            }
+            div { style: "display: flex; justify-content: flex-end; gap: 8px;",
+                if *is_running.read() {
+                    button {
+                        class: "md-button btn-text",
+                        onclick: stop,
+                        span { class: "material-symbols-outlined icon", "stop" }
+                        span { class: "label", "{dict.soak_btn_stop()}" }
+                    }
+                } else {
+                    button {
+                        class: "md-button btn-filled",
+                        disabled: port_name.is_empty(),
+                        onclick: run,
+                        span { class: "material-symbols-outlined icon", "restart_alt" }
+                        span { class: "label", "{dict.soak_btn_run()}" }
+                    }
+                }
+            }
+            if let Some(overview) = overview {
+                div {
+                    style: if failed_boots.is_empty() { "font-size: 0.85em; font-weight: 500; color: var(--md-sys-color-primary);" } else { "font-size: 0.85em; font-weight: 500; color: var(--md-sys-color-error);" },
+                    "{overview}"
+                }
+            }
+            for boot in failed_boots {
+                details { key: "{boot.cycle}", style: "font-size: 0.85em;",
+                    summary {
+                        "#{boot.cycle} · "
+                        {boot.faults.iter().map(|f| dict.soak_fault(f).to_string()).collect::<Vec<_>>().join(", ")}
+                        " · {boot.duration_ms} ms"
+                    }
+                    pre { style: "background: #1e1e1e; color: #d4d4d4; font-size: 0.9em; padding: 8px; border-radius: 8px; max-height: 200px; overflow: auto; white-space: pre-wrap;",
+                        "{boot.log}"
+                    }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                div { style: "font-size: 0.85em; color: var(--md-sys-color-error); word-break: break-word;",
+                    "{message}"
+                }
+            }
+            div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.8em; padding: 12px; border-radius: 8px; height: 200px; overflow-y: auto; white-space: pre-wrap; word-wrap: break-word;",
+                if output.read().is_empty() {
+                    span { style: "color: #666;", "{dict.devices_log_placeholder()}" }
+                }
+                "{output}"
+            }
+        }
+    }
+}
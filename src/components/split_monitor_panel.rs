@@ -0,0 +1,316 @@
+use crate::components::{use_toasts, Button};
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SplitConnectArgs {
+    port_name: String,
+    baud_rate: u32,
+}
+
+#[derive(Serialize)]
+struct SplitLinesArgs {
+    start: Option<u64>,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct SplitSendArgs {
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorStatus {
+    state: String,
+    port_name: Option<String>,
+    baud_rate: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct SplitLines {
+    lines: Vec<String>,
+}
+
+// The pane follows the tail only; scrolling back is for the main view
+const TAIL_LINES: usize = 500;
+
+/// Second serial session of the split view: its own port, baud, log and
+/// input, next to the main monitor. `ports` are (name, label) pairs.
+#[component]
+pub fn SplitMonitorPanel(ports: Vec<(String, String)>) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let toasts = use_toasts();
+
+    let mut port_name = use_signal(String::new);
+    let mut baud_rate = use_signal(|| "115200".to_string());
+    let mut state = use_signal(|| "disconnected".to_string());
+    let mut lines = use_signal(Vec::<String>::new);
+    let mut input_cmd = use_signal(String::new);
+    let mut fetching = use_signal(|| false);
+    let mut stale = use_signal(|| false);
+
+    struct ListenerGuard {
+        unlisten: Vec<js_sys::Function>,
+        _closures: Vec<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            for f in &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: Vec::new(),
+        _closures: Vec::new(),
+    });
+
+    // Bursts of output collapse into one more fetch, as in the main view
+    let mut refresh_log = move || {
+        if *fetching.peek() {
+            stale.set(true);
+            return;
+        }
+        fetching.set(true);
+        spawn(async move {
+            loop {
+                stale.set(false);
+                let args = serde_wasm_bindgen::to_value(&SplitLinesArgs {
+                    start: None,
+                    count: TAIL_LINES,
+                })
+                .unwrap_or(JsValue::NULL);
+                if let Ok(val) = invoke("split_monitor_lines", args).await {
+                    if let Ok(window) = serde_wasm_bindgen::from_value::<SplitLines>(val) {
+                        lines.set(window.lines);
+                    }
+                }
+                if !*stale.peek() {
+                    break;
+                }
+                gloo_timers::future::TimeoutFuture::new(50).await;
+            }
+            fetching.set(false);
+        });
+    };
+
+    let refresh_status = move || {
+        spawn(async move {
+            if let Ok(val) = invoke("split_monitor_status", JsValue::NULL).await {
+                if let Ok(status) = serde_wasm_bindgen::from_value::<MonitorStatus>(val) {
+                    if let Some(port) = status.port_name {
+                        port_name.set(port);
+                    }
+                    if let Some(baud) = status.baud_rate {
+                        baud_rate.set(baud.to_string());
+                    }
+                    state.set(status.state);
+                }
+            }
+        });
+    };
+
+    // Pick up a session left running when the pane was last closed
+    use_effect(move || {
+        refresh_status();
+        refresh_log();
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |_: JsValue| {
+                refresh_log();
+            });
+            match listen("split-serial-read", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // Flashing the pane's port pauses it; show that instead of "connected"
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |_: JsValue| {
+                refresh_status();
+            });
+            match listen("split-monitor-state", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let is_connected = *state.read() != "disconnected";
+
+    let toggle = move |_| {
+        let port = port_name.read().clone();
+        let baud = baud_rate.read().parse::<u32>().unwrap_or(115200);
+        spawn(async move {
+            if is_connected {
+                invoke("split_monitor_disconnect", JsValue::NULL).await.ok();
+            } else {
+                if port.is_empty() {
+                    toasts.push("error", "no_port", None);
+                    return;
+                }
+                let args = serde_wasm_bindgen::to_value(&SplitConnectArgs {
+                    port_name: port,
+                    baud_rate: baud,
+                })
+                .unwrap_or(JsValue::NULL);
+                if let Err(e) = invoke("split_monitor_connect", args).await {
+                    let dict = lang.peek().clone();
+                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                        .ok();
+                    toasts.push("error", "monitor_failed", detail);
+                }
+            }
+            refresh_status();
+        });
+    };
+
+    let mut send_input = move || {
+        let cmd = input_cmd.read().clone();
+        if cmd.is_empty() {
+            return;
+        }
+        input_cmd.set(String::new());
+        spawn(async move {
+            let args =
+                serde_wasm_bindgen::to_value(&SplitSendArgs { data: cmd }).unwrap_or(JsValue::NULL);
+            if invoke("split_monitor_send", args).await.is_ok() {
+                refresh_log();
+            }
+        });
+    };
+
+    let clear = move |_| {
+        spawn(async move {
+            invoke("split_monitor_clear", JsValue::NULL).await.ok();
+            lines.set(Vec::new());
+        });
+    };
+
+    let selected = port_name.read().clone();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            div { style: "display: flex; flex-wrap: wrap; align-items: center; gap: 8px;",
+                select {
+                    class: "md-select",
+                    style: "max-width: 220px;",
+                    title: "{dict.port()}",
+                    value: "{port_name}",
+                    disabled: is_connected,
+                    onchange: move |evt| port_name.set(evt.value()),
+                    option { value: "", disabled: true, selected: selected.is_empty(),
+                        if ports.is_empty() { "{dict.devices_no_ports()}" } else { "{dict.port()}" }
+                    }
+                    if !selected.is_empty() && !ports.iter().any(|(name, _)| *name == selected) {
+                        option { value: "{selected}", "{selected}" }
+                    }
+                    for (name, label) in ports.iter() {
+                        option { key: "{name}", value: "{name}", "{label}" }
+                    }
+                }
+                select {
+                    class: "md-select",
+                    title: "{dict.devices_label_baud_rate()}",
+                    value: "{baud_rate}",
+                    disabled: is_connected,
+                    onchange: move |evt| baud_rate.set(evt.value()),
+                    option { value: "9600", "9600" }
+                    option { value: "115200", "115200" }
+                    option { value: "921600", "921600" }
+                }
+                Button {
+                    variant: { if is_connected { "tonal" } else { "text" } }.to_string(),
+                    icon: { if is_connected { "link_off" } else { "link" } }.to_string(),
+                    onclick: toggle,
+                    if is_connected {
+                        "{dict.devices_btn_disconnect()}"
+                    } else {
+                        "{dict.connect()}"
+                    }
+                }
+                Button {
+                    variant: "text".to_string(),
+                    icon: "delete_sweep".to_string(),
+                    onclick: clear,
+                    "{dict.devices_btn_clear()}"
+                }
+                if *state.read() == "suspended" {
+                    span { style: "font-size: 0.85em; color: var(--md-sys-color-on-surface-variant);",
+                        "{dict.split_monitor_paused()}"
+                    }
+                }
+            }
+            div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.8em; padding: 12px; border-radius: 8px; height: 400px; overflow-y: auto; white-space: pre-wrap; word-wrap: break-word; display: flex; flex-direction: column-reverse;",
+                div {
+                    if lines.read().is_empty() {
+                        span { style: "color: #666;", "{dict.devices_log_placeholder()}" }
+                    }
+                    for (i, line) in lines.read().iter().enumerate() {
+                        div { key: "{i}", "{line}" }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1;",
+                    placeholder: "{dict.devices_input_placeholder()}",
+                    disabled: !is_connected,
+                    value: "{input_cmd}",
+                    oninput: move |evt| input_cmd.set(evt.value()),
+                    onkeydown: move |evt| {
+                        if evt.key() == Key::Enter {
+                            send_input();
+                        }
+                    },
+                }
+                Button {
+                    variant: "filled".to_string(),
+                    icon: "send".to_string(),
+                    onclick: move |_| send_input(),
+                    "{dict.devices_btn_send()}"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::i18n::Dict;
+use dioxus::html::geometry::PixelsVector2D;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+// Size of the emulated screen; most consoles assume 80 columns
+const ROWS: usize = 24;
+const COLS: usize = 80;
+const LINE_HEIGHT: f64 = 18.0;
+
+#[derive(Serialize)]
+struct MonitorTerminalArgs {
+    enabled: bool,
+    rows: usize,
+    cols: usize,
+}
+
+#[derive(Serialize)]
+struct MonitorSendKeysArgs {
+    data: String,
+}
+
+/// The backend's terminal screen, see `monitor_screen`.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct TerminalScreen {
+    scrollback: Vec<String>,
+    lines: Vec<String>,
+    cursor_row: usize,
+    cursor_col: usize,
+    cursor_visible: bool,
+}
+
+/// What a key sends to the device, as a VT100 would.
+fn key_bytes(key: &Key, ctrl: bool) -> Option<String> {
+    let text = match key {
+        Key::Enter => "\r",
+        Key::Backspace => "\u{7f}",
+        Key::Tab => "\t",
+        Key::Escape => "\u{1b}",
+        Key::ArrowUp => "\u{1b}[A",
+        Key::ArrowDown => "\u{1b}[B",
+        Key::ArrowRight => "\u{1b}[C",
+        Key::ArrowLeft => "\u{1b}[D",
+        Key::Home => "\u{1b}[H",
+        Key::End => "\u{1b}[F",
+        Key::Delete => "\u{1b}[3~",
+        Key::Character(c) if ctrl => {
+            // Ctrl-A .. Ctrl-Z, e.g. Ctrl-C to interrupt
+            let letter = c.chars().next().filter(|c| c.is_ascii_alphabetic())?;
+            return Some(char::from(letter.to_ascii_uppercase() as u8 & 0x1f).to_string());
+        }
+        Key::Character(c) => return Some(c.clone()),
+        _ => return None,
+    };
+    Some(text.to_string())
+}
+
+/// Monitor view for interactive consoles: the screen the device draws on
+/// with cursor movement and erase sequences, kept by the backend, with
+/// keystrokes sent straight to the device. Turns the backend's terminal
+/// mode on while shown.
+#[component]
+pub fn TerminalView() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut screen = use_signal(TerminalScreen::default);
+    let mut view = use_signal(|| None::<Rc<MountedData>>);
+    let mut fetching = use_signal(|| false);
+    let mut stale = use_signal(|| false);
+
+    // Collapses bursts of serial data into a few fetches
+    let mut refresh = move || {
+        if *fetching.peek() {
+            stale.set(true);
+            return;
+        }
+        fetching.set(true);
+        spawn(async move {
+            loop {
+                stale.set(false);
+                if let Ok(val) = invoke("monitor_screen", JsValue::NULL).await {
+                    if let Ok(Some(next)) =
+                        serde_wasm_bindgen::from_value::<Option<TerminalScreen>>(val)
+                    {
+                        if *screen.peek() != next {
+                            screen.set(next);
+                        }
+                    }
+                }
+                if !*stale.peek() {
+                    break;
+                }
+                gloo_timers::future::TimeoutFuture::new(30).await;
+            }
+            fetching.set(false);
+        });
+    };
+
+    use_hook(move || {
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MonitorTerminalArgs {
+                enabled: true,
+                rows: ROWS,
+                cols: COLS,
+            })
+            .unwrap_or(JsValue::NULL);
+            invoke("monitor_terminal", args).await.ok();
+            refresh();
+        });
+    });
+
+    use_drop(move || {
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MonitorTerminalArgs {
+                enabled: false,
+                rows: ROWS,
+                cols: COLS,
+            })
+            .unwrap_or(JsValue::NULL);
+            invoke("monitor_terminal", args).await.ok();
+        });
+    });
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |_: JsValue| refresh());
+            match listen("serial-read", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // Keep the screen, below the scrollback, in sight
+    use_effect(move || {
+        let lines = {
+            let screen = screen.read();
+            screen.scrollback.len() + screen.lines.len()
+        };
+        if let Some(view) = view.peek().clone() {
+            spawn(async move {
+                let bottom = lines as f64 * LINE_HEIGHT;
+                view.scroll(PixelsVector2D::new(0.0, bottom), ScrollBehavior::Instant)
+                    .await
+                    .ok();
+            });
+        }
+    });
+
+    let on_key = move |evt: KeyboardEvent| {
+        let Some(data) = key_bytes(&evt.key(), evt.modifiers().ctrl()) else {
+            return;
+        };
+        evt.prevent_default();
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MonitorSendKeysArgs { data })
+                .unwrap_or(JsValue::NULL);
+            invoke("monitor_send_keys", args).await.ok();
+        });
+    };
+
+    let current = screen.read().clone();
+    let height = ROWS as f64 * LINE_HEIGHT;
+    let rows: Vec<(String, String, String)> = current
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(row, line)| {
+            if !current.cursor_visible || row != current.cursor_row {
+                return (line.clone(), String::new(), String::new());
+            }
+            let mut chars: Vec<char> = line.chars().collect();
+            if chars.len() <= current.cursor_col {
+                chars.resize(current.cursor_col + 1, ' ');
+            }
+            (
+                chars[..current.cursor_col].iter().collect(),
+                chars[current.cursor_col].to_string(),
+                chars[current.cursor_col + 1..].iter().collect(),
+            )
+        })
+        .collect();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            div {
+                tabindex: "0",
+                style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.9em; padding: 12px; border-radius: 8px; height: {height}px; overflow: auto; white-space: pre; outline: none;",
+                onmounted: move |evt| view.set(Some(evt.data())),
+                onkeydown: on_key,
+                for (i, line) in current.scrollback.iter().enumerate() {
+                    div {
+                        key: "s{i}",
+                        style: "height: {LINE_HEIGHT}px; line-height: {LINE_HEIGHT}px; color: #9d9d9d;",
+                        "{line}"
+                    }
+                }
+                for (i, (before, cursor, after)) in rows.into_iter().enumerate() {
+                    div {
+                        key: "l{i}",
+                        style: "height: {LINE_HEIGHT}px; line-height: {LINE_HEIGHT}px;",
+                        "{before}"
+                        if !cursor.is_empty() {
+                            span { style: "background: #d4d4d4; color: #1e1e1e;", "{cursor}" }
+                        }
+                        "{after}"
+                    }
+                }
+            }
+            span { style: "font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);", "{dict.devices_terminal_hint()}" }
+        }
+    }
+}
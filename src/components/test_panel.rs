@@ -0,0 +1,281 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunTestSequenceArgs {
+    port_name: String,
+    baud_rate: u32,
+    path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelTaskArgs {
+    task_id: u64,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    id: u64,
+    kind: String,
+    state: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct StepResult {
+    description: String,
+    status: String, // "passed", "failed", "skipped"
+    duration_ms: u64,
+    detail: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct TestReport {
+    name: String,
+    passed: bool,
+    duration_ms: u64,
+    steps: Vec<StepResult>,
+}
+
+/// Runs a test sequence file against the board on `port_name` and shows
+/// the device output, then a pass/fail line per step.
+#[component]
+pub fn TestPanel(port_name: String, baud_rate: u32) -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    let mut path = use_signal(String::new);
+    let mut output = use_signal(String::new);
+    let mut report = use_signal(|| None::<TestReport>);
+    let mut error = use_signal(|| None::<String>);
+    let mut is_running = use_signal(|| false);
+    let mut task_id = use_signal(|| None::<u64>);
+
+    struct ListenerGuard {
+        unlisten: Vec<js_sys::Function>,
+        _closures: Vec<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            for f in &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: Vec::new(),
+        _closures: Vec::new(),
+    });
+
+    // Device output and step headers while the sequence runs
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: String,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    output.write().push_str(&e.payload);
+                }
+            });
+            match listen("test-output", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // Remember the task id so Stop can cancel it
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    if e.payload.kind == "test" && e.payload.state == "running" {
+                        task_id.set(Some(e.payload.id));
+                    }
+                }
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.unlisten.push(f);
+                    }
+                    guard._closures.push(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let pick = move |_| {
+        spawn(async move {
+            if let Ok(val) = invoke("pick_test_sequence", JsValue::NULL).await {
+                if let Ok(Some(picked)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                    path.set(picked);
+                    report.set(None);
+                }
+            }
+        });
+    };
+
+    let run = {
+        let port_name = port_name.clone();
+        move |_| {
+            let port_name = port_name.clone();
+            is_running.set(true);
+            output.set(String::new());
+            report.set(None);
+            error.set(None);
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&RunTestSequenceArgs {
+                    port_name,
+                    baud_rate,
+                    path: path.read().clone(),
+                })
+                .unwrap_or(JsValue::NULL);
+                match invoke("run_test_sequence", args).await {
+                    Ok(val) => report.set(serde_wasm_bindgen::from_value::<TestReport>(val).ok()),
+                    Err(e) => {
+                        let dict = lang.peek().clone();
+                        let message = match serde_wasm_bindgen::from_value::<AppError>(e) {
+                            Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                            Err(_) => dict.error_internal().to_string(),
+                        };
+                        error.set(Some(message));
+                    }
+                }
+                is_running.set(false);
+                task_id.set(None);
+            });
+        }
+    };
+
+    let stop = move |_| {
+        if let Some(id) = *task_id.read() {
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&CancelTaskArgs { task_id: id })
+                    .unwrap_or(JsValue::NULL);
+                invoke("cancel_task", args).await.ok();
+            });
+        }
+    };
+
+    let ready = !path.read().is_empty() && !port_name.is_empty();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 8px;",
+            div { style: "display: flex; align-items: center; gap: 8px;",
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: *is_running.read(),
+                    onclick: pick,
+                    span { class: "material-symbols-outlined icon", "folder_open" }
+                    span { class: "label", "{dict.test_btn_pick()}" }
+                }
+                span { style: "flex: 1; font-size: 0.85em; word-break: break-all;",
+                    if path.read().is_empty() {
+                        "{dict.test_no_file()}"
+                    } else {
+                        "{path}"
+                    }
+                }
+                if *is_running.read() {
+                    button {
+                        class: "md-button btn-text",
+                        onclick: stop,
+                        span { class: "material-symbols-outlined icon", "stop" }
+                        span { class: "label", "{dict.test_btn_stop()}" }
+                    }
+                } else {
+                    button {
+                        class: "md-button btn-filled",
+                        disabled: !ready,
+                        onclick: run,
+                        span { class: "material-symbols-outlined icon", "fact_check" }
+                        span { class: "label", "{dict.test_btn_run()}" }
+                    }
+                }
+            }
+            if let Some(report) = &*report.read() {
+                div { style: "display: flex; flex-direction: column; gap: 4px; font-size: 0.85em;",
+                    div {
+                        style: if report.passed { "font-weight: 500; color: var(--md-sys-color-primary);" } else { "font-weight: 500; color: var(--md-sys-color-error);" },
+                        if report.passed {
+                            "{report.name}: {dict.test_passed()} ({report.duration_ms} ms)"
+                        } else {
+                            "{report.name}: {dict.test_failed()} ({report.duration_ms} ms)"
+                        }
+                    }
+                    for (i, step) in report.steps.iter().enumerate() {
+                        div { key: "{i}", style: "display: flex; align-items: baseline; gap: 8px;",
+                            span {
+                                class: "material-symbols-outlined",
+                                style: match step.status.as_str() {
+                                    "passed" => "font-size: 16px; color: var(--md-sys-color-primary);",
+                                    "failed" => "font-size: 16px; color: var(--md-sys-color-error);",
+                                    _ => "font-size: 16px; color: var(--md-sys-color-outline);",
+                                },
+                                match step.status.as_str() {
+                                    "passed" => "check_circle",
+                                    "failed" => "cancel",
+                                    _ => "remove",
+                                }
+                            }
+                            span { style: "font-family: monospace;", "{step.description}" }
+                            span { style: "color: var(--md-sys-color-on-surface-variant);", "{step.duration_ms} ms" }
+                            if let Some(detail) = &step.detail {
+                                span { style: "flex: 1; color: var(--md-sys-color-on-surface-variant); word-break: break-word;",
+                                    "{detail.trim()}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                div { style: "font-size: 0.85em; color: var(--md-sys-color-error); word-break: break-word;",
+                    "{message}"
+                }
+            }
+            div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.8em; padding: 12px; border-radius: 8px; height: 200px; overflow-y: auto; white-space: pre-wrap; word-wrap: break-word;",
+                if output.read().is_empty() {
+                    span { style: "color: #666;", "{dict.devices_log_placeholder()}" }
+                }
+                "{output}"
+            }
+        }
+    }
+}
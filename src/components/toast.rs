@@ -0,0 +1,158 @@
+use crate::i18n::Dict;
+use dioxus::prelude::*;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+/// Payload of the backend `notify` event.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub level: String, // "info", "success", "warning", "error"
+    pub kind: String,
+    pub port_name: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, PartialEq)]
+struct Toast {
+    id: u64,
+    notification: Notification,
+}
+
+/// Shared toast list. Most toasts come from backend `notify` events; pages
+/// push their own for problems caught before anything reaches the backend.
+#[derive(Clone, Copy)]
+pub struct Toasts {
+    list: Signal<Vec<Toast>>,
+    next_id: Signal<u64>,
+}
+
+impl Toasts {
+    pub fn push(mut self, level: &str, kind: &str, message: Option<String>) {
+        self.show(Notification {
+            level: level.to_string(),
+            kind: kind.to_string(),
+            port_name: None,
+            message,
+        });
+    }
+
+    fn show(&mut self, notification: Notification) {
+        let id = *self.next_id.peek();
+        self.next_id.set(id + 1);
+        // Errors stay up long enough to read the detail
+        let ms = if notification.level == "error" {
+            8000
+        } else {
+            4000
+        };
+        self.list.write().push(Toast { id, notification });
+        let mut toasts = *self;
+        spawn(async move {
+            gloo_timers::future::TimeoutFuture::new(ms).await;
+            toasts.dismiss(id);
+        });
+    }
+
+    fn dismiss(&mut self, id: u64) {
+        self.list.write().retain(|t| t.id != id);
+    }
+}
+
+/// Provides the toast list to the pages below; call once in the layout.
+pub fn use_toasts_provider() -> Toasts {
+    use_context_provider(|| Toasts {
+        list: Signal::new(Vec::new()),
+        next_id: Signal::new(0),
+    })
+}
+
+pub fn use_toasts() -> Toasts {
+    use_context::<Toasts>()
+}
+
+#[component]
+pub fn ToastHost() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut toasts = use_toasts();
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: Notification,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    toasts.show(e.payload);
+                }
+            });
+            match listen("notify", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    rsx! {
+        div { class: "toast-stack",
+            for toast in toasts.list.read().iter() {
+                div {
+                    key: "{toast.id}",
+                    class: "toast toast-{toast.notification.level}",
+                    onclick: {
+                        let id = toast.id;
+                        move |_| toasts.dismiss(id)
+                    },
+                    span { class: "material-symbols-outlined", style: "font-size: 20px;",
+                        match toast.notification.level.as_str() {
+                            "success" => "check_circle",
+                            "warning" => "warning",
+                            "error" => "error",
+                            _ => "info",
+                        }
+                    }
+                    div { style: "display: flex; flex-direction: column; gap: 2px; min-width: 0;",
+                        span { style: "font-weight: 500;",
+                            "{dict.toast_title(&toast.notification.kind)}"
+                            if let Some(port) = &toast.notification.port_name {
+                                " · {port}"
+                            }
+                        }
+                        if let Some(message) = &toast.notification.message {
+                            span { style: "font-size: 0.85em; opacity: 0.8; word-break: break-word;", "{message}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
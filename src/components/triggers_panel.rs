@@ -0,0 +1,174 @@
+use crate::i18n::Dict;
+use crate::settings::{update_settings, MonitorTrigger, Settings, TriggerAction};
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+const KINDS: [&str; 4] = ["send", "mark", "notify", "stop"];
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+fn kind_of(action: &TriggerAction) -> &'static str {
+    match action {
+        TriggerAction::Send { .. } => "send",
+        TriggerAction::Mark { .. } => "mark",
+        TriggerAction::Notify { .. } => "notify",
+        TriggerAction::Stop => "stop",
+    }
+}
+
+fn argument_of(action: &TriggerAction) -> &str {
+    match action {
+        TriggerAction::Send { text } => text,
+        TriggerAction::Mark { label } => label,
+        TriggerAction::Notify { message } => message,
+        TriggerAction::Stop => "",
+    }
+}
+
+fn action(kind: &str, argument: String) -> TriggerAction {
+    match kind {
+        "send" => TriggerAction::Send { text: argument },
+        "mark" => TriggerAction::Mark { label: argument },
+        "notify" => TriggerAction::Notify { message: argument },
+        _ => TriggerAction::Stop,
+    }
+}
+
+/// Edits the monitor triggers kept in the settings. The backend checks
+/// every pattern before saving and runs the triggers on the serial stream.
+#[component]
+pub fn TriggersPanel() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    let mut pattern = use_signal(String::new);
+    let mut kind = use_signal(|| "send".to_string());
+    let mut argument = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    // Saves a changed list, keeping the old one if the backend refuses it
+    let mut save = move |triggers: Vec<MonitorTrigger>| {
+        let mut updated = settings.peek().clone();
+        updated.triggers = triggers;
+        error.set(None);
+        spawn(async move {
+            match update_settings(updated).await {
+                Ok(saved) => {
+                    settings.set(saved);
+                    pattern.set(String::new());
+                    argument.set(String::new());
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => format!("{}: {}", dict.error_kind(&err.kind), err.message),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    let add = move |_| {
+        let mut triggers = settings.peek().triggers.clone();
+        triggers.push(MonitorTrigger {
+            pattern: pattern.read().clone(),
+            action: action(&kind.read(), argument.read().clone()),
+            enabled: true,
+        });
+        save(triggers);
+    };
+
+    let triggers = settings.read().triggers.clone();
+    let needs_argument = matches!(kind.read().as_str(), "send" | "mark");
+    let ready =
+        !pattern.read().trim().is_empty() && (!needs_argument || !argument.read().is_empty());
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 12px;",
+            span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.triggers_hint()}" }
+            if triggers.is_empty() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-outline);", "{dict.triggers_empty()}" }
+            }
+            for (i, trigger) in triggers.iter().enumerate() {
+                div {
+                    key: "{i}",
+                    style: "display: flex; align-items: center; gap: 8px;",
+                    input {
+                        r#type: "checkbox",
+                        title: "{dict.triggers_enabled()}",
+                        checked: trigger.enabled,
+                        onchange: {
+                            let triggers = triggers.clone();
+                            move |evt: Event<FormData>| {
+                                let mut triggers = triggers.clone();
+                                triggers[i].enabled = evt.checked();
+                                save(triggers);
+                            }
+                        },
+                    }
+                    span { style: "flex: 1; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.9em; word-break: break-all;",
+                        "{trigger.pattern}"
+                    }
+                    span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                        "{dict.trigger_action(kind_of(&trigger.action))} {argument_of(&trigger.action)}"
+                    }
+                    button {
+                        class: "md-button btn-text",
+                        onclick: {
+                            let triggers = triggers.clone();
+                            move |_| {
+                                let mut triggers = triggers.clone();
+                                triggers.remove(i);
+                                save(triggers);
+                            }
+                        },
+                        span { class: "material-symbols-outlined icon", "delete" }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 8px;",
+                input {
+                    class: "md-input",
+                    style: "flex: 1; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace;",
+                    placeholder: "{dict.triggers_pattern_placeholder()}",
+                    value: "{pattern}",
+                    oninput: move |evt| pattern.set(evt.value()),
+                }
+                select {
+                    class: "md-input",
+                    style: "width: 140px;",
+                    value: "{kind}",
+                    onchange: move |evt| kind.set(evt.value()),
+                    for k in KINDS {
+                        option { key: "{k}", value: "{k}", "{dict.trigger_action(k)}" }
+                    }
+                }
+                if *kind.read() != "stop" {
+                    input {
+                        class: "md-input",
+                        style: "flex: 1;",
+                        placeholder: "{dict.triggers_argument_placeholder()}",
+                        value: "{argument}",
+                        oninput: move |evt| argument.set(evt.value()),
+                    }
+                }
+                button {
+                    class: "md-button btn-tonal",
+                    disabled: !ready,
+                    onclick: add,
+                    span { class: "material-symbols-outlined icon", "add" }
+                    span { class: "label", "{dict.triggers_btn_add()}" }
+                }
+            }
+            if let Some(message) = &*error.read() {
+                span { style: "font-size: 0.9em; color: var(--md-sys-color-error);", "{message}" }
+            }
+        }
+    }
+}
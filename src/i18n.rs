@@ -1,197 +1,460 @@
-#[derive(Clone, Copy, PartialEq)]
-pub enum Language {
-    En,
-    Zh,
+//! UI strings. A locale is a flat JSON object of key to text (see
+//! `locales/`); the backend serves the bundled ones plus any dropped into
+//! the app config dir, so languages can be added without a rebuild. Keys a
+//! locale lacks fall back to English, which is compiled in.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::LazyLock;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+static ENGLISH: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../locales/en.json")).expect("locales/en.json")
+});
+
+/// A locale the backend can serve, as listed by `list_locales`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct LocaleInfo {
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+struct GetLocaleArgs {
+    code: String,
 }
 
+/// Strings of the active locale, shared through context by `AppLayout`.
+/// Cheap to clone.
+#[derive(Clone)]
 pub struct Dict {
-    pub device_status_title: &'static str,
-    pub device_status_subtitle: &'static str,
-    pub device_disconnected: &'static str,
-    pub settings: &'static str,
-    pub connect: &'static str,
-
-    pub quick_actions_title: &'static str,
-    pub flash_firmware: &'static str,
-    pub monitor: &'static str,
-    pub files: &'static str,
-
-    pub home_nav: &'static str,
-    pub devices_nav: &'static str,
-    pub settings_nav: &'static str,
-
-    // Device Info UI
-    pub ready_to_flash: &'static str,
-    pub probing_error: &'static str,
-    pub connection_info: &'static str,
-    pub hardware_details: &'static str,
-    pub port: &'static str,
-    pub vid_pid: &'static str,
-    pub serial_number: &'static str,
-    pub chip_model: &'static str,
-    pub flash_size: &'static str,
-    pub mac_address: &'static str,
-    pub chip_revision: &'static str,
-    pub crystal_frequency: &'static str,
-    pub features: &'static str,
-
-    pub connection_type: &'static str,
-    pub type_native_usb: &'static str,
-    pub type_uart_bridge: &'static str,
-
-    pub driver_check_btn: &'static str,
-    pub driver_installed: &'static str,
-    pub driver_not_found: &'static str,
-
-    // Devices Page
-    pub devices_title_flashing: &'static str,
-    pub devices_subtitle_flashing: &'static str,
-    pub devices_label_firmware_file: &'static str,
-    pub devices_placeholder_firmware_file: &'static str,
-    pub devices_btn_browse: &'static str,
-    pub devices_label_flash_address: &'static str,
-    pub devices_flashing_status: &'static str,
-    pub devices_btn_start_flash: &'static str,
-    pub devices_btn_erase_flash: &'static str,
-
-    pub devices_title_monitor: &'static str,
-    pub devices_subtitle_monitor: &'static str,
-    pub devices_label_baud_rate: &'static str,
-    pub devices_log_placeholder: &'static str,
-    pub devices_input_placeholder: &'static str,
-    pub devices_btn_send: &'static str,
-    pub devices_btn_disconnect: &'static str,
-    pub devices_btn_clear: &'static str,
-    pub monitor_tab: &'static str,
-    pub board_view_tab: &'static str,
-    pub board_view_title: &'static str,
+    code: String,
+    strings: Rc<HashMap<String, String>>,
+}
+
+impl Default for Dict {
+    fn default() -> Self {
+        Dict {
+            code: "en".to_string(),
+            strings: Rc::new(HashMap::new()),
+        }
+    }
+}
+
+impl Dict {
+    pub fn new(code: &str, strings: HashMap<String, String>) -> Self {
+        Dict {
+            code: code.to_string(),
+            strings: Rc::new(strings),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    fn lookup(&self, key: &str) -> Option<&str> {
+        self.strings
+            .get(key)
+            .or_else(|| ENGLISH.get(key))
+            .map(String::as_str)
+    }
+
+    /// Text for `key`. Unknown keys come back as is, so a missing string
+    /// shows up in the UI instead of vanishing.
+    pub fn t<'a>(&'a self, key: &'a str) -> &'a str {
+        self.lookup(key).unwrap_or(key)
+    }
+
+    /// Localized label for a backend device lifecycle state.
+    pub fn device_state(&self, state: &str) -> &str {
+        self.lookup(&format!("state_{}", state))
+            .unwrap_or_else(|| self.ready_to_flash())
+    }
+
+    /// Localized label for a GPIO helper pin mode.
+    pub fn gpio_mode<'a>(&'a self, mode: &'a str) -> &'a str {
+        self.lookup(&format!("gpio_mode_{}", mode)).unwrap_or(mode)
+    }
+
+    /// Localized label for a BLE provisioning security choice.
+    pub fn ble_prov_security<'a>(&'a self, security: &'a str) -> &'a str {
+        self.lookup(&format!("ble_prov_security_{}", security))
+            .unwrap_or(security)
+    }
+
+    /// Localized label for a pin category from a board definition.
+    pub fn pin_tag<'a>(&'a self, tag: &'a str) -> &'a str {
+        self.lookup(&format!("pin_tag_{}", tag)).unwrap_or(tag)
+    }
+
+    /// Localized name of a pin role, e.g. "i2c".
+    pub fn pin_role<'a>(&'a self, role: &'a str) -> &'a str {
+        self.lookup(&format!("pin_role_{}", role)).unwrap_or(role)
+    }
+
+    /// Localized name of a soak test boot fault.
+    pub fn soak_fault<'a>(&'a self, fault: &'a str) -> &'a str {
+        self.lookup(&format!("soak_fault_{}", fault))
+            .unwrap_or(fault)
+    }
+
+    /// Localized name of a reset cause from the `reset-reason` event.
+    pub fn reset_cause<'a>(&'a self, cause: &'a str) -> &'a str {
+        self.lookup(&format!("reset_cause_{}", cause))
+            .unwrap_or(cause)
+    }
+
+    /// Localized explanation of a reset cause, and fixes to try one per
+    /// line when there are any.
+    pub fn reset_explanation(&self, cause: &str) -> (Option<&str>, Option<&str>) {
+        (
+            self.lookup(&format!("reset_explain_{}", cause)),
+            self.lookup(&format!("reset_fix_{}", cause)),
+        )
+    }
+
+    /// Localized name of an image kind from `place_image`.
+    pub fn drop_image_kind<'a>(&'a self, kind: &'a str) -> &'a str {
+        self.lookup(&format!("drop_kind_{}", kind)).unwrap_or(kind)
+    }
+
+    /// Localized label for a monitor trigger action kind.
+    pub fn trigger_action<'a>(&'a self, kind: &'a str) -> &'a str {
+        self.lookup(&format!("trigger_action_{}", kind))
+            .unwrap_or(kind)
+    }
+
+    /// Localized label for a flash task phase.
+    pub fn flash_phase(&self, phase: &str) -> &str {
+        self.lookup(&format!("flash_phase_{}", phase))
+            .unwrap_or_else(|| self.devices_flashing_status())
+    }
+
+    /// Localized title for a `notify` event kind.
+    pub fn toast_title(&self, kind: &str) -> &str {
+        if kind.ends_with("_cancelled") {
+            return self.toast_cancelled();
+        }
+        self.lookup(&format!("toast_{}", kind))
+            .unwrap_or_else(|| self.toast_notice())
+    }
+
+    /// Localized title for an `AppError` kind sent by the backend.
+    pub fn error_kind(&self, kind: &str) -> &str {
+        self.lookup(&format!("error_{}", snake_case(kind)))
+            .unwrap_or_else(|| self.error_internal())
+    }
+
+    /// Localized remediation hint for an `AppError` kind, if there is one.
+    pub fn error_hint(&self, kind: &str) -> Option<&str> {
+        self.lookup(&format!("hint_{}", snake_case(kind)))
+    }
+}
+
+/// `PortNotFound` -> `port_not_found`, to derive keys from error kinds.
+fn snake_case(kind: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in kind.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+// One accessor per key the UI uses, so a typo is a compile error
+macro_rules! keys {
+    ($($key:ident),* $(,)?) => {
+        impl Dict {
+            $(
+                pub fn $key(&self) -> &str {
+                    self.t(stringify!($key))
+                }
+            )*
+        }
+    };
+}
+
+keys! {
+    device_status_title, device_status_subtitle, device_disconnected, settings, connect,
+    quick_actions_title, flash_firmware, monitor, files, home_nav, devices_nav, settings_nav,
+    ready_to_flash, probing_error, state_detected, state_monitoring, state_flashing,
+    state_error, connection_info, hardware_details, port, vid_pid, serial_number, chip_model,
+    flash_size, mac_address, chip_revision, crystal_frequency, features, wafer_revision,
+    wafer_major, wafer_minor, package, rom_version, sdk_version, crystal_warning,
+    secure_download_mode, crystal_warning_hint, flash_location, psram, memory_embedded,
+    memory_external, memory_quad, memory_octal, octal_memory_hint, capabilities, cores,
+    max_flash, connection_type, type_native_usb, type_uart_bridge, driver_check_btn,
+    driver_installed, driver_not_found, devices_title_flashing, devices_no_ports,
+    toast_flash_completed, toast_flash_failed, toast_erase_completed, toast_erase_failed,
+    toast_script_completed, toast_script_failed, toast_cancelled, toast_device_lost,
+    toast_device_error, toast_no_port, toast_monitor_failed, devices_btn_erasing, toast_notice,
+    devices_subtitle_flashing, devices_label_firmware_file, devices_placeholder_firmware_file,
+    devices_btn_browse, devices_label_flash_address, devices_btn_add_file,
+    devices_btn_remove_file, flash_row_no_file, flash_row_bad_address,
+    flash_row_duplicate_address, flash_row_unaligned, devices_flashing_status, flash_phase_reading,
+    flash_phase_connecting, flash_phase_writing, flash_phase_verifying, flash_phase_resetting,
+    flash_status_failed, devices_btn_start_flash, devices_btn_erase_flash,
+    devices_title_monitor, devices_subtitle_monitor, devices_label_baud_rate,
+    devices_log_placeholder, devices_input_placeholder, devices_btn_send,
+    devices_btn_disconnect, devices_btn_clear, devices_btn_split, split_monitor_title,
+    split_monitor_paused, monitor_tab, board_view_tab, board_view_title,
+    pinout_hint, pinout_no_drawing, pinout_functions, pin_tag_adc, pin_tag_touch,
+    pin_tag_strapping, pin_tag_usb, pin_tag_psram, pin_tag_jtag, pin_tag_uart, pin_tag_led,
+    pin_tag_power, pin_tag_ground, monitor_suspended, monitor_resumed, debug_tab, debug_title,
+    debug_subtitle, debug_btn_refresh, debug_btn_collect, debug_bundle_saved,
+    debug_bundle_failed, script_tab, script_title, script_subtitle, script_btn_run,
+    script_btn_stop, script_finished, gpio_tab, gpio_title, gpio_subtitle, gpio_hint,
+    gpio_btn_detect, gpio_btn_flash, gpio_flashing, gpio_helper_ready, gpio_helper_missing,
+    gpio_no_pins, gpio_high, gpio_low, gpio_mode_off, gpio_mode_in, gpio_mode_pullup,
+    gpio_mode_out, gpio_mode_adc, api_server_on, api_server_off, error_port_not_found,
+    error_port_busy, error_not_connected, error_wrong_chip, error_stub_timeout,
+    error_connection, error_flasher, error_secure_download, error_io, error_config,
+    error_invalid_input, error_cancelled, error_task_not_found, error_script, error_timeout,
+    error_internal, hint_port_not_found, hint_port_busy, hint_not_connected, hint_wrong_chip,
+    hint_stub_timeout, hint_connection, hint_secure_download, hint_io, hint_invalid_input,
+    hint_script, hint_timeout, onboarding_nav, onboarding_title, onboarding_subtitle,
+    onboarding_btn_check, onboarding_btn_retry, onboarding_btn_open_monitor,
+    onboarding_help_no_device, onboarding_help_driver_ch34x, onboarding_help_driver_cp210x,
+    onboarding_help_driver_ftdi, onboarding_help_driver_generic, onboarding_help_driver_replug,
+    onboarding_help_download_mode, onboarding_help_firmware, onboarding_help_monitor,
+    examples_tab, examples_title, examples_subtitle, examples_hint, examples_none,
+    examples_not_bundled, examples_flashed, ota_tab, ota_title, ota_subtitle, ota_hint,
+    ota_host_placeholder, ota_password_placeholder, ota_filesystem, ota_btn_upload,
+    ota_uploaded, ota_server_title, ota_server_subtitle, ota_server_hint, ota_server_btn_start,
+    ota_server_btn_stop, ota_server_http_note, ota_server_no_downloads, devices_label_source,
+    devices_source_serial, devices_source_udp, devices_source_tcp, devices_label_listen_port,
+    devices_listen_hint, provision_tab, improv_title, improv_subtitle, improv_hint,
+    improv_ssid_placeholder, improv_password_placeholder, improv_provisioning, improv_provisioned,
+    improv_btn_send, ble_prov_title, ble_prov_subtitle, ble_prov_hint, ble_prov_no_devices,
+    ble_prov_btn_scan, ble_prov_scanning, ble_prov_label_security, ble_prov_pop_placeholder,
+    ble_prov_username_placeholder, ble_prov_provisioning, ble_prov_provisioned,
+    devices_source_webrepl, devices_webrepl_host_placeholder, devices_webrepl_password_placeholder,
+    devices_webrepl_remote_placeholder, devices_webrepl_btn_download, devices_webrepl_btn_upload,
+    mpy_files_tab, mpy_files_title, mpy_files_subtitle, mpy_files_hint, mpy_files_no_repl,
+    mpy_files_btn_connect, mpy_files_btn_upload, mpy_files_btn_mkdir, mpy_files_folder_placeholder,
+    mpy_files_empty, mpy_files_saved, mpy_firmware_title, mpy_firmware_subtitle, mpy_firmware_hint,
+    mpy_firmware_latest, mpy_firmware_preview, mpy_firmware_sha256_placeholder,
+    mpy_firmware_btn_flash, mpy_firmware_downloading, mpy_firmware_flashed, circuitpy_title,
+    circuitpy_subtitle, circuitpy_hint, circuitpy_no_drive, circuitpy_no_project, circuitpy_btn_find,
+    circuitpy_btn_copy, circuitpy_btn_watch, circuitpy_btn_unwatch, circuitpy_copied,
+    circuitpy_up_to_date, circuitpy_watching, devices_btn_run_py, devices_btn_stop_py,
+    esptool_title, esptool_write_flash, esptool_erase_flash, esptool_flash_id, esptool_btn_copy,
+    esptool_incomplete, devices_btn_idf_build, devices_btn_pio_build, devices_build_loaded,
+    devices_btn_forget_build, arduino_tab, arduino_title, arduino_subtitle, arduino_cli_found,
+    arduino_cli_missing, arduino_btn_pick_sketch, arduino_no_sketch, arduino_fqbn,
+    arduino_btn_build, arduino_btn_build_flash, arduino_btn_stop, arduino_built, arduino_flashed,
+    arduino_log_placeholder, flash_history, test_title, test_subtitle, test_btn_pick, test_no_file,
+    test_btn_run, test_btn_stop, test_passed, test_failed, soak_title, soak_subtitle, soak_cycles,
+    soak_marker, soak_timeout, soak_interval, soak_btn_run, soak_btn_stop, soak_failed_boots,
+    boot_bootloader, boot_marker, boot_waiting, boot_average, boot_marker_placeholder,
+    boot_marker_hint, sign_title, sign_subtitle, sign_hint, sign_key_placeholder,
+    sign_paste_placeholder, sign_done, sign_btn, encrypt_title, encrypt_subtitle, encrypt_hint,
+    encrypt_offset, encrypt_key_placeholder, encrypt_btn, triggers_title, triggers_subtitle,
+    triggers_hint, triggers_pattern_placeholder, triggers_argument_placeholder, triggers_btn_add,
+    triggers_empty, triggers_enabled, monitor_repeats_hint, monitor_repeats_title, pin_usage_title,
+    pin_usage_subtitle, pin_usage_hint, pin_usage_btn_import, pin_usage_btn_clear, pin_usage_empty,
+    pin_usage_signal_placeholder, pin_usage_btn_add, reserved_pins_title, reserved_pins_subtitle,
+    reserved_pins_none, reserved_pins_used, devices_ota_slot, devices_ota_slot_hint, export_report,
+    label_title, label_subtitle, label_flashed, label_never_flashed, label_dpi_hint, label_btn_export,
+    label_empty, label_png_note, label_saved, config_title, config_subtitle, config_hint,
+    config_btn_export, config_btn_import, config_saved,
+    capture_title, capture_subtitle, capture_btn_record, capture_btn_stop, capture_recording,
+    capture_saved, capture_target_monitor, capture_target_port, capture_target_hint,
+    capture_speed, capture_btn_replay, capture_btn_stop_replay, capture_replayed,
+    devices_btn_terminal, devices_terminal_hint, devices_local_echo, devices_local_echo_hint,
+    devices_label_encoding, devices_btn_sync_time, devices_time_command_hint,
+    benchmark_title, benchmark_subtitle, benchmark_hint, benchmark_baud_rates, benchmark_size,
+    benchmark_offset, benchmark_offset_placeholder, benchmark_btn_run, benchmark_btn_stop,
+    benchmark_col_baud, benchmark_col_write, benchmark_col_read, benchmark_col_result, benchmark_ok,
+    benchmark_fastest, benchmark_btn_use, benchmark_in_use,
+    devices_btn_image_info, image_info_loading, image_info_chip, image_info_entry, image_info_flash,
+    image_info_segments, image_info_checksum, image_info_signature, image_info_valid,
+    image_info_invalid, image_info_not_appended,
+    devices_btn_from_elf, elf_image_hint, elf_image_chip, elf_image_flash_mode,
+    elf_image_flash_size, elf_image_flash_freq, elf_image_btn_convert,
+    elf_image_full, elf_image_partitions, elf_image_partitions_placeholder, elf_image_bootloader,
+    elf_image_bootloader_placeholder, elf_image_idf_version,
+    inventory_title, inventory_subtitle, inventory_hint, inventory_batch, inventory_operator,
+    inventory_note, inventory_btn_start, inventory_btn_stop, inventory_count, inventory_waiting,
+    inventory_duplicate,
+    devices_source_ble,
+    devices_ble_device_placeholder,
+    devices_ble_btn_scan,
+    devices_ble_scanning,
+    devices_ble_scan_hint,
+    monitor_moved,
+    devices_btn_watch_build,
+    devices_btn_stop_watch,
+    devices_watch_hint,
+    openocd_title,
+    openocd_subtitle,
+    openocd_hint,
+    openocd_probe_usb_jtag,
+    openocd_probe_esp_prog,
+    openocd_no_probes,
+    openocd_btn_probes,
+    openocd_btn_start,
+    openocd_btn_stop,
+    openocd_btn_status,
+    openocd_no_chip,
+    openocd_starting,
+    openocd_ready,
+    openocd_exited,
+    openocd_no_log,
+    gdb_title,
+    gdb_subtitle,
+    gdb_hint,
+    gdb_placeholder_elf,
+    gdb_btn_attach,
+    gdb_btn_detach,
+    gdb_btn_break,
+    gdb_btn_reset,
+    gdb_btn_continue,
+    gdb_btn_halt,
+    gdb_state_stopped,
+    gdb_state_running,
+    gdb_state_exited,
+    gdb_backtrace,
+    gdb_no_log,
+    gdb_console_placeholder,
+    registers_title,
+    registers_subtitle,
+    registers_hint,
+    registers_btn_load,
+    registers_loading,
+    registers_select,
+    registers_filter,
+    registers_btn_read,
+    registers_reset,
+    registers_empty,
+    devices_btn_defmt,
+    devices_defmt_hint,
+    devices_usb_jtag_baud,
+    coredump_title,
+    coredump_subtitle,
+    coredump_hint,
+    coredump_monitor_hint,
+    coredump_btn_monitor,
+    coredump_btn_flash,
+    coredump_btn_file,
+    coredump_reading,
+    coredump_elf_mismatch,
+    coredump_exception,
+    coredump_crashed,
+    coredump_stack_used,
+    coredump_guessed,
+    coredump_regions,
+    coredump_empty,
+    kiosk_title,
+    kiosk_subtitle,
+    kiosk_hint,
+    kiosk_image,
+    kiosk_golden_hash,
+    kiosk_password,
+    kiosk_btn_enter,
+    kiosk_btn_exit,
+    kiosk_btn_flash,
+    kiosk_flashing,
+    kiosk_ready,
+    kiosk_pass,
+    kiosk_fail,
+    kiosk_passed,
+    kiosk_failed,
+    kiosk_total,
+    device_db_title,
+    device_db_subtitle,
+    device_db_hint,
+    device_db_operator,
+    device_db_search,
+    device_db_result_all,
+    device_db_result_pass,
+    device_db_result_fail,
+    device_db_btn_refresh,
+    device_db_btn_export,
+    device_db_exported,
+    device_db_empty,
+    device_db_col_time,
+    device_db_col_action,
+    device_db_col_device,
+    device_db_col_firmware,
+    device_db_col_operator,
+    device_db_col_result,
+    production_run_title,
+    production_run_subtitle,
+    production_run_hint,
+    production_run_current,
+    production_run_placeholder,
+    production_run_outside,
+    production_run_btn_refresh,
+    production_run_btn_csv,
+    production_run_btn_report,
+    production_run_exported,
+    production_run_empty,
+    production_run_units,
+    production_run_passed,
+    production_run_failed,
+    production_run_attempts,
+    production_run_yield,
+    production_run_duration,
+    production_run_failures,
+    reset_last,
+    reset_fixes,
+    reset_dismiss,
+    notifications_title,
+    notifications_subtitle,
+    notifications_hint,
+    notifications_device_connected,
+    notifications_device_disconnected,
+    notifications_flash_completed,
+    notifications_flash_failed,
+    notifications_monitor_triggers,
+    drop_converted,
+    drop_confirm,
+    drop_btn_flash,
+    drop_btn_later,
+    flash_eta,
+    flash_estimate,
+    api_copy_token,
+}
+
+/// Locales the backend can serve; just English if it can't be reached.
+pub async fn list_locales() -> Vec<LocaleInfo> {
+    let fallback = || {
+        vec![LocaleInfo {
+            code: "en".to_string(),
+            name: "English".to_string(),
+        }]
+    };
+    match invoke("list_locales", JsValue::NULL).await {
+        Ok(val) => serde_wasm_bindgen::from_value(val).unwrap_or_else(|_| fallback()),
+        Err(_) => fallback(),
+    }
 }
 
-pub const EN_DICT: Dict = Dict {
-    device_status_title: "Device Status",
-    device_status_subtitle: "Current Connection",
-    device_disconnected: "Disconnected",
-    settings: "Settings",
-    connect: "Connect",
-
-    quick_actions_title: "Quick Actions",
-    flash_firmware: "Flash Firmware",
-    monitor: "Monitor",
-    files: "Files",
-
-    home_nav: "Home",
-    devices_nav: "Devices",
-    settings_nav: "Settings",
-
-    ready_to_flash: "Ready to flash",
-    probing_error: "Probing Error",
-    connection_info: "Connection Info",
-    hardware_details: "Hardware Details",
-    port: "Port",
-    vid_pid: "VID:PID",
-    serial_number: "Serial Number",
-    chip_model: "Model",
-    flash_size: "Flash Size",
-    mac_address: "MAC Address",
-    chip_revision: "Revision",
-    crystal_frequency: "Crystal Frequency",
-    features: "Features",
-
-    connection_type: "Type",
-    type_native_usb: "Native USB",
-    type_uart_bridge: "UART Bridge",
-
-    driver_check_btn: "Check Driver",
-    driver_installed: "Driver Installed",
-    driver_not_found: "Driver Not Found",
-
-    devices_title_flashing: "Firmware Flashing",
-    devices_subtitle_flashing: "Flash .bin files to ESP32",
-    devices_label_firmware_file: "Firmware File",
-    devices_placeholder_firmware_file: "/path/to/firmware.bin",
-    devices_btn_browse: "Browse",
-    devices_label_flash_address: "Flash Address (Hex)",
-    devices_flashing_status: "Flashing...",
-    devices_btn_start_flash: "Start Flash",
-    devices_btn_erase_flash: "Erase Flash",
-
-    devices_title_monitor: "Serial Monitor",
-    devices_subtitle_monitor: "Real-time logs",
-    devices_label_baud_rate: "Baud Rate",
-    devices_log_placeholder: "No logs yet...",
-    devices_input_placeholder: "Send command...",
-    devices_btn_send: "Send",
-    devices_btn_disconnect: "Disconnect",
-    devices_btn_clear: "Clear Logs",
-    monitor_tab: "Monitor",
-    board_view_tab: "Board View",
-    board_view_title: "Board View",
-};
-
-pub const ZH_DICT: Dict = Dict {
-    device_status_title: "设备状态",
-    device_status_subtitle: "当前连接",
-    device_disconnected: "未连接",
-    settings: "设置",
-    connect: "连接",
-
-    quick_actions_title: "快捷操作",
-    flash_firmware: "烧录固件",
-    monitor: "串口监视",
-    files: "文件管理",
-
-    home_nav: "主页",
-    devices_nav: "设备",
-    settings_nav: "设置",
-
-    ready_to_flash: "就绪",
-    probing_error: "读取失败",
-    connection_info: "连接信息",
-    hardware_details: "硬件详情",
-    port: "端口",
-    vid_pid: "VID:PID",
-    serial_number: "序列号",
-    chip_model: "芯片型号",
-    flash_size: "Flash 容量",
-    mac_address: "MAC 地址",
-    chip_revision: "芯片版本",
-    crystal_frequency: "晶振频率",
-    features: "功能特性",
-
-    connection_type: "连接类型",
-    type_native_usb: "原生 USB",
-    type_uart_bridge: "UART 桥接",
-
-    driver_check_btn: "检查驱动",
-    driver_installed: "驱动已安装",
-    driver_not_found: "未检测到 CH34X 驱动",
-
-    devices_title_flashing: "固件烧录",
-    devices_subtitle_flashing: "烧录 .bin 文件到 ESP32",
-    devices_label_firmware_file: "固件文件",
-    devices_placeholder_firmware_file: "/path/to/firmware.bin",
-    devices_btn_browse: "浏览",
-    devices_label_flash_address: "烧录地址 (Hex)",
-    devices_flashing_status: "正在烧录...",
-    devices_btn_start_flash: "开始烧录",
-    devices_btn_erase_flash: "清空 Flash",
-
-    devices_title_monitor: "串口监视器",
-    devices_subtitle_monitor: "实时日志监控",
-    devices_label_baud_rate: "波特率",
-    devices_log_placeholder: "暂无日志...",
-    devices_input_placeholder: "发送指令...",
-    devices_btn_send: "发送",
-    devices_btn_disconnect: "断开连接",
-    devices_btn_clear: "清空日志",
-    monitor_tab: "串口监视",
-    board_view_tab: "开发板视图",
-    board_view_title: "开发板视图",
-};
-
-pub fn get_dict(lang: Language) -> Dict {
-    match lang {
-        Language::En => EN_DICT,
-        Language::Zh => ZH_DICT,
+/// Strings for `code`; English if the locale can't be loaded.
+pub async fn load_locale(code: &str) -> Dict {
+    let args = serde_wasm_bindgen::to_value(&GetLocaleArgs {
+        code: code.to_string(),
+    })
+    .unwrap_or(JsValue::NULL);
+    match invoke("get_locale", args).await {
+        Ok(val) => match serde_wasm_bindgen::from_value::<HashMap<String, String>>(val) {
+            Ok(strings) => Dict::new(code, strings),
+            Err(_) => Dict::default(),
+        },
+        Err(e) => {
+            web_sys::console::error_1(&e);
+            Dict::default()
+        }
     }
 }
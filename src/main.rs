@@ -2,6 +2,7 @@ pub mod app;
 pub mod components;
 pub mod i18n;
 pub mod pages;
+pub mod settings;
 
 use app::App;
 use dioxus::prelude::*;
@@ -1,8 +1,19 @@
-use crate::components::{Button, Card, PinoutView};
-use crate::i18n::{get_dict, Language};
+use crate::components::{
+    use_toasts, ArduinoPanel, BenchmarkPanel, BleProvPanel, Button, CapturePanel, Card,
+    CircuitPyPanel, ConfigPanel, CoreDumpPanel, DebugLogPanel, DeviceRecordsPanel, ElfImagePanel,
+    EncryptPanel, EsptoolPanel, ExamplePanel, GdbPanel, GpioPanel, ImageInfoView, ImprovPanel,
+    InventoryPanel, KioskPanel, LabelPanel, MpyFilesPanel, MpyFirmwarePanel, NotificationsPanel,
+    OpenOcdPanel, OtaPanel, OtaServerPanel, PinUsagePanel, PinoutView, ProductionRunPanel,
+    RegisterPanel, ReservedPinsPanel, ScriptPanel, SigningPanel, SoakPanel, SplitMonitorPanel,
+    TerminalView, TestPanel, TriggersPanel,
+};
+use crate::i18n::Dict;
+use crate::settings::{update_settings, Settings};
+use dioxus::html::geometry::PixelsVector2D;
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -26,19 +37,224 @@ struct DeviceStatus {
     connection_type: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct FlashArgs {
+struct SerialPortEntry {
     port_name: String,
+    description: Option<String>,
+    vid_pid: Option<String>,
+    is_esp: bool,
+}
+
+impl SerialPortEntry {
+    fn label(&self) -> String {
+        match &self.description {
+            Some(desc) => format!("{} ({})", self.port_name, desc),
+            None => self.port_name.clone(),
+        }
+    }
+}
+
+async fn fetch_serial_ports() -> Vec<SerialPortEntry> {
+    match invoke("list_serial_ports", JsValue::NULL).await {
+        Ok(val) => serde_wasm_bindgen::from_value(val).unwrap_or_default(),
+        Err(e) => {
+            web_sys::console::error_1(&e);
+            Vec::new()
+        }
+    }
+}
+
+/// One (file, address) row of the flashing form.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FlashRow {
     firmware_path: String,
     flash_address: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashSegmentsArgs {
+    port_name: String,
+    segments: Vec<FlashRow>,
+    expected_chip: Option<String>,
+    baud_rate: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashOtaSlotArgs {
+    port_name: String,
+    firmware_path: String,
+    expected_chip: Option<String>,
+    baud_rate: Option<u32>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct PlanSegment {
+    path: String,
+    address: u32,
+}
+
+/// Images of a build directory, e.g. from an ESP-IDF `flasher_args.json`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FlashPlan {
+    build_dir: String,
+    chip: Option<String>,
+    flash_mode: Option<String>,
+    flash_size: Option<String>,
+    flash_freq: Option<String>,
+    baud_rate: Option<u32>,
+    segments: Vec<PlanSegment>,
+}
+
+impl FlashPlan {
+    /// e.g. "esp32s3 · dio · 80m · 2MB"
+    fn summary(&self) -> String {
+        [
+            &self.chip,
+            &self.flash_mode,
+            &self.flash_freq,
+            &self.flash_size,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" · ")
+    }
+}
+
+/// A firmware file dropped onto the window, checked and placed by the
+/// backend.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct PlacedImage {
+    path: String,
+    kind: String,
+    address: u32,
+    chip: Option<String>,
+    converted: bool,
+}
+
+/// Same rules as the backend: hex with a 0x prefix, otherwise decimal.
+fn parse_flash_address(address: &str) -> Option<u32> {
+    let trimmed = address.trim();
+    let (digits, radix) = match trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        Some(hex) => (hex, 16),
+        None => (trimmed, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    u32::from_str_radix(digits, radix).ok()
+}
+
+// Images start on a flash sector
+const FLASH_SECTOR: u32 = 0x1000;
+
+/// Problem with row `index`, if any. Overlaps need the file sizes and are
+/// left to the backend.
+fn flash_row_error<'a>(rows: &[FlashRow], index: usize, dict: &'a Dict) -> Option<&'a str> {
+    let row = &rows[index];
+    let Some(address) = parse_flash_address(&row.flash_address) else {
+        return Some(dict.flash_row_bad_address());
+    };
+    if address % FLASH_SECTOR != 0 {
+        return Some(dict.flash_row_unaligned());
+    }
+    let duplicate = rows[..index]
+        .iter()
+        .any(|other| parse_flash_address(&other.flash_address) == Some(address));
+    if duplicate {
+        return Some(dict.flash_row_duplicate_address());
+    }
+    if row.firmware_path.trim().is_empty() {
+        return Some(dict.flash_row_no_file());
+    }
+    None
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct MonitorConnectArgs {
     port_name: String,
     baud_rate: u32,
+    boot_marker: Option<String>,
+}
+
+/// Milestones of one boot in ms after the ROM banner, the `boot-timing`
+/// event payload.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct BootTiming {
+    at: u64,
+    bootloader_ms: Option<u64>,
+    app_main_ms: Option<u64>,
+    marker_ms: Option<u64>,
+}
+
+/// Why the board last reset, the `reset-reason` event payload.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ResetReason {
+    cause: String,
+    rom_reason: String,
+    banner: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBootHistoryArgs {
+    port_name: String,
+}
+
+#[derive(Serialize)]
+struct MonitorListenArgs {
+    protocol: String,
+    port: u16,
+}
+
+#[derive(Serialize)]
+struct MonitorWebreplArgs {
+    host: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+struct MonitorBleArgs {
+    device: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct BleDevice {
+    id: String,
+    name: String,
+    rssi: Option<i16>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebreplDownloadArgs {
+    remote_path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MpyRunArgs {
+    port_name: String,
+    file_path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelTaskArgs {
+    task_id: u64,
 }
 
 #[derive(Serialize)]
@@ -46,6 +262,161 @@ struct MonitorSendArgs {
     data: String,
 }
 
+#[derive(Serialize)]
+struct MonitorLinesArgs {
+    start: Option<u64>,
+    count: usize,
+}
+
+/// A window of the backend's monitor output; `first..end` are the line
+/// numbers it still holds, `start` is the number of `lines[0]`, which
+/// `repeats[0]` more lines repeated.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct MonitorLines {
+    first: u64,
+    end: u64,
+    start: u64,
+    lines: Vec<String>,
+    repeats: Vec<u32>,
+}
+
+#[derive(Serialize)]
+struct MonitorRepeatsArgs {
+    number: u64,
+}
+
+// The log view is virtualized: only the lines in sight (plus a margin) are
+// in the DOM, so every line must have the same height.
+const LOG_LINE_HEIGHT: f64 = 18.0;
+const LOG_VIEW_HEIGHT: f64 = 400.0;
+const LOG_OVERSCAN: u64 = 30;
+
+// Default port for network logs, as in the backend
+const NET_LOG_PORT: u16 = 5140;
+
+// Right-hand panel tabs, also accepted as the `tab` route parameter
+const TABS: [&str; 10] = [
+    "monitor",
+    "pinout",
+    "gpio",
+    "examples",
+    "ota",
+    "provision",
+    "files",
+    "debug",
+    "script",
+    "arduino",
+];
+
+/// Payload of the backend `task-progress` event.
+#[derive(Deserialize, Clone, Debug)]
+struct TaskInfo {
+    id: u64,
+    kind: String,
+    state: String, // "running", "completed", "failed", "cancelled"
+    phase: String,
+    current: u64,
+    total: u64,
+    port_name: Option<String>,
+    message: Option<String>,
+    bytes_per_sec: Option<u64>,
+    eta_secs: Option<u64>,
+}
+
+/// One past flash of a board, as kept by the backend.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FlashRecord {
+    at: u64,
+    bytes: u64,
+    bytes_per_sec: u64,
+    baud_rate: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetFlashHistoryArgs {
+    port_name: String,
+}
+
+fn format_rate(bytes_per_sec: u64) -> String {
+    format!("{:.1} KB/s", bytes_per_sec as f64 / 1024.0)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EstimateFlashArgs {
+    segments: Vec<FlashRow>,
+    baud_rate: Option<u32>,
+}
+
+/// How long the backend expects flashing the rows to take.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FlashEstimate {
+    baud_rate: u32,
+    secs: u64,
+}
+
+/// e.g. "~40 s" or "~2 min 5 s"
+fn format_eta(secs: u64) -> String {
+    if secs < 60 {
+        format!("~{} s", secs)
+    } else {
+        format!("~{} min {} s", secs / 60, secs % 60)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MonitorStatus {
+    state: String, // "disconnected", "connected", "reconnecting", "suspended", "listening", "webrepl", "ble"
+    port_name: Option<String>,
+    baud_rate: Option<u32>,
+    local_echo: bool,
+    encoding: String, // "utf8", "gbk", "latin1"
+    defmt_elf: Option<String>,
+}
+
+#[derive(Serialize)]
+struct MonitorSetEchoArgs {
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct MonitorSetEncodingArgs {
+    encoding: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorSetDefmtArgs {
+    elf_path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct DevicePrefs {
+    port_name: String,
+    baud_rate: u32,
+    boot_marker: Option<String>,
+    local_echo: Option<bool>,
+    encoding: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetDevicePrefsArgs {
+    serial_number: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveDevicePrefsArgs {
+    serial_number: String,
+    prefs: DevicePrefs,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct ChipDetails {
     chip_model: Option<String>,
@@ -54,7 +425,12 @@ struct ChipDetails {
     chip_revision: Option<String>,
     crystal_frequency: Option<String>,
     features: Option<String>,
-    error: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct AppError {
+    kind: String,
+    message: String,
 }
 
 #[derive(Serialize)]
@@ -63,41 +439,654 @@ struct GetChipInfoArgs {
     port_name: String,
 }
 
+/// `tab` and `port` come from the route query; empty means the defaults
+/// (Monitor tab, auto-detected port).
 #[component]
-pub fn Devices() -> Element {
+pub fn Devices(tab: String, port: String) -> Element {
+    let mut settings = use_context::<Signal<Settings>>();
+
     // Shared State
-    let mut port_name = use_signal(|| "".to_string());
+    let mut port_name = use_signal(|| port.clone());
+    let mut serial_ports = use_signal(Vec::<SerialPortEntry>::new);
 
     // Flashing State
-    let mut firmware_path = use_signal(|| "".to_string());
-    let mut flash_address = use_signal(|| "0x0".to_string());
+    let mut flash_rows = use_signal(|| {
+        vec![FlashRow {
+            firmware_path: "".to_string(),
+            flash_address: settings.peek().flash.address.clone(),
+        }]
+    });
+    // Missing files are only flagged once the user tries to flash
+    let mut flash_attempted = use_signal(|| false);
+    // Row whose image header is shown below it
+    let mut inspected_row = use_signal(|| None::<usize>);
+    // Converter for builds that only leave an ELF
+    let mut show_elf_image = use_signal(|| false);
+    // Write a single app to the inactive OTA slot instead of its address
+    let mut flash_ota = use_signal(|| false);
+    // Image dropped onto the window, waiting to be confirmed for flashing
+    let mut dropped = use_signal(|| None::<PlacedImage>);
+    let mut drag_over = use_signal(|| false);
     let mut is_flashing = use_signal(|| false);
     let mut is_erasing = use_signal(|| false);
-    let mut erase_msg = use_signal(|| "".to_string());
     let mut flash_progress = use_signal(|| 0.0);
+    let mut flash_rate = use_signal(|| None::<u64>);
+    let mut flash_eta = use_signal(|| None::<u64>);
+    let mut flash_phase = use_signal(|| "starting".to_string());
+    // Why the last flash failed; keeps the bar up in its error state
+    let mut flash_error = use_signal(|| None::<String>);
+    // Build the rows were loaded from; its chip is checked before flashing
+    let mut flash_build = use_signal(|| None::<FlashPlan>);
+    // The form's images are flashed again whenever a build rewrites them
+    let mut flash_watching = use_signal(|| false);
 
     // Monitor State
-    let mut baud_rate = use_signal(|| "115200".to_string());
+    let mut baud_rate = use_signal(|| settings.peek().default_baud.to_string());
     let mut is_connected = use_signal(|| false);
-    let mut logs = use_signal(|| Vec::<String>::new()); // Mock logs
+    // Regex ending a timed boot instead of app_main; applies on connect
+    let mut boot_marker = use_signal(String::new);
+    let mut last_boot = use_signal(|| None::<BootTiming>);
+    let mut last_reset = use_signal(|| None::<ResetReason>);
+    // "serial", "udp"/"tcp" to watch logs a board forwards over the network,
+    // "webrepl" for the REPL of a MicroPython board on Wi-Fi, or "ble" for
+    // a logger's Nordic UART service
+    let mut monitor_source = use_signal(|| "serial".to_string());
+    let mut listen_port = use_signal(|| NET_LOG_PORT.to_string());
+    let mut webrepl_host = use_signal(String::new);
+    let mut webrepl_password = use_signal(String::new);
+    let mut webrepl_remote = use_signal(String::new);
+    let mut ble_device = use_signal(String::new);
+    let mut ble_devices = use_signal(Vec::<BleDevice>::new);
+    let mut ble_scanning = use_signal(|| false);
+    // MicroPython program running from a local file, and its task once known
+    let mut py_running = use_signal(|| false);
+    let mut py_task = use_signal(|| None::<u64>);
+    let mut log_window = use_signal(MonitorLines::default);
+    let mut log_scroll = use_signal(|| 0.0);
+    // Stick to the newest line until the user scrolls up
+    let mut log_follow = use_signal(|| true);
+    let mut log_view = use_signal(|| None::<Rc<MountedData>>);
+    let mut log_fetching = use_signal(|| false);
+    let mut log_stale = use_signal(|| false);
+    // Raw lines of a folded line opened from its repeat count
+    let mut log_run = use_signal(|| None::<Vec<String>>);
+    // Key of the note shown above the log while the flasher borrows the port
+    let mut monitor_note = use_signal(|| None::<&'static str>);
+    // Screen of an interactive console in place of the line log
+    let mut terminal_mode = use_signal(|| false);
+    // Second monitor session next to the main one
+    let mut split_view = use_signal(|| false);
+    // Sent lines shown in the output, for firmware that doesn't echo them
+    let mut local_echo = use_signal(|| true);
+    // Command setting the board's clock, saved to the settings on change
+    let mut time_command = use_signal(|| settings.peek().time_command.clone());
+    // How received bytes are decoded; applies from the next byte on
+    let mut encoding = use_signal(|| "utf8".to_string());
+    // App ELF whose defmt table decodes the output, if any
+    let mut defmt_elf = use_signal(|| None::<String>);
     let mut input_cmd = use_signal(|| "".to_string());
+    let mut input_view = use_signal(|| None::<Rc<MountedData>>);
+
+    // Serial number of the detected device, used to remember its settings
+    let mut device_serial = use_signal(|| None::<String>);
 
     // Tab State
-    let mut active_tab = use_signal(|| "monitor".to_string());
+    let mut active_tab = use_signal(|| {
+        if TABS.contains(&tab.as_str()) {
+            tab.clone()
+        } else {
+            "monitor".to_string()
+        }
+    });
     let mut detected_model = use_signal(|| "ESP32-S3".to_string()); // Default or detected
     let mut detected_connection_type = use_signal(|| None::<String>);
     let mut chip_details_info = use_signal(|| None::<ChipDetails>);
 
-    let lang = use_context::<Signal<Language>>();
-    let dict = get_dict(*lang.read());
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let toasts = use_toasts();
+
+    // Loads the lines around the scroll position. Requests made while a fetch
+    // is running collapse into one more round, so bursts of serial data cost
+    // a few invokes instead of one per event.
+    let mut refresh_log = move || {
+        if *log_fetching.peek() {
+            log_stale.set(true);
+            return;
+        }
+        log_fetching.set(true);
+        spawn(async move {
+            loop {
+                log_stale.set(false);
+                let in_view = (LOG_VIEW_HEIGHT / LOG_LINE_HEIGHT).ceil() as u64;
+                let start = if *log_follow.peek() {
+                    None
+                } else {
+                    let top =
+                        log_window.peek().first + (*log_scroll.peek() / LOG_LINE_HEIGHT) as u64;
+                    Some(top.saturating_sub(LOG_OVERSCAN))
+                };
+                let args = serde_wasm_bindgen::to_value(&MonitorLinesArgs {
+                    start,
+                    count: (in_view + 2 * LOG_OVERSCAN) as usize,
+                })
+                .unwrap_or(JsValue::NULL);
+                match invoke("monitor_lines", args).await {
+                    Ok(val) => {
+                        if let Ok(window) = serde_wasm_bindgen::from_value::<MonitorLines>(val) {
+                            if *log_window.peek() != window {
+                                log_window.set(window);
+                            }
+                        }
+                    }
+                    Err(e) => web_sys::console::error_1(&e),
+                }
+                if !*log_stale.peek() {
+                    break;
+                }
+                // Let a burst of output settle before the next round
+                gloo_timers::future::TimeoutFuture::new(50).await;
+            }
+            log_fetching.set(false);
+        });
+    };
+
+    // The backend echoes sent commands into the log
+    let mut send_input = move || {
+        let cmd = input_cmd.read().clone();
+        if cmd.is_empty() {
+            return;
+        }
+        input_cmd.set("".to_string());
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MonitorSendArgs { data: cmd }).unwrap();
+            if invoke("monitor_send", args).await.is_ok() {
+                log_follow.set(true);
+                refresh_log();
+            }
+        });
+    };
+
+    // The backend fills in the host time and echoes the line it sent
+    let sync_time = move || {
+        spawn(async move {
+            match invoke("sync_device_time", JsValue::NULL).await {
+                Ok(_) => {
+                    log_follow.set(true);
+                    refresh_log();
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                        .ok();
+                    toasts.push("error", "time_sync_failed", detail);
+                }
+            }
+        });
+    };
+
+    let save_time_command = move || {
+        let mut updated = settings.peek().clone();
+        updated.time_command = time_command.peek().trim().to_string();
+        if updated == *settings.peek() {
+            return;
+        }
+        spawn(async move {
+            match update_settings(updated).await {
+                Ok(saved) => settings.set(saved),
+                Err(e) => {
+                    // Back to the command still in effect
+                    time_command.set(settings.peek().time_command.clone());
+                    let dict = lang.peek().clone();
+                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                        .ok();
+                    toasts.push("error", "time_sync_failed", detail);
+                }
+            }
+        });
+    };
+
+    let webrepl_failed = move |e: JsValue| {
+        let dict = lang.peek().clone();
+        let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+            .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+            .ok();
+        toasts.push("error", "webrepl_failed", detail);
+    };
+
+    let webrepl_upload = move || {
+        spawn(async move {
+            match invoke("webrepl_upload", JsValue::NULL).await {
+                Ok(val) => {
+                    if let Ok(Some(name)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                        webrepl_remote.set(name.clone());
+                        toasts.push("success", "webrepl_uploaded", Some(name));
+                    }
+                }
+                Err(e) => webrepl_failed(e),
+            }
+        });
+    };
+
+    let webrepl_download = move || {
+        let remote_path = webrepl_remote.read().trim().to_string();
+        if remote_path.is_empty() {
+            return;
+        }
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&WebreplDownloadArgs { remote_path })
+                .unwrap_or(JsValue::NULL);
+            match invoke("webrepl_download", args).await {
+                Ok(val) => {
+                    if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                        toasts.push("success", "webrepl_downloaded", Some(path));
+                    }
+                }
+                Err(e) => webrepl_failed(e),
+            }
+        });
+    };
+
+    let run_python = move || {
+        let port = port_name.peek().clone();
+        if port.is_empty() {
+            toasts.push("error", "no_port", None);
+            return;
+        }
+        spawn(async move {
+            let Ok(val) = invoke("pick_python_file", JsValue::NULL).await else {
+                return;
+            };
+            let Ok(Some(file_path)) = serde_wasm_bindgen::from_value::<Option<String>>(val) else {
+                return;
+            };
+            let args = serde_wasm_bindgen::to_value(&MpyRunArgs {
+                port_name: port,
+                file_path,
+            })
+            .unwrap_or(JsValue::NULL);
+            py_running.set(true);
+            log_follow.set(true);
+            // The outcome is the closing line in the log and the task toast
+            invoke("mpy_run", args).await.ok();
+            py_running.set(false);
+            py_task.set(None);
+            refresh_log();
+        });
+    };
+
+    let stop_python = move || {
+        if let Some(task_id) = *py_task.peek() {
+            spawn(async move {
+                let args = serde_wasm_bindgen::to_value(&CancelTaskArgs { task_id })
+                    .unwrap_or(JsValue::NULL);
+                invoke("cancel_task", args).await.ok();
+            });
+        }
+    };
+
+    let clear_log = move || {
+        spawn(async move {
+            invoke("monitor_clear", JsValue::NULL).await.ok();
+            log_run.set(None);
+            log_follow.set(true);
+            refresh_log();
+        });
+    };
+
+    let show_run = move |number: u64| {
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&MonitorRepeatsArgs { number })
+                .unwrap_or(JsValue::NULL);
+            if let Ok(val) = invoke("monitor_repeats", args).await {
+                if let Ok(lines) = serde_wasm_bindgen::from_value::<Vec<String>>(val) {
+                    log_run.set(Some(lines));
+                }
+            }
+        });
+    };
+
+    // The input only exists on the Monitor tab, so switch there first
+    let mut focus_input = move || {
+        active_tab.set("monitor".to_string());
+        spawn(async move {
+            // Wait a frame for the tab to mount
+            gloo_timers::future::TimeoutFuture::new(0).await;
+            if let Some(view) = input_view.peek().clone() {
+                view.set_focus(true).await.ok();
+            }
+        });
+    };
+
+    let mut start_flash = move || {
+        let rows = flash_rows.peek().clone();
+        let port = port_name.peek().clone(); // Use dynamic port
+        let dict = lang.peek().clone();
+        let ota = *flash_ota.peek() && rows.len() == 1;
+        if (0..rows.len()).any(|i| flash_row_error(&rows, i, &dict).is_some()) {
+            flash_attempted.set(true);
+            return;
+        }
+        // Starting a flash answers the prompt for a dropped image too
+        dropped.set(None);
+
+        spawn(async move {
+            if port.is_empty() {
+                toasts.push("error", "no_port", None);
+                return;
+            }
+
+            is_flashing.set(true);
+            flash_progress.set(0.0);
+            flash_rate.set(None);
+            flash_eta.set(None);
+            flash_phase.set("starting".to_string());
+            flash_error.set(None);
+
+            let expected_chip = flash_build.peek().as_ref().and_then(|b| b.chip.clone());
+            let baud_rate = flash_build.peek().as_ref().and_then(|b| b.baud_rate);
+            let (command, args) = if ota {
+                let args = FlashOtaSlotArgs {
+                    port_name: port,
+                    firmware_path: rows[0].firmware_path.clone(),
+                    expected_chip,
+                    baud_rate,
+                };
+                ("flash_ota_slot", serde_wasm_bindgen::to_value(&args))
+            } else {
+                let args = FlashSegmentsArgs {
+                    port_name: port,
+                    segments: rows,
+                    expected_chip,
+                    baud_rate,
+                };
+                ("flash_segments", serde_wasm_bindgen::to_value(&args))
+            };
+            // Progress comes in as `task-progress` events meanwhile
+            if let Err(e) = invoke(command, args.unwrap()).await {
+                web_sys::console::error_1(&e);
+                if flash_error.peek().is_none() {
+                    let message = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| err.message)
+                        .unwrap_or_default();
+                    flash_error.set(Some(message));
+                }
+            }
+            is_flashing.set(false);
+        });
+    };
+
+    let mut toggle_watch = move || {
+        let rows = flash_rows.peek().clone();
+        let port = port_name.peek().clone();
+        let dict = lang.peek().clone();
+        let watching = *flash_watching.peek();
+        if !watching && (0..rows.len()).any(|i| flash_row_error(&rows, i, &dict).is_some()) {
+            flash_attempted.set(true);
+            return;
+        }
+        spawn(async move {
+            if watching {
+                if invoke("flash_unwatch", JsValue::NULL).await.is_ok() {
+                    flash_watching.set(false);
+                }
+                return;
+            }
+            if port.is_empty() {
+                toasts.push("error", "no_port", None);
+                return;
+            }
+            let args = FlashSegmentsArgs {
+                port_name: port,
+                segments: rows,
+                expected_chip: flash_build.peek().as_ref().and_then(|b| b.chip.clone()),
+                baud_rate: flash_build.peek().as_ref().and_then(|b| b.baud_rate),
+            };
+            match invoke("flash_watch", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(_) => flash_watching.set(true),
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                        .ok();
+                    toasts.push("error", "flash_watch_failed", detail);
+                }
+            }
+        });
+    };
+
+    // A watch keeps running when the page is left; show it again
+    use_effect(move || {
+        spawn(async move {
+            if let Ok(val) = invoke("flash_watch_status", JsValue::NULL).await {
+                if let Ok(watching) = serde_wasm_bindgen::from_value::<bool>(val) {
+                    flash_watching.set(watching);
+                }
+            }
+        });
+    });
+
+    // `command` asks for a project folder and reads its build, e.g. ESP-IDF's
+    let load_build = move |command: &'static str| {
+        spawn(async move {
+            match invoke(command, JsValue::NULL).await {
+                Ok(val) => {
+                    let Ok(Some(plan)) = serde_wasm_bindgen::from_value::<Option<FlashPlan>>(val)
+                    else {
+                        return;
+                    };
+                    flash_rows.set(
+                        plan.segments
+                            .iter()
+                            .map(|segment| FlashRow {
+                                firmware_path: segment.path.clone(),
+                                flash_address: format!("0x{:X}", segment.address),
+                            })
+                            .collect(),
+                    );
+                    flash_attempted.set(false);
+                    flash_build.set(Some(plan));
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                        .ok();
+                    toasts.push("error", "build_failed", detail);
+                }
+            }
+        });
+    };
+
+    // Into the row for the same address, else the first empty one; a merged
+    // image replaces them all
+    let drop_image = move |path: String| {
+        // An ELF is converted for the board on the selected port
+        let port = Some(port_name.peek().clone()).filter(|port| !port.is_empty());
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&json!({ "path": path, "portName": port }))
+                .unwrap_or(JsValue::NULL);
+            match invoke("place_image", args).await {
+                Ok(val) => {
+                    let placed = match serde_wasm_bindgen::from_value::<PlacedImage>(val) {
+                        Ok(placed) => placed,
+                        Err(e) => {
+                            toasts.push("error", "drop_failed", Some(e.to_string()));
+                            return;
+                        }
+                    };
+                    let row = FlashRow {
+                        firmware_path: placed.path.clone(),
+                        flash_address: format!("0x{:X}", placed.address),
+                    };
+                    {
+                        let mut rows = flash_rows.write();
+                        if placed.kind == "merged" {
+                            *rows = vec![row];
+                        } else {
+                            let at = rows
+                                .iter()
+                                .position(|r| {
+                                    parse_flash_address(&r.flash_address) == Some(placed.address)
+                                })
+                                .or_else(|| {
+                                    rows.iter().position(|r| r.firmware_path.trim().is_empty())
+                                });
+                            match at {
+                                Some(i) => rows[i] = row,
+                                None => rows.push(row),
+                            }
+                        }
+                    }
+                    if placed.kind != "app" {
+                        flash_ota.set(false);
+                    }
+                    inspected_row.set(None);
+                    flash_attempted.set(false);
+                    dropped.set(Some(placed));
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                        .ok();
+                    toasts.push("error", "drop_failed", detail);
+                }
+            }
+        });
+    };
+
+    let reset_device = move || {
+        let port = port_name.peek().clone();
+        spawn(async move {
+            if port.is_empty() {
+                toasts.push("error", "no_port", None);
+                return;
+            }
+            let args =
+                serde_wasm_bindgen::to_value(&json!({ "portName": port })).unwrap_or(JsValue::NULL);
+            if let Err(e) = invoke("reset_device", args).await {
+                let dict = lang.peek().clone();
+                let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                    .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                    .ok();
+                toasts.push("error", "reset_failed", detail);
+            }
+        });
+    };
+
+    // Keep the newest line in sight while following
+    use_effect(move || {
+        let window = log_window.read();
+        if !*log_follow.peek() {
+            return;
+        }
+        let height = (window.end - window.first) as f64 * LOG_LINE_HEIGHT;
+        if let Some(view) = log_view.peek().clone() {
+            spawn(async move {
+                view.scroll(PixelsVector2D::new(0.0, height), ScrollBehavior::Instant)
+                    .await
+                    .ok();
+            });
+        }
+    });
+
+    // Auto-detect port on mount
+    let requested_port = port.clone();
+    use_effect(move || {
+        let requested_port = requested_port.clone();
+        spawn(async move {
+            // Pick up a session started elsewhere (e.g. through the local API)
+            if let Ok(val) = invoke("monitor_status", JsValue::NULL).await {
+                if let Ok(status) = serde_wasm_bindgen::from_value::<MonitorStatus>(val) {
+                    local_echo.set(status.local_echo);
+                    encoding.set(status.encoding.clone());
+                    defmt_elf.set(status.defmt_elf.clone());
+                    if status.state == "webrepl" {
+                        if let Some(address) = status.port_name.as_deref() {
+                            webrepl_host.set(address.trim_start_matches("ws://").to_string());
+                        }
+                        monitor_source.set("webrepl".to_string());
+                        is_connected.set(true);
+                        return;
+                    }
+                    if status.state == "ble" {
+                        if let Some(address) = status.port_name.as_deref() {
+                            ble_device.set(address.trim_start_matches("ble://").to_string());
+                        }
+                        monitor_source.set("ble".to_string());
+                        is_connected.set(true);
+                        return;
+                    }
+                    if status.state == "listening" {
+                        if let Some((protocol, port)) =
+                            status.port_name.as_deref().and_then(|a| a.split_once(':'))
+                        {
+                            monitor_source.set(protocol.to_string());
+                            listen_port.set(port.to_string());
+                        }
+                        is_connected.set(true);
+                        return;
+                    }
+                    if status.state != "disconnected" {
+                        if let Some(p) = status.port_name {
+                            port_name.set(p);
+                        }
+                        if let Some(baud) = status.baud_rate {
+                            baud_rate.set(baud.to_string());
+                        }
+                        is_connected.set(true);
+                        return;
+                    }
+                }
+            }
 
-    // Auto-detect port on mount
-    use_effect(move || {
-        spawn(async move {
             if let Ok(js_res) = invoke("check_device_status", JsValue::NULL).await {
                 if let Ok(res) = serde_wasm_bindgen::from_value::<DeviceStatus>(js_res) {
+                    // Restore the last settings used with this device
+                    if let Some(sn) = res.serial_number.clone() {
+                        device_serial.set(Some(sn.clone()));
+                        spawn(async move {
+                            let args = serde_wasm_bindgen::to_value(&GetDevicePrefsArgs {
+                                serial_number: sn,
+                            })
+                            .unwrap_or(JsValue::NULL);
+                            if let Ok(val) = invoke("get_device_prefs", args).await {
+                                if let Ok(Some(prefs)) =
+                                    serde_wasm_bindgen::from_value::<Option<DevicePrefs>>(val)
+                                {
+                                    // The freshly detected port wins; the OS may have renumbered it
+                                    if port_name.read().is_empty() {
+                                        port_name.set(prefs.port_name);
+                                    }
+                                    baud_rate.set(prefs.baud_rate.to_string());
+                                    if let Some(marker) = prefs.boot_marker {
+                                        boot_marker.set(marker);
+                                    }
+                                    if let Some(enabled) = prefs.local_echo {
+                                        local_echo.set(enabled);
+                                        let args = serde_wasm_bindgen::to_value(&MonitorSetEchoArgs { enabled })
+                                            .unwrap_or(JsValue::NULL);
+                                        invoke("monitor_set_echo", args).await.ok();
+                                    }
+                                    if let Some(value) = prefs.encoding {
+                                        encoding.set(value.clone());
+                                        let args = serde_wasm_bindgen::to_value(&MonitorSetEncodingArgs { encoding: value })
+                                            .unwrap_or(JsValue::NULL);
+                                        invoke("monitor_set_encoding", args).await.ok();
+                                    }
+                                }
+                            }
+                        });
+                    }
+
                     if let Some(p) = res.port_name.clone() {
-                        port_name.set(p.clone());
+                        // A port passed in the link wins over detection
+                        if requested_port.is_empty() {
+                            port_name.set(p.clone());
+                        }
 
                         if let Some(conn_type) = res.connection_type.clone() {
                             detected_connection_type.set(Some(conn_type));
@@ -138,16 +1127,16 @@ pub fn Devices() -> Element {
 
     // Listener cleanup guard
     struct ListenerGuard {
-        unlisten: Option<js_sys::Function>,
-        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+        unlisten: Vec<js_sys::Function>,
+        _closures: Vec<Closure<dyn FnMut(JsValue)>>,
     }
     impl Drop for ListenerGuard {
         fn drop(&mut self) {
             // Unlisten
-            if let Some(f) = &self.unlisten {
-                web_sys::console::log_1(&"Unlistening serial-read".into());
+            for f in &self.unlisten {
                 f.call0(&JsValue::NULL).ok();
             }
+            web_sys::console::log_1(&"Unlistened device events".into());
 
             // Auto-disconnect backend
             spawn(async move {
@@ -159,8 +1148,8 @@ pub fn Devices() -> Element {
     // We use a signal to hold the guard so it drops when the component is unmounted
     let mut listener_guard = use_signal(|| {
         Chunk(ListenerGuard {
-            unlisten: None,
-            _closure: None,
+            unlisten: Vec::new(),
+            _closures: Vec::new(),
         })
     });
     // Helper wrapper because ListenerGuard doesn't implement Clone/PartialEq which Signal might want,
@@ -169,32 +1158,273 @@ pub fn Devices() -> Element {
     // Dioxus 0.5 Signal holds RefCell<T>.
     struct Chunk(ListenerGuard);
 
-    // Listen for serial data
+    // Keep the port list current; device-state fires on every hotplug
     use_effect(move || {
         spawn(async move {
+            serial_ports.set(fetch_serial_ports().await);
+
             let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
                 #[derive(Deserialize)]
-                struct SerialEvent {
-                    payload: String,
+                struct Snapshot {
+                    key: String,
+                    chip: Option<ChipDetails>,
+                }
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: Snapshot,
+                }
+                // Cached details are answered first and re-probed after
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    if let Some(chip) = e.payload.chip {
+                        if *port_name.peek() == e.payload.key {
+                            if let Some(model) = chip.chip_model.clone() {
+                                detected_model.set(model);
+                            }
+                            chip_details_info.set(Some(chip));
+                        }
+                    }
+                }
+                spawn(async move {
+                    let ports = fetch_serial_ports().await;
+                    // Pre-select the ESP board if nothing is chosen yet
+                    if port_name.peek().is_empty() {
+                        if let Some(esp) = ports.iter().find(|p| p.is_esp) {
+                            port_name.set(esp.port_name.clone());
+                        }
+                    }
+                    if *serial_ports.peek() != ports {
+                        serial_ports.set(ports);
+                    }
+                });
+            });
+
+            match listen("device-state", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.0.unlisten.push(f);
+                    }
+                    guard.0._closures.push(closure);
                 }
-                if let Ok(e) = serde_wasm_bindgen::from_value::<SerialEvent>(event) {
-                    // Check if write is safe? Dioxus panic implies we can't write if dropped.
-                    // But if we are here, closure is alive.
-                    // If component dropped, signal dropped?
-                    // The panic "Result::unwrap() on Err value: Dropped"
-                    // implies logs signal is accessed after drop.
-                    logs.write().push(e.payload);
+                Err(e) => {
+                    web_sys::console::error_1(&e);
                 }
+            }
+        });
+    });
+
+    // Listen for serial data
+    use_effect(move || {
+        spawn(async move {
+            // The payload is ignored; the backend keeps the lines and the
+            // view fetches the ones it shows
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |_: JsValue| {
+                refresh_log();
             });
 
             match listen("serial-read", &closure).await {
                 Ok(unlisten_js) => {
-                    let unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
                     // Store both to keep them alive until guard is dropped
-                    listener_guard.write().0 = ListenerGuard {
-                        unlisten,
-                        _closure: Some(closure),
-                    };
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.0.unlisten.push(f);
+                    }
+                    guard.0._closures.push(closure);
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        });
+    });
+
+    // Boot milestones the backend timed from the monitor stream
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: BootTiming,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    last_boot.set(Some(e.payload));
+                }
+            });
+            match listen("boot-timing", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.0.unlisten.push(f);
+                    }
+                    guard.0._closures.push(closure);
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        });
+    });
+
+    // Resets the backend explained from the monitor stream
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: ResetReason,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    last_reset.set(Some(e.payload));
+                }
+            });
+            match listen("reset-reason", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.0.unlisten.push(f);
+                    }
+                    guard.0._closures.push(closure);
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        });
+    });
+
+    // Drive the flash progress bar from the backend's byte counts
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                let task = e.payload;
+                if task.kind == "mpy_run" && task.state == "running" {
+                    py_task.set(Some(task.id));
+                }
+                if task.kind != "flash"
+                    || !*is_flashing.peek()
+                    || task.port_name.as_deref() != Some(port_name.peek().as_str())
+                {
+                    return;
+                }
+                match task.state.as_str() {
+                    "running" => {
+                        if task.total > 0 {
+                            flash_progress.set(task.current as f64 * 100.0 / task.total as f64);
+                        }
+                        if task.bytes_per_sec.is_some() {
+                            flash_rate.set(task.bytes_per_sec);
+                        }
+                        flash_eta.set(task.eta_secs);
+                        flash_phase.set(task.phase);
+                    }
+                    "failed" => flash_error.set(task.message),
+                    _ => {}
+                }
+            });
+
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.0.unlisten.push(f);
+                    }
+                    guard.0._closures.push(closure);
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        });
+    });
+
+    // Listen for the monitor being suspended while the flasher borrows the port
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct MonitorStateEvent {
+                    state: String,
+                    port_name: String,
+                }
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: MonitorStateEvent,
+                }
+                if let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) {
+                    if e.payload.state == "suspended" {
+                        monitor_note.set(Some("monitor_suspended"));
+                    } else if e.payload.state == "stopped" {
+                        is_connected.set(false);
+                        monitor_note.set(Some("monitor_stopped_by_trigger"));
+                    } else {
+                        // A native USB board re-enumerated under another name
+                        let note = if e.payload.state == "moved" {
+                            port_name.set(e.payload.port_name);
+                            "monitor_moved"
+                        } else {
+                            "monitor_resumed"
+                        };
+                        monitor_note.set(Some(note));
+                        spawn(async move {
+                            gloo_timers::future::TimeoutFuture::new(3000).await;
+                            if *monitor_note.peek() == Some(note) {
+                                monitor_note.set(None);
+                            }
+                        });
+                    }
+                }
+            });
+
+            match listen("monitor-state", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.0.unlisten.push(f);
+                    }
+                    guard.0._closures.push(closure);
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        });
+    });
+
+    // Keyboard shortcuts arrive from the backend as the action name
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: String,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                let busy = *is_flashing.peek() || *is_erasing.peek();
+                match e.payload.as_str() {
+                    "clear_log" => clear_log(),
+                    "focus_input" => focus_input(),
+                    "flash" if !busy => start_flash(),
+                    "reset" if !busy => reset_device(),
+                    _ => {}
+                }
+            });
+
+            match listen("shortcut", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                        guard.0.unlisten.push(f);
+                    }
+                    guard.0._closures.push(closure);
                 }
                 Err(e) => {
                     web_sys::console::error_1(&e);
@@ -203,129 +1433,454 @@ pub fn Devices() -> Element {
         });
     });
 
+    // Firmware files dragged onto the window
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize, Default)]
+                #[serde(default)]
+                struct DragPayload {
+                    paths: Vec<String>,
+                }
+                #[derive(Deserialize)]
+                struct Event {
+                    event: String,
+                    payload: Option<DragPayload>,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                match e.event.as_str() {
+                    "tauri://drag-enter" => drag_over.set(true),
+                    "tauri://drag-leave" => drag_over.set(false),
+                    "tauri://drag-drop" => {
+                        drag_over.set(false);
+                        if *is_flashing.peek() {
+                            return;
+                        }
+                        let paths = e.payload.unwrap_or_default().paths;
+                        let Some(path) = paths.into_iter().next() else {
+                            return;
+                        };
+                        let lower = path.to_ascii_lowercase();
+                        if lower.ends_with(".bin") || lower.ends_with(".elf") {
+                            drop_image(path);
+                        } else {
+                            toasts.push("error", "drop_unsupported", Some(path));
+                        }
+                    }
+                    _ => {}
+                }
+            });
+
+            for name in [
+                "tauri://drag-enter",
+                "tauri://drag-leave",
+                "tauri://drag-drop",
+            ] {
+                match listen(name, &closure).await {
+                    Ok(unlisten_js) => {
+                        if let Ok(f) = unlisten_js.dyn_into::<js_sys::Function>() {
+                            listener_guard.write().0.unlisten.push(f);
+                        }
+                    }
+                    Err(e) => {
+                        web_sys::console::error_1(&e);
+                    }
+                }
+            }
+            listener_guard.write().0._closures.push(closure);
+        });
+    });
+
+    let flash_percent = flash_progress.read().floor() as u64;
+    // e.g. "App · 0x10000 · esp32s3"
+    let dropped_label = dropped.read().as_ref().map_or_else(String::new, |placed| {
+        let mut label = format!(
+            "{} · 0x{:X}",
+            dict.drop_image_kind(&placed.kind),
+            placed.address
+        );
+        if let Some(chip) = &placed.chip {
+            label.push_str(&format!(" · {}", chip));
+        }
+        label
+    });
+    // e.g. "42% · 120.5 KB/s · ETA ~40 s"
+    let mut flash_progress_label = format!("{}%", flash_percent);
+    if let Some(rate) = *flash_rate.read() {
+        flash_progress_label.push_str(&format!(" · {}", format_rate(rate)));
+    }
+    if let Some(secs) = *flash_eta.read() {
+        flash_progress_label.push_str(&format!(" · {} {}", dict.flash_eta(), format_eta(secs)));
+    }
+
+    // Expected duration of flashing the rows as they stand
+    let flash_estimate = use_resource(move || async move {
+        let rows = flash_rows.read().clone();
+        let baud_rate = flash_build.read().as_ref().and_then(|b| b.baud_rate);
+        if rows.iter().any(|r| r.firmware_path.trim().is_empty()) {
+            return None;
+        }
+        let args = serde_wasm_bindgen::to_value(&EstimateFlashArgs {
+            segments: rows,
+            baud_rate,
+        })
+        .unwrap_or(JsValue::NULL);
+        let val = invoke("estimate_flash", args).await.ok()?;
+        serde_wasm_bindgen::from_value::<FlashEstimate>(val).ok()
+    });
+    let flash_estimate_label = flash_estimate.read().clone().flatten().map(|e| {
+        format!(
+            "{} {} @ {} baud",
+            dict.flash_estimate(),
+            format_eta(e.secs),
+            e.baud_rate
+        )
+    });
+
+    // Earlier boots of this board, to compare the last one against
+    let boot_history = use_resource(move || async move {
+        let port = port_name.read().clone();
+        // Reload whenever another boot was timed
+        let _ = last_boot.read();
+        if port.is_empty() {
+            return Vec::new();
+        }
+        let args = serde_wasm_bindgen::to_value(&GetBootHistoryArgs { port_name: port })
+            .unwrap_or(JsValue::NULL);
+        match invoke("get_boot_history", args).await {
+            Ok(val) => serde_wasm_bindgen::from_value::<Vec<BootTiming>>(val).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    });
+    let boot_average = {
+        let history = boot_history.read().clone().unwrap_or_default();
+        let times: Vec<u64> = history
+            .iter()
+            .filter_map(|b| b.marker_ms.or(b.app_main_ms))
+            .collect();
+        (!times.is_empty()).then(|| (times.iter().sum::<u64>() / times.len() as u64, times.len()))
+    };
+    let boot_ms = |ms: Option<u64>| ms.map_or("-".to_string(), |ms| format!("{} ms", ms));
+
+    // Speeds of earlier flashes of this board; reloaded after each flash
+    let flash_history = use_resource(move || async move {
+        let port = port_name.read().clone();
+        if port.is_empty() || *is_flashing.read() {
+            return Vec::new();
+        }
+        let args = serde_wasm_bindgen::to_value(&GetFlashHistoryArgs { port_name: port })
+            .unwrap_or(JsValue::NULL);
+        match invoke("get_flash_history", args).await {
+            Ok(val) => serde_wasm_bindgen::from_value::<Vec<FlashRecord>>(val).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    });
+    let recent_flashes: Vec<(u64, String)> = flash_history
+        .read()
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .take(5)
+        .map(|r| {
+            let line = format!(
+                "{} · {} KB @ {}",
+                format_rate(r.bytes_per_sec),
+                r.bytes / 1024,
+                r.baud_rate
+            );
+            (r.at, line)
+        })
+        .collect();
+    let (flash_label, flash_color) = match (&*flash_error.read(), flash_phase.read().as_str()) {
+        (Some(_), _) => (dict.flash_status_failed(), "var(--md-sys-color-error)"),
+        (None, "verifying") => (dict.flash_phase_verifying(), "var(--md-sys-color-tertiary)"),
+        (None, phase) => (dict.flash_phase(phase), "var(--md-sys-color-primary)"),
+    };
+
+    // Built-in USB-Serial/JTAG, see `devices::USB_JTAG_VID_PID`
+    let usb_jtag = serial_ports
+        .read()
+        .iter()
+        .any(|p| p.port_name == *port_name.read() && p.vid_pid.as_deref() == Some("303A:1001"));
+
+    let (log_empty, log_height, log_offset, log_lines) = {
+        let window = log_window.read();
+        (
+            window.end == window.first,
+            (window.end - window.first) as f64 * LOG_LINE_HEIGHT,
+            (window.start - window.first) as f64 * LOG_LINE_HEIGHT,
+            (window.start..)
+                .zip(window.lines.iter().cloned())
+                .zip(window.repeats.iter().copied().chain(std::iter::repeat(0)))
+                .map(|((number, line), repeats)| (number, line, repeats))
+                .collect::<Vec<_>>(),
+        )
+    };
+
     rsx! {
         div {
             class: "devices-container",
             // Use Flexbox with wrap to allow stacking on small screens
             style: "display: flex; flex-wrap: wrap; gap: 24px; height: 100%; align-items: flex-start; overflow-y: auto; padding-bottom: 24px;",
 
-            // Left: Flashing Panel
-            div { style: "flex: 1; min-width: 300px;",
+            // Left: Flashing Panel, outlined while a file is dragged over
+            div {
+                style: if *drag_over.read() { "flex: 1; min-width: 300px; outline: 2px dashed var(--md-sys-color-primary); outline-offset: 4px; border-radius: 12px;" } else { "flex: 1; min-width: 300px;" },
                 Card {
-                    title: dict.devices_title_flashing.to_string(),
-                    subtitle: dict.devices_subtitle_flashing.to_string(),
+                    title: dict.devices_title_flashing().to_string(),
+                    subtitle: dict.devices_subtitle_flashing().to_string(),
 
                     div { style: "display: flex; flex-direction: column; gap: 16px; margin-top: 16px;",
 
-                        // File Selection
-                        div {
-                            label { r#for: "firmware_path", style: "display: block; font-size: 0.8em; margin-bottom: 4px; color: var(--md-sys-color-on-surface-variant);",
-                                "{dict.devices_label_firmware_file}"
+                        // Images to flash, one (file, address) row each
+                        div { style: "display: flex; flex-direction: column; gap: 8px;",
+                            div { style: "display: flex; gap: 8px; font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);",
+                                span { style: "flex: 1;", "{dict.devices_label_firmware_file()}" }
+                                span { style: "width: 150px;", "{dict.devices_label_flash_address()}" }
                             }
-                            div { style: "display: flex; gap: 8px;",
-                                input {
-                                    r#type: "text",
-                                    name: "firmware_path",
-                                    id: "firmware_path",
-                                    value: "{firmware_path}",
-                                    placeholder: "{dict.devices_placeholder_firmware_file}",
-                                    class: "md-input",
-                                    style: "flex: 1;",
-                                    oninput: move |evt| firmware_path.set(evt.value()),
-                                }
-                                button {
-                                    class: "md-button btn-tonal",
-                                    onclick: move |_| {
-                                        web_sys::console::log_1(&"Browse button clicked".into());
-                                        spawn(async move {
-                                            match invoke("pick_firmware_file", JsValue::NULL).await {
-                                                Ok(res) => {
-                                                    web_sys::console::log_1(&"Invoke success".into());
-                                                    if let Some(path) = res.as_string() {
-                                                        firmware_path.set(path);
+                            for (index, row) in flash_rows.read().iter().enumerate() {
+                                div { key: "{index}", style: "display: flex; flex-direction: column; gap: 2px;",
+                                    div { style: "display: flex; gap: 8px; align-items: center;",
+                                        input {
+                                            r#type: "text",
+                                            name: "firmware_path_{index}",
+                                            value: "{row.firmware_path}",
+                                            placeholder: "{dict.devices_placeholder_firmware_file()}",
+                                            class: "md-input",
+                                            style: "flex: 1; min-width: 0;",
+                                            oninput: move |evt| flash_rows.write()[index].firmware_path = evt.value(),
+                                        }
+                                        button {
+                                            class: "md-button btn-tonal",
+                                            title: "{dict.devices_btn_browse()}",
+                                            onclick: move |_| {
+                                                spawn(async move {
+                                                    match invoke("pick_firmware_file", JsValue::NULL).await {
+                                                        Ok(res) => {
+                                                            if let Some(path) = res.as_string() {
+                                                                if let Some(row) = flash_rows.write().get_mut(index) {
+                                                                    row.firmware_path = path;
+                                                                }
+                                                            }
+                                                        }
+                                                        Err(e) => {
+                                                            web_sys::console::error_1(&e);
+                                                        }
                                                     }
+                                                });
+                                            },
+                                            span { class: "material-symbols-outlined icon", "folder_open" }
+                                        }
+                                        input {
+                                            r#type: "text",
+                                            name: "flash_address_{index}",
+                                            value: "{row.flash_address}",
+                                            class: "md-input",
+                                            style: "width: 100px;",
+                                            disabled: *flash_ota.read() && flash_rows.read().len() == 1,
+                                            oninput: move |evt| flash_rows.write()[index].flash_address = evt.value(),
+                                        }
+                                        button {
+                                            class: "md-button btn-text",
+                                            title: "{dict.devices_btn_image_info()}",
+                                            disabled: row.firmware_path.trim().is_empty(),
+                                            onclick: move |_| {
+                                                let shown = *inspected_row.peek() == Some(index);
+                                                inspected_row.set((!shown).then_some(index));
+                                            },
+                                            span { class: "material-symbols-outlined icon", "info" }
+                                        }
+                                        button {
+                                            class: "md-button btn-text",
+                                            title: "{dict.devices_btn_remove_file()}",
+                                            disabled: flash_rows.read().len() == 1,
+                                            onclick: move |_| {
+                                                if flash_rows.read().len() > 1 {
+                                                    flash_rows.write().remove(index);
+                                                    inspected_row.set(None);
                                                 }
-                                                Err(e) => {
-                                                    web_sys::console::error_1(&e);
-                                                }
-                                            }
+                                            },
+                                            span { class: "material-symbols-outlined icon", "remove_circle_outline" }
+                                        }
+                                    }
+                                    if let Some(error) = flash_row_error(&flash_rows.read(), index, &dict) {
+                                        if error != dict.flash_row_no_file() || *flash_attempted.read() {
+                                            span { style: "font-size: 0.75em; color: var(--md-sys-color-error);", "{error}" }
+                                        }
+                                    }
+                                    if *inspected_row.read() == Some(index) && !row.firmware_path.trim().is_empty() {
+                                        ImageInfoView { path: row.firmware_path.trim().to_string() }
+                                    }
+                                }
+                            }
+                            div { style: "display: flex; gap: 8px;",
+                                Button {
+                                    variant: "text".to_string(),
+                                    icon: "add".to_string(),
+                                    onclick: move |_| {
+                                        flash_rows.write().push(FlashRow {
+                                            firmware_path: "".to_string(),
+                                            flash_address: "".to_string(),
                                         });
                                     },
-                                    span { class: "material-symbols-outlined icon", "folder_open" }
-                                    span { class: "label", "{dict.devices_btn_browse}" }
+                                    "{dict.devices_btn_add_file()}"
+                                }
+                                Button {
+                                    variant: "text".to_string(),
+                                    icon: "folder_zip".to_string(),
+                                    onclick: move |_| load_build("idf_pick_build"),
+                                    "{dict.devices_btn_idf_build()}"
+                                }
+                                Button {
+                                    variant: "text".to_string(),
+                                    icon: "folder_zip".to_string(),
+                                    onclick: move |_| load_build("pio_pick_build"),
+                                    "{dict.devices_btn_pio_build()}"
+                                }
+                                Button {
+                                    variant: "text".to_string(),
+                                    icon: "transform".to_string(),
+                                    onclick: move |_| {
+                                        let shown = *show_elf_image.peek();
+                                        show_elf_image.set(!shown);
+                                    },
+                                    "{dict.devices_btn_from_elf()}"
+                                }
+                            }
+                            if *show_elf_image.read() {
+                                ElfImagePanel {
+                                    chip_model: chip_details_info.read().as_ref().and_then(|info| info.chip_model.clone()),
+                                    on_converted: move |(path, address): (String, String)| {
+                                        // Into the first empty row, or a new one
+                                        let mut rows = flash_rows.write();
+                                        match rows.iter_mut().find(|row| row.firmware_path.trim().is_empty()) {
+                                            Some(row) => {
+                                                row.firmware_path = path;
+                                                row.flash_address = address;
+                                            }
+                                            None => rows.push(FlashRow {
+                                                firmware_path: path,
+                                                flash_address: address,
+                                            }),
+                                        }
+                                        show_elf_image.set(false);
+                                    },
+                                }
+                            }
+                            if flash_rows.read().len() == 1 {
+                                label {
+                                    style: "display: flex; align-items: center; gap: 8px; font-size: 0.9em;",
+                                    title: "{dict.devices_ota_slot_hint()}",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: *flash_ota.read(),
+                                        onchange: move |evt| flash_ota.set(evt.checked()),
+                                    }
+                                    "{dict.devices_ota_slot()}"
+                                }
+                            }
+                            if let Some(build) = &*flash_build.read() {
+                                div { style: "display: flex; align-items: center; gap: 8px; font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);",
+                                    span { style: "flex: 1; overflow: hidden; text-overflow: ellipsis;",
+                                        title: "{build.build_dir}",
+                                        "{dict.devices_build_loaded()} {build.summary()}"
+                                    }
+                                    button {
+                                        class: "md-button btn-text",
+                                        title: "{dict.devices_btn_forget_build()}",
+                                        onclick: move |_| flash_build.set(None),
+                                        span { class: "material-symbols-outlined icon", "close" }
+                                    }
                                 }
                             }
                         }
 
-                        // Address Config
-                        div {
-                            label { r#for: "flash_address", style: "display: block; font-size: 0.8em; margin-bottom: 4px; color: var(--md-sys-color-on-surface-variant);",
-                                "{dict.devices_label_flash_address}"
-                            }
-                            input {
-                                r#type: "text",
-                                name: "flash_address",
-                                id: "flash_address",
-                                value: "{flash_address}",
-                                class: "md-input",
-                                style: "width: 100%;",
-                                oninput: move |evt| flash_address.set(evt.value()),
+                        if let Some(estimate) = flash_estimate_label.as_ref().filter(|_| !*is_flashing.read()) {
+                            div { style: "font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);",
+                                "{estimate}"
                             }
                         }
 
                         // Progress Bar
-                        if *is_flashing.read() {
+                        if *is_flashing.read() || flash_error.read().is_some() {
                             div { style: "display: flex; flex-direction: column; gap: 4px;",
                                 div { style: "display: flex; justify-content: space-between; font-size: 0.8em;",
-                                    span { "{dict.devices_flashing_status}" }
-                                    span { "{flash_progress.read()}%" }
+                                    span {
+                                        style: if flash_error.read().is_some() { "color: var(--md-sys-color-error);" } else { "" },
+                                        "{flash_label}"
+                                    }
+                                    span { "{flash_progress_label}" }
                                 }
                                 div { style: "height: 4px; background: var(--md-sys-color-surface-container-highest); border-radius: 2px; overflow: hidden;",
-                                    div { style: "height: 100%; background: var(--md-sys-color-primary); width: {flash_progress.read()}%; transition: width 0.2s;" }
+                                    div { style: "height: 100%; background: {flash_color}; width: {flash_progress}%; transition: width 0.2s;" }
+                                }
+                                if let Some(message) = &*flash_error.read() {
+                                    div { style: "font-size: 0.8em; color: var(--md-sys-color-error); word-break: break-word;",
+                                        "{message}"
+                                    }
                                 }
                             }
                         }
 
-                        // Action Button
-                        Button {
-                            variant: "filled".to_string(),
-                            icon: "bolt".to_string(),
-                            onclick: move |_| {
-                                let path = firmware_path.read().clone();
-                                let addr = flash_address.read().clone();
-                                let port = port_name.read().clone(); // Use dynamic port
+                        if !recent_flashes.is_empty() {
+                            div { style: "display: flex; flex-direction: column; gap: 2px; font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);",
+                                span { "{dict.flash_history()}" }
+                                for (at, line) in recent_flashes {
+                                    span { key: "{at}", "{line}" }
+                                }
+                            }
+                        }
 
-                                spawn(async move {
-                                    if port.is_empty() {
-                                        web_sys::console::error_1(&"No port selected".into());
-                                        return;
+                        if let Some(placed) = &*dropped.read() {
+                            div { style: "display: flex; flex-direction: column; gap: 8px; padding: 12px; border-radius: 8px; background: var(--md-sys-color-surface-container-high); font-size: 0.9em;",
+                                span { "{dropped_label}" }
+                                span { style: "font-size: 0.85em; color: var(--md-sys-color-on-surface-variant); word-break: break-all;",
+                                    "{placed.path}"
+                                }
+                                if placed.converted {
+                                    span { style: "font-size: 0.85em; color: var(--md-sys-color-on-surface-variant);",
+                                        "{dict.drop_converted()}"
                                     }
+                                }
+                                span { "{dict.drop_confirm()}" }
+                                div { style: "display: flex; gap: 8px;",
+                                    Button {
+                                        variant: "filled".to_string(),
+                                        icon: "bolt".to_string(),
+                                        onclick: move |_| start_flash(),
+                                        "{dict.drop_btn_flash()}"
+                                    }
+                                    Button {
+                                        variant: "text".to_string(),
+                                        onclick: move |_| dropped.set(None),
+                                        "{dict.drop_btn_later()}"
+                                    }
+                                }
+                            }
+                        }
 
-                                    is_flashing.set(true);
-                                    flash_progress.set(0.0);
-
-                                    let args = serde_wasm_bindgen::to_value(
+                        // Action Button
+                        Button {
+                            variant: "filled".to_string(),
+                            icon: "bolt".to_string(),
+                            onclick: move |_| start_flash(),
+                            "{dict.devices_btn_start_flash()}"
+                        }
 
-                                            &FlashArgs {
-                                                port_name: port,
-                                                firmware_path: path,
-                                                flash_address: addr,
-                                            },
-                                        )
-                                        .unwrap();
-                                    match invoke("flash_firmware", args).await {
-                                        Ok(_) => {
-                                            flash_progress.set(100.0);
-                                            is_flashing.set(false);
-                                        }
-                                        Err(e) => {
-                                            web_sys::console::error_1(&e);
-                                            is_flashing.set(false);
-                                        }
-                                    }
-                                });
-                            },
-                            "{dict.devices_btn_start_flash}"
+                        // Re-flash on every rebuild
+                        button {
+                            class: if *flash_watching.read() { "md-button btn-tonal" } else { "md-button btn-outlined" },
+                            title: "{dict.devices_watch_hint()}",
+                            onclick: move |_| toggle_watch(),
+                            span { class: "material-symbols-outlined icon",
+                                if *flash_watching.read() { "visibility_off" } else { "visibility" }
+                            }
+                            span { class: "label",
+                                if *flash_watching.read() { "{dict.devices_btn_stop_watch()}" } else { "{dict.devices_btn_watch_build()}" }
+                            }
                         }
 
                         // Erase Button
@@ -336,44 +1891,35 @@ pub fn Devices() -> Element {
                                 let port = port_name.read().clone();
                                 spawn(async move {
                                     if port.is_empty() {
-                                        web_sys::console::error_1(&"No port selected".into());
+                                        toasts.push("error", "no_port", None);
                                         return;
                                     }
                                     is_erasing.set(true);
-
-                                    // FIX 1: Use snake_case "port_name" to match Rust backend
-
-                                    // FIX 2: No alert, just log. Better UX would be a toast or status text.
+                                    // The outcome arrives as a `notify` toast
                                     let args = serde_wasm_bindgen::to_value(&json!({ "portName" : port }))
                                         .unwrap_or(JsValue::NULL);
-                                    web_sys::console::log_1(&"Invoking erase_flash...".into());
-                                    erase_msg.set("".to_string());
-                                    match invoke("erase_flash", args).await {
-                                        Ok(_) => {
-                                            web_sys::console::log_1(&"Erase success".into());
-                                            erase_msg.set("清除成功！".to_string());
-                                            // Clear message after 3 seconds
-                                            gloo_timers::future::TimeoutFuture::new(3000).await;
-                                            erase_msg.set("".to_string());
-                                        }
-                                        Err(e) => {
-                                            web_sys::console::error_1(&e);
-                                            erase_msg.set("清除失败！".to_string());
-                                        }
+                                    if let Err(e) = invoke("erase_flash", args).await {
+                                        web_sys::console::error_1(&e);
                                     }
                                     is_erasing.set(false);
                                 });
                             },
                             if *is_erasing.read() {
-                                "清除中..."
+                                "{dict.devices_btn_erasing()}"
                             } else {
-                                "{dict.devices_btn_erase_flash}"
+                                "{dict.devices_btn_erase_flash()}"
                             }
                         }
-                        if !erase_msg.read().is_empty() {
-                            div { style: "font-size: 0.8em; margin-top: 4px; color: var(--md-sys-color-primary);",
-                                "{erase_msg}"
-                            }
+
+                        EsptoolPanel {
+                            port_name: port_name.read().clone(),
+                            chip_model: chip_details_info.read().as_ref().and_then(|info| info.chip_model.clone()),
+                            segments: flash_rows
+                                .read()
+                                .iter()
+                                .map(|row| (row.firmware_path.clone(), row.flash_address.clone()))
+                                .collect::<Vec<_>>(),
+                            baud_rate: flash_build.read().as_ref().and_then(|b| b.baud_rate),
                         }
                     }
                 }
@@ -390,21 +1936,77 @@ pub fn Devices() -> Element {
                         style: "border-radius: 8px 8px 0 0;",
                         onclick: move |_| active_tab.set("monitor".to_string()),
                         span { class: "material-symbols-outlined icon", "terminal" }
-                        "{dict.monitor_tab}"
+                        "{dict.monitor_tab()}"
                     }
                     button {
                         class: if *active_tab.read() == "pinout" { "md-button btn-tonal" } else { "md-button btn-text" },
                         style: "border-radius: 8px 8px 0 0;",
                         onclick: move |_| active_tab.set("pinout".to_string()),
                         span { class: "material-symbols-outlined icon", "developer_board" }
-                        "{dict.board_view_tab}"
+                        "{dict.board_view_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "gpio" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("gpio".to_string()),
+                        span { class: "material-symbols-outlined icon", "toggle_on" }
+                        "{dict.gpio_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "examples" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("examples".to_string()),
+                        span { class: "material-symbols-outlined icon", "verified" }
+                        "{dict.examples_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "ota" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("ota".to_string()),
+                        span { class: "material-symbols-outlined icon", "wifi" }
+                        "{dict.ota_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "provision" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("provision".to_string()),
+                        span { class: "material-symbols-outlined icon", "wifi_password" }
+                        "{dict.provision_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "files" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("files".to_string()),
+                        span { class: "material-symbols-outlined icon", "folder" }
+                        "{dict.mpy_files_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "debug" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("debug".to_string()),
+                        span { class: "material-symbols-outlined icon", "bug_report" }
+                        "{dict.debug_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "script" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("script".to_string()),
+                        span { class: "material-symbols-outlined icon", "code" }
+                        "{dict.script_tab()}"
+                    }
+                    button {
+                        class: if *active_tab.read() == "arduino" { "md-button btn-tonal" } else { "md-button btn-text" },
+                        style: "border-radius: 8px 8px 0 0;",
+                        onclick: move |_| active_tab.set("arduino".to_string()),
+                        span { class: "material-symbols-outlined icon", "memory" }
+                        "{dict.arduino_tab()}"
                     }
                 }
 
                 if *active_tab.read() == "monitor" {
                     Card {
-                        title: dict.devices_title_monitor.to_string(),
-                        subtitle: dict.devices_subtitle_monitor.to_string(),
+                        title: dict.devices_title_monitor().to_string(),
+                        subtitle: dict.devices_subtitle_monitor().to_string(),
                         actions: rsx! {
                             // Port Input
                             // Baud Rate Select
@@ -417,52 +2019,265 @@ pub fn Devices() -> Element {
 
 
 
-                            div { style: "display: flex; align-items: center; gap: 8px;",
-                                span {
-                                    style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
-                                    "Port" // TODO: Add to Dict
+                            select {
+                                class: "md-select",
+                                name: "monitor_source",
+                                title: "{dict.devices_label_source()}",
+                                value: "{monitor_source}",
+                                disabled: *is_connected.read(),
+                                onchange: move |evt| monitor_source.set(evt.value()),
+                                option { value: "serial", "{dict.devices_source_serial()}" }
+                                option { value: "udp", "{dict.devices_source_udp()}" }
+                                option { value: "tcp", "{dict.devices_source_tcp()}" }
+                                option { value: "webrepl", "{dict.devices_source_webrepl()}" }
+                                option { value: "ble", "{dict.devices_source_ble()}" }
+                            }
+                            if *monitor_source.read() == "webrepl" {
+                                div { style: "display: flex; align-items: center; gap: 8px; margin-right: 8px;",
+                                    input {
+                                        class: "md-input",
+                                        style: "width: 150px;",
+                                        placeholder: "{dict.devices_webrepl_host_placeholder()}",
+                                        disabled: *is_connected.read(),
+                                        value: "{webrepl_host}",
+                                        oninput: move |evt| webrepl_host.set(evt.value()),
+                                    }
+                                    input {
+                                        class: "md-input",
+                                        style: "width: 110px;",
+                                        r#type: "password",
+                                        placeholder: "{dict.devices_webrepl_password_placeholder()}",
+                                        disabled: *is_connected.read(),
+                                        value: "{webrepl_password}",
+                                        oninput: move |evt| webrepl_password.set(evt.value()),
+                                    }
                                 }
-                                input {
-                                    r#type: "text",
-                                    name: "monitor_port",
-                                    id: "monitor_port",
-                                    value: "{port_name}",
-                                    class: "md-input",
-                                    style: "width: 80px;",
-                                    oninput: move |evt| port_name.set(evt.value()),
+                            } else if *monitor_source.read() == "ble" {
+                                div { style: "display: flex; align-items: center; gap: 8px; margin-right: 8px;",
+                                    input {
+                                        class: "md-input",
+                                        style: "width: 180px;",
+                                        list: "ble_devices",
+                                        placeholder: "{dict.devices_ble_device_placeholder()}",
+                                        disabled: *is_connected.read(),
+                                        value: "{ble_device}",
+                                        oninput: move |evt| ble_device.set(evt.value()),
+                                    }
+                                    datalist { id: "ble_devices",
+                                        for d in ble_devices.read().iter() {
+                                            {
+                                                let label = match d.rssi {
+                                                    Some(rssi) => format!("{} ({} dBm)", d.name, rssi),
+                                                    None => d.name.clone(),
+                                                };
+                                                rsx! {
+                                                    option { key: "{d.id}", value: "{d.id}", "{label}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    button {
+                                        class: "md-button btn-text",
+                                        title: "{dict.devices_ble_scan_hint()}",
+                                        disabled: *is_connected.read() || *ble_scanning.read(),
+                                        onclick: move |_| {
+                                            ble_scanning.set(true);
+                                            spawn(async move {
+                                                match invoke("monitor_ble_scan", JsValue::NULL).await {
+                                                    Ok(val) => {
+                                                        let found: Vec<BleDevice> =
+                                                            serde_wasm_bindgen::from_value(val).unwrap_or_default();
+                                                        if ble_device.peek().is_empty() {
+                                                            if let Some(first) = found.first() {
+                                                                ble_device.set(first.id.clone());
+                                                            }
+                                                        }
+                                                        if found.is_empty() {
+                                                            toasts.push("info", "ble_uart_none", None);
+                                                        }
+                                                        ble_devices.set(found);
+                                                    }
+                                                    Err(e) => {
+                                                        let dict = lang.peek().clone();
+                                                        let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                                                            .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                                                            .ok();
+                                                        toasts.push("error", "monitor_failed", detail);
+                                                    }
+                                                }
+                                                ble_scanning.set(false);
+                                            });
+                                        },
+                                        span { class: "material-symbols-outlined icon", "bluetooth_searching" }
+                                        span { class: "label",
+                                            if *ble_scanning.read() { "{dict.devices_ble_scanning()}" } else { "{dict.devices_ble_btn_scan()}" }
+                                        }
+                                    }
                                 }
-                            }
-                            div { style: "display: flex; align-items: center; gap: 8px; margin-right: 8px;",
-                                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
-                                    label {
-                                        r#for: "baud_rate",
-                                        "{dict.devices_label_baud_rate}"
+                            } else if *monitor_source.read() != "serial" {
+                                div { style: "display: flex; align-items: center; gap: 8px; margin-right: 8px;",
+                                    span {
+                                        style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                                        label { r#for: "listen_port", "{dict.devices_label_listen_port()}" }
+                                    }
+                                    input {
+                                        class: "md-input",
+                                        id: "listen_port",
+                                        style: "width: 80px;",
+                                        title: "{dict.devices_listen_hint()}",
+                                        disabled: *is_connected.read(),
+                                        value: "{listen_port}",
+                                        oninput: move |evt| listen_port.set(evt.value()),
                                     }
                                 }
-                                select {
-                                    class: "md-select",
-                                    name: "baud_rate",
-                                    id: "baud_rate",
-                                    value: "{baud_rate}",
-                                    onchange: move |evt| baud_rate.set(evt.value()),
-                                    option { value: "9600", "9600" }
-                                    option { value: "115200", "115200" }
-                                    option { value: "921600", "921600" }
+                            } else {
+                                div { style: "display: flex; align-items: center; gap: 8px;",
+                                    span {
+                                        style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                                        label { r#for: "monitor_port", "{dict.port()}" }
+                                    }
+                                    select {
+                                        class: "md-select",
+                                        name: "monitor_port",
+                                        id: "monitor_port",
+                                        value: "{port_name}",
+                                        style: "max-width: 220px;",
+                                        disabled: *is_connected.read(),
+                                        onchange: move |evt| port_name.set(evt.value()),
+                                        if serial_ports.read().is_empty() && port_name.read().is_empty() {
+                                            option { value: "", disabled: true, "{dict.devices_no_ports()}" }
+                                        }
+                                        // Keep a remembered port selectable while it is unplugged
+                                        if !port_name.read().is_empty()
+                                            && !serial_ports.read().iter().any(|p| p.port_name == *port_name.read())
+                                        {
+                                            option { value: "{port_name}", "{port_name}" }
+                                        }
+                                        for port in serial_ports.read().iter() {
+                                            option {
+                                                key: "{port.port_name}",
+                                                value: "{port.port_name}",
+                                                title: port.vid_pid.clone().unwrap_or_default(),
+                                                "{port.label()}"
+                                            }
+                                        }
+                                    }
+                                }
+                                div { style: "display: flex; align-items: center; gap: 8px; margin-right: 8px;",
+                                    span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);",
+                                        label {
+                                            r#for: "baud_rate",
+                                            "{dict.devices_label_baud_rate()}"
+                                        }
+                                    }
+                                    select {
+                                        class: "md-select",
+                                        name: "baud_rate",
+                                        id: "baud_rate",
+                                        value: "{baud_rate}",
+                                        // The chip's own USB-Serial/JTAG runs at USB speed whatever is set
+                                        disabled: usb_jtag,
+                                        title: if usb_jtag { "{dict.devices_usb_jtag_baud()}" } else { "" },
+                                        onchange: move |evt| baud_rate.set(evt.value()),
+                                        option { value: "9600", "9600" }
+                                        option { value: "115200", "115200" }
+                                        option { value: "921600", "921600" }
+                                    }
+                                }
+                            }
+                            select {
+                                class: "md-select",
+                                name: "monitor_encoding",
+                                title: "{dict.devices_label_encoding()}",
+                                value: "{encoding}",
+                                onchange: move |evt| {
+                                    let value = evt.value();
+                                    encoding.set(value.clone());
+                                    spawn(async move {
+                                        let args = serde_wasm_bindgen::to_value(&MonitorSetEncodingArgs { encoding: value })
+                                            .unwrap_or(JsValue::NULL);
+                                        invoke("monitor_set_encoding", args).await.ok();
+                                    });
+                                },
+                                option { value: "utf8", "UTF-8" }
+                                option { value: "gbk", "GBK" }
+                                option { value: "latin1", "Latin-1" }
+                            }
+                            span {
+                                title: defmt_elf.read().clone().unwrap_or_else(|| dict.devices_defmt_hint().to_string()),
+                                Button {
+                                    variant: { if defmt_elf.read().is_some() { "tonal" } else { "text" } }.to_string(),
+                                    icon: "data_object".to_string(),
+                                    onclick: move |_| {
+                                        let on = defmt_elf.peek().is_some();
+                                        spawn(async move {
+                                            // Off, or on with the ELF the user picks
+                                            let elf_path = if on {
+                                                None
+                                            } else {
+                                                match invoke("pick_elf_file", JsValue::NULL).await {
+                                                    Ok(val) => match serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                                                        Ok(Some(path)) => Some(path),
+                                                        _ => return,
+                                                    },
+                                                    Err(_) => return,
+                                                }
+                                            };
+                                            let args = serde_wasm_bindgen::to_value(&MonitorSetDefmtArgs { elf_path })
+                                                .unwrap_or(JsValue::NULL);
+                                            match invoke("monitor_set_defmt", args).await {
+                                                Ok(val) => {
+                                                    if let Ok(status) = serde_wasm_bindgen::from_value::<MonitorStatus>(val) {
+                                                        defmt_elf.set(status.defmt_elf);
+                                                    }
+                                                }
+                                                Err(e) => {
+                                                    let dict = lang.peek().clone();
+                                                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                                                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                                                        .ok();
+                                                    toasts.push("error", "defmt_failed", detail);
+                                                }
+                                            }
+                                        });
+                                    },
+                                    "{dict.devices_btn_defmt()}"
                                 }
                             }
                             Button {
-                                variant: "text".to_string(),
-                                icon: "delete_sweep".to_string(),
+                                variant: { if *terminal_mode.read() { "tonal" } else { "text" } }.to_string(),
+                                icon: "terminal".to_string(),
+                                onclick: move |_| {
+                                    let on = !*terminal_mode.peek();
+                                    terminal_mode.set(on);
+                                },
+                                "{dict.devices_btn_terminal()}"
+                            }
+                            Button {
+                                variant: { if *split_view.read() { "tonal" } else { "text" } }.to_string(),
+                                icon: "vertical_split".to_string(),
                                 onclick: move |_| {
-                                    logs.write().clear();
+                                    let on = !*split_view.peek();
+                                    split_view.set(on);
                                 },
-                                "{dict.devices_btn_clear}"
+                                "{dict.devices_btn_split()}"
+                            }
+                            Button {
+                                variant: "text".to_string(),
+                                icon: "delete_sweep".to_string(),
+                                onclick: move |_| clear_log(),
+                                "{dict.devices_btn_clear()}"
                             }
                             Button {
                                 variant: { if *is_connected.read() { "tonal" } else { "text" } }.to_string(),
                                 icon: { if *is_connected.read() { "link_off" } else { "link" } }.to_string(),
                                 onclick: move |_| {
                                     let connected = *is_connected.read();
+                                    let source = monitor_source.read().clone();
+                                    let listen_on = listen_port.read().trim().parse::<u16>().unwrap_or(NET_LOG_PORT);
+                                    let host = webrepl_host.read().trim().to_string();
+                                    let password = webrepl_password.read().clone();
+                                    let ble_target = ble_device.read().trim().to_string();
                                     let port = port_name.read().clone(); // Use dynamic port
                                     let baud_str = baud_rate.read().clone();
                                     let baud = baud_str.parse::<u32>().unwrap_or(115200);
@@ -472,97 +2287,577 @@ pub fn Devices() -> Element {
                                             if invoke("monitor_disconnect", JsValue::NULL).await.is_ok() {
                                                 is_connected.set(false);
                                             }
+                                        } else if source == "webrepl" {
+                                            let args = serde_wasm_bindgen::to_value(&MonitorWebreplArgs {
+                                                host,
+                                                password,
+                                            })
+                                            .unwrap_or(JsValue::NULL);
+                                            match invoke("monitor_webrepl", args).await {
+                                                Ok(_) => is_connected.set(true),
+                                                Err(e) => {
+                                                    let dict = lang.peek().clone();
+                                                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                                                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                                                        .ok();
+                                                    toasts.push("error", "monitor_failed", detail);
+                                                }
+                                            }
+                                        } else if source == "ble" {
+                                            let args = serde_wasm_bindgen::to_value(&MonitorBleArgs {
+                                                device: ble_target,
+                                            })
+                                            .unwrap_or(JsValue::NULL);
+                                            match invoke("monitor_ble", args).await {
+                                                Ok(_) => is_connected.set(true),
+                                                Err(e) => {
+                                                    let dict = lang.peek().clone();
+                                                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                                                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                                                        .ok();
+                                                    toasts.push("error", "monitor_failed", detail);
+                                                }
+                                            }
+                                        } else if source != "serial" {
+                                            let args = serde_wasm_bindgen::to_value(&MonitorListenArgs {
+                                                protocol: source,
+                                                port: listen_on,
+                                            })
+                                            .unwrap_or(JsValue::NULL);
+                                            match invoke("monitor_listen", args).await {
+                                                Ok(_) => is_connected.set(true),
+                                                Err(e) => {
+                                                    let dict = lang.peek().clone();
+                                                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                                                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                                                        .ok();
+                                                    toasts.push("error", "monitor_failed", detail);
+                                                }
+                                            }
                                         } else {
                                             if port.is_empty() {
-                                                web_sys::console::error_1(&"No port selected".into());
+                                                toasts.push("error", "no_port", None);
                                                 return;
                                             }
                                             let args = serde_wasm_bindgen::to_value(
 
                                                     &MonitorConnectArgs {
-                                                        port_name: port,
+                                                        port_name: port.clone(),
                                                         baud_rate: baud,
+                                                        boot_marker: Some(boot_marker.peek().clone()),
                                                     },
                                                 )
                                                 .unwrap();
-                                            if invoke("monitor_connect", args).await.is_ok() {
+                                            let connected = invoke("monitor_connect", args).await;
+                                            if let Err(e) = &connected {
+                                                let dict = lang.peek().clone();
+                                                let detail = serde_wasm_bindgen::from_value::<AppError>(e.clone())
+                                                    .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                                                    .ok();
+                                                toasts.push("error", "monitor_failed", detail);
+                                            }
+                                            if connected.is_ok() {
                                                 is_connected.set(true);
+
+                                                // Remember the working settings for this device
+                                                let serial = device_serial.read().clone();
+                                                if let Some(sn) = serial {
+                                                    let args = serde_wasm_bindgen::to_value(&SaveDevicePrefsArgs {
+                                                        serial_number: sn,
+                                                        prefs: DevicePrefs {
+                                                            port_name: port,
+                                                            baud_rate: baud,
+                                                            boot_marker: Some(boot_marker.peek().clone()),
+                                                            local_echo: Some(*local_echo.peek()),
+                                                            encoding: Some(encoding.peek().clone()),
+                                                        },
+                                                    })
+                                                    .unwrap_or(JsValue::NULL);
+                                                    invoke("save_device_prefs", args).await.ok();
+                                                }
                                             }
                                         }
                                     });
                                 },
                                 if *is_connected.read() {
-                                    "{dict.devices_btn_disconnect}"
+                                    "{dict.devices_btn_disconnect()}"
                                 } else {
-                                    "{dict.connect}"
+                                    "{dict.connect()}"
                                 }
                             }
                         },
 
                         div { style: "display: flex; flex-direction: column; gap: 12px; margin-top: 8px;",
 
-                            // Log Area
-                            div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.9em; padding: 12px; border-radius: 8px; height: 400px; overflow-y: auto; white-space: pre-wrap; word-wrap: break-word;",
-                                if logs.read().is_empty() {
-                                    span { style: "color: #666;", "{dict.devices_log_placeholder}" }
+                            if let Some(note) = *monitor_note.read() {
+                                div { style: "font-size: 0.8em; color: var(--md-sys-color-tertiary);", "{dict.t(note)}" }
+                            }
+
+                            if *terminal_mode.read() {
+                                TerminalView {}
+                            } else {
+                                // Log Area: a spacer as tall as all held lines, with
+                                // only the fetched window placed inside it
+                                div {
+                                    style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.9em; padding: 12px; border-radius: 8px; height: {LOG_VIEW_HEIGHT}px; overflow: auto; white-space: pre; box-sizing: content-box;",
+                                    onmounted: move |evt| {
+                                        log_view.set(Some(evt.data()));
+                                        log_follow.set(true);
+                                        refresh_log();
+                                    },
+                                    onscroll: move |evt| {
+                                        let top = evt.scroll_top();
+                                        log_scroll.set(top);
+                                        let bottom = (evt.scroll_height() - evt.client_height()) as f64;
+                                        log_follow.set(top >= bottom - LOG_LINE_HEIGHT);
+                                        refresh_log();
+                                    },
+                                    if log_empty {
+                                        span { style: "color: #666;", "{dict.devices_log_placeholder()}" }
+                                    } else {
+                                        div {
+                                            style: "position: relative; height: {log_height}px;",
+                                            div {
+                                                style: "position: absolute; left: 0; right: 0; top: {log_offset}px;",
+                                                for (number, line, repeats) in log_lines {
+                                                    div {
+                                                        key: "{number}",
+                                                        style: "height: {LOG_LINE_HEIGHT}px; line-height: {LOG_LINE_HEIGHT}px;",
+                                                        "{line}"
+                                                        if repeats > 0 {
+                                                            span {
+                                                                style: "margin-left: 8px; padding: 0 6px; border-radius: 8px; background: #3a3d41; color: #9cdcfe; cursor: pointer;",
+                                                                title: "{dict.monitor_repeats_hint()}",
+                                                                onclick: move |_| show_run(number),
+                                                                "×{repeats + 1}"
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+
+                                // Raw lines of the folded line last opened
+                                if let Some(run) = &*log_run.read() {
+                                    div { style: "display: flex; flex-direction: column; gap: 4px;",
+                                        div { style: "display: flex; align-items: center; justify-content: space-between; font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);",
+                                            span { "{dict.monitor_repeats_title()} ({run.len()})" }
+                                            button {
+                                                class: "md-button btn-text",
+                                                onclick: move |_| log_run.set(None),
+                                                span { class: "material-symbols-outlined icon", "close" }
+                                            }
+                                        }
+                                        div { style: "background: #1e1e1e; color: #d4d4d4; font-family: 'JetBrains Mono', 'Consolas', 'Courier New', monospace; font-size: 0.9em; padding: 12px; border-radius: 8px; max-height: 160px; overflow: auto; white-space: pre;",
+                                            for (i, line) in run.iter().enumerate() {
+                                                div { key: "{i}", "{line}" }
+                                            }
+                                        }
+                                    }
+                                }
+
+                                // Input Area
+                                div { style: "display: flex; gap: 8px;",
+                                    input {
+                                        r#type: "text",
+                                        name: "monitor_input",
+                                        id: "monitor_input",
+                                        value: "{input_cmd}",
+                                        placeholder: "{dict.devices_input_placeholder()}",
+                                        class: "md-input",
+                                        style: "flex: 1;",
+                                        onmounted: move |evt| input_view.set(Some(evt.data())),
+                                        oninput: move |evt| input_cmd.set(evt.value()),
+                                        onkeypress: move |evt| {
+                                            if evt.key() == Key::Enter {
+                                                send_input();
+                                            }
+                                        },
+                                    }
+                                    label {
+                                        style: "display: flex; align-items: center; gap: 4px; font-size: 0.9em;",
+                                        title: "{dict.devices_local_echo_hint()}",
+                                        input {
+                                            r#type: "checkbox",
+                                            checked: *local_echo.read(),
+                                            onchange: move |evt| {
+                                                let enabled = evt.checked();
+                                                local_echo.set(enabled);
+                                                spawn(async move {
+                                                    let args = serde_wasm_bindgen::to_value(&MonitorSetEchoArgs { enabled })
+                                                        .unwrap_or(JsValue::NULL);
+                                                    invoke("monitor_set_echo", args).await.ok();
+                                                });
+                                            },
+                                        }
+                                        "{dict.devices_local_echo()}"
+                                    }
+                                    Button {
+                                        variant: "tonal".to_string(),
+                                        icon: "send".to_string(),
+                                        onclick: move |_| send_input(),
+                                    }
+                                }
+
+                                // Host time for firmware with a clock-setting command
+                                div { style: "display: flex; gap: 8px; align-items: center;",
+                                    input {
+                                        class: "md-input",
+                                        style: "flex: 1; font-family: monospace; font-size: 0.9em;",
+                                        title: "{dict.devices_time_command_hint()}",
+                                        value: "{time_command}",
+                                        oninput: move |evt| time_command.set(evt.value()),
+                                        onchange: move |_| save_time_command(),
+                                    }
+                                    Button {
+                                        variant: "text".to_string(),
+                                        icon: "schedule".to_string(),
+                                        onclick: move |_| sync_time(),
+                                        "{dict.devices_btn_sync_time()}"
+                                    }
                                 }
-                                for log in logs.read().iter() {
-                                    span { "{log}" }
+                            }
+
+                            // Boot milestones of the last reset seen by the monitor
+                            if *monitor_source.read() == "serial" {
+                                div { style: "display: flex; align-items: center; flex-wrap: wrap; gap: 8px; font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);",
+                                    span { class: "material-symbols-outlined", style: "font-size: 16px;", "timer" }
+                                    if let Some(boot) = &*last_boot.read() {
+                                        span {
+                                            "{dict.boot_bootloader()} {boot_ms(boot.bootloader_ms)} · app_main {boot_ms(boot.app_main_ms)}"
+                                        }
+                                        if boot.marker_ms.is_some() {
+                                            span { "· {dict.boot_marker()} {boot_ms(boot.marker_ms)}" }
+                                        }
+                                    } else {
+                                        span { "{dict.boot_waiting()}" }
+                                    }
+                                    if let Some((average, count)) = boot_average {
+                                        span { "· {dict.boot_average()} {average} ms ({count})" }
+                                    }
+                                    span { style: "flex: 1;" }
+                                    input {
+                                        class: "md-input",
+                                        style: "width: 160px; font-family: monospace; font-size: 0.9em;",
+                                        placeholder: "{dict.boot_marker_placeholder()}",
+                                        title: "{dict.boot_marker_hint()}",
+                                        value: "{boot_marker}",
+                                        oninput: move |evt| boot_marker.set(evt.value()),
+                                    }
                                 }
                             }
 
-                            // Input Area
-                            div { style: "display: flex; gap: 8px;",
-                                input {
-                                    r#type: "text",
-                                    name: "monitor_input",
-                                    id: "monitor_input",
-                                    value: "{input_cmd}",
-                                    placeholder: "{dict.devices_input_placeholder}",
-                                    class: "md-input",
-                                    style: "flex: 1;",
-                                    oninput: move |evt| input_cmd.set(evt.value()),
-                                    onkeypress: move |evt| {
-                                        if evt.key() == Key::Enter {
-                                            if !input_cmd.read().is_empty() {
-                                                logs.write().push(format!("> {}", input_cmd.read()));
-                                                input_cmd.set("".to_string());
+                            // Why the board last reset, with fixes for the bad cases
+                            if *monitor_source.read() == "serial" {
+                                if let Some(reset) = &*last_reset.read() {
+                                    {
+                                        let (explanation, fixes) = dict.reset_explanation(&reset.cause);
+                                        let color = match reset.cause.as_str() {
+                                            "power" | "watchdog" | "panic" => "var(--md-sys-color-error)",
+                                            _ => "var(--md-sys-color-on-surface-variant)",
+                                        };
+                                        rsx! {
+                                            div { style: "display: flex; flex-direction: column; gap: 4px; padding: 8px 12px; border-radius: 8px; border: 1px solid {color}; font-size: 0.85em;",
+                                                div { style: "display: flex; align-items: center; gap: 8px;",
+                                                    span { class: "material-symbols-outlined", style: "font-size: 16px; color: {color};", "restart_alt" }
+                                                    span { style: "font-weight: 500; color: {color};",
+                                                        "{dict.reset_last()}: {dict.reset_cause(&reset.cause)}"
+                                                    }
+                                                    span { style: "font-family: monospace; color: var(--md-sys-color-outline);", "rst:{reset.rom_reason}" }
+                                                    span { style: "flex: 1;" }
+                                                    button {
+                                                        class: "md-button btn-text",
+                                                        title: "{dict.reset_dismiss()}",
+                                                        onclick: move |_| last_reset.set(None),
+                                                        span { class: "material-symbols-outlined icon", "close" }
+                                                    }
+                                                }
+                                                if let Some(explanation) = explanation {
+                                                    span { "{explanation}" }
+                                                }
+                                                if let Some(banner) = &reset.banner {
+                                                    span { style: "font-family: monospace; word-break: break-all; color: var(--md-sys-color-on-surface-variant);", "{banner}" }
+                                                }
+                                                if let Some(fixes) = fixes {
+                                                    span { style: "color: var(--md-sys-color-on-surface-variant);", "{dict.reset_fixes()}:" }
+                                                    ul { style: "margin: 0; padding-left: 20px;",
+                                                        for fix in fixes.lines() {
+                                                            li { key: "{fix}", "{fix}" }
+                                                        }
+                                                    }
+                                                }
                                             }
                                         }
-                                    },
+                                    }
                                 }
-                                Button {
-                                    variant: "tonal".to_string(),
-                                    icon: "send".to_string(),
-                                    onclick: move |_| {
-                                        let cmd = input_cmd.read().clone();
-                                        if !cmd.is_empty() {
-                                            logs.write().push(format!("> {}", cmd));
-                                            input_cmd.set("".to_string());
+                            }
 
-                                            spawn(async move {
-                                                let args = serde_wasm_bindgen::to_value(&MonitorSendArgs { data: cmd })
-                                                    .unwrap();
-                                                invoke("monitor_send", args).await.ok();
-                                            });
+                            // Programs run straight from a local .py file
+                            if *monitor_source.read() == "serial" {
+                                div { style: "display: flex; justify-content: flex-end;",
+                                    if *py_running.read() {
+                                        Button {
+                                            variant: "tonal".to_string(),
+                                            icon: "stop".to_string(),
+                                            onclick: move |_| stop_python(),
+                                            "{dict.devices_btn_stop_py()}"
                                         }
-                                    },
+                                    } else {
+                                        Button {
+                                            variant: "text".to_string(),
+                                            icon: "play_arrow".to_string(),
+                                            onclick: move |_| run_python(),
+                                            "{dict.devices_btn_run_py()}"
+                                        }
+                                    }
+                                }
+                            }
+
+                            // Files on a WebREPL board
+                            if *monitor_source.read() == "webrepl" && *is_connected.read() {
+                                div { style: "display: flex; gap: 8px; align-items: center;",
+                                    input {
+                                        class: "md-input",
+                                        style: "flex: 1;",
+                                        placeholder: "{dict.devices_webrepl_remote_placeholder()}",
+                                        value: "{webrepl_remote}",
+                                        oninput: move |evt| webrepl_remote.set(evt.value()),
+                                    }
+                                    Button {
+                                        variant: "text".to_string(),
+                                        icon: "download".to_string(),
+                                        onclick: move |_| webrepl_download(),
+                                        "{dict.devices_webrepl_btn_download()}"
+                                    }
+                                    Button {
+                                        variant: "text".to_string(),
+                                        icon: "upload".to_string(),
+                                        onclick: move |_| webrepl_upload(),
+                                        "{dict.devices_webrepl_btn_upload()}"
+                                    }
                                 }
                             }
                         }
                     }
+                    Card {
+                        title: dict.triggers_title().to_string(),
+                        subtitle: dict.triggers_subtitle().to_string(),
+                        TriggersPanel {}
+                    }
+                    Card {
+                        title: dict.notifications_title().to_string(),
+                        subtitle: dict.notifications_subtitle().to_string(),
+                        NotificationsPanel {}
+                    }
+                    Card {
+                        title: dict.capture_title().to_string(),
+                        subtitle: dict.capture_subtitle().to_string(),
+                        CapturePanel {}
+                    }
+                } else if *active_tab.read() == "debug" {
+                    Card {
+                        title: dict.debug_title().to_string(),
+                        subtitle: dict.debug_subtitle().to_string(),
+                        DebugLogPanel {}
+                    }
+                    Card {
+                        title: dict.config_title().to_string(),
+                        subtitle: dict.config_subtitle().to_string(),
+                        ConfigPanel {}
+                    }
+                    Card {
+                        title: dict.openocd_title().to_string(),
+                        subtitle: dict.openocd_subtitle().to_string(),
+                        OpenOcdPanel { chip_model: detected_model.read().clone() }
+                    }
+                    Card {
+                        title: dict.gdb_title().to_string(),
+                        subtitle: dict.gdb_subtitle().to_string(),
+                        GdbPanel { chip_model: detected_model.read().clone() }
+                    }
+                    Card {
+                        title: dict.registers_title().to_string(),
+                        subtitle: dict.registers_subtitle().to_string(),
+                        RegisterPanel { chip_model: detected_model.read().clone() }
+                    }
+                    Card {
+                        title: dict.coredump_title().to_string(),
+                        subtitle: dict.coredump_subtitle().to_string(),
+                        CoreDumpPanel { port_name: port_name.read().clone() }
+                    }
+                } else if *active_tab.read() == "gpio" {
+                    Card {
+                        title: dict.gpio_title().to_string(),
+                        subtitle: dict.gpio_subtitle().to_string(),
+                        GpioPanel {
+                            port_name: port_name.read().clone(),
+                            chip_model: detected_model.read().clone(),
+                            connection_type: detected_connection_type.read().clone(),
+                        }
+                    }
+                } else if *active_tab.read() == "examples" {
+                    Card {
+                        title: dict.examples_title().to_string(),
+                        subtitle: dict.examples_subtitle().to_string(),
+                        ExamplePanel {
+                            port_name: port_name.read().clone(),
+                            chip_model: detected_model.read().clone(),
+                        }
+                    }
+                } else if *active_tab.read() == "ota" {
+                    Card {
+                        title: dict.ota_title().to_string(),
+                        subtitle: dict.ota_subtitle().to_string(),
+                        OtaPanel {}
+                    }
+                    Card {
+                        title: dict.ota_server_title().to_string(),
+                        subtitle: dict.ota_server_subtitle().to_string(),
+                        OtaServerPanel {}
+                    }
+                    Card {
+                        title: dict.sign_title().to_string(),
+                        subtitle: dict.sign_subtitle().to_string(),
+                        SigningPanel {}
+                    }
+                    Card {
+                        title: dict.encrypt_title().to_string(),
+                        subtitle: dict.encrypt_subtitle().to_string(),
+                        EncryptPanel { chip_model: detected_model.read().clone() }
+                    }
+                } else if *active_tab.read() == "provision" {
+                    Card {
+                        title: dict.improv_title().to_string(),
+                        subtitle: dict.improv_subtitle().to_string(),
+                        ImprovPanel { port_name: port_name.read().clone() }
+                    }
+                    Card {
+                        title: dict.ble_prov_title().to_string(),
+                        subtitle: dict.ble_prov_subtitle().to_string(),
+                        BleProvPanel {}
+                    }
+                } else if *active_tab.read() == "files" {
+                    Card {
+                        title: dict.mpy_firmware_title().to_string(),
+                        subtitle: dict.mpy_firmware_subtitle().to_string(),
+                        MpyFirmwarePanel {
+                            port_name: port_name.read().clone(),
+                            chip_model: detected_model.read().clone(),
+                        }
+                    }
+                    Card {
+                        title: dict.mpy_files_title().to_string(),
+                        subtitle: dict.mpy_files_subtitle().to_string(),
+                        MpyFilesPanel { port_name: port_name.read().clone() }
+                    }
+                    Card {
+                        title: dict.circuitpy_title().to_string(),
+                        subtitle: dict.circuitpy_subtitle().to_string(),
+                        CircuitPyPanel { serial_number: device_serial.read().clone() }
+                    }
+                } else if *active_tab.read() == "script" {
+                    Card {
+                        title: dict.script_title().to_string(),
+                        subtitle: dict.script_subtitle().to_string(),
+                        ScriptPanel {
+                            port_name: port_name.read().clone(),
+                            baud_rate: baud_rate.read().parse::<u32>().unwrap_or(115200),
+                        }
+                    }
+                    Card {
+                        title: dict.test_title().to_string(),
+                        subtitle: dict.test_subtitle().to_string(),
+                        TestPanel {
+                            port_name: port_name.read().clone(),
+                            baud_rate: baud_rate.read().parse::<u32>().unwrap_or(115200),
+                        }
+                    }
+                    Card {
+                        title: dict.soak_title().to_string(),
+                        subtitle: dict.soak_subtitle().to_string(),
+                        SoakPanel {
+                            port_name: port_name.read().clone(),
+                            baud_rate: baud_rate.read().parse::<u32>().unwrap_or(115200),
+                        }
+                    }
+                    Card {
+                        title: dict.benchmark_title().to_string(),
+                        subtitle: dict.benchmark_subtitle().to_string(),
+                        BenchmarkPanel { port_name: port_name.read().clone() }
+                    }
+                    Card {
+                        title: dict.label_title().to_string(),
+                        subtitle: dict.label_subtitle().to_string(),
+                        LabelPanel { port_name: port_name.read().clone() }
+                    }
+                    Card {
+                        title: dict.inventory_title().to_string(),
+                        subtitle: dict.inventory_subtitle().to_string(),
+                        InventoryPanel {}
+                    }
+                    Card {
+                        title: dict.kiosk_title().to_string(),
+                        subtitle: dict.kiosk_subtitle().to_string(),
+                        KioskPanel {}
+                    }
+                    Card {
+                        title: dict.device_db_title().to_string(),
+                        subtitle: dict.device_db_subtitle().to_string(),
+                        DeviceRecordsPanel {}
+                    }
+                    Card {
+                        title: dict.production_run_title().to_string(),
+                        subtitle: dict.production_run_subtitle().to_string(),
+                        ProductionRunPanel {}
+                    }
+                } else if *active_tab.read() == "arduino" {
+                    Card {
+                        title: dict.arduino_title().to_string(),
+                        subtitle: dict.arduino_subtitle().to_string(),
+                        ArduinoPanel {
+                            port_name: port_name.read().clone(),
+                            chip_model: chip_details_info.read().as_ref().and_then(|info| info.chip_model.clone()),
+                        }
+                    }
                 } else {
                     Card {
-                        title: dict.board_view_title.to_string(),
+                        title: dict.board_view_title().to_string(),
                         subtitle: format!("View for {}", detected_model),
                         PinoutView {
                             chip_model: detected_model.read().clone(),
                             connection_type: detected_connection_type.read().clone(),
+                            pin_usage: settings.read().pin_usage.clone(),
                         }
                     }
+                    Card {
+                        title: dict.reserved_pins_title().to_string(),
+                        subtitle: dict.reserved_pins_subtitle().to_string(),
+                        ReservedPinsPanel { chip_model: detected_model.read().clone() }
+                    }
+                    Card {
+                        title: dict.pin_usage_title().to_string(),
+                        subtitle: dict.pin_usage_subtitle().to_string(),
+                        PinUsagePanel {}
+                    }
                 }
             }
 
+            // Next to the monitor: the split view's second session
+            if *active_tab.read() == "monitor" && *split_view.read() {
+                div { style: "flex: 1.5; min-width: 350px;",
+                    Card { title: dict.split_monitor_title().to_string(),
+                        SplitMonitorPanel {
+                            ports: serial_ports
+                                .read()
+                                .iter()
+                                .map(|p| (p.port_name.clone(), p.label()))
+                                .collect::<Vec<_>>(),
+                        }
+                    }
+                }
+            }
         }
     }
 }
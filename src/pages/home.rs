@@ -1,5 +1,6 @@
-use crate::components::{Button, Card};
-use crate::i18n::{get_dict, Language};
+use crate::app::Route;
+use crate::components::{use_toasts, Button, Card};
+use crate::i18n::Dict;
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
@@ -8,6 +9,10 @@ use wasm_bindgen::prelude::*;
 extern "C" {
     #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
     async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -26,10 +31,120 @@ struct ChipDetails {
     chip_model: Option<String>,
     mac_address: Option<String>,
     flash_size: Option<String>,
+    flash_size_bytes: Option<u32>,
     chip_revision: Option<String>,
     crystal_frequency: Option<String>,
     features: Option<String>,
-    error: Option<String>,
+    wafer_major: Option<u32>,
+    wafer_minor: Option<u32>,
+    package: Option<String>,
+    rom_version: Option<String>,
+    sdk_version: Option<String>,
+    crystal_warning: Option<CrystalWarning>,
+    #[serde(default)]
+    flash: Option<MemoryInfo>,
+    #[serde(default)]
+    psram: Option<MemoryInfo>,
+    #[serde(default)]
+    capabilities: Option<ChipCapabilities>,
+    #[serde(default)]
+    secure_download_mode: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MemoryInfo {
+    embedded: bool,
+    size: Option<String>,
+    vendor: Option<String>,
+    mode: Option<String>, // "quad" or "octal"
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ChipCapabilities {
+    wifi: bool,
+    ble: bool,
+    ieee802154: bool,
+    usb_otg: bool,
+    cores: u8,
+    max_flash_bytes: u32,
+}
+
+impl ChipCapabilities {
+    /// e.g. "Wi-Fi · BLE · 2 cores · flash up to 16 MB"
+    fn describe(&self, dict: &Dict) -> String {
+        let mut parts = Vec::new();
+        for (present, name) in [
+            (self.wifi, "Wi-Fi"),
+            (self.ble, "BLE"),
+            (self.ieee802154, "802.15.4"),
+            (self.usb_otg, "USB-OTG"),
+        ] {
+            if present {
+                parts.push(name.to_string());
+            }
+        }
+        parts.push(format!("{} {}", self.cores, dict.cores()));
+        const MB: u32 = 1024 * 1024;
+        let max_flash = if self.max_flash_bytes >= 1024 * MB {
+            format!("{} GB", self.max_flash_bytes / (1024 * MB))
+        } else {
+            format!("{} MB", self.max_flash_bytes / MB)
+        };
+        parts.push(format!("{} {}", dict.max_flash(), max_flash));
+        parts.join(" · ")
+    }
+}
+
+impl MemoryInfo {
+    fn is_octal(&self) -> bool {
+        self.mode.as_deref() == Some("octal")
+    }
+
+    /// e.g. "Embedded 8 MB · GD · Quad"
+    fn describe(&self, dict: &Dict) -> String {
+        let mut parts = vec![if self.embedded {
+            dict.memory_embedded().to_string()
+        } else {
+            dict.memory_external().to_string()
+        }];
+        if let Some(size) = &self.size {
+            parts[0] = format!("{} {}", parts[0], size);
+        }
+        parts.extend(self.vendor.clone());
+        match self.mode.as_deref() {
+            Some("octal") => parts.push(dict.memory_octal().to_string()),
+            Some("quad") => parts.push(dict.memory_quad().to_string()),
+            _ => {}
+        }
+        parts.join(" · ")
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CrystalWarning {
+    detected: String,
+    expected: String,
+    apparent_baud: u32,
+    message: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+/// Backend device lifecycle snapshot, pushed as `device-state` events.
+#[derive(Deserialize, Clone, Debug)]
+struct DeviceSnapshot {
+    key: String,
+    state: String, // "detected", "identified", "monitoring", "flashing", "error", "disconnected"
+    status: DeviceStatus,
+    chip: Option<ChipDetails>,
+    error: Option<AppError>,
 }
 
 #[derive(Serialize)]
@@ -38,13 +153,8 @@ struct GetChipInfoArgs {
     port_name: String,
 }
 
-#[component]
-pub fn Home() -> Element {
-    let lang = use_context::<Signal<Language>>();
-    let dict = get_dict(*lang.read());
-
-    // Default status: disconnected
-    let mut device_status = use_signal(|| DeviceStatus {
+fn disconnected_status() -> DeviceStatus {
+    DeviceStatus {
         code: "none".to_string(),
         message: "Disconnected".to_string(),
         port_name: None,
@@ -52,17 +162,41 @@ pub fn Home() -> Element {
         serial_number: None,
         vid_pid: None,
         connection_type: None,
-    });
+    }
+}
 
-    let mut chip_details = use_signal(|| ChipDetails {
+fn empty_chip_details() -> ChipDetails {
+    ChipDetails {
         chip_model: None,
         mac_address: None,
         flash_size: None,
+        flash_size_bytes: None,
         chip_revision: None,
         crystal_frequency: None,
         features: None,
-        error: None,
-    });
+        wafer_major: None,
+        wafer_minor: None,
+        package: None,
+        rom_version: None,
+        sdk_version: None,
+        crystal_warning: None,
+        flash: None,
+        psram: None,
+        capabilities: None,
+        secure_download_mode: false,
+    }
+}
+
+#[component]
+pub fn Home() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+
+    // Default status: disconnected
+    let mut device_status = use_signal(disconnected_status);
+
+    let mut chip_details = use_signal(empty_chip_details);
+    let mut chip_error = use_signal(|| None::<AppError>);
 
     // Manual refresh handler
     let refresh_chip_info = move |_| {
@@ -74,6 +208,7 @@ pub fn Home() -> Element {
                 let args =
                     serde_wasm_bindgen::to_value(&GetChipInfoArgs { port_name: port }).unwrap();
 
+                chip_error.set(None);
                 match invoke("get_chip_info", args).await {
                     Ok(detail_res) => {
                         if let Ok(details) =
@@ -83,7 +218,7 @@ pub fn Home() -> Element {
                         }
                     }
                     Err(e) => {
-                        chip_details.write().error = Some(format!("Invoke Error: {:?}", e));
+                        chip_error.set(serde_wasm_bindgen::from_value::<AppError>(e).ok());
                     }
                 }
             }
@@ -107,88 +242,125 @@ pub fn Home() -> Element {
         });
     };
 
-    // Polling effect (every 2s)
-    use_effect(move || {
+    // Board shown on this page, following the backend state machine
+    let mut device_key = use_signal(|| None::<String>);
+    let mut device_state = use_signal(|| None::<String>);
+
+    let mut show_device = move |snapshot: Option<DeviceSnapshot>| match snapshot {
+        Some(d) => {
+            device_key.set(Some(d.key));
+            device_state.set(Some(d.state));
+            device_status.set(d.status);
+            chip_details.set(d.chip.unwrap_or_else(empty_chip_details));
+            chip_error.set(d.error);
+        }
+        None => {
+            device_key.set(None);
+            device_state.set(None);
+            device_status.set(disconnected_status());
+            chip_details.set(empty_chip_details());
+            chip_error.set(None);
+        }
+    };
+
+    let refresh_devices = move || {
         spawn(async move {
-            loop {
-                // Manually call check
-                match invoke("check_device_status", JsValue::NULL).await {
-                    Ok(js_res) => {
-                        if let Ok(res) = serde_wasm_bindgen::from_value::<DeviceStatus>(js_res) {
-                            let current_code = device_status.read().code.clone();
-                            let current_port = device_status.read().port_name.clone();
-                            device_status.set(res.clone());
-
-                            // Trigger chip info fetch only if connected and not yet fetched
-                            // Or if port changed
-                            if res.code == "ok" {
-                                let new_port = res.port_name.clone();
-                                // If it's a new connection or we haven't fetched details yet
-                                if current_code != "ok" || current_port != new_port {
-                                    // Clear previous details
-                                    chip_details.set(ChipDetails {
-                                        chip_model: None,
-                                        mac_address: None,
-                                        flash_size: None,
-                                        chip_revision: None,
-                                        crystal_frequency: None,
-                                        features: None,
-                                        error: None,
-                                    });
-
-                                    // AUTO-FETCH with Retry
-                                    let port_clone = new_port.clone();
-                                    if let Some(port) = port_clone {
-                                        spawn(async move {
-                                            let args =
-                                                serde_wasm_bindgen::to_value(&GetChipInfoArgs {
-                                                    port_name: port,
-                                                })
-                                                .unwrap();
-                                            match invoke("get_chip_info", args).await {
-                                                Ok(detail_res) => {
-                                                    if let Ok(details) =
-                                                        serde_wasm_bindgen::from_value::<ChipDetails>(
-                                                            detail_res,
-                                                        )
-                                                    {
-                                                        chip_details.set(details);
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    chip_details.write().error =
-                                                        Some(format!("Error: {:?}", e));
-                                                }
-                                            }
-                                        });
-                                    }
-                                }
-                            } else {
-                                // Clear details if disconnected
-                                if current_code == "ok" {
-                                    chip_details.set(ChipDetails {
-                                        chip_model: None,
-                                        mac_address: None,
-                                        flash_size: None,
-                                        chip_revision: None,
-                                        crystal_frequency: None,
-                                        features: None,
-                                        error: None,
-                                    });
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        web_sys::console::error_1(&e);
+            match invoke("list_devices", JsValue::NULL).await {
+                Ok(res) => {
+                    if let Ok(list) = serde_wasm_bindgen::from_value::<Vec<DeviceSnapshot>>(res) {
+                        show_device(list.into_iter().next());
                     }
                 }
-                // Sleep 2s
-                gloo_timers::future::TimeoutFuture::new(2000).await;
+                Err(e) => web_sys::console::error_1(&e),
             }
         });
+    };
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
     });
 
+    use_effect(move || {
+        refresh_devices();
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: DeviceSnapshot,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                let current = device_key.peek().clone();
+                if current.as_ref().is_some_and(|k| *k != e.payload.key) {
+                    // Another board; this page follows the first one
+                    return;
+                }
+                if e.payload.state == "disconnected" {
+                    // Fall back to any other board still attached
+                    refresh_devices();
+                } else {
+                    show_device(Some(e.payload));
+                }
+            });
+            match listen("device-state", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    // The backend asks where to save the report, then reads the board
+    let toasts = use_toasts();
+    let mut is_exporting = use_signal(|| false);
+    let export_report = move |_| {
+        let Some(port) = device_status.peek().port_name.clone() else {
+            return;
+        };
+        is_exporting.set(true);
+        spawn(async move {
+            let args = serde_wasm_bindgen::to_value(&GetChipInfoArgs { port_name: port }).unwrap();
+            match invoke("export_device_report", args).await {
+                Ok(res) => {
+                    if let Ok(Some(path)) = serde_wasm_bindgen::from_value::<Option<String>>(res) {
+                        toasts.push("success", "report_saved", Some(path));
+                    }
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    let detail = serde_wasm_bindgen::from_value::<AppError>(e)
+                        .map(|err| format!("{}: {}", dict.error_kind(&err.kind), err.message))
+                        .ok();
+                    toasts.push("error", "report_failed", detail);
+                }
+            }
+            is_exporting.set(false);
+        });
+    };
+
+    let open_devices = move |tab: &str| {
+        navigator().push(Route::Devices {
+            tab: tab.to_string(),
+            port: device_status.peek().port_name.clone().unwrap_or_default(),
+        });
+    };
+
     rsx! {
         div {
             class: "dashboard-container",
@@ -196,13 +368,13 @@ pub fn Home() -> Element {
 
             // Card 1: Device Status
             Card {
-                title: dict.device_status_title.to_string(),
+                title: dict.device_status_title().to_string(),
                 subtitle: if let Some(model) = &chip_details.read().chip_model {
                     format!("{} Connected", model)
                 } else if let Some(product) = &device_status.read().product_name {
                         product.clone()
                 } else {
-                    dict.device_status_subtitle.to_string()
+                    dict.device_status_subtitle().to_string()
                 },
                 actions: rsx! {
                         if device_status.read().code == "missing_driver" {
@@ -218,7 +390,7 @@ pub fn Home() -> Element {
                                 variant: "text".to_string(),
                                 icon: "verified".to_string(), // or 'security' or 'build'
                                 onclick: check_driver,
-                                "{dict.driver_check_btn}"
+                                "{dict.driver_check_btn()}"
                             }
                     }
                     // Refresh Button (Manual Trigger for Level 2 Info)
@@ -254,21 +426,71 @@ pub fn Home() -> Element {
                                     style: "font-weight: 500; color: var(--md-sys-color-on-surface);",
                                     "{device_status.read().message}"
                                 }
-                                if device_status.read().code == "ok" {
+                                if let Some(state) = &*device_state.read() {
                                     span {
                                         style: "font-size: 0.8em; color: var(--md-sys-color-on-surface-variant);",
-                                        "{dict.ready_to_flash}"
+                                        "{dict.device_state(state)}"
                                     }
                                 }
                             }
                     }
 
                     // Error Row (if probing failed)
-                    if let Some(err) = &chip_details.read().error {
+                    if let Some(err) = &*chip_error.read() {
                         div {
                             style: "background-color: var(--md-sys-color-error-container); color: var(--md-sys-color-on-error-container); padding: 8px 12px; border-radius: 8px; font-size: 0.9em; display: flex; gap: 8px; align-items: center;",
                             span { class: "material-symbols-outlined", style: "font-size: 18px;", "report" }
-                            "{dict.probing_error}: {err}"
+                            div {
+                                style: "display: flex; flex-direction: column; gap: 2px;",
+                                span {
+                                    title: "{err.message}",
+                                    "{dict.probing_error()}: {dict.error_kind(&err.kind)}"
+                                }
+                                if let Some(hint) = dict.error_hint(&err.kind) {
+                                    span { style: "font-size: 0.9em; opacity: 0.8;", "{hint}" }
+                                }
+                            }
+                        }
+                    }
+
+                    // Secure Download Mode: only a limited command set works
+                    if chip_details.read().secure_download_mode {
+                        div {
+                            style: "background-color: var(--md-sys-color-tertiary-container); color: var(--md-sys-color-on-tertiary-container); padding: 8px 12px; border-radius: 8px; font-size: 0.9em; display: flex; gap: 8px; align-items: center;",
+                            span { class: "material-symbols-outlined", style: "font-size: 18px;", "lock" }
+                            div {
+                                style: "display: flex; flex-direction: column; gap: 2px;",
+                                span { "{dict.secure_download_mode()}" }
+                                span { style: "font-size: 0.9em; opacity: 0.8;", "{dict.hint_secure_download()}" }
+                            }
+                        }
+                    }
+
+                    // Crystal mismatch (classic garbled UART on 26 MHz boards)
+                    if let Some(warning) = &chip_details.read().crystal_warning {
+                        div {
+                            style: "background-color: var(--md-sys-color-tertiary-container); color: var(--md-sys-color-on-tertiary-container); padding: 8px 12px; border-radius: 8px; font-size: 0.9em; display: flex; gap: 8px; align-items: center;",
+                            span { class: "material-symbols-outlined", style: "font-size: 18px;", "warning" }
+                            div {
+                                style: "display: flex; flex-direction: column; gap: 2px;",
+                                span {
+                                    title: "{warning.message}",
+                                    "{dict.crystal_warning()}: {warning.detected} ≠ {warning.expected}"
+                                }
+                                span {
+                                    style: "font-size: 0.9em; opacity: 0.8;",
+                                    "{dict.crystal_warning_hint()} {warning.apparent_baud} baud"
+                                }
+                            }
+                        }
+                    }
+
+                    // Octal parts only boot firmware built for OPI mode
+                    if chip_details.read().flash.iter().chain(chip_details.read().psram.iter()).any(MemoryInfo::is_octal) {
+                        div {
+                            style: "background-color: var(--md-sys-color-tertiary-container); color: var(--md-sys-color-on-tertiary-container); padding: 8px 12px; border-radius: 8px; font-size: 0.9em; display: flex; gap: 8px; align-items: center;",
+                            span { class: "material-symbols-outlined", style: "font-size: 18px;", "info" }
+                            "{dict.octal_memory_hint()}"
                         }
                     }
 
@@ -285,7 +507,7 @@ pub fn Home() -> Element {
                                 style: "font-size: 18px;",
                                 if is_installed { "check_circle" } else { "warning" }
                             }
-                            if is_installed { "{dict.driver_installed}" } else { "{dict.driver_not_found}" }
+                            if is_installed { "{dict.driver_installed()}" } else { "{dict.driver_not_found()}" }
                         }
                     }
 
@@ -302,14 +524,14 @@ pub fn Home() -> Element {
                                 if let Some(port) = &device_status.read().port_name {
                                     InfoItem {
                                         icon: "usb",
-                                        label: dict.port.to_string(),
+                                        label: dict.port().to_string(),
                                         value: port.clone(),
                                     }
                                 }
                                 if let Some(vid_pid) = &device_status.read().vid_pid {
                                     InfoItem {
                                         icon: "fingerprint",
-                                        label: dict.vid_pid.to_string(),
+                                        label: dict.vid_pid().to_string(),
                                         value: vid_pid.clone(),
                                     }
                                 }
@@ -317,7 +539,7 @@ pub fn Home() -> Element {
                                     if chip_details.read().mac_address.as_ref() != Some(sn) {
                                         InfoItem {
                                             icon: "pin",
-                                            label: dict.serial_number.to_string(),
+                                            label: dict.serial_number().to_string(),
                                             value: sn.clone(),
                                             full_width: true,
                                         }
@@ -326,8 +548,8 @@ pub fn Home() -> Element {
                                 if let Some(ctype) = &device_status.read().connection_type {
                                     InfoItem {
                                         icon: "cable",
-                                        label: dict.connection_type.to_string(),
-                                        value: if ctype == "native_usb" { dict.type_native_usb.to_string() } else { dict.type_uart_bridge.to_string() },
+                                        label: dict.connection_type().to_string(),
+                                        value: if ctype == "native_usb" { dict.type_native_usb().to_string() } else { dict.type_uart_bridge().to_string() },
                                     }
                                 }
                             }
@@ -343,21 +565,35 @@ pub fn Home() -> Element {
                                     if let Some(model) = &chip_details.read().chip_model {
                                         InfoItem {
                                             icon: "memory",
-                                            label: dict.chip_model.to_string(),
+                                            label: dict.chip_model().to_string(),
                                             value: model.clone(),
                                         }
                                     }
                                     if let Some(flash) = &chip_details.read().flash_size {
                                         InfoItem {
                                             icon: "save",
-                                            label: dict.flash_size.to_string(),
+                                            label: dict.flash_size().to_string(),
                                             value: flash.clone(),
                                         }
                                     }
+                                    if let Some(flash) = &chip_details.read().flash {
+                                        InfoItem {
+                                            icon: "sd_card",
+                                            label: dict.flash_location().to_string(),
+                                            value: flash.describe(&dict),
+                                        }
+                                    }
+                                    if let Some(psram) = &chip_details.read().psram {
+                                        InfoItem {
+                                            icon: "memory_alt",
+                                            label: dict.psram().to_string(),
+                                            value: psram.describe(&dict),
+                                        }
+                                    }
                                     if let Some(mac) = &chip_details.read().mac_address {
                                         InfoItem {
                                             icon: "lan",
-                                            label: dict.mac_address.to_string(),
+                                            label: dict.mac_address().to_string(),
                                             value: mac.clone(),
                                             full_width: true,
                                         }
@@ -365,21 +601,57 @@ pub fn Home() -> Element {
                                     if let Some(rev) = &chip_details.read().chip_revision {
                                         InfoItem {
                                             icon: "verified_user",
-                                            label: dict.chip_revision.to_string(),
+                                            label: dict.chip_revision().to_string(),
                                             value: rev.clone(),
                                         }
                                     }
+                                    if let (Some(major), Some(minor)) = (chip_details.read().wafer_major, chip_details.read().wafer_minor) {
+                                        InfoItem {
+                                            icon: "layers",
+                                            label: dict.wafer_revision().to_string(),
+                                            value: format!("{} {} · {} {}", dict.wafer_major(), major, dict.wafer_minor(), minor),
+                                        }
+                                    }
+                                    if let Some(package) = &chip_details.read().package {
+                                        InfoItem {
+                                            icon: "inventory_2",
+                                            label: dict.package().to_string(),
+                                            value: package.clone(),
+                                        }
+                                    }
+                                    if let Some(rom) = &chip_details.read().rom_version {
+                                        InfoItem {
+                                            icon: "developer_board",
+                                            label: dict.rom_version().to_string(),
+                                            value: rom.clone(),
+                                        }
+                                    }
+                                    if let Some(sdk) = &chip_details.read().sdk_version {
+                                        InfoItem {
+                                            icon: "code",
+                                            label: dict.sdk_version().to_string(),
+                                            value: sdk.clone(),
+                                        }
+                                    }
                                     if let Some(freq) = &chip_details.read().crystal_frequency {
                                         InfoItem {
                                             icon: "sensors",
-                                            label: dict.crystal_frequency.to_string(),
+                                            label: dict.crystal_frequency().to_string(),
                                             value: freq.clone(),
                                         }
                                     }
+                                    if let Some(caps) = &chip_details.read().capabilities {
+                                        InfoItem {
+                                            icon: "checklist",
+                                            label: dict.capabilities().to_string(),
+                                            value: caps.describe(&dict),
+                                            full_width: true,
+                                        }
+                                    }
                                     if let Some(feats) = &chip_details.read().features {
                                         InfoItem {
                                             icon: "featured_play_list",
-                                            label: dict.features.to_string(),
+                                            label: dict.features().to_string(),
                                             value: feats.clone(),
                                             full_width: true,
                                         }
@@ -390,6 +662,60 @@ pub fn Home() -> Element {
                     }
                 }
             }
+
+            // Card 2: Quick Actions, deep links into Devices for the detected port
+            Card {
+                title: dict.quick_actions_title().to_string(),
+                div {
+                    style: "display: grid; grid-template-columns: repeat(3, 1fr); gap: 12px; margin-top: 16px;",
+                    QuickAction {
+                        icon: "bolt",
+                        label: dict.flash_firmware().to_string(),
+                        onclick: move |_| open_devices(""),
+                    }
+                    QuickAction {
+                        icon: "terminal",
+                        label: dict.monitor().to_string(),
+                        onclick: move |_| open_devices("monitor"),
+                    }
+                    // No file manager yet
+                    QuickAction {
+                        icon: "folder",
+                        label: dict.files().to_string(),
+                        disabled: true,
+                    }
+                    QuickAction {
+                        icon: "description",
+                        label: dict.export_report().to_string(),
+                        disabled: device_status.read().port_name.is_none() || *is_exporting.read(),
+                        onclick: export_report,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn QuickAction(
+    icon: String,
+    label: String,
+    #[props(default = false)] disabled: bool,
+    onclick: Option<EventHandler<MouseEvent>>,
+) -> Element {
+    let opacity = if disabled { "0.4" } else { "1" };
+    let cursor = if disabled { "default" } else { "pointer" };
+
+    rsx! {
+        button {
+            style: "background: var(--md-sys-color-surface-container); border: none; padding: 16px 8px; border-radius: 12px; display: flex; flex-direction: column; align-items: center; gap: 8px; color: var(--md-sys-color-on-surface); font: inherit; opacity: {opacity}; cursor: {cursor};",
+            disabled,
+            onclick: move |evt| if let Some(h) = &onclick { h.call(evt) },
+            div {
+                style: "width: 40px; height: 40px; border-radius: 12px; background: var(--md-sys-color-secondary-container); color: var(--md-sys-color-on-secondary-container); display: flex; align-items: center; justify-content: center;",
+                span { class: "material-symbols-outlined", style: "font-size: 24px;", "{icon}" }
+            }
+            span { style: "font-size: 0.9em; font-weight: 500;", "{label}" }
         }
     }
 }
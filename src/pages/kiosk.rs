@@ -0,0 +1,268 @@
+use crate::i18n::Dict;
+use crate::settings::Settings;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct KioskRun {
+    summary: Option<String>,
+    error: Option<AppError>,
+    passed: u32,
+    failed: u32,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    kind: String,
+    state: String,
+    current: u64,
+    total: u64,
+}
+
+#[derive(Serialize)]
+struct KioskExitArgs {
+    password: String,
+}
+
+fn error_text(dict: &Dict, err: &AppError) -> String {
+    format!("{}: {}", dict.error_kind(&err.kind), err.message)
+}
+
+/// What a line operator sees in kiosk mode, in place of the whole app: the
+/// golden image, one button flashing it to the board plugged in, the
+/// result in large, and the units counted so far. Leaving takes the
+/// password set when kiosk mode was entered.
+#[component]
+pub fn Kiosk() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let mut settings = use_context::<Signal<Settings>>();
+
+    let mut busy = use_signal(|| false);
+    let mut progress = use_signal(|| 0.0f64);
+    // The flasher summary, or what went wrong
+    let mut outcome = use_signal(|| None::<Result<String, String>>);
+    let mut unlocking = use_signal(|| false);
+    let mut password = use_signal(String::new);
+    let mut unlock_error = use_signal(|| None::<String>);
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                let task = e.payload;
+                if task.kind == "kiosk" && task.state == "running" && task.total > 0 {
+                    progress.set(task.current as f64 * 100.0 / task.total as f64);
+                }
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let flash = move |_| {
+        busy.set(true);
+        progress.set(0.0);
+        outcome.set(None);
+        spawn(async move {
+            let dict = lang.peek().clone();
+            match invoke("kiosk_flash", JsValue::NULL).await {
+                Ok(val) => {
+                    if let Ok(run) = serde_wasm_bindgen::from_value::<KioskRun>(val) {
+                        let mut current = settings.write();
+                        current.kiosk.passed = run.passed;
+                        current.kiosk.failed = run.failed;
+                        outcome.set(Some(match run.error {
+                            Some(err) => Err(error_text(&dict, &err)),
+                            None => Ok(run.summary.unwrap_or_default()),
+                        }));
+                    }
+                }
+                // Not counted: the board was never tried
+                Err(e) => outcome.set(Some(Err(
+                    match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => error_text(&dict, &err),
+                        Err(_) => dict.error_internal().to_string(),
+                    },
+                ))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let exit = move || {
+        let args = serde_wasm_bindgen::to_value(&KioskExitArgs {
+            password: password.read().clone(),
+        })
+        .unwrap_or(JsValue::NULL);
+        spawn(async move {
+            match invoke("kiosk_exit", args).await {
+                Ok(val) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<Settings>(val) {
+                        settings.set(saved);
+                    }
+                }
+                Err(e) => {
+                    let dict = lang.peek().clone();
+                    password.set(String::new());
+                    unlock_error.set(Some(match serde_wasm_bindgen::from_value::<AppError>(e) {
+                        Ok(err) => error_text(&dict, &err),
+                        Err(_) => dict.error_internal().to_string(),
+                    }));
+                }
+            }
+        });
+    };
+
+    let kiosk = settings.read().kiosk.clone();
+    let image_name = kiosk
+        .image_path
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let short_hash = kiosk.golden_hash.get(..12).unwrap_or_default();
+    let total = kiosk.passed + kiosk.failed;
+    let percent = *progress.read() as u32;
+
+    rsx! {
+        div { style: "min-height: 100vh; display: flex; flex-direction: column; gap: 32px; padding: 32px; box-sizing: border-box; background: var(--md-sys-color-background); color: var(--md-sys-color-on-background);",
+            div { style: "display: flex; align-items: center; gap: 16px;",
+                span { class: "material-symbols-outlined", style: "font-size: 32px; color: var(--md-sys-color-primary);", "factory" }
+                div { style: "flex: 1; display: flex; flex-direction: column;",
+                    span { style: "font-size: 1.4em; font-weight: 500;", "{dict.kiosk_title()}" }
+                    span { style: "color: var(--md-sys-color-on-surface-variant);", title: "{kiosk.image_path}\nSHA-256 {kiosk.golden_hash}",
+                        "{image_name} @ {kiosk.address} · {short_hash}"
+                    }
+                }
+                if *unlocking.read() {
+                    input {
+                        class: "md-input",
+                        style: "width: 200px;",
+                        r#type: "password",
+                        placeholder: "{dict.kiosk_password()}",
+                        value: "{password}",
+                        oninput: move |evt| password.set(evt.value()),
+                        onkeypress: move |evt| {
+                            if evt.key() == Key::Enter {
+                                exit();
+                            }
+                        },
+                    }
+                    button {
+                        class: "md-button btn-tonal",
+                        onclick: move |_| exit(),
+                        span { class: "material-symbols-outlined icon", "lock_open" }
+                        span { class: "label", "{dict.kiosk_btn_exit()}" }
+                    }
+                } else {
+                    button {
+                        class: "md-button btn-text",
+                        title: "{dict.kiosk_btn_exit()}",
+                        disabled: *busy.read(),
+                        onclick: move |_| {
+                            unlock_error.set(None);
+                            unlocking.set(true);
+                        },
+                        span { class: "material-symbols-outlined icon", "lock" }
+                    }
+                }
+            }
+            if let Some(message) = &*unlock_error.read() {
+                span { style: "align-self: flex-end; color: var(--md-sys-color-error);", "{message}" }
+            }
+
+            div { style: "flex: 1; display: flex; flex-direction: column; align-items: center; justify-content: center; gap: 32px;",
+                button {
+                    class: "md-button btn-filled",
+                    style: "height: 120px; padding: 0 64px; border-radius: 60px; font-size: 1.8em;",
+                    disabled: *busy.read(),
+                    onclick: flash,
+                    span { class: "material-symbols-outlined", style: "font-size: 48px;", "bolt" }
+                    span {
+                        if *busy.read() { "{dict.kiosk_flashing()} {percent}%" } else { "{dict.kiosk_btn_flash()}" }
+                    }
+                }
+                match &*outcome.read() {
+                    Some(Ok(summary)) => rsx! {
+                        div { style: "display: flex; flex-direction: column; align-items: center; gap: 8px; padding: 24px 64px; border-radius: 24px; background: var(--md-sys-color-primary-container); color: var(--md-sys-color-on-primary-container);",
+                            span { style: "font-size: 4em; font-weight: 700;", "{dict.kiosk_pass()}" }
+                            span { "{summary}" }
+                        }
+                    },
+                    Some(Err(message)) => rsx! {
+                        div { style: "display: flex; flex-direction: column; align-items: center; gap: 8px; padding: 24px 64px; border-radius: 24px; background: var(--md-sys-color-error-container); color: var(--md-sys-color-on-error-container);",
+                            span { style: "font-size: 4em; font-weight: 700;", "{dict.kiosk_fail()}" }
+                            span { style: "max-width: 640px; text-align: center; word-break: break-word;", "{message}" }
+                        }
+                    },
+                    None => rsx! {
+                        if !*busy.read() {
+                            span { style: "font-size: 1.2em; color: var(--md-sys-color-on-surface-variant);", "{dict.kiosk_ready()}" }
+                        }
+                    },
+                }
+            }
+
+            div { style: "display: flex; justify-content: center; gap: 48px; font-size: 1.2em;",
+                div { style: "display: flex; flex-direction: column; align-items: center;",
+                    span { style: "font-size: 2.4em; font-weight: 600; color: var(--md-sys-color-primary);", "{kiosk.passed}" }
+                    span { style: "color: var(--md-sys-color-on-surface-variant);", "{dict.kiosk_passed()}" }
+                }
+                div { style: "display: flex; flex-direction: column; align-items: center;",
+                    span { style: "font-size: 2.4em; font-weight: 600; color: var(--md-sys-color-error);", "{kiosk.failed}" }
+                    span { style: "color: var(--md-sys-color-on-surface-variant);", "{dict.kiosk_failed()}" }
+                }
+                div { style: "display: flex; flex-direction: column; align-items: center;",
+                    span { style: "font-size: 2.4em; font-weight: 600;", "{total}" }
+                    span { style: "color: var(--md-sys-color-on-surface-variant);", "{dict.kiosk_total()}" }
+                }
+            }
+        }
+    }
+}
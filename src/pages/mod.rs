@@ -1,2 +1,4 @@
 pub mod devices;
 pub mod home;
+pub mod kiosk;
+pub mod onboarding;
@@ -0,0 +1,376 @@
+use crate::app::Route;
+use crate::components::{Button, Card};
+use crate::i18n::Dict;
+use crate::settings::Settings;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>)
+        -> Result<JsValue, JsValue>;
+}
+
+// In order; a step only unlocks once the one before it is done
+const STEPS: [&str; 5] = ["detect", "driver", "firmware", "flash", "monitor"];
+
+#[derive(Deserialize, Clone, Debug)]
+struct DeviceStatus {
+    code: String, // "ok", "missing_driver", "none"
+    port_name: Option<String>,
+    product_name: Option<String>,
+    vid_pid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChipSummary {
+    chip_model: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AppError {
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TaskInfo {
+    kind: String,
+    state: String,
+    current: u64,
+    total: u64,
+    port_name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PortArgs {
+    port_name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashRow {
+    firmware_path: String,
+    flash_address: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlashSegmentsArgs {
+    port_name: String,
+    segments: Vec<FlashRow>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorConnectArgs {
+    port_name: String,
+    baud_rate: u32,
+}
+
+#[derive(Clone, PartialEq)]
+enum StepState {
+    Pending,
+    Running,
+    // What the step found, e.g. the port or chip
+    Done(String),
+    // What went wrong and what to try about it
+    Failed { message: String, help: String },
+}
+
+/// Help for a failed backend call: the error kind's hint, else `fallback`.
+fn failure(dict: &Dict, e: JsValue, fallback: &str) -> StepState {
+    match serde_wasm_bindgen::from_value::<AppError>(e) {
+        Ok(err) => StepState::Failed {
+            message: format!("{}: {}", dict.error_kind(&err.kind), err.message),
+            help: dict.error_hint(&err.kind).unwrap_or(fallback).to_string(),
+        },
+        Err(_) => StepState::Failed {
+            message: dict.error_internal().to_string(),
+            help: fallback.to_string(),
+        },
+    }
+}
+
+/// Driver download advice for the USB bridge, told apart by vendor id.
+fn driver_help<'a>(dict: &'a Dict, vid_pid: Option<&str>) -> &'a str {
+    match vid_pid.and_then(|v| v.split(':').next()) {
+        Some("1A86") => dict.onboarding_help_driver_ch34x(),
+        Some("10C4") => dict.onboarding_help_driver_cp210x(),
+        Some("0403") => dict.onboarding_help_driver_ftdi(),
+        _ => dict.onboarding_help_driver_generic(),
+    }
+}
+
+/// Guided first flash for newcomers: each step drives the same backend
+/// commands as the Devices page and explains what to do when it fails.
+#[component]
+pub fn Onboarding() -> Element {
+    let lang = use_context::<Signal<Dict>>();
+    let dict = lang.read().clone();
+    let settings = use_context::<Signal<Settings>>();
+
+    let mut states = use_signal(|| vec![StepState::Pending; STEPS.len()]);
+    let mut device = use_signal(|| None::<DeviceStatus>);
+    let mut firmware = use_signal(|| None::<String>);
+    let mut flash_progress = use_signal(|| 0.0);
+
+    let port = move || {
+        device
+            .peek()
+            .as_ref()
+            .and_then(|d| d.port_name.clone())
+            .unwrap_or_default()
+    };
+
+    let mut run_step = move |step: usize| {
+        {
+            let mut states = states.write();
+            states[step] = StepState::Running;
+            // Redoing a step invalidates everything after it
+            for later in &mut states[step + 1..] {
+                *later = StepState::Pending;
+            }
+        }
+        spawn(async move {
+            let dict = lang.peek().clone();
+            let state = match STEPS[step] {
+                "detect" => match invoke("check_device_status", JsValue::NULL).await {
+                    Ok(val) => match serde_wasm_bindgen::from_value::<DeviceStatus>(val) {
+                        Ok(status) if status.code != "none" => {
+                            let found = status
+                                .product_name
+                                .clone()
+                                .or_else(|| status.vid_pid.clone())
+                                .unwrap_or_default();
+                            device.set(Some(status));
+                            StepState::Done(found)
+                        }
+                        _ => StepState::Failed {
+                            message: dict.device_disconnected().to_string(),
+                            help: dict.onboarding_help_no_device().to_string(),
+                        },
+                    },
+                    Err(e) => failure(&dict, e, dict.onboarding_help_no_device()),
+                },
+                // The board shows up on USB either way; only a working
+                // driver gives it a serial port the chip answers on
+                "driver" => {
+                    let status = device.peek().clone();
+                    match status {
+                        Some(status) if status.code == "missing_driver" => {
+                            let vid_pid = status.vid_pid.as_deref();
+                            // An installed CH34x driver that didn't bind wants a replug
+                            let installed = vid_pid.is_some_and(|v| v.starts_with("1A86"))
+                                && invoke("check_ch34x_driver", JsValue::NULL)
+                                    .await
+                                    .ok()
+                                    .and_then(|res| res.as_bool())
+                                    .unwrap_or(false);
+                            StepState::Failed {
+                                message: dict.driver_not_found().to_string(),
+                                help: if installed {
+                                    dict.onboarding_help_driver_replug().to_string()
+                                } else {
+                                    driver_help(&dict, vid_pid).to_string()
+                                },
+                            }
+                        }
+                        _ => {
+                            let args =
+                                serde_wasm_bindgen::to_value(&PortArgs { port_name: port() })
+                                    .unwrap_or(JsValue::NULL);
+                            match invoke("get_chip_info", args).await {
+                                Ok(val) => {
+                                    let model = serde_wasm_bindgen::from_value::<ChipSummary>(val)
+                                        .ok()
+                                        .and_then(|chip| chip.chip_model)
+                                        .unwrap_or_default();
+                                    StepState::Done(format!("{} · {}", port(), model))
+                                }
+                                Err(e) => failure(&dict, e, dict.onboarding_help_download_mode()),
+                            }
+                        }
+                    }
+                }
+                "firmware" => match invoke("pick_firmware_file", JsValue::NULL).await {
+                    Ok(val) => match serde_wasm_bindgen::from_value::<Option<String>>(val) {
+                        Ok(Some(path)) => {
+                            let name = path.rsplit(['/', '\\']).next().unwrap_or(&path).to_string();
+                            firmware.set(Some(path));
+                            StepState::Done(name)
+                        }
+                        // Dialog dismissed; let them pick again
+                        _ => StepState::Pending,
+                    },
+                    Err(e) => failure(&dict, e, dict.onboarding_help_firmware()),
+                },
+                "flash" => {
+                    flash_progress.set(0.0);
+                    let address = settings.peek().flash.address.clone();
+                    let args = serde_wasm_bindgen::to_value(&FlashSegmentsArgs {
+                        port_name: port(),
+                        segments: vec![FlashRow {
+                            firmware_path: firmware.peek().clone().unwrap_or_default(),
+                            flash_address: address.clone(),
+                        }],
+                    })
+                    .unwrap_or(JsValue::NULL);
+                    match invoke("flash_segments", args).await {
+                        Ok(_) => StepState::Done(address),
+                        Err(e) => failure(&dict, e, dict.onboarding_help_download_mode()),
+                    }
+                }
+                _ => {
+                    let args = serde_wasm_bindgen::to_value(&MonitorConnectArgs {
+                        port_name: port(),
+                        baud_rate: settings.peek().default_baud,
+                    })
+                    .unwrap_or(JsValue::NULL);
+                    match invoke("monitor_connect", args).await {
+                        // The Devices page picks up the running session
+                        Ok(_) => {
+                            navigator().push(Route::Devices {
+                                tab: "monitor".to_string(),
+                                port: port(),
+                            });
+                            StepState::Done(String::new())
+                        }
+                        Err(e) => failure(&dict, e, dict.onboarding_help_monitor()),
+                    }
+                }
+            };
+            states.write()[step] = state;
+        });
+    };
+
+    struct ListenerGuard {
+        unlisten: Option<js_sys::Function>,
+        _closure: Option<Closure<dyn FnMut(JsValue)>>,
+    }
+    impl Drop for ListenerGuard {
+        fn drop(&mut self) {
+            if let Some(f) = &self.unlisten {
+                f.call0(&JsValue::NULL).ok();
+            }
+        }
+    }
+    let mut listener_guard = use_signal(|| ListenerGuard {
+        unlisten: None,
+        _closure: None,
+    });
+
+    // Look for a board straight away and follow the flash progress
+    use_effect(move || {
+        run_step(0);
+        spawn(async move {
+            let closure = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                #[derive(Deserialize)]
+                struct Event {
+                    payload: TaskInfo,
+                }
+                let Ok(e) = serde_wasm_bindgen::from_value::<Event>(event) else {
+                    return;
+                };
+                let task = e.payload;
+                if task.kind == "flash"
+                    && task.state == "running"
+                    && task.total > 0
+                    && task.port_name.as_deref() == Some(port().as_str())
+                {
+                    flash_progress.set(task.current as f64 * 100.0 / task.total as f64);
+                }
+            });
+            match listen("task-progress", &closure).await {
+                Ok(unlisten_js) => {
+                    let mut guard = listener_guard.write();
+                    guard.unlisten = unlisten_js.dyn_into::<js_sys::Function>().ok();
+                    guard._closure = Some(closure);
+                }
+                Err(e) => web_sys::console::error_1(&e),
+            }
+        });
+    });
+
+    let flash_percent = flash_progress.read().floor() as u64;
+
+    rsx! {
+        div { style: "max-width: 720px; margin: 0 auto;",
+            Card {
+                title: dict.onboarding_title().to_string(),
+                subtitle: dict.onboarding_subtitle().to_string(),
+                div { style: "display: flex; flex-direction: column; gap: 12px; margin-top: 8px;",
+                    for (i, step) in STEPS.iter().enumerate() {
+                        {
+                            let state = states.read()[i].clone();
+                            // The first step not done yet is the one to work on
+                            let active = (0..i).all(|before| matches!(states.read()[before], StepState::Done(_)))
+                                && !matches!(state, StepState::Done(_));
+                            let (icon, color) = match &state {
+                                StepState::Done(_) => ("check_circle", "var(--md-sys-color-primary)"),
+                                StepState::Failed { .. } => ("error", "var(--md-sys-color-error)"),
+                                StepState::Running => ("progress_activity", "var(--md-sys-color-tertiary)"),
+                                StepState::Pending if active => ("radio_button_checked", "var(--md-sys-color-primary)"),
+                                StepState::Pending => ("radio_button_unchecked", "var(--md-sys-color-outline)"),
+                            };
+                            let title = dict.t(&format!("onboarding_step_{}", step)).to_string();
+                            let description = dict.t(&format!("onboarding_step_{}_desc", step)).to_string();
+                            let number = i + 1;
+                            let opacity = if active || matches!(state, StepState::Done(_)) { 1.0 } else { 0.6 };
+                            let action = match (*step, &state) {
+                                (_, StepState::Failed { .. }) => dict.onboarding_btn_retry(),
+                                ("firmware", _) => dict.devices_btn_browse(),
+                                ("flash", _) => dict.devices_btn_start_flash(),
+                                ("monitor", _) => dict.onboarding_btn_open_monitor(),
+                                _ => dict.onboarding_btn_check(),
+                            }
+                            .to_string();
+                            rsx! {
+                                div {
+                                    key: "{step}",
+                                    style: "display: flex; gap: 12px; padding: 12px; border-radius: 12px; background: var(--md-sys-color-surface-container); opacity: {opacity};",
+                                    span { class: "material-symbols-outlined", style: "color: {color};", "{icon}" }
+                                    div { style: "flex: 1; display: flex; flex-direction: column; gap: 4px;",
+                                        span { style: "font-weight: 500;", "{number}. {title}" }
+                                        {match &state {
+                                            StepState::Done(found) if !found.is_empty() => rsx! {
+                                                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{found}" }
+                                            },
+                                            StepState::Failed { message, help } => rsx! {
+                                                div {
+                                                    style: "background-color: var(--md-sys-color-error-container); color: var(--md-sys-color-on-error-container); padding: 8px 12px; border-radius: 8px; font-size: 0.9em; display: flex; flex-direction: column; gap: 2px;",
+                                                    span { "{message}" }
+                                                    span { style: "opacity: 0.8;", "{help}" }
+                                                }
+                                            },
+                                            StepState::Running if *step == "flash" => rsx! {
+                                                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{dict.devices_flashing_status()} {flash_percent}%" }
+                                            },
+                                            _ if active => rsx! {
+                                                span { style: "font-size: 0.9em; color: var(--md-sys-color-on-surface-variant);", "{description}" }
+                                            },
+                                            _ => rsx! {},
+                                        }}
+                                    }
+                                    if active && state != StepState::Running {
+                                        Button {
+                                            variant: "tonal".to_string(),
+                                            onclick: move |_| run_step(i),
+                                            "{action}"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
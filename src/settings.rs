@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FlashSettings {
+    pub address: String,
+    pub baud_rate: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+/// Accelerators such as "Ctrl+L" per shortcut action.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ShortcutSettings {
+    pub clear_log: String,
+    pub focus_input: String,
+    pub flash: String,
+    pub reset: String,
+}
+
+/// What a monitor trigger does, tagged by `kind` like the backend's.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerAction {
+    Send { text: String },
+    Mark { label: String },
+    Notify { message: String },
+    Stop,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MonitorTrigger {
+    pub pattern: String,
+    pub action: TriggerAction,
+    pub enabled: bool,
+}
+
+/// Events raising an OS notification besides the in-app toast.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DesktopNotifications {
+    pub device_connected: bool,
+    pub device_disconnected: bool,
+    pub flash_completed: bool,
+    pub flash_failed: bool,
+    pub monitor_triggers: bool,
+}
+
+/// A GPIO the firmware uses; `role` is "i2c", "spi", "uart", "pwm" or
+/// "gpio".
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PinUsage {
+    pub gpio: u32,
+    pub role: String,
+    pub signal: String,
+}
+
+/// Kiosk mode; the backend keeps the password hash to itself and only
+/// changes these through the kiosk commands.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct KioskSettings {
+    pub enabled: bool,
+    pub image_path: String,
+    pub address: String,
+    pub golden_hash: String,
+    pub passed: u32,
+    pub failed: u32,
+}
+
+/// Mirror of the backend `Settings`, shared through context by `AppLayout`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub theme: String,
+    pub language: String,
+    pub default_baud: u32,
+    pub vid_allowlist: Vec<u16>,
+    pub flash: FlashSettings,
+    pub api: ApiSettings,
+    pub shortcuts: ShortcutSettings,
+    pub triggers: Vec<MonitorTrigger>,
+    pub desktop_notifications: DesktopNotifications,
+    pub pin_usage: Vec<PinUsage>,
+    /// Console command setting the board's clock; `{unix}`, `{unix_ms}`,
+    /// `{date}` and `{time}` are filled in with the host time.
+    pub time_command: String,
+    pub kiosk: KioskSettings,
+    /// Written with every record in the device database
+    pub operator: String,
+    pub production_run: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: "dark".to_string(),
+            language: "zh".to_string(),
+            default_baud: 115200,
+            vid_allowlist: vec![0x10C4, 0x1A86, 0x303A, 0x0403],
+            flash: FlashSettings {
+                address: "0x0".to_string(),
+                baud_rate: 115200,
+            },
+            api: ApiSettings {
+                enabled: false,
+                port: 8765,
+            },
+            shortcuts: ShortcutSettings {
+                clear_log: "Ctrl+L".to_string(),
+                focus_input: "Ctrl+K".to_string(),
+                flash: "F5".to_string(),
+                reset: "F6".to_string(),
+            },
+            triggers: Vec::new(),
+            desktop_notifications: DesktopNotifications {
+                device_connected: false,
+                device_disconnected: true,
+                flash_completed: true,
+                flash_failed: true,
+                monitor_triggers: true,
+            },
+            pin_usage: Vec::new(),
+            time_command: "time set {unix}".to_string(),
+            kiosk: KioskSettings::default(),
+            operator: String::new(),
+            production_run: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateSettingsArgs {
+    new_settings: Settings,
+}
+
+pub async fn load_settings() -> Option<Settings> {
+    let res = invoke("get_settings", JsValue::NULL).await.ok()?;
+    serde_wasm_bindgen::from_value::<Settings>(res).ok()
+}
+
+pub async fn save_settings(settings: Settings) {
+    if let Err(e) = update_settings(settings).await {
+        web_sys::console::error_1(&e);
+    }
+}
+
+/// Saves `settings` and returns what the backend kept; the error is the
+/// serialized `AppError` when it refused them.
+pub async fn update_settings(settings: Settings) -> Result<Settings, JsValue> {
+    let args = serde_wasm_bindgen::to_value(&UpdateSettingsArgs {
+        new_settings: settings,
+    })
+    .unwrap_or(JsValue::NULL);
+    let saved = invoke("update_settings", args).await?;
+    serde_wasm_bindgen::from_value(saved).map_err(JsValue::from)
+}